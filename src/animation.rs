@@ -0,0 +1,317 @@
+//! Small keyframed animations for entities: tweens between two values with an easing
+//! curve, chained into timelines. Entities keep the handles, step them with the frame
+//! time and read the current value into a render offset, scale or color.
+
+use macroquad::prelude::*;
+
+use std::f32::consts::PI;
+
+/// How a tween moves from start to end. Every curve starts at 0 and finishes at 1;
+/// `Overshoot` goes outside that range on the way, and `Bounce` turns back on itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    // Linear and Bounce round out the set; nothing is animated with them yet
+    #[allow(dead_code)]
+    Linear,
+    /// Slow start
+    EaseIn,
+    /// Slow finish
+    EaseOut,
+    /// Slow start and finish
+    EaseInOut,
+    /// Half a cosine wave, for smooth back-and-forth motion
+    SineInOut,
+    /// Drops onto the end and bounces a few times before settling
+    #[allow(dead_code)]
+    Bounce,
+    /// Runs past the end and comes back; the amount is how far (1.7 is about 10%)
+    Overshoot(f32),
+}
+
+impl Easing {
+    /// Where the curve is at `t` through the tween, with `t` clamped to 0..1
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineInOut => (1.0 - (PI * t).cos()) / 2.0,
+            Easing::Bounce => bounce_out(t),
+            Easing::Overshoot(amount) => {
+                let t = t - 1.0;
+                1.0 + (amount + 1.0) * t * t * t + amount * t * t
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Something a tween can move between: a number, a point or a color
+pub trait Tweenable: Copy {
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Color::new(
+            Tweenable::lerp(self.r, to.r, t),
+            Tweenable::lerp(self.g, to.g, t),
+            Tweenable::lerp(self.b, to.b, t),
+            Tweenable::lerp(self.a, to.a, t),
+        )
+    }
+}
+
+/// Move from `from` to `to` over `duration` seconds along `easing`
+pub fn tween<T: Tweenable>(from: T, to: T, duration: f32, easing: Easing) -> Tween<T> {
+    Tween {
+        from,
+        to,
+        duration,
+        easing,
+        elapsed: 0.0,
+    }
+}
+
+/// One value moving between two ends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: f32,
+    pub easing: Easing,
+    elapsed: f32,
+}
+
+impl<T: Tweenable> Tween<T> {
+    /// Step the tween, returning the seconds left over once it finished
+    pub fn update(&mut self, delta_time: f32) -> f32 {
+        let left = self.duration - self.elapsed;
+        self.elapsed = (self.elapsed + delta_time).min(self.duration);
+        (delta_time - left).max(0.0)
+    }
+
+    /// How far through it is, 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.elapsed / self.duration
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.easing.apply(self.progress()))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// Follow this tween with another
+    pub fn then(self, next: Tween<T>) -> Timeline<T> {
+        Timeline::from(self).then(next)
+    }
+}
+
+/// Tweens played one after another, optionally over and over
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline<T> {
+    steps: Vec<Tween<T>>,
+    current: usize,
+    looping: bool,
+}
+
+impl<T: Tweenable> From<Tween<T>> for Timeline<T> {
+    fn from(first: Tween<T>) -> Self {
+        Self {
+            steps: vec![first],
+            current: 0,
+            looping: false,
+        }
+    }
+}
+
+impl<T: Tweenable> Timeline<T> {
+    pub fn then(mut self, next: Tween<T>) -> Self {
+        self.steps.push(next);
+        self
+    }
+
+    /// Start again from the first tween whenever the last one ends
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn update(&mut self, mut delta_time: f32) {
+        // A looping timeline with no length would never use up the time
+        let total: f32 = self.steps.iter().map(|step| step.duration).sum();
+        if self.looping && total > 0.0 {
+            delta_time %= total;
+        }
+        loop {
+            let left = self.steps[self.current].update(delta_time);
+            if !self.steps[self.current].is_finished() {
+                return;
+            }
+            if self.current + 1 < self.steps.len() {
+                self.current += 1;
+            } else if self.looping && total > 0.0 {
+                self.restart();
+            } else {
+                return;
+            }
+            delta_time = left;
+            if delta_time <= 0.0 {
+                return;
+            }
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.steps[self.current].value()
+    }
+
+    /// Whether the last tween has finished; never for a looping timeline
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && self.current + 1 == self.steps.len()
+            && self.steps[self.current].is_finished()
+    }
+
+    pub fn restart(&mut self) {
+        for step in &mut self.steps {
+            step.restart();
+        }
+        self.current = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 7] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::SineInOut,
+        Easing::Bounce,
+        Easing::Overshoot(1.7),
+    ];
+
+    /// The curve at 1000 even steps from 0 to 1
+    fn samples(easing: Easing) -> Vec<f32> {
+        (0..=1000)
+            .map(|i| easing.apply(i as f32 / 1000.0))
+            .collect()
+    }
+
+    #[test]
+    fn every_curve_starts_at_0_and_ends_at_1() {
+        for easing in ALL {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{easing:?} at 0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?} at 1");
+        }
+    }
+
+    #[test]
+    fn time_outside_the_tween_is_clamped() {
+        for easing in ALL {
+            assert_eq!(easing.apply(-0.5), easing.apply(0.0), "{easing:?}");
+            assert_eq!(easing.apply(1.5), easing.apply(1.0), "{easing:?}");
+        }
+    }
+
+    #[test]
+    fn plain_curves_never_turn_back() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::SineInOut,
+        ] {
+            let values = samples(easing);
+            assert!(
+                values.windows(2).all(|pair| pair[1] >= pair[0]),
+                "{easing:?} goes backwards"
+            );
+            assert!(values.iter().all(|value| (0.0..=1.0).contains(value)));
+        }
+    }
+
+    #[test]
+    fn in_and_out_curves_are_slow_at_the_right_end() {
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!((Easing::SineInOut.apply(0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn overshoot_runs_past_the_end_and_comes_back() {
+        let values = samples(Easing::Overshoot(1.7));
+        let peak = values.iter().copied().fold(0.0, f32::max);
+        assert!(peak > 1.05 && peak < 1.15, "peak {peak}");
+        // Rises to the peak, then only falls back to 1
+        let top = values.iter().position(|&value| value == peak).unwrap();
+        assert!(values[..=top].windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(values[top..].windows(2).all(|pair| pair[1] <= pair[0]));
+    }
+
+    #[test]
+    fn bounce_stays_in_range_and_lands_on_the_end() {
+        let values = samples(Easing::Bounce);
+        assert!(values
+            .iter()
+            .all(|value| (0.0..=1.0 + 1e-6).contains(value)));
+        // Touches down at the end of the first drop and of each bounce
+        for landing in [1.0 / 2.75, 2.0 / 2.75, 2.5 / 2.75] {
+            assert!((Easing::Bounce.apply(landing) - 1.0).abs() < 1e-3);
+        }
+        assert!(Easing::Bounce.apply(1.5 / 2.75) < 0.8);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use macroquad::audio::{load_sound, Sound};
+use macroquad::prelude::*;
+
+use crate::audio::{SoundId, SOUND_DIR};
+use crate::graphics::text::UI_FONT;
+
+/// A file the game loads at startup, looked up by this id once it's in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetId {
+    Sound(SoundId),
+    /// Image at this path, relative to the working directory
+    Texture(&'static str),
+    /// TrueType font at this path, relative to the working directory
+    Font(&'static str),
+}
+
+impl AssetId {
+    /// Everything loaded before the main menu opens
+    pub fn manifest() -> Vec<AssetId> {
+        std::iter::once(AssetId::Font(UI_FONT))
+            .chain(SoundId::ALL.into_iter().map(AssetId::Sound))
+            .collect()
+    }
+
+    pub fn path(&self) -> String {
+        match self {
+            AssetId::Sound(sound) => format!("{}/{}", SOUND_DIR, sound.file_name()),
+            AssetId::Texture(path) | AssetId::Font(path) => path.to_string(),
+        }
+    }
+}
+
+/// Loads the game's files without freezing the window, a few each frame behind the loading
+/// screen, then hands them out by id. Files that are missing or don't load get a
+/// placeholder (silence, or a checkered texture) and a warning rather than an error.
+pub struct AssetManager {
+    /// Still to load, in order
+    pending: Vec<AssetId>,
+    total: usize,
+    sounds: HashMap<SoundId, Sound>,
+    textures: HashMap<&'static str, Texture2D>,
+    fonts: HashMap<&'static str, Font>,
+    /// One line per file that didn't load
+    warnings: Vec<String>,
+    /// When each file was last loaded from, to find the ones changed since
+    modified: HashMap<AssetId, SystemTime>,
+    reload_requested: bool,
+}
+
+impl AssetManager {
+    pub fn new(manifest: Vec<AssetId>) -> Self {
+        let mut pending = manifest;
+        // Popped from the back, so reversed to load in manifest order
+        pending.reverse();
+        Self {
+            total: pending.len(),
+            pending,
+            sounds: HashMap::new(),
+            textures: HashMap::new(),
+            fonts: HashMap::new(),
+            warnings: Vec::new(),
+            modified: HashMap::new(),
+            reload_requested: false,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Files loaded so far and how many there are, for the progress bar
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.pending.len(), self.total)
+    }
+
+    /// Load files until `budget` seconds have gone by, always at least one, so the loading
+    /// screen still gets drawn in between
+    pub async fn load_for(&mut self, budget: f64) {
+        let started = get_time();
+        while let Some(id) = self.pending.pop() {
+            self.load(id).await;
+            if get_time() - started >= budget {
+                break;
+            }
+        }
+    }
+
+    async fn load(&mut self, id: AssetId) {
+        let path = id.path();
+        let result = if !Path::new(&path).exists() {
+            Err("missing".to_string())
+        } else {
+            match id {
+                AssetId::Sound(sound) => load_sound(&path).await.map(|loaded| {
+                    self.sounds.insert(sound, loaded);
+                }),
+                AssetId::Texture(name) => load_texture(&path).await.map(|loaded| {
+                    self.textures.insert(name, loaded);
+                }),
+                AssetId::Font(name) => load_ttf_font(&path).await.map(|loaded| {
+                    self.fonts.insert(name, loaded);
+                }),
+            }
+            .map_err(|err| err.to_string())
+        };
+
+        self.warnings.retain(|warning| !warning.starts_with(&path));
+        match result {
+            Ok(()) => {
+                if let Some(modified) = modified_time(&path) {
+                    self.modified.insert(id, modified);
+                }
+            }
+            Err(err) => {
+                // Made here rather than up front, so the manager can be built without a window
+                if let AssetId::Texture(name) = id {
+                    self.textures.insert(name, Self::placeholder_texture());
+                }
+                self.warnings.push(format!("{} ({})", path, err));
+            }
+        }
+    }
+
+    /// A sound, or `None` if its file didn't load
+    pub fn sound(&self, id: SoundId) -> Option<&Sound> {
+        self.sounds.get(&id)
+    }
+
+    /// A texture, or the placeholder if its file didn't load; `None` for one that was
+    /// never asked for
+    // The manifest has no textures yet; this is the lookup they'll be drawn through
+    #[allow(dead_code)]
+    pub fn texture(&self, path: &str) -> Option<&Texture2D> {
+        self.textures.get(path)
+    }
+
+    /// A font, or `None` if its file didn't load and the built-in font should be used
+    pub fn font(&self, path: &str) -> Option<&Font> {
+        self.fonts.get(path)
+    }
+
+    /// The files that didn't load and why
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Ask for changed files to be loaded again at the start of the next frame
+    pub fn request_reload(&mut self) {
+        self.reload_requested = true;
+    }
+
+    pub fn take_reload_request(&mut self) -> bool {
+        std::mem::take(&mut self.reload_requested)
+    }
+
+    /// Load again every file that's changed on disk since it was loaded, or that has
+    /// turned up since it was found missing, returning the ones that did
+    pub async fn reload_changed(&mut self) -> Vec<AssetId> {
+        let changed: Vec<AssetId> = AssetId::manifest()
+            .into_iter()
+            .chain(self.textures.keys().map(|&path| AssetId::Texture(path)))
+            .filter(|id| {
+                let current = modified_time(&id.path());
+                current.is_some() && current != self.modified.get(id).copied()
+            })
+            .collect();
+        for &id in &changed {
+            self.load(id).await;
+        }
+        changed
+    }
+
+    /// Magenta and black squares, hard to miss
+    fn placeholder_texture() -> Texture2D {
+        let magenta = [255, 0, 255, 255];
+        let black = [0, 0, 0, 255];
+        let pixels = [magenta, black, black, magenta].concat();
+        let texture = Texture2D::from_rgba8(2, 2, &pixels);
+        texture.set_filter(FilterMode::Nearest);
+        texture
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
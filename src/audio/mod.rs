@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+use macroquad::audio::{play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+
+use crate::assets::AssetManager;
+use crate::config::GameConfig;
+use crate::rng::SeededRng;
+use crate::settings::Settings;
+
+/// Folder sound files are loaded from, relative to the working directory
+pub const SOUND_DIR: &str = "assets/sounds";
+
+/// Every sound the game can play
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SoundId {
+    Jump,
+    Footstep1,
+    Footstep2,
+    Footstep3,
+    Land,
+    Coin,
+    Death,
+    LevelComplete,
+    MenuSelect,
+    EnemyDefeat,
+    PlatformBreak,
+    Spring,
+    BossShot,
+    Explosion,
+    Music,
+}
+
+impl SoundId {
+    pub const ALL: [SoundId; 15] = [
+        SoundId::Jump,
+        SoundId::Footstep1,
+        SoundId::Footstep2,
+        SoundId::Footstep3,
+        SoundId::Land,
+        SoundId::Coin,
+        SoundId::Death,
+        SoundId::LevelComplete,
+        SoundId::MenuSelect,
+        SoundId::EnemyDefeat,
+        SoundId::PlatformBreak,
+        SoundId::Spring,
+        SoundId::BossShot,
+        SoundId::Explosion,
+        SoundId::Music,
+    ];
+
+    /// Takes turns at random so walking doesn't sound like a machine gun
+    pub const FOOTSTEPS: [SoundId; 3] =
+        [SoundId::Footstep1, SoundId::Footstep2, SoundId::Footstep3];
+
+    /// File in `SOUND_DIR` the sound is loaded from
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SoundId::Jump => "jump.wav",
+            SoundId::Footstep1 => "footstep1.wav",
+            SoundId::Footstep2 => "footstep2.wav",
+            SoundId::Footstep3 => "footstep3.wav",
+            SoundId::Land => "land.wav",
+            SoundId::Coin => "coin.wav",
+            SoundId::Death => "death.wav",
+            SoundId::LevelComplete => "level_complete.wav",
+            SoundId::MenuSelect => "menu_select.wav",
+            SoundId::EnemyDefeat => "enemy_defeat.wav",
+            SoundId::PlatformBreak => "platform_break.wav",
+            SoundId::Spring => "spring.wav",
+            SoundId::BossShot => "boss_shot.wav",
+            SoundId::Explosion => "explosion.wav",
+            SoundId::Music => "music.ogg",
+        }
+    }
+
+    pub fn category(&self) -> AudioCategory {
+        match self {
+            SoundId::MenuSelect => AudioCategory::Ui,
+            SoundId::Music => AudioCategory::Music,
+            _ => AudioCategory::Sfx,
+        }
+    }
+
+    /// Sounds the music makes room for while they play
+    pub fn ducks_music(&self) -> bool {
+        matches!(self, SoundId::Death | SoundId::LevelComplete)
+    }
+}
+
+/// Volume group a sound belongs to, each with its own level and mute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioCategory {
+    Sfx,
+    Music,
+    /// Menu sounds, at the SFX level but muted on their own
+    Ui,
+}
+
+/// Whatever actually makes the noise
+pub trait AudioBackend {
+    fn play(&mut self, sound: SoundId, volume: f32, looped: bool);
+    /// Change the volume of a sound that's already playing, e.g. looping music
+    fn set_volume(&mut self, sound: SoundId, volume: f32);
+    fn stop(&mut self, sound: SoundId);
+}
+
+/// Plays nothing; used until sounds are loaded
+pub struct SilentBackend;
+
+impl AudioBackend for SilentBackend {
+    fn play(&mut self, _sound: SoundId, _volume: f32, _looped: bool) {}
+    fn set_volume(&mut self, _sound: SoundId, _volume: f32) {}
+    fn stop(&mut self, _sound: SoundId) {}
+}
+
+/// Sound files played through macroquad. Files that are missing or don't load are left
+/// out, so those sounds are silent rather than an error.
+pub struct MacroquadBackend {
+    sounds: HashMap<SoundId, Sound>,
+}
+
+impl MacroquadBackend {
+    /// Play whichever sounds the asset manager has loaded
+    pub fn new(assets: &AssetManager) -> Self {
+        let sounds = SoundId::ALL
+            .into_iter()
+            .filter_map(|id| assets.sound(id).map(|sound| (id, sound.clone())))
+            .collect();
+        Self { sounds }
+    }
+}
+
+impl AudioBackend for MacroquadBackend {
+    fn play(&mut self, sound: SoundId, volume: f32, looped: bool) {
+        if let Some(sound) = self.sounds.get(&sound) {
+            play_sound(sound, PlaySoundParams { looped, volume });
+        }
+    }
+
+    fn set_volume(&mut self, sound: SoundId, volume: f32) {
+        if let Some(sound) = self.sounds.get(&sound) {
+            set_sound_volume(sound, volume);
+        }
+    }
+
+    fn stop(&mut self, sound: SoundId) {
+        if let Some(sound) = self.sounds.get(&sound) {
+            stop_sound(sound);
+        }
+    }
+}
+
+/// Mixes every sound through its category: the volume it plays at is master × category,
+/// and looping music follows setting changes and ducking as they happen
+pub struct AudioBus {
+    backend: Box<dyn AudioBackend>,
+    master_volume: f32,
+    sfx_volume: f32,
+    music_volume: f32,
+    muted: bool,
+    sfx_muted: bool,
+    music_muted: bool,
+    ui_muted: bool,
+    /// Seconds the music stays ducked for the important sound that last played
+    duck_hold: f32,
+    /// How far the music is ducked right now, 0 to 1, easing in and out
+    duck_level: f32,
+    /// The looping track, if one has been started
+    music: Option<SoundId>,
+    /// Volume last given to the music, to only tell the backend about changes
+    music_applied: f32,
+    /// Where sounds in the world are heard from, the middle of the view
+    listener: Vec2,
+    /// Picks sound variants; kept apart from the run's randomness so sounds can't change
+    /// what happens in a seeded run
+    variety: SeededRng,
+    last_variant: Option<SoundId>,
+}
+
+impl AudioBus {
+    pub fn new(backend: Box<dyn AudioBackend>, settings: &Settings) -> Self {
+        let mut bus = Self {
+            backend,
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            muted: false,
+            sfx_muted: false,
+            music_muted: false,
+            ui_muted: false,
+            duck_hold: 0.0,
+            duck_level: 0.0,
+            music: None,
+            music_applied: 0.0,
+            listener: Vec2::ZERO,
+            variety: SeededRng::new(SeededRng::clock_seed()),
+            last_variant: None,
+        };
+        bus.apply_settings(settings);
+        bus
+    }
+
+    /// Swap where sounds are played, carrying on with the music there
+    pub fn set_backend(&mut self, backend: Box<dyn AudioBackend>) {
+        if let Some(music) = self.music {
+            self.backend.stop(music);
+        }
+        self.backend = backend;
+        if let Some(music) = self.music {
+            self.music_applied = self.volume(AudioCategory::Music);
+            self.backend.play(music, self.music_applied, true);
+        }
+    }
+
+    /// Pick up the latest volumes and mutes
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.master_volume = settings.master_volume;
+        self.sfx_volume = settings.sfx_volume;
+        self.music_volume = settings.music_volume;
+        self.muted = settings.muted;
+        self.sfx_muted = settings.sfx_muted;
+        self.music_muted = settings.music_muted;
+        self.ui_muted = settings.ui_muted;
+    }
+
+    /// The level a sound in `category` plays at right now
+    pub fn volume(&self, category: AudioCategory) -> f32 {
+        let (level, muted) = match category {
+            AudioCategory::Sfx => (self.sfx_volume, self.sfx_muted),
+            AudioCategory::Ui => (self.sfx_volume, self.ui_muted),
+            AudioCategory::Music => (
+                self.music_volume * (1.0 - GameConfig::MUSIC_DUCK_AMOUNT * self.duck_level),
+                self.music_muted,
+            ),
+        };
+        if self.muted || muted {
+            0.0
+        } else {
+            self.master_volume * level
+        }
+    }
+
+    pub fn play(&mut self, sound: SoundId) {
+        self.play_with_volume(sound, 1.0);
+    }
+
+    /// Play a sound at `gain` times its category's volume, e.g. softer for a small bump
+    pub fn play_with_volume(&mut self, sound: SoundId, gain: f32) {
+        if sound.ducks_music() {
+            self.duck_hold = GameConfig::MUSIC_DUCK_TIME;
+        }
+        let volume = self.volume(sound.category()) * gain.clamp(0.0, 1.0);
+        if volume > 0.0 {
+            self.backend.play(sound, volume, false);
+        }
+    }
+
+    /// Play one of `variants` at random, never the same one twice running
+    pub fn play_variant(&mut self, variants: &[SoundId], gain: f32) {
+        let fresh: Vec<SoundId> = variants
+            .iter()
+            .copied()
+            .filter(|sound| Some(*sound) != self.last_variant)
+            .collect();
+        let choices = if fresh.is_empty() { variants } else { &fresh };
+        if choices.is_empty() {
+            return;
+        }
+        let sound = choices[self.variety.index(choices.len())];
+        self.last_variant = Some(sound);
+        self.play_with_volume(sound, gain);
+    }
+
+    /// Play a sound made at `position` in the world, quieter the further it is from the
+    /// listener and silent past `SOUND_SILENT_DISTANCE`. macroquad can't pan, so it's
+    /// heard in the middle either way.
+    pub fn play_at(&mut self, sound: SoundId, position: Vec2) {
+        let volume = self.volume(sound.category()) * attenuation(self.listener.distance(position));
+        if volume > 0.0 {
+            self.backend.play(sound, volume, false);
+        }
+    }
+
+    /// Move the point world sounds are heard from
+    pub fn set_listener(&mut self, position: Vec2) {
+        self.listener = position;
+    }
+
+    /// Start `track` looping in place of any other
+    pub fn play_music(&mut self, track: SoundId) {
+        if self.music == Some(track) {
+            return;
+        }
+        if let Some(music) = self.music {
+            self.backend.stop(music);
+        }
+        self.music = Some(track);
+        // Started even when silent, so unmuting brings it in where it would have been
+        self.music_applied = self.volume(AudioCategory::Music);
+        self.backend.play(track, self.music_applied, true);
+    }
+
+    /// Stop whatever music is playing
+    pub fn stop_music(&mut self) {
+        if let Some(music) = self.music.take() {
+            self.backend.stop(music);
+        }
+    }
+
+    /// Follow the settings and move the ducking envelope along
+    pub fn update(&mut self, delta_time: f32, settings: &Settings) {
+        self.apply_settings(settings);
+
+        self.duck_hold = (self.duck_hold - delta_time).max(0.0);
+        let target = if self.duck_hold > 0.0 { 1.0 } else { 0.0 };
+        let step = delta_time / GameConfig::MUSIC_DUCK_FADE;
+        self.duck_level = if target > self.duck_level {
+            (self.duck_level + step).min(target)
+        } else {
+            (self.duck_level - step).max(target)
+        };
+
+        if let Some(music) = self.music {
+            let volume = self.volume(AudioCategory::Music);
+            if (volume - self.music_applied).abs() > 0.001 {
+                self.music_applied = volume;
+                self.backend.set_volume(music, volume);
+            }
+        }
+    }
+}
+
+/// Volume multiplier for a sound `distance` away from the listener: full up to
+/// `SOUND_FULL_VOLUME_DISTANCE`, easing down to nothing at `SOUND_SILENT_DISTANCE`
+pub fn attenuation(distance: f32) -> f32 {
+    let near = GameConfig::SOUND_FULL_VOLUME_DISTANCE;
+    let far = GameConfig::SOUND_SILENT_DISTANCE;
+    let t = ((distance - near) / (far - near)).clamp(0.0, 1.0);
+    // Smoothstep, so there's no sudden drop at either end
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Call {
+        Play(SoundId, f32, bool),
+        SetVolume(SoundId, f32),
+        Stop(SoundId),
+    }
+
+    /// Notes every call instead of making a sound
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        calls: Rc<RefCell<Vec<Call>>>,
+    }
+
+    impl AudioBackend for MockBackend {
+        fn play(&mut self, sound: SoundId, volume: f32, looped: bool) {
+            self.calls
+                .borrow_mut()
+                .push(Call::Play(sound, volume, looped));
+        }
+        fn set_volume(&mut self, sound: SoundId, volume: f32) {
+            self.calls.borrow_mut().push(Call::SetVolume(sound, volume));
+        }
+        fn stop(&mut self, sound: SoundId) {
+            self.calls.borrow_mut().push(Call::Stop(sound));
+        }
+    }
+
+    fn mock_bus(settings: &Settings) -> (AudioBus, Rc<RefCell<Vec<Call>>>) {
+        let backend = MockBackend::default();
+        let calls = backend.calls.clone();
+        (AudioBus::new(Box::new(backend), settings), calls)
+    }
+
+    fn settings(master: f32, sfx: f32, music: f32) -> Settings {
+        Settings {
+            master_volume: master,
+            sfx_volume: sfx,
+            music_volume: music,
+            ..Settings::default()
+        }
+    }
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-4, "{value} != {expected}");
+    }
+
+    #[test]
+    fn volume_is_master_times_category() {
+        let (bus, _) = mock_bus(&settings(0.5, 0.8, 0.6));
+        assert_near(bus.volume(AudioCategory::Sfx), 0.4);
+        assert_near(bus.volume(AudioCategory::Music), 0.3);
+        // Menu sounds follow the SFX level
+        assert_near(bus.volume(AudioCategory::Ui), 0.4);
+    }
+
+    #[test]
+    fn mutes_silence_their_category_or_everything() {
+        let mut muted = settings(1.0, 1.0, 1.0);
+        muted.music_muted = true;
+        let (bus, _) = mock_bus(&muted);
+        assert_eq!(bus.volume(AudioCategory::Music), 0.0);
+        assert_eq!(bus.volume(AudioCategory::Sfx), 1.0);
+
+        muted.ui_muted = true;
+        let (bus, _) = mock_bus(&muted);
+        assert_eq!(bus.volume(AudioCategory::Ui), 0.0);
+        assert_eq!(bus.volume(AudioCategory::Sfx), 1.0);
+
+        let everything = Settings {
+            muted: true,
+            ..settings(1.0, 1.0, 1.0)
+        };
+        let (bus, _) = mock_bus(&everything);
+        for category in [AudioCategory::Sfx, AudioCategory::Music, AudioCategory::Ui] {
+            assert_eq!(bus.volume(category), 0.0);
+        }
+    }
+
+    #[test]
+    fn sounds_play_at_their_mixed_volume() {
+        let (mut bus, calls) = mock_bus(&settings(0.5, 0.8, 1.0));
+        bus.play(SoundId::Jump);
+        bus.play_with_volume(SoundId::Land, 0.5);
+        assert_eq!(
+            *calls.borrow(),
+            [
+                Call::Play(SoundId::Jump, 0.4, false),
+                Call::Play(SoundId::Land, 0.2, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn silent_sounds_are_not_played() {
+        let muted = Settings {
+            sfx_muted: true,
+            ..Settings::default()
+        };
+        let (mut bus, calls) = mock_bus(&muted);
+        bus.play(SoundId::Coin);
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn setting_changes_reach_the_playing_music() {
+        let (mut bus, calls) = mock_bus(&settings(1.0, 1.0, 1.0));
+        bus.play_music(SoundId::Music);
+        bus.update(0.016, &settings(1.0, 1.0, 0.25));
+        assert_eq!(
+            *calls.borrow(),
+            [
+                Call::Play(SoundId::Music, 1.0, true),
+                Call::SetVolume(SoundId::Music, 0.25)
+            ]
+        );
+        // Nothing changed, so the backend isn't told again
+        bus.update(0.016, &settings(1.0, 1.0, 0.25));
+        assert_eq!(calls.borrow().len(), 2);
+    }
+
+    #[test]
+    fn important_sounds_duck_the_music_and_it_recovers() {
+        const FRAME: f32 = 1.0 / 100.0;
+        let fade = GameConfig::MUSIC_DUCK_FADE;
+        let hold = GameConfig::MUSIC_DUCK_TIME;
+        let all = settings(1.0, 1.0, 1.0);
+        let (mut bus, calls) = mock_bus(&all);
+        bus.play_music(SoundId::Music);
+        bus.play(SoundId::Death);
+
+        // Level after each frame, with the time it was reached
+        let envelope: Vec<(f32, f32)> = (1..=300)
+            .map(|frame| {
+                bus.update(FRAME, &all);
+                (frame as f32 * FRAME, bus.duck_level)
+            })
+            .collect();
+        let level_at = |time: f32| {
+            envelope
+                .iter()
+                .find(|(at, _)| *at >= time - 1e-4)
+                .map(|(_, level)| *level)
+                .unwrap()
+        };
+
+        // Within a frame's worth of the fade, as it turns on the frame the hold runs out
+        let near = |time: f32, expected: f32| {
+            let level = level_at(time);
+            assert!(
+                (level - expected).abs() <= FRAME / fade + 1e-4,
+                "level {level} at {time}s, expected {expected}"
+            );
+        };
+        // Eases down over the fade rather than dropping at once
+        near(fade / 2.0, 0.5);
+        near(fade, 1.0);
+        // Held until the sound is over, then back up over the fade
+        near(hold - FRAME, 1.0);
+        near(hold + fade / 2.0, 0.5);
+        assert_eq!(level_at(hold + fade + FRAME), 0.0);
+
+        // Fully ducked, the music loses its share; recovered, it's back where it was
+        let music_volumes: Vec<f32> = calls
+            .borrow()
+            .iter()
+            .filter_map(|call| match call {
+                Call::SetVolume(SoundId::Music, volume) => Some(*volume),
+                _ => None,
+            })
+            .collect();
+        let lowest = music_volumes.iter().copied().fold(1.0, f32::min);
+        assert_near(lowest, 1.0 - GameConfig::MUSIC_DUCK_AMOUNT);
+        assert_near(*music_volumes.last().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn ordinary_sounds_leave_the_music_alone() {
+        let all = settings(1.0, 1.0, 1.0);
+        let (mut bus, _) = mock_bus(&all);
+        bus.play_music(SoundId::Music);
+        bus.play(SoundId::Coin);
+        bus.update(GameConfig::MUSIC_DUCK_FADE, &all);
+        assert_eq!(bus.duck_level, 0.0);
+    }
+}
@@ -0,0 +1,247 @@
+//! Collision benchmark, run with `cargo run --release --features bench --bin collision-bench`.
+//! Times the per-step platform collision pass with a plain loop over every platform
+//! against the spatial hash broad phase, in worlds of growing size, plus the F2 stress
+//! scenario of a crowd of enemies and a cloud of particles.
+
+#![allow(dead_code)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use macroquad::prelude::*;
+
+mod animation;
+mod assets;
+mod audio;
+mod config;
+mod date;
+mod editor;
+mod entities;
+mod game;
+mod graphics;
+mod input;
+mod leaderboard;
+mod level;
+mod locale;
+mod physics;
+mod profile;
+mod records;
+mod rng;
+mod save;
+mod settings;
+mod shop;
+mod stats;
+
+use config::GameConfig;
+use entities::{BoxKind, Entity, Platform, Player};
+use game::stress;
+use graphics::ParticleSystem;
+use physics::spatial::SpatialHash;
+use physics::Physics;
+
+/// Fixed steps simulated per measurement
+const STEPS: usize = 2_000;
+const WORLD_SIZES: [usize; 3] = [100, 1_000, 10_000];
+/// Platforms in the world the stress scenario runs in
+const STRESS_WORLD_SIZE: usize = 1_000;
+/// Floating platforms stacked this many rows high in a benchmark world
+const WORLD_ROWS: usize = 8;
+const WORLD_COLUMN_SPACING: f32 = 160.0;
+const WORLD_ROW_SPACING: f32 = 70.0;
+
+/// How a collision pass picks the platforms it tests against
+#[derive(Clone, Copy)]
+enum BroadPhase<'a> {
+    BruteForce,
+    Hashed(&'a SpatialHash<usize>),
+}
+
+impl BroadPhase<'_> {
+    /// Indices of the platforms worth testing against `bounds`, in level order
+    fn candidates(
+        &self,
+        platforms: &[Platform],
+        bounds: (f32, f32, f32, f32),
+        found: &mut Vec<usize>,
+    ) {
+        match self {
+            BroadPhase::BruteForce => {
+                found.clear();
+                found.extend(0..platforms.len());
+            }
+            BroadPhase::Hashed(grid) => grid.query(bounds, found),
+        }
+    }
+}
+
+struct Measurement {
+    per_step: Duration,
+    /// Where the scripted player or the first enemy ended up, to check both passes agree
+    final_position: Vec2,
+}
+
+/// A long strip of ground with `count` floating platforms staggered over it, for
+/// measuring collision cost as levels grow
+fn platform_world(count: usize) -> Vec<Platform> {
+    let columns = count.div_ceil(WORLD_ROWS);
+    let width = columns as f32 * WORLD_COLUMN_SPACING + GameConfig::VIRTUAL_WIDTH;
+    let mut platforms = vec![Platform::new_ground(
+        0.0,
+        GameConfig::LEVEL_FLOOR_Y,
+        width,
+        40.0,
+    )];
+    platforms.extend((0..count).map(|index| {
+        let column = index / WORLD_ROWS;
+        let row = index % WORLD_ROWS;
+        // Odd rows sit half a column over so there's always something to land on
+        let x =
+            column as f32 * WORLD_COLUMN_SPACING + (row % 2) as f32 * WORLD_COLUMN_SPACING / 2.0;
+        let y = GameConfig::LEVEL_FLOOR_Y - (row + 1) as f32 * WORLD_ROW_SPACING;
+        Platform::new(x, y, 100.0, 20.0)
+    }));
+    platforms
+}
+
+fn build_grid(platforms: &[Platform]) -> SpatialHash<usize> {
+    let mut grid = SpatialHash::new(GameConfig::SPATIAL_CELL_SIZE);
+    for (index, platform) in platforms.iter().enumerate() {
+        grid.insert(index, platform.get_bounds());
+    }
+    grid
+}
+
+/// Bounds grown by a few pixels, so pushes earlier in the pass can't carry a body into
+/// a platform the query didn't return
+fn padded(bounds: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+    let (x1, y1, x2, y2) = bounds;
+    (x1 - 8.0, y1 - 8.0, x2 + 8.0, y2 + 8.0)
+}
+
+/// A player holding right and jumping whenever they land, timing only the collision pass
+fn scripted_player(platforms: &[Platform], broad_phase: BroadPhase) -> Measurement {
+    let physics = Physics::new();
+    let delta_time = GameConfig::FIXED_TIMESTEP;
+    let mut player = Player::new(20.0, GameConfig::LEVEL_FLOOR_Y - 100.0);
+    let mut candidates = Vec::new();
+    let mut elapsed = Duration::ZERO;
+
+    for _ in 0..STEPS {
+        player.move_right();
+        if player.is_on_ground() {
+            player.jump();
+        }
+        physics.apply_gravity(&mut player, delta_time);
+        physics.update_position(&mut player, delta_time);
+
+        let start = Instant::now();
+        let bounds = padded(player.body.box_bounds(BoxKind::Solid));
+        broad_phase.candidates(platforms, bounds, &mut candidates);
+        let mut landed = false;
+        for &index in &candidates {
+            let platform = &platforms[index];
+            if platform.is_solid() {
+                landed |= physics
+                    .touch_solid(&mut player, platform.get_bounds())
+                    .is_some_and(|(_, impact)| impact.is_some());
+            }
+        }
+        elapsed += start.elapsed();
+
+        if !landed {
+            player.set_on_ground(false);
+        }
+    }
+
+    Measurement {
+        per_step: elapsed / STEPS as u32,
+        final_position: black_box(player.position()),
+    }
+}
+
+/// The F2 stress scenario: the enemy crowd walking and colliding while the particle cloud
+/// is kept topped up, timing the whole step
+fn stress_scenario(platforms: &[Platform], broad_phase: BroadPhase) -> Measurement {
+    let physics = Physics::new();
+    let delta_time = GameConfig::FIXED_TIMESTEP;
+    let center = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::LEVEL_FLOOR_Y - 60.0);
+    let mut enemies = stress::crowd(center.x, GameConfig::LEVEL_FLOOR_Y - 300.0);
+    let mut particles = ParticleSystem::new();
+    let mut candidates = Vec::new();
+    let mut elapsed = Duration::ZERO;
+    let solids: Vec<&Platform> = platforms.iter().collect();
+
+    for _ in 0..STEPS {
+        let start = Instant::now();
+        stress::top_up_particles(&mut particles, center);
+        particles.update(delta_time);
+        for enemy in &mut enemies {
+            if enemy.body.on_ground {
+                enemy.steer(center, &solids);
+            }
+            enemy.apply_gravity(physics.gravity, physics.terminal_velocity, delta_time);
+            enemy.update(delta_time);
+            broad_phase.candidates(platforms, padded(enemy.body.get_bounds()), &mut candidates);
+            for &index in &candidates {
+                enemy.collide(platforms[index].get_bounds());
+            }
+        }
+        elapsed += start.elapsed();
+    }
+
+    Measurement {
+        per_step: elapsed / STEPS as u32,
+        final_position: black_box(enemies[0].body.position),
+    }
+}
+
+fn print_row(scenario: &str, brute: &Measurement, hashed: &Measurement) {
+    let micros = |measurement: &Measurement| measurement.per_step.as_secs_f64() * 1e6;
+    let speedup = micros(brute) / micros(hashed).max(1e-3);
+    let agree = if brute.final_position.distance(hashed.final_position) < 0.01 {
+        "yes"
+    } else {
+        "NO"
+    };
+    println!(
+        "{:<34}{:>12.2}{:>14.2}{:>10.1}x{:>8}",
+        scenario,
+        micros(brute),
+        micros(hashed),
+        speedup,
+        agree
+    );
+}
+
+fn main() {
+    println!(
+        "Collision benchmark: {} fixed steps per run, microseconds per step",
+        STEPS
+    );
+    println!(
+        "{:<34}{:>12}{:>14}{:>11}{:>8}",
+        "scenario", "brute force", "spatial hash", "speed-up", "agree"
+    );
+
+    for count in WORLD_SIZES {
+        let platforms = platform_world(count);
+        let grid = build_grid(&platforms);
+        let brute = scripted_player(&platforms, BroadPhase::BruteForce);
+        let hashed = scripted_player(&platforms, BroadPhase::Hashed(&grid));
+        print_row(&format!("{} platforms", count), &brute, &hashed);
+    }
+
+    let platforms = platform_world(STRESS_WORLD_SIZE);
+    let grid = build_grid(&platforms);
+    let brute = stress_scenario(&platforms, BroadPhase::BruteForce);
+    let hashed = stress_scenario(&platforms, BroadPhase::Hashed(&grid));
+    print_row(
+        &format!(
+            "stress: {} enemies, {} particles",
+            GameConfig::STRESS_ENEMIES,
+            GameConfig::STRESS_PARTICLES
+        ),
+        &brute,
+        &hashed,
+    );
+}
@@ -9,117 +9,527 @@ impl GameConfig {
     pub const PLAYER_JUMP_FORCE: f32 = -400.0;
     pub const PLAYER_SIZE: (f32, f32) = (32.0, 32.0);
     pub const PLAYER_MAX_JUMPS: u32 = 2;
-    pub const PLAYER_COLOR: Color = BLUE;
+    pub const PLAYER_FOOT_HEIGHT: f32 = 6.0; // Depth of the stomping box along the player's bottom edge
+    pub const PLAYER_TRAIL_LENGTH: usize = 12; // Simulation steps of history drawn by trail skins
 
     // Physics Constants
     pub const GRAVITY: f32 = 980.0;
     pub const TERMINAL_VELOCITY: f32 = 500.0;
-    pub const FRICTION: f32 = 0.8;
+    // Setting both gravity scales to 1.0 and the apex boost to 1.0 gives the original symmetric arc
+    pub const APEX_HANG_VELOCITY: f32 = 60.0; // Below this vertical speed mid-jump the player is at the apex
+    pub const APEX_HANG_GRAVITY_SCALE: f32 = 0.5; // Gravity multiplier around the apex, for a little hang time
+    pub const FALL_GRAVITY_SCALE: f32 = 1.4; // Gravity multiplier while falling, so jumps come down snappier
+    pub const APEX_HORIZONTAL_BOOST: f32 = 1.1; // Run speed multiplier while steering at the apex
+    pub const LEDGE_JOIN_TOLERANCE: f32 = 1.0; // Platforms this close, edge to edge and top to top, form one surface
 
     // Platform Settings
-    pub const GROUND_HEIGHT: f32 = 40.0;
-    pub const PLATFORM_COLOR: Color = GREEN;
-    pub const GROUND_COLOR: Color = BROWN;
+    pub const PLATFORM_PATH_SPEED: f32 = 80.0; // Pixels per second along a path, unless the level says otherwise
+    pub const PLATFORM_PATH_STEPS: usize = 12; // Straight pieces per smoothed stretch between waypoints
+    pub const PLATFORM_MOMENTUM_TRANSFER: f32 = 1.0; // Share of a moving platform's velocity added to a jump off it, 0 for none
+    pub const PLATFORM_PATH_DOT_SPACING: f32 = 10.0; // Pixels between dots when a path is drawn
+    pub const PLATFORM_PATH_COLOR: Color = Color::new(1.0, 0.85, 0.2, 0.8);
+    pub const CYCLE_BEAT: f32 = 0.5; // Seconds per beat of the level clock that timed platforms and spikes keep to
+    pub const CYCLE_WARNING_BEATS: f32 = 1.0; // Timed things flicker for this long before they switch off
+    pub const PLATFORM_SHAKE_DISTANCE: f32 = 2.0; // Pixels a breakable platform wobbles when landed on
+    pub const PLATFORM_SHAKE_STEP: f32 = 0.04; // Seconds for each swing of the wobble
 
     // Collectible Settings
-    pub const COIN_VALUE: i32 = 10;
-    pub const GEM_VALUE: i32 = 50;
-    pub const POWERUP_VALUE: i32 = 100;
     pub const COLLECTIBLE_SIZE: (f32, f32) = (16.0, 16.0);
-    pub const COLLECTIBLE_ANIMATION_SPEED: f32 = 3.0;
+    pub const COLLECTIBLE_SPAWN_TIME: f32 = 0.25; // Seconds to pop in when first seen or spawned
+    pub const COLLECTIBLE_SPAWN_OVERSHOOT: f32 = 1.7; // How far past full size the pop-in swells (about 10%)
+
+    // High Contrast Colors
+    pub const HIGH_CONTRAST_PLAYER: Color = YELLOW;
+    pub const HIGH_CONTRAST_PLATFORM: Color = WHITE;
+    pub const HIGH_CONTRAST_HAZARD: Color = MAGENTA;
+    pub const HIGH_CONTRAST_COLLECTIBLE: Color = Color::new(0.0, 1.0, 1.0, 1.0);
+    pub const HIGH_CONTRAST_WATER: Color = Color::new(0.0, 0.25, 1.0, 0.6);
+    pub const HIGH_CONTRAST_OUTLINE: Color = BLACK;
+    pub const HIGH_CONTRAST_OUTLINE_WIDTH: f32 = 3.0;
+
+    // Sprint Settings
+    pub const SPRINT_KEY: KeyCode = KeyCode::LeftShift;
+    pub const SPRINT_MULTIPLIER: f32 = 1.6;
+
+    // Melee Settings
+    pub const ATTACK_KEY: KeyCode = KeyCode::X;
+    pub const ATTACK_ACTIVE_TIME: f32 = 0.15; // Seconds the swipe's hitbox is out
+    pub const ATTACK_COOLDOWN: f32 = 0.4; // Seconds from one swipe to the next
+    pub const ATTACK_REACH: f32 = 36.0; // How far in front of the player the swipe hits
+
+    // Control Preset Settings
+    pub const ONE_BUTTON_DOUBLE_TAP: f32 = 0.3; // Most seconds between taps that turn a one-button player around
+    pub const JUMP_CUT_SHARE: f32 = 0.45; // Share of the jump's rise kept when the button is let go early
+
+    // Movement State Settings
+    pub const DASH_KEY: KeyCode = KeyCode::C;
+    pub const DASH_SPEED: f32 = 520.0;
+    pub const DASH_TIME: f32 = 0.16; // Seconds a dash lasts, holding its height the whole way
+    pub const DASH_COOLDOWN: f32 = 0.35; // Seconds from the start of one dash to the next
+    pub const WALL_SLIDE_SPEED: f32 = 90.0; // Fastest the player slides down a wall they're clinging to
+    pub const WALL_STICK_SPEED: f32 = 30.0; // Pull toward the wall while sliding, so it stays touched
+    pub const WALL_JUMP_PUSH: f32 = 260.0; // Sideways speed away from the wall on a wall jump
+    pub const WALL_JUMP_LOCK_TIME: f32 = 0.15; // Seconds steering is ignored after a wall jump
+    pub const GLIDE_FALL_SPEED: f32 = 70.0; // Fastest the player falls while gliding
+    pub const SWIM_GRAVITY_SCALE: f32 = 0.3;
+    pub const SWIM_SINK_SPEED: f32 = 80.0; // Fastest the player sinks in water
+    pub const SWIM_STROKE_VELOCITY: f32 = -220.0; // Upward speed from each press of jump in water
+    pub const SWIM_ENTRY_DAMPING: f32 = 0.4; // Share of speed kept when splashing into water
+    pub const LEDGE_GRAB_REACH: f32 = 10.0; // How far from the top of the head a ledge can be caught
+    pub const LEDGE_REGRAB_COOLDOWN: f32 = 0.3; // Seconds after letting go before a ledge can be caught again
+
+    // Zipline Settings
+    pub const ZIPLINE_COLOR: Color = DARKGRAY;
+    pub const ZIPLINE_HAND_REACH: f32 = 10.0; // How far above the head the player can grab
+    pub const ZIPLINE_HANG_OFFSET: f32 = 6.0; // Gap between the cable and the player's head
+    pub const ZIPLINE_MAX_SPEED: f32 = 450.0;
+    pub const ZIPLINE_ATTACH_COOLDOWN: f32 = 0.4; // Seconds before the player can re-grab
+
+    // Bomb Settings
+    pub const BOMB_SIZE: (f32, f32) = (20.0, 20.0);
+    pub const BOMB_FUSE_TIME: f32 = 2.0;
+    pub const BOMB_CHAIN_FUSE: f32 = 0.2; // Fuse left on a bomb another blast lights
+    pub const BOMB_FLASH_RATE: f32 = 2.0; // Blinks per second just after lighting; it speeds up from there
+    pub const BOMB_KICK_SPEED: f32 = 220.0;
+    pub const BOMB_KICK_HOP: f32 = 150.0;
+    pub const BOMB_FRICTION: f32 = 3.0; // Share of its speed a skidding bomb loses per second
+    pub const BOMB_BLAST_RADIUS: f32 = 70.0; // Hurts, breaks and carves everything this close
+    pub const BOMB_KNOCKBACK_RADIUS: f32 = 160.0;
+    pub const BOMB_KNOCKBACK: f32 = 600.0; // Push right at the center, fading to nothing at the knockback radius
+    pub const BOMB_SHAKE_TIME: f32 = 0.4;
+    pub const BOMB_SHAKE_STRENGTH: f32 = 10.0;
+    pub const BOMB_RING_PARTICLES: usize = 24;
+
+    // Destructible Terrain Settings
+    pub const TERRAIN_CELL_SIZE: f32 = 8.0;
+    pub const TERRAIN_COLOR: Color = Color::new(0.55, 0.42, 0.3, 1.0);
+    pub const TERRAIN_SWIPE_RADIUS: f32 = 14.0; // How much of the terrain a melee swipe chips away
+
+    // Swinging Rope Settings
+    pub const ROPE_COLOR: Color = Color::new(0.75, 0.6, 0.35, 1.0);
+    pub const ROPE_LINK_COLOR: Color = Color::new(0.6, 0.45, 0.25, 1.0);
+    pub const ROPE_LINK_LENGTH: f32 = 8.0;
+    pub const ROPE_DAMPING: f32 = 0.15; // Share of the swing lost per second while held
+    pub const ROPE_FREE_DAMPING: f32 = 1.2; // Share lost per second once let go, so it settles
+    pub const ROPE_PUMP: f32 = 2.0; // Angular acceleration from steering, in radians per second squared
+    pub const ROPE_MAX_ANGLE: f32 = 1.4; // Furthest the rope swings from straight down, in radians
+    pub const ROPE_CLIMB_SPEED: f32 = 120.0;
+    pub const ROPE_MIN_GRIP: f32 = 24.0; // Closest the hands can climb to the anchor
+    pub const ROPE_HANG_OFFSET: f32 = 4.0; // Gap between the hands and the player's head
+    pub const ROPE_RELEASE_BOOST: f32 = 180.0; // Extra upward speed when jumping off
+    pub const ROPE_REGRAB_COOLDOWN: f32 = 0.5; // Seconds before the same rope can be grabbed again
+    pub const ROPE_WALL_BOUNCE: f32 = 0.3; // Share of the swing kept after swinging into something solid
+
+    // Rope Bridge Settings
+    pub const BRIDGE_SEGMENT_LENGTH: f32 = 16.0; // Rough length of each plank
+    pub const BRIDGE_SAG_DEPTH: f32 = 6.0; // How far the plank under the player dips
+    pub const BRIDGE_SAG_SPREAD: f32 = 48.0; // Planks this far from the player don't dip at all
+    pub const BRIDGE_SAG_RATE: f32 = 40.0; // Pixels per second the dip eases in and out
+    pub const BRIDGE_CATCH_DEPTH: f32 = 8.0; // How far below the planks the feet can get and still be caught, so slopes don't drop the player through
+    pub const BRIDGE_BREAK_TIME: f32 = 1.0; // Seconds on the middle of a fragile bridge before it snaps
+    pub const BRIDGE_COLOR: Color = Color::new(0.55, 0.38, 0.2, 1.0);
+    pub const BRIDGE_FRAGILE_COLOR: Color = Color::new(0.75, 0.62, 0.45, 1.0);
+    pub const BRIDGE_ROPE_COLOR: Color = Color::new(0.8, 0.7, 0.5, 1.0);
+
+    // Darkness Settings
+    pub const DARKNESS_ENABLED: bool = true; // Honor the "dark" level property
+    pub const REDUCED_DARKNESS: bool = false; // Accessibility: keep the world partly visible
+    pub const DARKNESS_ALPHA: f32 = 0.97;
+    pub const REDUCED_DARKNESS_ALPHA: f32 = 0.6;
+    pub const LIGHT_RADIUS: f32 = 140.0;
+    pub const LIGHT_SOFTNESS: f32 = 60.0; // Width of the fade at the edge of the light
+    pub const LIGHT_FLICKER: f32 = 0.04; // Fraction of the radius the light wavers by
+    pub const COLLECTIBLE_GLOW_RADIUS: f32 = 24.0;
+    pub const LANTERN_RADIUS_BONUS: f32 = 120.0;
+    pub const LANTERN_DURATION: f32 = 20.0;
+
+    // Lava Settings
+    pub const LAVA_START_DEPTH: f32 = 200.0; // How far below the screen the lava starts
+    pub const LAVA_BASE_RISE_SPEED: f32 = 12.0;
+    pub const LAVA_RISE_ACCELERATION: f32 = 0.4; // Rise speed gained per second
+    pub const LAVA_MAX_RISE_SPEED: f32 = 60.0;
+    pub const LAVA_BONUS_RANGE: f32 = 150.0; // Staying closer than this earns bonus points
+    pub const LAVA_BONUS_RATE: f32 = 40.0; // Points per second when right above the lava
+    pub const LAVA_FRAMING_MARGIN: f32 = 40.0;
+
+    // Water Settings
+    pub const WATER_COLOR: Color = Color::new(0.2, 0.45, 0.8, 0.45);
+    pub const WATER_DRAG: f32 = 2.0; // Share of its speed a body wholly under water loses per second
+    pub const WATER_SWIM_DEPTH: f32 = 0.5; // Share of the player under water before they swim
+    pub const WATER_RIPPLE_HEIGHT: f32 = 3.0;
+    pub const WATER_RIPPLE_LENGTH: f32 = 96.0; // Pixels from one crest to the next
+    pub const WATER_RIPPLE_SPEED: f32 = 1.5; // Radians per second the swell moves along
+    pub const WATER_FOAM_SPEED: f32 = 60.0; // Speed through the surface that throws up foam
+    pub const WATER_FOAM_COUNT: usize = 3; // Most foam specks one body throws up per step
+    pub const WATER_FOAM_COLOR: Color = Color::new(0.95, 0.98, 1.0, 0.9);
+    pub const BOMB_BUOYANCY: f32 = 1.6; // Floats with 1 / 1.6 of its height under
+    pub const ENEMY_BUOYANCY: f32 = 0.6; // Below 1, so enemies and their shells sink, slowly
+
+    // Pad Settings
+    pub const BOOST_PAD_SIZE: (f32, f32) = (48.0, 6.0);
+    pub const BOOST_PAD_IMPULSE: f32 = 250.0;
+    pub const BOOST_PAD_SCROLL_SPEED: f32 = 30.0;
+    pub const BOOST_MAX_SPEED: f32 = 550.0;
+    pub const BOOST_DECAY_TIME: f32 = 2.0; // Seconds to fall back from max boost to normal speed
+    pub const SPRING_SIZE: (f32, f32) = (32.0, 16.0);
+    pub const SPRING_LAUNCH_VELOCITY: f32 = -650.0;
+    pub const LAUNCH_MOMENTUM_KEEP: f32 = 1.1; // Multiplier on speed across a spring's or stomp's push, so chained bounces gain a little
+    pub const SPIKES_SIZE: (f32, f32) = (32.0, 16.0);
+    pub const SPIKE_COUNT: usize = 4; // Triangles across one spikes pad
+
+    // Boulder Settings
+    pub const BOULDER_RADIUS: f32 = 48.0;
+    pub const BOULDER_ACCELERATION: f32 = 180.0;
+    // A touch faster than the player can sprint, so the chase can't be won on foot alone
+    pub const BOULDER_MAX_SPEED: f32 = Self::PLAYER_MOVE_SPEED * Self::SPRINT_MULTIPLIER * 1.05;
+    pub const BOULDER_SHAKE_RANGE: f32 = 600.0; // The camera shakes while a boulder is this close
+    pub const BOULDER_SHAKE_STRENGTH: f32 = 6.0; // Pixels of shake with the boulder right behind
+
+    // Trigger Zone Settings
+    pub const ZONE_HINT_DURATION: f32 = 4.0; // Seconds a tutorial zone's hint stays up
+    pub const CAMERA_LOCK_BLEND_TIME: f32 = 0.5; // Seconds the camera eases into and out of a lock
+    pub const AUTO_SCROLL_SPEED: f32 = 120.0; // Default scroll zone speed in pixels per second
+
+    // Shop Settings
+    pub const SPEED_UPGRADE_STEP: f32 = 0.1; // Move speed gained per level of the speed upgrade
+    pub const HEAD_START_SHIELD_TIME: f32 = 5.0;
+
+    // Boss Settings (the fight itself is tuned in levels/boss.cfg)
+    pub const BOSS_SIZE: (f32, f32) = (96.0, 96.0);
+    pub const SHOCKWAVE_WIDTH: f32 = 28.0;
+    pub const BOSS_HURT_FLASH_TIME: f32 = 0.2;
+    pub const BOSS_STOMP_BOUNCE: f32 = -520.0; // Upward velocity after stomping the boss
+    pub const VICTORY_DELAY: f32 = 2.0; // Real seconds between the final stomp and the victory screen
+
+    // Survival Settings
+    pub const ENEMY_SIZE: (f32, f32) = (30.0, 30.0);
+    pub const ENEMY_SPEED: f32 = 90.0; // Walking speed in the first wave
+    pub const ENEMY_SPEED_STEP: f32 = 0.08; // Extra walking speed per wave, as a fraction of the first
+    pub const ENEMY_SPEED_JITTER: f32 = 0.1; // Random spread in each enemy's pace, as a fraction either way
+    pub const ENEMY_STOMP_BOUNCE: f32 = -450.0; // Upward velocity after stomping an enemy
+    pub const ENEMY_KILL_SCORE: i32 = 50;
+    pub const ENEMY_SHOT_SPEED: f32 = 260.0; // Shots from enemies placed with `shoot` in a level
+    pub const ENEMY_SHOT_RADIUS: f32 = 6.0;
+    pub const ENEMY_SHOT_RANGE: f32 = 600.0; // Shots fizzle out this far from the enemy
+    pub const WAVE_CLEAR_BONUS: i32 = 200; // Times the wave number, for clearing a wave
+    pub const WAVE_BASE_SIZE: u32 = 3;
+    pub const WAVE_SIZE_STEP: u32 = 2; // Extra enemies per wave
+    pub const MAX_ENEMIES: usize = 6; // Never more than this many in the arena at once
+    pub const ENEMY_SPAWN_INTERVAL: f32 = 0.8;
+    pub const WAVE_BREATHER: f32 = 5.0; // Rest between waves
+    pub const SHELLED_FIRST_WAVE: u32 = 2; // Shelled enemies start turning up from this wave
+    pub const SHELLED_CHANCE: f32 = 0.3; // Share of enemies sent out shelled once they do
+    pub const SHELL_HEIGHT: f32 = 0.6; // Shell height as a fraction of the enemy's
+    pub const SHELL_SLIDE_SPEED: f32 = 420.0;
+    pub const SHELL_KICK_GRACE: f32 = 0.3; // Seconds a kicked shell can't hurt the player, so the kick itself is safe
+    pub const SHELL_WAKE_TIME: f32 = 6.0; // A still shell's owner comes back out after this long
+    pub const SHELL_CHAIN_BONUS: i32 = 100; // For the first enemy a sliding shell takes out; doubles for each after
+    pub const SHELL_CHAIN_MAX_DOUBLINGS: u32 = 4;
+
+    // NPC Settings
+    pub const NPC_SIZE: (f32, f32) = (28.0, 44.0);
+    pub const NPC_TALK_RANGE: f32 = 70.0; // Center-to-center distance the player can talk from
+    pub const DIALOGUE_CHARS_PER_SECOND: f32 = 40.0;
+
+    // Cutscene Settings
+    pub const CUTSCENE_BAR_HEIGHT: f32 = 56.0; // Black bars across the top and bottom while one plays
+    pub const CUTSCENE_BAR_TIME: f32 = 0.4; // Seconds the bars take to slide in
+
+    // Door Settings
+    pub const SWITCH_SIZE: (f32, f32) = (28.0, 10.0);
+    pub const DOOR_WARNING_TIME: f32 = 2.0; // The door flashes for this long before shutting
+    pub const DOOR_TICK_RATE: f32 = 2.0; // Flashes per second when the warning starts
+    pub const DOOR_TICK_RATE_MAX: f32 = 10.0; // Flashes per second just before it shuts
+
+    // Landing Settings
+    pub const LANDING_SQUASH_SPEED: f32 = 250.0; // Landing faster than this squashes the player and kicks up dust
+    pub const LANDING_SQUASH_TIME: f32 = 0.15; // Seconds to ease back to normal shape after a hard landing
+    pub const LANDING_SQUASH_SCALE: (f32, f32) = (1.3, 0.7); // Drawn width and height multipliers at the deepest squash
+    pub const LANDING_DUST_COUNT: usize = 8;
+    // Falls top out at terminal velocity, so the harder tiers go by how far the player dropped
+    pub const HEAVY_LANDING_HEIGHT: f32 = 250.0; // Dropping further than this stuns the player briefly
+    pub const HEAVY_LANDING_STUN: f32 = 0.2;
+    pub const FALL_DAMAGE_HEIGHT: f32 = 500.0; // With fall damage on, dropping further than this is fatal
+    pub const GROUND_PROBE_TOLERANCE: f32 = 1.0; // How close below the feet a platform must be to keep standing on it
+
+    // Respawn Settings
+    pub const RESPAWN_FADE_TIME: f32 = 0.2;
+    pub const RESPAWN_PAN_TIME: f32 = 0.6;
+    pub const RESPAWN_DIM: f32 = 0.6; // Darkest the view gets while the camera pans
+    pub const SPAWN_PROTECTION_TIME: f32 = 2.0; // Seconds hazards can't hurt a respawned player
+    pub const SPAWN_PROTECTION_BLINK_RATE: f32 = 10.0; // Visibility flips per second
+    pub const OUT_OF_BOUNDS_BOUNCE_VELOCITY: f32 = -900.0; // Launch out of a bouncy pit, enough to clear the floor
 
     // Scoring
-    pub const DISTANCE_SCORE_MULTIPLIER: f32 = 0.1;
-    pub const TIME_SCORE_MULTIPLIER: i32 = 10;
+    pub const PERFECT_EDGE_FRACTION: f32 = 0.1; // Outer share of a platform's width a perfect landing hits
+    pub const PERFECT_EDGE_MAX: f32 = 24.0; // Widest that window gets on long platforms
+    pub const PERFECT_VANISH_BEATS: f32 = 0.25; // Landing on a timed platform this close to it vanishing is perfect too
+    pub const PERFECT_LANDING_BONUS: i32 = 50;
+    pub const PERFECT_MULTIPLIER: f32 = 0.25; // Extra share of bonus points after a perfect landing
+    pub const PERFECT_MULTIPLIER_STEP: f32 = 0.1; // Added by each further perfect landing before it fades
+    pub const PERFECT_MULTIPLIER_MAX: f32 = 0.75;
+    pub const PERFECT_MULTIPLIER_TIME: f32 = 5.0; // Seconds for the extra share to fade out
+    pub const PERFECT_BAR_WIDTH: f32 = 120.0;
+    pub const PERFECT_BAR_HEIGHT: f32 = 3.0;
 
     // Camera Settings
-    pub const CAMERA_SMOOTHING: f32 = 0.1;
-    pub const CAMERA_OFFSET_X: f32 = 0.0;
-    pub const CAMERA_OFFSET_Y: f32 = 0.0;
+    pub const CAMERA_SMOOTHING: f32 = 0.1; // Default share of the gap closed per step where the camera eases
+    pub const CAMERA_SMOOTHING_MIN: f32 = 0.02;
+    pub const CAMERA_SMOOTHING_MAX: f32 = 1.0; // Snaps straight to the target
 
     // Visual Settings
-    pub const BACKGROUND_COLOR: Color = SKYBLUE;
-    pub const UI_TEXT_COLOR: Color = WHITE;
-    pub const SCORE_TEXT_COLOR: Color = YELLOW;
-    pub const DEBUG_TEXT_COLOR: Color = DARKGRAY;
-
-    // Background Elements
-    pub const CLOUD_PARALLAX_SPEED: f32 = 0.3;
-    pub const MOUNTAIN_PARALLAX_SPEED: f32 = 0.1;
-    pub const TREE_PARALLAX_SPEED: f32 = 0.5;
-    pub const GRASS_PARALLAX_SPEED: f32 = 0.8;
+    pub const TRAIL_SAMPLE_INTERVAL: f32 = 0.02; // Seconds between motion trail samples
+    pub const TRAIL_BREAK_DISTANCE: f32 = 150.0; // A jump this far between samples is a teleport
+    pub const PLAYER_MOTION_TRAIL_POINTS: usize = 14; // Behind the player while boosted or in bullet time
+    pub const PROJECTILE_TRAIL_POINTS: usize = 8;
+    pub const TRAIL_WIDTH_FALLOFF: f32 = 0.2; // Width left at a trail's tail, as a fraction of its head
+
+    // Decorative Prop Settings
+    pub const PROP_CULL_MARGIN: f32 = 100.0; // Pixels outside the view a prop keeps moving
+    pub const PROP_GRASS_SPACING: f32 = 60.0; // Average gap between tufts scattered on ground platforms
+    pub const PROP_GRASS_SIZE: (f32, f32) = (14.0, 16.0); // Width and height of a tuft
+    pub const PROP_GRASS_STIFFNESS: f32 = 120.0; // Spring pulling a bent tuft back upright
+    pub const PROP_GRASS_DAMPING: f32 = 8.0;
+    pub const PROP_GRASS_PUSH: f32 = 0.004; // Radians of bend per pixel per second the player runs through
+    pub const PROP_GRASS_MAX_BEND: f32 = 0.9; // Radians
+    pub const PROP_WIND_LEAN: f32 = 0.004; // Radians grass leans per pixel per second of wind
+    pub const PROP_SIGN_SIZE: (f32, f32) = (40.0, 24.0); // Board hanging below its hook
+    pub const PROP_SIGN_CHAIN: f32 = 14.0; // Length of the chains from hook to board
+    pub const PROP_SIGN_SWING: f32 = 40.0; // Pendulum pull back to hanging straight
+    pub const PROP_SIGN_DAMPING: f32 = 1.5;
+    pub const PROP_SIGN_BUMP: f32 = 0.01; // Radians per second of swing per pixel per second of the bump
+    pub const PROP_SIGN_WIND: f32 = 0.02; // Push from each pixel per second of wind
+    pub const PROP_LEAF_INTERVAL: f32 = 0.8; // Seconds between leaves from one spot
+    pub const PROP_LEAF_LIMIT: usize = 6; // Most leaves falling from one spot at once
+    pub const PROP_LEAF_SPREAD: f32 = 40.0; // Pixels either side of the spot a leaf can start
+    pub const PROP_LEAF_FALL_SPEED: f32 = 35.0;
+    pub const PROP_LEAF_DRIFT: f32 = 0.8; // Share of the wind speed leaves are carried along at
+    pub const PROP_LEAF_SWAY: f32 = 20.0; // Pixels per second of side-to-side flutter
+    pub const PROP_LEAF_LIFE: f32 = 6.0; // Seconds a leaf falls before fading out
+    pub const PROP_BUSH_SIZE: (f32, f32) = (64.0, 48.0); // Width and height of a foreground bush
+    pub const PROP_BUSH_SEE_THROUGH: f32 = 0.55; // Opacity of a bush while the player is behind it
+
+    // Display Settings
+    pub const VIRTUAL_WIDTH: f32 = 1280.0; // All world and UI coordinates target this resolution
+    pub const VIRTUAL_HEIGHT: f32 = 720.0;
 
     // Game Rules
-    pub const DEATH_Y_THRESHOLD: f32 = 100.0; // How far below screen before death
-    pub const WORLD_WIDTH: f32 = 2000.0; // For minimap calculations
-
-    // Input Settings
-    pub const INPUT_BUFFER_TIME: f32 = 0.1; // Seconds to buffer jump input
+    pub const LEVEL_FLOOR_Y: f32 = Self::VIRTUAL_HEIGHT; // World y of the bottom of the level; level files measure up from here
+    pub const DEATH_Y_THRESHOLD: f32 = 100.0; // How far below the level floor before death
+
+    // Time Scale Settings
+    pub const BULLET_TIME_SCALE: f32 = 0.5;
+    pub const BULLET_TIME_DURATION: f32 = 5.0; // Real seconds
+    pub const DEATH_TIME_SCALE: f32 = 0.3;
+    pub const DEATH_SLOWMO_DURATION: f32 = 1.0; // Real seconds before the run ends
+    pub const DEATH_ICON_SIZE: f32 = 48.0; // Picture of the cause on the game over screen
+    pub const STATS_TOP_DEATHS: usize = 3; // Causes listed next to the death count in statistics
+
+    // Assist Settings
+    pub const ASSIST_SPEED_MIN: f32 = 0.5; // Limits for the game_speed setting
+    pub const ASSIST_SPEED_MAX: f32 = 2.0;
+    pub const ASSIST_SPEED_STEP: f32 = 0.1; // Game speed moved by one Left/Right press
+    pub const LEDGE_ASSIST_HEIGHT: f32 = 4.0; // Pixels short of a ledge's top the player is lifted onto it from
+    pub const SKIP_LEVEL_DEATHS: u32 = 5; // Deaths on one level before the pause menu offers to skip it
+
+    // Daily Challenge Settings
+    pub const DAILY_DARK_CHANCE: f32 = 0.3;
+    pub const DAILY_LAVA_CHANCE: f32 = 0.4;
+    pub const DAILY_WIND_CHANCE: f32 = 0.5;
+    pub const DAILY_WIND_MIN: f32 = 20.0;
+    pub const DAILY_WIND_MAX: f32 = 70.0;
+    pub const DAILY_MIN_PLATFORM_SCALE: f32 = 0.6;
+
+    // Loading Settings
+    pub const ASSET_LOAD_BUDGET: f64 = 1.0 / 60.0; // Seconds of loading per frame before the loading screen is drawn again
+    pub const LOADING_BAR_WIDTH: f32 = 480.0;
+    pub const ASSET_WARNING_TIME: f32 = 8.0; // Seconds the list of files that didn't load stays up
+    pub const ASSET_WARNING_LINES: usize = 6; // Files listed before the rest are counted
+
+    // Level Settings
+    pub const LEVEL_HOT_RELOAD: bool = true; // Debug: F9 and file polling reload the level
+    pub const LEVEL_RELOAD_POLL_INTERVAL: f32 = 1.0;
+    pub const LEVEL_RELOAD_BANNER_TIME: f32 = 2.0;
+
+    // Level Select Settings
+    pub const THUMBNAIL_SIZE: (u16, u16) = (160, 90); // Pixels in each level's preview
+    pub const THUMBNAIL_MARGIN: f32 = 4.0; // Pixels of sky kept around the layout
+    pub const THUMBNAIL_SKY_COLOR: Color = Color::new(0.1, 0.12, 0.2, 1.0);
+    pub const LEVEL_SELECT_VISIBLE_ROWS: usize = 4; // Entries shown before the list scrolls
+    pub const LEVEL_SELECT_STAR_RADIUS: f32 = 9.0;
+
+    // Star Rating Settings
+    pub const STAR_REVEAL_DELAY: f32 = 0.6; // Seconds between each star appearing on the victory screen
+    pub const STAR_POP_TIME: f32 = 0.25; // Seconds a revealed star takes to shrink to its size
+    pub const STAR_POP_SCALE: f32 = 1.6; // Size a star is revealed at, shrinking to 1
+    pub const STAR_RADIUS: f32 = 28.0;
+    pub const STAR_SPACING: f32 = 76.0;
+
+    // Editor Settings
+    pub const EDITOR_PAN_SPEED: f32 = 500.0;
+    pub const EDITOR_STATUS_TIME: f32 = 3.0;
+    pub const EDITOR_HANDLE_SIZE: f32 = 8.0; // Side of a selection handle square in pixels
+    pub const EDITOR_HANDLE_GRAB_RADIUS: f32 = 8.0; // Screen pixels from a handle that still grab it
+    pub const EDITOR_UNDO_LIMIT: usize = 200; // Oldest edits are forgotten past this
+    pub const EDITOR_AUTOSAVE_INTERVAL: f32 = 60.0; // Seconds between autosaves of unsaved work
+    pub const EDITOR_ZOOM_STEP: f32 = 1.25; // Zoom change per mouse wheel notch
+    pub const EDITOR_MIN_ZOOM: f32 = 0.25;
+    pub const EDITOR_MAX_ZOOM: f32 = 2.0;
+    pub const EDITOR_HIDDEN_OUTLINES: bool = true; // Hidden editor layers leave faint outlines
+    pub const EDITOR_ZONE_COOLDOWN: f32 = 5.0; // Seconds a zone waits once R makes it repeat
+
+    // Jump Arc Preview Settings
+    pub const JUMP_ARC_SAMPLE_TIME: f32 = 1.0 / 30.0; // Seconds of flight between points along an arc
+    pub const JUMP_ARC_DROP: f32 = 200.0; // How far below the take-off point an arc is followed
+    pub const JUMP_ARC_DOT_SPACING: f32 = 8.0;
+    pub const JUMP_ARC_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8); // A single jump
+    pub const JUMP_ARC_EXTENDED_COLOR: Color = Color::new(1.0, 0.6, 0.2, 0.8); // Every jump chained at its apex
+
+    // Touch Control Settings (positions are fractions of the screen, sizes virtual pixels)
+    pub const TOUCH_LEFT_POSITION: Vec2 = Vec2::new(0.09, 0.82);
+    pub const TOUCH_RIGHT_POSITION: Vec2 = Vec2::new(0.23, 0.82);
+    pub const TOUCH_JUMP_POSITION: Vec2 = Vec2::new(0.9, 0.8);
+    pub const TOUCH_PAUSE_POSITION: Vec2 = Vec2::new(0.95, 0.08);
+    pub const TOUCH_BUTTON_RADIUS: f32 = 72.0; // Scales with the window along with everything else
+    pub const TOUCH_PAUSE_RADIUS: f32 = 40.0;
+    pub const TOUCH_BUTTON_ALPHA: f32 = 0.35; // See-through so the level shows behind them
+    pub const TOUCH_HELD_ALPHA: f32 = 0.7;
 
     // Animation Settings
     pub const FLOAT_AMPLITUDE: f32 = 3.0;
     pub const FLOAT_FREQUENCY: f32 = 3.0;
-    pub const SPARKLE_FREQUENCY: f32 = 6.0;
-    pub const GLOW_PULSE_SPEED: f32 = 4.0;
+    pub const FACING_EYE_SHIFT: f32 = 3.0; // Pixels the eyes sit toward the way the player faces
+    pub const TEETER_EYE_SHIFT: f32 = 3.0; // Pixels the eyes look toward a ledge the player is hanging over
+    pub const TEETER_SWAY: f32 = 1.5;
+    pub const TEETER_FREQUENCY: f32 = 12.0;
+    pub const IDLE_DELAY: f32 = 4.0; // Seconds standing still before the idle animation starts
+    pub const IDLE_SIT_DELAY: f32 = 15.0; // Seconds standing still before the player sits down to yawn
+    pub const IDLE_STILL_SPEED: f32 = 1.0; // Slower than this counts as standing still
+    pub const IDLE_BLINK_INTERVAL: f32 = 3.0;
+    pub const IDLE_BLINK_TIME: f32 = 0.1;
+    pub const IDLE_GLANCE_INTERVAL: f32 = 5.0; // Seconds between looks to one side, alternating
+    pub const IDLE_GLANCE_TIME: f32 = 1.2;
+    pub const IDLE_GLANCE_SHIFT: f32 = 4.0; // Pixels the eyes move when looking aside
+    pub const IDLE_BREATH_PERIOD: f32 = 2.5;
+    pub const IDLE_BREATH_DEPTH: f32 = 1.0; // Pixels the body bobs while breathing
+    pub const IDLE_SIT_SCALE: (f32, f32) = (1.1, 0.8); // Drawn width and height multipliers while sitting
+    pub const IDLE_SIT_TIME: f32 = 0.4; // Seconds to settle into sitting
+    pub const IDLE_YAWN_INTERVAL: f32 = 6.0; // Seconds between yawns once sitting
+    pub const IDLE_YAWN_TIME: f32 = 1.5;
+    pub const JUMP_STRETCH_SCALE: (f32, f32) = (0.8, 1.25); // Drawn width and height multipliers at jump takeoff speed
+    pub const FALL_STRETCH_SCALE: (f32, f32) = (0.9, 1.1); // Drawn width and height multipliers at terminal velocity
+    pub const FALL_STRETCH_SPEED: f32 = 500.0; // Falling speed that reaches the full fall stretch
 
     // Performance Settings
-    pub const TARGET_FPS: i32 = 60;
+    pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0; // Simulation runs at this rate whatever the render rate
+    pub const MAX_FRAME_TIME: f32 = 0.05; // Most simulation time a single frame can add
+    pub const AUTO_PAUSE_FRAME_TIME: f32 = 0.25; // A frame this long means the window lost focus
     pub const MAX_PARTICLES: usize = 100;
+    pub const MAX_ACTIVE_PROPS: usize = 40; // Decorative props animated at once, nearest the view first
+    pub const PARTICLE_DRAG: f32 = 6.0; // Fraction of particle speed lost per second
+    #[allow(dead_code)] // Only the collision bench uses it so far
+    pub const SPATIAL_CELL_SIZE: f32 = 256.0; // Side of a broad phase grid cell
+    pub const SIM_REDUCED_DISTANCE: f32 = 2.0; // Screens from the view past which enemies and moving platforms update less often
+    pub const SIM_SLEEP_DISTANCE: f32 = 4.0; // Screens from the view past which they stop until it comes closer
+    pub const SIM_REDUCED_INTERVAL: u64 = 4; // Steps between updates of those in between
 
     // UI Layout
     pub const UI_MARGIN: f32 = 10.0;
     pub const UI_LINE_HEIGHT: f32 = 25.0;
+    pub const UI_LABEL_FONT_SIZE: f32 = 14.0; // Sizes behind each TextStyle preset
+    pub const UI_SMALL_FONT_SIZE: f32 = 16.0;
+    pub const UI_CAPTION_FONT_SIZE: f32 = 18.0;
     pub const UI_FONT_SIZE: f32 = 20.0;
+    pub const UI_BODY_FONT_SIZE: f32 = 24.0;
+    pub const UI_HUD_LARGE_FONT_SIZE: f32 = 28.0;
+    pub const UI_HEADING_FONT_SIZE: f32 = 36.0;
     pub const UI_LARGE_FONT_SIZE: f32 = 50.0;
-    pub const UI_SMALL_FONT_SIZE: f32 = 16.0;
+    pub const UI_LOGO_FONT_SIZE: f32 = 60.0;
+    pub const UI_MIN_SCALE: f32 = 0.5; // Limits for the ui_scale setting
+    pub const UI_MAX_SCALE: f32 = 2.0;
+    pub const UI_MAX_AUTO_SCALE: f32 = 1.5; // Most the HUD grows by itself in a small window
+    pub const SLIDER_STEP: f32 = 0.05; // Share of a slider's range moved by one Left/Right press
+    pub const RESTART_CONFIRM_TIME: f32 = 60.0; // Restarting from the pause menu asks first past this many seconds
+    pub const SCORE_COUNT_RATE: f32 = 8.0; // Share of the gap the HUD score closes per second
+    pub const SCORE_MILESTONE: i32 = 500; // The HUD score flashes each time it passes a multiple of this
+    pub const SCORE_FLASH_TIME: f32 = 0.3;
+    pub const SCORE_GAIN_MIN: i32 = 25; // Smaller jumps in score don't get a "+N"
+    pub const PICKUP_FLIGHT_TIME: f32 = 0.4; // Seconds a collected coin takes to reach the HUD counter
+    pub const PICKUP_FLIGHT_ARC: f32 = 80.0; // How far above its path a flying coin rises
+    pub const SCORE_GAIN_TIME: f32 = 0.5; // Gains this close together add up into one "+N"
+    pub const POPUP_TIME: f32 = 1.0; // Seconds text like "Perfect!" floats above the world
+    pub const POPUP_RISE: f32 = 50.0;
+    pub const FEED_MAX_ENTRIES: usize = 5; // Lines in the event feed before the oldest is dropped
+    pub const FEED_ENTRY_TIME: f32 = 4.0; // Seconds each line stays, fading out at the end
+    pub const FEED_FADE_TIME: f32 = 0.5;
+    pub const FEED_SLIDE_TIME: f32 = 0.2; // Seconds a new line takes to slide in from the edge
+    pub const FEED_SLIDE_DISTANCE: f32 = 120.0;
+    pub const FEED_SLIDE_RATE: f32 = 8.0; // Rows per second older lines move up
 
     // Debug Settings
-    pub const SHOW_DEBUG_INFO: bool = true;
-    pub const SHOW_VELOCITY_INDICATOR: bool = true;
     pub const SHOW_COLLISION_BOXES: bool = false;
-    pub const SHOW_GRID: bool = false;
     pub const GRID_SIZE: f32 = 32.0;
-
-    // Color Palette
-    pub const PALETTE_PRIMARY: Color = Color::new(0.2, 0.4, 1.0, 1.0);
-    pub const PALETTE_SECONDARY: Color = Color::new(1.0, 0.6, 0.2, 1.0);
-    pub const PALETTE_ACCENT: Color = Color::new(0.8, 0.2, 0.8, 1.0);
-    pub const PALETTE_SUCCESS: Color = Color::new(0.2, 0.8, 0.2, 1.0);
-    pub const PALETTE_WARNING: Color = Color::new(1.0, 1.0, 0.2, 1.0);
-    pub const PALETTE_DANGER: Color = Color::new(1.0, 0.2, 0.2, 1.0);
-
-    // Game Balance
-    pub const SCORE_THRESHOLD_BRONZE: i32 = 100;
-    pub const SCORE_THRESHOLD_SILVER: i32 = 500;
-    pub const SCORE_THRESHOLD_GOLD: i32 = 1000;
-
-    // Audio Settings (for future implementation)
+    pub const DEBUG_TIME_SCALE_KEY: bool = true; // Shift+F7 cycles the world speed
+    pub const CONTACT_LOG_CAPACITY: usize = 300; // Contacts kept by the contact-log feature
+    pub const CONTACT_LOG_ARROW_LENGTH: f32 = 10.0; // Pixels of each logged contact's normal arrow
+    pub const DEBUG_STEP_REPEAT_RATE: f32 = 10.0; // Steps per second while '.' is held in step mode
+    pub const DEBUG_TIME_SCALES: [f32; 4] = [0.25, 0.5, 1.0, 2.0];
+    pub const DEBUG_TOOLS: bool = false; // Free camera, noclip and console in release builds; debug builds always have them
+    pub const FREE_CAMERA_SPEED: f32 = 600.0;
+    pub const FREE_CAMERA_ZOOM_RATE: f32 = 1.5;
+    pub const FREE_CAMERA_MIN_ZOOM: f32 = 0.25;
+    pub const FREE_CAMERA_MAX_ZOOM: f32 = 4.0;
+    pub const NOCLIP_SPEED: f32 = 500.0;
+    pub const CONSOLE_LOG_LINES: usize = 200;
+    pub const CONSOLE_VISIBLE_LINES: usize = 12;
+    pub const CONSOLE_MAX_TIME_SCALE: f32 = 10.0;
+    pub const CONSOLE_MIN_TICK_RATE: f32 = 5.0;
+    pub const CONSOLE_MAX_TICK_RATE: f32 = 240.0;
+    pub const STRESS_ENEMIES: usize = 50; // F2 stress test crowd, also used by the collision benchmark
+    pub const STRESS_PARTICLES: usize = 500;
+    pub const DEBUG_CLIP_RECORDING: bool = true; // F6 saves the last few seconds as a GIF
+    pub const CLIP_SECONDS: f32 = 5.0;
+    pub const CLIP_FPS: f32 = 15.0;
+    pub const CLIP_DOWNSCALE: usize = 2; // Clip frames keep every Nth pixel of the virtual frame
+
+    // Audio Settings (defaults for settings.cfg)
     pub const MASTER_VOLUME: f32 = 1.0;
     pub const SFX_VOLUME: f32 = 0.8;
     pub const MUSIC_VOLUME: f32 = 0.6;
+    pub const MUSIC_DUCK_AMOUNT: f32 = 0.3; // Share of the music volume taken away under death and victory sounds
+    pub const MUSIC_DUCK_TIME: f32 = 1.5; // Seconds the music stays ducked
+    pub const MUSIC_DUCK_FADE: f32 = 0.25; // Seconds to duck fully or recover
+    pub const SOUND_FULL_VOLUME_DISTANCE: f32 = 640.0; // World sounds this close to the middle of the view play at full volume
+    pub const FOOTSTEP_STRIDE: f32 = 40.0; // Pixels walked between footsteps
+    pub const FOOTSTEP_MIN_SPEED: f32 = 20.0; // Slower than this is standing still
+    pub const FOOTSTEP_VOLUME: f32 = 0.5;
+    pub const LANDING_THUD_MIN_SPEED: f32 = 120.0; // Softer touchdowns make no sound
+    pub const LANDING_THUD_FULL_SPEED: f32 = 700.0; // Landing this fast thuds at full volume
+    pub const SOUND_SILENT_DISTANCE: f32 = 1920.0; // About 1.5 screen widths; further away is silent
+
+    // Rumble Settings (strengths 0 to 1, before the rumble_intensity setting)
+    pub const RUMBLE_LANDING: f32 = 0.4; // Landing at LANDING_THUD_FULL_SPEED; slower landings are lighter
+    pub const RUMBLE_LANDING_TIME: f32 = 0.1;
+    pub const RUMBLE_DAMAGE: f32 = 0.6;
+    pub const RUMBLE_DAMAGE_TIME: f32 = 0.25;
+    pub const RUMBLE_EXPLOSION: f32 = 1.0;
+    pub const RUMBLE_EXPLOSION_TIME: f32 = 0.4;
+    pub const RUMBLE_SLAM: f32 = 0.9; // The boss landing a ground slam
+    pub const RUMBLE_SLAM_TIME: f32 = 0.35;
+    pub const RUMBLE_WALL_SLIDE: f32 = 0.15; // Held for as long as the slide lasts
 
     // Helper methods for common calculations
     pub fn screen_center() -> Vec2 {
-        Vec2::new(screen_width() / 2.0, screen_height() / 2.0)
-    }
-
-    pub fn ground_y() -> f32 {
-        screen_height() - Self::GROUND_HEIGHT
+        Vec2::new(Self::VIRTUAL_WIDTH / 2.0, Self::VIRTUAL_HEIGHT / 2.0)
     }
 
     pub fn death_threshold() -> f32 {
-        screen_height() + Self::DEATH_Y_THRESHOLD
-    }
-
-    pub fn player_spawn_position() -> Vec2 {
-        Vec2::new(100.0, Self::ground_y() - Self::PLAYER_SIZE.1)
+        Self::LEVEL_FLOOR_Y + Self::DEATH_Y_THRESHOLD
     }
 
-    pub fn ui_position(line: i32) -> Vec2 {
-        Vec2::new(
-            Self::UI_MARGIN,
-            Self::UI_MARGIN + (line as f32) * Self::UI_LINE_HEIGHT,
-        )
+    /// Vertical camera offset that keeps the level floor at the bottom of the screen
+    pub fn floor_camera_y() -> f32 {
+        Self::LEVEL_FLOOR_Y - Self::VIRTUAL_HEIGHT
     }
 
     // Color utility methods
@@ -127,53 +537,11 @@ impl GameConfig {
         Color::new(color.r, color.g, color.b, alpha)
     }
 
-    pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
-        let t = t.clamp(0.0, 1.0);
-        Color::new(
-            a.r + (b.r - a.r) * t,
-            a.g + (b.g - a.g) * t,
-            a.b + (b.b - a.b) * t,
-            a.a + (b.a - a.a) * t,
-        )
-    }
-
-    // Get collectible color by type
-    pub fn collectible_color(collectible_type: &str) -> Color {
-        match collectible_type {
-            "coin" => Self::PALETTE_WARNING,
-            "gem" => Self::PALETTE_ACCENT,
-            "powerup" => PINK,
-            _ => WHITE,
-        }
-    }
-
-    // Get platform color by type
-    pub fn platform_color(platform_type: &str) -> Color {
-        match platform_type {
-            "ground" => Self::GROUND_COLOR,
-            "normal" => Self::PLATFORM_COLOR,
-            "breakable" => ORANGE,
-            "moving" => Self::PALETTE_SECONDARY,
-            _ => GRAY,
-        }
-    }
-
     // Difficulty scaling (for future implementation)
     pub fn get_difficulty_multiplier(time_survived: f32) -> f32 {
         1.0 + (time_survived / 60.0) * 0.1 // Increase difficulty by 10% every minute
     }
 
-    // Score formatting
-    pub fn format_score(score: i32) -> String {
-        if score >= 1000000 {
-            format!("{:.1}M", score as f32 / 1000000.0)
-        } else if score >= 1000 {
-            format!("{:.1}K", score as f32 / 1000.0)
-        } else {
-            score.to_string()
-        }
-    }
-
     // Time formatting
     pub fn format_time(seconds: f32) -> String {
         let minutes = (seconds / 60.0) as i32;
@@ -184,102 +552,4 @@ impl GameConfig {
             format!("{:.1}s", secs)
         }
     }
-
-    // Performance monitoring
-    pub fn is_performance_good() -> bool {
-        get_fps() as f32 >= (Self::TARGET_FPS as f32 * 0.8) // 80% of target FPS
-    }
-
-    // Debug information
-    pub fn get_debug_info() -> String {
-        format!(
-            "FPS: {} | Frame: {:.2}ms | Memory: Debug",
-            get_fps(),
-            get_frame_time() * 1000.0
-        )
-    }
-}
-
-// Configuration presets for different difficulty levels
-pub struct DifficultyPresets;
-
-impl DifficultyPresets {
-    pub fn easy() -> DifficultyConfig {
-        DifficultyConfig {
-            gravity: GameConfig::GRAVITY * 0.8,
-            jump_force: GameConfig::PLAYER_JUMP_FORCE * 1.2,
-            move_speed: GameConfig::PLAYER_MOVE_SPEED * 1.1,
-            max_jumps: 3,
-        }
-    }
-
-    pub fn normal() -> DifficultyConfig {
-        DifficultyConfig {
-            gravity: GameConfig::GRAVITY,
-            jump_force: GameConfig::PLAYER_JUMP_FORCE,
-            move_speed: GameConfig::PLAYER_MOVE_SPEED,
-            max_jumps: GameConfig::PLAYER_MAX_JUMPS,
-        }
-    }
-
-    pub fn hard() -> DifficultyConfig {
-        DifficultyConfig {
-            gravity: GameConfig::GRAVITY * 1.2,
-            jump_force: GameConfig::PLAYER_JUMP_FORCE * 0.9,
-            move_speed: GameConfig::PLAYER_MOVE_SPEED * 0.9,
-            max_jumps: 1,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct DifficultyConfig {
-    pub gravity: f32,
-    pub jump_force: f32,
-    pub move_speed: f32,
-    pub max_jumps: u32,
-}
-
-// Environment presets for different visual themes
-pub struct EnvironmentPresets;
-
-impl EnvironmentPresets {
-    pub fn day() -> EnvironmentConfig {
-        EnvironmentConfig {
-            background_color: SKYBLUE,
-            ground_color: BROWN,
-            platform_color: GREEN,
-            cloud_alpha: 0.8,
-            mountain_alpha: 0.8,
-        }
-    }
-
-    pub fn sunset() -> EnvironmentConfig {
-        EnvironmentConfig {
-            background_color: Color::new(1.0, 0.7, 0.3, 1.0),
-            ground_color: Color::new(0.4, 0.2, 0.1, 1.0),
-            platform_color: Color::new(0.6, 0.4, 0.2, 1.0),
-            cloud_alpha: 0.6,
-            mountain_alpha: 0.9,
-        }
-    }
-
-    pub fn night() -> EnvironmentConfig {
-        EnvironmentConfig {
-            background_color: Color::new(0.1, 0.1, 0.3, 1.0),
-            ground_color: Color::new(0.2, 0.2, 0.2, 1.0),
-            platform_color: Color::new(0.3, 0.3, 0.4, 1.0),
-            cloud_alpha: 0.4,
-            mountain_alpha: 0.5,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct EnvironmentConfig {
-    pub background_color: Color,
-    pub ground_color: Color,
-    pub platform_color: Color,
-    pub cloud_alpha: f32,
-    pub mountain_alpha: f32,
 }
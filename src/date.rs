@@ -0,0 +1,87 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A calendar day in UTC, written as YYYY-MM-DD
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Today in UTC, from the browser's clock on the web, where `SystemTime` isn't available
+    pub fn today() -> Self {
+        let seconds = macroquad::miniquad::date::now().max(0.0);
+        Self::from_days_since_epoch((seconds / 86_400.0) as i64)
+    }
+
+    /// Civil date for a day count since 1970-01-01 (Howard Hinnant's algorithm)
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+
+        Self { year, month, day }
+    }
+
+    /// Inverse of `from_days_since_epoch`
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The day before this one
+    pub fn previous(&self) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() - 1)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.trim().splitn(3, '-');
+        let mut next = |what: &str| {
+            parts
+                .next()
+                .and_then(|part| part.parse::<i64>().ok())
+                .ok_or_else(|| format!("invalid {} in date '{}'", what, text))
+        };
+        let year = next("year")?;
+        let month = next("month")?;
+        let day = next("day")?;
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(format!("date '{}' is out of range", text));
+        }
+
+        Ok(Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        })
+    }
+}
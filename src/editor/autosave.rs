@@ -0,0 +1,45 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::level::Level;
+
+/// The sidecar file next to a level that holds unsaved editor work, such as
+/// `levels/level1.autosave` for `levels/level1.lvl`
+pub fn autosave_path(level_path: &Path) -> PathBuf {
+    level_path.with_extension("autosave")
+}
+
+/// Write the editor's level to the autosave file. It goes to a temporary file first and
+/// is renamed over the old autosave, so a crash mid-write never leaves a broken one.
+pub fn write(level_path: &Path, level: &Level) -> io::Result<()> {
+    let path = autosave_path(level_path);
+    let temp = path.with_extension("autosave.tmp");
+    std::fs::write(&temp, level.to_string())?;
+    std::fs::rename(&temp, &path)
+}
+
+/// The autosaved level, if there is one written after the level file was last saved
+pub fn recoverable(level_path: &Path) -> Option<Level> {
+    let path = autosave_path(level_path);
+    let autosaved = modified_time(&path)?;
+    // A level file that can't be read is older than any autosave
+    if modified_time(level_path).is_some_and(|saved| saved >= autosaved) {
+        return None;
+    }
+    Level::load(&path).ok()
+}
+
+/// Remove the autosave once its work has been saved or thrown away
+pub fn discard(level_path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(autosave_path(level_path)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
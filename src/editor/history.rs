@@ -0,0 +1,394 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::pad::PadKind;
+use crate::entities::zone::ZoneEffect;
+use crate::level::{CollectibleDef, EnemyDef, Level, PadDef, PlatformDef, ZiplineDef, ZoneDef};
+
+use super::EditorItem;
+
+/// A copy of one item's definition, enough to put it back exactly as it was
+#[derive(Debug, Clone, PartialEq)]
+pub enum Snapshot {
+    Platform(PlatformDef),
+    Collectible(CollectibleDef),
+    Zipline(ZiplineDef),
+    Pad(PadDef),
+    Zone(ZoneDef),
+    Enemy(EnemyDef),
+    Spawn(Vec2),
+}
+
+impl Snapshot {
+    pub fn take(level: &Level, item: EditorItem) -> Self {
+        match item {
+            EditorItem::Platform(index) => Snapshot::Platform(level.platforms[index].clone()),
+            EditorItem::Collectible(index) => {
+                Snapshot::Collectible(level.collectibles[index].clone())
+            }
+            EditorItem::Zipline(index) => Snapshot::Zipline(level.ziplines[index].clone()),
+            EditorItem::Pad(index) => Snapshot::Pad(level.pads[index].clone()),
+            EditorItem::Zone(index) => Snapshot::Zone(level.zones[index].clone()),
+            EditorItem::Enemy(index) => Snapshot::Enemy(level.enemies[index].clone()),
+            EditorItem::Spawn => Snapshot::Spawn(level.spawn),
+        }
+    }
+
+    /// The item this would be if it sat at `index` in its list
+    pub fn item(&self, index: usize) -> EditorItem {
+        match self {
+            Snapshot::Platform(_) => EditorItem::Platform(index),
+            Snapshot::Collectible(_) => EditorItem::Collectible(index),
+            Snapshot::Zipline(_) => EditorItem::Zipline(index),
+            Snapshot::Pad(_) => EditorItem::Pad(index),
+            Snapshot::Zone(_) => EditorItem::Zone(index),
+            Snapshot::Enemy(_) => EditorItem::Enemy(index),
+            Snapshot::Spawn(_) => EditorItem::Spawn,
+        }
+    }
+
+    /// What the item is called in undo messages
+    pub fn noun(&self) -> &'static str {
+        match self {
+            Snapshot::Platform(_) => "platform",
+            Snapshot::Collectible(def) => def.collectible_type.name(),
+            Snapshot::Zipline(_) => "zipline",
+            Snapshot::Pad(def) => match def.pad_kind {
+                PadKind::Spring => "spring",
+                PadKind::Boost { .. } => "boost pad",
+                PadKind::Spikes => "spikes",
+            },
+            Snapshot::Zone(_) => "zone",
+            Snapshot::Enemy(_) => "enemy",
+            Snapshot::Spawn(_) => "spawn point",
+        }
+    }
+
+    /// Start of the ids given to copies
+    pub fn id_prefix(&self) -> &'static str {
+        match self {
+            Snapshot::Platform(def) => def.platform_type.name(),
+            Snapshot::Collectible(def) => def.collectible_type.name(),
+            Snapshot::Zipline(_) => "zipline",
+            Snapshot::Pad(def) => match def.pad_kind {
+                PadKind::Spring => "spring",
+                PadKind::Boost { .. } => "boost",
+                PadKind::Spikes => "spikes",
+            },
+            Snapshot::Zone(_) => "zone",
+            Snapshot::Enemy(def) => def.kind.name(),
+            Snapshot::Spawn(_) => "spawn",
+        }
+    }
+
+    pub fn set_id(&mut self, id: String) {
+        match self {
+            Snapshot::Platform(def) => def.id = id,
+            Snapshot::Collectible(def) => def.id = id,
+            Snapshot::Zipline(def) => def.id = id,
+            Snapshot::Pad(def) => def.id = id,
+            Snapshot::Zone(def) => def.id = id,
+            Snapshot::Enemy(def) => def.id = id,
+            Snapshot::Spawn(_) => {}
+        }
+    }
+
+    /// Top-left-most point of the item, which copies are lined up by
+    pub fn origin(&self) -> Vec2 {
+        match self {
+            Snapshot::Platform(def) => def.position,
+            Snapshot::Collectible(def) => def.position,
+            Snapshot::Zipline(def) => def.start.min(def.end),
+            Snapshot::Pad(def) => Vec2::new(def.x, def.surface_y),
+            Snapshot::Zone(def) => def.position,
+            Snapshot::Enemy(def) => Vec2::new(def.x, def.surface_y),
+            Snapshot::Spawn(spawn) => *spawn,
+        }
+    }
+
+    /// The same item moved by `offset`
+    pub fn shifted(mut self, offset: Vec2) -> Self {
+        match &mut self {
+            Snapshot::Platform(def) => {
+                def.position += offset;
+                if let Some(path) = &mut def.path {
+                    for waypoint in &mut path.waypoints {
+                        *waypoint += offset;
+                    }
+                }
+            }
+            Snapshot::Collectible(def) => def.position += offset,
+            Snapshot::Zipline(def) => {
+                def.start += offset;
+                def.end += offset;
+            }
+            Snapshot::Pad(def) => {
+                def.x += offset.x;
+                def.surface_y += offset.y;
+            }
+            // Where a zone sends the player or the camera goes along with it
+            Snapshot::Zone(def) => {
+                def.position += offset;
+                match &mut def.effect {
+                    ZoneEffect::CameraLock { position, .. } => *position += offset,
+                    ZoneEffect::Teleport(target) => *target += offset,
+                    ZoneEffect::AutoScroll { end_x, .. } => *end_x += offset.x,
+                    _ => {}
+                }
+            }
+            Snapshot::Enemy(def) => {
+                def.x += offset.x;
+                def.surface_y += offset.y;
+                if let Some((left, right)) = &mut def.behavior.patrol {
+                    *left += offset.x;
+                    *right += offset.x;
+                }
+            }
+            Snapshot::Spawn(spawn) => *spawn += offset,
+        }
+        self
+    }
+
+    /// Number of items in the list this one belongs in
+    pub fn list_len(&self, level: &Level) -> usize {
+        match self {
+            Snapshot::Platform(_) => level.platforms.len(),
+            Snapshot::Collectible(_) => level.collectibles.len(),
+            Snapshot::Zipline(_) => level.ziplines.len(),
+            Snapshot::Pad(_) => level.pads.len(),
+            Snapshot::Zone(_) => level.zones.len(),
+            Snapshot::Enemy(_) => level.enemies.len(),
+            Snapshot::Spawn(_) => 1,
+        }
+    }
+
+    /// World bounds as (x1, y1, x2, y2)
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Snapshot::Platform(def) => def.build().get_bounds(),
+            Snapshot::Collectible(def) => def.build().get_bounds(),
+            Snapshot::Pad(def) => def.build().body.get_bounds(),
+            Snapshot::Zone(def) => def.build().body.get_bounds(),
+            Snapshot::Enemy(def) => def.build().body.get_bounds(),
+            Snapshot::Zipline(def) => {
+                let (min, max) = (def.start.min(def.end), def.start.max(def.end));
+                (min.x, min.y, max.x, max.y)
+            }
+            Snapshot::Spawn(spawn) => {
+                let size = Vec2::from(GameConfig::PLAYER_SIZE);
+                (spawn.x, spawn.y, spawn.x + size.x, spawn.y + size.y)
+            }
+        }
+    }
+
+    /// Add the item to the end of its list
+    pub fn push_onto(&self, level: &mut Level) {
+        self.insert(level, self.list_len(level));
+    }
+
+    fn insert(&self, level: &mut Level, index: usize) {
+        match self {
+            Snapshot::Platform(def) => level.platforms.insert(index, def.clone()),
+            Snapshot::Collectible(def) => level.collectibles.insert(index, def.clone()),
+            Snapshot::Zipline(def) => level.ziplines.insert(index, def.clone()),
+            Snapshot::Pad(def) => level.pads.insert(index, def.clone()),
+            Snapshot::Zone(def) => level.zones.insert(index, def.clone()),
+            Snapshot::Enemy(def) => level.enemies.insert(index, def.clone()),
+            Snapshot::Spawn(spawn) => level.spawn = *spawn,
+        }
+    }
+
+    fn remove(&self, level: &mut Level, index: usize) {
+        match self {
+            Snapshot::Platform(_) => {
+                level.platforms.remove(index);
+            }
+            Snapshot::Collectible(_) => {
+                level.collectibles.remove(index);
+            }
+            Snapshot::Zipline(_) => {
+                level.ziplines.remove(index);
+            }
+            Snapshot::Pad(_) => {
+                level.pads.remove(index);
+            }
+            Snapshot::Zone(_) => {
+                level.zones.remove(index);
+            }
+            Snapshot::Enemy(_) => {
+                level.enemies.remove(index);
+            }
+            // The spawn point is always there
+            Snapshot::Spawn(_) => {}
+        }
+    }
+
+    pub fn write(&self, level: &mut Level, index: usize) {
+        match self {
+            Snapshot::Platform(def) => level.platforms[index] = def.clone(),
+            Snapshot::Collectible(def) => level.collectibles[index] = def.clone(),
+            Snapshot::Zipline(def) => level.ziplines[index] = def.clone(),
+            Snapshot::Pad(def) => level.pads[index] = def.clone(),
+            Snapshot::Zone(def) => level.zones[index] = def.clone(),
+            Snapshot::Enemy(def) => level.enemies[index] = def.clone(),
+            Snapshot::Spawn(spawn) => level.spawn = *spawn,
+        }
+    }
+}
+
+/// One edit to the editor's level data, which can be done and undone
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Place {
+        index: usize,
+        snapshot: Snapshot,
+    },
+    Delete {
+        index: usize,
+        snapshot: Snapshot,
+    },
+    /// Any change to an item that stays in place in its list: moving, resizing or
+    /// switching type, named by `action`
+    Edit {
+        index: usize,
+        action: &'static str,
+        before: Snapshot,
+        after: Snapshot,
+    },
+    /// Several commands done and undone as one, such as a paste or a group move
+    Group {
+        label: String,
+        commands: Vec<Command>,
+    },
+}
+
+impl Command {
+    pub fn apply(&self, level: &mut Level) {
+        match self {
+            Command::Place { index, snapshot } => snapshot.insert(level, *index),
+            Command::Delete { index, snapshot } => snapshot.remove(level, *index),
+            Command::Edit { index, after, .. } => after.write(level, *index),
+            Command::Group { commands, .. } => {
+                for command in commands {
+                    command.apply(level);
+                }
+            }
+        }
+    }
+
+    pub fn revert(&self, level: &mut Level) {
+        match self {
+            Command::Place { index, snapshot } => snapshot.remove(level, *index),
+            Command::Delete { index, snapshot } => snapshot.insert(level, *index),
+            Command::Edit { index, before, .. } => before.write(level, *index),
+            Command::Group { commands, .. } => {
+                for command in commands.iter().rev() {
+                    command.revert(level);
+                }
+            }
+        }
+    }
+
+    /// The items the command touched that exist once it has been applied, or reverted
+    /// when `undone`
+    pub fn items_after(&self, undone: bool) -> Vec<EditorItem> {
+        match self {
+            Command::Place { index, snapshot } if !undone => vec![snapshot.item(*index)],
+            Command::Delete { index, snapshot } if undone => vec![snapshot.item(*index)],
+            Command::Place { .. } | Command::Delete { .. } => Vec::new(),
+            Command::Edit { index, after, .. } => vec![after.item(*index)],
+            Command::Group { commands, .. } => commands
+                .iter()
+                .flat_map(|command| command.items_after(undone))
+                .collect(),
+        }
+    }
+
+    /// Short description such as "Move platform"
+    pub fn label(&self) -> String {
+        match self {
+            Command::Place { snapshot, .. } => format!("Place {}", snapshot.noun()),
+            Command::Delete { snapshot, .. } => format!("Delete {}", snapshot.noun()),
+            Command::Edit { action, before, .. } => format!("{} {}", action, before.noun()),
+            Command::Group { label, .. } => label.clone(),
+        }
+    }
+}
+
+/// Edits that can be undone and, until something new is done, redone
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Command>,
+    redo: Vec<Command>,
+    /// Goes up with every change to the level through the history
+    revision: u64,
+    /// Revision the level file on disk matches, or None when it matches none of them
+    saved: Option<u64>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            revision: 0,
+            saved: Some(0),
+        }
+    }
+
+    /// Remember an edit that has already been applied
+    pub fn push(&mut self, command: Command) {
+        if self.undo.len() >= GameConfig::EDITOR_UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(command);
+        self.redo.clear();
+        self.revision += 1;
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The level as it is now has been written to the level file
+    pub fn mark_saved(&mut self) {
+        self.saved = Some(self.revision);
+    }
+
+    /// The level differs from the level file however it is edited, such as after
+    /// recovering an autosave
+    pub fn mark_unsaved(&mut self) {
+        self.saved = None;
+    }
+
+    /// Whether there are edits the level file doesn't have
+    pub fn is_dirty(&self) -> bool {
+        self.saved != Some(self.revision)
+    }
+
+    /// Revert the latest edit, returning it
+    pub fn undo(&mut self, level: &mut Level) -> Option<Command> {
+        let command = self.undo.pop_back()?;
+        command.revert(level);
+        self.redo.push(command.clone());
+        self.revision += 1;
+        Some(command)
+    }
+
+    /// Apply the latest undone edit again, returning it
+    pub fn redo(&mut self, level: &mut Level) -> Option<Command> {
+        let command = self.redo.pop()?;
+        command.apply(level);
+        self.undo.push_back(command.clone());
+        self.revision += 1;
+        Some(command)
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,115 @@
+use crate::config::GameConfig;
+
+use super::EditorItem;
+
+/// A category of entities that can be hidden while editing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// Platforms, terrain, water, ziplines, bridges and ropes
+    Platforms,
+    Collectibles,
+    /// Pads, bombs, enemies, enemy spawn points and bosses
+    Hazards,
+    /// Triggers, trigger zones, switches and doors
+    Triggers,
+    /// NPCs and props
+    Decorations,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 5] = [
+        Layer::Platforms,
+        Layer::Collectibles,
+        Layer::Hazards,
+        Layer::Triggers,
+        Layer::Decorations,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Layer::Platforms => "Platforms",
+            Layer::Collectibles => "Collectibles",
+            Layer::Hazards => "Hazards",
+            Layer::Triggers => "Triggers",
+            Layer::Decorations => "Decorations",
+        }
+    }
+
+    /// The layer an editable item is on; the spawn point is on none and always shown
+    pub fn of(item: EditorItem) -> Option<Self> {
+        match item {
+            EditorItem::Platform(_) | EditorItem::Zipline(_) => Some(Layer::Platforms),
+            EditorItem::Collectible(_) => Some(Layer::Collectibles),
+            EditorItem::Pad(_) | EditorItem::Enemy(_) => Some(Layer::Hazards),
+            EditorItem::Zone(_) => Some(Layer::Triggers),
+            EditorItem::Spawn => None,
+        }
+    }
+
+    fn slot(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|layer| layer == self)
+            .unwrap_or(0)
+    }
+}
+
+/// How a layer's entities are drawn in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerView {
+    Shown,
+    /// Hidden, leaving a faint outline of each entity
+    Outlined,
+    Hidden,
+}
+
+/// Which layers the editor shows. Hidden layers can't be hovered or selected; none of this
+/// reaches play-tests or the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layers {
+    visible: [bool; Layer::ALL.len()],
+    /// Whether hidden layers still show outlines
+    pub outlines: bool,
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Self {
+            visible: [true; Layer::ALL.len()],
+            outlines: GameConfig::EDITOR_HIDDEN_OUTLINES,
+        }
+    }
+
+    pub fn is_visible(&self, layer: Layer) -> bool {
+        self.visible[layer.slot()]
+    }
+
+    pub fn toggle(&mut self, layer: Layer) {
+        self.visible[layer.slot()] = !self.is_visible(layer);
+    }
+
+    pub fn show(&mut self, layer: Layer) {
+        self.visible[layer.slot()] = true;
+    }
+
+    /// Whether an item can be seen, and so hovered and selected
+    pub fn shows(&self, item: EditorItem) -> bool {
+        Layer::of(item).is_none_or(|layer| self.is_visible(layer))
+    }
+
+    pub fn view(&self, layer: Layer) -> LayerView {
+        if self.is_visible(layer) {
+            LayerView::Shown
+        } else if self.outlines {
+            LayerView::Outlined
+        } else {
+            LayerView::Hidden
+        }
+    }
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,2578 @@
+pub mod autosave;
+pub mod history;
+pub mod layers;
+pub mod prefab;
+
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::collectible::CollectibleType;
+use crate::entities::enemy::{EnemyBehavior, EnemyKind};
+use crate::entities::pad::PadKind;
+use crate::entities::platform::PlatformType;
+use crate::entities::zone::{self, ZoneEffect};
+use crate::entities::{BossTuning, Cycle, Entity};
+use crate::graphics::{GraphicsUtils, TextStyle, Viewport};
+use crate::input::InputHandler;
+use crate::level::{
+    share, BombDef, BridgeDef, CollectibleDef, DoorDef, EnemyDef, Level, LevelIssue, NpcDef,
+    PadDef, PathDef, PlatformDef, PropDef, RopeDef, SwitchDef, TerrainDef, TriggerDef, WaterDef,
+    ZiplineDef, ZoneDef,
+};
+use crate::physics::jump_arc;
+
+use history::{Command, History, Snapshot};
+use layers::{Layer, LayerView, Layers};
+use prefab::{Prefab, PREFAB_PATH};
+
+/// What a left click in the editor places
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorTool {
+    Platform(PlatformType),
+    Collectible(CollectibleType),
+    Pad(PadKind),
+    Spawn,
+    /// A trigger zone, dragged out like a platform
+    Zone(ZoneEffect),
+    Enemy(EnemyKind),
+}
+
+/// Something in the level the editor can point at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorItem {
+    Platform(usize),
+    Collectible(usize),
+    Zipline(usize),
+    Pad(usize),
+    Zone(usize),
+    Enemy(usize),
+    Spawn,
+}
+
+impl EditorItem {
+    /// Position in its list; the spawn point has no list and is always 0
+    pub fn index(&self) -> usize {
+        match self {
+            EditorItem::Platform(index)
+            | EditorItem::Collectible(index)
+            | EditorItem::Zipline(index)
+            | EditorItem::Pad(index)
+            | EditorItem::Zone(index)
+            | EditorItem::Enemy(index) => *index,
+            EditorItem::Spawn => 0,
+        }
+    }
+}
+
+/// A grab point on the selected item that reshapes it when dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Handle {
+    /// A platform or zone corner or edge; -1 moves the left or top side, 1 the right or
+    /// bottom one and 0 leaves that axis alone
+    Resize {
+        x: i8,
+        y: i8,
+    },
+    ZiplineStart,
+    ZiplineEnd,
+    /// Where a teleport zone sends the player
+    TeleportTarget,
+    /// Where a scroll zone's camera stops, level with the zone
+    ScrollEnd,
+    /// One of a moving platform's waypoints after its start, by position in the path
+    Waypoint(usize),
+}
+
+/// Requests from the editor that the game has to carry out
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorAction {
+    None,
+    /// Play the level, from `spawn` instead of its spawn point when given
+    PlayTest {
+        spawn: Option<Vec2>,
+    },
+    Save,
+    Exit,
+}
+
+/// Level editor working on its own copy of the level data
+pub struct Editor {
+    pub level: Level,
+    /// World position shown at the top-left corner of the screen when not zoomed
+    pub camera: Vec2,
+    /// Magnification of the level around the screen center
+    pub zoom: f32,
+    /// Side of the grid cells placement and moves snap to
+    pub grid: f32,
+    pub tool: EditorTool,
+    /// Grid corner where the current platform drag started
+    pub drag_start: Option<Vec2>,
+    pub hovered: Option<EditorItem>,
+    /// Items picked with clicks or a rubber band; one on its own shows its handles and
+    /// properties
+    pub selected: Vec<EditorItem>,
+    /// Handle of the selected item being dragged with the mouse
+    pub dragging: Option<Handle>,
+    /// The selection as it was when a handle or move drag started, so the whole drag
+    /// undoes in one go
+    drag_before: Vec<Snapshot>,
+    /// World point where a drag moving the selection grabbed it
+    move_grab: Option<Vec2>,
+    /// Offset the current move drag has already applied
+    move_applied: Vec2,
+    /// World point where a rubber band selection started
+    band_start: Option<Vec2>,
+    /// Items copied with Ctrl+C, kept through play-tests
+    clipboard: Vec<Snapshot>,
+    /// Saved arrangements listed in the sidebar
+    pub prefabs: Vec<Prefab>,
+    /// Prefab picked in the sidebar, placed with every click until put away
+    pub stamp: Option<usize>,
+    /// Name being typed for a new prefab made from the selection
+    pub naming: Option<String>,
+    pub history: History,
+    /// Entity categories shown while editing
+    pub layers: Layers,
+    /// Level file being edited, which Ctrl+S writes and autosaves sit next to
+    pub path: PathBuf,
+    /// Unsaved work from an autosave newer than the level file, waiting on the player to
+    /// recover or discard it
+    pub recovery: Option<Level>,
+    /// Level code from Ctrl+E shown for copying by hand where there's no clipboard
+    pub share_code: Option<String>,
+    /// Whether the selected platform shows how far the player can jump from its edges
+    pub show_jump_arcs: bool,
+    autosaved_revision: u64,
+    /// Seconds until the next autosave
+    autosave_timer: f32,
+    /// Seconds since Ctrl+S last wrote the level file
+    pub since_saved: Option<f32>,
+    /// Seconds since the last autosave of work that is still unsaved
+    pub since_autosave: Option<f32>,
+    /// Problems `Level::validate` found, checked again after every edit
+    pub issues: Vec<LevelIssue>,
+    validated_revision: u64,
+    pub status: Option<(String, f32)>,
+}
+
+const TOOL_KEYS: [KeyCode; 10] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Key0,
+];
+
+/// Toolbar buttons: one per number key, then the zone and enemy tools
+const TOOL_SLOTS: usize = TOOL_KEYS.len() + 2;
+const TOOLBAR_COLUMNS: usize = 6;
+const TOOL_BUTTON_SIZE: (f32, f32) = (100.0, 28.0);
+/// Rows of the level problems list; any more are summed up in a last row
+const ISSUE_ROWS: usize = 6;
+const ISSUE_ROW_SIZE: (f32, f32) = (560.0, 20.0);
+const PREFAB_ROW_SIZE: (f32, f32) = (200.0, 22.0);
+/// Top of the prefab sidebar, clear of the properties panel
+const PREFAB_LIST_TOP: f32 = 220.0;
+const STATUS_BAR_HEIGHT: f32 = 24.0;
+const LAYER_ROW_SIZE: (f32, f32) = (170.0, 20.0);
+/// Grid sizes [ and ] step through
+const GRID_SIZES: [f32; 4] = [8.0, 16.0, 32.0, 64.0];
+
+impl EditorTool {
+    /// The tool bound to each number key, in toolbar order, then the zone and enemy tools
+    fn for_slot(slot: usize) -> Option<Self> {
+        match slot {
+            0 => Some(EditorTool::Platform(PlatformType::Normal)),
+            1 => Some(EditorTool::Platform(PlatformType::Ground)),
+            2 => Some(EditorTool::Platform(PlatformType::Breakable)),
+            3 => Some(EditorTool::Platform(PlatformType::Moving)),
+            4 => Some(EditorTool::Collectible(CollectibleType::Coin)),
+            5 => Some(EditorTool::Collectible(CollectibleType::Gem)),
+            6 => Some(EditorTool::Collectible(CollectibleType::PowerUp)),
+            7 => Some(EditorTool::Collectible(CollectibleType::Lantern)),
+            8 => Some(EditorTool::Pad(PadKind::Spring)),
+            9 => Some(EditorTool::Spawn),
+            10 => Some(EditorTool::Zone(ZoneEffect::ShowTutorial(
+                "Hint".to_string(),
+            ))),
+            11 => Some(EditorTool::Enemy(EnemyKind::Walker)),
+            _ => None,
+        }
+    }
+
+    fn slot(&self) -> usize {
+        match self {
+            EditorTool::Platform(PlatformType::Normal) => 0,
+            EditorTool::Platform(PlatformType::Ground) => 1,
+            EditorTool::Platform(PlatformType::Breakable) => 2,
+            EditorTool::Platform(PlatformType::Moving) => 3,
+            EditorTool::Collectible(CollectibleType::Coin) => 4,
+            EditorTool::Collectible(CollectibleType::Gem) => 5,
+            EditorTool::Collectible(CollectibleType::PowerUp) => 6,
+            EditorTool::Collectible(CollectibleType::Lantern) => 7,
+            EditorTool::Pad(_) => 8,
+            EditorTool::Spawn => 9,
+            EditorTool::Zone(_) => 10,
+            EditorTool::Enemy(_) => 11,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EditorTool::Platform(PlatformType::Normal) => "Platform",
+            EditorTool::Platform(PlatformType::Ground) => "Ground",
+            EditorTool::Platform(PlatformType::Breakable) => "Breakable",
+            EditorTool::Platform(PlatformType::Moving) => "Moving",
+            EditorTool::Collectible(CollectibleType::Coin) => "Coin",
+            EditorTool::Collectible(CollectibleType::Gem) => "Gem",
+            EditorTool::Collectible(CollectibleType::PowerUp) => "Power-up",
+            EditorTool::Collectible(CollectibleType::Lantern) => "Lantern",
+            EditorTool::Pad(PadKind::Spring) => "Spring",
+            EditorTool::Pad(PadKind::Boost { direction }) if *direction < 0.0 => "Boost <",
+            EditorTool::Pad(PadKind::Boost { .. }) => "Boost >",
+            EditorTool::Pad(PadKind::Spikes) => "Spikes",
+            EditorTool::Spawn => "Spawn",
+            EditorTool::Zone(effect) => match effect {
+                ZoneEffect::ShowTutorial(_) => "Hint zone",
+                ZoneEffect::StartBoulder => "Boulder zone",
+                ZoneEffect::SetCheckpoint => "Checkpoint",
+                ZoneEffect::ChangeMusic(_) => "Music zone",
+                ZoneEffect::SetWeather(_) => "Weather zone",
+                ZoneEffect::CameraLock { .. } => "Camera zone",
+                ZoneEffect::GiveScore(_) => "Score zone",
+                ZoneEffect::Teleport(_) => "Teleport",
+                ZoneEffect::AutoScroll { .. } => "Scroll zone",
+            },
+            EditorTool::Enemy(EnemyKind::Walker) => "Enemy",
+            EditorTool::Enemy(EnemyKind::Shelled) => "Shelled enemy",
+        }
+    }
+
+    /// Pressing a tool's key again cycles through its variants
+    fn cycled(&self) -> Self {
+        match self {
+            EditorTool::Pad(pad_kind) => EditorTool::Pad(next_pad_kind(pad_kind)),
+            // The settings are filled in when the zone is placed
+            EditorTool::Zone(effect) => EditorTool::Zone(effect.next(Vec2::ZERO, Vec2::ZERO)),
+            EditorTool::Enemy(kind) => EditorTool::Enemy(kind.next()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Spring, right boost, left boost, spikes and round again
+fn next_pad_kind(pad_kind: &PadKind) -> PadKind {
+    match pad_kind {
+        PadKind::Spring => PadKind::Boost { direction: 1.0 },
+        PadKind::Boost { direction } if *direction > 0.0 => PadKind::Boost { direction: -1.0 },
+        PadKind::Boost { .. } => PadKind::Spikes,
+        PadKind::Spikes => PadKind::Spring,
+    }
+}
+
+/// Properties panel line for something timed against the level clock
+fn cycle_line(cycle: Cycle) -> String {
+    format!(
+        "timed: every {} beats from beat {}",
+        cycle.period, cycle.phase
+    )
+}
+
+/// Nearest crossing of `grid` lines to a world position, where resized edges land
+fn snap_to_lines(position: Vec2, grid: f32) -> Vec2 {
+    (position / grid).round() * grid
+}
+
+impl Editor {
+    pub fn new(level: Level, camera: Vec2, path: PathBuf) -> Self {
+        let recovery = autosave::recoverable(&path);
+        let issues = level.validate();
+        let (prefabs, prefab_error) = match prefab::load_prefabs(PREFAB_PATH) {
+            Ok(prefabs) => (prefabs, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+        let mut editor = Self {
+            level,
+            camera,
+            zoom: 1.0,
+            grid: GameConfig::GRID_SIZE,
+            tool: EditorTool::Platform(PlatformType::Normal),
+            drag_start: None,
+            hovered: None,
+            selected: Vec::new(),
+            dragging: None,
+            drag_before: Vec::new(),
+            move_grab: None,
+            move_applied: Vec2::ZERO,
+            band_start: None,
+            clipboard: Vec::new(),
+            prefabs,
+            stamp: None,
+            naming: None,
+            history: History::new(),
+            layers: Layers::new(),
+            path,
+            recovery,
+            share_code: None,
+            show_jump_arcs: true,
+            autosaved_revision: 0,
+            autosave_timer: GameConfig::EDITOR_AUTOSAVE_INTERVAL,
+            since_saved: None,
+            since_autosave: None,
+            issues,
+            validated_revision: 0,
+            status: None,
+        };
+        if let Some(err) = prefab_error {
+            editor.set_status(format!("Prefabs not loaded: {}", err));
+        }
+        editor
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        self.status = Some((message, GameConfig::EDITOR_STATUS_TIME));
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some((_, remaining)) = &mut self.status {
+            *remaining -= delta_time;
+            if *remaining <= 0.0 {
+                self.status = None;
+            }
+        }
+        for since in [&mut self.since_saved, &mut self.since_autosave]
+            .into_iter()
+            .flatten()
+        {
+            *since += delta_time;
+        }
+
+        self.autosave_timer -= delta_time;
+        if self.autosave_timer <= 0.0 {
+            self.autosave_timer = GameConfig::EDITOR_AUTOSAVE_INTERVAL;
+            self.autosave();
+        }
+    }
+
+    /// Write the level to its autosave file, unless nothing has changed since the level
+    /// was last saved or autosaved
+    pub fn autosave(&mut self) {
+        let revision = self.history.revision();
+        if !self.history.is_dirty() || revision == self.autosaved_revision {
+            return;
+        }
+        match autosave::write(&self.path, &self.level) {
+            Ok(()) => {
+                self.autosaved_revision = revision;
+                self.since_autosave = Some(0.0);
+            }
+            Err(err) => self.set_status(format!("Autosave failed: {}", err)),
+        }
+    }
+
+    /// The level file now holds the editor's level, so any autosave is out of date
+    pub fn mark_saved(&mut self) {
+        self.history.mark_saved();
+        self.autosaved_revision = self.history.revision();
+        self.since_saved = Some(0.0);
+        self.since_autosave = None;
+        if let Err(err) = autosave::discard(&self.path) {
+            self.set_status(format!("Couldn't remove the old autosave: {}", err));
+        }
+    }
+
+    /// R takes the autosaved level in place of the level file's, D throws it away
+    fn answer_recovery(&mut self, input: &InputHandler) {
+        if input.is_key_pressed(KeyCode::R) {
+            let Some(level) = self.recovery.take() else {
+                return;
+            };
+            self.level = level;
+            self.selected.clear();
+            self.hovered = None;
+            // Undoing past the recovery would need the level file's version, so start afresh
+            self.history = History::new();
+            self.history.mark_unsaved();
+            self.autosaved_revision = self.history.revision();
+            self.revalidate();
+            self.set_status("Recovered the autosave; Ctrl+S to keep it".to_string());
+        } else if input.is_key_pressed(KeyCode::D) || input.is_key_pressed(KeyCode::Escape) {
+            self.recovery = None;
+            match autosave::discard(&self.path) {
+                Ok(()) => self.set_status("Discarded the autosave".to_string()),
+                Err(err) => self.set_status(format!("Couldn't remove the autosave: {}", err)),
+            }
+        }
+    }
+
+    pub fn mouse_world_position(&self) -> Vec2 {
+        self.screen_to_world(Viewport::mouse_position())
+    }
+
+    /// The world point shown at a virtual screen position, through the pan and the zoom
+    /// around the screen center
+    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        let center = GameConfig::screen_center();
+        (screen - center) / self.zoom + center + self.camera
+    }
+
+    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        let center = GameConfig::screen_center();
+        (world - self.camera - center) * self.zoom + center
+    }
+
+    /// Zoom one step in (positive `steps`) or out, keeping the world point under `screen`
+    /// where it is
+    fn zoom_at(&mut self, screen: Vec2, steps: f32) {
+        let anchor = self.screen_to_world(screen);
+        self.zoom = (self.zoom * GameConfig::EDITOR_ZOOM_STEP.powf(steps))
+            .clamp(GameConfig::EDITOR_MIN_ZOOM, GameConfig::EDITOR_MAX_ZOOM);
+        self.camera += anchor - self.screen_to_world(screen);
+    }
+
+    /// Move to the next smaller (negative `direction`) or larger grid size
+    fn step_grid(&mut self, direction: i32) {
+        let current = GRID_SIZES
+            .iter()
+            .position(|&size| size == self.grid)
+            .unwrap_or(0) as i32;
+        let next = (current + direction).clamp(0, GRID_SIZES.len() as i32 - 1);
+        self.grid = GRID_SIZES[next as usize];
+        self.set_status(format!("Grid {}", self.grid));
+    }
+
+    /// Top-left corner of the grid cell containing a world position
+    pub fn snap(&self, position: Vec2) -> Vec2 {
+        (position / self.grid).floor() * self.grid
+    }
+
+    pub fn handle_input(&mut self, input: &InputHandler) -> EditorAction {
+        let ctrl =
+            input.is_key_down(KeyCode::LeftControl) || input.is_key_down(KeyCode::RightControl);
+
+        if self.recovery.is_some() {
+            self.answer_recovery(input);
+            return EditorAction::None;
+        }
+        if self.naming.is_some() {
+            self.name_prefab(input);
+            return EditorAction::None;
+        }
+        if self.share_code.is_some() {
+            if input.is_key_pressed(KeyCode::Escape) || input.is_key_pressed(KeyCode::Enter) {
+                self.share_code = None;
+            }
+            return EditorAction::None;
+        }
+
+        if input.is_key_pressed(KeyCode::F10) {
+            return EditorAction::Exit;
+        }
+        if self.history.revision() != self.validated_revision {
+            self.revalidate();
+        }
+
+        let shift = input.is_key_down(KeyCode::LeftShift) || input.is_key_down(KeyCode::RightShift);
+        if input.is_key_pressed(KeyCode::Enter) {
+            self.drag_start = None;
+            self.revalidate();
+            // Warnings are fine to play through, errors aren't
+            if let Some(error) = self.issues.iter().find(|issue| issue.is_error()) {
+                self.set_status(format!("Can't play-test: {}", error.message));
+                return EditorAction::None;
+            }
+            // Shift+Enter starts on the ground under the cursor
+            let spawn = if shift {
+                match self.level.safe_spot_below(self.mouse_world_position()) {
+                    Some(spot) => Some(spot),
+                    None => {
+                        self.set_status("Nothing to stand on below the cursor".to_string());
+                        return EditorAction::None;
+                    }
+                }
+            } else {
+                None
+            };
+            self.autosave();
+            return EditorAction::PlayTest { spawn };
+        }
+        if ctrl && input.is_key_pressed(KeyCode::S) {
+            self.revalidate();
+            return EditorAction::Save;
+        }
+        let mouse_busy = self.dragging.is_some()
+            || self.drag_start.is_some()
+            || self.move_grab.is_some()
+            || self.band_start.is_some();
+        if ctrl && !mouse_busy {
+            if input.is_key_pressed(KeyCode::Z) && !shift {
+                self.undo();
+            } else if input.is_key_pressed(KeyCode::Y)
+                || (input.is_key_pressed(KeyCode::Z) && shift)
+            {
+                self.redo();
+            } else if input.is_key_pressed(KeyCode::C) {
+                self.copy_selection();
+            } else if input.is_key_pressed(KeyCode::V) {
+                self.paste(self.mouse_world_position());
+            } else if input.is_key_pressed(KeyCode::E) {
+                self.copy_level_code();
+            } else if input.is_key_pressed(KeyCode::G) {
+                if self.selected.iter().any(|&item| item != EditorItem::Spawn) {
+                    self.naming = Some(String::new());
+                } else {
+                    self.set_status("Select something to save as a prefab".to_string());
+                }
+            }
+        }
+
+        // Alt with a number key toggles a layer instead of picking a tool, 6 the outlines
+        let alt = input.is_key_down(KeyCode::LeftAlt) || input.is_key_down(KeyCode::RightAlt);
+        for (slot, key) in TOOL_KEYS.iter().enumerate() {
+            if input.is_key_pressed(*key) {
+                if alt {
+                    self.toggle_layer_row(slot);
+                } else {
+                    self.select_slot(slot);
+                }
+            }
+        }
+        if !mouse_busy {
+            if input.is_key_pressed(KeyCode::LeftBracket) {
+                self.step_grid(-1);
+            } else if input.is_key_pressed(KeyCode::RightBracket) {
+                self.step_grid(1);
+            }
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            self.zoom_at(Viewport::mouse_position(), wheel.signum());
+        }
+
+        if self.stamp.is_some()
+            && (input.is_key_pressed(KeyCode::Escape)
+                || is_mouse_button_pressed(MouseButton::Right))
+        {
+            self.stamp = None;
+            return EditorAction::None;
+        }
+        if !self.selected.is_empty() && !mouse_busy {
+            if input.is_key_pressed(KeyCode::Escape) {
+                self.selected.clear();
+            } else if input.is_key_pressed(KeyCode::Tab) {
+                match self.single_selection() {
+                    Some(item) => self.cycle_type(item),
+                    None => self.set_status("Select one item to change its type".to_string()),
+                }
+            } else if input.is_key_pressed(KeyCode::R) {
+                match self.single_selection() {
+                    Some(EditorItem::Zone(index)) => self.toggle_zone_repeat(index),
+                    Some(EditorItem::Platform(index)) => {
+                        self.edit_path(index, |path| path.mode = path.mode.next())
+                    }
+                    _ => self.set_status("Select one zone to make it repeat".to_string()),
+                }
+            } else if input.is_key_pressed(KeyCode::P) {
+                match self.single_selection() {
+                    Some(EditorItem::Platform(index)) => {
+                        self.add_waypoint(index, self.mouse_world_position())
+                    }
+                    _ => {
+                        self.set_status("Select one moving platform to add a waypoint".to_string())
+                    }
+                }
+            } else if input.is_key_pressed(KeyCode::K) {
+                match self.single_selection() {
+                    Some(EditorItem::Platform(index)) => {
+                        self.edit_path(index, |path| path.smooth = !path.smooth)
+                    }
+                    _ => {
+                        self.set_status("Select one moving platform to smooth its path".to_string())
+                    }
+                }
+            } else if input.is_key_pressed(KeyCode::J) {
+                self.show_jump_arcs = !self.show_jump_arcs;
+                let state = if self.show_jump_arcs { "on" } else { "off" };
+                self.set_status(format!("Jump arcs {}", state));
+            } else {
+                self.nudge_with_arrows(input);
+            }
+        }
+
+        // Free camera panning, left alone while the mouse is moving things. The arrow keys
+        // nudge the selection instead when there is one.
+        if !ctrl && self.dragging.is_none() && self.move_grab.is_none() {
+            let pan = if !self.selected.is_empty() {
+                let axis = |negative, positive| {
+                    input.is_key_down(positive) as i32 as f32
+                        - input.is_key_down(negative) as i32 as f32
+                };
+                Vec2::new(axis(KeyCode::A, KeyCode::D), axis(KeyCode::W, KeyCode::S))
+            } else {
+                Vec2::new(input.get_horizontal_input(), input.get_vertical_input())
+            };
+            // The same speed on screen at any zoom
+            self.camera += pan * GameConfig::EDITOR_PAN_SPEED * get_frame_time() / self.zoom;
+        }
+
+        let mouse_world = self.mouse_world_position();
+        let mouse_down = is_mouse_button_down(MouseButton::Left);
+
+        if let Some(handle) = self.dragging {
+            if mouse_down {
+                self.drag_handle(handle, mouse_world);
+            } else {
+                self.finish_drag(handle);
+            }
+            return EditorAction::None;
+        }
+        if let Some(grab) = self.move_grab {
+            if mouse_down {
+                self.drag_selection(grab, mouse_world);
+            } else {
+                self.finish_move();
+            }
+            return EditorAction::None;
+        }
+        if let Some(start) = self.band_start {
+            if !mouse_down {
+                self.band_start = None;
+                self.select_in_band(start, mouse_world);
+            }
+            return EditorAction::None;
+        }
+
+        self.hovered = self.item_at(mouse_world);
+
+        if let Some(slot) = Self::toolbar_slot_at(Viewport::mouse_position()) {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.select_slot(slot);
+            }
+            return EditorAction::None;
+        }
+        if let Some(row) = self.issue_row_at(Viewport::mouse_position()) {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.show_issue(row);
+            }
+            return EditorAction::None;
+        }
+        if let Some(row) = Self::layer_row_at(Viewport::mouse_position()) {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.toggle_layer_row(row);
+            }
+            return EditorAction::None;
+        }
+        if let Some(row) = self.prefab_row_at(Viewport::mouse_position()) {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.stamp = if self.stamp == Some(row) {
+                    None
+                } else {
+                    Some(row)
+                };
+            } else if is_mouse_button_pressed(MouseButton::Right) {
+                self.delete_prefab(row);
+            }
+            return EditorAction::None;
+        }
+        if let Some(index) = self.stamp {
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.stamp_prefab(index, mouse_world);
+            }
+            return EditorAction::None;
+        }
+
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(handle) = self.handle_at(Viewport::mouse_position()) {
+                self.dragging = Some(handle);
+                self.drag_before = self.selection_snapshots();
+                return EditorAction::None;
+            }
+            match self.hovered {
+                Some(item) if shift => {
+                    if let Some(position) = self.selected.iter().position(|&s| s == item) {
+                        self.selected.remove(position);
+                    } else {
+                        self.selected.push(item);
+                    }
+                }
+                // Clicking an item selects it and grabs the selection to move it
+                Some(item) => {
+                    if !self.selected.contains(&item) {
+                        self.selected = vec![item];
+                    }
+                    self.move_grab = Some(mouse_world);
+                    self.move_applied = Vec2::ZERO;
+                    self.drag_before = self.selection_snapshots();
+                }
+                None if shift => self.band_start = Some(mouse_world),
+                // Clicking empty space places with the tool
+                None => {
+                    self.selected.clear();
+                    match self.tool {
+                        EditorTool::Platform(_) | EditorTool::Zone(_) => {
+                            self.drag_start = Some(self.snap(mouse_world))
+                        }
+                        _ => self.place(mouse_world),
+                    }
+                }
+            }
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            if let Some(start) = self.drag_start.take() {
+                self.place_area(start, mouse_world);
+            }
+        }
+
+        if is_mouse_button_pressed(MouseButton::Right) {
+            if let (Some(Handle::Waypoint(waypoint)), Some(EditorItem::Platform(index))) = (
+                self.handle_at(Viewport::mouse_position()),
+                self.single_selection(),
+            ) {
+                self.remove_waypoint(index, waypoint);
+            } else if let Some(item) = self.hovered {
+                self.remove(item);
+                self.hovered = None;
+            }
+        } else if input.is_key_pressed(KeyCode::Delete) {
+            if !self.selected.is_empty() {
+                self.delete_selection();
+            } else if let Some(item) = self.hovered {
+                self.remove(item);
+            }
+            self.hovered = None;
+        }
+
+        EditorAction::None
+    }
+
+    /// Check the level again for problems
+    pub fn revalidate(&mut self) {
+        self.issues = self.level.validate();
+        self.validated_revision = self.history.revision();
+    }
+
+    /// Center the view on the `row`th problem in the list
+    fn show_issue(&mut self, row: usize) {
+        if let Some(issue) = self.issues.get(row) {
+            let screen = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT);
+            self.camera = issue.position - screen / 2.0;
+        }
+    }
+
+    fn issue_row_rect(row: usize) -> Rect {
+        let (width, height) = ISSUE_ROW_SIZE;
+        let toolbar = Self::toolbar_button_rect(TOOL_SLOTS - 1);
+        Rect::new(
+            GameConfig::UI_MARGIN,
+            toolbar.y + toolbar.h + 8.0 + row as f32 * height,
+            width,
+            height,
+        )
+    }
+
+    /// The listed problem under a screen position
+    fn issue_row_at(&self, point: Vec2) -> Option<usize> {
+        (0..self.issues.len().min(ISSUE_ROWS))
+            .find(|&row| Self::issue_row_rect(row).contains(point))
+    }
+
+    /// The selected item, when exactly one is selected
+    pub fn single_selection(&self) -> Option<EditorItem> {
+        match self.selected.as_slice() {
+            [item] => Some(*item),
+            _ => None,
+        }
+    }
+
+    fn selection_snapshots(&self) -> Vec<Snapshot> {
+        self.selected
+            .iter()
+            .map(|&item| Snapshot::take(&self.level, item))
+            .collect()
+    }
+
+    fn undo(&mut self) {
+        match self.history.undo(&mut self.level) {
+            Some(command) => {
+                self.selected = command.items_after(true);
+                self.hovered = None;
+                self.set_status(format!("Undid: {}", command.label()));
+            }
+            None => self.set_status("Nothing to undo".to_string()),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.history.redo(&mut self.level) {
+            Some(command) => {
+                self.selected = command.items_after(false);
+                self.hovered = None;
+                self.set_status(format!("Redid: {}", command.label()));
+            }
+            None => self.set_status("Nothing to redo".to_string()),
+        }
+    }
+
+    /// Record a change to `item` made since `before` was taken, if anything changed
+    fn record_edit(&mut self, item: EditorItem, action: &'static str, before: Snapshot) {
+        self.record_edits(&[item], action, vec![before]);
+    }
+
+    /// Record the changes to several items as one undo step
+    fn record_edits(&mut self, items: &[EditorItem], action: &'static str, before: Vec<Snapshot>) {
+        let commands = items
+            .iter()
+            .zip(before)
+            .filter_map(|(&item, before)| {
+                let after = Snapshot::take(&self.level, item);
+                (after != before).then_some(Command::Edit {
+                    index: item.index(),
+                    action,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        self.record_group(action, commands);
+    }
+
+    /// Record commands that have been applied as one undo step, named like "Move 3 items"
+    fn record_group(&mut self, verb: &str, mut commands: Vec<Command>) {
+        match commands.len() {
+            0 => {}
+            1 => self.history.push(commands.remove(0)),
+            count => self.history.push(Command::Group {
+                label: format!("{} {} items", verb, count),
+                commands,
+            }),
+        }
+    }
+
+    /// Record the item just pushed onto the end of its list
+    fn record_place(&mut self, item: EditorItem) {
+        self.reveal(item);
+        self.history.push(Command::Place {
+            index: item.index(),
+            snapshot: Snapshot::take(&self.level, item),
+        });
+    }
+
+    /// The whole drag becomes a single undo step when the mouse is released
+    fn finish_drag(&mut self, handle: Handle) {
+        self.dragging = None;
+        let before = std::mem::take(&mut self.drag_before);
+        let action = match handle {
+            Handle::Resize { .. } => "Resize",
+            Handle::ZiplineStart | Handle::ZiplineEnd => "Move end of",
+            Handle::TeleportTarget => "Move target of",
+            Handle::ScrollEnd => "Move scroll end of",
+            Handle::Waypoint(_) => "Move waypoint of",
+        };
+        let items = self.selected.clone();
+        self.record_edits(&items, action, before);
+    }
+
+    /// Move the grabbed selection with the mouse in whole grid cells
+    fn drag_selection(&mut self, grab: Vec2, mouse_world: Vec2) {
+        let offset = snap_to_lines(mouse_world - grab, self.grid);
+        let step = offset - self.move_applied;
+        if step != Vec2::ZERO {
+            for item in self.selected.clone() {
+                self.shift(item, step);
+            }
+            self.move_applied = offset;
+        }
+    }
+
+    /// A moved selection is one undo step, recorded when the mouse is released
+    fn finish_move(&mut self) {
+        self.move_grab = None;
+        let before = std::mem::take(&mut self.drag_before);
+        let items = self.selected.clone();
+        self.record_edits(&items, "Move", before);
+    }
+
+    /// Add every item touching the rubber band to the selection
+    fn select_in_band(&mut self, start: Vec2, end: Vec2) {
+        let (min, max) = (start.min(end), start.max(end));
+        for item in self.all_items() {
+            if !self.layers.shows(item) {
+                continue;
+            }
+            let (x1, y1, x2, y2) = self.item_bounds(item);
+            let touches = x1 <= max.x && x2 >= min.x && y1 <= max.y && y2 >= min.y;
+            if touches && !self.selected.contains(&item) {
+                self.selected.push(item);
+            }
+        }
+    }
+
+    /// Every item in the level the editor can select
+    fn all_items(&self) -> Vec<EditorItem> {
+        let level = &self.level;
+        (0..level.platforms.len())
+            .map(EditorItem::Platform)
+            .chain((0..level.collectibles.len()).map(EditorItem::Collectible))
+            .chain((0..level.ziplines.len()).map(EditorItem::Zipline))
+            .chain((0..level.pads.len()).map(EditorItem::Pad))
+            .chain((0..level.zones.len()).map(EditorItem::Zone))
+            .chain((0..level.enemies.len()).map(EditorItem::Enemy))
+            .chain(std::iter::once(EditorItem::Spawn))
+            .collect()
+    }
+
+    /// Put copies of the selection on the clipboard; the spawn point can't be copied
+    fn copy_selection(&mut self) {
+        self.clipboard = self
+            .selection_snapshots()
+            .into_iter()
+            .filter(|snapshot| !matches!(snapshot, Snapshot::Spawn(_)))
+            .collect();
+        match self.clipboard.len() {
+            0 => self.set_status("Nothing to copy".to_string()),
+            1 => self.set_status(format!("Copied {}", self.clipboard[0].noun())),
+            count => self.set_status(format!("Copied {} items", count)),
+        }
+    }
+
+    /// Add the clipboard to the level with its top-left item in the cell under the
+    /// cursor, giving each copy a new id, and select the copies
+    fn paste(&mut self, mouse_world: Vec2) {
+        if self.clipboard.is_empty() {
+            self.set_status("Nothing to paste".to_string());
+            return;
+        }
+        let count = self.place_copies(self.clipboard.clone(), mouse_world, "Paste");
+        self.set_status(format!("Pasted {} items", count));
+    }
+
+    /// Place a prefab from the sidebar at the cursor
+    fn stamp_prefab(&mut self, index: usize, mouse_world: Vec2) {
+        let Some(prefab) = self.prefabs.get(index) else {
+            self.stamp = None;
+            return;
+        };
+        let name = prefab.name.clone();
+        self.place_copies(prefab.items.clone(), mouse_world, "Stamp");
+        self.set_status(format!("Stamped '{}'", name));
+    }
+
+    /// Add `copies` with their top-left item in the grid cell under the cursor, giving
+    /// each a new id, as one undo step; selects and returns how many were placed
+    fn place_copies(&mut self, copies: Vec<Snapshot>, mouse_world: Vec2, verb: &str) -> usize {
+        let anchor = copies
+            .iter()
+            .map(Snapshot::origin)
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let offset = self.snap(mouse_world) - self.snap(anchor);
+
+        let mut commands = Vec::with_capacity(copies.len());
+        for copied in copies {
+            let mut snapshot = copied.shifted(offset);
+            snapshot.set_id(self.level.fresh_id(snapshot.id_prefix()));
+            let command = Command::Place {
+                index: snapshot.list_len(&self.level),
+                snapshot,
+            };
+            command.apply(&mut self.level);
+            for item in command.items_after(false) {
+                self.reveal(item);
+            }
+            commands.push(command);
+        }
+        self.selected = commands
+            .iter()
+            .flat_map(|command| command.items_after(false))
+            .collect();
+        let count = commands.len();
+        self.record_group(verb, commands);
+        count
+    }
+
+    /// Put the level on the clipboard as a code that can be imported from the mode select
+    /// screen. The browser build can't write the clipboard, so the code is shown instead.
+    fn copy_level_code(&mut self) {
+        self.revalidate();
+        if let Some(error) = self.issues.iter().find(|issue| issue.is_error()) {
+            self.set_status(format!("Can't share: {}", error.message));
+            return;
+        }
+        let code = share::export_code(&self.level);
+        let size = if code.len() > share::SHARE_CODE_WARN_LENGTH {
+            format!(
+                "{} KB, which may be too long to paste in some places",
+                code.len() / 1024
+            )
+        } else {
+            format!("{} characters", code.len())
+        };
+        if cfg!(target_arch = "wasm32") {
+            self.set_status(format!("Level code ready ({})", size));
+            self.share_code = Some(code);
+        } else {
+            miniquad::window::clipboard_set(&code);
+            self.set_status(format!("Copied the level code ({})", size));
+        }
+    }
+
+    /// A text field has the keyboard, so letter keys are being typed rather than pressed
+    pub fn is_typing(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    /// Type the name for a new prefab: Enter saves the selection under it, Escape gives up
+    fn name_prefab(&mut self, input: &InputHandler) {
+        let Some(name) = &mut self.naming else {
+            return;
+        };
+        name.push_str(input.typed());
+        if input.is_key_pressed(KeyCode::Backspace) {
+            name.pop();
+        }
+        if input.is_key_pressed(KeyCode::Escape) {
+            self.naming = None;
+        } else if input.is_key_pressed(KeyCode::Enter) {
+            let name = self.naming.take().unwrap_or_default().trim().to_string();
+            if name.is_empty() {
+                self.set_status("A prefab needs a name".to_string());
+                return;
+            }
+            let prefab = Prefab::new(name.clone(), self.selection_snapshots());
+            // Saving under an existing name replaces that prefab
+            match self.prefabs.iter().position(|other| other.name == name) {
+                Some(index) => self.prefabs[index] = prefab,
+                None => self.prefabs.push(prefab),
+            }
+            self.save_prefabs(format!("Saved prefab '{}'", name));
+        }
+    }
+
+    fn delete_prefab(&mut self, index: usize) {
+        let prefab = self.prefabs.remove(index);
+        self.stamp = None;
+        self.save_prefabs(format!("Deleted prefab '{}'", prefab.name));
+    }
+
+    /// Write the prefabs file, reporting `done` or what went wrong
+    fn save_prefabs(&mut self, done: String) {
+        match prefab::save_prefabs(PREFAB_PATH, &self.prefabs) {
+            Ok(()) => self.set_status(done),
+            Err(err) => self.set_status(format!("Couldn't save prefabs: {}", err)),
+        }
+    }
+
+    fn prefab_row_rect(row: usize) -> Rect {
+        let (width, height) = PREFAB_ROW_SIZE;
+        Rect::new(
+            GameConfig::VIRTUAL_WIDTH - GameConfig::UI_MARGIN - width,
+            PREFAB_LIST_TOP + height + row as f32 * (height + 2.0),
+            width,
+            height,
+        )
+    }
+
+    /// The sidebar prefab under a screen position
+    fn prefab_row_at(&self, point: Vec2) -> Option<usize> {
+        (0..self.prefabs.len()).find(|&row| Self::prefab_row_rect(row).contains(point))
+    }
+
+    /// Remove everything selected as one undo step
+    fn delete_selection(&mut self) {
+        let mut items: Vec<EditorItem> = self
+            .selected
+            .drain(..)
+            .filter(|&item| item != EditorItem::Spawn)
+            .collect();
+        // Highest indices first, so removing one doesn't shift the rest
+        items.sort_by_key(|item| std::cmp::Reverse(item.index()));
+
+        let mut commands = Vec::with_capacity(items.len());
+        for item in items {
+            let command = Command::Delete {
+                index: item.index(),
+                snapshot: Snapshot::take(&self.level, item),
+            };
+            command.apply(&mut self.level);
+            commands.push(command);
+        }
+        self.record_group("Delete", commands);
+    }
+
+    fn select_slot(&mut self, slot: usize) {
+        if self.tool.slot() == slot {
+            self.tool = self.tool.cycled();
+        } else if let Some(tool) = EditorTool::for_slot(slot) {
+            self.tool = tool;
+        }
+        self.drag_start = None;
+    }
+
+    /// Place a single-click entity in the grid cell under the cursor
+    fn place(&mut self, mouse_world: Vec2) {
+        let cell = self.snap(mouse_world);
+        let grid = self.grid;
+
+        match self.tool.clone() {
+            EditorTool::Collectible(collectible_type) => {
+                let size = Vec2::from(GameConfig::COLLECTIBLE_SIZE);
+                let id = self.level.fresh_id(collectible_type.name());
+                self.level.collectibles.push(CollectibleDef {
+                    id,
+                    collectible_type,
+                    position: cell + (Vec2::splat(grid) - size) / 2.0,
+                });
+                self.record_place(EditorItem::Collectible(self.level.collectibles.len() - 1));
+            }
+            EditorTool::Pad(pad_kind) => {
+                let id = self.level.fresh_id(match pad_kind {
+                    PadKind::Spring => "spring",
+                    PadKind::Boost { .. } => "boost",
+                    PadKind::Spikes => "spikes",
+                });
+                // Pads sit on the bottom edge of the cell
+                self.level.pads.push(PadDef {
+                    id,
+                    pad_kind,
+                    x: cell.x,
+                    surface_y: cell.y + grid,
+                    cycle: None,
+                });
+                self.record_place(EditorItem::Pad(self.level.pads.len() - 1));
+            }
+            EditorTool::Enemy(kind) => {
+                let id = self.level.fresh_id(kind.name());
+                // Enemies stand on the bottom edge of the cell, centered in it
+                self.level.enemies.push(EnemyDef {
+                    id,
+                    kind,
+                    x: cell.x + (grid - GameConfig::ENEMY_SIZE.0) / 2.0,
+                    surface_y: cell.y + grid,
+                    behavior: EnemyBehavior::default(),
+                });
+                self.record_place(EditorItem::Enemy(self.level.enemies.len() - 1));
+            }
+            EditorTool::Spawn => {
+                let before = Snapshot::take(&self.level, EditorItem::Spawn);
+                self.level.spawn = cell;
+                self.record_edit(EditorItem::Spawn, "Move", before);
+            }
+            EditorTool::Platform(_) | EditorTool::Zone(_) => {}
+        }
+    }
+
+    /// Create a platform or zone covering every grid cell between the drag start and the
+    /// cursor
+    fn place_area(&mut self, start: Vec2, mouse_world: Vec2) {
+        let (min, max) = self.drag_rect(start, mouse_world);
+        match self.tool.clone() {
+            EditorTool::Platform(platform_type) => {
+                let id = self.level.fresh_id(platform_type.name());
+                self.level.platforms.push(PlatformDef {
+                    id,
+                    platform_type,
+                    position: min,
+                    size: max - min,
+                    path: None,
+                    cycle: None,
+                });
+                self.record_place(EditorItem::Platform(self.level.platforms.len() - 1));
+            }
+            EditorTool::Zone(effect) => {
+                // Camera locks start out framing the zone and teleports at its corner,
+                // ready to be changed in the level file or with the target handle
+                let effect = match effect {
+                    ZoneEffect::CameraLock { .. } => ZoneEffect::CameraLock {
+                        position: min,
+                        size: max - min,
+                    },
+                    ZoneEffect::Teleport(_) => ZoneEffect::Teleport(min),
+                    ZoneEffect::AutoScroll { speed, .. } => ZoneEffect::AutoScroll {
+                        speed,
+                        end_x: max.x + GameConfig::VIRTUAL_WIDTH,
+                    },
+                    other => other,
+                };
+                let id = self.level.fresh_id("zone");
+                self.level.zones.push(ZoneDef {
+                    id,
+                    position: min,
+                    size: max - min,
+                    effect,
+                    repeat: None,
+                });
+                self.record_place(EditorItem::Zone(self.level.zones.len() - 1));
+            }
+            _ => {}
+        }
+    }
+
+    /// Switch a zone between firing once and firing again after a cooldown
+    /// Add a waypoint under the cursor to the end of a moving platform's path, starting a
+    /// path if it has none yet
+    fn add_waypoint(&mut self, index: usize, mouse_world: Vec2) {
+        let item = EditorItem::Platform(index);
+        let before = Snapshot::take(&self.level, item);
+        let grid = self.grid;
+        let def = &mut self.level.platforms[index];
+        if def.platform_type != PlatformType::Moving {
+            self.set_status("Only moving platforms follow a path".to_string());
+            return;
+        }
+        // The cursor marks where the middle of the platform passes through
+        let waypoint = snap_to_lines(mouse_world - def.size / 2.0, grid);
+        match &mut def.path {
+            Some(path) => path.waypoints.push(waypoint),
+            None => def.path = Some(PathDef::new(waypoint)),
+        }
+        self.record_edit(item, "Add waypoint to", before);
+    }
+
+    /// Take a waypoint out of a platform's path, dropping the path with its last one
+    fn remove_waypoint(&mut self, index: usize, waypoint: usize) {
+        let item = EditorItem::Platform(index);
+        let before = Snapshot::take(&self.level, item);
+        let def = &mut self.level.platforms[index];
+        let Some(path) = &mut def.path else {
+            return;
+        };
+        path.waypoints.remove(waypoint);
+        if path.waypoints.is_empty() {
+            def.path = None;
+        }
+        self.record_edit(item, "Remove waypoint from", before);
+    }
+
+    /// Change a setting of a platform's path
+    fn edit_path(&mut self, index: usize, change: impl FnOnce(&mut PathDef)) {
+        let item = EditorItem::Platform(index);
+        let before = Snapshot::take(&self.level, item);
+        match &mut self.level.platforms[index].path {
+            Some(path) => change(path),
+            None => {
+                self.set_status("This platform has no path; P adds a waypoint".to_string());
+                return;
+            }
+        }
+        self.record_edit(item, "Change path of", before);
+    }
+
+    fn toggle_zone_repeat(&mut self, index: usize) {
+        let item = EditorItem::Zone(index);
+        let before = Snapshot::take(&self.level, item);
+        let def = &mut self.level.zones[index];
+        def.repeat = match def.repeat {
+            Some(_) => None,
+            None => Some(GameConfig::EDITOR_ZONE_COOLDOWN),
+        };
+        self.record_edit(item, "Change repeat of", before);
+    }
+
+    /// Corners of the grid-aligned rectangle spanned by a drag
+    fn drag_rect(&self, start: Vec2, mouse_world: Vec2) -> (Vec2, Vec2) {
+        let grid = self.grid;
+        let end = self.snap(mouse_world);
+        let min = start.min(end);
+        let max = start.max(end) + Vec2::splat(grid);
+        (min, max)
+    }
+
+    fn remove(&mut self, item: EditorItem) {
+        // Indices after the removed item shift down, so a selection could point at the
+        // wrong thing
+        if item == EditorItem::Spawn {
+            self.set_status("The spawn point can't be removed".to_string());
+            return;
+        }
+        self.selected.clear();
+        let command = Command::Delete {
+            index: item.index(),
+            snapshot: Snapshot::take(&self.level, item),
+        };
+        command.apply(&mut self.level);
+        self.history.push(command);
+    }
+
+    /// Move an item by `offset`, keeping its shape
+    fn shift(&mut self, item: EditorItem, offset: Vec2) {
+        Snapshot::take(&self.level, item)
+            .shifted(offset)
+            .write(&mut self.level, item.index());
+    }
+
+    /// Move the selection one grid cell per arrow key press
+    fn nudge_with_arrows(&mut self, input: &InputHandler) {
+        let directions = [
+            (KeyCode::Left, Vec2::new(-1.0, 0.0)),
+            (KeyCode::Right, Vec2::new(1.0, 0.0)),
+            (KeyCode::Up, Vec2::new(0.0, -1.0)),
+            (KeyCode::Down, Vec2::new(0.0, 1.0)),
+        ];
+        for (key, direction) in directions {
+            if input.is_key_pressed(key) {
+                let before = self.selection_snapshots();
+                let items = self.selected.clone();
+                for &item in &items {
+                    self.shift(item, direction * self.grid);
+                }
+                self.record_edits(&items, "Move", before);
+            }
+        }
+    }
+
+    /// Switch an item to the next variant of its kind
+    fn cycle_type(&mut self, item: EditorItem) {
+        let before = Snapshot::take(&self.level, item);
+        match item {
+            EditorItem::Platform(index) => {
+                let def = &mut self.level.platforms[index];
+                def.platform_type = def.platform_type.next();
+                // Only moving platforms follow a path
+                if def.platform_type != PlatformType::Moving {
+                    def.path = None;
+                }
+            }
+            EditorItem::Collectible(index) => {
+                let def = &mut self.level.collectibles[index];
+                def.collectible_type = def.collectible_type.next();
+            }
+            EditorItem::Pad(index) => {
+                let def = &mut self.level.pads[index];
+                def.pad_kind = next_pad_kind(&def.pad_kind);
+                // Only spikes can be timed
+                if def.pad_kind != PadKind::Spikes {
+                    def.cycle = None;
+                }
+            }
+            EditorItem::Zone(index) => {
+                let def = &mut self.level.zones[index];
+                def.effect = def.effect.next(def.position, def.size);
+            }
+            EditorItem::Enemy(index) => {
+                let def = &mut self.level.enemies[index];
+                def.kind = def.kind.next();
+            }
+            EditorItem::Zipline(_) | EditorItem::Spawn => {
+                self.set_status(format!("The {} has no other types", self.item_label(item)))
+            }
+        }
+        self.record_edit(item, "Change type of", before);
+    }
+
+    /// Handles on the selected item and where they sit in the world
+    pub fn handles(&self) -> Vec<(Handle, Vec2)> {
+        let resize = |position: Vec2, size: Vec2| {
+            let mut handles = Vec::with_capacity(9);
+            for y in -1..=1i8 {
+                for x in -1..=1i8 {
+                    if x == 0 && y == 0 {
+                        continue;
+                    }
+                    let along = Vec2::new(x as f32 + 1.0, y as f32 + 1.0) / 2.0;
+                    handles.push((Handle::Resize { x, y }, position + size * along));
+                }
+            }
+            handles
+        };
+        match self.single_selection() {
+            Some(EditorItem::Platform(index)) => {
+                let def = &self.level.platforms[index];
+                let mut handles = resize(def.position, def.size);
+                if let Some(path) = &def.path {
+                    handles.extend(
+                        path.waypoints
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &waypoint)| (Handle::Waypoint(i), waypoint + def.size / 2.0)),
+                    );
+                }
+                handles
+            }
+            Some(EditorItem::Zone(index)) => {
+                let def = &self.level.zones[index];
+                let mut handles = resize(def.position, def.size);
+                match def.effect {
+                    ZoneEffect::Teleport(target) => handles.push((Handle::TeleportTarget, target)),
+                    ZoneEffect::AutoScroll { end_x, .. } => handles.push((
+                        Handle::ScrollEnd,
+                        Vec2::new(end_x, def.position.y + def.size.y / 2.0),
+                    )),
+                    _ => {}
+                }
+                handles
+            }
+            Some(EditorItem::Zipline(index)) => {
+                let def = &self.level.ziplines[index];
+                vec![
+                    (Handle::ZiplineStart, def.start),
+                    (Handle::ZiplineEnd, def.end),
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The handle under a screen position, tested on screen so they're as easy to grab
+    /// however far the view has moved
+    fn handle_at(&self, screen: Vec2) -> Option<Handle> {
+        self.handles()
+            .into_iter()
+            .find(|(_, world)| {
+                self.world_to_screen(*world).distance(screen)
+                    <= GameConfig::EDITOR_HANDLE_GRAB_RADIUS
+            })
+            .map(|(handle, _)| handle)
+    }
+
+    /// Follow the mouse with a dragged handle. Platform and zone edges snap to grid lines
+    /// and never pass each other, leaving at least one cell.
+    fn drag_handle(&mut self, handle: Handle, mouse_world: Vec2) {
+        let grid = self.grid;
+        let snapped = snap_to_lines(mouse_world, grid);
+        let resize = |position: &mut Vec2, size: &mut Vec2, x: i8, y: i8| {
+            let mut min = *position;
+            let mut max = *position + *size;
+            match x {
+                -1 => min.x = snapped.x.min(max.x - grid),
+                1 => max.x = snapped.x.max(min.x + grid),
+                _ => {}
+            }
+            match y {
+                -1 => min.y = snapped.y.min(max.y - grid),
+                1 => max.y = snapped.y.max(min.y + grid),
+                _ => {}
+            }
+            *position = min;
+            *size = max - min;
+        };
+        match (handle, self.single_selection()) {
+            (Handle::Resize { x, y }, Some(EditorItem::Platform(index))) => {
+                let def = &mut self.level.platforms[index];
+                resize(&mut def.position, &mut def.size, x, y);
+            }
+            (Handle::Resize { x, y }, Some(EditorItem::Zone(index))) => {
+                let def = &mut self.level.zones[index];
+                resize(&mut def.position, &mut def.size, x, y);
+            }
+            (Handle::TeleportTarget, Some(EditorItem::Zone(index))) => {
+                if let ZoneEffect::Teleport(target) = &mut self.level.zones[index].effect {
+                    *target = snapped;
+                }
+            }
+            (Handle::ScrollEnd, Some(EditorItem::Zone(index))) => {
+                if let ZoneEffect::AutoScroll { end_x, .. } = &mut self.level.zones[index].effect {
+                    *end_x = snapped.x;
+                }
+            }
+            (Handle::Waypoint(waypoint), Some(EditorItem::Platform(index))) => {
+                let def = &mut self.level.platforms[index];
+                let position = snap_to_lines(mouse_world - def.size / 2.0, grid);
+                match def
+                    .path
+                    .as_mut()
+                    .and_then(|path| path.waypoints.get_mut(waypoint))
+                {
+                    Some(target) => *target = position,
+                    None => self.dragging = None,
+                }
+            }
+            (Handle::ZiplineStart, Some(EditorItem::Zipline(index))) => {
+                self.level.ziplines[index].start = snapped;
+            }
+            (Handle::ZiplineEnd, Some(EditorItem::Zipline(index))) => {
+                self.level.ziplines[index].end = snapped;
+            }
+            _ => self.dragging = None,
+        }
+    }
+
+    /// Find the topmost item at a world position
+    pub fn item_at(&self, point: Vec2) -> Option<EditorItem> {
+        let contains = |(x1, y1, x2, y2): (f32, f32, f32, f32)| {
+            point.x >= x1 && point.x <= x2 && point.y >= y1 && point.y <= y2
+        };
+
+        if contains(self.spawn_bounds()) {
+            return Some(EditorItem::Spawn);
+        }
+        // Whatever is on a hidden layer can't be picked
+        let hit = |item: EditorItem| self.layers.shows(item) && contains(self.item_bounds(item));
+        if let Some(index) = (0..self.level.enemies.len())
+            .rev()
+            .find(|&i| hit(EditorItem::Enemy(i)))
+        {
+            return Some(EditorItem::Enemy(index));
+        }
+        if let Some(index) = (0..self.level.pads.len())
+            .rev()
+            .find(|&i| hit(EditorItem::Pad(i)))
+        {
+            return Some(EditorItem::Pad(index));
+        }
+        if let Some(index) = (0..self.level.collectibles.len())
+            .rev()
+            .find(|&i| hit(EditorItem::Collectible(i)))
+        {
+            return Some(EditorItem::Collectible(index));
+        }
+        if self.layers.is_visible(Layer::Platforms) {
+            if let Some(index) = self.level.ziplines.iter().rposition(|def| {
+                let line = def.end - def.start;
+                let t = ((point - def.start).dot(line) / line.length_squared()).clamp(0.0, 1.0);
+                point.distance(def.start + line * t) <= 6.0
+            }) {
+                return Some(EditorItem::Zipline(index));
+            }
+        }
+        if let Some(index) = (0..self.level.platforms.len())
+            .rev()
+            .find(|&i| hit(EditorItem::Platform(i)))
+        {
+            return Some(EditorItem::Platform(index));
+        }
+        // Zones are big and empty, so anything inside one is picked first
+        (0..self.level.zones.len())
+            .rev()
+            .find(|&i| hit(EditorItem::Zone(i)))
+            .map(EditorItem::Zone)
+    }
+
+    /// Toggle the `row`th layer in the layer list, or the outlines on the row after them.
+    /// Whatever a hidden layer holds drops out of the selection.
+    fn toggle_layer_row(&mut self, row: usize) {
+        match Layer::ALL.get(row) {
+            Some(&layer) => {
+                self.layers.toggle(layer);
+                let layers = &self.layers;
+                self.selected.retain(|&item| layers.shows(item));
+                self.hovered = self.hovered.filter(|&item| layers.shows(item));
+            }
+            None if row == Layer::ALL.len() => self.layers.outlines = !self.layers.outlines,
+            None => {}
+        }
+    }
+
+    /// Make sure a newly placed item's layer is shown
+    fn reveal(&mut self, item: EditorItem) {
+        if let Some(layer) = Layer::of(item) {
+            self.layers.show(layer);
+        }
+    }
+
+    /// The layer list sits in the bottom-left corner, above the status message
+    fn layer_row_rect(row: usize) -> Rect {
+        let (width, height) = LAYER_ROW_SIZE;
+        let rows = Layer::ALL.len() + 1;
+        let bottom = GameConfig::VIRTUAL_HEIGHT - STATUS_BAR_HEIGHT - 80.0;
+        Rect::new(
+            GameConfig::UI_MARGIN,
+            bottom - (rows - row) as f32 * (height + 2.0),
+            width,
+            height,
+        )
+    }
+
+    fn layer_row_at(point: Vec2) -> Option<usize> {
+        (0..=Layer::ALL.len()).find(|&row| Self::layer_row_rect(row).contains(point))
+    }
+
+    fn spawn_bounds(&self) -> (f32, f32, f32, f32) {
+        let size = Vec2::from(GameConfig::PLAYER_SIZE);
+        let spawn = self.level.spawn;
+        (spawn.x, spawn.y, spawn.x + size.x, spawn.y + size.y)
+    }
+
+    /// World bounds of an item as (x1, y1, x2, y2)
+    pub fn item_bounds(&self, item: EditorItem) -> (f32, f32, f32, f32) {
+        match item {
+            EditorItem::Platform(index) => self.level.platforms[index].build().get_bounds(),
+            EditorItem::Collectible(index) => self.level.collectibles[index].build().get_bounds(),
+            EditorItem::Pad(index) => self.level.pads[index].build().body.get_bounds(),
+            EditorItem::Zone(index) => self.level.zones[index].build().body.get_bounds(),
+            EditorItem::Enemy(index) => self.level.enemies[index].build().body.get_bounds(),
+            EditorItem::Zipline(index) => {
+                let zipline = self.level.ziplines[index].build();
+                let (min, max) = (
+                    zipline.start.min(zipline.end),
+                    zipline.start.max(zipline.end),
+                );
+                (min.x, min.y, max.x, max.y)
+            }
+            EditorItem::Spawn => self.spawn_bounds(),
+        }
+    }
+
+    /// Short description of an item for the hover tooltip
+    pub fn item_label(&self, item: EditorItem) -> String {
+        match item {
+            EditorItem::Platform(index) => {
+                let def = &self.level.platforms[index];
+                format!("{} platform '{}'", def.platform_type.name(), def.id)
+            }
+            EditorItem::Collectible(index) => {
+                let def = &self.level.collectibles[index];
+                format!("{} '{}'", def.collectible_type.name(), def.id)
+            }
+            EditorItem::Zipline(index) => format!("zipline '{}'", self.level.ziplines[index].id),
+            EditorItem::Pad(index) => {
+                let def = &self.level.pads[index];
+                match def.pad_kind {
+                    PadKind::Spring => format!("spring '{}'", def.id),
+                    PadKind::Boost { .. } => format!("boost pad '{}'", def.id),
+                    PadKind::Spikes => format!("spikes '{}'", def.id),
+                }
+            }
+            EditorItem::Zone(index) => {
+                let def = &self.level.zones[index];
+                format!("{} zone '{}'", def.effect.name(), def.id)
+            }
+            EditorItem::Enemy(index) => {
+                let def = &self.level.enemies[index];
+                format!("{} enemy '{}'", def.kind.name(), def.id)
+            }
+            EditorItem::Spawn => "player spawn".to_string(),
+        }
+    }
+
+    /// Lines for the properties panel of the selected item
+    pub fn item_properties(&self, item: EditorItem) -> Vec<String> {
+        let (x1, y1, x2, y2) = self.item_bounds(item);
+        let mut lines = vec![self.item_label(item)];
+        match item {
+            EditorItem::Platform(index) => {
+                let def = &self.level.platforms[index];
+                lines.push(format!("type: {} (Tab)", def.platform_type.name()));
+                lines.push(format!(
+                    "position: {:.0}, {:.0}",
+                    def.position.x, def.position.y
+                ));
+                lines.push(format!("size: {:.0} x {:.0}", def.size.x, def.size.y));
+                if let Some(cycle) = def.cycle {
+                    lines.push(cycle_line(cycle));
+                }
+                match &def.path {
+                    Some(path) => {
+                        lines.push(format!(
+                            "path: {} waypoints, {} (R), {} (K)",
+                            path.waypoints.len() + 1,
+                            path.mode.name(),
+                            if path.smooth { "smooth" } else { "straight" }
+                        ));
+                        lines.push(format!("speed: {} px/s", path.speed));
+                    }
+                    None if def.platform_type == PlatformType::Moving => {
+                        lines.push("path: none (P adds a waypoint)".to_string())
+                    }
+                    None => {}
+                }
+            }
+            EditorItem::Collectible(index) => {
+                let def = &self.level.collectibles[index];
+                lines.push(format!("type: {} (Tab)", def.collectible_type.name()));
+                lines.push(format!(
+                    "position: {:.0}, {:.0}",
+                    def.position.x, def.position.y
+                ));
+            }
+            EditorItem::Zipline(index) => {
+                let def = &self.level.ziplines[index];
+                lines.push(format!("start: {:.0}, {:.0}", def.start.x, def.start.y));
+                lines.push(format!("end: {:.0}, {:.0}", def.end.x, def.end.y));
+            }
+            EditorItem::Pad(index) => {
+                let def = &self.level.pads[index];
+                lines.push(format!(
+                    "type: {} (Tab)",
+                    EditorTool::Pad(def.pad_kind.clone()).label()
+                ));
+                lines.push(format!("x: {:.0}  surface: {:.0}", def.x, def.surface_y));
+                if let Some(cycle) = def.cycle {
+                    lines.push(cycle_line(cycle));
+                }
+            }
+            EditorItem::Zone(index) => {
+                let def = &self.level.zones[index];
+                lines.push(format!("effect: {} (Tab)", def.effect.name()));
+                match &def.effect {
+                    ZoneEffect::ShowTutorial(text) => lines.push(format!("text: {}", text)),
+                    ZoneEffect::ChangeMusic(track) => {
+                        lines.push(format!("track: {}", zone::music_name(*track)))
+                    }
+                    ZoneEffect::SetWeather(weather) => {
+                        lines.push(format!("weather: {}", weather.name()))
+                    }
+                    ZoneEffect::CameraLock { position, size } => lines.push(format!(
+                        "camera: {:.0}, {:.0} {:.0} x {:.0}",
+                        position.x, position.y, size.x, size.y
+                    )),
+                    ZoneEffect::GiveScore(points) => lines.push(format!("score: {}", points)),
+                    ZoneEffect::Teleport(target) => {
+                        lines.push(format!("target: {:.0}, {:.0}", target.x, target.y))
+                    }
+                    ZoneEffect::AutoScroll { speed, end_x } => {
+                        lines.push(format!("scroll: {} px/s to x {:.0}", speed, end_x))
+                    }
+                    ZoneEffect::StartBoulder | ZoneEffect::SetCheckpoint => {}
+                }
+                lines.push(match def.repeat {
+                    Some(cooldown) => format!("fires every {}s (R)", cooldown),
+                    None => "fires once (R)".to_string(),
+                });
+                lines.push(format!(
+                    "position: {:.0}, {:.0}",
+                    def.position.x, def.position.y
+                ));
+            }
+            EditorItem::Enemy(index) => {
+                let def = &self.level.enemies[index];
+                let behavior = &def.behavior;
+                lines.push(format!("type: {} (Tab)", def.kind.name()));
+                lines.push(format!("x: {:.0}  surface: {:.0}", def.x, def.surface_y));
+                if let Some((left, right)) = behavior.patrol {
+                    lines.push(format!("patrol: x {:.0} to {:.0}", left, right));
+                }
+                if let Some(radius) = behavior.detect_radius {
+                    lines.push(format!("detects within {:.0}", radius));
+                }
+                if let Some(interval) = behavior.shoot_interval {
+                    lines.push(format!("shoots every {}s", interval));
+                }
+                if let Some(switch) = &behavior.switch {
+                    lines.push(format!("asleep until switch '{}'", switch));
+                }
+            }
+            EditorItem::Spawn => lines.push(format!("position: {:.0}, {:.0}", x1, y1)),
+        }
+        if !matches!(item, EditorItem::Zipline(_)) {
+            lines.push(format!("bounds: {:.0} x {:.0}", x2 - x1, y2 - y1));
+        }
+        lines
+    }
+
+    fn toolbar_button_rect(slot: usize) -> Rect {
+        let (width, height) = TOOL_BUTTON_SIZE;
+        let column = slot % TOOLBAR_COLUMNS;
+        let row = slot / TOOLBAR_COLUMNS;
+        Rect::new(
+            GameConfig::UI_MARGIN + column as f32 * (width + 4.0),
+            GameConfig::UI_MARGIN + row as f32 * (height + 4.0),
+            width,
+            height,
+        )
+    }
+
+    fn toolbar_slot_at(point: Vec2) -> Option<usize> {
+        (0..TOOL_SLOTS).find(|&slot| Self::toolbar_button_rect(slot).contains(point))
+    }
+
+    /// Draw the level zoomed, then the interface over it at its normal size
+    pub fn render(&self, viewport: &Viewport) {
+        let cam_x = -self.camera.x;
+        let cam_y = -self.camera.y;
+
+        viewport.begin_zoomed(self.zoom);
+        self.render_grid();
+
+        let level = &self.level;
+        let camera = Vec2::new(cam_x, cam_y);
+        self.render_layer(
+            Layer::Platforms,
+            level.platforms.iter().map(PlatformDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Platforms,
+            level.terrain.iter().map(TerrainDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Platforms,
+            level.water.iter().map(WaterDef::build),
+            camera,
+        );
+        self.render_layer(Layer::Hazards, level.pads.iter().map(PadDef::build), camera);
+        self.render_layer(
+            Layer::Hazards,
+            level.bombs.iter().map(BombDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Triggers,
+            level.doors.iter().map(DoorDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Triggers,
+            level.triggers.iter().map(TriggerDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Triggers,
+            level.zones.iter().map(ZoneDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Decorations,
+            level.npcs.iter().map(NpcDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Decorations,
+            level.props.iter().map(PropDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Hazards,
+            level
+                .bosses
+                .iter()
+                .map(|def| def.build(BossTuning::default())),
+            camera,
+        );
+        self.render_layer(
+            Layer::Hazards,
+            level.enemies.iter().map(EnemyDef::build),
+            camera,
+        );
+        if self.layers.view(Layer::Hazards) != LayerView::Hidden {
+            for def in &level.spawn_points {
+                // Enemies only exist in a run, so mark where they'll come from
+                let (w, h) = GameConfig::ENEMY_SIZE;
+                draw_rectangle_lines(
+                    def.x - w / 2.0 + cam_x,
+                    def.surface_y - h + cam_y,
+                    w,
+                    h,
+                    2.0,
+                    self.layer_color(Layer::Hazards, RED),
+                );
+            }
+            // Where placed enemies patrol, and how close the player has to come to be seen
+            let color = self.layer_color(Layer::Hazards, ORANGE);
+            for def in &level.enemies {
+                let behavior = &def.behavior;
+                if let Some((left, right)) = behavior.patrol {
+                    let y = def.surface_y + cam_y;
+                    draw_line(left + cam_x, y, right + cam_x, y, 3.0, color);
+                }
+                if let Some(radius) = behavior.detect_radius {
+                    let body = def.build().body;
+                    let center = body.position + body.size / 2.0;
+                    draw_circle_lines(center.x + cam_x, center.y + cam_y, radius, 1.0, color);
+                }
+            }
+        }
+        self.render_layer(
+            Layer::Triggers,
+            level.switches.iter().map(SwitchDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Platforms,
+            level.ziplines.iter().map(ZiplineDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Platforms,
+            level.bridges.iter().map(BridgeDef::build),
+            camera,
+        );
+        self.render_layer(
+            Layer::Platforms,
+            level.ropes.iter().map(RopeDef::build),
+            camera,
+        );
+        if self.layers.view(Layer::Platforms) == LayerView::Shown {
+            for def in &level.platforms {
+                if let Some(path) = def.build().path {
+                    path.render(def.size, cam_x, cam_y);
+                }
+            }
+        }
+        self.render_layer(
+            Layer::Collectibles,
+            level.collectibles.iter().map(CollectibleDef::build),
+            camera,
+        );
+
+        // Spawn marker
+        let (sx1, sy1, sx2, sy2) = self.spawn_bounds();
+        draw_rectangle_lines(sx1 + cam_x, sy1 + cam_y, sx2 - sx1, sy2 - sy1, 2.0, BLUE);
+        GraphicsUtils::text(
+            "SPAWN",
+            sx1 + cam_x - 4.0,
+            sy1 + cam_y - 4.0,
+            TextStyle::HudSmall.size(),
+            BLUE,
+        );
+
+        self.render_selection(cam_x, cam_y);
+        self.render_jump_arcs(cam_x, cam_y);
+        self.render_cursor(cam_x, cam_y);
+        self.render_stamp(cam_x, cam_y);
+
+        viewport.begin();
+        self.render_handles();
+        self.render_tooltip();
+        self.render_toolbar();
+        self.render_properties();
+        self.render_issues();
+        self.render_prefabs();
+        self.render_layer_list();
+        self.render_help();
+        self.render_status_bar();
+        if self.recovery.is_some() {
+            self.render_recovery();
+        }
+        if let Some(code) = &self.share_code {
+            self.render_share_code(code);
+        }
+    }
+
+    /// Draw the entities of one layer as it's currently shown
+    fn render_layer<E: Entity>(
+        &self,
+        layer: Layer,
+        entities: impl Iterator<Item = E>,
+        camera: Vec2,
+    ) {
+        match self.layers.view(layer) {
+            LayerView::Shown => {
+                for entity in entities {
+                    entity.render(camera.x, camera.y);
+                }
+            }
+            LayerView::Outlined => {
+                let color = self.layer_color(layer, WHITE);
+                for entity in entities {
+                    let position = entity.position() + camera;
+                    let size = entity.size();
+                    draw_rectangle_lines(position.x, position.y, size.x, size.y, 1.0, color);
+                }
+            }
+            LayerView::Hidden => {}
+        }
+    }
+
+    /// `color`, or a faint version of it while the layer is hidden
+    fn layer_color(&self, layer: Layer, color: Color) -> Color {
+        if self.layers.is_visible(layer) {
+            color
+        } else {
+            Color::new(color.r, color.g, color.b, 0.25)
+        }
+    }
+
+    /// Grid lines over the part of the world in view, whatever the zoom
+    fn render_grid(&self) {
+        let color = Color::new(1.0, 1.0, 1.0, 0.15);
+        let top_left = self.screen_to_world(Vec2::ZERO);
+        let bottom_right = self.screen_to_world(Vec2::new(
+            GameConfig::VIRTUAL_WIDTH,
+            GameConfig::VIRTUAL_HEIGHT,
+        ));
+        // Lines are drawn in the zoomed frame, where world points sit at `world - camera`
+        let (min, max) = (top_left - self.camera, bottom_right - self.camera);
+        let first = self.snap(top_left) - self.camera;
+
+        let mut x = first.x;
+        while x < max.x {
+            draw_line(x, min.y, x, max.y, 1.0 / self.zoom, color);
+            x += self.grid;
+        }
+        let mut y = first.y;
+        while y < max.y {
+            draw_line(min.x, y, max.x, y, 1.0 / self.zoom, color);
+            y += self.grid;
+        }
+    }
+
+    fn render_cursor(&self, cam_x: f32, cam_y: f32) {
+        let mouse_world = self.mouse_world_position();
+
+        if let Some(start) = self.drag_start {
+            let (min, max) = self.drag_rect(start, mouse_world);
+            let size = max - min;
+            draw_rectangle(
+                min.x + cam_x,
+                min.y + cam_y,
+                size.x,
+                size.y,
+                Color::new(1.0, 1.0, 1.0, 0.2),
+            );
+            draw_rectangle_lines(min.x + cam_x, min.y + cam_y, size.x, size.y, 2.0, WHITE);
+        } else if self.dragging.is_some() {
+            // The properties panel shows the new shape
+        } else if let Some(item) = self.hovered {
+            let (x1, y1, x2, y2) = self.item_bounds(item);
+            if !self.selected.contains(&item) {
+                draw_rectangle_lines(
+                    x1 + cam_x - 2.0,
+                    y1 + cam_y - 2.0,
+                    x2 - x1 + 4.0,
+                    y2 - y1 + 4.0,
+                    2.0,
+                    YELLOW,
+                );
+            }
+        } else if self.stamp.is_none() {
+            let cell = self.snap(mouse_world);
+            let grid = self.grid;
+            draw_rectangle_lines(
+                cell.x + cam_x,
+                cell.y + cam_y,
+                grid,
+                grid,
+                1.0,
+                Color::new(1.0, 1.0, 1.0, 0.6),
+            );
+        }
+    }
+
+    /// Type and position of the hovered item next to the cursor
+    fn render_tooltip(&self) {
+        if self.drag_start.is_some() || self.dragging.is_some() {
+            return;
+        }
+        let Some(item) = self.hovered else {
+            return;
+        };
+        let (x1, y1, _, _) = self.item_bounds(item);
+        let mouse = Viewport::mouse_position();
+        GraphicsUtils::draw_text_with_shadow(
+            &format!("{} at {:.0}, {:.0}", self.item_label(item), x1, y1),
+            mouse.x + 14.0,
+            mouse.y - 6.0,
+            TextStyle::HudSmall.size(),
+            WHITE,
+            BLACK,
+            Vec2::new(1.0, 1.0),
+        );
+    }
+
+    /// Outline the selected items and any rubber band
+    fn render_selection(&self, cam_x: f32, cam_y: f32) {
+        for &item in &self.selected {
+            let (x1, y1, x2, y2) = self.item_bounds(item);
+            draw_rectangle_lines(
+                x1 + cam_x - 2.0,
+                y1 + cam_y - 2.0,
+                x2 - x1 + 4.0,
+                y2 - y1 + 4.0,
+                2.0,
+                SKYBLUE,
+            );
+        }
+
+        // Where a lone selected zone points the camera or sends the player
+        if let Some(EditorItem::Zone(index)) = self.single_selection() {
+            let def = &self.level.zones[index];
+            let center = def.position + def.size / 2.0;
+            match def.effect {
+                ZoneEffect::CameraLock { position, size } => draw_rectangle_lines(
+                    position.x + cam_x,
+                    position.y + cam_y,
+                    size.x,
+                    size.y,
+                    2.0,
+                    ORANGE,
+                ),
+                ZoneEffect::Teleport(target) => draw_line(
+                    center.x + cam_x,
+                    center.y + cam_y,
+                    target.x + cam_x,
+                    target.y + cam_y,
+                    2.0,
+                    ORANGE,
+                ),
+                // The view the scroll ends on
+                ZoneEffect::AutoScroll { end_x, .. } => draw_rectangle_lines(
+                    end_x + cam_x,
+                    GameConfig::floor_camera_y() + cam_y,
+                    GameConfig::VIRTUAL_WIDTH,
+                    GameConfig::VIRTUAL_HEIGHT,
+                    2.0,
+                    ORANGE,
+                ),
+                _ => {}
+            }
+        }
+
+        if let Some(start) = self.band_start {
+            let end = self.mouse_world_position();
+            let (min, max) = (start.min(end), start.max(end));
+            let size = max - min;
+            draw_rectangle(
+                min.x + cam_x,
+                min.y + cam_y,
+                size.x,
+                size.y,
+                Color::new(0.53, 0.81, 0.92, 0.15),
+            );
+            draw_rectangle_lines(min.x + cam_x, min.y + cam_y, size.x, size.y, 1.0, SKYBLUE);
+        }
+    }
+
+    /// Handles of a lone selected item, drawn on screen so they keep their size at any zoom
+    fn render_handles(&self) {
+        let size = GameConfig::EDITOR_HANDLE_SIZE;
+        let mouse = Viewport::mouse_position();
+        for (handle, world) in self.handles() {
+            let screen = self.world_to_screen(world);
+            let active = self.dragging == Some(handle)
+                || (self.dragging.is_none()
+                    && screen.distance(mouse) <= GameConfig::EDITOR_HANDLE_GRAB_RADIUS);
+            let fill = if active { YELLOW } else { WHITE };
+            draw_rectangle(
+                screen.x - size / 2.0,
+                screen.y - size / 2.0,
+                size,
+                size,
+                fill,
+            );
+            draw_rectangle_lines(
+                screen.x - size / 2.0,
+                screen.y - size / 2.0,
+                size,
+                size,
+                1.0,
+                BLACK,
+            );
+        }
+    }
+
+    /// Readout of the selected item's properties in the top-right corner, or of how much
+    /// is selected
+    fn render_properties(&self) {
+        let mut lines = match self.single_selection() {
+            Some(item) => self.item_properties(item),
+            None if self.selected.is_empty() => return,
+            None => vec![
+                format!("{} items selected", self.selected.len()),
+                "Drag or arrows move them all".to_string(),
+                "Ctrl+C copy | Del delete".to_string(),
+            ],
+        };
+        lines.push("Arrows nudge | Esc deselect".to_string());
+
+        let font_size = TextStyle::HudSmall.size();
+        let line_height = font_size + 4.0;
+        let width = lines
+            .iter()
+            .map(|line| GraphicsUtils::measure_text(line, font_size).width)
+            .fold(0.0, f32::max)
+            + 16.0;
+        let height = lines.len() as f32 * line_height + 12.0;
+        let x = GameConfig::VIRTUAL_WIDTH - GameConfig::UI_MARGIN - width;
+        let y = GameConfig::UI_MARGIN;
+
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_rectangle_lines(x, y, width, height, 1.0, SKYBLUE);
+        for (row, line) in lines.iter().enumerate() {
+            let color = if row == 0 { SKYBLUE } else { WHITE };
+            GraphicsUtils::text(
+                line,
+                x + 8.0,
+                y + 6.0 + line_height * (row as f32 + 0.8),
+                font_size,
+                color,
+            );
+        }
+    }
+
+    /// The level's problems under the toolbar, errors in red; click one to go to it
+    fn render_issues(&self) {
+        let mouse = Viewport::mouse_position();
+        for (row, issue) in self.issues.iter().take(ISSUE_ROWS).enumerate() {
+            let rect = Self::issue_row_rect(row);
+            let background = if rect.contains(mouse) {
+                Color::new(0.2, 0.2, 0.2, 0.8)
+            } else {
+                Color::new(0.0, 0.0, 0.0, 0.6)
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, background);
+            let (prefix, color) = if issue.is_error() {
+                ("ERROR", RED)
+            } else {
+                ("WARNING", ORANGE)
+            };
+            GraphicsUtils::text(
+                &format!("{}: {}", prefix, issue),
+                rect.x + 6.0,
+                rect.y + rect.h * 0.75,
+                TextStyle::HudSmall.size(),
+                color,
+            );
+        }
+        if self.issues.len() > ISSUE_ROWS {
+            let rect = Self::issue_row_rect(ISSUE_ROWS);
+            GraphicsUtils::text(
+                &format!("...and {} more", self.issues.len() - ISSUE_ROWS),
+                rect.x + 6.0,
+                rect.y + rect.h * 0.75,
+                TextStyle::HudSmall.size(),
+                LIGHTGRAY,
+            );
+        }
+    }
+
+    /// Outlines of where the picked-up prefab would be stamped
+    fn render_stamp(&self, cam_x: f32, cam_y: f32) {
+        let Some(prefab) = self.stamp.and_then(|index| self.prefabs.get(index)) else {
+            return;
+        };
+        let offset = self.snap(self.mouse_world_position()) + Vec2::new(cam_x, cam_y);
+        for item in &prefab.items {
+            let (x1, y1, x2, y2) = item.clone().shifted(offset).bounds();
+            draw_rectangle(x1, y1, x2 - x1, y2 - y1, Color::new(1.0, 1.0, 1.0, 0.15));
+            draw_rectangle_lines(x1, y1, x2 - x1, y2 - y1, 1.0, WHITE);
+        }
+    }
+
+    /// The prefab sidebar and the name prompt
+    fn render_prefabs(&self) {
+        let (width, height) = PREFAB_ROW_SIZE;
+        let x = GameConfig::VIRTUAL_WIDTH - GameConfig::UI_MARGIN - width;
+        GraphicsUtils::draw_text_with_shadow(
+            "PREFABS (Ctrl+G saves)",
+            x,
+            PREFAB_LIST_TOP + height * 0.75,
+            TextStyle::HudSmall.size(),
+            WHITE,
+            BLACK,
+            Vec2::new(1.0, 1.0),
+        );
+        let mouse = Viewport::mouse_position();
+        for (row, prefab) in self.prefabs.iter().enumerate() {
+            let rect = Self::prefab_row_rect(row);
+            GraphicsUtils::draw_button(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h,
+                &prefab.name,
+                rect.contains(mouse),
+                self.stamp == Some(row),
+            );
+        }
+
+        if let Some(name) = &self.naming {
+            let center = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT) / 2.0;
+            draw_rectangle(
+                center.x - 220.0,
+                center.y - 40.0,
+                440.0,
+                80.0,
+                Color::new(0.0, 0.0, 0.0, 0.85),
+            );
+            draw_rectangle_lines(center.x - 220.0, center.y - 40.0, 440.0, 80.0, 2.0, WHITE);
+            GraphicsUtils::draw_text_centered(
+                "Prefab name (Enter saves, Esc cancels)",
+                center.x,
+                center.y - 12.0,
+                TextStyle::HudSmall.size(),
+                LIGHTGRAY,
+            );
+            GraphicsUtils::draw_text_centered(
+                &format!("{}_", name),
+                center.x,
+                center.y + 20.0,
+                TextStyle::Hud.size(),
+                WHITE,
+            );
+        }
+    }
+
+    /// Checkboxes for each layer and for outlining hidden ones
+    fn render_layer_list(&self) {
+        let mouse = Viewport::mouse_position();
+        for row in 0..=Layer::ALL.len() {
+            let (checked, label) = match Layer::ALL.get(row) {
+                Some(&layer) => (self.layers.is_visible(layer), layer.label()),
+                None => (self.layers.outlines, "Outline hidden"),
+            };
+            let rect = Self::layer_row_rect(row);
+            let background = if rect.contains(mouse) {
+                Color::new(0.2, 0.2, 0.2, 0.8)
+            } else {
+                Color::new(0.0, 0.0, 0.0, 0.6)
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, background);
+            let box_size = rect.h - 8.0;
+            draw_rectangle_lines(rect.x + 4.0, rect.y + 4.0, box_size, box_size, 1.0, WHITE);
+            if checked {
+                draw_rectangle(
+                    rect.x + 6.0,
+                    rect.y + 6.0,
+                    box_size - 4.0,
+                    box_size - 4.0,
+                    SKYBLUE,
+                );
+            }
+            GraphicsUtils::text(
+                &format!("Alt+{} {}", row + 1, label),
+                rect.x + box_size + 10.0,
+                rect.y + rect.h * 0.75,
+                TextStyle::HudSmall.size(),
+                if checked { WHITE } else { GRAY },
+            );
+        }
+    }
+
+    fn render_toolbar(&self) {
+        let selected = self.tool.slot();
+
+        for slot in 0..TOOL_SLOTS {
+            let Some(default_tool) = EditorTool::for_slot(slot) else {
+                continue;
+            };
+            let tool = if slot == selected {
+                &self.tool
+            } else {
+                &default_tool
+            };
+            // The zone and enemy tools have no key of their own
+            let label = if slot < TOOL_KEYS.len() {
+                format!("{} {}", (slot + 1) % 10, tool.label())
+            } else {
+                tool.label().to_string()
+            };
+            let rect = Self::toolbar_button_rect(slot);
+            let hovered = rect.contains(Viewport::mouse_position());
+
+            GraphicsUtils::draw_button(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h,
+                &label,
+                hovered,
+                slot == selected,
+            );
+        }
+    }
+
+    /// The player's longest jumps off both top corners of the selected platform
+    fn render_jump_arcs(&self, cam_x: f32, cam_y: f32) {
+        if !self.show_jump_arcs {
+            return;
+        }
+        if let Some(EditorItem::Platform(index)) = self.single_selection() {
+            let def = &self.level.platforms[index];
+            let jumps = GameConfig::PLAYER_MAX_JUMPS;
+            jump_arc::render(def.position, -1.0, jumps, cam_x, cam_y);
+            jump_arc::render(
+                def.position + vec2(def.size.x, 0.0),
+                1.0,
+                jumps,
+                cam_x,
+                cam_y,
+            );
+        }
+    }
+
+    fn render_help(&self) {
+        let y = GameConfig::VIRTUAL_HEIGHT - STATUS_BAR_HEIGHT - GameConfig::UI_MARGIN;
+        draw_rectangle(
+            0.0,
+            y - 20.0,
+            GameConfig::VIRTUAL_WIDTH,
+            30.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        GraphicsUtils::text(
+            "EDITOR  WASD pan | Wheel zoom | [ ] grid | LMB place/select | Shift+LMB multi | Ctrl+G prefab | Ctrl+E share | J jump arcs | RMB/Del remove | Enter test (Shift: at cursor) | Ctrl+S save | F10 exit",
+            GameConfig::UI_MARGIN,
+            y,
+            TextStyle::HudSmall.size(),
+            WHITE,
+        );
+
+        if let Some((message, _)) = &self.status {
+            GraphicsUtils::text(
+                message,
+                GameConfig::UI_MARGIN,
+                y - 30.0,
+                TextStyle::Hud.size(),
+                YELLOW,
+            );
+        }
+    }
+
+    /// The bottom strip: cursor position, tool, grid and zoom on the left, what the level
+    /// holds in the middle and the file and whether it's saved on the right
+    fn render_status_bar(&self) {
+        let top = GameConfig::VIRTUAL_HEIGHT - STATUS_BAR_HEIGHT;
+        draw_rectangle(
+            0.0,
+            top,
+            GameConfig::VIRTUAL_WIDTH,
+            STATUS_BAR_HEIGHT,
+            Color::new(0.1, 0.1, 0.15, 0.9),
+        );
+        let baseline = top + STATUS_BAR_HEIGHT * 0.7;
+        let font_size = TextStyle::HudSmall.size();
+
+        let mouse = self.mouse_world_position();
+        let cell = self.snap(mouse);
+        let cursor = format!(
+            "{:.0}, {:.0} (cell {:.0}, {:.0}) | {} | Grid {} | Zoom {:.0}%",
+            mouse.x,
+            mouse.y,
+            cell.x,
+            cell.y,
+            self.tool.label(),
+            self.grid,
+            self.zoom * 100.0
+        );
+        GraphicsUtils::text(&cursor, GameConfig::UI_MARGIN, baseline, font_size, WHITE);
+
+        GraphicsUtils::draw_text_centered(
+            &self.entity_counts(),
+            GameConfig::VIRTUAL_WIDTH * 0.55,
+            baseline - font_size * 0.35,
+            font_size,
+            LIGHTGRAY,
+        );
+
+        let file = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let saved = format!("{} | {}", file, self.save_state());
+        let width = GraphicsUtils::measure_text(&saved, font_size).width;
+        GraphicsUtils::text(
+            &saved,
+            GameConfig::VIRTUAL_WIDTH - GameConfig::UI_MARGIN - width,
+            baseline,
+            font_size,
+            if self.history.is_dirty() {
+                ORANGE
+            } else {
+                LIGHTGRAY
+            },
+        );
+    }
+
+    /// How many of each kind of entity the level has, leaving out kinds it has none of
+    pub fn entity_counts(&self) -> String {
+        let level = &self.level;
+        [
+            (level.platforms.len(), "platforms"),
+            (level.terrain.len(), "terrain blocks"),
+            (level.water.len(), "pools of water"),
+            (level.collectibles.len(), "collectibles"),
+            (level.ziplines.len(), "ziplines"),
+            (level.bridges.len(), "bridges"),
+            (level.ropes.len(), "ropes"),
+            (level.pads.len(), "pads"),
+            (level.bombs.len(), "bombs"),
+            (level.switches.len(), "switches"),
+            (level.doors.len(), "doors"),
+            (level.triggers.len(), "triggers"),
+            (level.zones.len(), "zones"),
+            (level.npcs.len(), "NPCs"),
+            (level.bosses.len(), "bosses"),
+            (level.enemies.len(), "enemies"),
+            (level.spawn_points.len(), "enemy spawns"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, name)| format!("{} {}", count, name))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// Whether there is unsaved work and when the level was last written, such as
+    /// "Unsaved, autosaved 45s ago"
+    pub fn save_state(&self) -> String {
+        let ago = |seconds: f32| {
+            let seconds = seconds as u32;
+            if seconds < 60 {
+                format!("{}s ago", seconds)
+            } else {
+                format!("{}m ago", seconds / 60)
+            }
+        };
+        match (
+            self.history.is_dirty(),
+            self.since_saved,
+            self.since_autosave,
+        ) {
+            (true, _, Some(autosaved)) => format!("Unsaved, autosaved {}", ago(autosaved)),
+            (true, _, None) => "Unsaved changes".to_string(),
+            (false, Some(saved), _) => format!("Saved {}", ago(saved)),
+            (false, None, _) => "No changes".to_string(),
+        }
+    }
+
+    /// The level code wrapped into a panel, for selecting and copying by hand
+    fn render_share_code(&self, code: &str) {
+        let center = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT) / 2.0;
+        let (width, height) = (900.0, 520.0);
+        let (x, y) = (center.x - width / 2.0, center.y - height / 2.0);
+        GraphicsUtils::draw_panel(x, y, width, height);
+        GraphicsUtils::draw_text_centered(
+            "Level code (Enter or Esc closes)",
+            center.x,
+            y + 24.0,
+            TextStyle::Hud.size(),
+            WHITE,
+        );
+        let font_size = TextStyle::HudSmall.size();
+        let per_line = ((width - 40.0) / (font_size * 0.5)) as usize;
+        let rows = ((height - 60.0) / (font_size + 2.0)) as usize;
+        let lines: Vec<&[u8]> = code.as_bytes().chunks(per_line.max(1)).collect();
+        for (row, line) in lines.iter().take(rows).enumerate() {
+            GraphicsUtils::text(
+                std::str::from_utf8(line).unwrap_or(""),
+                x + 20.0,
+                y + 56.0 + row as f32 * (font_size + 2.0),
+                font_size,
+                LIGHTGRAY,
+            );
+        }
+        if lines.len() > rows {
+            GraphicsUtils::draw_text_centered(
+                "Too long to show in full",
+                center.x,
+                y + height - 12.0,
+                TextStyle::HudSmall.size(),
+                YELLOW,
+            );
+        }
+    }
+
+    fn render_recovery(&self) {
+        let center = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT) / 2.0;
+        let (width, height) = (520.0, 100.0);
+        GraphicsUtils::draw_panel(
+            center.x - width / 2.0,
+            center.y - height / 2.0,
+            width,
+            height,
+        );
+        GraphicsUtils::draw_text_centered(
+            "Found an autosave newer than the level file",
+            center.x,
+            center.y - 20.0,
+            TextStyle::Hud.size(),
+            WHITE,
+        );
+        GraphicsUtils::draw_text_centered(
+            "R recover it | D discard it",
+            center.x,
+            center.y + 18.0,
+            TextStyle::HudSmall.size(),
+            YELLOW,
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::level::{Level, LevelError};
+
+use super::history::Snapshot;
+
+/// Where the editor keeps saved prefabs
+pub const PREFAB_PATH: &str = "levels/prefabs.lvl";
+
+const PREFAB_FILE_HEADER: &str = "\
+# Level editor prefabs. Each 'prefab <name>' line starts one, followed by what it
+# contains in the level file format (see levels/level1.lvl), measured from the
+# prefab's top-left corner.
+";
+
+/// A named arrangement of items that can be stamped into a level, kept relative to its
+/// top-left corner
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    pub name: String,
+    pub items: Vec<Snapshot>,
+}
+
+impl Prefab {
+    /// A prefab of copies of `items`, moved so their top-left corner is at the origin.
+    /// The spawn point isn't part of any prefab.
+    pub fn new(name: String, items: Vec<Snapshot>) -> Self {
+        let items: Vec<Snapshot> = items
+            .into_iter()
+            .filter(|item| !matches!(item, Snapshot::Spawn(_)))
+            .collect();
+        let origin = items
+            .iter()
+            .map(Snapshot::origin)
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        Self {
+            name,
+            items: items
+                .into_iter()
+                .map(|item| item.shifted(-origin))
+                .collect(),
+        }
+    }
+
+    /// The items as level data, with the top-left corner on the level floor so the file
+    /// holds offsets from it
+    fn to_level(&self) -> Level {
+        let mut level = Level::default();
+        for item in &self.items {
+            item.clone()
+                .shifted(Vec2::new(0.0, GameConfig::LEVEL_FLOOR_Y))
+                .push_onto(&mut level);
+        }
+        level
+    }
+
+    fn from_level(name: String, level: &Level) -> Self {
+        let items = level
+            .platforms
+            .iter()
+            .cloned()
+            .map(Snapshot::Platform)
+            .chain(
+                level
+                    .collectibles
+                    .iter()
+                    .cloned()
+                    .map(Snapshot::Collectible),
+            )
+            .chain(level.ziplines.iter().cloned().map(Snapshot::Zipline))
+            .chain(level.pads.iter().cloned().map(Snapshot::Pad))
+            .chain(level.zones.iter().cloned().map(Snapshot::Zone))
+            .chain(level.enemies.iter().cloned().map(Snapshot::Enemy))
+            .map(|item| item.shifted(Vec2::new(0.0, -GameConfig::LEVEL_FLOOR_Y)))
+            .collect();
+        Self { name, items }
+    }
+}
+
+/// Read the prefabs file. A missing file just means none have been saved yet.
+pub fn load_prefabs(path: impl AsRef<Path>) -> Result<Vec<Prefab>, LevelError> {
+    let path = path.as_ref();
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(LevelError {
+                line: 0,
+                message: format!("could not read {}: {}", path.display(), err),
+            })
+        }
+    };
+    parse_prefabs(&source)
+}
+
+/// Split the prefabs file at each `prefab` line and parse what follows as level lines
+pub fn parse_prefabs(source: &str) -> Result<Vec<Prefab>, LevelError> {
+    let mut prefabs = Vec::new();
+    // Name, first line number and lines of the prefab being read
+    let mut current: Option<(String, usize, String)> = None;
+
+    let mut finish = |current: Option<(String, usize, String)>| -> Result<(), LevelError> {
+        if let Some((name, first_line, body)) = current {
+            let level = Level::parse_fragment(&body).map_err(|err| LevelError {
+                line: if err.line == 0 {
+                    0
+                } else {
+                    err.line + first_line
+                },
+                message: format!("prefab '{}': {}", name, err.message),
+            })?;
+            prefabs.push(Prefab::from_level(name, &level));
+        }
+        Ok(())
+    };
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let content = raw_line.split('#').next().unwrap_or("").trim();
+        if let Some(name) = content.strip_prefix("prefab ") {
+            finish(current.take())?;
+            current = Some((name.trim().to_string(), index + 1, String::new()));
+            continue;
+        }
+        match &mut current {
+            Some((_, _, body)) => {
+                body.push_str(raw_line);
+                body.push('\n');
+            }
+            None if content.is_empty() => {}
+            None => {
+                return Err(LevelError {
+                    line: index + 1,
+                    message: "expected 'prefab <name>' before any entities".to_string(),
+                })
+            }
+        }
+    }
+    finish(current)?;
+    Ok(prefabs)
+}
+
+/// Write every prefab back out to the prefabs file
+pub fn save_prefabs(path: impl AsRef<Path>, prefabs: &[Prefab]) -> std::io::Result<()> {
+    let mut text = PREFAB_FILE_HEADER.to_string();
+    for prefab in prefabs {
+        // Writing to a String can't fail
+        let _ = write!(
+            text,
+            "\nprefab {}\n{}",
+            prefab.name,
+            prefab.to_level().entities()
+        );
+    }
+    std::fs::write(path, text)
+}
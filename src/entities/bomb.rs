@@ -0,0 +1,153 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::RenderStyle;
+use crate::physics::collision::CollisionDetector;
+
+/// A bomb lying in the level. Walking into it lights the fuse and kicks it along, and a
+/// nearby blast lights it too, so bombs can set each other off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bomb {
+    pub body: PhysicsBody,
+    /// Seconds until it goes off, or None while it's unlit
+    pub fuse: Option<f32>,
+}
+
+impl Bomb {
+    /// A bomb resting on the surface at `surface_y`
+    pub fn new(x: f32, surface_y: f32) -> Self {
+        let (width, height) = GameConfig::BOMB_SIZE;
+        Self {
+            body: PhysicsBody::new(x, surface_y - height, width, height),
+            fuse: None,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.body.position + self.body.size / 2.0
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.fuse.is_some()
+    }
+
+    /// Light the fuse, or shorten it to `seconds` if it's already burning down slower
+    pub fn light(&mut self, seconds: f32) {
+        self.fuse = Some(self.fuse.map_or(seconds, |fuse| fuse.min(seconds)));
+    }
+
+    /// Send it skidding in `direction` (-1 or 1) with a little hop
+    pub fn kick(&mut self, direction: f32) {
+        self.body.velocity = Vec2::new(
+            direction * GameConfig::BOMB_KICK_SPEED,
+            -GameConfig::BOMB_KICK_HOP,
+        );
+        self.body.on_ground = false;
+    }
+
+    /// Burn the fuse down; true once it's time to go off
+    pub fn tick(&mut self, delta_time: f32) -> bool {
+        match &mut self.fuse {
+            Some(fuse) => {
+                *fuse -= delta_time;
+                *fuse <= 0.0
+            }
+            None => false,
+        }
+    }
+
+    pub fn apply_gravity(&mut self, gravity: f32, terminal_velocity: f32, delta_time: f32) {
+        self.body.velocity.y = (self.body.velocity.y + gravity * delta_time).min(terminal_velocity);
+        self.body.on_ground = false;
+    }
+
+    /// Push the bomb out of a solid rectangle along the shallower overlap
+    pub fn collide(&mut self, bounds: (f32, f32, f32, f32)) {
+        let (bx1, by1, bx2, by2) = self.body.get_bounds();
+        if !CollisionDetector::aabb_overlap((bx1, by1, bx2, by2), bounds) {
+            return;
+        }
+
+        let (x1, y1, x2, y2) = bounds;
+        let overlap_x = (bx2 - x1).min(x2 - bx1);
+        let overlap_y = (by2 - y1).min(y2 - by1);
+        if overlap_x < overlap_y {
+            if bx1 < x1 {
+                self.body.position.x -= bx2 - x1;
+            } else {
+                self.body.position.x += x2 - bx1;
+            }
+            self.body.velocity.x = 0.0;
+        } else if by1 < y1 {
+            self.body.position.y -= by2 - y1;
+            self.body.velocity.y = self.body.velocity.y.min(0.0);
+            self.body.on_ground = true;
+        } else {
+            self.body.position.y += y2 - by1;
+            self.body.velocity.y = self.body.velocity.y.max(0.0);
+        }
+    }
+
+    /// Whether the flash is showing: it blinks faster the closer the fuse gets to zero
+    fn is_flashing(&self) -> bool {
+        let Some(fuse) = self.fuse else {
+            return false;
+        };
+        // Blinks per second go up steadily as the fuse burns
+        let burnt = (GameConfig::BOMB_FUSE_TIME - fuse).max(0.0);
+        (GameConfig::BOMB_FLASH_RATE * burnt * (1.0 + burnt)).fract() < 0.5
+    }
+}
+
+impl Entity for Bomb {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        let center = self.center() + Vec2::new(camera_x, camera_y);
+        let radius = self.body.size.x / 2.0;
+
+        let color = if self.is_flashing() {
+            style.pick(RED, GameConfig::HIGH_CONTRAST_HAZARD)
+        } else {
+            Color::new(0.15, 0.15, 0.18, 1.0)
+        };
+        draw_circle(center.x, center.y, radius, color);
+        draw_circle(
+            center.x - radius * 0.35,
+            center.y - radius * 0.35,
+            radius * 0.25,
+            Color::new(1.0, 1.0, 1.0, 0.4),
+        );
+
+        // The fuse, with a spark at the end once it's lit
+        let tip = center + Vec2::new(radius * 0.5, -radius * 1.4);
+        draw_line(
+            center.x + radius * 0.3,
+            center.y - radius * 0.8,
+            tip.x,
+            tip.y,
+            2.0,
+            BROWN,
+        );
+        if self.is_lit() {
+            draw_circle(tip.x, tip.y, 3.0, ORANGE);
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.body.position += self.body.velocity * delta_time;
+        // Skids to a stop along the ground
+        if self.body.on_ground {
+            self.body.velocity.x *= 1.0 - (GameConfig::BOMB_FRICTION * delta_time).min(1.0);
+        }
+    }
+}
@@ -0,0 +1,669 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use super::{BoxKind, Entity, Hitboxes, LocalBox, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{
+    colors, Anchor, GraphicsUtils, HudLayout, RenderStyle, TextStyle, TrailColor, TrailConfig,
+    TrailRenderer,
+};
+use crate::locale::tr;
+use crate::physics::collision::CollisionDetector;
+use crate::settings::set_parsed;
+
+/// Tuning file read when a level with a boss is built
+pub const BOSS_TUNING_PATH: &str = "levels/boss.cfg";
+pub const DEFAULT_BOSS_TUNING: &str = include_str!("../../levels/boss.cfg");
+
+/// One of the boss's moves
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BossAttack {
+    /// Leap at the player and land with a shockwave along the ground
+    Slam,
+    /// Several fans of projectiles aimed at the player
+    Volley,
+}
+
+impl BossAttack {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "slam" => Some(BossAttack::Slam),
+            "volley" => Some(BossAttack::Volley),
+            _ => None,
+        }
+    }
+}
+
+/// Everything that shapes the fight, kept in `levels/boss.cfg` as `key = value` lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossTuning {
+    pub health: u32,
+    /// Attacks used in turn, looping
+    pub pattern: Vec<BossAttack>,
+    pub idle_time: f32,
+    pub slam_windup: f32,
+    pub slam_air_time: f32,
+    pub slam_height: f32,
+    pub shockwave_speed: f32,
+    pub shockwave_height: f32,
+    /// How far a shockwave travels before dying out
+    pub shockwave_range: f32,
+    /// How long the boss stays dazed, and stompable, after landing a slam
+    pub vulnerable_time: f32,
+    pub volleys: u32,
+    pub volley_shots: u32,
+    /// Radians across each fan
+    pub volley_spread: f32,
+    pub volley_interval: f32,
+    pub projectile_speed: f32,
+    pub projectile_radius: f32,
+    /// Multiplier on every speed and one over it on every wait once health is at half
+    pub phase_two_speed: f32,
+    pub phase_two_extra_shots: u32,
+}
+
+impl Default for BossTuning {
+    fn default() -> Self {
+        Self {
+            health: 6,
+            pattern: vec![BossAttack::Slam, BossAttack::Volley],
+            idle_time: 1.2,
+            slam_windup: 0.6,
+            slam_air_time: 0.9,
+            slam_height: 180.0,
+            shockwave_speed: 340.0,
+            shockwave_height: 22.0,
+            shockwave_range: 700.0,
+            vulnerable_time: 1.6,
+            volleys: 3,
+            volley_shots: 5,
+            volley_spread: 0.7,
+            volley_interval: 0.45,
+            projectile_speed: 260.0,
+            projectile_radius: 8.0,
+            phase_two_speed: 1.5,
+            phase_two_extra_shots: 2,
+        }
+    }
+}
+
+impl BossTuning {
+    /// The tuning file on disk, or the copy built into the binary if it can't be read
+    pub fn load_default() -> Self {
+        Self::load(BOSS_TUNING_PATH).unwrap_or_else(|| Self::parse(DEFAULT_BOSS_TUNING))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|source| Self::parse(&source))
+    }
+
+    /// Parse the file, keeping the default for unknown keys and bad values
+    pub fn parse(source: &str) -> Self {
+        let mut tuning = Self::default();
+
+        for line in source.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "health" => set_parsed(&mut tuning.health, value),
+                "pattern" => {
+                    let pattern: Option<Vec<BossAttack>> = value
+                        .split_whitespace()
+                        .map(BossAttack::from_name)
+                        .collect();
+                    if let Some(pattern) = pattern.filter(|pattern| !pattern.is_empty()) {
+                        tuning.pattern = pattern;
+                    }
+                }
+                "idle_time" => set_parsed(&mut tuning.idle_time, value),
+                "slam_windup" => set_parsed(&mut tuning.slam_windup, value),
+                "slam_air_time" => set_parsed(&mut tuning.slam_air_time, value),
+                "slam_height" => set_parsed(&mut tuning.slam_height, value),
+                "shockwave_speed" => set_parsed(&mut tuning.shockwave_speed, value),
+                "shockwave_height" => set_parsed(&mut tuning.shockwave_height, value),
+                "shockwave_range" => set_parsed(&mut tuning.shockwave_range, value),
+                "vulnerable_time" => set_parsed(&mut tuning.vulnerable_time, value),
+                "volleys" => set_parsed(&mut tuning.volleys, value),
+                "volley_shots" => set_parsed(&mut tuning.volley_shots, value),
+                "volley_spread" => set_parsed(&mut tuning.volley_spread, value),
+                "volley_interval" => set_parsed(&mut tuning.volley_interval, value),
+                "projectile_speed" => set_parsed(&mut tuning.projectile_speed, value),
+                "projectile_radius" => set_parsed(&mut tuning.projectile_radius, value),
+                "phase_two_speed" => set_parsed(&mut tuning.phase_two_speed, value),
+                "phase_two_extra_shots" => set_parsed(&mut tuning.phase_two_extra_shots, value),
+                _ => {}
+            }
+        }
+
+        // Nothing below can be zero without breaking the fight
+        tuning.health = tuning.health.max(1);
+        tuning.phase_two_speed = tuning.phase_two_speed.max(0.1);
+        tuning
+    }
+}
+
+/// Where the boss is in its routine
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BossState {
+    /// Standing still until the player walks into the arena
+    Waiting,
+    Idle,
+    SlamWindup,
+    /// In the air, from `from_x` to `to_x`
+    Leaping {
+        from_x: f32,
+        to_x: f32,
+    },
+    /// Just landed a slam; the head can be stomped
+    Dazed,
+    Volley {
+        fired: u32,
+    },
+    Defeated,
+}
+
+/// A wave running along the ground away from a slam; jump it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shockwave {
+    pub x: f32,
+    /// The ground it runs along
+    pub y: f32,
+    pub direction: f32,
+    pub travelled: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Knocked back by the player's swipe; it hits enemies now instead of the player
+    #[serde(default)]
+    pub reflected: bool,
+    #[serde(skip, default = "Projectile::trail")]
+    pub trail: TrailRenderer,
+}
+
+impl Projectile {
+    pub fn new(position: Vec2, velocity: Vec2, radius: f32) -> Self {
+        let mut trail = Self::trail();
+        trail.config.width = radius * 2.0;
+        Self {
+            position,
+            velocity,
+            reflected: false,
+            trail,
+        }
+    }
+
+    /// Tinted to match the shot when drawn, so the color here is only a fallback
+    fn trail() -> TrailRenderer {
+        TrailRenderer::new(TrailConfig {
+            sample_interval: GameConfig::TRAIL_SAMPLE_INTERVAL,
+            max_points: GameConfig::PROJECTILE_TRAIL_POINTS,
+            width: 16.0,
+            width_falloff: GameConfig::TRAIL_WIDTH_FALLOFF,
+            color: TrailColor::Solid(WHITE),
+            break_distance: GameConfig::TRAIL_BREAK_DISTANCE,
+        })
+    }
+
+    /// Send it back the way it came, on the player's side
+    pub fn reflect(&mut self) {
+        self.velocity = -self.velocity;
+        self.reflected = true;
+    }
+
+    pub fn touches(&self, radius: f32, bounds: (f32, f32, f32, f32)) -> bool {
+        CollisionDetector::circle_aabb_push(self.position, radius, bounds).is_some()
+    }
+}
+
+/// A big enemy guarding an arena. It cycles through its tuning's attack pattern, and
+/// speeds up once it's down to half health.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {
+    pub body: PhysicsBody,
+    /// Top edge of the arena floor, where it lands
+    pub ground_y: f32,
+    /// The camera and player are kept between these while the fight is on
+    pub arena_left: f32,
+    pub arena_right: f32,
+    pub health: u32,
+    pub state: BossState,
+    /// Seconds spent in the current state
+    pub timer: f32,
+    /// Index into the attack pattern of the next attack
+    pub next_attack: usize,
+    pub shockwaves: Vec<Shockwave>,
+    pub projectiles: Vec<Projectile>,
+    /// Seconds left of the white flash after taking a hit
+    pub hurt_flash: f32,
+    pub tuning: BossTuning,
+}
+
+impl Boss {
+    /// Create a boss standing on a floor whose top edge is at `surface_y`
+    pub fn new(
+        x: f32,
+        surface_y: f32,
+        arena_left: f32,
+        arena_right: f32,
+        tuning: BossTuning,
+    ) -> Self {
+        let (width, height) = GameConfig::BOSS_SIZE;
+        // Only the head can be stomped
+        let body = PhysicsBody::new(x, surface_y - height, width, height).with_hitboxes(Hitboxes {
+            hurt: Some(LocalBox::new(width * 0.15, 0.0, width * 0.7, height * 0.3)),
+            ..Hitboxes::default()
+        });
+        Self {
+            body,
+            ground_y: surface_y,
+            arena_left,
+            arena_right,
+            health: tuning.health,
+            state: BossState::Waiting,
+            timer: 0.0,
+            next_attack: 0,
+            shockwaves: Vec::new(),
+            projectiles: Vec::new(),
+            hurt_flash: 0.0,
+            tuning,
+        }
+    }
+
+    pub fn max_health(&self) -> u32 {
+        self.tuning.health
+    }
+
+    /// Phase two starts at half health
+    pub fn is_phase_two(&self) -> bool {
+        self.health * 2 <= self.max_health()
+    }
+
+    /// How much faster than phase one everything runs
+    fn speed(&self) -> f32 {
+        if self.is_phase_two() {
+            self.tuning.phase_two_speed
+        } else {
+            1.0
+        }
+    }
+
+    /// Whether the fight is on
+    pub fn is_active(&self) -> bool {
+        !matches!(self.state, BossState::Waiting | BossState::Defeated)
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.state == BossState::Defeated
+    }
+
+    pub fn is_vulnerable(&self) -> bool {
+        self.state == BossState::Dazed
+    }
+
+    /// Whether x is inside the arena
+    pub fn arena_contains(&self, x: f32) -> bool {
+        x >= self.arena_left && x <= self.arena_right
+    }
+
+    pub fn start(&mut self) {
+        if self.state == BossState::Waiting {
+            self.enter(BossState::Idle);
+        }
+    }
+
+    fn enter(&mut self, state: BossState) {
+        self.state = state;
+        self.timer = 0.0;
+    }
+
+    /// Advance the routine, aiming at `target`
+    pub fn fight(&mut self, delta_time: f32, target: Vec2) {
+        self.timer += delta_time;
+        self.hurt_flash = (self.hurt_flash - delta_time).max(0.0);
+        self.update_attacks(delta_time);
+
+        let speed = self.speed();
+        let tuning = &self.tuning;
+        match self.state {
+            BossState::Waiting | BossState::Defeated => {}
+            BossState::Idle => {
+                if self.timer >= tuning.idle_time / speed {
+                    let attack = tuning.pattern[self.next_attack % tuning.pattern.len()];
+                    self.next_attack = (self.next_attack + 1) % tuning.pattern.len();
+                    match attack {
+                        BossAttack::Slam => self.enter(BossState::SlamWindup),
+                        BossAttack::Volley => self.enter(BossState::Volley { fired: 0 }),
+                    }
+                }
+            }
+            BossState::SlamWindup => {
+                if self.timer >= tuning.slam_windup / speed {
+                    // Come down on where the player is now, without leaving the arena
+                    let to_x = (target.x - self.body.size.x / 2.0)
+                        .clamp(self.arena_left, self.arena_right - self.body.size.x);
+                    self.enter(BossState::Leaping {
+                        from_x: self.body.position.x,
+                        to_x,
+                    });
+                }
+            }
+            BossState::Leaping { from_x, to_x } => {
+                let t = (self.timer / (tuning.slam_air_time / speed)).min(1.0);
+                let rest_y = self.ground_y - self.body.size.y;
+                self.body.position.x = from_x + (to_x - from_x) * t;
+                self.body.position.y = rest_y - 4.0 * tuning.slam_height * t * (1.0 - t);
+                if t >= 1.0 {
+                    self.land();
+                }
+            }
+            BossState::Dazed => {
+                if self.timer >= tuning.vulnerable_time / speed {
+                    self.enter(BossState::Idle);
+                }
+            }
+            BossState::Volley { fired } => {
+                let (interval, volleys) = (tuning.volley_interval / speed, tuning.volleys);
+                if self.timer >= interval {
+                    self.fire_volley(target);
+                    let fired = fired + 1;
+                    if fired >= volleys {
+                        self.enter(BossState::Idle);
+                    } else {
+                        self.enter(BossState::Volley { fired });
+                    }
+                }
+            }
+        }
+    }
+
+    fn land(&mut self) {
+        self.body.position.y = self.ground_y - self.body.size.y;
+        let center_x = self.body.position.x + self.body.size.x / 2.0;
+        for direction in [-1.0, 1.0] {
+            self.shockwaves.push(Shockwave {
+                x: center_x + direction * self.body.size.x / 2.0,
+                y: self.ground_y,
+                direction,
+                travelled: 0.0,
+            });
+        }
+        self.enter(BossState::Dazed);
+    }
+
+    fn fire_volley(&mut self, target: Vec2) {
+        let origin = self.body.position + Vec2::new(self.body.size.x / 2.0, self.body.size.y * 0.3);
+        let aim = (target - origin).normalize_or(Vec2::NEG_X);
+        let mut shots = self.tuning.volley_shots.max(1);
+        if self.is_phase_two() {
+            shots += self.tuning.phase_two_extra_shots;
+        }
+        let speed = self.tuning.projectile_speed * self.speed();
+        for shot in 0..shots {
+            let offset = if shots > 1 {
+                (shot as f32 / (shots - 1) as f32 - 0.5) * self.tuning.volley_spread
+            } else {
+                0.0
+            };
+            self.projectiles.push(Projectile::new(
+                origin,
+                Vec2::from_angle(offset).rotate(aim) * speed,
+                self.tuning.projectile_radius,
+            ));
+        }
+    }
+
+    fn update_attacks(&mut self, delta_time: f32) {
+        let wave_speed = self.tuning.shockwave_speed * self.speed();
+        for wave in &mut self.shockwaves {
+            let step = wave_speed * delta_time;
+            wave.x += wave.direction * step;
+            wave.travelled += step;
+        }
+        let (range, left, right) = (
+            self.tuning.shockwave_range,
+            self.arena_left,
+            self.arena_right,
+        );
+        self.shockwaves
+            .retain(|wave| wave.travelled < range && wave.x > left && wave.x < right);
+
+        for projectile in &mut self.projectiles {
+            projectile.position += projectile.velocity * delta_time;
+            projectile
+                .trail
+                .update(delta_time, Some(projectile.position));
+        }
+        let floor = self.ground_y;
+        let (radius, body) = (self.tuning.projectile_radius, self.body.get_bounds());
+        self.projectiles.retain(|projectile| {
+            let p = projectile.position;
+            // Shots knocked back at the boss burst harmlessly against it
+            let burst = projectile.reflected && projectile.touches(radius, body);
+            !burst
+                && p.x > left
+                && p.x < right
+                && p.y < floor
+                && p.y > floor - GameConfig::VIRTUAL_HEIGHT
+        });
+    }
+
+    /// Knock back every shot inside the player's swipe
+    pub fn deflect(&mut self, bounds: (f32, f32, f32, f32)) {
+        let radius = self.tuning.projectile_radius;
+        for projectile in &mut self.projectiles {
+            if !projectile.reflected && projectile.touches(radius, bounds) {
+                projectile.reflect();
+            }
+        }
+    }
+
+    /// Whether a reflected shot hit something in `bounds`; the shot is used up
+    pub fn take_reflected_hit(&mut self, bounds: (f32, f32, f32, f32)) -> bool {
+        let radius = self.tuning.projectile_radius;
+        let hit = self
+            .projectiles
+            .iter()
+            .position(|projectile| projectile.reflected && projectile.touches(radius, bounds));
+        if let Some(index) = hit {
+            self.projectiles.swap_remove(index);
+        }
+        hit.is_some()
+    }
+
+    fn shockwave_bounds(&self, wave: &Shockwave) -> (f32, f32, f32, f32) {
+        let half = GameConfig::SHOCKWAVE_WIDTH / 2.0;
+        (
+            wave.x - half,
+            wave.y - self.tuning.shockwave_height,
+            wave.x + half,
+            wave.y,
+        )
+    }
+
+    /// Take one stomp; returns true if that finished it off
+    pub fn take_hit(&mut self) -> bool {
+        self.health = self.health.saturating_sub(1);
+        self.hurt_flash = GameConfig::BOSS_HURT_FLASH_TIME;
+        if self.health == 0 {
+            self.shockwaves.clear();
+            self.projectiles.clear();
+            self.body.position.y = self.ground_y - self.body.size.y;
+            self.enter(BossState::Defeated);
+            return true;
+        }
+        // Knocked out of the daze, it goes straight back on the attack
+        self.enter(BossState::Idle);
+        false
+    }
+
+    /// Whether the body, a shockwave or a projectile is touching the body's hurt box
+    pub fn hurts(&self, body: &PhysicsBody) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        let target = body.box_bounds(BoxKind::Hurt);
+        // A dazed boss can be walked into safely
+        let touching = !self.is_vulnerable()
+            && CollisionDetector::aabb_overlap(self.body.get_bounds(), target);
+        touching
+            || self
+                .shockwaves
+                .iter()
+                .any(|wave| CollisionDetector::aabb_overlap(self.shockwave_bounds(wave), target))
+            || self.projectiles.iter().any(|projectile| {
+                !projectile.reflected && projectile.touches(self.tuning.projectile_radius, target)
+            })
+    }
+
+    /// Health bar across the top of the screen
+    /// The name and health bar across the top of the screen
+    pub fn render_health_bar(&self, hud: &HudLayout) {
+        let size = Vec2::new(600.0, 18.0) * hud.scale;
+        let label_size = hud.font_size(TextStyle::Body.size());
+        let bar = hud.place(Anchor::TopCenter, Vec2::new(0.0, 24.0), size);
+        let label = if self.is_phase_two() {
+            tr("boss.name_enraged")
+        } else {
+            tr("boss.name")
+        };
+        GraphicsUtils::text(&label, bar.x, bar.y - label_size * 0.35, label_size, WHITE);
+        GraphicsUtils::draw_health_bar(
+            bar.x,
+            bar.y,
+            size.x,
+            size.y,
+            self.health as f32,
+            self.max_health() as f32,
+        );
+    }
+
+    /// The boss itself, without its attacks
+    pub fn render_body(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        let palette = style.palette();
+        let (w, mut h) = (self.body.size.x, self.body.size.y);
+        let x = self.body.position.x + camera_x;
+        let mut y = self.body.position.y + camera_y;
+
+        // Crouch before leaping
+        if self.state == BossState::SlamWindup {
+            let crouch = h * 0.15;
+            y += crouch;
+            h -= crouch;
+        }
+
+        let base = if self.is_phase_two() {
+            Color::new(0.6, 0.15, 0.15, 1.0)
+        } else {
+            Color::new(0.35, 0.2, 0.45, 1.0)
+        };
+        let color = if self.hurt_flash > 0.0 {
+            WHITE
+        } else if self.is_defeated() {
+            colors::with_alpha(GRAY, 0.7)
+        } else {
+            style.pick(base, GameConfig::HIGH_CONTRAST_HAZARD)
+        };
+        draw_rectangle(x, y, w, h, color);
+        style.outline_rectangle(x, y, w, h);
+
+        // The head band turns bright while it can be stomped
+        let band = if self.is_vulnerable() {
+            palette.warning
+        } else {
+            DARKGRAY
+        };
+        draw_rectangle(x + w * 0.15, y, w * 0.7, h * 0.12, band);
+
+        // Eyes, crossed out when dazed or beaten
+        let eye_y = y + h * 0.35;
+        for eye_x in [x + w * 0.3, x + w * 0.7] {
+            if self.is_vulnerable() || self.is_defeated() {
+                draw_line(
+                    eye_x - 6.0,
+                    eye_y - 6.0,
+                    eye_x + 6.0,
+                    eye_y + 6.0,
+                    3.0,
+                    BLACK,
+                );
+                draw_line(
+                    eye_x - 6.0,
+                    eye_y + 6.0,
+                    eye_x + 6.0,
+                    eye_y - 6.0,
+                    3.0,
+                    BLACK,
+                );
+            } else {
+                draw_circle(eye_x, eye_y, 6.0, YELLOW);
+            }
+        }
+    }
+
+    /// Shockwaves and shots, drawn `lag` seconds back along their path so they line up
+    /// with bodies drawn between simulation steps
+    pub fn render_attacks(&self, camera_x: f32, camera_y: f32, lag: f32) {
+        let style = RenderStyle::current();
+        let palette = style.palette();
+        let wave_lag = self.tuning.shockwave_speed * self.speed() * lag;
+        for wave in &self.shockwaves {
+            let (mut x1, y1, mut x2, y2) = self.shockwave_bounds(wave);
+            x1 -= wave.direction * wave_lag;
+            x2 -= wave.direction * wave_lag;
+            let fade = 1.0 - wave.travelled / self.tuning.shockwave_range;
+            draw_triangle(
+                Vec2::new(x1 + camera_x, y2 + camera_y),
+                Vec2::new((x1 + x2) / 2.0 + camera_x, y1 + camera_y),
+                Vec2::new(x2 + camera_x, y2 + camera_y),
+                colors::with_alpha(palette.danger, 0.4 + 0.6 * fade),
+            );
+        }
+        for projectile in &self.projectiles {
+            let position = projectile.position - projectile.velocity * lag;
+            let color = if projectile.reflected {
+                style.pick(SKYBLUE, GameConfig::HIGH_CONTRAST_PLAYER)
+            } else {
+                style.pick(palette.danger, GameConfig::HIGH_CONTRAST_HAZARD)
+            };
+            projectile
+                .trail
+                .render_with(camera_x, camera_y, TrailColor::Solid(color));
+            draw_circle(
+                position.x + camera_x,
+                position.y + camera_y,
+                self.tuning.projectile_radius,
+                color,
+            );
+        }
+    }
+}
+
+impl Entity for Boss {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        self.render_body(camera_x, camera_y);
+        self.render_attacks(camera_x, camera_y, 0.0);
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.update_attacks(delta_time);
+    }
+}
@@ -0,0 +1,145 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{BoxKind, Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderLayer, RenderStyle};
+use crate::physics::collision::CollisionDetector;
+
+/// A big rock that rolls right, speeding up until it's a little faster than the player
+/// can run. It rides along platform tops, drops off their edges and flattens anything
+/// breakable in its way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boulder {
+    pub center: Vec2,
+    pub radius: f32,
+    pub velocity: Vec2,
+    /// Radians, so the surface turns as fast as the boulder moves
+    pub rotation: f32,
+    pub on_ground: bool,
+    /// Center before the last simulation step, for drawing between steps
+    #[serde(skip)]
+    pub previous_center: Option<Vec2>,
+}
+
+impl Boulder {
+    pub fn new(center: Vec2) -> Self {
+        Self {
+            center,
+            radius: GameConfig::BOULDER_RADIUS,
+            velocity: Vec2::ZERO,
+            rotation: 0.0,
+            on_ground: false,
+            previous_center: None,
+        }
+    }
+
+    /// Remember the center before a simulation step moves it
+    pub fn store_previous(&mut self) {
+        self.previous_center = Some(self.center);
+    }
+
+    /// Like `PhysicsBody::render_offset`
+    pub fn render_offset(&self, alpha: f32) -> Vec2 {
+        match self.previous_center {
+            Some(previous) => (previous - self.center) * (1.0 - alpha),
+            None => Vec2::ZERO,
+        }
+    }
+
+    /// Fall under `gravity` and move, ready to be pushed out of platforms again
+    pub fn apply_gravity(&mut self, gravity: f32, terminal_velocity: f32, delta_time: f32) {
+        self.velocity.y = (self.velocity.y + gravity * delta_time).min(terminal_velocity);
+        self.on_ground = false;
+    }
+
+    /// Push the boulder out of a rectangle. Resting on top counts as ground; running into
+    /// a side stops it rolling.
+    pub fn collide(&mut self, bounds: (f32, f32, f32, f32)) -> bool {
+        let Some(push) = CollisionDetector::circle_aabb_push(self.center, self.radius, bounds)
+        else {
+            return false;
+        };
+
+        self.center += push;
+        let normal = push.normalize_or_zero();
+        if normal.y < -0.5 {
+            self.velocity.y = self.velocity.y.min(0.0);
+            self.on_ground = true;
+        } else if normal.y > 0.5 {
+            self.velocity.y = self.velocity.y.max(0.0);
+        } else {
+            self.velocity.x = 0.0;
+        }
+        true
+    }
+
+    pub fn touches(&self, bounds: (f32, f32, f32, f32)) -> bool {
+        CollisionDetector::circle_aabb_push(self.center, self.radius, bounds).is_some()
+    }
+
+    /// Whether it overlaps the body's hurt box
+    pub fn hurts(&self, body: &PhysicsBody) -> bool {
+        self.touches(body.box_bounds(BoxKind::Hurt))
+    }
+}
+
+impl Entity for Boulder {
+    fn position(&self) -> Vec2 {
+        self.center - Vec2::splat(self.radius)
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::splat(self.radius * 2.0)
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let center = self.center + Vec2::new(camera_x, camera_y);
+        let style = RenderStyle::current();
+        let color = style.pick(
+            Color::new(0.5, 0.42, 0.35, 1.0),
+            GameConfig::HIGH_CONTRAST_HAZARD,
+        );
+        GraphicsUtils::draw_circle_with_border(
+            center.x,
+            center.y,
+            self.radius,
+            color,
+            DARKGRAY,
+            3.0,
+        );
+
+        // Cracks fixed to the surface show it turning
+        let rotation = if style.reduced_motion {
+            0.0
+        } else {
+            self.rotation
+        };
+        for i in 0..3 {
+            let angle = rotation + i as f32 * std::f32::consts::TAU / 3.0;
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            let inner = center + direction * self.radius * 0.25;
+            let outer = center + direction * self.radius * 0.85;
+            draw_line(inner.x, inner.y, outer.x, outer.y, 3.0, DARKGRAY);
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        // Only picks up speed while rolling on something
+        if self.on_ground {
+            self.velocity.x = (self.velocity.x + GameConfig::BOULDER_ACCELERATION * delta_time)
+                .min(GameConfig::BOULDER_MAX_SPEED);
+        }
+        self.center += self.velocity * delta_time;
+        self.rotation += self.velocity.x * delta_time / self.radius;
+    }
+
+    /// Rolls over the player it's chasing
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Player
+    }
+
+    fn render_depth(&self) -> f32 {
+        1.0
+    }
+}
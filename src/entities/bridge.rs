@@ -0,0 +1,238 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+
+/// A loose plank falling away from a snapped bridge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plank {
+    pub center: Vec2,
+    pub velocity: Vec2,
+    /// Radians
+    pub angle: f32,
+    pub spin: f32,
+}
+
+/// Planks strung between two anchors that dip under the player. The player can jump up
+/// through it and land on top. A fragile bridge snaps if they stand on its middle too long.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RopeBridge {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub fragile: bool,
+    /// How far the plank under the load dips, easing in and out
+    pub sag: f32,
+    /// World x the dip is centered on; it stays put while the bridge springs back
+    pub sag_x: f32,
+    /// Seconds the player has stood on the middle third of a fragile bridge
+    pub strain: f32,
+    pub broken: bool,
+    /// What's left of the bridge once it has snapped
+    pub debris: Vec<Plank>,
+}
+
+impl RopeBridge {
+    /// A bridge between two anchors; `start` is swapped with `end` if it's on the right
+    pub fn new(start: Vec2, end: Vec2, fragile: bool) -> Self {
+        let (start, end) = if start.x <= end.x {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        Self {
+            start,
+            end,
+            fragile,
+            sag: 0.0,
+            sag_x: start.x,
+            strain: 0.0,
+            broken: false,
+            debris: Vec::new(),
+        }
+    }
+
+    /// How far along the span `x` is, from 0 at the start anchor to 1 at the end
+    fn progress(&self, x: f32) -> f32 {
+        let span = self.end.x - self.start.x;
+        if span <= 0.0 {
+            return 0.0;
+        }
+        ((x - self.start.x) / span).clamp(0.0, 1.0)
+    }
+
+    /// Height of the planks at `x` with nobody on them
+    fn rest_y(&self, x: f32) -> f32 {
+        self.start.y + (self.end.y - self.start.y) * self.progress(x)
+    }
+
+    /// How far the planks at `x` hang below their rest height. Planks dip less the further
+    /// they are from the load, and the anchors never move.
+    fn dip(&self, x: f32) -> f32 {
+        let falloff = (1.0 - (x - self.sag_x).abs() / GameConfig::BRIDGE_SAG_SPREAD).max(0.0);
+        let t = self.progress(x);
+        self.sag * falloff * 4.0 * t * (1.0 - t)
+    }
+
+    /// World y of the walking surface at `x`, or None past the anchors or once it's snapped
+    pub fn surface_y(&self, x: f32) -> Option<f32> {
+        if self.broken || x < self.start.x || x > self.end.x {
+            return None;
+        }
+        Some(self.rest_y(x) + self.dip(x))
+    }
+
+    /// Whether a body's feet are on the planks, to within `tolerance`
+    pub fn supports(&self, body: &PhysicsBody, tolerance: f32) -> bool {
+        let (x1, _, x2, bottom) = body.get_bounds();
+        self.surface_y((x1 + x2) / 2.0)
+            .is_some_and(|surface| (bottom - surface).abs() <= tolerance)
+    }
+
+    fn segment_count(&self) -> usize {
+        ((self.end - self.start).length() / GameConfig::BRIDGE_SEGMENT_LENGTH)
+            .ceil()
+            .max(1.0) as usize
+    }
+
+    /// Where the planks meet, from the start anchor to the end anchor
+    fn joints(&self) -> Vec<Vec2> {
+        let segments = self.segment_count();
+        (0..=segments)
+            .map(|i| {
+                let x = self.start.x + (self.end.x - self.start.x) * i as f32 / segments as f32;
+                Vec2::new(x, self.rest_y(x) + self.dip(x))
+            })
+            .collect()
+    }
+
+    /// Carry the player's weight at world x `load`, or nothing, for a step. Returns true
+    /// if a fragile bridge snapped under them.
+    pub fn bear(&mut self, load: Option<f32>, delta_time: f32) -> bool {
+        self.update_debris(delta_time);
+        if self.broken {
+            return false;
+        }
+
+        let target = match load {
+            Some(x) => {
+                self.sag_x = x;
+                GameConfig::BRIDGE_SAG_DEPTH
+            }
+            None => 0.0,
+        };
+        let step = GameConfig::BRIDGE_SAG_RATE * delta_time;
+        self.sag += (target - self.sag).clamp(-step, step);
+
+        let in_middle = load.is_some_and(|x| (1.0 / 3.0..=2.0 / 3.0).contains(&self.progress(x)));
+        if !self.fragile || !in_middle {
+            self.strain = 0.0;
+            return false;
+        }
+        self.strain += delta_time;
+        if self.strain < GameConfig::BRIDGE_BREAK_TIME {
+            return false;
+        }
+        self.snap();
+        true
+    }
+
+    /// Break every plank loose, falling away from the middle with a little spin
+    fn snap(&mut self) {
+        let joints = self.joints();
+        let middle = (self.start.x + self.end.x) / 2.0;
+        self.debris = joints
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let center = (pair[0] + pair[1]) / 2.0;
+                let side = (center.x - middle).signum();
+                let spin = if i % 2 == 0 { 3.0 } else { -2.0 };
+                Plank {
+                    center,
+                    velocity: Vec2::new(side * 30.0, -40.0 - (i % 3) as f32 * 20.0),
+                    angle: angle_of(pair[1] - pair[0]),
+                    spin,
+                }
+            })
+            .collect();
+        self.broken = true;
+        self.sag = 0.0;
+        self.strain = 0.0;
+    }
+
+    fn update_debris(&mut self, delta_time: f32) {
+        for plank in &mut self.debris {
+            plank.velocity.y += GameConfig::GRAVITY * delta_time;
+            plank.center += plank.velocity * delta_time;
+            plank.angle += plank.spin * delta_time;
+        }
+        let floor = GameConfig::death_threshold();
+        self.debris.retain(|plank| plank.center.y < floor);
+    }
+
+    /// Fragile bridges are built from paler, older wood
+    fn plank_color(&self) -> Color {
+        if self.fragile {
+            GameConfig::BRIDGE_FRAGILE_COLOR
+        } else {
+            GameConfig::BRIDGE_COLOR
+        }
+    }
+
+    fn render_plank(&self, center: Vec2, angle: f32, camera: Vec2) {
+        let half = Vec2::from_angle(angle) * (GameConfig::BRIDGE_SEGMENT_LENGTH / 2.0 - 1.0);
+        let (a, b) = (center - half + camera, center + half + camera);
+        draw_line(a.x, a.y, b.x, b.y, 5.0, self.plank_color());
+    }
+}
+
+impl Entity for RopeBridge {
+    fn position(&self) -> Vec2 {
+        Vec2::new(self.start.x, self.start.y.min(self.end.y))
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(
+            self.end.x - self.start.x,
+            (self.end.y - self.start.y).abs() + GameConfig::BRIDGE_SAG_DEPTH,
+        )
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let camera = Vec2::new(camera_x, camera_y);
+
+        for anchor in [self.start, self.end] {
+            let anchor = anchor + camera;
+            draw_rectangle(anchor.x - 3.0, anchor.y - 20.0, 6.0, 28.0, BROWN);
+        }
+        for plank in &self.debris {
+            self.render_plank(plank.center, plank.angle, camera);
+        }
+        if self.broken {
+            return;
+        }
+
+        // A fragile bridge shakes more and more as it's about to go
+        let shake = if self.fragile {
+            (self.strain * 60.0).sin() * self.strain / GameConfig::BRIDGE_BREAK_TIME
+        } else {
+            0.0
+        };
+        let joints = self.joints();
+        for pair in joints.windows(2) {
+            let (a, b) = (pair[0] + camera, pair[1] + camera);
+            draw_line(a.x, a.y, b.x, b.y, 1.0, GameConfig::BRIDGE_ROPE_COLOR);
+            let center = (pair[0] + pair[1]) / 2.0 + Vec2::new(0.0, shake);
+            self.render_plank(center, angle_of(pair[1] - pair[0]), camera);
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.bear(None, delta_time);
+    }
+}
+
+fn angle_of(direction: Vec2) -> f32 {
+    direction.y.atan2(direction.x)
+}
@@ -1,52 +1,132 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::{Entity, PhysicsBody};
+use crate::animation::{tween, Easing, Timeline, Tween};
+use crate::config::GameConfig;
+use crate::graphics::RenderStyle;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Collectible {
+    /// Stable id from the level file, used to keep state across reloads
+    pub id: String,
     pub body: PhysicsBody,
-    pub color: Color,
     pub collected: bool,
     pub value: i32,
     pub collectible_type: CollectibleType,
     pub animation_time: f32,
+    /// Whether it has been on screen yet this run; it pops in the first time only
+    #[serde(default)]
+    pub has_appeared: bool,
+    /// Size multiplier while it pops in
+    #[serde(skip)]
+    spawn: Option<Tween<f32>>,
+    /// Bobbing up and down while it waits to be picked up
+    #[serde(skip, default = "Collectible::float")]
+    float: Timeline<f32>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CollectibleType {
     Coin,
     Gem,
     PowerUp,
+    Lantern,
+}
+
+impl CollectibleType {
+    /// Look up a collectible type by the name used in level files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "coin" => Some(CollectibleType::Coin),
+            "gem" => Some(CollectibleType::Gem),
+            "powerup" => Some(CollectibleType::PowerUp),
+            "lantern" => Some(CollectibleType::Lantern),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollectibleType::Coin => "coin",
+            CollectibleType::Gem => "gem",
+            CollectibleType::PowerUp => "powerup",
+            CollectibleType::Lantern => "lantern",
+        }
+    }
+
+    /// The type after this one, wrapping around
+    pub fn next(&self) -> Self {
+        match self {
+            CollectibleType::Coin => CollectibleType::Gem,
+            CollectibleType::Gem => CollectibleType::PowerUp,
+            CollectibleType::PowerUp => CollectibleType::Lantern,
+            CollectibleType::Lantern => CollectibleType::Coin,
+        }
+    }
 }
 
 impl Collectible {
     pub fn new(x: f32, y: f32, collectible_type: CollectibleType) -> Self {
-        let (color, value) = match collectible_type {
-            CollectibleType::Coin => (YELLOW, 10),
-            CollectibleType::Gem => (PURPLE, 50),
-            CollectibleType::PowerUp => (PINK, 100),
+        let value = match collectible_type {
+            CollectibleType::Coin => 10,
+            CollectibleType::Gem => 50,
+            CollectibleType::PowerUp => 100,
+            CollectibleType::Lantern => 25,
         };
 
         Self {
+            id: String::new(),
             body: PhysicsBody::new(x, y, 16.0, 16.0),
-            color,
             collected: false,
             value,
             collectible_type,
             animation_time: 0.0,
+            has_appeared: false,
+            spawn: None,
+            float: Self::float(),
         }
     }
 
-    pub fn new_coin(x: f32, y: f32) -> Self {
-        Self::new(x, y, CollectibleType::Coin)
+    fn float() -> Timeline<f32> {
+        let amplitude = GameConfig::FLOAT_AMPLITUDE;
+        let half_period = std::f32::consts::PI / GameConfig::FLOAT_FREQUENCY;
+        tween(-amplitude, amplitude, half_period, Easing::SineInOut)
+            .then(tween(amplitude, -amplitude, half_period, Easing::SineInOut))
+            .looping()
     }
 
-    pub fn new_gem(x: f32, y: f32) -> Self {
-        Self::new(x, y, CollectibleType::Gem)
+    /// Pop in right away, for one that appears mid-run rather than with the level
+    pub fn spawned(mut self) -> Self {
+        self.appear();
+        self
     }
 
-    pub fn new_power_up(x: f32, y: f32) -> Self {
-        Self::new(x, y, CollectibleType::PowerUp)
+    /// Start the pop-in the first time it comes into view
+    pub fn appear(&mut self) {
+        if !self.has_appeared {
+            self.has_appeared = true;
+            self.spawn = Some(tween(
+                0.0,
+                1.0,
+                GameConfig::COLLECTIBLE_SPAWN_TIME,
+                Easing::Overshoot(GameConfig::COLLECTIBLE_SPAWN_OVERSHOOT),
+            ));
+        }
+    }
+
+    /// Size multiplier from the pop-in: growing from nothing, overshooting a little and
+    /// settling at full size
+    pub fn spawn_scale(&self) -> f32 {
+        match &self.spawn {
+            Some(spawn) if !RenderStyle::current().reduced_motion => spawn.value(),
+            _ => 1.0,
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
     }
 
     pub fn collect(&mut self) -> i32 {
@@ -73,6 +153,13 @@ impl Collectible {
     pub fn get_bounds(&self) -> (f32, f32, f32, f32) {
         self.body.get_bounds()
     }
+
+    /// Color for this collectible type in the active palette
+    pub fn color(&self) -> Color {
+        RenderStyle::current()
+            .palette()
+            .collectible_color(&self.collectible_type)
+    }
 }
 
 impl Entity for Collectible {
@@ -89,114 +176,161 @@ impl Entity for Collectible {
             return; // Don't render collected items
         }
 
-        let render_x = self.body.position.x + camera_x;
-        let render_y = self.body.position.y + camera_y;
+        // Drawn scaled around its center while it pops in
+        let scale = self.spawn_scale();
+        if scale <= 0.0 {
+            return;
+        }
+        let size = self.body.size * scale;
+        let render_x = self.body.position.x + (self.body.size.x - size.x) / 2.0 + camera_x;
+        let render_y = self.body.position.y + (self.body.size.y - size.y) / 2.0 + camera_y;
+
+        let style = RenderStyle::current();
+        let time = style.animation_time(self.animation_time);
+        let color = style.pick(self.color(), GameConfig::HIGH_CONTRAST_COLLECTIBLE);
 
         // Animate the collectible with floating motion
-        let float_offset = (self.animation_time * 3.0).sin() * 3.0;
+        let float_offset = if style.reduced_motion {
+            0.0
+        } else {
+            self.float.value()
+        };
         let animated_y = render_y + float_offset;
 
         // Animate color brightness
-        let brightness = 0.8 + 0.2 * (self.animation_time * 4.0).sin();
+        let brightness = 0.8 + 0.2 * (time * 4.0).sin();
         let animated_color = Color::new(
-            self.color.r * brightness,
-            self.color.g * brightness,
-            self.color.b * brightness,
-            self.color.a,
+            color.r * brightness,
+            color.g * brightness,
+            color.b * brightness,
+            color.a,
         );
 
         match self.collectible_type {
             CollectibleType::Coin => {
                 // Draw coin as a circle with inner circle
                 draw_circle(
-                    render_x + self.body.size.x / 2.0,
-                    animated_y + self.body.size.y / 2.0,
-                    self.body.size.x / 2.0,
+                    render_x + size.x / 2.0,
+                    animated_y + size.y / 2.0,
+                    size.x / 2.0,
                     animated_color,
                 );
                 draw_circle_lines(
-                    render_x + self.body.size.x / 2.0,
-                    animated_y + self.body.size.y / 2.0,
-                    self.body.size.x / 2.0,
+                    render_x + size.x / 2.0,
+                    animated_y + size.y / 2.0,
+                    size.x / 2.0,
                     2.0,
                     ORANGE,
                 );
                 // Inner symbol
                 draw_circle(
-                    render_x + self.body.size.x / 2.0,
-                    animated_y + self.body.size.y / 2.0,
-                    3.0,
+                    render_x + size.x / 2.0,
+                    animated_y + size.y / 2.0,
+                    3.0 * scale,
                     ORANGE,
                 );
             }
             CollectibleType::Gem => {
                 // Draw gem as a diamond shape
-                let center_x = render_x + self.body.size.x / 2.0;
-                let center_y = animated_y + self.body.size.y / 2.0;
-                let size = self.body.size.x / 2.0;
+                let center_x = render_x + size.x / 2.0;
+                let center_y = animated_y + size.y / 2.0;
+                let radius = size.x / 2.0;
 
                 // Diamond vertices
-                let top = Vec2::new(center_x, center_y - size);
-                let right = Vec2::new(center_x + size * 0.7, center_y);
-                let bottom = Vec2::new(center_x, center_y + size);
-                let left = Vec2::new(center_x - size * 0.7, center_y);
+                let top = Vec2::new(center_x, center_y - radius);
+                let right = Vec2::new(center_x + radius * 0.7, center_y);
+                let bottom = Vec2::new(center_x, center_y + radius);
+                let left = Vec2::new(center_x - radius * 0.7, center_y);
 
                 // Draw diamond
                 draw_triangle(top, right, bottom, animated_color);
                 draw_triangle(top, left, bottom, animated_color);
 
                 // Draw sparkle effect
-                let sparkle_time = self.animation_time * 6.0;
+                let sparkle_time = time * 6.0;
                 if (sparkle_time % 2.0) > 1.5 {
-                    draw_circle(center_x + 3.0, center_y - 3.0, 1.0, WHITE);
-                    draw_circle(center_x - 2.0, center_y + 2.0, 1.0, WHITE);
+                    draw_circle(center_x + 3.0 * scale, center_y - 3.0 * scale, scale, WHITE);
+                    draw_circle(center_x - 2.0 * scale, center_y + 2.0 * scale, scale, WHITE);
                 }
             }
             CollectibleType::PowerUp => {
                 // Draw power-up as a glowing rectangle with plus sign
-                draw_rectangle(
-                    render_x,
-                    animated_y,
-                    self.body.size.x,
-                    self.body.size.y,
-                    animated_color,
-                );
-                draw_rectangle_lines(
-                    render_x,
-                    animated_y,
-                    self.body.size.x,
-                    self.body.size.y,
-                    2.0,
-                    WHITE,
-                );
+                draw_rectangle(render_x, animated_y, size.x, size.y, animated_color);
+                draw_rectangle_lines(render_x, animated_y, size.x, size.y, 2.0, WHITE);
 
                 // Draw plus sign
-                let center_x = render_x + self.body.size.x / 2.0;
-                let center_y = animated_y + self.body.size.y / 2.0;
+                let center_x = render_x + size.x / 2.0;
+                let center_y = animated_y + size.y / 2.0;
                 draw_line(
                     center_x,
-                    center_y - 4.0,
+                    center_y - 4.0 * scale,
                     center_x,
-                    center_y + 4.0,
+                    center_y + 4.0 * scale,
                     2.0,
                     WHITE,
                 );
                 draw_line(
-                    center_x - 4.0,
+                    center_x - 4.0 * scale,
                     center_y,
-                    center_x + 4.0,
+                    center_x + 4.0 * scale,
                     center_y,
                     2.0,
                     WHITE,
                 );
 
                 // Glow effect
-                draw_circle(center_x, center_y, 12.0, Color::new(1.0, 1.0, 1.0, 0.1));
+                draw_circle(
+                    center_x,
+                    center_y,
+                    12.0 * scale,
+                    Color::new(1.0, 1.0, 1.0, 0.1),
+                );
+            }
+            CollectibleType::Lantern => {
+                // Draw lantern as a lit glass body with a cap and handle
+                let center_x = render_x + size.x / 2.0;
+                let center_y = animated_y + size.y / 2.0;
+
+                draw_circle(
+                    center_x,
+                    center_y + scale,
+                    10.0 * scale,
+                    Color::new(1.0, 0.9, 0.4, 0.2),
+                );
+                draw_rectangle(
+                    render_x + 3.0 * scale,
+                    animated_y + 4.0 * scale,
+                    size.x - 6.0 * scale,
+                    size.y - 4.0 * scale,
+                    animated_color,
+                );
+                draw_rectangle(
+                    render_x + scale,
+                    animated_y + 2.0 * scale,
+                    size.x - 2.0 * scale,
+                    3.0 * scale,
+                    DARKGRAY,
+                );
+                draw_circle_lines(center_x, animated_y + scale, 3.0 * scale, 1.0, DARKGRAY);
+                draw_circle(center_x, center_y + 2.0 * scale, 2.0 * scale, WHITE);
             }
         }
+
+        style.outline_circle(
+            render_x + size.x / 2.0,
+            animated_y + size.y / 2.0,
+            size.x / 2.0 + 3.0 * scale,
+        );
     }
 
-    fn update(&mut self) {
-        self.animation_time += get_frame_time();
+    fn update(&mut self, delta_time: f32) {
+        self.animation_time += delta_time;
+        self.float.update(delta_time);
+        if let Some(spawn) = &mut self.spawn {
+            spawn.update(delta_time);
+            if spawn.is_finished() {
+                self.spawn = None;
+            }
+        }
     }
 }
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+
+/// An on-off rhythm timed against the level clock rather than a timer of its own, so
+/// everything on the same beat switches together and a respawn can't knock it out of
+/// step. It's on for the first half of each period.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cycle {
+    /// Beats from one switch on to the next
+    pub period: f32,
+    /// Beat the first switch on happens on
+    pub phase: f32,
+}
+
+impl Cycle {
+    pub fn new(period: f32, phase: f32) -> Self {
+        Self { period, phase }
+    }
+
+    /// Beats since the last switch on
+    fn position(&self, beat: f32) -> f32 {
+        (beat - self.phase).rem_euclid(self.period)
+    }
+
+    /// Whether it's on at `beat`
+    pub fn is_on(&self, beat: f32) -> bool {
+        self.position(beat) < self.period / 2.0
+    }
+
+    /// Beats until it next switches on or off
+    pub fn until_switch(&self, beat: f32) -> f32 {
+        let half = self.period / 2.0;
+        let position = self.position(beat);
+        if position < half {
+            half - position
+        } else {
+            self.period - position
+        }
+    }
+
+    /// Whether it's on but about to switch off, for a warning flicker
+    pub fn is_ending(&self, beat: f32) -> bool {
+        self.is_on(beat) && self.until_switch(beat) < GameConfig::CYCLE_WARNING_BEATS
+    }
+}
+
+/// The level clock in beats
+pub fn beat_at(clock: f32) -> f32 {
+    clock / GameConfig::CYCLE_BEAT
+}
@@ -0,0 +1,213 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{colors, RenderLayer, RenderStyle};
+
+/// A button on the floor that opens the door whose id is `door` when stepped on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Switch {
+    #[serde(default)]
+    pub id: String,
+    pub body: PhysicsBody,
+    pub door: String,
+    /// Whether something is standing on it; doors open when this turns on
+    pub pressed: bool,
+}
+
+/// A gate that blocks the way until its switch is pressed, then shuts again after
+/// `open_time` seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Door {
+    pub id: String,
+    pub body: PhysicsBody,
+    pub open_time: f32,
+    /// Seconds until the door shuts; zero or less while it's closed
+    pub remaining: f32,
+    /// Set for the one update in which the door shut
+    #[serde(skip)]
+    pub just_closed: bool,
+}
+
+impl Switch {
+    /// Create a switch resting on a surface whose top edge is at `surface_y`
+    pub fn new(x: f32, surface_y: f32, door: impl Into<String>) -> Self {
+        let (width, height) = GameConfig::SWITCH_SIZE;
+        Self {
+            id: String::new(),
+            body: PhysicsBody::new(x, surface_y - height, width, height),
+            door: door.into(),
+            pressed: false,
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Update whether the body is standing on it; true only on the update it got pressed
+    pub fn press(&mut self, body: &PhysicsBody) -> bool {
+        let was_pressed = self.pressed;
+        self.pressed = self.body.overlaps_with(body);
+        self.pressed && !was_pressed
+    }
+}
+
+impl Door {
+    pub fn new(x: f32, y: f32, width: f32, height: f32, open_time: f32) -> Self {
+        Self {
+            id: String::new(),
+            body: PhysicsBody::new(x, y, width, height),
+            open_time,
+            remaining: 0.0,
+            just_closed: false,
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Open the door, or restart its timer if it's already open
+    pub fn open(&mut self) {
+        self.remaining = self.open_time;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    /// Whether the door is in the last seconds before shutting
+    pub fn is_closing_soon(&self) -> bool {
+        self.is_open() && self.remaining <= GameConfig::DOOR_WARNING_TIME
+    }
+
+    pub fn get_bounds(&self) -> (f32, f32, f32, f32) {
+        self.body.get_bounds()
+    }
+
+    /// Whether the warning flash is lit. Ticks speed up from `DOOR_TICK_RATE` to
+    /// `DOOR_TICK_RATE_MAX` per second over the warning, so the count of ticks so far is
+    /// the integral of that linear ramp.
+    pub fn warning_flash(&self) -> bool {
+        if !self.is_closing_soon() {
+            return false;
+        }
+        let span = GameConfig::DOOR_WARNING_TIME;
+        let progress = 1.0 - self.remaining / span;
+        let (start, end) = (GameConfig::DOOR_TICK_RATE, GameConfig::DOOR_TICK_RATE_MAX);
+        let ticks = span * (start * progress + (end - start) * progress * progress / 2.0);
+        ticks.fract() < 0.5
+    }
+
+    fn render_timer(&self, x: f32, y: f32) {
+        let width = self.body.size.x + 8.0;
+        let bar_x = x - 4.0;
+        let bar_y = y - 10.0;
+        let fraction = (self.remaining / self.open_time).clamp(0.0, 1.0);
+        let palette = RenderStyle::current().palette();
+        let color = if self.warning_flash() {
+            palette.danger
+        } else if self.is_closing_soon() {
+            palette.warning
+        } else {
+            palette.health_high
+        };
+
+        draw_rectangle(bar_x, bar_y, width, 5.0, Color::new(0.0, 0.0, 0.0, 0.6));
+        draw_rectangle(bar_x, bar_y, width * fraction, 5.0, color);
+    }
+}
+
+impl Entity for Switch {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y;
+        let (w, h) = (self.body.size.x, self.body.size.y);
+
+        // The button sinks into its base while held down
+        let base_height = h * 0.4;
+        let button_height = if self.pressed { h * 0.2 } else { h * 0.6 };
+        draw_rectangle(
+            x + w * 0.2,
+            y + h - base_height - button_height,
+            w * 0.6,
+            button_height,
+            RenderStyle::current().palette().warning,
+        );
+        draw_rectangle(x, y + h - base_height, w, base_height, DARKGRAY);
+        draw_rectangle_lines(x, y + h - base_height, w, base_height, 1.0, BLACK);
+    }
+
+    fn update(&mut self, _delta_time: f32) {}
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Platforms
+    }
+}
+
+impl Entity for Door {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y;
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        let style = RenderStyle::current();
+        let color = style.pick(
+            Color::new(0.45, 0.45, 0.55, 1.0),
+            GameConfig::HIGH_CONTRAST_PLATFORM,
+        );
+
+        if !self.is_open() {
+            draw_rectangle(x, y, w, h, color);
+            // Bars down the door
+            let bars = (w / 8.0).max(1.0) as usize;
+            for i in 1..bars {
+                let bar_x = x + w * i as f32 / bars as f32;
+                draw_line(bar_x, y, bar_x, y + h, 1.0, DARKGRAY);
+            }
+            draw_rectangle_lines(x, y, w, h, 2.0, BLACK);
+            return;
+        }
+
+        // Open: only an outline of the frame, lit up in the last seconds
+        let frame = if self.warning_flash() {
+            style.palette().danger
+        } else {
+            colors::with_alpha(color, 0.4)
+        };
+        draw_rectangle_lines(x, y, w, h, 2.0, frame);
+        if self.warning_flash() {
+            draw_rectangle(x, y, w, h, colors::with_alpha(style.palette().danger, 0.2));
+        }
+        self.render_timer(x, y);
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        let was_open = self.is_open();
+        self.remaining = (self.remaining - delta_time).max(0.0);
+        self.just_closed = was_open && !self.is_open();
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Platforms
+    }
+}
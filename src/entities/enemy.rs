@@ -0,0 +1,700 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::boss::Projectile;
+use super::throttle::Throttle;
+use super::{BoxKind, Entity, Hitboxes, Layer, LocalBox, PhysicsBody, Platform};
+use crate::config::GameConfig;
+use crate::graphics::{RenderStyle, TrailColor};
+use crate::physics::collision::{CollisionDetector, CollisionSide};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyKind {
+    /// Goes down to a single stomp
+    #[default]
+    Walker,
+    /// Hides in its shell when stomped, and the shell can be kicked along
+    Shelled,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 2] = [EnemyKind::Walker, EnemyKind::Shelled];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "walker" => Some(EnemyKind::Walker),
+            "shelled" => Some(EnemyKind::Shelled),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Walker => "walker",
+            EnemyKind::Shelled => "shelled",
+        }
+    }
+
+    /// The next kind, for cycling through them in the editor
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// How an enemy placed in a level goes about things. Survival's enemies use the default:
+/// they chase the player from anywhere and never shoot.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnemyBehavior {
+    /// World x range it walks back and forth along while it hasn't noticed the player,
+    /// turning early where the ground runs out
+    pub patrol: Option<(f32, f32)>,
+    /// How close the player has to come for it to notice them; None notices them anywhere.
+    /// Without a patrol it stands guard until then.
+    pub detect_radius: Option<f32>,
+    /// Seconds between shots at the player once it has noticed them
+    pub shoot_interval: Option<f32>,
+    /// Id of a switch it sleeps until, standing still and harmless
+    pub switch: Option<String>,
+}
+
+/// What a shelled enemy is up to; walkers only ever walk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyState {
+    #[default]
+    Walking,
+    /// Hiding in its shell, lying still and harmless
+    Shell,
+    /// Kicked shell sliding along, knocking out anything in its way
+    Sliding,
+}
+
+/// What a stomp did to an enemy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stomp {
+    Defeated,
+    /// Pulled into its shell, or a sliding shell stopped
+    Shelled,
+    /// A still shell sent sliding
+    Kicked,
+}
+
+/// A small walker that heads for the player. Landing on its head defeats it; touching it
+/// anywhere else hurts. Shelled ones hide in their shell instead: a touch or a stomp kicks
+/// the shell off sliding, and stomping a sliding shell stops it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enemy {
+    pub body: PhysicsBody,
+    /// Walking speed in pixels per second
+    pub speed: f32,
+    /// -1 walking left, 1 walking right
+    pub facing: f32,
+    pub animation_time: f32,
+    #[serde(default)]
+    pub kind: EnemyKind,
+    #[serde(default)]
+    pub state: EnemyState,
+    /// Enemies this shell has knocked out since it was kicked, for the chain bonus
+    #[serde(default)]
+    pub chain: u32,
+    /// Seconds left before a kicked shell can hurt the player who kicked it, or before a
+    /// still shell's owner comes back out
+    #[serde(default)]
+    timer: f32,
+    #[serde(default)]
+    pub behavior: EnemyBehavior,
+    /// Waiting for its switch
+    #[serde(default)]
+    pub asleep: bool,
+    /// Whether it has noticed the player and is after them
+    #[serde(default)]
+    alert: bool,
+    /// Seconds until its next shot
+    #[serde(default)]
+    shot_timer: f32,
+    #[serde(default)]
+    pub shots: Vec<Projectile>,
+    /// Whether it has ever hurt the player, which keeps it fully simulated wherever it is
+    #[serde(default)]
+    pub has_hurt_player: bool,
+    /// How closely it's simulated while far from the view
+    #[serde(skip)]
+    pub throttle: Throttle,
+}
+
+impl Enemy {
+    /// Create an enemy standing on a surface whose top edge is at `surface_y`
+    pub fn new(x: f32, surface_y: f32, speed: f32) -> Self {
+        let (width, height) = GameConfig::ENEMY_SIZE;
+        let mut enemy = Self {
+            body: PhysicsBody::new(x, surface_y - height, width, height),
+            speed,
+            facing: 1.0,
+            animation_time: 0.0,
+            kind: EnemyKind::Walker,
+            state: EnemyState::Walking,
+            chain: 0,
+            timer: 0.0,
+            behavior: EnemyBehavior::default(),
+            asleep: false,
+            alert: true,
+            shot_timer: 0.0,
+            shots: Vec::new(),
+            has_hurt_player: false,
+            throttle: Throttle::default(),
+        };
+        enemy.resize(height);
+        enemy
+    }
+
+    pub fn new_shelled(x: f32, surface_y: f32, speed: f32) -> Self {
+        Self {
+            kind: EnemyKind::Shelled,
+            ..Self::new(x, surface_y, speed)
+        }
+    }
+
+    pub fn with_kind(mut self, kind: EnemyKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_behavior(mut self, behavior: EnemyBehavior) -> Self {
+        self.asleep = behavior.switch.is_some();
+        self.alert = behavior.detect_radius.is_none() && behavior.patrol.is_none();
+        self.shot_timer = behavior.shoot_interval.unwrap_or(0.0);
+        self.behavior = behavior;
+        self
+    }
+
+    /// Wake it if it's sleeping until the switch with this id
+    pub fn switch_pressed(&mut self, switch: &str) {
+        if self.behavior.switch.as_deref() == Some(switch) {
+            self.asleep = false;
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        self.body.position + self.body.size / 2.0
+    }
+
+    /// Change height keeping the feet where they are. The top of the head is the stomp
+    /// target; the rest of it bites.
+    fn resize(&mut self, height: f32) {
+        let width = self.body.size.x;
+        self.body.position.y += self.body.size.y - height;
+        self.body.size.y = height;
+        self.body.hitboxes = Hitboxes {
+            hurt: Some(LocalBox::new(0.0, 0.0, width, height * 0.4)),
+            damage: Some(LocalBox::new(2.0, height * 0.3, width - 4.0, height * 0.7)),
+            ..Hitboxes::default()
+        };
+    }
+
+    /// Which side it's on: walking it's after the player, a still shell is harmless and a
+    /// sliding one hits everything
+    pub fn layer(&self) -> Layer {
+        match self.state {
+            EnemyState::Walking => Layer::Enemy,
+            EnemyState::Shell => Layer::Neutral,
+            EnemyState::Sliding => Layer::Projectile,
+        }
+    }
+
+    /// Land on it from above, with the stomper's center at `from_x`
+    pub fn stomp(&mut self, from_x: f32) -> Stomp {
+        match (self.kind, self.state) {
+            (EnemyKind::Walker, _) => Stomp::Defeated,
+            (EnemyKind::Shelled, EnemyState::Shell) => {
+                self.kick(self.away_from(from_x));
+                Stomp::Kicked
+            }
+            (EnemyKind::Shelled, _) => {
+                self.state = EnemyState::Shell;
+                self.timer = GameConfig::SHELL_WAKE_TIME;
+                self.resize(GameConfig::ENEMY_SIZE.1 * GameConfig::SHELL_HEIGHT);
+                Stomp::Shelled
+            }
+        }
+    }
+
+    /// Which way (-1 or 1) points away from `x`
+    pub fn away_from(&self, x: f32) -> f32 {
+        if self.body.position.x + self.body.size.x / 2.0 < x {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Send a still shell sliding towards `direction` (-1 or 1)
+    pub fn kick(&mut self, direction: f32) {
+        self.state = EnemyState::Sliding;
+        self.facing = direction;
+        self.chain = 0;
+        self.timer = GameConfig::SHELL_KICK_GRACE;
+    }
+
+    /// Look out for the player at `target` and turn towards them once it has noticed them,
+    /// ignoring small differences so it doesn't jitter underneath; until then it walks its
+    /// patrol, turning early at any ledge in `platforms`. Shells go where they're kicked.
+    pub fn steer(&mut self, target: Vec2, platforms: &[&Platform]) {
+        if self.state != EnemyState::Walking || self.asleep {
+            return;
+        }
+        let center = self.center();
+        self.alert = match self.behavior.detect_radius {
+            Some(radius) => center.distance(target) <= radius,
+            None => self.behavior.patrol.is_none() || self.alert,
+        };
+        if self.alert {
+            if (target.x - center.x).abs() > self.body.size.x / 2.0 {
+                self.facing = (target.x - center.x).signum();
+            }
+        } else if let Some((left, right)) = self.walkable_patrol(platforms) {
+            if center.x <= left {
+                self.facing = 1.0;
+            } else if center.x >= right {
+                self.facing = -1.0;
+            }
+        }
+    }
+
+    /// Its patrol, cut short wherever the ground under it runs out first, so it turns at a
+    /// ledge rather than walking off. Off the ground the patrol is left as it is.
+    fn walkable_patrol(&self, platforms: &[&Platform]) -> Option<(f32, f32)> {
+        let (left, right) = self.behavior.patrol?;
+        let tolerance = GameConfig::GROUND_PROBE_TOLERANCE;
+        let Some(ground) = platforms
+            .iter()
+            .find(|platform| CollisionDetector::is_on_platform(&self.body, platform, tolerance))
+        else {
+            return Some((left, right));
+        };
+        let center = self.center().x;
+        let half_width = self.body.size.x / 2.0;
+        let room = |direction: f32| {
+            CollisionDetector::distance_to_edge(
+                &self.body,
+                ground,
+                platforms.iter().copied(),
+                direction,
+            ) - half_width
+        };
+        Some((left.max(center - room(-1.0)), right.min(center + room(1.0))))
+    }
+
+    /// Whether it has to be simulated every step however far away it is: with shots in
+    /// flight, sliding as a shell, or having hurt the player before
+    pub fn needs_full_simulation(&self) -> bool {
+        !self.shots.is_empty() || self.state == EnemyState::Sliding || self.has_hurt_player
+    }
+
+    /// Turn at the ends of its patrol, or at a ledge, without looking out for the player,
+    /// for while it's too far away to notice them anyway
+    pub fn steer_blind(&mut self, platforms: &[&Platform]) {
+        if self.state != EnemyState::Walking || self.asleep || self.alert {
+            return;
+        }
+        if let Some((left, right)) = self.walkable_patrol(platforms) {
+            let center = self.center();
+            if center.x <= left {
+                self.facing = 1.0;
+            } else if center.x >= right {
+                self.facing = -1.0;
+            }
+        }
+    }
+
+    /// Bring it forward by `elapsed` seconds spent asleep far from the view: its timers
+    /// run down and a patroller is put wherever walking its patrol would have taken it,
+    /// turning at ledges in `platforms` as it would have. Anything else stays where it was.
+    pub fn catch_up(&mut self, elapsed: f32, platforms: &[&Platform]) {
+        self.timer = (self.timer - elapsed).max(0.0);
+        self.animation_time += elapsed;
+        if self.state != EnemyState::Walking || self.is_idle() || self.alert {
+            return;
+        }
+        let Some((left, right)) = self.walkable_patrol(platforms) else {
+            return;
+        };
+        let span = right - left;
+        if span <= 0.0 {
+            return;
+        }
+        // There and back again is a loop twice the patrol's length
+        let along = (self.center().x - left).clamp(0.0, span);
+        let looped = if self.facing > 0.0 {
+            along
+        } else {
+            2.0 * span - along
+        };
+        let looped = (looped + self.speed * elapsed).rem_euclid(2.0 * span);
+        let (along, facing) = if looped > span {
+            (2.0 * span - looped, -1.0)
+        } else {
+            (looped, 1.0)
+        };
+        self.body.position.x = left + along - self.body.size.x / 2.0;
+        self.facing = facing;
+        self.body.reset_interpolation();
+    }
+
+    /// Whether it stays where it is this step: asleep, or with nobody to chase and no
+    /// patrol to walk
+    fn is_idle(&self) -> bool {
+        self.asleep || (!self.alert && self.behavior.patrol.is_none())
+    }
+
+    /// Count down to its next shot and fire at `target` when it's due, as long as it's
+    /// awake, walking and has noticed the player
+    pub fn shoot(&mut self, target: Vec2, delta_time: f32) {
+        let Some(interval) = self.behavior.shoot_interval else {
+            return;
+        };
+        if self.asleep || !self.alert || self.state != EnemyState::Walking {
+            return;
+        }
+        self.shot_timer -= delta_time;
+        if self.shot_timer <= 0.0 {
+            self.shot_timer = interval;
+            let origin = self.center();
+            let aim = (target - origin).normalize_or(Vec2::new(self.facing, 0.0));
+            self.shots.push(Projectile::new(
+                origin,
+                aim * GameConfig::ENEMY_SHOT_SPEED,
+                GameConfig::ENEMY_SHOT_RADIUS,
+            ));
+        }
+    }
+
+    /// Drop shots that hit something solid in `bounds`
+    pub fn block_shots(&mut self, bounds: (f32, f32, f32, f32)) {
+        self.shots
+            .retain(|shot| !shot.touches(GameConfig::ENEMY_SHOT_RADIUS, bounds));
+    }
+
+    pub fn apply_gravity(&mut self, gravity: f32, terminal_velocity: f32, delta_time: f32) {
+        self.body.velocity.y = (self.body.velocity.y + gravity * delta_time).min(terminal_velocity);
+        self.body.on_ground = false;
+    }
+
+    /// Push the enemy out of a solid rectangle along the shallower overlap, returning the
+    /// side of the enemy that hit it
+    pub fn collide(&mut self, bounds: (f32, f32, f32, f32)) -> Option<CollisionSide> {
+        let (ex1, ey1, ex2, ey2) = self.body.get_bounds();
+        if !CollisionDetector::aabb_overlap((ex1, ey1, ex2, ey2), bounds) {
+            return None;
+        }
+
+        let (x1, y1, x2, y2) = bounds;
+        let overlap_x = (ex2 - x1).min(x2 - ex1);
+        let overlap_y = (ey2 - y1).min(y2 - ey1);
+        let side = if overlap_x < overlap_y {
+            if ex1 < x1 {
+                self.body.position.x -= ex2 - x1;
+                CollisionSide::Right
+            } else {
+                self.body.position.x += x2 - ex1;
+                CollisionSide::Left
+            }
+        } else if ey1 < y1 {
+            self.body.position.y -= ey2 - y1;
+            self.body.velocity.y = self.body.velocity.y.min(0.0);
+            self.body.on_ground = true;
+            CollisionSide::Bottom
+        } else {
+            self.body.position.y += y2 - ey1;
+            self.body.velocity.y = self.body.velocity.y.max(0.0);
+            CollisionSide::Top
+        };
+        // Walked or slid into a wall; head back the way the wall faces
+        if let Some(wall) = side.wall_direction() {
+            self.facing = -wall;
+        }
+        Some(side)
+    }
+
+    /// Whether it hurts the player's hurt box: walkers bite, and kicked shells hit once
+    /// they're clear of the kick
+    pub fn hurts(&self, body: &PhysicsBody) -> bool {
+        let target = body.box_bounds(BoxKind::Hurt);
+        let shot = self
+            .shots
+            .iter()
+            .any(|shot| shot.touches(GameConfig::ENEMY_SHOT_RADIUS, target));
+        let bites = self.layer().hurts(Layer::Player)
+            && !self.asleep
+            && !(self.state == EnemyState::Sliding && self.timer > 0.0)
+            && self.body.damages(body);
+        shot || bites
+    }
+
+    /// Whether a sliding shell runs into `other`
+    pub fn hits(&self, other: &Enemy) -> bool {
+        self.layer().hurts(other.layer()) && self.body.overlaps_with(&other.body)
+    }
+}
+
+impl Enemy {
+    /// A domed shell; a sliding one spins, a still one shows the owner peeking out as it
+    /// gets ready to come back out
+    fn render_shell(&self, x: f32, y: f32, w: f32, h: f32, style: &RenderStyle) {
+        let color = if self.state == EnemyState::Sliding {
+            style.pick(
+                Color::new(0.3, 0.6, 0.25, 1.0),
+                GameConfig::HIGH_CONTRAST_HAZARD,
+            )
+        } else {
+            Color::new(0.3, 0.6, 0.25, 1.0)
+        };
+        draw_rectangle(x, y + h * 0.3, w, h * 0.7, color);
+        draw_circle(x + w / 2.0, y + h * 0.45, w * 0.45, color);
+        style.outline_rectangle(x, y + h * 0.3, w, h * 0.7);
+        draw_rectangle(x, y + h * 0.7, w, h * 0.3, Color::new(0.9, 0.85, 0.6, 1.0));
+
+        if self.state == EnemyState::Sliding {
+            let spin = if style.reduced_motion {
+                0.0
+            } else {
+                (self.animation_time * 20.0 * self.facing).sin() * w * 0.3
+            };
+            draw_rectangle(x + w / 2.0 + spin - 2.0, y + h * 0.3, 4.0, h * 0.4, BLACK);
+        } else if self.timer < GameConfig::SHELL_WAKE_TIME * 0.3 {
+            draw_circle(x + w * 0.35, y + h * 0.6, 2.0, WHITE);
+            draw_circle(x + w * 0.65, y + h * 0.6, 2.0, WHITE);
+        }
+    }
+}
+
+impl Enemy {
+    fn render_shots(&self, camera_x: f32, camera_y: f32, style: &RenderStyle) {
+        let color = style.pick(style.palette().danger, GameConfig::HIGH_CONTRAST_HAZARD);
+        for shot in &self.shots {
+            shot.trail
+                .render_with(camera_x, camera_y, TrailColor::Solid(color));
+            draw_circle(
+                shot.position.x + camera_x,
+                shot.position.y + camera_y,
+                GameConfig::ENEMY_SHOT_RADIUS,
+                color,
+            );
+        }
+    }
+}
+
+impl Entity for Enemy {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y;
+
+        self.render_shots(camera_x, camera_y, &style);
+        if self.state != EnemyState::Walking {
+            self.render_shell(x, y, w, h, &style);
+            return;
+        }
+
+        let color = match self.kind {
+            EnemyKind::Walker => Color::new(0.75, 0.25, 0.2, 1.0),
+            EnemyKind::Shelled => Color::new(0.3, 0.6, 0.25, 1.0),
+        };
+        let color = style.pick(color, GameConfig::HIGH_CONTRAST_HAZARD);
+        draw_rectangle(x, y, w, h, color);
+        style.outline_rectangle(x, y, w, h);
+        // A darker cap marks the spot to stomp
+        draw_rectangle(x, y, w, h * 0.25, Color::new(0.4, 0.1, 0.1, 1.0));
+
+        // Eyes look the way it's walking, and are shut while it sleeps
+        let eye_y = y + h * 0.45;
+        if self.asleep {
+            for eye_x in [x + w * 0.3, x + w * 0.7] {
+                draw_line(eye_x - 3.0, eye_y, eye_x + 3.0, eye_y, 2.0, WHITE);
+            }
+        } else {
+            let look = self.facing * 3.0;
+            draw_circle(x + w * 0.3 + look, eye_y, 3.0, WHITE);
+            draw_circle(x + w * 0.7 + look, eye_y, 3.0, WHITE);
+        }
+
+        // Feet shuffle as it walks
+        let step = if style.reduced_motion || self.is_idle() {
+            0.0
+        } else {
+            (self.animation_time * 12.0).sin() * 3.0
+        };
+        draw_rectangle(x + 3.0 + step, y + h - 4.0, 8.0, 4.0, BLACK);
+        draw_rectangle(x + w - 11.0 - step, y + h - 4.0, 8.0, 4.0, BLACK);
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.timer = (self.timer - delta_time).max(0.0);
+        self.body.velocity.x = match self.state {
+            EnemyState::Walking if self.is_idle() => 0.0,
+            EnemyState::Walking => self.facing * self.speed,
+            EnemyState::Shell => 0.0,
+            EnemyState::Sliding => self.facing * GameConfig::SHELL_SLIDE_SPEED,
+        };
+        // Left alone long enough, it comes back out
+        if self.state == EnemyState::Shell && self.timer == 0.0 {
+            self.state = EnemyState::Walking;
+            self.resize(GameConfig::ENEMY_SIZE.1);
+        }
+        self.body.position += self.body.velocity * delta_time;
+        self.animation_time += delta_time;
+
+        for shot in &mut self.shots {
+            shot.position += shot.velocity * delta_time;
+            shot.trail.update(delta_time, Some(shot.position));
+        }
+        let origin = self.center();
+        self.shots
+            .retain(|shot| shot.position.distance(origin) < GameConfig::ENEMY_SHOT_RANGE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::throttle::{Due, SimLevel};
+
+    const SPEED: f32 = 50.0;
+    /// Where the patrol's center runs between
+    const LEFT: f32 = 100.0;
+    const RIGHT: f32 = 300.0;
+
+    /// A patroller that hasn't noticed the player, centered at `x` and walking `facing`
+    fn patroller(x: f32, facing: f32) -> Enemy {
+        let mut enemy = Enemy::new(0.0, 400.0, SPEED).with_behavior(EnemyBehavior {
+            patrol: Some((LEFT, RIGHT)),
+            detect_radius: Some(200.0),
+            ..EnemyBehavior::default()
+        });
+        enemy.body.position.x = x - enemy.body.size.x / 2.0;
+        enemy.facing = facing;
+        enemy
+    }
+
+    fn assert_at(enemy: &Enemy, x: f32, facing: f32) {
+        assert!(
+            (enemy.center().x - x).abs() < 1e-3,
+            "at {} not {}",
+            enemy.center().x,
+            x
+        );
+        assert_eq!(enemy.facing, facing);
+    }
+
+    #[test]
+    fn catching_up_walks_along_the_patrol() {
+        let mut enemy = patroller(150.0, 1.0);
+        enemy.catch_up(2.0, &[]);
+        assert_at(&enemy, 250.0, 1.0);
+    }
+
+    #[test]
+    fn catching_up_turns_at_the_right_end() {
+        // 100 to the end, then 50 back
+        let mut enemy = patroller(250.0, 1.0);
+        enemy.catch_up(3.0, &[]);
+        assert_at(&enemy, 200.0, -1.0);
+    }
+
+    #[test]
+    fn catching_up_turns_at_the_left_end() {
+        // 50 to the end, then 100 back
+        let mut enemy = patroller(150.0, -1.0);
+        enemy.catch_up(3.0, &[]);
+        assert_at(&enemy, 200.0, 1.0);
+    }
+
+    #[test]
+    fn whole_laps_come_back_to_the_same_place() {
+        // There and back is 400, eight seconds at 50
+        let mut enemy = patroller(180.0, -1.0);
+        enemy.catch_up(8.0 * 3.0, &[]);
+        assert_at(&enemy, 180.0, -1.0);
+    }
+
+    #[test]
+    fn only_patrollers_that_havent_noticed_the_player_move() {
+        let mut chaser = Enemy::new(200.0, 400.0, SPEED);
+        let start = chaser.body.position;
+        chaser.catch_up(5.0, &[]);
+        assert_eq!(chaser.body.position, start);
+
+        let mut shell = patroller(150.0, 1.0);
+        shell.state = EnemyState::Shell;
+        shell.catch_up(2.0, &[]);
+        assert_at(&shell, 150.0, 1.0);
+    }
+
+    #[test]
+    fn a_sleeping_patroller_wakes_where_it_would_have_walked_to() {
+        const STEP: f32 = 1.0 / 120.0;
+        let mut enemy = patroller(150.0, 1.0);
+        // Five seconds asleep, then woken by the view coming back
+        for step in 0..600 {
+            assert_eq!(enemy.throttle.step(SimLevel::Asleep, STEP, step), Due::Wait);
+        }
+        let Due::Wake(elapsed) = enemy.throttle.step(SimLevel::Full, STEP, 600) else {
+            panic!("should wake");
+        };
+        // The waking step itself is simulated as usual, so only the rest is caught up
+        enemy.catch_up(elapsed - STEP, &[]);
+        // 150 to the right end, then 100 back
+        assert_at(&enemy, 200.0, -1.0);
+    }
+
+    #[test]
+    fn a_patroller_turns_at_the_edge_of_a_short_platform() {
+        const STEP: f32 = 1.0 / 120.0;
+        // Far narrower than the patrol, which runs well past both ends
+        let ground = Platform::new(150.0, 400.0, 100.0, 20.0);
+        let mut enemy = patroller(200.0, 1.0);
+        let nowhere_near = Vec2::new(5000.0, 0.0);
+        let mut turns = Vec::new();
+        for _ in 0..(8.0 / STEP) as usize {
+            if enemy.body.on_ground {
+                let facing = enemy.facing;
+                enemy.steer(nowhere_near, &[&ground]);
+                if enemy.facing != facing {
+                    turns.push(enemy.center().x);
+                }
+            }
+            enemy.apply_gravity(GameConfig::GRAVITY, GameConfig::TERMINAL_VELOCITY, STEP);
+            enemy.update(STEP);
+            enemy.collide(ground.get_bounds());
+            assert_eq!(enemy.body.get_bounds().3, 400.0, "walked off the platform");
+        }
+        // Right to the edge and back, again and again, with its feet never past the end
+        let half_width = enemy.body.size.x / 2.0;
+        assert!(turns.len() >= 4, "only turned {} times", turns.len());
+        for x in turns {
+            let nearest_edge = (x - 150.0).min(250.0 - x);
+            assert!((nearest_edge - half_width).abs() < 1.0, "turned at {x}");
+        }
+    }
+
+    #[test]
+    fn a_sleeping_patroller_stays_on_its_platform() {
+        let ground = Platform::new(150.0, 400.0, 100.0, 20.0);
+        let mut enemy = patroller(200.0, 1.0);
+        let half_width = enemy.body.size.x / 2.0;
+        enemy.catch_up(1.5, &[&ground]);
+        let x = enemy.center().x;
+        assert!(
+            (150.0 + half_width..=250.0 - half_width).contains(&x),
+            "ended up at {x}"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::PhysicsBody;
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle};
+
+/// A full-width plane of lava that keeps rising from below the level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lava {
+    /// World y of the lava surface (smaller is higher)
+    pub surface_y: f32,
+    pub rise_speed: f32,
+    pub elapsed: f32,
+}
+
+impl Lava {
+    pub fn new(start_y: f32) -> Self {
+        Self {
+            surface_y: start_y,
+            rise_speed: GameConfig::LAVA_BASE_RISE_SPEED,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Raise the lava, speeding up slowly the longer it has been rising
+    pub fn update(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+        self.rise_speed = (GameConfig::LAVA_BASE_RISE_SPEED
+            + GameConfig::LAVA_RISE_ACCELERATION * self.elapsed)
+            .min(GameConfig::LAVA_MAX_RISE_SPEED);
+        self.surface_y -= self.rise_speed * delta_time;
+    }
+
+    /// Vertical distance from the bottom of a body down to the lava surface
+    pub fn height_above(&self, body: &PhysicsBody) -> f32 {
+        let (_, _, _, bottom) = body.get_bounds();
+        self.surface_y - bottom
+    }
+
+    pub fn is_touching(&self, body: &PhysicsBody) -> bool {
+        self.height_above(body) <= 0.0
+    }
+
+    pub fn render(&self, camera_y: f32) {
+        let surface = self.surface_y + camera_y;
+        let bottom = GameConfig::VIRTUAL_HEIGHT;
+        if surface > bottom {
+            return;
+        }
+
+        let style = RenderStyle::current();
+        let time = style.animation_time(get_time() as f32);
+        let top_color = style.pick(style.palette().hazard_top, GameConfig::HIGH_CONTRAST_HAZARD);
+        let bottom_color = style.pick(
+            style.palette().hazard_bottom,
+            GameConfig::HIGH_CONTRAST_HAZARD,
+        );
+
+        GraphicsUtils::draw_gradient_rectangle(
+            0.0,
+            surface.max(0.0),
+            GameConfig::VIRTUAL_WIDTH,
+            bottom - surface.max(0.0),
+            top_color,
+            bottom_color,
+        );
+
+        // Wobbling surface made of overlapping bubbles
+        let step = 24.0;
+        let mut x = 0.0;
+        while x < GameConfig::VIRTUAL_WIDTH + step {
+            let wobble = (time * 3.0 + x * 0.05).sin() * 4.0 + (time * 1.7 + x * 0.02).sin() * 2.0;
+            draw_circle(x, surface + wobble, step * 0.6, top_color);
+            x += step;
+        }
+
+        // Bright glowing crust along the surface
+        draw_line(
+            0.0,
+            surface,
+            GameConfig::VIRTUAL_WIDTH,
+            surface,
+            2.0,
+            Color::new(1.0, 0.9, 0.3, 0.8),
+        );
+        if style.high_contrast {
+            draw_line(
+                0.0,
+                surface,
+                GameConfig::VIRTUAL_WIDTH,
+                surface,
+                GameConfig::HIGH_CONTRAST_OUTLINE_WIDTH,
+                GameConfig::HIGH_CONTRAST_OUTLINE,
+            );
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::collision::CollisionDetector;
+
+use super::{
+    Bomb, Boss, Boulder, Collectible, Door, Enemy, Npc, Pad, PhysicsBody, Platform, Rope,
+    RopeBridge, Switch, Terrain, Trigger, TriggerZone, Water, Zipline,
+};
+
+/// Stable handle to an entity in an `Arena`. Once the entity is despawned the id stays
+/// invalid, even after its slot is reused for something else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Storage for one kind of entity, handing out generational ids
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> EntityId {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return EntityId {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        let index = self.slots.len() as u32;
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        EntityId {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// Take the entity out, invalidating its id
+    pub fn remove(&mut self, id: EntityId) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        // Bumping the generation is what makes old ids miss after the slot is reused
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&T> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut T> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove everything; ids handed out before stay invalid
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Live entities with their ids, in slot order
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let id = EntityId {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            slot.value.as_ref().map(|value| (id, value))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let id = EntityId {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                slot.value.as_mut().map(|value| (id, value))
+            })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for Arena<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut arena = Self::new();
+        for value in iter {
+            arena.insert(value);
+        }
+        arena
+    }
+}
+
+/// An entity waiting to be added at the end of the frame
+#[derive(Debug, Clone)]
+pub enum Spawn {
+    Platform(Platform),
+    Terrain(Terrain),
+    Water(Water),
+    Collectible(Collectible),
+    Zipline(Zipline),
+    Bridge(RopeBridge),
+    Rope(Rope),
+    Pad(Pad),
+    Switch(Switch),
+    Door(Door),
+    Trigger(Trigger),
+    Zone(TriggerZone),
+    Boulder(Boulder),
+    Npc(Npc),
+    Boss(Boss),
+    Enemy(Enemy),
+    Bomb(Bomb),
+}
+
+/// An id together with the arena it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityRef {
+    Platform(EntityId),
+    Terrain(EntityId),
+    Water(EntityId),
+    Collectible(EntityId),
+    Zipline(EntityId),
+    Bridge(EntityId),
+    Rope(EntityId),
+    Pad(EntityId),
+    Switch(EntityId),
+    Door(EntityId),
+    Trigger(EntityId),
+    Zone(EntityId),
+    Boulder(EntityId),
+    Npc(EntityId),
+    Boss(EntityId),
+    Enemy(EntityId),
+    Bomb(EntityId),
+}
+
+/// Every level entity, stored by type. Spawns and despawns requested mid-frame are
+/// queued and applied together so nothing changes under an ongoing iteration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityManager {
+    pub platforms: Arena<Platform>,
+    #[serde(default)]
+    pub terrain: Arena<Terrain>,
+    #[serde(default)]
+    pub water: Arena<Water>,
+    pub collectibles: Arena<Collectible>,
+    pub ziplines: Arena<Zipline>,
+    #[serde(default)]
+    pub bridges: Arena<RopeBridge>,
+    #[serde(default)]
+    pub ropes: Arena<Rope>,
+    pub pads: Arena<Pad>,
+    #[serde(default)]
+    pub switches: Arena<Switch>,
+    #[serde(default)]
+    pub doors: Arena<Door>,
+    #[serde(default)]
+    pub triggers: Arena<Trigger>,
+    #[serde(default)]
+    pub zones: Arena<TriggerZone>,
+    #[serde(default)]
+    pub boulders: Arena<Boulder>,
+    #[serde(default)]
+    pub npcs: Arena<Npc>,
+    #[serde(default)]
+    pub bosses: Arena<Boss>,
+    #[serde(default)]
+    pub enemies: Arena<Enemy>,
+    #[serde(default)]
+    pub bombs: Arena<Bomb>,
+    #[serde(skip)]
+    spawns: Vec<Spawn>,
+    #[serde(skip)]
+    despawns: Vec<EntityRef>,
+}
+
+impl EntityManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every entity and anything still queued
+    pub fn clear(&mut self) {
+        self.platforms.clear();
+        self.terrain.clear();
+        self.water.clear();
+        self.collectibles.clear();
+        self.ziplines.clear();
+        self.bridges.clear();
+        self.ropes.clear();
+        self.pads.clear();
+        self.switches.clear();
+        self.doors.clear();
+        self.triggers.clear();
+        self.zones.clear();
+        self.boulders.clear();
+        self.npcs.clear();
+        self.bosses.clear();
+        self.enemies.clear();
+        self.bombs.clear();
+        self.spawns.clear();
+        self.despawns.clear();
+    }
+
+    pub fn queue_spawn(&mut self, spawn: Spawn) {
+        self.spawns.push(spawn);
+    }
+
+    /// Remove an entity once the frame's updates are done
+    pub fn queue_despawn(&mut self, entity: EntityRef) {
+        self.despawns.push(entity);
+    }
+
+    /// Add an entity straight away, outside of any iteration
+    pub fn spawn(&mut self, spawn: Spawn) -> EntityRef {
+        match spawn {
+            Spawn::Platform(platform) => EntityRef::Platform(self.platforms.insert(platform)),
+            Spawn::Terrain(terrain) => EntityRef::Terrain(self.terrain.insert(terrain)),
+            Spawn::Water(water) => EntityRef::Water(self.water.insert(water)),
+            Spawn::Collectible(collectible) => {
+                EntityRef::Collectible(self.collectibles.insert(collectible))
+            }
+            Spawn::Zipline(zipline) => EntityRef::Zipline(self.ziplines.insert(zipline)),
+            Spawn::Bridge(bridge) => EntityRef::Bridge(self.bridges.insert(bridge)),
+            Spawn::Rope(rope) => EntityRef::Rope(self.ropes.insert(rope)),
+            Spawn::Pad(pad) => EntityRef::Pad(self.pads.insert(pad)),
+            Spawn::Switch(switch) => EntityRef::Switch(self.switches.insert(switch)),
+            Spawn::Door(door) => EntityRef::Door(self.doors.insert(door)),
+            Spawn::Trigger(trigger) => EntityRef::Trigger(self.triggers.insert(trigger)),
+            Spawn::Zone(zone) => EntityRef::Zone(self.zones.insert(zone)),
+            Spawn::Boulder(boulder) => EntityRef::Boulder(self.boulders.insert(boulder)),
+            Spawn::Npc(npc) => EntityRef::Npc(self.npcs.insert(npc)),
+            Spawn::Boss(boss) => EntityRef::Boss(self.bosses.insert(boss)),
+            Spawn::Enemy(enemy) => EntityRef::Enemy(self.enemies.insert(enemy)),
+            Spawn::Bomb(bomb) => EntityRef::Bomb(self.bombs.insert(bomb)),
+        }
+    }
+
+    /// Remove an entity straight away; returns false if it was already gone
+    pub fn despawn(&mut self, entity: EntityRef) -> bool {
+        match entity {
+            EntityRef::Platform(id) => self.platforms.remove(id).is_some(),
+            EntityRef::Terrain(id) => self.terrain.remove(id).is_some(),
+            EntityRef::Water(id) => self.water.remove(id).is_some(),
+            EntityRef::Collectible(id) => self.collectibles.remove(id).is_some(),
+            EntityRef::Zipline(id) => self.ziplines.remove(id).is_some(),
+            EntityRef::Bridge(id) => self.bridges.remove(id).is_some(),
+            EntityRef::Rope(id) => self.ropes.remove(id).is_some(),
+            EntityRef::Pad(id) => self.pads.remove(id).is_some(),
+            EntityRef::Switch(id) => self.switches.remove(id).is_some(),
+            EntityRef::Door(id) => self.doors.remove(id).is_some(),
+            EntityRef::Trigger(id) => self.triggers.remove(id).is_some(),
+            EntityRef::Zone(id) => self.zones.remove(id).is_some(),
+            EntityRef::Boulder(id) => self.boulders.remove(id).is_some(),
+            EntityRef::Npc(id) => self.npcs.remove(id).is_some(),
+            EntityRef::Boss(id) => self.bosses.remove(id).is_some(),
+            EntityRef::Enemy(id) => self.enemies.remove(id).is_some(),
+            EntityRef::Bomb(id) => self.bombs.remove(id).is_some(),
+        }
+    }
+
+    /// The loose bodies, enemies and bombs, whose bounds come within `radius` of `center`
+    pub fn bodies_within(&self, center: Vec2, radius: f32) -> Vec<EntityRef> {
+        let near = |body: &PhysicsBody| {
+            CollisionDetector::aabb_within_radius(body.get_bounds(), center, radius)
+        };
+        let enemies = self
+            .enemies
+            .iter()
+            .filter(|(_, enemy)| near(&enemy.body))
+            .map(|(id, _)| EntityRef::Enemy(id));
+        let bombs = self
+            .bombs
+            .iter()
+            .filter(|(_, bomb)| near(&bomb.body))
+            .map(|(id, _)| EntityRef::Bomb(id));
+        enemies.chain(bombs).collect()
+    }
+
+    /// The physics body of a loose entity, as found by `bodies_within`
+    pub fn body_mut(&mut self, entity: EntityRef) -> Option<&mut PhysicsBody> {
+        match entity {
+            EntityRef::Enemy(id) => self.enemies.get_mut(id).map(|enemy| &mut enemy.body),
+            EntityRef::Bomb(id) => self.bombs.get_mut(id).map(|bomb| &mut bomb.body),
+            _ => None,
+        }
+    }
+
+    /// Apply queued despawns, then spawns; call once the frame's updates are done
+    pub fn apply_pending(&mut self) {
+        for entity in std::mem::take(&mut self.despawns) {
+            self.despawn(entity);
+        }
+        for spawn in std::mem::take(&mut self.spawns) {
+            self.spawn(spawn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_ids_stay_invalid_after_the_slot_is_reused() {
+        let mut arena = Arena::new();
+        let old = arena.insert("old");
+        assert_eq!(arena.remove(old), Some("old"));
+        assert_eq!(arena.get(old), None);
+
+        let new = arena.insert("new");
+        assert_eq!(new.index, old.index);
+        assert_eq!(arena.get(old), None);
+        assert_eq!(arena.get(new), Some(&"new"));
+        // Removing through the stale id leaves the new occupant alone
+        assert_eq!(arena.remove(old), None);
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn clear_invalidates_every_id() {
+        let mut arena: Arena<i32> = (0..3).collect();
+        let ids: Vec<EntityId> = arena.iter().map(|(id, _)| id).collect();
+        arena.clear();
+        assert!(arena.is_empty());
+        for _ in 0..3 {
+            arena.insert(7);
+        }
+        for id in ids {
+            assert_eq!(arena.get(id), None);
+        }
+    }
+
+    #[test]
+    fn pending_despawns_apply_before_spawns() {
+        let mut entities = EntityManager::new();
+        let EntityRef::Platform(old) =
+            entities.spawn(Spawn::Platform(Platform::new(0.0, 0.0, 10.0, 10.0)))
+        else {
+            unreachable!()
+        };
+        entities.queue_spawn(Spawn::Platform(Platform::new(50.0, 0.0, 10.0, 10.0)));
+        entities.queue_despawn(EntityRef::Platform(old));
+        // Nothing changes until the frame ends
+        assert!(entities.platforms.get(old).is_some());
+
+        entities.apply_pending();
+        // The spawn took the slot the despawn freed, so the despawn went first
+        let live: Vec<(EntityId, &Platform)> = entities.platforms.iter().collect();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].0.index, old.index);
+        assert_eq!(live[0].1.body.position.x, 50.0);
+        assert!(entities.platforms.get(old).is_none());
+    }
+}
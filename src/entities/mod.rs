@@ -1,29 +1,157 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::graphics::RenderLayer;
+
+pub mod bomb;
+pub mod boss;
+pub mod boulder;
+pub mod bridge;
 pub mod collectible;
+pub mod cycle;
+pub mod door;
+pub mod enemy;
+pub mod lava;
+pub mod manager;
+pub mod npc;
+pub mod pad;
+pub mod path;
 pub mod platform;
 pub mod player;
+pub mod player_state;
+pub mod prop;
+pub mod rope;
+pub mod skin;
+pub mod terrain;
+pub mod throttle;
+pub mod trigger;
+pub mod water;
+pub mod zipline;
+pub mod zone;
 
+pub use bomb::Bomb;
+pub use boss::{Boss, BossTuning};
+pub use boulder::Boulder;
+pub use bridge::RopeBridge;
 pub use collectible::Collectible;
+pub use cycle::Cycle;
+pub use door::{Door, Switch};
+pub use enemy::{Enemy, EnemyState, Stomp};
+pub use lava::Lava;
+pub use manager::EntityManager;
+pub use npc::Npc;
+pub use pad::Pad;
+pub use path::PlatformPath;
 pub use platform::Platform;
 pub use player::Player;
+pub use prop::{Prop, Props};
+pub use rope::Rope;
+pub use skin::PlayerSkin;
+pub use terrain::Terrain;
+pub use trigger::Trigger;
+pub use water::Water;
+pub use zipline::Zipline;
+pub use zone::TriggerZone;
 
 // Base trait for all entities
 pub trait Entity {
     fn position(&self) -> Vec2;
     fn size(&self) -> Vec2;
     fn render(&self, camera_x: f32, camera_y: f32);
-    fn update(&mut self);
+    fn update(&mut self, delta_time: f32);
+
+    /// Which band of the world it's drawn in
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Entities
+    }
+
+    /// Order within its layer, lowest drawn first
+    fn render_depth(&self) -> f32 {
+        0.0
+    }
+}
+
+/// What a sub-rectangle of a body is used for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoxKind {
+    /// Pushed against platforms and walls
+    Solid,
+    /// Where the entity can be hurt
+    Hurt,
+    /// Where the entity hurts whatever it touches
+    Damage,
+}
+
+/// Which side a body fights on, deciding whose hurt boxes its damage box counts against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Player,
+    Enemy,
+    /// Hurts nobody, e.g. an enemy's empty shell lying still
+    Neutral,
+    /// Hurts friend and foe alike, e.g. a kicked shell
+    Projectile,
+}
+
+impl Layer {
+    /// Whether a body on this layer can hurt one on `target`
+    pub fn hurts(self, target: Layer) -> bool {
+        match self {
+            Layer::Enemy => target == Layer::Player,
+            Layer::Projectile => true,
+            Layer::Player | Layer::Neutral => false,
+        }
+    }
+}
+
+/// A rectangle relative to a body's top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LocalBox {
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+impl LocalBox {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            offset: Vec2::new(x, y),
+            size: Vec2::new(width, height),
+        }
+    }
+}
+
+/// Optional per-purpose boxes; any left unset fall back to the whole body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hitboxes {
+    pub solid: Option<LocalBox>,
+    pub hurt: Option<LocalBox>,
+    pub damage: Option<LocalBox>,
+}
+
+impl Hitboxes {
+    pub fn get(&self, kind: BoxKind) -> Option<LocalBox> {
+        match kind {
+            BoxKind::Solid => self.solid,
+            BoxKind::Hurt => self.hurt,
+            BoxKind::Damage => self.damage,
+        }
+    }
 }
 
 // Common physics properties for entities
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhysicsBody {
     pub position: Vec2,
     pub velocity: Vec2,
     pub size: Vec2,
     pub on_ground: bool,
     pub mass: f32,
+    #[serde(default)]
+    pub hitboxes: Hitboxes,
+    /// Where the body was before the last simulation step, so frames drawn between steps
+    /// can be placed part way; `None` after a teleport so it isn't smeared across the jump
+    #[serde(skip)]
+    pub previous_position: Option<Vec2>,
 }
 
 impl PhysicsBody {
@@ -34,6 +162,32 @@ impl PhysicsBody {
             size: Vec2::new(width, height),
             on_ground: false,
             mass: 1.0,
+            hitboxes: Hitboxes::default(),
+            previous_position: None,
+        }
+    }
+
+    pub fn with_hitboxes(mut self, hitboxes: Hitboxes) -> Self {
+        self.hitboxes = hitboxes;
+        self
+    }
+
+    /// Remember where the body is before a simulation step moves it
+    pub fn store_previous(&mut self) {
+        self.previous_position = Some(self.position);
+    }
+
+    /// Forget the previous position after moving the body somewhere it didn't travel to
+    pub fn reset_interpolation(&mut self) {
+        self.previous_position = None;
+    }
+
+    /// How far from `position` to draw the body when `alpha` of the next step has passed.
+    /// Bodies are drawn between their last two positions, so a step behind.
+    pub fn render_offset(&self, alpha: f32) -> Vec2 {
+        match self.previous_position {
+            Some(previous) => (previous - self.position) * (1.0 - alpha),
+            None => Vec2::ZERO,
         }
     }
 
@@ -46,10 +200,30 @@ impl PhysicsBody {
         )
     }
 
+    /// World bounds of one of the body's boxes, or of the whole body if it doesn't have one
+    pub fn box_bounds(&self, kind: BoxKind) -> (f32, f32, f32, f32) {
+        match self.hitboxes.get(kind) {
+            Some(local) => {
+                let min = self.position + local.offset;
+                let max = min + local.size;
+                (min.x, min.y, max.x, max.y)
+            }
+            None => self.get_bounds(),
+        }
+    }
+
     pub fn overlaps_with(&self, other: &PhysicsBody) -> bool {
         let (x1, y1, x2, y2) = self.get_bounds();
         let (ox1, oy1, ox2, oy2) = other.get_bounds();
 
         x1 < ox2 && x2 > ox1 && y1 < oy2 && y2 > oy1
     }
+
+    /// Whether this body's damage box touches the other's hurt box
+    pub fn damages(&self, other: &PhysicsBody) -> bool {
+        let (x1, y1, x2, y2) = self.box_bounds(BoxKind::Damage);
+        let (ox1, oy1, ox2, oy2) = other.box_bounds(BoxKind::Hurt);
+
+        x1 < ox2 && x2 > ox1 && y1 < oy2 && y2 > oy1
+    }
 }
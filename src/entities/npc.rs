@@ -0,0 +1,81 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, TextStyle};
+use crate::locale::tr;
+use crate::physics::collision::CollisionDetector;
+
+/// A character standing around in the level with something to say
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Npc {
+    /// Id from the level file, which cutscenes move it by
+    #[serde(default)]
+    pub id: String,
+    pub body: PhysicsBody,
+    /// Dialogue pages, shown one at a time
+    pub pages: Vec<String>,
+    pub animation_time: f32,
+}
+
+impl Npc {
+    /// Create an NPC standing on a surface whose top edge is at `surface_y`
+    pub fn new(x: f32, surface_y: f32, pages: Vec<String>) -> Self {
+        let (width, height) = GameConfig::NPC_SIZE;
+        Self {
+            id: String::new(),
+            body: PhysicsBody::new(x, surface_y - height, width, height),
+            pages,
+            animation_time: 0.0,
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Whether the body is close enough to start talking
+    pub fn in_talk_range(&self, body: &PhysicsBody) -> bool {
+        CollisionDetector::within_distance(&self.body, body, GameConfig::NPC_TALK_RANGE)
+    }
+
+    /// "Press Enter to talk" floating over the NPC's head
+    pub fn render_prompt(&self, camera_x: f32, camera_y: f32) {
+        let x = self.body.position.x + self.body.size.x / 2.0 + camera_x;
+        let y = self.body.position.y + camera_y - 16.0;
+        GraphicsUtils::draw_text_centered(&tr("npc.talk"), x, y, TextStyle::HudSmall.size(), WHITE);
+    }
+}
+
+impl Entity for Npc {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        // A gentle bob so they look alive
+        let bob = (style.animation_time(self.animation_time) * 2.0).sin() * 1.5;
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y + bob;
+
+        let head = w * 0.5;
+        let robe = Color::new(0.55, 0.35, 0.7, 1.0);
+        draw_rectangle(x, y + head, w, h - head, robe);
+        style.outline_rectangle(x, y + head, w, h - head);
+        draw_circle(x + w / 2.0, y + head / 2.0, head / 2.0 + 2.0, BEIGE);
+        draw_rectangle(x + w * 0.35, y + head * 0.4, 2.0, 2.0, BLACK);
+        draw_rectangle(x + w * 0.6, y + head * 0.4, 2.0, 2.0, BLACK);
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.animation_time += delta_time;
+    }
+}
@@ -0,0 +1,216 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Cycle, Entity, Hitboxes, LocalBox, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{RenderLayer, RenderStyle};
+
+/// A pad sitting on top of a platform that launches or hurts the player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pad {
+    pub body: PhysicsBody,
+    pub pad_kind: PadKind,
+    pub animation_time: f32,
+    /// Time since the pad last launched something, drives the spring compression
+    pub triggered_time: f32,
+    /// When timed spikes are out; without one they always are
+    #[serde(default)]
+    pub cycle: Option<Cycle>,
+    /// Level clock beat the pad was last brought up to
+    #[serde(skip)]
+    beat: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PadKind {
+    /// Pushes the player horizontally; direction is -1.0 (left) or 1.0 (right)
+    Boost { direction: f32 },
+    /// Bounces the player upward while keeping their horizontal speed
+    Spring,
+    /// A row of spikes that ends the run on contact
+    Spikes,
+}
+
+impl Pad {
+    /// Create a pad resting on a surface whose top edge is at `surface_y`
+    pub fn new(x: f32, surface_y: f32, pad_kind: PadKind) -> Self {
+        let (width, height) = match pad_kind {
+            PadKind::Boost { .. } => GameConfig::BOOST_PAD_SIZE,
+            PadKind::Spring => GameConfig::SPRING_SIZE,
+            PadKind::Spikes => GameConfig::SPIKES_SIZE,
+        };
+
+        let mut body = PhysicsBody::new(x, surface_y - height, width, height);
+        if pad_kind == PadKind::Spikes {
+            // Only the solid middle of the triangles hurts, not the gaps between the tips
+            let tip = height * 0.4;
+            body.hitboxes = Hitboxes {
+                damage: Some(LocalBox::new(2.0, tip, width - 4.0, height - tip)),
+                ..Hitboxes::default()
+            };
+        }
+
+        Self {
+            body,
+            pad_kind,
+            animation_time: 0.0,
+            triggered_time: f32::MAX,
+            cycle: None,
+            beat: 0.0,
+        }
+    }
+
+    /// Check whether a body is touching the pad in a way that should set it off
+    pub fn is_triggered_by(&self, body: &PhysicsBody) -> bool {
+        if !self.body.overlaps_with(body) {
+            return false;
+        }
+
+        match self.pad_kind {
+            // Boost pads only work when running across them, not flying over
+            PadKind::Boost { .. } => body.on_ground,
+            // Springs fire when landed on from above
+            PadKind::Spring => body.velocity.y >= 0.0,
+            // Spikes don't launch anything; see `hurts`
+            PadKind::Spikes => false,
+        }
+    }
+
+    /// Make timed spikes come out and go back in on `cycle`
+    pub fn with_cycle(mut self, cycle: Cycle) -> Self {
+        self.cycle = Some(cycle);
+        self
+    }
+
+    /// Catch up with the level clock
+    pub fn keep_time(&mut self, beat: f32) {
+        self.beat = beat;
+    }
+
+    /// Whether timed spikes are out, or always for untimed ones
+    pub fn is_extended(&self) -> bool {
+        self.cycle.is_none_or(|cycle| cycle.is_on(self.beat))
+    }
+
+    /// Whether the pad's damage box is touching the body's hurt box
+    pub fn hurts(&self, body: &PhysicsBody) -> bool {
+        self.pad_kind == PadKind::Spikes && self.is_extended() && self.body.damages(body)
+    }
+
+    pub fn mark_triggered(&mut self) {
+        self.triggered_time = 0.0;
+    }
+
+    fn render_boost(&self, x: f32, y: f32, direction: f32) {
+        let (w, h) = (self.body.size.x, self.body.size.y);
+
+        draw_rectangle(x, y, w, h, Color::new(0.1, 0.1, 0.2, 1.0));
+        draw_rectangle_lines(x, y, w, h, 1.0, DARKGRAY);
+
+        // Chevrons scroll in the boost direction
+        let spacing = 12.0;
+        let time = RenderStyle::current().animation_time(self.animation_time);
+        let scroll = (time * GameConfig::BOOST_PAD_SCROLL_SPEED) % spacing;
+        let mut offset = -spacing + scroll;
+        while offset < w {
+            let cx = if direction > 0.0 {
+                x + offset
+            } else {
+                x + w - offset
+            };
+            if cx > x + 2.0 && cx < x + w - 2.0 {
+                let fade = 0.5 + 0.5 * (offset / w);
+                let color = Color::new(0.2, 0.9, 1.0, fade.clamp(0.3, 1.0));
+                let tip = cx + direction * 4.0;
+                draw_line(cx, y + 1.0, tip, y + h / 2.0, 2.0, color);
+                draw_line(tip, y + h / 2.0, cx, y + h - 1.0, 2.0, color);
+            }
+            offset += spacing;
+        }
+    }
+
+    fn render_spring(&self, x: f32, y: f32) {
+        let (w, h) = (self.body.size.x, self.body.size.y);
+
+        // Squash the coil briefly after launching
+        let squash = if self.triggered_time < 0.2 {
+            0.5 + 0.5 * (self.triggered_time / 0.2)
+        } else {
+            1.0
+        };
+        let coil_height = (h - 4.0) * squash;
+        let base_y = y + h;
+        let top_y = base_y - coil_height - 4.0;
+
+        // Coil zigzag
+        let turns = 4;
+        for i in 0..turns {
+            let y0 = base_y - coil_height * i as f32 / turns as f32;
+            let y1 = base_y - coil_height * (i + 1) as f32 / turns as f32;
+            let (x0, x1) = if i % 2 == 0 {
+                (x + 4.0, x + w - 4.0)
+            } else {
+                (x + w - 4.0, x + 4.0)
+            };
+            draw_line(x0, y0, x1, y1, 2.0, LIGHTGRAY);
+        }
+
+        // Top plate
+        draw_rectangle(x, top_y, w, 4.0, RenderStyle::current().palette().danger);
+        draw_rectangle_lines(x, top_y, w, 4.0, 1.0, MAROON);
+    }
+
+    fn render_spikes(&self, x: f32, y: f32) {
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        let style = RenderStyle::current();
+        let color = style.pick(style.palette().danger, GameConfig::HIGH_CONTRAST_HAZARD);
+        let spike_width = w / GameConfig::SPIKE_COUNT as f32;
+        // Retracted spikes show just their stubs, and shiver before going back in
+        let (tip_y, color) = if !self.is_extended() {
+            (y + h * 0.75, Color::new(color.r, color.g, color.b, 0.5))
+        } else if self.cycle.is_some_and(|cycle| cycle.is_ending(self.beat)) {
+            (y + (self.beat * 40.0).sin().abs() * 2.0, color)
+        } else {
+            (y, color)
+        };
+
+        for i in 0..GameConfig::SPIKE_COUNT {
+            let left = x + i as f32 * spike_width;
+            let tip = Vec2::new(left + spike_width / 2.0, tip_y);
+            let base_left = Vec2::new(left, y + h);
+            let base_right = Vec2::new(left + spike_width, y + h);
+            draw_triangle(tip, base_left, base_right, color);
+            draw_triangle_lines(tip, base_left, base_right, 1.0, MAROON);
+        }
+    }
+}
+
+impl Entity for Pad {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let render_x = self.body.position.x + camera_x;
+        let render_y = self.body.position.y + camera_y;
+
+        match self.pad_kind {
+            PadKind::Boost { direction } => self.render_boost(render_x, render_y, direction),
+            PadKind::Spring => self.render_spring(render_x, render_y),
+            PadKind::Spikes => self.render_spikes(render_x, render_y),
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.animation_time += delta_time;
+        self.triggered_time += delta_time;
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Platforms
+    }
+}
@@ -0,0 +1,331 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::graphics::GraphicsUtils;
+
+/// What a platform does when it reaches the end of its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathMode {
+    /// Carry on from the last waypoint back round to the first
+    Loop,
+    /// Turn around and go back the way it came
+    PingPong,
+}
+
+impl PathMode {
+    /// Look up a mode by the name used in level files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "loop" => Some(PathMode::Loop),
+            "pingpong" => Some(PathMode::PingPong),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PathMode::Loop => "loop",
+            PathMode::PingPong => "pingpong",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            PathMode::Loop => PathMode::PingPong,
+            PathMode::PingPong => PathMode::Loop,
+        }
+    }
+}
+
+/// Waypoints a moving platform travels through at a steady speed, however far apart they
+/// are. The path is flattened into short straight pieces up front, so moving along it is
+/// a matter of distance rather than of which stretch the platform is on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformPath {
+    /// Where the platform's top-left corner passes through, in order
+    pub waypoints: Vec<Vec2>,
+    /// Pixels per second along the path
+    pub speed: f32,
+    pub mode: PathMode,
+    /// Curve through the waypoints instead of going straight from one to the next
+    pub smooth: bool,
+    /// The path as a line of points, with the distance along it at each
+    points: Vec<Vec2>,
+    lengths: Vec<f32>,
+    /// How far along the path the platform is
+    pub distance: f32,
+    /// 1 heading forwards, -1 on the way back of a ping-pong
+    pub heading: f32,
+}
+
+impl PlatformPath {
+    pub fn new(waypoints: Vec<Vec2>, speed: f32, mode: PathMode, smooth: bool) -> Self {
+        let points = flatten(&waypoints, mode, smooth);
+        let mut lengths = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                total += points[i - 1].distance(*point);
+            }
+            lengths.push(total);
+        }
+        Self {
+            waypoints,
+            speed,
+            mode,
+            smooth,
+            points,
+            lengths,
+            distance: 0.0,
+            heading: 1.0,
+        }
+    }
+
+    /// Length of one trip from the first waypoint to the last, or back round to the first
+    /// for a loop
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Position at `distance` along the path, clamped to its ends
+    pub fn position_at(&self, distance: f32) -> Vec2 {
+        if self.points.len() < 2 {
+            return self.points.first().copied().unwrap_or_default();
+        }
+        let distance = distance.clamp(0.0, self.length());
+        let next = self
+            .lengths
+            .partition_point(|&length| length < distance)
+            .clamp(1, self.points.len() - 1);
+        let (start, end) = (self.lengths[next - 1], self.lengths[next]);
+        let t = if end > start {
+            (distance - start) / (end - start)
+        } else {
+            0.0
+        };
+        self.points[next - 1].lerp(self.points[next], t)
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position_at(self.distance)
+    }
+
+    /// Travel along the path for a step, wrapping round a loop or bouncing back off the
+    /// ends of a ping-pong, and return where the platform is now
+    pub fn advance(&mut self, delta_time: f32) -> Vec2 {
+        let length = self.length();
+        if length <= 0.0 {
+            return self.position();
+        }
+        let step = self.speed * delta_time;
+        match self.mode {
+            PathMode::Loop => {
+                self.distance = (self.distance + self.heading * step).rem_euclid(length);
+            }
+            PathMode::PingPong => {
+                // There and back again is a loop twice the length, so any overshoot is
+                // carried back the other way, however long the step
+                let looped = if self.heading > 0.0 {
+                    self.distance
+                } else {
+                    2.0 * length - self.distance
+                };
+                let looped = (looped + step).rem_euclid(2.0 * length);
+                if looped > length {
+                    self.distance = 2.0 * length - looped;
+                    self.heading = -1.0;
+                } else {
+                    self.distance = looped;
+                    self.heading = 1.0;
+                }
+            }
+        }
+        self.position()
+    }
+
+    /// Draw the path as a dotted line, through the middle of a platform of `size`
+    pub fn render(&self, size: Vec2, camera_x: f32, camera_y: f32) {
+        let offset = size / 2.0 + Vec2::new(camera_x, camera_y);
+        let points: Vec<Vec2> = self.points.iter().map(|&point| point + offset).collect();
+        GraphicsUtils::draw_dotted_path(
+            &points,
+            GameConfig::PLATFORM_PATH_DOT_SPACING,
+            2.0,
+            GameConfig::PLATFORM_PATH_COLOR,
+        );
+        for &waypoint in &self.waypoints {
+            let waypoint = waypoint + offset;
+            draw_circle_lines(
+                waypoint.x,
+                waypoint.y,
+                4.0,
+                1.0,
+                GameConfig::PLATFORM_PATH_COLOR,
+            );
+        }
+    }
+}
+
+/// Points along the path through `waypoints`, ending back at the first for a loop.
+/// Smoothing follows a Catmull-Rom curve, which passes through every waypoint.
+fn flatten(waypoints: &[Vec2], mode: PathMode, smooth: bool) -> Vec<Vec2> {
+    let count = waypoints.len();
+    if count < 2 {
+        return waypoints.to_vec();
+    }
+    let closed = mode == PathMode::Loop;
+    let waypoint = |i: isize| {
+        if closed {
+            waypoints[i.rem_euclid(count as isize) as usize]
+        } else {
+            waypoints[i.clamp(0, count as isize - 1) as usize]
+        }
+    };
+
+    let stretches = if closed { count } else { count - 1 };
+    let mut points = Vec::new();
+    for i in 0..stretches as isize {
+        if !smooth {
+            points.push(waypoint(i));
+            continue;
+        }
+        let steps = GameConfig::PLATFORM_PATH_STEPS;
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            points.push(catmull_rom(
+                waypoint(i - 1),
+                waypoint(i),
+                waypoint(i + 1),
+                waypoint(i + 2),
+                t,
+            ));
+        }
+    }
+    points.push(waypoint(stretches as isize));
+    points
+}
+
+/// Point `t` of the way from `p1` to `p2` on a curve shaped by their neighbours
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A short stretch then a long one, both along the x axis: 100 pixels in all
+    fn uneven(mode: PathMode) -> PlatformPath {
+        let waypoints = vec![Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(100.0, 0.0)];
+        PlatformPath::new(waypoints, 50.0, mode, false)
+    }
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-3, "{value} != {expected}");
+    }
+
+    #[test]
+    fn position_is_found_by_distance_along_the_path() {
+        let path = uneven(PathMode::PingPong);
+        assert_near(path.length(), 100.0);
+        assert_eq!(path.position_at(5.0), Vec2::new(5.0, 0.0));
+        assert_eq!(path.position_at(55.0), Vec2::new(55.0, 0.0));
+        // Clamped to the ends
+        assert_eq!(path.position_at(-20.0), Vec2::ZERO);
+        assert_eq!(path.position_at(250.0), Vec2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn straight_paths_are_crossed_at_a_steady_speed() {
+        let mut path = uneven(PathMode::PingPong);
+        let mut last = path.position();
+        // 50 pixels a second, 5 a step, on the short stretch and the long one alike
+        for _ in 0..19 {
+            let now = path.advance(0.1);
+            assert_near(now.distance(last), 5.0);
+            last = now;
+        }
+    }
+
+    #[test]
+    fn smooth_paths_are_crossed_at_a_steady_speed() {
+        let waypoints = vec![
+            Vec2::ZERO,
+            Vec2::new(40.0, -60.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(220.0, 100.0),
+        ];
+        let mut path = PlatformPath::new(waypoints, 120.0, PathMode::Loop, true);
+        let steps = (path.length() / 2.0) as usize;
+        let mut last = path.position();
+        for step in 1..=steps {
+            let now = path.advance(1.0 / 60.0);
+            // 2 pixels along the curve each step, wherever the waypoints are
+            assert_near(path.distance, step as f32 * 2.0);
+            // A straight line between steps cuts the corner a little where it bends
+            let moved = now.distance(last);
+            assert!((1.9..=2.0 + 1e-3).contains(&moved), "moved {moved}");
+            last = now;
+        }
+    }
+
+    #[test]
+    fn ping_pong_turns_back_at_the_far_end() {
+        let mut path = uneven(PathMode::PingPong);
+        path.distance = 90.0;
+        path.advance(0.4);
+        // 20 pixels on from 90: 10 to the end and 10 back
+        assert_near(path.distance, 90.0);
+        assert_eq!(path.heading, -1.0);
+        path.advance(0.4);
+        assert_near(path.distance, 70.0);
+    }
+
+    #[test]
+    fn ping_pong_turns_back_at_the_start() {
+        let mut path = uneven(PathMode::PingPong);
+        path.distance = 10.0;
+        path.heading = -1.0;
+        path.advance(0.6);
+        assert_near(path.distance, 20.0);
+        assert_eq!(path.heading, 1.0);
+    }
+
+    #[test]
+    fn ping_pong_reaches_each_end_exactly() {
+        let mut path = uneven(PathMode::PingPong);
+        path.advance(2.0);
+        assert_eq!(path.position(), Vec2::new(100.0, 0.0));
+        path.advance(2.0);
+        assert_eq!(path.position(), Vec2::ZERO);
+        assert_eq!(path.heading, 1.0);
+    }
+
+    #[test]
+    fn long_steps_bounce_as_many_times_as_they_cover() {
+        let mut path = uneven(PathMode::PingPong);
+        path.distance = 30.0;
+        // 260 pixels: 70 on to the end, all 100 the way back, and 90 out again
+        path.advance(5.2);
+        assert_near(path.distance, 90.0);
+        assert_eq!(path.heading, 1.0);
+    }
+
+    #[test]
+    fn loops_wrap_round_to_the_start() {
+        let waypoints = vec![Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0)];
+        let mut path = PlatformPath::new(waypoints, 50.0, PathMode::Loop, false);
+        // Back to the first waypoint closes the loop
+        assert_near(path.length(), 200.0 + 100.0 * 2f32.sqrt());
+        path.distance = path.length() - 10.0;
+        path.advance(0.5);
+        assert_near(path.distance, 15.0);
+        assert_eq!(path.heading, 1.0);
+    }
+}
@@ -1,15 +1,34 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use super::{Entity, PhysicsBody};
+use super::throttle::Throttle;
+use super::{Cycle, Entity, PhysicsBody, PlatformPath};
+use crate::animation::{tween, Easing, Timeline};
+use crate::config::GameConfig;
+use crate::graphics::{RenderLayer, RenderStyle};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Platform {
     pub body: PhysicsBody,
-    pub color: Color,
     pub platform_type: PlatformType,
+    /// Route a moving platform follows; without one it stays put
+    #[serde(default)]
+    pub path: Option<PlatformPath>,
+    /// When a timed platform is there; without one it always is
+    #[serde(default)]
+    pub cycle: Option<Cycle>,
+    /// Level clock beat the platform was last brought up to
+    #[serde(skip)]
+    beat: f32,
+    /// Sideways wobble while it plays, such as a breakable platform being landed on
+    #[serde(skip)]
+    shake: Option<Timeline<f32>>,
+    /// How closely it's simulated while far from the view
+    #[serde(skip)]
+    pub throttle: Throttle,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PlatformType {
     Ground,
     Normal,
@@ -17,51 +36,182 @@ pub enum PlatformType {
     Moving,
 }
 
+impl PlatformType {
+    /// Look up a platform type by the name used in level files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ground" => Some(PlatformType::Ground),
+            "normal" => Some(PlatformType::Normal),
+            "breakable" => Some(PlatformType::Breakable),
+            "moving" => Some(PlatformType::Moving),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlatformType::Ground => "ground",
+            PlatformType::Normal => "normal",
+            PlatformType::Breakable => "breakable",
+            PlatformType::Moving => "moving",
+        }
+    }
+
+    /// The type after this one, wrapping around
+    pub fn next(&self) -> Self {
+        match self {
+            PlatformType::Normal => PlatformType::Ground,
+            PlatformType::Ground => PlatformType::Breakable,
+            PlatformType::Breakable => PlatformType::Moving,
+            PlatformType::Moving => PlatformType::Normal,
+        }
+    }
+}
+
 impl Platform {
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self {
             body: PhysicsBody::new(x, y, width, height),
-            color: GREEN,
             platform_type: PlatformType::Normal,
+            path: None,
+            cycle: None,
+            beat: 0.0,
+            shake: None,
+            throttle: Throttle::default(),
         }
     }
 
     pub fn new_ground(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self {
             body: PhysicsBody::new(x, y, width, height),
-            color: BROWN,
             platform_type: PlatformType::Ground,
+            path: None,
+            cycle: None,
+            beat: 0.0,
+            shake: None,
+            throttle: Throttle::default(),
         }
     }
 
     pub fn new_breakable(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self {
             body: PhysicsBody::new(x, y, width, height),
-            color: ORANGE,
             platform_type: PlatformType::Breakable,
+            path: None,
+            cycle: None,
+            beat: 0.0,
+            shake: None,
+            throttle: Throttle::default(),
+        }
+    }
+
+    pub fn new_of_type(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        platform_type: PlatformType,
+    ) -> Self {
+        match platform_type {
+            PlatformType::Ground => Self::new_ground(x, y, width, height),
+            PlatformType::Normal => Self::new(x, y, width, height),
+            PlatformType::Breakable => Self::new_breakable(x, y, width, height),
+            PlatformType::Moving => Self {
+                body: PhysicsBody::new(x, y, width, height),
+                platform_type,
+                path: None,
+                cycle: None,
+                beat: 0.0,
+                shake: None,
+                throttle: Throttle::default(),
+            },
         }
     }
 
+    /// Send the platform along `path`, starting from wherever the path is at
+    pub fn with_path(mut self, path: PlatformPath) -> Self {
+        self.body.position = path.position();
+        self.path = Some(path);
+        self
+    }
+
+    /// Make the platform come and go on `cycle`
+    pub fn with_cycle(mut self, cycle: Cycle) -> Self {
+        self.cycle = Some(cycle);
+        self
+    }
+
+    /// Catch up with the level clock
+    pub fn keep_time(&mut self, beat: f32) {
+        self.beat = beat;
+    }
+
+    /// Whether the platform is there to stand on; timed ones come and go
+    pub fn is_solid(&self) -> bool {
+        self.cycle.is_none_or(|cycle| cycle.is_on(self.beat))
+    }
+
+    /// Beats until a timed platform that's there now vanishes
+    pub fn until_vanishing(&self) -> Option<f32> {
+        self.cycle
+            .filter(|cycle| cycle.is_on(self.beat))
+            .map(|cycle| cycle.until_switch(self.beat))
+    }
+
+    /// Wobble side to side for a moment
+    pub fn shake(&mut self) {
+        let distance = GameConfig::PLATFORM_SHAKE_DISTANCE;
+        let step = GameConfig::PLATFORM_SHAKE_STEP;
+        self.shake = Some(
+            tween(0.0, distance, step, Easing::EaseOut)
+                .then(tween(distance, -distance, step * 2.0, Easing::EaseInOut))
+                .then(tween(
+                    -distance,
+                    distance / 2.0,
+                    step * 2.0,
+                    Easing::EaseInOut,
+                ))
+                .then(tween(distance / 2.0, 0.0, step, Easing::EaseIn)),
+        );
+    }
+
+    /// Play the platform's animations for a step
+    pub fn animate(&mut self, delta_time: f32) {
+        if let Some(shake) = &mut self.shake {
+            shake.update(delta_time);
+            if shake.is_finished() {
+                self.shake = None;
+            }
+        }
+    }
+
+    /// Follow the path for a step, returning how far the platform moved
+    pub fn travel(&mut self, delta_time: f32) -> Vec2 {
+        let Some(path) = &mut self.path else {
+            return Vec2::ZERO;
+        };
+        let before = self.body.position;
+        self.body.position = path.advance(delta_time);
+        let moved = self.body.position - before;
+        if delta_time > 0.0 {
+            self.body.velocity = moved / delta_time;
+        }
+        moved
+    }
+
     pub fn get_bounds(&self) -> (f32, f32, f32, f32) {
         self.body.get_bounds()
     }
 
-    pub fn overlaps_with(&self, other: &PhysicsBody) -> bool {
-        self.body.overlaps_with(other)
+    /// Fill color for this platform type in the active palette
+    pub fn color(&self) -> Color {
+        RenderStyle::current()
+            .palette()
+            .platform_color(&self.platform_type)
     }
 
-    // Check if an entity is landing on top of this platform
-    pub fn is_landing_on(&self, entity_body: &PhysicsBody, entity_prev_y: f32) -> bool {
-        let (_, platform_top, _, _) = self.get_bounds();
-        let (_, entity_bottom, _, _) = entity_body.get_bounds();
-
-        // Entity is landing if:
-        // 1. It's overlapping horizontally
-        // 2. Entity's bottom is touching or slightly below platform top
-        // 3. Entity was above the platform in the previous frame
-        entity_prev_y + entity_body.size.y <= platform_top + 5.0
-            && entity_bottom >= platform_top - 5.0
-            && entity_body.velocity.y >= 0.0
+    pub fn overlaps_with(&self, other: &PhysicsBody) -> bool {
+        self.body.overlaps_with(other)
     }
 }
 
@@ -75,16 +225,40 @@ impl Entity for Platform {
     }
 
     fn render(&self, camera_x: f32, camera_y: f32) {
-        let render_x = self.body.position.x + camera_x;
+        let style = RenderStyle::current();
+        let shake = match &self.shake {
+            Some(shake) if !style.reduced_motion => shake.value(),
+            _ => 0.0,
+        };
+        let render_x = self.body.position.x + camera_x + shake;
         let render_y = self.body.position.y + camera_y;
 
+        // A timed platform that's away leaves a faint outline of where it will be, and
+        // flickers for a moment before it goes
+        if let Some(cycle) = self.cycle {
+            if !cycle.is_on(self.beat) {
+                draw_rectangle_lines(
+                    render_x,
+                    render_y,
+                    self.body.size.x,
+                    self.body.size.y,
+                    1.0,
+                    Color::new(1.0, 1.0, 1.0, 0.3),
+                );
+                return;
+            }
+            if cycle.is_ending(self.beat) && (self.beat * 8.0).fract() < 0.5 {
+                return;
+            }
+        }
+
         // Draw platform
         draw_rectangle(
             render_x,
             render_y,
             self.body.size.x,
             self.body.size.y,
-            self.color,
+            style.pick(self.color(), GameConfig::HIGH_CONTRAST_PLATFORM),
         );
 
         // Draw platform border
@@ -96,6 +270,7 @@ impl Entity for Platform {
             2.0,
             DARKGRAY,
         );
+        style.outline_rectangle(render_x, render_y, self.body.size.x, self.body.size.y);
 
         // Add visual indicators based on platform type
         match self.platform_type {
@@ -103,7 +278,14 @@ impl Entity for Platform {
                 // Draw grass texture on top
                 for i in 0..((self.body.size.x / 8.0) as i32) {
                     let grass_x = render_x + (i as f32) * 8.0;
-                    draw_line(grass_x, render_y - 2.0, grass_x, render_y - 8.0, 2.0, LIME);
+                    draw_line(
+                        grass_x,
+                        render_y - 2.0,
+                        grass_x,
+                        render_y - 8.0,
+                        2.0,
+                        style.palette().grass,
+                    );
                 }
             }
             PlatformType::Breakable => {
@@ -142,15 +324,13 @@ impl Entity for Platform {
         }
     }
 
-    fn update(&mut self) {
-        // Platforms are generally static, but moving platforms would update here
-        match self.platform_type {
-            PlatformType::Moving => {
-                // TODO: Implement moving platform logic
-            }
-            _ => {
-                // Static platforms don't need updates
-            }
-        }
+    fn update(&mut self, delta_time: f32) {
+        // Only moving platforms with a path go anywhere
+        self.travel(delta_time);
+        self.animate(delta_time);
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Platforms
     }
 }
@@ -1,54 +1,769 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use super::{Entity, PhysicsBody};
+use std::collections::VecDeque;
 
-#[derive(Debug, Clone)]
+use super::manager::EntityId;
+use super::player_state::{Intent, PlayerState};
+use super::rope::RopeSwing;
+use super::skin::{Face, PlayerSkin};
+use super::zipline::ZiplineRide;
+use super::{Entity, Hitboxes, LocalBox, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{RenderLayer, RenderStyle, TrailColor, TrailConfig, TrailRenderer};
+use crate::physics::launch::{launch, MomentumRule};
+
+/// How hard the player hit the ground
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Landing {
+    Soft,
+    /// Squash and a puff of dust
+    Hard,
+    /// Hard, plus a moment of being unable to move
+    Heavy,
+    /// Far enough to be fatal when fall damage is on
+    Fatal,
+}
+
+impl Landing {
+    /// Sort a landing by impact speed and by how far the player dropped
+    pub fn classify(impact_speed: f32, drop: f32, fall_damage: bool) -> Self {
+        if fall_damage && drop >= GameConfig::FALL_DAMAGE_HEIGHT {
+            Landing::Fatal
+        } else if drop >= GameConfig::HEAVY_LANDING_HEIGHT {
+            Landing::Heavy
+        } else if impact_speed >= GameConfig::LANDING_SQUASH_SPEED {
+            Landing::Hard
+        } else {
+            Landing::Soft
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub body: PhysicsBody,
     pub move_speed: f32,
     pub jump_force: f32,
     pub max_jump_count: u32,
     pub current_jump_count: u32,
-    pub color: Color,
+    pub zipline: Option<ZiplineRide>,
+    pub zipline_cooldown: f32,
+    #[serde(default)]
+    pub swing: Option<RopeSwing>,
+    /// The rope last let go of, which can't be grabbed again until the cooldown runs out
+    #[serde(default)]
+    pub last_rope: Option<EntityId>,
+    #[serde(default)]
+    pub rope_cooldown: f32,
+    /// Extra speed above move_speed granted by boost pads, decays over time
+    pub speed_boost: f32,
+    pub boost_direction: f32,
+    /// Sideways speed kept from a moving platform jumped off. Steering and friction work
+    /// on top of it until the player lands or takes hold of something.
+    #[serde(default)]
+    pub carried: f32,
+    pub sprinting: bool,
+    /// The platform last landed on, while the player is still grounded
+    pub standing_on: Option<EntityId>,
+    /// Which way (-1 or 1) the player is hanging over a ledge, 0 when they aren't
+    #[serde(skip)]
+    pub teeter: f32,
+    /// Highest point (smallest y) reached since last standing on something
+    #[serde(default)]
+    pub fall_peak_y: f32,
+    /// What the player is doing. Only changed through `set_state`, which runs the
+    /// enter and exit effects and keeps `body.on_ground` in step.
+    #[serde(default)]
+    state: PlayerState,
+    /// Seconds until the player can dash again
+    #[serde(default)]
+    dash_cooldown: f32,
+    /// Whether the one dash allowed per trip through the air has been spent
+    #[serde(default)]
+    air_dash_used: bool,
+    /// Seconds steering is ignored after a wall jump
+    #[serde(default)]
+    wall_jump_lock: f32,
+    /// Seconds until a ledge can be grabbed again after letting go of one
+    #[serde(default)]
+    ledge_cooldown: f32,
+    /// Whether a wall was touched in the last step, which a wall slide needs to carry on
+    #[serde(skip)]
+    wall_contact: bool,
+    /// Seconds left of the landing squash
+    #[serde(skip)]
+    pub squash: f32,
+    /// Chosen in the skins menu, so it isn't part of a saved run
+    #[serde(skip, default = "PlayerSkin::classic")]
+    pub skin: &'static PlayerSkin,
+    /// Recent centers, oldest first, for the skin's trail
+    #[serde(skip)]
+    pub trail: VecDeque<Vec2>,
+    /// Which way (-1 or 1) the player last steered. Only input changes it, so being
+    /// knocked back or carried by a boost doesn't turn them around.
+    #[serde(default = "facing_right")]
+    facing: f32,
+    /// Seconds left of the melee swipe's hitbox
+    #[serde(default)]
+    pub attack: f32,
+    /// Seconds until the player can swipe again
+    #[serde(default)]
+    pub attack_cooldown: f32,
+    /// Streak left behind while boosted or in bullet time
+    #[serde(skip, default = "Player::motion_trail")]
+    pub motion_trail: TrailRenderer,
+    /// Pixels walked since the last footstep
+    #[serde(skip)]
+    pub stride: f32,
+    /// Seconds spent standing still with nothing pressed, for the idle animation
+    #[serde(skip)]
+    idle: f32,
+}
+
+/// Render-only touches for a player left standing still
+struct IdlePose {
+    face: Face,
+    /// Pixels the body is drawn up or down from where it is
+    bob: f32,
+    /// From 0 standing to 1 seated
+    sit: f32,
+}
+
+fn facing_right() -> f32 {
+    1.0
 }
 
 impl Player {
     pub fn new(x: f32, y: f32) -> Self {
         Self {
-            body: PhysicsBody::new(x, y, 32.0, 32.0),
+            // The feet are the damage box, so only landing on something can stomp it
+            body: PhysicsBody::new(x, y, 32.0, 32.0).with_hitboxes(Hitboxes {
+                damage: Some(LocalBox::new(
+                    4.0,
+                    32.0 - GameConfig::PLAYER_FOOT_HEIGHT,
+                    24.0,
+                    GameConfig::PLAYER_FOOT_HEIGHT,
+                )),
+                ..Hitboxes::default()
+            }),
             move_speed: 200.0,
             jump_force: -400.0,
             max_jump_count: 2, // Allow double jump
             current_jump_count: 0,
-            color: BLUE,
+            zipline: None,
+            zipline_cooldown: 0.0,
+            swing: None,
+            last_rope: None,
+            rope_cooldown: 0.0,
+            speed_boost: 0.0,
+            boost_direction: 0.0,
+            carried: 0.0,
+            sprinting: false,
+            standing_on: None,
+            teeter: 0.0,
+            fall_peak_y: y,
+            state: PlayerState::Airborne,
+            dash_cooldown: 0.0,
+            air_dash_used: false,
+            wall_jump_lock: 0.0,
+            ledge_cooldown: 0.0,
+            wall_contact: false,
+            squash: 0.0,
+            skin: PlayerSkin::classic(),
+            trail: VecDeque::new(),
+            facing: facing_right(),
+            attack: 0.0,
+            attack_cooldown: 0.0,
+            motion_trail: Self::motion_trail(),
+            stride: 0.0,
+            idle: 0.0,
+        }
+    }
+
+    fn motion_trail() -> TrailRenderer {
+        TrailRenderer::new(TrailConfig {
+            sample_interval: GameConfig::TRAIL_SAMPLE_INTERVAL,
+            max_points: GameConfig::PLAYER_MOTION_TRAIL_POINTS,
+            width: 24.0,
+            width_falloff: GameConfig::TRAIL_WIDTH_FALLOFF,
+            color: TrailColor::Solid(WHITE),
+            break_distance: GameConfig::TRAIL_BREAK_DISTANCE,
+        })
+    }
+
+    /// Leave the motion trail while boosted or in bullet time, in rainbow colors for the
+    /// latter; otherwise let what's there fade out
+    pub fn update_motion_trail(&mut self, delta_time: f32, bullet_time: bool) {
+        self.motion_trail.config.color = if bullet_time {
+            TrailColor::Rainbow(2.0)
+        } else {
+            TrailColor::Solid(self.skin.body_color())
+        };
+        let center = self.body.position + self.body.size / 2.0;
+        let dashing = matches!(self.state, PlayerState::Dash { .. });
+        let emitting = self.is_boosted() || bullet_time || dashing;
+        self.motion_trail
+            .update(delta_time, emitting.then_some(center));
+    }
+
+    /// Walk on, returning true when a foot comes down. Steps come every `FOOTSTEP_STRIDE`
+    /// pixels, so they quicken with speed, and stop in the air or when standing still.
+    pub fn update_footsteps(&mut self, delta_time: f32) -> bool {
+        let speed = self.body.velocity.x.abs();
+        if !self.is_on_ground() || self.is_hanging() || speed < GameConfig::FOOTSTEP_MIN_SPEED {
+            // Halfway into a stride, so setting off is heard straight away
+            self.stride = GameConfig::FOOTSTEP_STRIDE / 2.0;
+            return false;
+        }
+        self.stride += speed * delta_time;
+        if self.stride < GameConfig::FOOTSTEP_STRIDE {
+            return false;
+        }
+        self.stride -= GameConfig::FOOTSTEP_STRIDE;
+        true
+    }
+
+    /// Whether the player is coming down feet-first onto the target's hurt box
+    pub fn can_stomp(&self, target: &PhysicsBody) -> bool {
+        self.body.velocity.y > 0.0 && self.body.damages(target)
+    }
+
+    /// Remember the current center for the trail, once per simulation step
+    pub fn record_trail(&mut self) {
+        if self.skin.trail.is_none() {
+            self.trail.clear();
+            return;
+        }
+        self.trail
+            .push_back(self.body.position + self.body.size / 2.0);
+        while self.trail.len() > GameConfig::PLAYER_TRAIL_LENGTH {
+            self.trail.pop_front();
         }
     }
 
     pub fn move_left(&mut self) {
-        self.body.velocity.x = -self.move_speed;
+        self.steer(-1.0);
     }
 
     pub fn move_right(&mut self) {
-        self.body.velocity.x = self.move_speed;
+        self.steer(1.0);
+    }
+
+    /// Run in a direction at top speed, including any active boost. Steering away from a
+    /// wall being slid down lets go of it.
+    fn steer(&mut self, direction: f32) {
+        if !self.state.honors(Intent::Steer) || self.wall_jump_lock > 0.0 {
+            return;
+        }
+        if let PlayerState::WallSlide { wall } = self.state {
+            if direction == wall {
+                // Pressing into the wall just keeps hold of it
+                return;
+            }
+            self.set_state(PlayerState::Airborne);
+        }
+        if self.speed_boost > 0.0 && direction != self.boost_direction {
+            // Steering against a boost cancels it
+            self.speed_boost = 0.0;
+        }
+        self.body.velocity.x = self.carried + direction * (self.run_speed() + self.speed_boost);
+        self.facing = direction;
+    }
+
+    /// Top speed on foot, before any boost
+    fn run_speed(&self) -> f32 {
+        let speed = if self.sprinting {
+            self.move_speed * GameConfig::SPRINT_MULTIPLIER
+        } else {
+            self.move_speed
+        };
+        // A little extra air control at the top of a jump
+        if self.is_at_apex() {
+            speed * GameConfig::APEX_HORIZONTAL_BOOST
+        } else {
+            speed
+        }
+    }
+
+    /// Near the top of a jump, where vertical speed is about to change direction
+    pub fn is_at_apex(&self) -> bool {
+        self.state == PlayerState::Airborne
+            && self.current_jump_count > 0
+            && !self.is_hanging()
+            && self.body.velocity.y.abs() < GameConfig::APEX_HANG_VELOCITY
+    }
+
+    /// Push the player horizontally, allowing them past their normal top speed
+    pub fn apply_boost(&mut self, direction: f32, impulse: f32) {
+        if direction != self.boost_direction {
+            self.speed_boost = 0.0;
+        }
+        self.boost_direction = direction;
+        self.speed_boost =
+            (self.speed_boost + impulse).min(GameConfig::BOOST_MAX_SPEED - self.move_speed);
+        self.body.velocity.x = direction * (self.move_speed + self.speed_boost);
+    }
+
+    /// Bounce straight up while keeping horizontal momentum
+    pub fn launch_upward(&mut self, launch_velocity: f32) {
+        self.launch(Vec2::new(0.0, launch_velocity), MomentumRule::Preserve);
+    }
+
+    /// Throw the player into the air. Sideways speed past a run carries on as a boost,
+    /// the way a boost pad's does, so it isn't lost to friction straight away.
+    pub fn launch(&mut self, impulse: Vec2, rule: MomentumRule) {
+        self.set_state(PlayerState::Airborne);
+        launch(&mut self.body, impulse, rule);
+        let excess = self.body.velocity.x.abs() - self.move_speed;
+        if excess > self.speed_boost {
+            self.boost_direction = self.body.velocity.x.signum();
+            self.speed_boost = excess.min(GameConfig::BOOST_MAX_SPEED - self.move_speed);
+        }
+        // A launch counts as the first jump so the double jump is still available
+        self.current_jump_count = 1;
+    }
+
+    pub fn is_boosted(&self) -> bool {
+        self.speed_boost > 0.0
+    }
+
+    /// Start a melee swipe unless the last one is still cooling down; works in the air too
+    pub fn attack(&mut self) -> bool {
+        if !self.state.honors(Intent::Attack) || self.attack_cooldown > 0.0 {
+            return false;
+        }
+        self.attack = GameConfig::ATTACK_ACTIVE_TIME;
+        self.attack_cooldown = GameConfig::ATTACK_COOLDOWN;
+        true
+    }
+
+    /// The swipe's hitbox in front of the player, while it's active
+    pub fn attack_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        if self.attack <= 0.0 {
+            return None;
+        }
+        let (x1, y1, x2, y2) = self.body.get_bounds();
+        let reach = GameConfig::ATTACK_REACH;
+        let (left, right) = if self.facing < 0.0 {
+            (x1 - reach, x1)
+        } else {
+            (x2, x2 + reach)
+        };
+        Some((left, y1 - 4.0, right, y2 + 4.0))
+    }
+
+    /// Jump if the state allows it and any jumps are left; returns whether the player
+    /// actually jumped. Off a wall it's a wall jump, from a ledge it's a climb on top,
+    /// and in water it's a stroke upward.
+    pub fn jump(&mut self) -> bool {
+        if !self.state.honors(Intent::Jump) {
+            return false;
+        }
+        match self.state {
+            PlayerState::WallSlide { wall } => {
+                self.set_state(PlayerState::Airborne);
+                self.body.velocity = Vec2::new(-wall * GameConfig::WALL_JUMP_PUSH, self.jump_force);
+                self.facing = -wall;
+                self.wall_jump_lock = GameConfig::WALL_JUMP_LOCK_TIME;
+                // Counts as the first jump, leaving the double jump
+                self.current_jump_count = 1;
+                true
+            }
+            PlayerState::LedgeHang { wall } => {
+                // Hands are level with the top, so pull up and step onto it
+                self.body.position += Vec2::new(wall * self.body.size.x, -self.body.size.y);
+                self.body.velocity = Vec2::ZERO;
+                self.set_on_ground(true);
+                true
+            }
+            PlayerState::Swim => {
+                self.body.velocity.y = GameConfig::SWIM_STROKE_VELOCITY;
+                true
+            }
+            _ if self.current_jump_count < self.max_jump_count => {
+                self.set_state(PlayerState::Airborne);
+                self.body.velocity.y = self.jump_force;
+                self.current_jump_count += 1;
+                true
+            }
+            _ => false,
+        }
     }
 
-    pub fn jump(&mut self) {
-        if self.current_jump_count < self.max_jump_count {
-            self.body.velocity.y = self.jump_force;
-            self.current_jump_count += 1;
-            self.body.on_ground = false;
+    /// Cut a jump short when the button is let go on the way up, so a tap hops and a
+    /// hold clears the full height
+    pub fn cut_jump(&mut self) {
+        let floor = self.jump_force * GameConfig::JUMP_CUT_SHARE;
+        if self.state == PlayerState::Airborne && self.body.velocity.y < floor {
+            self.body.velocity.y = floor;
         }
     }
 
+    /// Add the velocity of a moving platform just jumped off, keeping the sideways part
+    /// going through the air
+    pub fn carry(&mut self, velocity: Vec2) {
+        self.body.velocity += velocity;
+        self.carried = velocity.x;
+    }
+
+    /// Be pushed along by flowing water: `push` is the current's acceleration and `drag`
+    /// the share of the drift lost per second, so it settles at `push / drag`. The
+    /// sideways part is carried like a moving platform's speed, so running and friction
+    /// work on top of it.
+    pub fn drift(&mut self, push: Vec2, drag: f32, delta_time: f32) {
+        let carried = self.carried;
+        self.carried += (push.x - carried * drag) * delta_time;
+        self.body.velocity.x += self.carried - carried;
+        self.body.velocity.y += push.y * delta_time;
+    }
+
     pub fn reset_jump(&mut self) {
         self.current_jump_count = 0;
     }
 
+    /// Touch down on something, or leave it. Touching down gives back the jumps and the
+    /// air dash whatever the state, but only ends the states that landing ends.
     pub fn set_on_ground(&mut self, on_ground: bool) {
-        self.body.on_ground = on_ground;
         if on_ground {
             self.reset_jump();
+            self.air_dash_used = false;
+            self.set_state(PlayerState::Grounded);
+        } else if self.state.is_grounded() {
+            self.set_state(PlayerState::Airborne);
+        }
+    }
+
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    /// Switch state if the transition is allowed, running the old state's exit and the
+    /// new one's enter. Returns whether it switched.
+    fn set_state(&mut self, next: PlayerState) -> bool {
+        if !self.state.can_become(&next) {
+            return false;
+        }
+        let previous = std::mem::replace(&mut self.state, next);
+        self.exit_state(previous);
+        self.enter_state(next);
+        self.body.on_ground = next.is_grounded();
+        true
+    }
+
+    fn exit_state(&mut self, previous: PlayerState) {
+        match previous {
+            PlayerState::Dash { direction, .. } => {
+                // Come out of it at running speed rather than dash speed
+                self.body.velocity.x = direction * self.run_speed();
+            }
+            PlayerState::LedgeHang { .. } => {
+                self.ledge_cooldown = GameConfig::LEDGE_REGRAB_COOLDOWN;
+            }
+            _ => {}
+        }
+    }
+
+    fn enter_state(&mut self, next: PlayerState) {
+        if !matches!(next, PlayerState::Airborne | PlayerState::Glide) {
+            self.carried = 0.0;
+        }
+        match next {
+            PlayerState::Dash { direction, .. } => {
+                self.speed_boost = 0.0;
+                self.dash_cooldown = GameConfig::DASH_COOLDOWN;
+                self.body.velocity = Vec2::new(direction * GameConfig::DASH_SPEED, 0.0);
+            }
+            PlayerState::WallSlide { .. } | PlayerState::LedgeHang { .. } => {
+                // Holding on to a wall gives the jumps and the air dash back
+                self.speed_boost = 0.0;
+                self.air_dash_used = false;
+                self.reset_jump();
+                if matches!(next, PlayerState::LedgeHang { .. }) {
+                    self.body.velocity = Vec2::ZERO;
+                }
+            }
+            PlayerState::Swim => {
+                self.let_go();
+                self.reset_jump();
+                self.air_dash_used = false;
+                self.body.velocity *= GameConfig::SWIM_ENTRY_DAMPING;
+            }
+            PlayerState::Stunned { .. } => {
+                self.speed_boost = 0.0;
+                self.body.velocity.x = 0.0;
+            }
+            PlayerState::InCannon => {
+                self.let_go();
+                self.speed_boost = 0.0;
+                self.body.velocity = Vec2::ZERO;
+            }
+            PlayerState::Grounded | PlayerState::Airborne | PlayerState::Glide => {}
+        }
+        if next.breaks_fall() {
+            self.fall_peak_y = self.body.position.y;
+        }
+    }
+
+    /// Run the current state for a step: count down the timed ones and keep the player
+    /// against the wall or ledge they're holding
+    fn update_state(&mut self, delta_time: f32) {
+        let wall_contact = std::mem::take(&mut self.wall_contact);
+        match self.state {
+            PlayerState::Dash {
+                direction,
+                remaining,
+            } => {
+                let remaining = remaining - delta_time;
+                if remaining <= 0.0 {
+                    self.set_state(PlayerState::Airborne);
+                } else {
+                    self.state = PlayerState::Dash {
+                        direction,
+                        remaining,
+                    };
+                    self.body.velocity = Vec2::new(direction * GameConfig::DASH_SPEED, 0.0);
+                }
+            }
+            PlayerState::Stunned { remaining } => {
+                let remaining = remaining - delta_time;
+                if remaining <= 0.0 {
+                    self.set_state(PlayerState::Grounded);
+                } else {
+                    self.state = PlayerState::Stunned { remaining };
+                }
+            }
+            // Sliding off the bottom of the wall, or it going away, ends the slide
+            PlayerState::WallSlide { .. } if !wall_contact => {
+                self.set_state(PlayerState::Airborne);
+            }
+            PlayerState::WallSlide { wall } => {
+                self.body.velocity.x = wall * GameConfig::WALL_STICK_SPEED;
+            }
+            PlayerState::LedgeHang { .. } | PlayerState::InCannon => {
+                self.body.velocity = Vec2::ZERO;
+            }
+            PlayerState::Grounded
+            | PlayerState::Airborne
+            | PlayerState::Glide
+            | PlayerState::Swim => {}
+        }
+    }
+
+    /// Dash the way the player faces, or away from the wall they're sliding down. Once
+    /// per trip through the air, and never again until the cooldown runs out.
+    pub fn dash(&mut self) -> bool {
+        if !self.state.honors(Intent::Dash) || self.dash_cooldown > 0.0 || self.is_hanging() {
+            return false;
+        }
+        let airborne = !self.state.is_grounded();
+        if airborne && self.air_dash_used {
+            return false;
+        }
+        let direction = match self.state {
+            PlayerState::WallSlide { wall } => -wall,
+            _ => self.facing,
+        };
+        let dash = PlayerState::Dash {
+            direction,
+            remaining: GameConfig::DASH_TIME,
+        };
+        if !self.set_state(dash) {
+            return false;
+        }
+        self.facing = direction;
+        self.air_dash_used |= airborne;
+        true
+    }
+
+    /// Glide while jump is held, once falling with no jumps left; letting go drops out
+    /// of it
+    pub fn glide(&mut self, holding: bool) {
+        match self.state {
+            PlayerState::Glide if !holding => {
+                self.set_state(PlayerState::Airborne);
+            }
+            _ if holding
+                && self.state.honors(Intent::Glide)
+                && self.current_jump_count >= self.max_jump_count
+                && self.body.velocity.y > 0.0
+                && !self.is_hanging() =>
+            {
+                self.set_state(PlayerState::Glide);
+            }
+            _ => {}
+        }
+    }
+
+    /// Brush against a wall on one side (-1 left, 1 right). Falling or dashing into it
+    /// starts a wall slide.
+    pub fn touch_wall(&mut self, wall: f32) {
+        self.wall_contact = true;
+        match self.state {
+            PlayerState::Airborne | PlayerState::Glide
+                if self.body.velocity.y >= 0.0 && !self.is_hanging() =>
+            {
+                self.set_state(PlayerState::WallSlide { wall });
+            }
+            PlayerState::Dash { direction, .. } if direction == wall => {
+                self.set_state(PlayerState::WallSlide { wall });
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a ledge could be caught now: not rising, not holding anything else, and
+    /// not straight after letting go of one
+    pub fn can_grab_ledge(&self) -> bool {
+        self.ledge_cooldown <= 0.0
+            && self.body.velocity.y >= 0.0
+            && !self.is_hanging()
+            && self.state.can_become(&PlayerState::LedgeHang { wall: 0.0 })
+    }
+
+    /// Catch the top of the wall on one side (-1 left, 1 right), hanging with the top of
+    /// the head level with `top`
+    pub fn grab_ledge(&mut self, wall: f32, top: f32) -> bool {
+        if !self.can_grab_ledge() {
+            return false;
         }
+        self.body.position.y = top;
+        self.set_state(PlayerState::LedgeHang { wall })
+    }
+
+    /// Drop down from a ledge being held
+    pub fn drop_from_ledge(&mut self) {
+        if matches!(self.state, PlayerState::LedgeHang { .. }) {
+            self.set_state(PlayerState::Airborne);
+        }
+    }
+
+    /// Knock the player flat for `duration` seconds, if they're standing
+    pub fn stun(&mut self, duration: f32) {
+        self.set_state(PlayerState::Stunned {
+            remaining: duration,
+        });
+    }
+
+    pub fn enter_water(&mut self) {
+        self.set_state(PlayerState::Swim);
+    }
+
+    pub fn leave_water(&mut self) {
+        if self.state == PlayerState::Swim {
+            self.set_state(PlayerState::Airborne);
+        }
+    }
+
+    /// Climb into a cannon, which holds the player still until it fires
+    // No cannon is placed in levels yet; the state is ready for when one is
+    #[allow(dead_code)]
+    pub fn enter_cannon(&mut self) -> bool {
+        self.set_state(PlayerState::InCannon)
+    }
+
+    /// Shoot out of the cannon the player is in with the given velocity
+    #[allow(dead_code)]
+    pub fn fire_from_cannon(&mut self, velocity: Vec2) {
+        if self.state == PlayerState::InCannon {
+            self.launch(velocity, MomentumRule::Overwrite);
+        }
+    }
+
+    /// Drop out of whatever the player was doing into free fall, such as when they're
+    /// moved somewhere else or blown off their feet
+    pub fn become_airborne(&mut self) {
+        self.set_state(PlayerState::Airborne);
+    }
+
+    /// Airborne, not stuck to anything, so a zipline or rope can be caught
+    fn can_hold_on(&self) -> bool {
+        matches!(
+            self.state,
+            PlayerState::Airborne | PlayerState::Glide | PlayerState::Dash { .. }
+        )
+    }
+
+    pub fn is_on_zipline(&self) -> bool {
+        self.zipline.is_some()
+    }
+
+    pub fn can_grab_zipline(&self) -> bool {
+        self.can_hold_on() && self.zipline.is_none() && self.zipline_cooldown <= 0.0
+    }
+
+    pub fn attach_to_zipline(&mut self, ride: ZiplineRide) {
+        self.set_state(PlayerState::Airborne);
+        self.carried = 0.0;
+        self.zipline = Some(ride);
+    }
+
+    /// Let go of the zipline, keeping the given velocity
+    pub fn release_zipline(&mut self, velocity: Vec2) {
+        self.zipline = None;
+        // A drop is measured from where the player let go
+        self.fall_peak_y = self.body.position.y;
+        self.zipline_cooldown = GameConfig::ZIPLINE_ATTACH_COOLDOWN;
+        self.body.velocity = velocity;
+    }
+
+    pub fn update_zipline_cooldown(&mut self, delta_time: f32) {
+        self.zipline_cooldown = (self.zipline_cooldown - delta_time).max(0.0);
+    }
+
+    pub fn is_swinging(&self) -> bool {
+        self.swing.is_some()
+    }
+
+    /// Riding a zipline or swinging on a rope, with gravity and walking left to those
+    pub fn is_hanging(&self) -> bool {
+        self.is_on_zipline() || self.is_swinging()
+    }
+
+    pub fn can_grab_rope(&self, rope: EntityId) -> bool {
+        self.can_hold_on()
+            && !self.is_hanging()
+            && (self.last_rope != Some(rope) || self.rope_cooldown <= 0.0)
+    }
+
+    /// Take hold of a rope; the jumps are back, so there's one left after jumping off
+    pub fn attach_to_rope(&mut self, swing: RopeSwing) {
+        self.set_state(PlayerState::Airborne);
+        self.carried = 0.0;
+        self.swing = Some(swing);
+        self.reset_jump();
+    }
+
+    /// Let go of the rope, flying off with the given velocity
+    pub fn release_rope(&mut self, velocity: Vec2) {
+        if let Some(swing) = self.swing.take() {
+            self.last_rope = Some(swing.rope);
+            self.rope_cooldown = GameConfig::ROPE_REGRAB_COOLDOWN;
+        }
+        self.fall_peak_y = self.body.position.y;
+        self.body.velocity = velocity;
+    }
+
+    pub fn update_rope_cooldown(&mut self, delta_time: f32) {
+        self.rope_cooldown = (self.rope_cooldown - delta_time).max(0.0);
+    }
+
+    /// Drop whatever the player is holding on to without any of the effects of letting go,
+    /// such as when they're moved somewhere else
+    pub fn let_go(&mut self) {
+        self.zipline = None;
+        self.swing = None;
+    }
+
+    /// Area just above the player's head where their hands can catch a zipline
+    pub fn hand_bounds(&self) -> (f32, f32, f32, f32) {
+        let (x1, y1, x2, _) = self.body.get_bounds();
+        (
+            x1 + 4.0,
+            y1 - GameConfig::ZIPLINE_HAND_REACH,
+            x2 - 4.0,
+            y1 + 4.0,
+        )
     }
 
     // Getters for physics system
@@ -72,9 +787,110 @@ impl Player {
         self.body.position = position;
     }
 
+    /// Move straight to `position` without drawing the trip in between
+    pub fn teleport(&mut self, position: Vec2) {
+        self.body.position = position;
+        self.body.reset_interpolation();
+        self.trail.clear();
+        self.motion_trail.clear();
+    }
+
     pub fn is_on_ground(&self) -> bool {
         self.body.on_ground
     }
+
+    /// -1 facing left, 1 facing right; kept while standing still. The eyes, the swipe and
+    /// anything thrown or aimed go this way.
+    pub fn facing(&self) -> f32 {
+        self.facing
+    }
+
+    /// Any input wakes the player from the idle animation
+    pub fn stir(&mut self) {
+        self.idle = 0.0;
+    }
+
+    /// Blinks, glances to either side and breathing once the player has been still for
+    /// a few seconds; after a while longer they sit down and yawn. Nothing under reduced
+    /// motion, or while teetering, which has its own sway.
+    fn idle_pose(&self) -> IdlePose {
+        let mut pose = IdlePose {
+            face: Face::looking(Vec2::new(self.facing * GameConfig::FACING_EYE_SHIFT, 0.0)),
+            bob: 0.0,
+            sit: 0.0,
+        };
+        if RenderStyle::current().reduced_motion
+            || self.teeter != 0.0
+            || self.idle < GameConfig::IDLE_DELAY
+        {
+            return pose;
+        }
+        let time = self.idle - GameConfig::IDLE_DELAY;
+        pose.bob = (time * std::f32::consts::TAU / GameConfig::IDLE_BREATH_PERIOD).sin()
+            * GameConfig::IDLE_BREATH_DEPTH;
+
+        if self.idle >= GameConfig::IDLE_SIT_DELAY {
+            let seated = self.idle - GameConfig::IDLE_SIT_DELAY;
+            pose.sit = (seated / GameConfig::IDLE_SIT_TIME).min(1.0);
+            let phase = seated % GameConfig::IDLE_YAWN_INTERVAL;
+            let yawn = if phase < GameConfig::IDLE_YAWN_TIME {
+                (phase / GameConfig::IDLE_YAWN_TIME * std::f32::consts::PI).sin()
+            } else {
+                0.0
+            };
+            // Drowsy, and shutting them through each yawn
+            pose.face.look.x = 0.0;
+            pose.face.closed = 0.5 + 0.5 * yawn;
+            pose.face.yawn = yawn;
+            return pose;
+        }
+
+        if time % GameConfig::IDLE_BLINK_INTERVAL
+            > GameConfig::IDLE_BLINK_INTERVAL - GameConfig::IDLE_BLINK_TIME
+        {
+            pose.face.closed = 1.0;
+        }
+        if time % GameConfig::IDLE_GLANCE_INTERVAL
+            > GameConfig::IDLE_GLANCE_INTERVAL - GameConfig::IDLE_GLANCE_TIME
+        {
+            // Away from the way they face first, then back past it
+            let glance = (time / GameConfig::IDLE_GLANCE_INTERVAL) as u32;
+            let side = if glance.is_multiple_of(2) {
+                -self.facing
+            } else {
+                self.facing
+            };
+            pose.face.look.x = side * GameConfig::IDLE_GLANCE_SHIFT;
+        }
+        pose
+    }
+
+    /// Width and height multipliers for drawing the body: stretched tall on the way up
+    /// and a little when falling fast, squashed flat for a moment after a hard landing.
+    /// Only the picture changes; the physics body keeps its size.
+    pub fn render_scale(&self) -> Vec2 {
+        if RenderStyle::current().reduced_motion {
+            return Vec2::ONE;
+        }
+        if self.squash > 0.0 {
+            // Ease out: most of the recovery happens straight after touching down
+            let t = 1.0 - self.squash / GameConfig::LANDING_SQUASH_TIME;
+            let eased = 1.0 - (1.0 - t).powi(3);
+            return Vec2::from(GameConfig::LANDING_SQUASH_SCALE).lerp(Vec2::ONE, eased);
+        }
+        if !matches!(self.state, PlayerState::Airborne | PlayerState::Glide) || self.is_hanging() {
+            return Vec2::ONE;
+        }
+
+        let vertical = self.body.velocity.y;
+        if vertical < 0.0 {
+            let amount = (vertical / self.jump_force).clamp(0.0, 1.0);
+            Vec2::ONE.lerp(Vec2::from(GameConfig::JUMP_STRETCH_SCALE), amount)
+        } else {
+            let amount = (vertical / GameConfig::FALL_STRETCH_SPEED).clamp(0.0, 1.0);
+            Vec2::ONE.lerp(Vec2::from(GameConfig::FALL_STRETCH_SCALE), amount)
+        }
+    }
 }
 
 impl Entity for Player {
@@ -89,51 +905,298 @@ impl Entity for Player {
     fn render(&self, camera_x: f32, camera_y: f32) {
         let render_x = self.body.position.x + camera_x;
         let render_y = self.body.position.y + camera_y;
+        self.motion_trail.render(camera_x, camera_y);
+        let camera = Vec2::new(camera_x, camera_y);
+        let trail: Vec<Vec2> = self.trail.iter().map(|point| *point + camera).collect();
+        self.skin.draw_trail(&trail, self.body.size);
 
-        // Draw player as a rectangle
-        draw_rectangle(
-            render_x,
-            render_y,
-            self.body.size.x,
-            self.body.size.y,
-            self.color,
+        // The eyes look the way the player faces, or down over a ledge they're teetering on
+        let mut pose = self.idle_pose();
+        let mut body_x = render_x;
+        if self.teeter != 0.0 {
+            pose.face.look = Vec2::new(self.teeter, 1.0) * GameConfig::TEETER_EYE_SHIFT;
+            let time = RenderStyle::current().animation_time(get_time() as f32);
+            body_x += (time * GameConfig::TEETER_FREQUENCY).sin() * GameConfig::TEETER_SWAY;
+        }
+        // Deform around the middle of the feet so the player stays planted
+        let sit = Vec2::ONE.lerp(
+            Vec2::new(GameConfig::IDLE_SIT_SCALE.0, GameConfig::IDLE_SIT_SCALE.1),
+            pose.sit,
+        );
+        let size = self.body.size * self.render_scale() * sit;
+        self.skin.draw_body(
+            body_x + (self.body.size.x - size.x) / 2.0,
+            render_y + self.body.size.y - size.y + pose.bob,
+            size,
+            pose.face,
         );
 
-        // Draw eyes to show facing direction
-        let eye_size = 4.0;
-        let eye_y = render_y + 8.0;
-
-        // Left eye
-        draw_rectangle(render_x + 8.0, eye_y, eye_size, eye_size, WHITE);
+        // Draw arms reaching up to the zipline handle or the rope
+        let hang_offset = if self.is_on_zipline() {
+            Some(GameConfig::ZIPLINE_HANG_OFFSET)
+        } else if self.is_swinging() {
+            Some(GameConfig::ROPE_HANG_OFFSET)
+        } else {
+            None
+        };
+        if let Some(hang_offset) = hang_offset {
+            let color = self.skin.body_color();
+            let hand_x = render_x + self.body.size.x / 2.0;
+            let hand_y = render_y - hang_offset;
+            draw_line(render_x + 6.0, render_y, hand_x, hand_y, 2.0, color);
+            draw_line(
+                render_x + self.body.size.x - 6.0,
+                render_y,
+                hand_x,
+                hand_y,
+                2.0,
+                color,
+            );
+        }
 
-        // Right eye
-        draw_rectangle(render_x + 20.0, eye_y, eye_size, eye_size, WHITE);
+        match self.state {
+            // Arms up over the top of the wall
+            PlayerState::LedgeHang { wall } => {
+                let color = self.skin.body_color();
+                let edge_x = if wall < 0.0 {
+                    render_x
+                } else {
+                    render_x + self.body.size.x
+                };
+                for shoulder_x in [render_x + 6.0, render_x + self.body.size.x - 6.0] {
+                    draw_line(
+                        shoulder_x,
+                        render_y + 4.0,
+                        edge_x,
+                        render_y - 2.0,
+                        2.0,
+                        color,
+                    );
+                }
+            }
+            // Arms spread wide to catch the air
+            PlayerState::Glide => {
+                let color = self.skin.body_color();
+                let y = render_y + 8.0;
+                draw_line(render_x - 10.0, y - 4.0, render_x, y, 3.0, color);
+                draw_line(
+                    render_x + self.body.size.x,
+                    y,
+                    render_x + self.body.size.x + 10.0,
+                    y - 4.0,
+                    3.0,
+                    color,
+                );
+            }
+            _ => {}
+        }
 
-        // Draw velocity indicator (for debugging)
-        if self.body.velocity.length() > 0.1 {
-            let vel_end = Vec2::new(
-                render_x + self.body.size.x / 2.0 + self.body.velocity.x * 0.1,
-                render_y + self.body.size.y / 2.0 + self.body.velocity.y * 0.1,
+        // The swipe: a quarter circle in front of the player that fades as it ends
+        if self.attack > 0.0 {
+            let center = Vec2::new(
+                render_x + self.body.size.x / 2.0,
+                render_y + self.body.size.y / 2.0,
             );
+            let radius = self.body.size.x / 2.0 + GameConfig::ATTACK_REACH;
+            let alpha = self.attack / GameConfig::ATTACK_ACTIVE_TIME;
+            let color = Color::new(1.0, 1.0, 1.0, 0.7 * alpha);
+            let start = if self.facing < 0.0 {
+                std::f32::consts::PI * 0.75
+            } else {
+                -std::f32::consts::FRAC_PI_4
+            };
+            let segments = 8;
+            for i in 0..segments {
+                let a1 = start + std::f32::consts::FRAC_PI_2 * i as f32 / segments as f32;
+                let a2 = start + std::f32::consts::FRAC_PI_2 * (i + 1) as f32 / segments as f32;
+                draw_triangle(
+                    center,
+                    center + Vec2::from_angle(a1) * radius,
+                    center + Vec2::from_angle(a2) * radius,
+                    color,
+                );
+            }
+        }
 
-            draw_line(
-                render_x + self.body.size.x / 2.0,
+        // Draw velocity indicator (for debugging), from the front of the face
+        if self.body.velocity.length() > 0.1 {
+            let origin = Vec2::new(
+                render_x + self.body.size.x / 2.0 * (1.0 + self.facing * 0.5),
                 render_y + self.body.size.y / 2.0,
-                vel_end.x,
-                vel_end.y,
-                2.0,
-                YELLOW,
             );
+            let vel_end = origin + self.body.velocity * 0.1;
+
+            draw_line(origin.x, origin.y, vel_end.x, vel_end.y, 2.0, YELLOW);
         }
     }
 
-    fn update(&mut self) {
-        // Apply friction to horizontal movement
-        self.body.velocity.x *= 0.8;
+    fn update(&mut self, delta_time: f32) {
+        let still = matches!(self.state, PlayerState::Grounded)
+            && self.body.velocity.length() < GameConfig::IDLE_STILL_SPEED;
+        self.idle = if still { self.idle + delta_time } else { 0.0 };
+        self.attack = (self.attack - delta_time).max(0.0);
+        self.attack_cooldown = (self.attack_cooldown - delta_time).max(0.0);
+        self.dash_cooldown = (self.dash_cooldown - delta_time).max(0.0);
+        self.wall_jump_lock = (self.wall_jump_lock - delta_time).max(0.0);
+        self.ledge_cooldown = (self.ledge_cooldown - delta_time).max(0.0);
+        self.squash = (self.squash - delta_time).max(0.0);
+        self.update_state(delta_time);
+        if self.state.breaks_fall() {
+            self.fall_peak_y = self.body.position.y;
+        } else {
+            self.fall_peak_y = self.fall_peak_y.min(self.body.position.y);
+        }
+
+        // The push off a wall carries until steering comes back
+        if !self.state.applies_friction() || self.wall_jump_lock > 0.0 {
+            return;
+        }
+
+        if self.speed_boost > 0.0 {
+            // Boosted momentum carries on (even through the air) until it decays back to normal
+            let decay_rate =
+                (GameConfig::BOOST_MAX_SPEED - self.move_speed) / GameConfig::BOOST_DECAY_TIME;
+            self.speed_boost = (self.speed_boost - decay_rate * delta_time).max(0.0);
+            self.body.velocity.x = self.boost_direction * (self.move_speed + self.speed_boost);
+            return;
+        }
+
+        // Apply friction to horizontal movement, relative to any speed carried off a platform
+        let relative = (self.body.velocity.x - self.carried) * 0.8;
 
         // Stop very small movements
-        if self.body.velocity.x.abs() < 1.0 {
-            self.body.velocity.x = 0.0;
+        self.body.velocity.x = if relative.abs() < 1.0 {
+            self.carried
+        } else {
+            self.carried + relative
+        };
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Player
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gentle_landings_are_soft() {
+        let speed = GameConfig::LANDING_SQUASH_SPEED - 1.0;
+        assert_eq!(Landing::classify(speed, 40.0, true), Landing::Soft);
+    }
+
+    #[test]
+    fn fast_landings_squash() {
+        let speed = GameConfig::LANDING_SQUASH_SPEED;
+        assert_eq!(Landing::classify(speed, 40.0, false), Landing::Hard);
+        assert_eq!(Landing::classify(speed * 2.0, 40.0, true), Landing::Hard);
+    }
+
+    #[test]
+    fn long_drops_stun() {
+        let drop = GameConfig::HEAVY_LANDING_HEIGHT;
+        assert_eq!(Landing::classify(0.0, drop - 1.0, false), Landing::Soft);
+        assert_eq!(Landing::classify(0.0, drop, false), Landing::Heavy);
+        assert_eq!(Landing::classify(900.0, drop, true), Landing::Heavy);
+    }
+
+    #[test]
+    fn fall_damage_only_when_it_is_on() {
+        let drop = GameConfig::FALL_DAMAGE_HEIGHT;
+        assert_eq!(Landing::classify(900.0, drop, true), Landing::Fatal);
+        assert_eq!(Landing::classify(900.0, drop, false), Landing::Heavy);
+        assert_eq!(Landing::classify(900.0, drop - 1.0, true), Landing::Heavy);
+    }
+
+    #[test]
+    fn rapid_tapping_settles_on_the_last_direction() {
+        let mut player = Player::new(0.0, 0.0);
+        player.set_on_ground(true);
+        for tap in 0..9 {
+            if tap % 2 == 0 {
+                player.move_left();
+            } else {
+                player.move_right();
+            }
+            player.update(1.0 / 120.0);
+        }
+        assert_eq!(player.facing(), -1.0);
+
+        player.move_right();
+        // Letting go keeps the last direction while friction brings them to a stop
+        for _ in 0..120 {
+            player.update(1.0 / 120.0);
+        }
+        assert_eq!(player.velocity().x, 0.0);
+        assert_eq!(player.facing(), 1.0);
+    }
+
+    #[test]
+    fn knockback_does_not_turn_the_player() {
+        let mut player = Player::new(0.0, 0.0);
+        player.move_right();
+        player.launch(Vec2::new(-600.0, -200.0), MomentumRule::Preserve);
+        assert!(player.velocity().x < 0.0);
+        assert_eq!(player.facing(), 1.0);
+    }
+
+    const STEP: f32 = 1.0 / 120.0;
+
+    /// Jump off a platform moving at `platform`, holding right the whole way, and return
+    /// how far right the player got by landing back at take-off height, how high they
+    /// rose and how long they were in the air
+    fn jump_off(platform: Vec2, transfer: f32) -> (f32, f32, f32) {
+        let physics = crate::physics::Physics::new();
+        let mut player = Player::new(0.0, 0.0);
+        player.set_on_ground(true);
+        player.move_right();
+        assert!(player.jump());
+        player.carry(platform * transfer);
+        let (mut time, mut top) = (0.0, 0.0_f32);
+        while time < 5.0 {
+            player.move_right();
+            physics.apply_gravity(&mut player, STEP);
+            physics.update_position(&mut player, STEP);
+            player.update(STEP);
+            time += STEP;
+            top = top.min(player.position().y);
+            if player.position().y >= 0.0 {
+                break;
+            }
         }
+        (player.position().x, -top, time)
+    }
+
+    #[test]
+    fn a_platform_moving_the_same_way_flings_the_jump_further() {
+        let (still, _, flight) = jump_off(Vec2::ZERO, 1.0);
+        let (moving, _, moving_flight) = jump_off(Vec2::new(150.0, 0.0), 1.0);
+        assert_eq!(flight, moving_flight);
+        // The platform's speed is kept for the whole flight
+        assert!((moving - still - 150.0 * flight).abs() < 150.0 * STEP);
+    }
+
+    #[test]
+    fn a_platform_moving_the_other_way_shortens_the_jump() {
+        let (still, _, _) = jump_off(Vec2::ZERO, 1.0);
+        let (against, _, _) = jump_off(Vec2::new(-150.0, 0.0), 1.0);
+        assert!(against < still - 100.0);
+    }
+
+    #[test]
+    fn a_rising_platform_adds_height() {
+        let (_, still, _) = jump_off(Vec2::ZERO, 1.0);
+        let (_, lifted, _) = jump_off(Vec2::new(0.0, -100.0), 1.0);
+        assert!(lifted > still + 20.0);
+    }
+
+    #[test]
+    fn no_transfer_jumps_as_if_the_platform_were_still() {
+        assert_eq!(
+            jump_off(Vec2::new(150.0, -100.0), 0.0),
+            jump_off(Vec2::ZERO, 1.0)
+        );
     }
 }
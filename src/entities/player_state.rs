@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+
+/// What the player is doing, which decides which inputs they can act on and how gravity
+/// treats them. Only one at a time, so there's no dashing while stunned or gliding
+/// while standing.
+///
+/// Holding a zipline or rope isn't one of these: the player is airborne while they hang.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PlayerState {
+    Grounded,
+    #[default]
+    Airborne,
+    /// Clinging to a wall on one side (-1 left, 1 right) and sliding slowly down it
+    WallSlide {
+        wall: f32,
+    },
+    /// Shooting sideways at a fixed height for `remaining` seconds
+    Dash {
+        direction: f32,
+        remaining: f32,
+    },
+    /// Drifting down slowly with jump held
+    Glide,
+    Swim,
+    /// Knocked flat by a heavy landing, unable to move for `remaining` seconds
+    Stunned {
+        remaining: f32,
+    },
+    /// Holding the top of a wall on one side (-1 left, 1 right)
+    LedgeHang {
+        wall: f32,
+    },
+    /// Loaded into a cannon, waiting to be fired
+    InCannon,
+}
+
+/// Something the player asks to do; each state decides whether it listens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    Steer,
+    Jump,
+    Dash,
+    Glide,
+    Attack,
+}
+
+impl PlayerState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayerState::Grounded => "grounded",
+            PlayerState::Airborne => "airborne",
+            PlayerState::WallSlide { .. } => "wall slide",
+            PlayerState::Dash { .. } => "dash",
+            PlayerState::Glide => "glide",
+            PlayerState::Swim => "swim",
+            PlayerState::Stunned { .. } => "stunned",
+            PlayerState::LedgeHang { .. } => "ledge hang",
+            PlayerState::InCannon => "in cannon",
+        }
+    }
+
+    /// Standing on something, so gravity leaves the player alone
+    pub fn is_grounded(&self) -> bool {
+        matches!(self, PlayerState::Grounded | PlayerState::Stunned { .. })
+    }
+
+    /// Whether the player can act on `intent` in this state
+    pub fn honors(&self, intent: Intent) -> bool {
+        use Intent::*;
+        match self {
+            PlayerState::Grounded => !matches!(intent, Glide),
+            PlayerState::Airborne => true,
+            PlayerState::WallSlide { .. } => matches!(intent, Steer | Jump | Dash),
+            PlayerState::Dash { .. } => matches!(intent, Attack),
+            PlayerState::Glide => matches!(intent, Steer | Dash | Glide | Attack),
+            PlayerState::Swim => matches!(intent, Steer | Jump | Attack),
+            PlayerState::Stunned { .. } => false,
+            PlayerState::LedgeHang { .. } => matches!(intent, Jump),
+            PlayerState::InCannon => false,
+        }
+    }
+
+    /// Whether the state can hand over to `next`. Every state can be left for
+    /// `Airborne`, as that's where the player ends up when moved or knocked loose.
+    pub fn can_become(&self, next: &PlayerState) -> bool {
+        use PlayerState::*;
+        if std::mem::discriminant(self) == std::mem::discriminant(next) {
+            return false;
+        }
+        match (self, next) {
+            (_, Airborne) => true,
+            // Only firing gets the player out of a cannon
+            (InCannon, _) => false,
+            (_, InCannon) | (_, Swim) => true,
+            (Swim, _) => false,
+            (Grounded, Dash { .. } | Stunned { .. }) => true,
+            (Grounded, _) => false,
+            (Stunned { .. }, Grounded) => true,
+            (Stunned { .. }, _) => false,
+            (_, Stunned { .. }) => false,
+            // Landing ends a slide, a glide or a hang, but a dash runs its course
+            (WallSlide { .. } | Glide | LedgeHang { .. }, Grounded) => true,
+            (Airborne, Grounded) => true,
+            (Dash { .. }, Grounded) => false,
+            (Airborne | Glide | Dash { .. }, WallSlide { .. }) => true,
+            (Airborne | Glide | WallSlide { .. }, Dash { .. }) => true,
+            (Airborne, Glide) => true,
+            (Airborne | Glide | WallSlide { .. } | Dash { .. }, LedgeHang { .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// Multiplier on the usual gravity
+    pub fn gravity_scale(&self) -> f32 {
+        match self {
+            PlayerState::Swim => GameConfig::SWIM_GRAVITY_SCALE,
+            PlayerState::Dash { .. } | PlayerState::LedgeHang { .. } | PlayerState::InCannon => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Fastest the player can fall, if slower than terminal velocity
+    pub fn max_fall_speed(&self) -> Option<f32> {
+        match self {
+            PlayerState::WallSlide { .. } => Some(GameConfig::WALL_SLIDE_SPEED),
+            PlayerState::Glide => Some(GameConfig::GLIDE_FALL_SPEED),
+            PlayerState::Swim => Some(GameConfig::SWIM_SINK_SPEED),
+            _ => None,
+        }
+    }
+
+    /// Whether horizontal speed bleeds away as usual. The rest set the player's
+    /// velocity themselves every step.
+    pub fn applies_friction(&self) -> bool {
+        matches!(
+            self,
+            PlayerState::Grounded
+                | PlayerState::Airborne
+                | PlayerState::Glide
+                | PlayerState::Swim
+                | PlayerState::Stunned { .. }
+        )
+    }
+
+    /// Whether a fall is measured from here rather than from the highest point reached,
+    /// so sliding, gliding or swimming down never ends in a fatal landing
+    pub fn breaks_fall(&self) -> bool {
+        !matches!(self, PlayerState::Airborne | PlayerState::Dash { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::math::Vec2;
+
+    use super::*;
+    use crate::entities::{Entity, Player};
+
+    const STEP: f32 = 1.0 / 120.0;
+
+    fn standing() -> Player {
+        let mut player = Player::new(0.0, 0.0);
+        player.set_on_ground(true);
+        player
+    }
+
+    /// Falling with both jumps spent, the way a glide starts
+    fn falling_after_double_jump() -> Player {
+        let mut player = standing();
+        assert!(player.jump());
+        assert!(player.jump());
+        player.body.velocity.y = 100.0;
+        player
+    }
+
+    fn run_for(player: &mut Player, seconds: f32) {
+        for _ in 0..(seconds / STEP).ceil() as usize {
+            player.update(STEP);
+        }
+    }
+
+    fn is_dash(state: PlayerState) -> bool {
+        matches!(state, PlayerState::Dash { .. })
+    }
+
+    #[test]
+    fn jump_and_land() {
+        let mut player = standing();
+        assert_eq!(player.state(), PlayerState::Grounded);
+        assert!(player.jump());
+        assert_eq!(player.state(), PlayerState::Airborne);
+        assert!(!player.body.on_ground);
+        player.set_on_ground(true);
+        assert_eq!(player.state(), PlayerState::Grounded);
+    }
+
+    #[test]
+    fn dash_into_a_wall_then_wall_jump_away() {
+        let mut player = standing();
+        player.become_airborne();
+        assert!(player.dash());
+        assert!(is_dash(player.state()));
+        // Facing right, so the dash hits a wall on the right
+        player.touch_wall(1.0);
+        assert_eq!(player.state(), PlayerState::WallSlide { wall: 1.0 });
+        player.touch_wall(1.0);
+        player.update(STEP);
+        assert_eq!(player.state(), PlayerState::WallSlide { wall: 1.0 });
+
+        assert!(player.jump());
+        assert_eq!(player.state(), PlayerState::Airborne);
+        assert!(player.velocity().x < 0.0);
+        assert!(player.velocity().y < 0.0);
+        assert_eq!(player.facing(), -1.0);
+    }
+
+    #[test]
+    fn a_wall_slide_ends_when_the_wall_does() {
+        let mut player = falling_after_double_jump();
+        player.touch_wall(-1.0);
+        assert_eq!(player.state(), PlayerState::WallSlide { wall: -1.0 });
+        // No contact this step
+        player.update(STEP);
+        player.update(STEP);
+        assert_eq!(player.state(), PlayerState::Airborne);
+    }
+
+    #[test]
+    fn glide_while_held_and_drop_out_on_release() {
+        let mut player = falling_after_double_jump();
+        player.glide(true);
+        assert_eq!(player.state(), PlayerState::Glide);
+        player.glide(false);
+        assert_eq!(player.state(), PlayerState::Airborne);
+    }
+
+    #[test]
+    fn no_glide_with_a_jump_left_or_on_the_ground() {
+        let mut player = standing();
+        player.glide(true);
+        assert_eq!(player.state(), PlayerState::Grounded);
+        assert!(player.jump());
+        player.body.velocity.y = 100.0;
+        player.glide(true);
+        assert_eq!(player.state(), PlayerState::Airborne);
+    }
+
+    #[test]
+    fn glide_into_a_wall_slides_down_it() {
+        let mut player = falling_after_double_jump();
+        player.glide(true);
+        player.touch_wall(1.0);
+        assert_eq!(player.state(), PlayerState::WallSlide { wall: 1.0 });
+    }
+
+    #[test]
+    fn grab_a_ledge_and_climb_up() {
+        let mut player = Player::new(0.0, 0.0);
+        assert!(player.grab_ledge(1.0, 50.0));
+        assert_eq!(player.state(), PlayerState::LedgeHang { wall: 1.0 });
+        // Hanging still, with nothing but the climb on offer
+        assert!(!player.dash());
+        player.move_right();
+        assert_eq!(player.velocity(), Vec2::ZERO);
+        assert!(player.jump());
+        assert_eq!(player.state(), PlayerState::Grounded);
+        assert_eq!(player.position(), Vec2::new(32.0, 18.0));
+    }
+
+    #[test]
+    fn dropping_from_a_ledge_cant_regrab_at_once() {
+        let mut player = Player::new(0.0, 0.0);
+        assert!(player.grab_ledge(-1.0, 50.0));
+        player.drop_from_ledge();
+        assert_eq!(player.state(), PlayerState::Airborne);
+        assert!(!player.grab_ledge(-1.0, 60.0));
+        run_for(&mut player, GameConfig::LEDGE_REGRAB_COOLDOWN);
+        player.body.velocity.y = 0.0;
+        assert!(player.grab_ledge(-1.0, 60.0));
+    }
+
+    #[test]
+    fn stunned_players_ignore_everything_until_it_wears_off() {
+        let mut player = standing();
+        player.stun(0.2);
+        assert_eq!(player.state(), PlayerState::Stunned { remaining: 0.2 });
+        assert!(!player.jump());
+        assert!(!player.dash());
+        assert!(!player.attack());
+        player.move_right();
+        assert_eq!(player.velocity().x, 0.0);
+        run_for(&mut player, 0.25);
+        assert_eq!(player.state(), PlayerState::Grounded);
+        assert!(player.dash());
+    }
+
+    #[test]
+    fn only_standing_players_are_stunned() {
+        let mut player = Player::new(0.0, 0.0);
+        player.stun(0.2);
+        assert_eq!(player.state(), PlayerState::Airborne);
+    }
+
+    #[test]
+    fn swim_strokes_and_climb_out() {
+        let mut player = Player::new(0.0, 0.0);
+        player.enter_water();
+        assert_eq!(player.state(), PlayerState::Swim);
+        assert!(player.jump());
+        assert_eq!(player.state(), PlayerState::Swim);
+        assert_eq!(player.velocity().y, GameConfig::SWIM_STROKE_VELOCITY);
+        assert!(!player.dash());
+        player.leave_water();
+        assert_eq!(player.state(), PlayerState::Airborne);
+    }
+
+    #[test]
+    fn landing_mid_dash_does_not_cut_it_short() {
+        let mut player = standing();
+        assert!(player.dash());
+        player.set_on_ground(true);
+        assert!(is_dash(player.state()));
+        run_for(&mut player, GameConfig::DASH_TIME);
+        assert_eq!(player.state(), PlayerState::Airborne);
+        // Out of it at no more than running speed
+        assert!(player.velocity().x <= player.move_speed);
+    }
+
+    #[test]
+    fn one_dash_per_trip_through_the_air() {
+        let mut player = Player::new(0.0, 0.0);
+        assert!(player.dash());
+        run_for(&mut player, GameConfig::DASH_COOLDOWN);
+        assert_eq!(player.state(), PlayerState::Airborne);
+        assert!(!player.dash());
+        player.set_on_ground(true);
+        assert!(player.dash());
+    }
+
+    #[test]
+    fn nothing_gets_out_of_a_cannon_but_firing() {
+        let mut player = Player::new(0.0, 0.0);
+        assert!(player.enter_cannon());
+        assert_eq!(player.state(), PlayerState::InCannon);
+        assert!(!player.jump());
+        assert!(!player.dash());
+        assert!(!player.attack());
+        assert!(!player.grab_ledge(1.0, 0.0));
+        player.touch_wall(1.0);
+        player.enter_water();
+        player.set_on_ground(true);
+        player.stun(1.0);
+        player.glide(true);
+        player.move_left();
+        run_for(&mut player, 0.5);
+        assert_eq!(player.state(), PlayerState::InCannon);
+        assert_eq!(player.velocity(), Vec2::ZERO);
+
+        player.fire_from_cannon(Vec2::new(300.0, -500.0));
+        assert_eq!(player.state(), PlayerState::Airborne);
+        assert_eq!(player.velocity(), Vec2::new(300.0, -500.0));
+    }
+
+    #[test]
+    fn transition_table() {
+        use PlayerState::*;
+        let dash = Dash {
+            direction: 1.0,
+            remaining: 0.1,
+        };
+        let stunned = Stunned { remaining: 0.1 };
+        let slide = WallSlide { wall: 1.0 };
+        let hang = LedgeHang { wall: 1.0 };
+        let all = [
+            Grounded, Airborne, slide, dash, Glide, Swim, stunned, hang, InCannon,
+        ];
+        for state in all {
+            // Anything can be knocked loose, and nothing becomes itself
+            assert_eq!(state.can_become(&Airborne), state != Airborne, "{state:?}");
+            assert!(!state.can_become(&state), "{state:?}");
+        }
+        for next in all {
+            assert_eq!(InCannon.can_become(&next), next == Airborne, "{next:?}");
+        }
+        assert!(!dash.can_become(&Grounded));
+        assert!(!stunned.can_become(&dash));
+        assert!(!Grounded.can_become(&Glide));
+        assert!(!Airborne.can_become(&stunned));
+        assert!(!Swim.can_become(&Grounded));
+        assert!(Grounded.can_become(&stunned));
+        assert!(stunned.can_become(&Grounded));
+        assert!(dash.can_become(&slide));
+        assert!(Glide.can_become(&hang));
+    }
+}
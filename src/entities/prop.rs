@@ -0,0 +1,421 @@
+use macroquad::prelude::*;
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{colors, RenderLayer, RenderStyle};
+use crate::physics::collision::CollisionDetector;
+use crate::rng::SeededRng;
+
+/// Scenery that moves but never gets in the way: nothing collides with a prop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+    /// A tuft standing on the ground that bends as the player runs through it
+    Grass,
+    /// A board hanging from a hook that swings when bumped
+    Sign,
+    /// A spot in a canopy that leaves drift down from
+    Leaves,
+    /// A leafy bush in the foreground that the player passes behind. It sways like grass
+    /// and thins out while they're behind it so they stay visible.
+    Bush,
+}
+
+impl PropKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "grass" => Some(PropKind::Grass),
+            "sign" => Some(PropKind::Sign),
+            "leaves" => Some(PropKind::Leaves),
+            "bush" => Some(PropKind::Bush),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PropKind::Grass => "grass",
+            PropKind::Sign => "sign",
+            PropKind::Leaves => "leaves",
+            PropKind::Bush => "bush",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Leaf {
+    position: Vec2,
+    /// Seconds since it came loose, which also drives its flutter
+    age: f32,
+    phase: f32,
+}
+
+/// A decorative prop. Grass and signs lean by `angle`, pulled back by a spring or a
+/// pendulum; leaf spots keep the leaves they've dropped.
+#[derive(Debug, Clone)]
+pub struct Prop {
+    pub kind: PropKind,
+    /// Base of a tuft, hook of a sign or the spot leaves fall from
+    pub anchor: Vec2,
+    /// Radians from upright for grass, from hanging straight down for a sign
+    pub angle: f32,
+    spin: f32,
+    /// Sideways wind in pixels per second
+    pub wind: f32,
+    /// Speed of the player running through it, while they are
+    push: Option<f32>,
+    leaves: Vec<Leaf>,
+    leaf_timer: f32,
+    rng: SeededRng,
+}
+
+impl Prop {
+    pub fn new(kind: PropKind, anchor: Vec2) -> Self {
+        Self {
+            kind,
+            anchor,
+            angle: 0.0,
+            spin: 0.0,
+            wind: 0.0,
+            push: None,
+            leaves: Vec::new(),
+            leaf_timer: 0.0,
+            rng: SeededRng::new(anchor.x.to_bits() as u64 ^ (anchor.y.to_bits() as u64) << 32),
+        }
+    }
+
+    /// The part the player can brush against: the tuft or the board
+    pub fn get_bounds(&self) -> (f32, f32, f32, f32) {
+        let size = self.size();
+        match self.kind {
+            PropKind::Grass | PropKind::Bush => (
+                self.anchor.x - size.x / 2.0,
+                self.anchor.y - size.y,
+                self.anchor.x + size.x / 2.0,
+                self.anchor.y,
+            ),
+            PropKind::Sign => (
+                self.anchor.x - size.x / 2.0,
+                self.anchor.y,
+                self.anchor.x + size.x / 2.0,
+                self.anchor.y + size.y,
+            ),
+            PropKind::Leaves => (
+                self.anchor.x - size.x / 2.0,
+                self.anchor.y - size.y / 2.0,
+                self.anchor.x + size.x / 2.0,
+                self.anchor.y + size.y / 2.0,
+            ),
+        }
+    }
+
+    /// Let the player brush past. Grass bends away from them while they're in it and a
+    /// sign is knocked swinging when they first hit it.
+    pub fn touch(&mut self, player: &PhysicsBody) {
+        let touching = CollisionDetector::aabb_overlap(self.get_bounds(), player.get_bounds());
+        if self.kind == PropKind::Sign && touching && self.push.is_none() {
+            self.spin += player.velocity.x * GameConfig::PROP_SIGN_BUMP;
+        }
+        self.push = touching.then_some(player.velocity.x);
+    }
+
+    fn update_leaves(&mut self, delta_time: f32) {
+        self.leaf_timer -= delta_time;
+        if self.leaf_timer <= 0.0 && self.leaves.len() < GameConfig::PROP_LEAF_LIMIT {
+            self.leaf_timer = GameConfig::PROP_LEAF_INTERVAL * self.rng.range(0.5, 1.5);
+            let spread = GameConfig::PROP_LEAF_SPREAD;
+            self.leaves.push(Leaf {
+                position: self.anchor + Vec2::new(self.rng.range(-spread, spread), 0.0),
+                age: 0.0,
+                phase: self.rng.range(0.0, std::f32::consts::TAU),
+            });
+        }
+        for leaf in &mut self.leaves {
+            leaf.age += delta_time;
+            let sway = (leaf.age * 2.0 + leaf.phase).sin() * GameConfig::PROP_LEAF_SWAY;
+            leaf.position += Vec2::new(
+                self.wind * GameConfig::PROP_LEAF_DRIFT + sway,
+                GameConfig::PROP_LEAF_FALL_SPEED,
+            ) * delta_time;
+        }
+        self.leaves
+            .retain(|leaf| leaf.age < GameConfig::PROP_LEAF_LIFE);
+    }
+
+    fn render_grass(&self, x: f32, y: f32, angle: f32) {
+        let (w, h) = GameConfig::PROP_GRASS_SIZE;
+        let color = RenderStyle::current().pick(Color::new(0.2, 0.65, 0.2, 1.0), GREEN);
+        for (offset, height, lean) in [(-0.35, 0.75, -0.25), (0.0, 1.0, 0.0), (0.35, 0.8, 0.25)] {
+            let base = x + offset * w;
+            let blade = angle + lean;
+            let tip = Vec2::new(
+                base + blade.sin() * h * height,
+                y - blade.cos() * h * height,
+            );
+            draw_triangle(vec2(base - 2.0, y), vec2(base + 2.0, y), tip, color);
+        }
+    }
+
+    fn render_bush(&self, x: f32, y: f32, angle: f32) {
+        let (w, h) = GameConfig::PROP_BUSH_SIZE;
+        let style = RenderStyle::current();
+        let alpha = if self.push.is_some() {
+            GameConfig::PROP_BUSH_SEE_THROUGH
+        } else {
+            1.0
+        };
+        let dark = colors::with_alpha(
+            style.pick(Color::new(0.1, 0.38, 0.12, 1.0), DARKGREEN),
+            alpha,
+        );
+        let light = colors::with_alpha(style.pick(Color::new(0.16, 0.5, 0.16, 1.0), GREEN), alpha);
+        // Stiffer than a tuft, and higher clumps lean further so it bends from its roots
+        let angle = angle * 0.3;
+        for (offset, height, radius, color) in [
+            (-0.3, 0.35, 0.3, dark),
+            (0.3, 0.35, 0.3, dark),
+            (0.0, 0.55, 0.35, light),
+            (-0.15, 0.8, 0.22, light),
+            (0.2, 0.75, 0.2, light),
+        ] {
+            let lift = h * height;
+            draw_circle(
+                x + offset * w + angle.sin() * lift,
+                y - angle.cos() * lift,
+                w * radius,
+                color,
+            );
+        }
+    }
+
+    fn render_sign(&self, x: f32, y: f32, angle: f32) {
+        let (w, h) = GameConfig::PROP_SIGN_SIZE;
+        let style = RenderStyle::current();
+        let down = Vec2::new(-angle.sin(), angle.cos());
+        let across = Vec2::new(down.y, -down.x);
+        let hook = vec2(x, y);
+        let top = hook + down * GameConfig::PROP_SIGN_CHAIN;
+        let corners = [
+            top - across * w / 2.0,
+            top + across * w / 2.0,
+            top + across * w / 2.0 + down * h,
+            top - across * w / 2.0 + down * h,
+        ];
+        let chain = style.pick(GRAY, WHITE);
+        draw_line(hook.x, hook.y, corners[0].x, corners[0].y, 1.5, chain);
+        draw_line(hook.x, hook.y, corners[1].x, corners[1].y, 1.5, chain);
+        let wood = style.pick(Color::new(0.6, 0.4, 0.2, 1.0), ORANGE);
+        draw_triangle(corners[0], corners[1], corners[2], wood);
+        draw_triangle(corners[0], corners[2], corners[3], wood);
+        for i in 0..4 {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            draw_line(a.x, a.y, b.x, b.y, 2.0, Color::new(0.35, 0.2, 0.1, 1.0));
+        }
+        draw_circle(hook.x, hook.y, 2.0, DARKGRAY);
+    }
+
+    fn render_leaves(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        // A clump of foliage marks where they come from
+        let foliage = style.pick(Color::new(0.15, 0.5, 0.15, 1.0), GREEN);
+        let spread = GameConfig::PROP_LEAF_SPREAD;
+        for (offset, radius) in [(-0.6, 10.0), (0.0, 13.0), (0.6, 10.0)] {
+            draw_circle(
+                self.anchor.x + offset * spread + camera_x,
+                self.anchor.y + camera_y,
+                radius,
+                foliage,
+            );
+        }
+        if style.reduced_motion {
+            return;
+        }
+        let color = style.pick(Color::new(0.85, 0.55, 0.15, 1.0), YELLOW);
+        for leaf in &self.leaves {
+            // Fade out over the last second
+            let fade = (GameConfig::PROP_LEAF_LIFE - leaf.age).min(1.0);
+            let tilt = (leaf.age * 2.0 + leaf.phase).cos() * 0.8;
+            draw_rectangle_ex(
+                leaf.position.x + camera_x,
+                leaf.position.y + camera_y,
+                6.0,
+                3.0,
+                DrawRectangleParams {
+                    offset: vec2(0.5, 0.5),
+                    rotation: tilt,
+                    color: colors::with_alpha(color, fade),
+                },
+            );
+        }
+    }
+}
+
+impl Entity for Prop {
+    fn position(&self) -> Vec2 {
+        let (x1, y1, _, _) = self.get_bounds();
+        Vec2::new(x1, y1)
+    }
+
+    fn size(&self) -> Vec2 {
+        let (w, h) = match self.kind {
+            PropKind::Grass => GameConfig::PROP_GRASS_SIZE,
+            PropKind::Sign => (
+                GameConfig::PROP_SIGN_SIZE.0,
+                GameConfig::PROP_SIGN_SIZE.1 + GameConfig::PROP_SIGN_CHAIN,
+            ),
+            PropKind::Leaves => (GameConfig::PROP_LEAF_SPREAD * 2.0, 24.0),
+            PropKind::Bush => GameConfig::PROP_BUSH_SIZE,
+        };
+        Vec2::new(w, h)
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        // Held still under reduced motion
+        let angle = if RenderStyle::current().reduced_motion {
+            0.0
+        } else {
+            self.angle
+        };
+        let (x, y) = (self.anchor.x + camera_x, self.anchor.y + camera_y);
+        match self.kind {
+            PropKind::Grass => self.render_grass(x, y, angle),
+            PropKind::Sign => self.render_sign(x, y, angle),
+            PropKind::Leaves => self.render_leaves(camera_x, camera_y),
+            PropKind::Bush => self.render_bush(x, y, angle),
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        match self.kind {
+            PropKind::Grass | PropKind::Bush => {
+                // Bent away from the player while they're in it, otherwise leaning with the wind
+                let rest = match self.push {
+                    Some(speed) => speed * GameConfig::PROP_GRASS_PUSH,
+                    None => self.wind * GameConfig::PROP_WIND_LEAN,
+                };
+                let max = GameConfig::PROP_GRASS_MAX_BEND;
+                self.spin += (-(self.angle - rest.clamp(-max, max))
+                    * GameConfig::PROP_GRASS_STIFFNESS
+                    - self.spin * GameConfig::PROP_GRASS_DAMPING)
+                    * delta_time;
+                self.angle = (self.angle + self.spin * delta_time).clamp(-max, max);
+            }
+            PropKind::Sign => {
+                self.spin += (-self.angle.sin() * GameConfig::PROP_SIGN_SWING
+                    - self.spin * GameConfig::PROP_SIGN_DAMPING
+                    + self.wind * GameConfig::PROP_SIGN_WIND)
+                    * delta_time;
+                self.angle += self.spin * delta_time;
+            }
+            PropKind::Leaves => self.update_leaves(delta_time),
+        }
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        match self.kind {
+            PropKind::Bush => RenderLayer::Foreground,
+            PropKind::Grass | PropKind::Sign | PropKind::Leaves => RenderLayer::Background,
+        }
+    }
+}
+
+/// Every prop in the level. Only those near the view are animated, up to
+/// `MAX_ACTIVE_PROPS` of them, and only those on screen are drawn.
+#[derive(Debug, Clone, Default)]
+pub struct Props {
+    props: Vec<Prop>,
+}
+
+impl Props {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.props.clear();
+    }
+
+    pub fn spawn(&mut self, prop: Prop) {
+        self.props.push(prop);
+    }
+
+    /// Tufts of grass along the top of a ground platform, at uneven gaps that come out
+    /// the same every time for the same platform
+    pub fn scatter_grass(&mut self, position: Vec2, width: f32) {
+        let mut rng = SeededRng::new(position.x.to_bits() as u64 ^ 0x6A55);
+        let margin = GameConfig::PROP_GRASS_SIZE.0;
+        let mut x = position.x + margin + rng.range(0.0, GameConfig::PROP_GRASS_SPACING);
+        while x < position.x + width - margin {
+            self.spawn(Prop::new(PropKind::Grass, Vec2::new(x, position.y)));
+            x += GameConfig::PROP_GRASS_SPACING * rng.range(0.5, 1.5);
+        }
+    }
+
+    /// Step the props near `view` with the wind and the player. Nothing moves under
+    /// reduced motion.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        view: (f32, f32, f32, f32),
+        wind: f32,
+        player: &PhysicsBody,
+        reduced_motion: bool,
+    ) {
+        if reduced_motion {
+            return;
+        }
+        let center = Vec2::new(view.0 + view.2, view.1 + view.3) / 2.0;
+        let area = Self::cull_area(view);
+        let mut nearby: Vec<&mut Prop> = self
+            .props
+            .iter_mut()
+            .filter(|prop| CollisionDetector::aabb_overlap(prop.get_bounds(), area))
+            .collect();
+        if nearby.len() > GameConfig::MAX_ACTIVE_PROPS {
+            nearby.sort_by(|a, b| {
+                a.anchor
+                    .distance_squared(center)
+                    .total_cmp(&b.anchor.distance_squared(center))
+            });
+            nearby.truncate(GameConfig::MAX_ACTIVE_PROPS);
+        }
+        for prop in nearby {
+            prop.wind = wind;
+            prop.touch(player);
+            prop.update(delta_time);
+        }
+    }
+
+    /// Draw the props in `layer`
+    pub fn render(
+        &self,
+        layer: RenderLayer,
+        view: (f32, f32, f32, f32),
+        camera_x: f32,
+        camera_y: f32,
+    ) {
+        let area = Self::cull_area(view);
+        for prop in self
+            .props
+            .iter()
+            .filter(|prop| prop.render_layer() == layer)
+        {
+            // Leaves drift well away from their spot, so those are always drawn
+            if prop.kind == PropKind::Leaves
+                || CollisionDetector::aabb_overlap(prop.get_bounds(), area)
+            {
+                prop.render(camera_x, camera_y);
+            }
+        }
+    }
+
+    /// `view` grown by the cull margin
+    fn cull_area(view: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+        let margin = GameConfig::PROP_CULL_MARGIN;
+        (
+            view.0 - margin,
+            view.1 - margin,
+            view.2 + margin,
+            view.3 + margin,
+        )
+    }
+}
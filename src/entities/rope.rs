@@ -0,0 +1,129 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::manager::EntityId;
+use super::Entity;
+use crate::config::GameConfig;
+use crate::physics::collision::CollisionDetector;
+
+/// A rope hanging from a fixed anchor that the player can grab in mid-air and swing on.
+/// It swings as a stiff rod pivoting at the anchor, and keeps swinging for a while after
+/// it's let go.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rope {
+    pub anchor: Vec2,
+    pub length: f32,
+    /// Radians from straight down; positive swings the free end right
+    pub angle: f32,
+    /// Radians per second
+    pub angular_velocity: f32,
+}
+
+/// Tracks a player's hold on a rope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RopeSwing {
+    pub rope: EntityId,
+    /// How far down the rope from the anchor the player's hands are
+    pub distance: f32,
+    /// Steering input, -1 to pump the swing left and 1 to pump it right
+    #[serde(skip)]
+    pub push: f32,
+    /// Climbing input, -1 to climb up and 1 to slide down
+    #[serde(skip)]
+    pub climb: f32,
+}
+
+impl Rope {
+    pub fn new(anchor: Vec2, length: f32) -> Self {
+        Self {
+            anchor,
+            length,
+            angle: 0.0,
+            angular_velocity: 0.0,
+        }
+    }
+
+    /// Unit vector from the anchor toward the free end
+    pub fn direction(&self) -> Vec2 {
+        Vec2::new(self.angle.sin(), self.angle.cos())
+    }
+
+    /// Which way a point on the rope moves as the angle grows
+    pub fn tangent(&self) -> Vec2 {
+        Vec2::new(self.angle.cos(), -self.angle.sin())
+    }
+
+    /// World position at a distance down the rope, clamped to its ends
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        self.anchor + self.direction() * distance.clamp(0.0, self.length)
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.point_at(self.length)
+    }
+
+    /// Distance down the rope of the point closest to `point`
+    pub fn project(&self, point: Vec2) -> f32 {
+        (point - self.anchor)
+            .dot(self.direction())
+            .clamp(0.0, self.length)
+    }
+
+    /// How fast a point `distance` down the rope is moving
+    pub fn velocity_at(&self, distance: f32) -> Vec2 {
+        self.tangent() * self.angular_velocity * distance
+    }
+
+    /// Whether the rope passes through a body's bounds
+    pub fn touches(&self, bounds: (f32, f32, f32, f32)) -> bool {
+        CollisionDetector::segment_intersects_aabb(self.anchor, self.end(), bounds)
+    }
+}
+
+impl Entity for Rope {
+    fn position(&self) -> Vec2 {
+        self.anchor - Vec2::new(self.length, 0.0)
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.length * 2.0, self.length)
+    }
+
+    /// The rope is drawn as a chain of links; it only ever bends at the anchor
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let camera = Vec2::new(camera_x, camera_y);
+        let links = (self.length / GameConfig::ROPE_LINK_LENGTH).ceil().max(1.0) as usize;
+        let step = self.length / links as f32;
+        for i in 0..links {
+            let a = self.point_at(i as f32 * step) + camera;
+            let b = self.point_at((i + 1) as f32 * step) + camera;
+            let color = if i % 2 == 0 {
+                GameConfig::ROPE_COLOR
+            } else {
+                GameConfig::ROPE_LINK_COLOR
+            };
+            draw_line(a.x, a.y, b.x, b.y, 3.0, color);
+        }
+
+        let anchor = self.anchor + camera;
+        draw_rectangle(anchor.x - 8.0, anchor.y - 4.0, 16.0, 4.0, DARKGRAY);
+        draw_circle(anchor.x, anchor.y, 3.0, GRAY);
+        let end = self.end() + camera;
+        draw_circle(end.x, end.y, 3.0, GameConfig::ROPE_COLOR);
+    }
+
+    fn update(&mut self, _delta_time: f32) {
+        // Swinging is integrated by the physics step, which knows the level's gravity
+    }
+}
+
+impl RopeSwing {
+    pub fn new(rope: EntityId, distance: f32) -> Self {
+        Self {
+            rope,
+            distance,
+            push: 0.0,
+            climb: 0.0,
+        }
+    }
+}
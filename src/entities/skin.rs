@@ -0,0 +1,292 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::graphics::{colors, GraphicsUtils, RenderStyle};
+use crate::locale::{tr, tr_with};
+use crate::stats::{DeathCause, Stats};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EyeStyle {
+    Square,
+    Round,
+    /// One wide band across the face
+    Visor,
+    /// Half-closed slits
+    Sleepy,
+}
+
+/// How the face is posed this frame, on top of the skin's own eye style
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Face {
+    /// Offset of the eyes from where they sit looking straight ahead
+    pub look: Vec2,
+    /// How far the eyelids are down, from 0 open to 1 shut
+    pub closed: f32,
+    /// How wide a yawning mouth is open, 0 for no mouth
+    pub yawn: f32,
+}
+
+impl Face {
+    pub fn looking(look: Vec2) -> Self {
+        Self {
+            look,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailKind {
+    /// Fading copies of the body outline
+    Streak,
+    /// Twinkling particles left behind
+    Sparkle,
+}
+
+/// What it takes to be allowed to wear a skin, checked against lifetime stats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkinUnlock {
+    Always,
+    BestScore(i32),
+    Runs(u32),
+    Jumps(u32),
+    Coins(u32),
+    /// Died to every one of `DeathCause::HAZARDS` at least once
+    EveryHazard,
+}
+
+impl SkinUnlock {
+    pub fn is_met(&self, stats: &Stats) -> bool {
+        match *self {
+            SkinUnlock::Always => true,
+            SkinUnlock::BestScore(score) => stats.best_normal.max(stats.best_daily) >= score,
+            SkinUnlock::Runs(runs) => stats.runs >= runs,
+            SkinUnlock::Jumps(jumps) => stats.jumps >= jumps,
+            SkinUnlock::Coins(coins) => stats.coins >= coins,
+            SkinUnlock::EveryHazard => DeathCause::HAZARDS
+                .iter()
+                .all(|cause| stats.deaths_by(*cause) > 0),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match *self {
+            SkinUnlock::Always => tr("unlock.always"),
+            SkinUnlock::BestScore(score) => tr_with("unlock.best_score", &[("score", &score)]),
+            SkinUnlock::Runs(runs) => tr_with("unlock.runs", &[("runs", &runs)]),
+            SkinUnlock::Jumps(jumps) => tr_with("unlock.jumps", &[("jumps", &jumps)]),
+            SkinUnlock::Coins(coins) => tr_with("unlock.coins", &[("coins", &coins)]),
+            SkinUnlock::EveryHazard => tr("unlock.every_hazard"),
+        }
+    }
+}
+
+/// How the player looks: body color, eyes and an optional trail
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerSkin {
+    /// Name used in `settings.cfg`, and for its label in the language files
+    pub name: &'static str,
+    /// Body color; `None` follows the active palette so color-blind palettes still apply
+    pub color: Option<Color>,
+    pub eye_color: Color,
+    pub eyes: EyeStyle,
+    pub trail: Option<TrailKind>,
+    pub unlock: SkinUnlock,
+}
+
+pub const SKINS: [PlayerSkin; 6] = [
+    PlayerSkin {
+        name: "classic",
+        color: None,
+        eye_color: WHITE,
+        eyes: EyeStyle::Square,
+        trail: None,
+        unlock: SkinUnlock::Always,
+    },
+    PlayerSkin {
+        name: "mint",
+        color: Some(Color::new(0.4, 0.9, 0.7, 1.0)),
+        eye_color: Color::new(0.1, 0.2, 0.2, 1.0),
+        eyes: EyeStyle::Round,
+        trail: None,
+        unlock: SkinUnlock::Runs(10),
+    },
+    PlayerSkin {
+        name: "ember",
+        color: Some(Color::new(0.95, 0.4, 0.15, 1.0)),
+        eye_color: YELLOW,
+        eyes: EyeStyle::Visor,
+        trail: Some(TrailKind::Streak),
+        unlock: SkinUnlock::Jumps(500),
+    },
+    PlayerSkin {
+        name: "shadow",
+        color: Some(Color::new(0.25, 0.15, 0.35, 1.0)),
+        eye_color: Color::new(0.9, 0.3, 0.9, 1.0),
+        eyes: EyeStyle::Sleepy,
+        trail: Some(TrailKind::Streak),
+        unlock: SkinUnlock::Coins(100),
+    },
+    PlayerSkin {
+        name: "gold",
+        color: Some(Color::new(1.0, 0.8, 0.2, 1.0)),
+        eye_color: WHITE,
+        eyes: EyeStyle::Square,
+        trail: Some(TrailKind::Sparkle),
+        unlock: SkinUnlock::BestScore(1000),
+    },
+    PlayerSkin {
+        name: "ghost",
+        color: Some(Color::new(0.85, 0.9, 1.0, 1.0)),
+        eye_color: Color::new(0.15, 0.2, 0.35, 1.0),
+        eyes: EyeStyle::Round,
+        trail: Some(TrailKind::Streak),
+        unlock: SkinUnlock::EveryHazard,
+    },
+];
+
+impl PlayerSkin {
+    /// Name shown on the skins screen
+    pub fn label(&self) -> String {
+        tr(&format!("skin.{}", self.name))
+    }
+
+    pub fn classic() -> &'static PlayerSkin {
+        &SKINS[0]
+    }
+
+    pub fn from_name(name: &str) -> Option<&'static PlayerSkin> {
+        SKINS.iter().find(|skin| skin.name == name)
+    }
+
+    pub fn is_unlocked(&self, stats: &Stats) -> bool {
+        self.unlock.is_met(stats)
+    }
+
+    /// Body color in the current render style
+    pub fn body_color(&self) -> Color {
+        let style = RenderStyle::current();
+        let color = self.color.unwrap_or(style.palette().player);
+        style.pick(color, GameConfig::HIGH_CONTRAST_PLAYER)
+    }
+
+    /// Draw the body and face at a screen position
+    pub fn draw_body(&self, x: f32, y: f32, size: Vec2, face: Face) {
+        draw_rectangle(x, y, size.x, size.y, self.body_color());
+        RenderStyle::current().outline_rectangle(x, y, size.x, size.y);
+        self.draw_eyes(x + face.look.x, y + face.look.y, size, face.closed);
+        if face.yawn > 0.0 {
+            draw_ellipse(
+                x + size.x / 2.0,
+                y + size.y * 0.7,
+                size.x * 0.12,
+                size.y * 0.15 * face.yawn,
+                0.0,
+                Color::new(0.0, 0.0, 0.0, 0.6),
+            );
+        }
+    }
+
+    fn draw_eyes(&self, x: f32, y: f32, size: Vec2, closed: f32) {
+        let eye_y = y + size.y * 0.25;
+        let left_x = x + size.x * 0.25;
+        let right_x = x + size.x * 0.625;
+        let eye_size = size.x / 8.0;
+
+        if closed > 0.0 && self.eyes != EyeStyle::Visor {
+            // Lids come down from the top, leaving a thin line when shut
+            let open = eye_size * (1.0 - closed).max(0.2);
+            let lid_y = eye_y + eye_size - open;
+            draw_rectangle(left_x, lid_y, eye_size, open, self.eye_color);
+            draw_rectangle(right_x, lid_y, eye_size, open, self.eye_color);
+            return;
+        }
+        match self.eyes {
+            EyeStyle::Square => {
+                draw_rectangle(left_x, eye_y, eye_size, eye_size, self.eye_color);
+                draw_rectangle(right_x, eye_y, eye_size, eye_size, self.eye_color);
+            }
+            EyeStyle::Round => {
+                let radius = eye_size * 0.6;
+                draw_circle(left_x + radius, eye_y + radius, radius, self.eye_color);
+                draw_circle(right_x + radius, eye_y + radius, radius, self.eye_color);
+            }
+            EyeStyle::Visor => {
+                let open = eye_size * (1.0 - closed).max(0.2);
+                draw_rectangle(
+                    left_x - eye_size * 0.5,
+                    eye_y + eye_size - open,
+                    right_x - left_x + eye_size * 2.0,
+                    open,
+                    self.eye_color,
+                );
+            }
+            EyeStyle::Sleepy => {
+                let slit = eye_size * 0.4;
+                draw_rectangle(left_x, eye_y + slit, eye_size, slit, self.eye_color);
+                draw_rectangle(right_x, eye_y + slit, eye_size, slit, self.eye_color);
+            }
+        }
+    }
+
+    /// Draw the trail behind the player from oldest to newest screen-space centers
+    pub fn draw_trail(&self, points: &[Vec2], size: Vec2) {
+        let Some(trail) = self.trail else {
+            return;
+        };
+        let style = RenderStyle::current();
+        if style.reduced_motion {
+            return;
+        }
+
+        let color = self.body_color();
+        let count = points.len().max(1) as f32;
+        for (index, point) in points.iter().enumerate() {
+            // Older points are fainter and smaller
+            let age = 1.0 - index as f32 / count;
+            let strength = (1.0 - age) * 0.5;
+            match trail {
+                TrailKind::Streak => {
+                    let scale = 0.4 + 0.6 * (1.0 - age);
+                    let trail_size = size * scale;
+                    draw_rectangle(
+                        point.x - trail_size.x / 2.0,
+                        point.y - trail_size.y / 2.0,
+                        trail_size.x,
+                        trail_size.y,
+                        colors::with_alpha(color, strength),
+                    );
+                }
+                TrailKind::Sparkle => {
+                    if index % 3 == 0 {
+                        GraphicsUtils::draw_particles(
+                            *point,
+                            3,
+                            size.x * 0.4,
+                            colors::with_alpha(color, strength + 0.2),
+                            index as f32,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Menu preview: the skin bobbing between idle and a small hop, leaving its trail
+    pub fn draw_preview(&self, center: Vec2, size: Vec2) {
+        let time = RenderStyle::current().animation_time(get_time() as f32);
+        let hop = |t: f32| (t * 2.5).sin().max(0.0) * size.y * 1.5;
+
+        let points: Vec<Vec2> = (0..GameConfig::PLAYER_TRAIL_LENGTH)
+            .rev()
+            .map(|step| {
+                let t = time - step as f32 * GameConfig::FIXED_TIMESTEP;
+                Vec2::new(center.x, center.y - hop(t))
+            })
+            .collect();
+        self.draw_trail(&points, size);
+
+        let top_left = Vec2::new(center.x, center.y - hop(time)) - size / 2.0;
+        self.draw_body(top_left.x, top_left.y, size, Face::default());
+    }
+}
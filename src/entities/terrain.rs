@@ -0,0 +1,206 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::Entity;
+use crate::config::GameConfig;
+use crate::graphics::RenderLayer;
+
+/// A block of small square cells that can be blown away a few at a time. It collides as
+/// the cells that are left, with each row's unbroken runs merged into one rectangle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Terrain {
+    /// Top-left corner of the first cell
+    pub position: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Row by row, true where a cell is still there
+    cells: Vec<bool>,
+    /// World bounds of the runs of cells in each row, rebuilt whenever cells go
+    solids: Vec<(f32, f32, f32, f32)>,
+}
+
+impl Terrain {
+    /// A solid block covering `size`, rounded to whole cells
+    pub fn new(position: Vec2, size: Vec2) -> Self {
+        let cell = GameConfig::TERRAIN_CELL_SIZE;
+        let columns = (size.x / cell).round().max(1.0) as usize;
+        let rows = (size.y / cell).round().max(1.0) as usize;
+        let mut terrain = Self {
+            position,
+            columns,
+            rows,
+            cells: vec![true; columns * rows],
+            solids: Vec::new(),
+        };
+        terrain.rebuild_solids();
+        terrain
+    }
+
+    pub fn is_solid(&self, column: usize, row: usize) -> bool {
+        self.cells[row * self.columns + column]
+    }
+
+    /// What's left to collide with, as world bounds
+    pub fn solids(&self) -> &[(f32, f32, f32, f32)] {
+        &self.solids
+    }
+
+    pub fn cell_bounds(&self, column: usize, row: usize) -> (f32, f32, f32, f32) {
+        let cell = GameConfig::TERRAIN_CELL_SIZE;
+        let x = self.position.x + column as f32 * cell;
+        let y = self.position.y + row as f32 * cell;
+        (x, y, x + cell, y + cell)
+    }
+
+    /// Remove every cell whose center is within `radius` of `center`, returning how many
+    /// went
+    pub fn carve(&mut self, center: Vec2, radius: f32) -> usize {
+        let mut removed = 0;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let (x1, y1, x2, y2) = self.cell_bounds(column, row);
+                let cell_center = Vec2::new((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+                let index = row * self.columns + column;
+                if self.cells[index] && cell_center.distance(center) <= radius {
+                    self.cells[index] = false;
+                    removed += 1;
+                }
+            }
+        }
+        if removed > 0 {
+            self.rebuild_solids();
+        }
+        removed
+    }
+
+    fn rebuild_solids(&mut self) {
+        self.solids.clear();
+        for row in 0..self.rows {
+            let mut run_start = None;
+            for column in 0..=self.columns {
+                let solid = column < self.columns && self.is_solid(column, row);
+                match (solid, run_start) {
+                    (true, None) => run_start = Some(column),
+                    (false, Some(start)) => {
+                        let (x1, y1, _, _) = self.cell_bounds(start, row);
+                        let (_, _, x2, y2) = self.cell_bounds(column - 1, row);
+                        self.solids.push((x1, y1, x2, y2));
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// A steady shade for each cell, so the grain stays put as the camera moves
+fn cell_shade(column: usize, row: usize) -> f32 {
+    let hash = (column as u32)
+        .wrapping_mul(73_856_093)
+        .wrapping_add((row as u32).wrapping_mul(19_349_663));
+    1.0 + ((hash >> 8) % 21) as f32 / 100.0 - 0.1
+}
+
+impl Entity for Terrain {
+    fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.columns as f32, self.rows as f32) * GameConfig::TERRAIN_CELL_SIZE
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let base = GameConfig::TERRAIN_COLOR;
+        let cell = GameConfig::TERRAIN_CELL_SIZE;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                if !self.is_solid(column, row) {
+                    continue;
+                }
+                let shade = cell_shade(column, row);
+                let color = Color::new(base.r * shade, base.g * shade, base.b * shade, base.a);
+                let (x, y, _, _) = self.cell_bounds(column, row);
+                draw_rectangle(x + camera_x, y + camera_y, cell, cell, color);
+            }
+        }
+    }
+
+    fn update(&mut self, _delta_time: f32) {
+        // Terrain only changes when something carves it
+    }
+
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Platforms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Player;
+    use crate::physics::Physics;
+
+    /// A wall 4 cells thick and 12 tall, standing at x 100
+    fn wall() -> Terrain {
+        Terrain::new(Vec2::new(100.0, 0.0), Vec2::new(32.0, 96.0))
+    }
+
+    /// Walk a player right from x 40 at head height `y` for two seconds, pushing them out
+    /// of whatever's solid, and return how far they got
+    fn walk_through(terrain: &Terrain, y: f32) -> f32 {
+        let physics = Physics::new();
+        let mut player = Player::new(40.0, y);
+        for _ in 0..120 {
+            player.body.position.x += 5.0;
+            for &solid in terrain.solids() {
+                physics.touch_solid(&mut player, solid);
+            }
+        }
+        player.position().x
+    }
+
+    #[test]
+    fn a_solid_block_stops_the_player() {
+        let terrain = wall();
+        assert_eq!(terrain.solids().len(), 12);
+        assert!(walk_through(&terrain, 40.0) + 32.0 <= 100.0 + 0.01);
+    }
+
+    #[test]
+    fn a_carved_tunnel_lets_the_player_through() {
+        let mut terrain = wall();
+        // Bore along the middle at the player's height, 40 to 72
+        let removed: usize = (0..4)
+            .map(|step| terrain.carve(Vec2::new(104.0 + step as f32 * 8.0, 56.0), 24.0))
+            .sum();
+        assert!(removed >= 16);
+        for row in 5..9 {
+            for column in 0..4 {
+                assert!(!terrain.is_solid(column, row));
+            }
+        }
+        // The rebuilt rectangles leave the tunnel rows open
+        assert!(terrain
+            .solids()
+            .iter()
+            .all(|&(_, top, _, bottom)| bottom <= 40.0 || top >= 72.0));
+        assert!(walk_through(&terrain, 40.0) > 132.0);
+        // Lower down it's still a wall
+        assert!(walk_through(&terrain, 64.0 + 8.0) + 32.0 <= 100.0 + 0.01);
+    }
+
+    #[test]
+    fn carving_a_hole_splits_the_row() {
+        let mut terrain = Terrain::new(Vec2::ZERO, Vec2::new(80.0, 8.0));
+        assert_eq!(terrain.solids(), &[(0.0, 0.0, 80.0, 8.0)]);
+        assert_eq!(terrain.carve(Vec2::new(36.0, 4.0), 1.0), 1);
+        assert_eq!(
+            terrain.solids(),
+            &[(0.0, 0.0, 32.0, 8.0), (40.0, 0.0, 80.0, 8.0)]
+        );
+        // Nothing there to carve
+        assert_eq!(terrain.carve(Vec2::new(36.0, 4.0), 1.0), 0);
+    }
+}
@@ -0,0 +1,175 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+
+/// How closely something is simulated, going by how far it is from the view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SimLevel {
+    /// Every step
+    #[default]
+    Full,
+    /// Every few steps, with the time in between added on, and without looking out for
+    /// the player
+    Reduced,
+    /// Not at all until the view comes closer
+    Asleep,
+}
+
+impl SimLevel {
+    /// The level for something centered at `center` while the view is centered on
+    /// `view_center`, measured in screens across or up and down, whichever is further
+    pub fn at(center: Vec2, view_center: Vec2) -> Self {
+        let offset = (center - view_center).abs();
+        let screens =
+            (offset.x / GameConfig::VIRTUAL_WIDTH).max(offset.y / GameConfig::VIRTUAL_HEIGHT);
+        if screens > GameConfig::SIM_SLEEP_DISTANCE {
+            SimLevel::Asleep
+        } else if screens > GameConfig::SIM_REDUCED_DISTANCE {
+            SimLevel::Reduced
+        } else {
+            SimLevel::Full
+        }
+    }
+}
+
+/// What to do with a throttled entity this step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Due {
+    /// Nothing yet
+    Wait,
+    /// Update it as usual over this many seconds
+    Step(f32),
+    /// It has just woken up after this many seconds asleep: bring it up to date without
+    /// simulating every moment of it
+    Wake(f32),
+}
+
+/// Keeps track of the time an entity far from the view has missed, so that it's running
+/// at the right pace and in the right place whenever it's looked at
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Throttle {
+    pub level: SimLevel,
+    /// Seconds passed since it was last updated
+    owed: f32,
+    /// Whether it has been asleep since it was last updated
+    slept: bool,
+}
+
+impl Throttle {
+    /// Count a step of `delta_time` at `level`; `step` is the simulation step number, which
+    /// reduced entities are updated on every `SIM_REDUCED_INTERVAL`th of
+    pub fn step(&mut self, level: SimLevel, delta_time: f32, step: u64) -> Due {
+        self.level = level;
+        self.owed += delta_time;
+        self.slept |= level == SimLevel::Asleep;
+        if level == SimLevel::Asleep
+            || (level == SimLevel::Reduced
+                && !step.is_multiple_of(GameConfig::SIM_REDUCED_INTERVAL))
+        {
+            return Due::Wait;
+        }
+        let owed = std::mem::take(&mut self.owed);
+        if std::mem::take(&mut self.slept) {
+            Due::Wake(owed)
+        } else {
+            Due::Step(owed)
+        }
+    }
+}
+
+/// How many throttled entities were at each level in the last step, for the performance
+/// overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimCounts {
+    pub full: usize,
+    pub reduced: usize,
+    pub asleep: usize,
+}
+
+impl SimCounts {
+    pub fn count(&mut self, level: SimLevel) {
+        match level {
+            SimLevel::Full => self.full += 1,
+            SimLevel::Reduced => self.reduced += 1,
+            SimLevel::Asleep => self.asleep += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 1.0 / 120.0;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn distance_from_the_view_picks_the_level() {
+        let view = Vec2::new(640.0, 360.0);
+        let screens = |x: f32| view + Vec2::new(x * GameConfig::VIRTUAL_WIDTH, 0.0);
+        assert_eq!(SimLevel::at(view, view), SimLevel::Full);
+        assert_eq!(SimLevel::at(screens(-1.5), view), SimLevel::Full);
+        assert_eq!(SimLevel::at(screens(3.0), view), SimLevel::Reduced);
+        assert_eq!(SimLevel::at(screens(-5.0), view), SimLevel::Asleep);
+        let below = view + Vec2::new(0.0, 5.0 * GameConfig::VIRTUAL_HEIGHT);
+        assert_eq!(SimLevel::at(below, view), SimLevel::Asleep);
+    }
+
+    #[test]
+    fn full_simulation_is_due_every_step() {
+        let mut throttle = Throttle::default();
+        for step in 0..10 {
+            assert_eq!(throttle.step(SimLevel::Full, STEP, step), Due::Step(STEP));
+        }
+    }
+
+    #[test]
+    fn reduced_simulation_catches_up_on_the_interval() {
+        let mut throttle = Throttle::default();
+        let interval = GameConfig::SIM_REDUCED_INTERVAL;
+        let mut owed = 0.0;
+        for step in 1..=interval * 3 {
+            match throttle.step(SimLevel::Reduced, STEP, step) {
+                Due::Wait => assert!(!step.is_multiple_of(interval)),
+                Due::Step(elapsed) => {
+                    assert!(step.is_multiple_of(interval));
+                    owed += elapsed;
+                }
+                Due::Wake(_) => panic!("never slept"),
+            }
+        }
+        // None of the time is lost
+        assert!(close(owed, STEP * (interval * 3) as f32));
+    }
+
+    #[test]
+    fn waking_hands_over_all_the_time_asleep() {
+        let mut throttle = Throttle::default();
+        for step in 0..300 {
+            assert_eq!(throttle.step(SimLevel::Asleep, STEP, step), Due::Wait);
+        }
+        let Due::Wake(elapsed) = throttle.step(SimLevel::Full, STEP, 300) else {
+            panic!("should wake");
+        };
+        assert!(close(elapsed, 301.0 * STEP));
+        // Back to plain steps afterwards
+        assert_eq!(throttle.step(SimLevel::Full, STEP, 301), Due::Step(STEP));
+    }
+
+    #[test]
+    fn waking_into_reduced_waits_for_its_turn() {
+        let mut throttle = Throttle::default();
+        let interval = GameConfig::SIM_REDUCED_INTERVAL;
+        throttle.step(SimLevel::Asleep, STEP, 0);
+        for step in 1..interval {
+            assert_eq!(throttle.step(SimLevel::Reduced, STEP, step), Due::Wait);
+        }
+        let Due::Wake(elapsed) = throttle.step(SimLevel::Reduced, STEP, interval) else {
+            panic!("should wake on the interval");
+        };
+        assert!(close(elapsed, (interval + 1) as f32 * STEP));
+    }
+}
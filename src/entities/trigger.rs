@@ -0,0 +1,86 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::graphics::{GraphicsUtils, TextStyle};
+
+/// What happens when the player first walks into a trigger region
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    /// A boulder rolls in from behind the player
+    BoulderChase,
+    /// Boulders that reach this region break apart
+    ChaseEnd,
+}
+
+impl TriggerAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "boulder" => Some(TriggerAction::BoulderChase),
+            "chase_end" => Some(TriggerAction::ChaseEnd),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TriggerAction::BoulderChase => "boulder",
+            TriggerAction::ChaseEnd => "chase_end",
+        }
+    }
+}
+
+/// An invisible region that sets off a scripted event once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trigger {
+    pub body: PhysicsBody,
+    pub action: TriggerAction,
+    pub fired: bool,
+}
+
+impl Trigger {
+    pub fn new(x: f32, y: f32, width: f32, height: f32, action: TriggerAction) -> Self {
+        Self {
+            body: PhysicsBody::new(x, y, width, height),
+            action,
+            fired: false,
+        }
+    }
+
+    /// True the first time the body enters the region, never again after that
+    pub fn fire(&mut self, body: &PhysicsBody) -> bool {
+        if self.fired || !self.body.overlaps_with(body) {
+            return false;
+        }
+        self.fired = true;
+        true
+    }
+}
+
+impl Entity for Trigger {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    /// Triggers are invisible in play; this outline is for the editor
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y;
+        let color = Color::new(1.0, 0.5, 0.0, 0.6);
+        draw_rectangle_lines(x, y, w, h, 1.0, color);
+        GraphicsUtils::text(
+            self.action.name(),
+            x + 4.0,
+            y + 14.0,
+            TextStyle::Label.size(),
+            color,
+        );
+    }
+
+    fn update(&mut self, _delta_time: f32) {}
+}
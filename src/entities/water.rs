@@ -0,0 +1,120 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::config::GameConfig;
+use crate::graphics::{RenderLayer, RenderStyle};
+
+/// A pool of water. The player swims in it, loose things float or sink in it depending
+/// on their buoyancy, and everything in it is pushed along by its current.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Water {
+    /// The top of the body is the still surface
+    pub body: PhysicsBody,
+    /// Acceleration on whatever is under the surface, in pixels per second squared,
+    /// scaled by how much of it is under
+    pub current: Vec2,
+    /// Seconds the surface has been rippling, kept with the simulation so floating things
+    /// ride the same waves that are drawn
+    pub elapsed: f32,
+}
+
+impl Water {
+    pub fn new(position: Vec2, size: Vec2, current: Vec2) -> Self {
+        Self {
+            body: PhysicsBody::new(position.x, position.y, size.x, size.y),
+            current,
+            elapsed: 0.0,
+        }
+    }
+
+    /// World y of the surface at world `x`, a gentle swell either side of the still level
+    /// that drifts the way the current flows
+    pub fn surface_at(&self, x: f32) -> f32 {
+        let phase = x / GameConfig::WATER_RIPPLE_LENGTH * std::f32::consts::TAU
+            - self.elapsed * GameConfig::WATER_RIPPLE_SPEED * self.current.x.signum();
+        self.body.position.y + phase.sin() * GameConfig::WATER_RIPPLE_HEIGHT
+    }
+
+    /// Share of `body`'s bounding box that is in the water, from 0 when it's clear of it
+    /// to 1 when it's wholly under the surface
+    pub fn submerged(&self, body: &PhysicsBody) -> f32 {
+        let (x1, y1, x2, y2) = body.get_bounds();
+        let (wx1, _, wx2, wy2) = self.body.get_bounds();
+        let across = (x2.min(wx2) - x1.max(wx1)) / (x2 - x1);
+        if across <= 0.0 {
+            return 0.0;
+        }
+        let surface = self.surface_at((x1 + x2) / 2.0);
+        let under = (y2.min(wy2) - y1.max(surface)) / (y2 - y1);
+        (across * under).clamp(0.0, 1.0)
+    }
+
+    /// Keep a point inside the pool, for things that only move with the water
+    pub fn clamp(&self, position: Vec2, size: Vec2) -> Vec2 {
+        let (x1, y1, x2, y2) = self.body.get_bounds();
+        Vec2::new(
+            position.x.clamp(x1, (x2 - size.x).max(x1)),
+            position.y.clamp(y1, (y2 - size.y).max(y1)),
+        )
+    }
+}
+
+impl Entity for Water {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let style = RenderStyle::current();
+        let (x1, y1, x2, y2) = self.body.get_bounds();
+        let color = style.pick(GameConfig::WATER_COLOR, GameConfig::HIGH_CONTRAST_WATER);
+        let surface_color = GameConfig::WATER_FOAM_COLOR;
+
+        // The body of the water hangs down from its rippling surface in thin columns
+        let step: f32 = 4.0;
+        let mut x = x1;
+        let mut previous: Option<Vec2> = None;
+        while x < x2 {
+            let width = step.min(x2 - x);
+            let surface = if style.reduced_motion {
+                y1
+            } else {
+                self.surface_at(x + width / 2.0)
+            };
+            draw_rectangle(x + camera_x, surface + camera_y, width, y2 - surface, color);
+            let point = Vec2::new(x + camera_x, surface + camera_y);
+            if let Some(last) = previous {
+                draw_line(last.x, last.y, point.x, point.y, 2.0, surface_color);
+            }
+            previous = Some(point);
+            x += step;
+        }
+        if let Some(last) = previous {
+            draw_line(last.x, last.y, x2 + camera_x, last.y, 2.0, surface_color);
+        }
+        if style.high_contrast {
+            draw_rectangle_lines(
+                x1 + camera_x,
+                y1 + camera_y,
+                x2 - x1,
+                y2 - y1,
+                GameConfig::HIGH_CONTRAST_OUTLINE_WIDTH,
+                GameConfig::HIGH_CONTRAST_OUTLINE,
+            );
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+    }
+
+    /// The fill is see-through, so drawn over the player it looks like they're in it
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Foreground
+    }
+}
@@ -0,0 +1,115 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::manager::EntityId;
+use super::Entity;
+use crate::config::GameConfig;
+use crate::physics::collision::CollisionDetector;
+
+/// A taut line between two anchor points that the player can hang from and slide along
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Zipline {
+    pub start: Vec2,
+    pub end: Vec2,
+    #[serde(with = "crate::save::color_serde")]
+    pub color: Color,
+}
+
+/// Tracks a player's progress while riding a zipline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZiplineRide {
+    pub line: EntityId,
+    /// Distance along the line measured from the start anchor
+    pub distance: f32,
+    /// Signed speed along the line (positive moves toward the end anchor)
+    pub speed: f32,
+}
+
+impl Zipline {
+    pub fn new(start_x: f32, start_y: f32, end_x: f32, end_y: f32) -> Self {
+        Self {
+            start: Vec2::new(start_x, start_y),
+            end: Vec2::new(end_x, end_y),
+            color: GameConfig::ZIPLINE_COLOR,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.end - self.start).length()
+    }
+
+    /// Unit vector pointing from the start anchor to the end anchor
+    pub fn direction(&self) -> Vec2 {
+        (self.end - self.start).normalize_or_zero()
+    }
+
+    /// World position at a distance along the line, clamped to the anchors
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        self.start + self.direction() * distance.clamp(0.0, self.length())
+    }
+
+    /// Distance along the line of the point closest to `point`
+    pub fn project(&self, point: Vec2) -> f32 {
+        (point - self.start)
+            .dot(self.direction())
+            .clamp(0.0, self.length())
+    }
+
+    /// Acceleration along the line caused by gravity; positive pulls toward the end anchor
+    pub fn slide_acceleration(&self, gravity: f32) -> f32 {
+        gravity * self.direction().y
+    }
+
+    /// Check whether a body's grab area touches the line
+    pub fn can_grab(&self, grab_bounds: (f32, f32, f32, f32)) -> bool {
+        CollisionDetector::segment_intersects_aabb(self.start, self.end, grab_bounds)
+    }
+
+    /// Draw the trolley handle the player is holding on to
+    pub fn render_handle(&self, distance: f32, camera_x: f32, camera_y: f32) {
+        let point = self.point_at(distance);
+        let x = point.x + camera_x;
+        let y = point.y + camera_y;
+
+        draw_rectangle(x - 6.0, y - 3.0, 12.0, 6.0, GRAY);
+        draw_rectangle_lines(x - 6.0, y - 3.0, 12.0, 6.0, 1.0, BLACK);
+    }
+}
+
+impl Entity for Zipline {
+    fn position(&self) -> Vec2 {
+        self.start.min(self.end)
+    }
+
+    fn size(&self) -> Vec2 {
+        (self.end - self.start).abs()
+    }
+
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let start = self.start + Vec2::new(camera_x, camera_y);
+        let end = self.end + Vec2::new(camera_x, camera_y);
+
+        // Draw the cable
+        draw_line(start.x, start.y, end.x, end.y, 2.0, self.color);
+
+        // Draw anchor posts
+        for anchor in [start, end] {
+            draw_rectangle(anchor.x - 3.0, anchor.y - 4.0, 6.0, 24.0, BROWN);
+            draw_circle(anchor.x, anchor.y, 4.0, self.color);
+        }
+    }
+
+    fn update(&mut self, _delta_time: f32) {
+        // Ziplines are static
+    }
+}
+
+impl ZiplineRide {
+    pub fn new(line: EntityId, distance: f32, speed: f32) -> Self {
+        Self {
+            line,
+            distance,
+            speed,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Entity, PhysicsBody};
+use crate::audio::SoundId;
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, TextStyle};
+
+/// Weather a zone can switch the level to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Weather {
+    /// No wind and full light
+    Clear,
+    /// Only the area around the player is lit
+    Dark,
+    /// A steady push, in pixels per second; negative blows left
+    Wind(f32),
+}
+
+impl Weather {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Dark => "dark",
+            Weather::Wind(_) => "wind",
+        }
+    }
+}
+
+/// Music tracks by their name in level files; "off" is silence
+pub fn music_from_name(name: &str) -> Option<Option<SoundId>> {
+    match name {
+        "main" => Some(Some(SoundId::Music)),
+        "off" => Some(None),
+        _ => None,
+    }
+}
+
+pub fn music_name(track: Option<SoundId>) -> &'static str {
+    match track {
+        Some(_) => "main",
+        None => "off",
+    }
+}
+
+/// What a trigger zone does when the player walks in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ZoneEffect {
+    /// Show a hint at the top of the screen for a few seconds
+    ShowTutorial(String),
+    /// A boulder rolls in from behind the player
+    StartBoulder,
+    /// Dying in a play-test brings the player back here rather than to the spawn point
+    SetCheckpoint,
+    /// Switch to another music track, or stop the music
+    ChangeMusic(Option<SoundId>),
+    SetWeather(Weather),
+    /// Hold the camera inside a world rectangle, given as position and size, until the
+    /// player leaves the zone
+    CameraLock {
+        position: Vec2,
+        size: Vec2,
+    },
+    GiveScore(i32),
+    /// Move the player to a world position
+    Teleport(Vec2),
+    /// Scroll the camera right at `speed` pixels per second whatever the player does,
+    /// until its left edge reaches `end_x`
+    AutoScroll {
+        speed: f32,
+        end_x: f32,
+    },
+}
+
+impl ZoneEffect {
+    /// Every kind of effect with placeholder settings, in the order the editor offers them
+    pub fn defaults(position: Vec2, size: Vec2) -> [ZoneEffect; 9] {
+        [
+            ZoneEffect::ShowTutorial("Hint".to_string()),
+            ZoneEffect::StartBoulder,
+            ZoneEffect::SetCheckpoint,
+            ZoneEffect::ChangeMusic(Some(SoundId::Music)),
+            ZoneEffect::SetWeather(Weather::Clear),
+            ZoneEffect::CameraLock { position, size },
+            ZoneEffect::GiveScore(100),
+            ZoneEffect::Teleport(position),
+            ZoneEffect::AutoScroll {
+                speed: GameConfig::AUTO_SCROLL_SPEED,
+                end_x: position.x + size.x + GameConfig::VIRTUAL_WIDTH,
+            },
+        ]
+    }
+
+    /// The kind of effect after this one, with placeholder settings for a zone at
+    /// `position` of `size`
+    pub fn next(&self, position: Vec2, size: Vec2) -> ZoneEffect {
+        let defaults = Self::defaults(position, size);
+        let current = defaults
+            .iter()
+            .position(|effect| effect.name() == self.name())
+            .unwrap_or(0);
+        defaults[(current + 1) % defaults.len()].clone()
+    }
+
+    /// Name of the effect in level files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ZoneEffect::ShowTutorial(_) => "tutorial",
+            ZoneEffect::StartBoulder => "boulder",
+            ZoneEffect::SetCheckpoint => "checkpoint",
+            ZoneEffect::ChangeMusic(_) => "music",
+            ZoneEffect::SetWeather(_) => "weather",
+            ZoneEffect::CameraLock { .. } => "camera",
+            ZoneEffect::GiveScore(_) => "score",
+            ZoneEffect::Teleport(_) => "teleport",
+            ZoneEffect::AutoScroll { .. } => "scroll",
+        }
+    }
+}
+
+/// Something that happened at a zone this update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneEvent {
+    /// The player came in and the zone was ready, so its effect should happen
+    Enter,
+    /// The player left after an `Enter`
+    Exit,
+}
+
+/// An invisible region that does something when the player walks in, once or again
+/// every time they come back after a cooldown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerZone {
+    pub body: PhysicsBody,
+    pub effect: ZoneEffect,
+    /// Seconds before the zone can fire again, or None to fire only once
+    pub repeat: Option<f32>,
+    /// Whether the player was inside at the last update
+    pub inside: bool,
+    /// Whether the player is inside after an `Enter`, waiting for the `Exit`
+    pub active: bool,
+    pub fired: bool,
+    /// Seconds until a repeating zone is ready again
+    pub cooldown: f32,
+}
+
+impl TriggerZone {
+    pub fn new(position: Vec2, size: Vec2, effect: ZoneEffect, repeat: Option<f32>) -> Self {
+        Self {
+            body: PhysicsBody::new(position.x, position.y, size.x, size.y),
+            effect,
+            repeat,
+            inside: false,
+            active: false,
+            fired: false,
+            cooldown: 0.0,
+        }
+    }
+
+    fn ready(&self) -> bool {
+        match self.repeat {
+            Some(_) => self.cooldown <= 0.0,
+            None => !self.fired,
+        }
+    }
+
+    /// Follow `body` in and out of the zone. Starting inside counts as walking in, and
+    /// walking in while the zone isn't ready gives no event and so no `Exit` either.
+    pub fn track(&mut self, body: &PhysicsBody, delta_time: f32) -> Option<ZoneEvent> {
+        self.cooldown = (self.cooldown - delta_time).max(0.0);
+        let inside = self.body.overlaps_with(body);
+        if inside == self.inside {
+            return None;
+        }
+        self.inside = inside;
+
+        if !inside {
+            return std::mem::take(&mut self.active).then_some(ZoneEvent::Exit);
+        }
+        if !self.ready() {
+            return None;
+        }
+        self.fired = true;
+        self.active = true;
+        self.cooldown = self.repeat.unwrap_or(0.0);
+        Some(ZoneEvent::Enter)
+    }
+}
+
+impl Entity for TriggerZone {
+    fn position(&self) -> Vec2 {
+        self.body.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.body.size
+    }
+
+    /// Zones are invisible in play; this outline is for the editor
+    fn render(&self, camera_x: f32, camera_y: f32) {
+        let (w, h) = (self.body.size.x, self.body.size.y);
+        let x = self.body.position.x + camera_x;
+        let y = self.body.position.y + camera_y;
+        let color = Color::new(0.3, 0.8, 1.0, 0.7);
+        draw_rectangle(x, y, w, h, Color::new(0.3, 0.8, 1.0, 0.08));
+        draw_rectangle_lines(x, y, w, h, 1.0, color);
+        let label = match self.repeat {
+            Some(cooldown) => format!("{} (every {}s)", self.effect.name(), cooldown),
+            None => self.effect.name().to_string(),
+        };
+        GraphicsUtils::text(&label, x + 4.0, y + 14.0, TextStyle::Label.size(), color);
+    }
+
+    fn update(&mut self, _delta_time: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 1.0 / 60.0;
+    /// Player x positions in and well clear of the zone
+    const INSIDE: f32 = 40.0;
+    const OUTSIDE: f32 = 300.0;
+
+    /// A 100 pixel square zone at the origin
+    fn zone(repeat: Option<f32>) -> TriggerZone {
+        TriggerZone::new(
+            Vec2::ZERO,
+            Vec2::splat(100.0),
+            ZoneEffect::GiveScore(10),
+            repeat,
+        )
+    }
+
+    fn player_at(x: f32) -> PhysicsBody {
+        PhysicsBody::new(x, 40.0, 20.0, 20.0)
+    }
+
+    #[test]
+    fn spawning_inside_counts_as_walking_in() {
+        let mut zone = zone(None);
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+        // Staying put doesn't fire again
+        assert_eq!(zone.track(&player_at(INSIDE + 5.0), STEP), None);
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), Some(ZoneEvent::Exit));
+    }
+
+    #[test]
+    fn one_shot_zones_fire_once() {
+        let mut zone = zone(None);
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), None);
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), Some(ZoneEvent::Exit));
+        // Coming back does nothing, and so leaving again has no exit either
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), None);
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), None);
+    }
+
+    #[test]
+    fn repeating_zones_fire_again_after_the_cooldown() {
+        let mut zone = zone(Some(1.0));
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), Some(ZoneEvent::Exit));
+
+        // Straight back in is too soon
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), None);
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), None);
+
+        // Waiting outside lets the cooldown run out
+        assert_eq!(zone.track(&player_at(OUTSIDE), 1.0), None);
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+    }
+
+    #[test]
+    fn the_cooldown_runs_while_inside() {
+        let mut zone = zone(Some(0.5));
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+        assert_eq!(zone.track(&player_at(INSIDE), 0.5), None);
+        assert_eq!(zone.track(&player_at(OUTSIDE), STEP), Some(ZoneEvent::Exit));
+        assert_eq!(zone.track(&player_at(INSIDE), STEP), Some(ZoneEvent::Enter));
+    }
+}
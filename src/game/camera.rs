@@ -0,0 +1,57 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+
+/// An eased hand-over between two ways of placing the camera, such as following the
+/// player and holding still in a locked room
+#[derive(Debug, Clone)]
+pub struct CameraBlend {
+    /// Camera position when the hand-over started
+    from: Vec2,
+    /// Seconds since it started
+    elapsed: f32,
+}
+
+impl CameraBlend {
+    pub fn new(from: Vec2) -> Self {
+        Self { from, elapsed: 0.0 }
+    }
+
+    /// Advance by `delta_time` seconds, returning whether the blend has finished
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.elapsed += delta_time;
+        self.elapsed >= GameConfig::CAMERA_LOCK_BLEND_TIME
+    }
+
+    /// Camera position on the way from where the blend started to `target`, which may
+    /// itself be moving
+    pub fn position(&self, target: Vec2) -> Vec2 {
+        let t = (self.elapsed / GameConfig::CAMERA_LOCK_BLEND_TIME).clamp(0.0, 1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        self.from.lerp(target, eased)
+    }
+}
+
+/// A forced-scroll segment: the camera moves right on its own until it reaches the end.
+/// The view's left edge pushes the player along, and being squeezed between it and a
+/// solid counts as a hit. It stands still while paused, and losing a heart or dying ends
+/// it, so a zone with `every 0` starts it again when the player walks back in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoScroll {
+    /// Pixels per second
+    pub speed: f32,
+    /// Camera left edge where the segment ends
+    pub end_x: f32,
+}
+
+impl AutoScroll {
+    /// Camera left edge `delta_time` seconds on from `camera_x`; it never scrolls back,
+    /// even when the segment starts past its end
+    pub fn advance(&self, camera_x: f32, delta_time: f32) -> f32 {
+        (camera_x + self.speed * delta_time).min(self.end_x.max(camera_x))
+    }
+
+    pub fn is_finished(&self, camera_x: f32) -> bool {
+        camera_x >= self.end_x
+    }
+}
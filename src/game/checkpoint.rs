@@ -0,0 +1,134 @@
+use macroquad::prelude::*;
+
+use crate::entities::manager::{Arena, EntityManager};
+use crate::entities::{
+    Bomb, Boss, Boulder, Door, Enemy, Pad, Platform, Switch, Terrain, Trigger, TriggerZone,
+};
+
+/// Everything in the level that can change during play, as it was when a checkpoint was
+/// reached. Respawning there puts it back, so the stretch after the checkpoint is played
+/// fresh while whatever was dealt with before it (defeated enemies, broken platforms,
+/// carved terrain, opened doors) stays that way.
+///
+/// Pickups aren't part of it: the score already has them, so they stay collected.
+///
+/// What's past the checkpoint comes back as it was when the checkpoint was reached, not
+/// as the level file places it: a patroller ahead resumes from wherever its patrol had
+/// taken it by then, rather than its starting spot. Asleep or not, patrols keep time, so
+/// that's where it would be had the level been played fresh up to the checkpoint.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Where the player reappears
+    pub position: Vec2,
+    platforms: Arena<Platform>,
+    terrain: Arena<Terrain>,
+    pads: Arena<Pad>,
+    switches: Arena<Switch>,
+    doors: Arena<Door>,
+    triggers: Arena<Trigger>,
+    zones: Arena<TriggerZone>,
+    boulders: Arena<Boulder>,
+    bosses: Arena<Boss>,
+    enemies: Arena<Enemy>,
+    bombs: Arena<Bomb>,
+}
+
+impl Snapshot {
+    pub fn take(entities: &EntityManager, position: Vec2) -> Self {
+        Self {
+            position,
+            platforms: entities.platforms.clone(),
+            terrain: entities.terrain.clone(),
+            pads: entities.pads.clone(),
+            switches: entities.switches.clone(),
+            doors: entities.doors.clone(),
+            triggers: entities.triggers.clone(),
+            zones: entities.zones.clone(),
+            boulders: entities.boulders.clone(),
+            bosses: entities.bosses.clone(),
+            enemies: entities.enemies.clone(),
+            bombs: entities.bombs.clone(),
+        }
+    }
+
+    /// Put the entities back as they were. The arenas are copied whole, so ids handed out
+    /// before the checkpoint point at the same entities again and anything that appeared
+    /// since, like a chasing boulder, is gone.
+    pub fn restore(&self, entities: &mut EntityManager) {
+        entities.platforms = self.platforms.clone();
+        entities.terrain = self.terrain.clone();
+        entities.pads = self.pads.clone();
+        entities.switches = self.switches.clone();
+        entities.doors = self.doors.clone();
+        entities.triggers = self.triggers.clone();
+        entities.zones = self.zones.clone();
+        entities.boulders = self.boulders.clone();
+        entities.bosses = self.bosses.clone();
+        entities.enemies = self.enemies.clone();
+        entities.bombs = self.bombs.clone();
+
+        // Drawn where they are now, not blending in from where they were before the respawn
+        for platform in entities.platforms.values_mut() {
+            platform.body.reset_interpolation();
+        }
+        for boss in entities.bosses.values_mut() {
+            boss.body.reset_interpolation();
+        }
+        for enemy in entities.enemies.values_mut() {
+            enemy.body.reset_interpolation();
+        }
+        for bomb in entities.bombs.values_mut() {
+            bomb.body.reset_interpolation();
+        }
+        for boulder in entities.boulders.values_mut() {
+            boulder.previous_center = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_platforms_broken_after_the_checkpoint_come_back() {
+        let mut entities = EntityManager::new();
+        let before = entities
+            .platforms
+            .insert(Platform::new_breakable(100.0, 400.0, 80.0, 20.0));
+        let after = entities
+            .platforms
+            .insert(Platform::new_breakable(900.0, 400.0, 80.0, 20.0));
+
+        entities.platforms.remove(before);
+        let checkpoint = Snapshot::take(&entities, Vec2::new(500.0, 300.0));
+        entities.platforms.remove(after);
+
+        checkpoint.restore(&mut entities);
+        assert!(entities.platforms.get(before).is_none());
+        let restored = entities
+            .platforms
+            .get(after)
+            .expect("broken after the checkpoint");
+        assert_eq!(restored.body.position, Vec2::new(900.0, 400.0));
+    }
+
+    #[test]
+    fn enemies_past_the_checkpoint_are_put_back_where_they_were() {
+        let mut entities = EntityManager::new();
+        let defeated = entities.enemies.insert(Enemy::new(100.0, 400.0, 60.0));
+        let ahead = entities.enemies.insert(Enemy::new(900.0, 400.0, 60.0));
+
+        entities.enemies.remove(defeated);
+        let checkpoint = Snapshot::take(&entities, Vec2::new(500.0, 300.0));
+        let start = entities.enemies.get(ahead).unwrap().body.position;
+        entities.enemies.get_mut(ahead).unwrap().body.position.x -= 200.0;
+        // Something spawned since, like a chasing boulder, is cleared away
+        entities.enemies.insert(Enemy::new(600.0, 400.0, 60.0));
+
+        checkpoint.restore(&mut entities);
+        assert!(entities.enemies.get(defeated).is_none());
+        assert_eq!(entities.enemies.len(), 1);
+        assert_eq!(entities.enemies.get(ahead).unwrap().body.position, start);
+    }
+}
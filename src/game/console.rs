@@ -0,0 +1,425 @@
+use macroquad::prelude::*;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use super::timing::FixedTimestep;
+use super::Game;
+use crate::config::GameConfig;
+use crate::entities::collectible::CollectibleType;
+use crate::entities::manager::Spawn;
+use crate::entities::Collectible;
+use crate::graphics::{GraphicsUtils, TextStyle};
+use crate::input::InputHandler;
+use crate::level::{Level, SCHEMA_LEVEL_PATH};
+use crate::locale::{self, Locale};
+use crate::stats::DeathCause;
+
+/// Runs a console command against the game, returning a line for the log or an error
+pub type CommandHandler = Rc<dyn Fn(&mut Game, &[&str]) -> Result<String, String>>;
+
+#[derive(Clone)]
+pub struct Command {
+    /// Shown by `help`, e.g. `tp <x> <y>`
+    pub usage: &'static str,
+    pub handler: CommandHandler,
+}
+
+/// Drop-down developer console: typed commands, a scrollable log and a command registry
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    log: VecDeque<String>,
+    /// Lines scrolled up from the newest entry
+    scroll: usize,
+    commands: BTreeMap<String, Command>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let mut console = Self {
+            open: false,
+            input: String::new(),
+            log: VecDeque::new(),
+            scroll: 0,
+            commands: BTreeMap::new(),
+        };
+        register_default_commands(&mut console);
+        console
+    }
+
+    /// Add a command, replacing any existing one with the same name
+    pub fn register(
+        &mut self,
+        name: &str,
+        usage: &'static str,
+        handler: impl Fn(&mut Game, &[&str]) -> Result<String, String> + 'static,
+    ) {
+        self.commands.insert(
+            name.to_string(),
+            Command {
+                usage,
+                handler: Rc::new(handler),
+            },
+        );
+    }
+
+    pub fn command(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.scroll = 0;
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push_back(line.into());
+        while self.log.len() > GameConfig::CONSOLE_LOG_LINES {
+            self.log.pop_front();
+        }
+        self.scroll = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+        self.scroll = 0;
+    }
+
+    /// Edit the input line; returns a submitted command line when Enter is pressed
+    pub fn handle_input(&mut self, input: &InputHandler) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+        self.input.extend(
+            input
+                .typed()
+                .chars()
+                .filter(|&character| character != '`' && character != '~'),
+        );
+
+        if input.is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if input.is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        }
+
+        let max_scroll = self
+            .log
+            .len()
+            .saturating_sub(GameConfig::CONSOLE_VISIBLE_LINES);
+        let (_, wheel) = mouse_wheel();
+        if input.is_key_pressed(KeyCode::PageUp) || wheel > 0.0 {
+            self.scroll = (self.scroll + 1).min(max_scroll);
+        }
+        if input.is_key_pressed(KeyCode::PageDown) || wheel < 0.0 {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
+
+        if input.is_key_pressed(KeyCode::Enter) {
+            let line = std::mem::take(&mut self.input);
+            if !line.trim().is_empty() {
+                self.print(format!("> {}", line));
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    pub fn render(&self) {
+        if !self.open {
+            return;
+        }
+
+        let line_height = GameConfig::UI_LINE_HEIGHT * 0.8;
+        let height = (GameConfig::CONSOLE_VISIBLE_LINES + 1) as f32 * line_height + 16.0;
+        draw_rectangle(
+            0.0,
+            0.0,
+            GameConfig::VIRTUAL_WIDTH,
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        draw_line(0.0, height, GameConfig::VIRTUAL_WIDTH, height, 2.0, GRAY);
+
+        // Newest lines sit just above the input line
+        let input_y = height - 10.0;
+        let visible = self
+            .log
+            .iter()
+            .rev()
+            .skip(self.scroll)
+            .take(GameConfig::CONSOLE_VISIBLE_LINES);
+        for (index, line) in visible.enumerate() {
+            let color = if line.starts_with("> ") {
+                LIGHTGRAY
+            } else if line.starts_with("error") {
+                RED
+            } else {
+                WHITE
+            };
+            let y = input_y - (index + 1) as f32 * line_height;
+            GraphicsUtils::text(line, 10.0, y, TextStyle::HudSmall.size(), color);
+        }
+
+        GraphicsUtils::text(
+            &format!("> {}_", self.input),
+            10.0,
+            input_y,
+            TextStyle::HudSmall.size(),
+            YELLOW,
+        );
+        if self.scroll > 0 {
+            let label = format!("scrolled up {} lines", self.scroll);
+            let width = GraphicsUtils::measure_text(&label, TextStyle::HudSmall.size()).width;
+            GraphicsUtils::text(
+                &label,
+                GameConfig::VIRTUAL_WIDTH - width - 10.0,
+                input_y,
+                TextStyle::HudSmall.size(),
+                GRAY,
+            );
+        }
+    }
+}
+
+/// Check the argument count, e.g. `expect_args(args, 2, "tp <x> <y>")`
+fn expect_args(args: &[&str], count: usize, usage: &str) -> Result<(), String> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        Err(format!("usage: {}", usage))
+    }
+}
+
+/// Parse one argument, naming it in the error
+fn parse_arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let text = args.get(index).ok_or_else(|| format!("missing {}", name))?;
+    text.parse()
+        .map_err(|_| format!("invalid {} '{}'", name, text))
+}
+
+fn collectible_type(name: &str) -> Result<CollectibleType, String> {
+    CollectibleType::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown item '{}' (try coin, gem, powerup or lantern)",
+            name
+        )
+    })
+}
+
+fn register_default_commands(console: &mut Console) {
+    console.register("help", "help", |game, _| {
+        let lines: Vec<String> = game
+            .console
+            .commands
+            .values()
+            .map(|command| format!("  {}", command.usage))
+            .collect();
+        game.console.print("Commands:");
+        for line in lines {
+            game.console.print(line);
+        }
+        Ok(String::new())
+    });
+
+    console.register("clear", "clear", |game, _| {
+        game.console.clear();
+        Ok(String::new())
+    });
+
+    console.register(
+        "give",
+        "give <coin|gem|powerup|lantern> [count]",
+        |game, args| {
+            if args.is_empty() || args.len() > 2 {
+                return Err("usage: give <coin|gem|powerup|lantern> [count]".to_string());
+            }
+            let kind = collectible_type(args[0])?;
+            let count: u32 = if args.len() == 2 {
+                parse_arg(args, 1, "count")?
+            } else {
+                1
+            };
+            let value = Collectible::new(0.0, 0.0, kind).value;
+            for _ in 0..count {
+                game.apply_pickup(kind, value);
+            }
+            Ok(format!("Gave {} {}", count, kind.name()))
+        },
+    );
+
+    console.register("tp", "tp <x> <y>", |game, args| {
+        expect_args(args, 2, "tp <x> <y>")?;
+        let position = Vec2::new(parse_arg(args, 0, "x")?, parse_arg(args, 1, "y")?);
+        game.player.let_go();
+        game.player.teleport(position);
+        game.player.become_airborne();
+        game.player.set_velocity(Vec2::ZERO);
+        game.player.fall_peak_y = position.y;
+        Ok(format!("Teleported to ({}, {})", position.x, position.y))
+    });
+
+    console.register(
+        "set",
+        "set <gravity|terminal_velocity|apex_gravity|fall_gravity|wind> <value>",
+        |game, args| {
+            expect_args(
+                args,
+                2,
+                "set <gravity|terminal_velocity|apex_gravity|fall_gravity|wind> <value>",
+            )?;
+            let value: f32 = parse_arg(args, 1, "value")?;
+            match args[0] {
+                "gravity" => game.physics.gravity = value,
+                "terminal_velocity" => game.physics.terminal_velocity = value,
+                "apex_gravity" => game.physics.apex_hang_gravity_scale = value,
+                "fall_gravity" => game.physics.fall_gravity_scale = value,
+                "wind" => game.run_level.properties.wind = value,
+                other => return Err(format!("unknown setting '{}'", other)),
+            }
+            Ok(format!("{} = {}", args[0], value))
+        },
+    );
+
+    console.register(
+        "spawn",
+        "spawn <coin|gem|powerup|lantern> <x> <y>",
+        |game, args| {
+            expect_args(args, 3, "spawn <coin|gem|powerup|lantern> <x> <y>")?;
+            let kind = collectible_type(args[0])?;
+            let x = parse_arg(args, 1, "x")?;
+            let y = parse_arg(args, 2, "y")?;
+            game.entities
+                .queue_spawn(Spawn::Collectible(Collectible::new(x, y, kind).spawned()));
+            Ok(format!("Spawned {} at ({}, {})", kind.name(), x, y))
+        },
+    );
+
+    console.register("kill", "kill [cause]", |game, args| {
+        if args.len() > 1 {
+            return Err("usage: kill [cause]".to_string());
+        }
+        let cause = match args.first() {
+            Some(name) => DeathCause::from_name(name)
+                .ok_or_else(|| format!("unknown death cause '{}'", name))?,
+            None => DeathCause::FellOffWorld,
+        };
+        if game.dying.is_none() {
+            game.dying = Some(GameConfig::DEATH_SLOWMO_DURATION);
+            game.death_cause = Some(cause);
+        }
+        Ok(format!("Player killed ({})", cause.name()))
+    });
+
+    console.register("respawn", "respawn", |game, args| {
+        expect_args(args, 0, "respawn")?;
+        game.begin_respawn(game.run_level.spawn);
+        Ok("Respawning at the level start".to_string())
+    });
+
+    console.register("reload_level", "reload_level", |game, args| {
+        expect_args(args, 0, "reload_level")?;
+        game.reload_level();
+        match &game.reload_banner {
+            Some(banner) if banner.is_error => Err(banner.message.clone()),
+            _ => Ok(format!("Reloaded {}", game.level.name)),
+        }
+    });
+
+    console.register("roundtrip", "roundtrip [path]", |_, args| {
+        if args.len() > 1 {
+            return Err("usage: roundtrip [path]".to_string());
+        }
+        let path = args.first().copied().unwrap_or(SCHEMA_LEVEL_PATH);
+        let level = Level::load(path).map_err(|err| format!("{}: {}", path, err))?;
+        let lines = level
+            .check_round_trip()
+            .map_err(|err| format!("{}: {}", path, err))?;
+        Ok(format!(
+            "{}: all {} lines written back unchanged",
+            path, lines
+        ))
+    });
+    console.register("reload_assets", "reload_assets", |game, args| {
+        expect_args(args, 0, "reload_assets")?;
+        game.assets.request_reload();
+        Ok("Reloading changed asset files".to_string())
+    });
+
+    console.register("lang", "lang [code|keys]", |game, args| {
+        if args.len() > 1 {
+            return Err("usage: lang [code|keys]".to_string());
+        }
+        let Some(&code) = args.first() else {
+            return Ok(format!(
+                "Language {} (available: {})",
+                locale::current_language(),
+                Locale::available().join(", ")
+            ));
+        };
+        if code == "keys" {
+            locale::set_show_keys(!locale::show_keys());
+            return Ok(if locale::show_keys() {
+                "Showing text keys".to_string()
+            } else {
+                "Showing text".to_string()
+            });
+        }
+        if !Locale::available()
+            .iter()
+            .any(|available| available == code)
+        {
+            return Err(format!(
+                "no language '{}' (try {})",
+                code,
+                Locale::available().join(", ")
+            ));
+        }
+        game.settings.language = code.to_string();
+        Locale::load(code).apply();
+        Ok(format!("Language {}", code))
+    });
+
+    console.register("timescale", "timescale <scale>", |game, args| {
+        expect_args(args, 1, "timescale <scale>")?;
+        let scale: f32 = parse_arg(args, 0, "scale")?;
+        if !(scale > 0.0 && scale <= GameConfig::CONSOLE_MAX_TIME_SCALE) {
+            return Err(format!(
+                "scale must be above 0 and at most {}",
+                GameConfig::CONSOLE_MAX_TIME_SCALE
+            ));
+        }
+        game.time_scale = scale;
+        Ok(format!("World speed {}x", scale))
+    });
+
+    // A slow tick makes gaps in render interpolation easy to see
+    console.register("tickrate", "tickrate <hz>", |game, args| {
+        expect_args(args, 1, "tickrate <hz>")?;
+        let rate: f32 = parse_arg(args, 0, "hz")?;
+        if !(GameConfig::CONSOLE_MIN_TICK_RATE..=GameConfig::CONSOLE_MAX_TICK_RATE).contains(&rate)
+        {
+            return Err(format!(
+                "rate must be between {} and {}",
+                GameConfig::CONSOLE_MIN_TICK_RATE,
+                GameConfig::CONSOLE_MAX_TICK_RATE
+            ));
+        }
+        game.timestep = FixedTimestep::new(1.0 / rate);
+        Ok(format!("Simulating at {} Hz", rate))
+    });
+
+    // Replays a run exactly: same seed, same inputs, same outcome
+    console.register("seed", "seed [value]", |game, args| {
+        if args.is_empty() {
+            return Ok(format!("Run seed {}", game.run_seed()));
+        }
+        expect_args(args, 1, "seed [value]")?;
+        game.seed = parse_arg(args, 0, "value")?;
+        game.reset_game();
+        Ok(format!("Restarted with seed {}", game.seed))
+    });
+}
@@ -0,0 +1,137 @@
+use macroquad::prelude::*;
+
+use super::menu::Menu;
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, TextStyle};
+use crate::input::bindings::BINDABLE_KEYS;
+use crate::input::{Action, InputHandler, KeyBindings};
+use crate::locale::{tr, tr_with};
+
+/// What the player did on the controls screen this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlsEvent {
+    /// A key was bound; the screen's keys become the custom preset and play the game
+    Changed,
+    Back,
+}
+
+/// Every action with the keys bound to it, where any of them can be given a new key
+#[derive(Debug, Clone)]
+pub struct ControlsScreen {
+    pub bindings: KeyBindings,
+    pub cursor: usize,
+    /// Waiting for the next key press to bind to the action under the cursor
+    pub listening: bool,
+}
+
+impl ControlsScreen {
+    /// Start from the keys currently in use, so changing one key keeps the rest of a preset
+    pub fn new(bindings: KeyBindings) -> Self {
+        Self {
+            bindings,
+            cursor: 0,
+            listening: false,
+        }
+    }
+
+    fn row_rect(index: usize) -> (f32, f32, f32, f32) {
+        let width = 560.0;
+        let height = 40.0;
+        let x = (GameConfig::VIRTUAL_WIDTH - width) / 2.0;
+        let y = 150.0 + index as f32 * (height + 6.0);
+        (x, y, width, height)
+    }
+
+    pub fn handle_input(&mut self, input: &InputHandler) -> Option<ControlsEvent> {
+        if self.listening {
+            if input.is_key_pressed(KeyCode::Escape) {
+                self.listening = false;
+            } else if let Some(key) = BINDABLE_KEYS.into_iter().find(|k| input.is_key_pressed(*k)) {
+                self.listening = false;
+                self.bindings.bind(Action::ALL[self.cursor], key);
+                return Some(ControlsEvent::Changed);
+            }
+            return None;
+        }
+
+        let count = Action::ALL.len();
+        self.cursor = Menu::navigate(self.cursor, count, input);
+        let mut chosen = input.is_action_pressed();
+        for index in 0..count {
+            let (x, y, w, h) = Self::row_rect(index);
+            if GraphicsUtils::is_mouse_over_rect(x, y, w, h) {
+                self.cursor = index;
+                chosen |= is_mouse_button_pressed(MouseButton::Left);
+            }
+        }
+
+        if chosen {
+            self.listening = true;
+        } else if input.is_key_pressed(KeyCode::R) {
+            self.bindings = KeyBindings::default();
+            return Some(ControlsEvent::Changed);
+        } else if input.is_key_pressed(KeyCode::Escape) {
+            return Some(ControlsEvent::Back);
+        }
+        None
+    }
+
+    pub fn render(&self) {
+        let center_x = GameConfig::VIRTUAL_WIDTH / 2.0;
+        GraphicsUtils::draw_text_centered(
+            &tr("controls.title"),
+            center_x,
+            100.0,
+            TextStyle::Title.size(),
+            WHITE,
+        );
+
+        for (index, action) in Action::ALL.iter().enumerate() {
+            let (x, y, w, h) = Self::row_rect(index);
+            GraphicsUtils::draw_panel(x, y, w, h);
+            let selected = index == self.cursor;
+            if selected {
+                draw_rectangle_lines(x, y, w, h, 3.0, YELLOW);
+            }
+            GraphicsUtils::text(
+                &action.label(),
+                x + 16.0,
+                y + 27.0,
+                TextStyle::Body.size(),
+                WHITE,
+            );
+
+            let (keys, color) = if selected && self.listening {
+                (tr("controls.press_key"), YELLOW)
+            } else if self.bindings.keys(*action).is_empty() {
+                (tr("controls.unbound"), GRAY)
+            } else {
+                (self.bindings.describe(*action), LIGHTGRAY)
+            };
+            let width = GraphicsUtils::measure_text(&keys, TextStyle::Body.size()).width;
+            GraphicsUtils::text(
+                &keys,
+                x + w - 16.0 - width,
+                y + 27.0,
+                TextStyle::Body.size(),
+                color,
+            );
+        }
+
+        let footer = if self.listening {
+            tr_with(
+                "controls.listening",
+                &[("action", &Action::ALL[self.cursor].label())],
+            )
+        } else {
+            tr("controls.prompt")
+        };
+        GraphicsUtils::draw_text_centered(
+            &footer,
+            center_x,
+            GameConfig::VIRTUAL_HEIGHT - 70.0,
+            TextStyle::Hud.size(),
+            LIGHTGRAY,
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use macroquad::prelude::*;
+
+use super::dialogue::Dialogue;
+use crate::config::GameConfig;
+use crate::entities::manager::Arena;
+use crate::entities::Npc;
+use crate::graphics::{GraphicsUtils, TextStyle};
+use crate::level::{Cutscene, CutsceneKind, CutsceneStep};
+use crate::locale::{tr, tr_content};
+
+/// Plays a level's cutscene script a step at a time. The run is on hold and the player
+/// has no control; the camera, the fade and any NPCs the script moves are driven from here.
+#[derive(Debug, Clone)]
+pub struct CutscenePlayer {
+    pub kind: CutsceneKind,
+    steps: Vec<CutsceneStep>,
+    /// Step being played; past the end once it's over
+    index: usize,
+    /// Seconds into the current step
+    elapsed: f32,
+    /// Camera, fade or actor feet when the current step started, to ease from
+    from: Option<Vec2>,
+    /// The line the current step is showing
+    pub dialogue: Option<Dialogue>,
+    /// Opacity of the black drawn over the view
+    pub fade: f32,
+    /// Seconds since it started, for sliding the bars in
+    age: f32,
+}
+
+impl CutscenePlayer {
+    pub fn new(cutscene: &Cutscene) -> Self {
+        Self {
+            kind: cutscene.kind,
+            steps: cutscene.steps.clone(),
+            index: 0,
+            elapsed: 0.0,
+            from: None,
+            dialogue: None,
+            fade: 0.0,
+            age: 0.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.steps.len()
+    }
+
+    /// Advance in real seconds. `camera` is the camera position to drive, and `frame`
+    /// gives the camera position that centers a point, or follows the player for `None`.
+    pub fn update(
+        &mut self,
+        real_dt: f32,
+        camera: &mut Vec2,
+        frame: impl Fn(Option<Vec2>) -> Vec2,
+        npcs: &mut Arena<Npc>,
+    ) {
+        self.age += real_dt;
+        // Steps that take no time all happen this frame
+        while let Some(step) = self.steps.get(self.index) {
+            let starting = self.from.is_none() && self.dialogue.is_none();
+            if starting {
+                self.elapsed = 0.0;
+                self.from = Some(match step {
+                    CutsceneStep::Pan { .. } => *camera,
+                    CutsceneStep::Move { actor, .. } => actor_feet(npcs, actor).unwrap_or_default(),
+                    CutsceneStep::Fade { .. } => Vec2::new(self.fade, 0.0),
+                    CutsceneStep::Say(_) | CutsceneStep::Wait(_) => Vec2::ZERO,
+                });
+                if let CutsceneStep::Say(text) = step {
+                    self.dialogue = Some(Dialogue::new(vec![tr_content(text)]));
+                }
+            } else {
+                self.elapsed += real_dt;
+            }
+
+            let Some(duration) = step.duration() else {
+                // A line stays up until `advance` takes it down
+                if let Some(dialogue) = &mut self.dialogue {
+                    dialogue.update(real_dt);
+                }
+                return;
+            };
+            let t = if duration > 0.0 {
+                (self.elapsed / duration).min(1.0)
+            } else {
+                1.0
+            };
+            let from = self.from.unwrap_or_default();
+            match step {
+                CutsceneStep::Pan { target, .. } => {
+                    let eased = t * t * (3.0 - 2.0 * t);
+                    *camera = from.lerp(frame(*target), eased);
+                }
+                CutsceneStep::Move { actor, path, .. } => {
+                    place_actor(npcs, actor, point_along(from, path, t));
+                }
+                CutsceneStep::Fade { out, .. } => {
+                    let to = if *out { 1.0 } else { 0.0 };
+                    self.fade = from.x + (to - from.x) * t;
+                }
+                CutsceneStep::Say(_) | CutsceneStep::Wait(_) => {}
+            }
+            if t < 1.0 {
+                return;
+            }
+            self.next_step();
+        }
+    }
+
+    fn next_step(&mut self) {
+        self.index += 1;
+        self.from = None;
+        self.dialogue = None;
+    }
+
+    /// The player pressed on: finish typing the line, or take it down and move on
+    pub fn advance(&mut self) {
+        let more = self
+            .dialogue
+            .as_mut()
+            .is_some_and(|dialogue| dialogue.advance());
+        if self.dialogue.is_some() && !more {
+            self.next_step();
+        }
+    }
+
+    /// Jump to the end: every NPC the rest of the script moves goes straight to where it
+    /// would have ended up, and the view clears
+    pub fn skip(&mut self, npcs: &mut Arena<Npc>) {
+        for step in &self.steps[self.index.min(self.steps.len())..] {
+            if let CutsceneStep::Move { actor, path, .. } = step {
+                if let Some(end) = path.last() {
+                    place_actor(npcs, actor, *end);
+                }
+            }
+        }
+        self.index = self.steps.len();
+        self.from = None;
+        self.dialogue = None;
+        self.fade = 0.0;
+    }
+
+    /// Bars across the top and bottom, the fade, the line being said and how to skip
+    pub fn render(&self) {
+        let height = GameConfig::CUTSCENE_BAR_HEIGHT
+            * (self.age / GameConfig::CUTSCENE_BAR_TIME).clamp(0.0, 1.0);
+        let (width, screen_height) = (GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT);
+        draw_rectangle(
+            0.0,
+            0.0,
+            width,
+            screen_height,
+            Color::new(0.0, 0.0, 0.0, self.fade),
+        );
+        draw_rectangle(0.0, 0.0, width, height, BLACK);
+        draw_rectangle(0.0, screen_height - height, width, height, BLACK);
+
+        if let Some(dialogue) = &self.dialogue {
+            dialogue.render();
+        }
+        let hint = tr("cutscene.skip");
+        let hint_width = GraphicsUtils::measure_text(&hint, TextStyle::Caption.size()).width;
+        GraphicsUtils::text(
+            &hint,
+            width - hint_width - 16.0,
+            height.max(GameConfig::CUTSCENE_BAR_HEIGHT) - 12.0,
+            TextStyle::Caption.size(),
+            GRAY,
+        );
+    }
+}
+
+/// Where an NPC's feet are
+fn actor_feet(npcs: &Arena<Npc>, actor: &str) -> Option<Vec2> {
+    npcs.values()
+        .find(|npc| npc.id == actor)
+        .map(|npc| npc.body.position + Vec2::new(0.0, npc.body.size.y))
+}
+
+/// Stand an NPC with its feet at `feet`; scripts naming NPCs the level no longer has do
+/// nothing
+fn place_actor(npcs: &mut Arena<Npc>, actor: &str, feet: Vec2) {
+    if let Some(npc) = npcs.values_mut().find(|npc| npc.id == actor) {
+        npc.body.position = feet - Vec2::new(0.0, npc.body.size.y);
+    }
+}
+
+/// The point `t` of the way along the line from `start` through `path`, by distance
+fn point_along(start: Vec2, path: &[Vec2], t: f32) -> Vec2 {
+    let points: Vec<Vec2> = std::iter::once(start).chain(path.iter().copied()).collect();
+    let total: f32 = points
+        .windows(2)
+        .map(|pair| pair[0].distance(pair[1]))
+        .sum();
+    let mut remaining = total * t;
+    for pair in points.windows(2) {
+        let length = pair[0].distance(pair[1]);
+        if remaining <= length && length > 0.0 {
+            return pair[0].lerp(pair[1], remaining / length);
+        }
+        remaining -= length;
+    }
+    points.last().copied().unwrap_or(start)
+}
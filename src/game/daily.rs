@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::GameConfig;
+use crate::date::Date;
+use crate::entities::platform::PlatformType;
+use crate::level::Level;
+use crate::locale::{tr, tr_with};
+use crate::profile::Profile;
+use crate::rng::SeededRng;
+
+/// Where daily challenge attempts are persisted, inside each profile's folder
+pub const DAILY_RECORDS_PATH: &str = "daily.txt";
+
+/// Today's twist on the regular level, identical for everyone playing on the same date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyChallenge {
+    pub date: Date,
+    pub seed: u64,
+    pub dark: bool,
+    pub rising_lava: bool,
+    /// Sideways push in pixels per second; positive blows right
+    pub wind: f32,
+    /// Width multiplier for floating platforms, below 1.0 for narrower footing
+    pub platform_scale: f32,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_date(Date::today())
+    }
+
+    pub fn for_date(date: Date) -> Self {
+        let seed = Self::seed_for(date);
+        let mut rng = SeededRng::new(seed);
+
+        let dark = rng.chance(GameConfig::DAILY_DARK_CHANCE);
+        let rising_lava = rng.chance(GameConfig::DAILY_LAVA_CHANCE);
+        let wind = if rng.chance(GameConfig::DAILY_WIND_CHANCE) {
+            let direction = if rng.chance(0.5) { 1.0 } else { -1.0 };
+            direction * rng.range(GameConfig::DAILY_WIND_MIN, GameConfig::DAILY_WIND_MAX)
+        } else {
+            0.0
+        };
+        let platform_scale = rng.range(GameConfig::DAILY_MIN_PLATFORM_SCALE, 1.0);
+
+        Self {
+            date,
+            seed,
+            dark,
+            rising_lava,
+            wind,
+            platform_scale,
+        }
+    }
+
+    /// Stable seed derived from the calendar date alone
+    pub fn seed_for(date: Date) -> u64 {
+        let stamp = date.year as u64 * 10_000 + date.month as u64 * 100 + date.day as u64;
+        SeededRng::new(stamp).next_u64()
+    }
+
+    /// The regular level with today's modifiers layered on top
+    pub fn apply(&self, level: &Level) -> Level {
+        let mut level = level.clone();
+        level.name = format!("{} (Daily {})", level.name, self.date);
+        level.properties.dark |= self.dark;
+        level.properties.rising_lava |= self.rising_lava;
+        level.properties.wind += self.wind;
+
+        // Shrink floating platforms around their centers so the layout stays recognizable
+        for def in &mut level.platforms {
+            if def.platform_type != PlatformType::Ground {
+                let new_width = def.size.x * self.platform_scale;
+                def.position.x += (def.size.x - new_width) / 2.0;
+                def.size.x = new_width;
+            }
+        }
+
+        level
+    }
+
+    /// Short list of today's modifiers for menus and the HUD
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.dark {
+            parts.push(tr("daily.modifier.dark"));
+        }
+        if self.rising_lava {
+            parts.push(tr("daily.modifier.lava"));
+        }
+        if self.wind != 0.0 {
+            let arrow = if self.wind > 0.0 { "->" } else { "<-" };
+            parts.push(tr_with(
+                "hud.wind",
+                &[
+                    ("arrow", &arrow),
+                    ("strength", &format!("{:.0}", self.wind.abs())),
+                ],
+            ));
+        }
+        parts.push(tr_with(
+            "daily.modifier.ledges",
+            &[(
+                "percent",
+                &format!("{:.0}", (self.platform_scale * 100.0).round()),
+            )],
+        ));
+        parts.join(", ")
+    }
+}
+
+/// Whether a finished daily run was written to the records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DailyOutcome {
+    Recorded,
+    AlreadyPlayed,
+    Practice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyResult {
+    pub score: i32,
+    pub outcome: DailyOutcome,
+}
+
+/// The one scored attempt allowed per day, persisted as `DATE SCORE` lines
+#[derive(Debug, Clone, Default)]
+pub struct DailyRecords {
+    pub scores: BTreeMap<Date, i32>,
+}
+
+impl DailyRecords {
+    pub fn load(profile: &Profile) -> Self {
+        Self::load_from(profile.path(DAILY_RECORDS_PATH))
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse the records, skipping malformed lines; the first entry for a date wins
+    pub fn parse(source: &str) -> Self {
+        let mut records = Self::default();
+        for line in source.lines() {
+            let mut words = line.split_whitespace();
+            let (Some(date), Some(score)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if let (Ok(date), Ok(score)) = (date.parse(), score.parse()) {
+                records.record(date, score);
+            }
+        }
+        records
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(DAILY_RECORDS_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn score_on(&self, date: Date) -> Option<i32> {
+        self.scores.get(&date).copied()
+    }
+
+    pub fn has_played(&self, date: Date) -> bool {
+        self.scores.contains_key(&date)
+    }
+
+    /// Store the day's attempt; returns false if that day already has one
+    pub fn record(&mut self, date: Date, score: i32) -> bool {
+        if self.has_played(date) {
+            return false;
+        }
+        self.scores.insert(date, score);
+        true
+    }
+}
+
+impl fmt::Display for DailyRecords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (date, score) in &self.scores {
+            writeln!(f, "{} {}", date, score)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use macroquad::prelude::*;
+
+use crate::entities::enemy::EnemyKind;
+use crate::graphics::{GraphicsUtils, RenderStyle};
+use crate::locale::tr;
+use crate::stats::DeathCause;
+
+/// The game over screen's line about what ended the run
+pub fn message(cause: DeathCause) -> String {
+    match cause {
+        DeathCause::Enemy { kind } => tr(&format!("death.enemy.{}", kind.name())),
+        cause => tr(&format!("death.{}", cause.name())),
+    }
+}
+
+/// Short name for a cause counted in the statistics, by its `DeathCause::name`
+pub fn label(name: &str) -> String {
+    tr(&format!("death.name.{}", name))
+}
+
+/// A small picture of the cause, about `size` across and centered on `center`
+pub fn render_icon(cause: DeathCause, center: Vec2, size: f32) {
+    let palette = RenderStyle::current().palette();
+    let half = size / 2.0;
+    let (left, top) = (center.x - half, center.y - half);
+    match cause {
+        DeathCause::FellOffWorld | DeathCause::HardLanding => {
+            // An arrow pointing down, onto a floor for a hard landing
+            let shaft = size * 0.2;
+            draw_rectangle(center.x - shaft / 2.0, top, shaft, size * 0.5, LIGHTGRAY);
+            draw_triangle(
+                Vec2::new(left + size * 0.15, center.y),
+                Vec2::new(left + size * 0.85, center.y),
+                Vec2::new(center.x, top + size * 0.85),
+                LIGHTGRAY,
+            );
+            if cause == DeathCause::HardLanding {
+                draw_rectangle(left, top + size * 0.9, size, size * 0.1, palette.ground);
+            }
+        }
+        DeathCause::Spikes => {
+            let width = size / 3.0;
+            for index in 0..3 {
+                let x = left + index as f32 * width;
+                draw_triangle(
+                    Vec2::new(x, top + size),
+                    Vec2::new(x + width, top + size),
+                    Vec2::new(x + width / 2.0, top + size * 0.2),
+                    palette.hazard_top,
+                );
+            }
+        }
+        DeathCause::Enemy { kind } => {
+            let height = match kind {
+                EnemyKind::Walker => size * 0.8,
+                EnemyKind::Shelled => size * 0.55,
+            };
+            let y = top + size - height;
+            draw_rectangle(left + size * 0.1, y, size * 0.8, height, palette.danger);
+            let eye = size * 0.12;
+            draw_rectangle(left + size * 0.25, y + height * 0.25, eye, eye, WHITE);
+            draw_rectangle(left + size * 0.6, y + height * 0.25, eye, eye, WHITE);
+        }
+        DeathCause::Boss => {
+            draw_rectangle(left, top + size * 0.15, size, size * 0.85, palette.danger);
+            // A crown of three points
+            for index in 0..3 {
+                let x = left + index as f32 * size / 3.0;
+                draw_triangle(
+                    Vec2::new(x, top + size * 0.15),
+                    Vec2::new(x + size / 3.0, top + size * 0.15),
+                    Vec2::new(x + size / 6.0, top),
+                    palette.coin,
+                );
+            }
+        }
+        DeathCause::Crushed => {
+            // Two walls closing in
+            let wall = size * 0.3;
+            draw_rectangle(left, top, wall, size, GRAY);
+            draw_rectangle(left + size - wall, top, wall, size, GRAY);
+            draw_rectangle(
+                left + wall,
+                center.y - size * 0.1,
+                size - wall * 2.0,
+                size * 0.2,
+                palette.player,
+            );
+        }
+        DeathCause::Lava => {
+            draw_rectangle(left, center.y, size, half, palette.hazard_bottom);
+            for index in 0..3 {
+                let x = left + (index as f32 + 0.5) * size / 3.0;
+                draw_circle(x, center.y, size / 6.0, palette.hazard_top);
+            }
+        }
+        DeathCause::Drowned => {
+            // A falling drop
+            draw_circle(center.x, center.y + size * 0.15, size * 0.35, SKYBLUE);
+            draw_triangle(
+                Vec2::new(center.x - size * 0.3, center.y),
+                Vec2::new(center.x + size * 0.3, center.y),
+                Vec2::new(center.x, top),
+                SKYBLUE,
+            );
+        }
+        DeathCause::Boulder => {
+            draw_circle(center.x, center.y, half, GRAY);
+            draw_circle(
+                center.x - size * 0.15,
+                center.y - size * 0.1,
+                size * 0.1,
+                DARKGRAY,
+            );
+        }
+        DeathCause::Explosion => {
+            GraphicsUtils::draw_star(center, half, ORANGE);
+            GraphicsUtils::draw_star(center, half * 0.5, YELLOW);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::{cycle, BoxKind, PhysicsBody, Player};
+use crate::graphics::{GraphicsUtils, TextStyle, Viewport};
+use crate::input::{Action, InputHandler, Rumble};
+use crate::physics::collision::{CollisionInfo, CollisionSide};
+use crate::physics::contact_log;
+
+/// Whether the camera follows the player or is being flown around by hand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FreeCamera {
+    Off,
+    /// Detached, with the world paused
+    Frozen,
+    /// Detached while the game keeps running
+    Live,
+}
+
+impl FreeCamera {
+    /// F8 steps through frozen, live, then back to following the player
+    pub fn next(&self) -> Self {
+        match self {
+            FreeCamera::Off => FreeCamera::Frozen,
+            FreeCamera::Frozen => FreeCamera::Live,
+            FreeCamera::Live => FreeCamera::Off,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != FreeCamera::Off
+    }
+}
+
+/// Something the player was pushed out of during a simulation step
+#[derive(Debug, Clone)]
+pub struct StepContact {
+    /// What was touched, such as "platform (Normal)" or "door d1"
+    pub what: String,
+    pub info: CollisionInfo,
+}
+
+/// Level inspection helpers: a detachable zoomable camera, a noclip player and stepping
+/// through the simulation one fixed step at a time
+#[derive(Debug, Clone)]
+pub struct DebugTools {
+    pub free_camera: FreeCamera,
+    /// Top-left of the view while the camera is detached, like `Game::camera_offset`
+    pub camera: Vec2,
+    pub zoom: f32,
+    /// Virtual mouse position at the last middle-drag update
+    drag_from: Option<Vec2>,
+    pub noclip: bool,
+    /// Easing the camera back onto the player after the free camera is switched off
+    pub reattaching: bool,
+    /// Outline every entity's solid, hurt and damage boxes
+    pub show_hitboxes: bool,
+    /// Dotted curves of how far the player can jump from where they stand
+    pub show_jump_arcs: bool,
+    /// A crowd of harmless enemies and a cloud of dust around the player, for watching
+    /// the frame rate under load
+    pub stress: bool,
+    /// The simulation only moves when '.' asks for another step
+    pub stepping: bool,
+    /// Real seconds until holding '.' takes the next step
+    step_repeat: f32,
+    /// Fixed steps simulated since the run started
+    pub step_count: u64,
+    /// What the player touched in the last step, recorded while stepping
+    pub contacts: Vec<StepContact>,
+}
+
+impl DebugTools {
+    pub fn new() -> Self {
+        Self {
+            free_camera: FreeCamera::Off,
+            camera: Vec2::ZERO,
+            zoom: 1.0,
+            drag_from: None,
+            noclip: false,
+            reattaching: false,
+            show_hitboxes: GameConfig::SHOW_COLLISION_BOXES,
+            show_jump_arcs: false,
+            stress: false,
+            stepping: false,
+            step_repeat: 0.0,
+            step_count: 0,
+            contacts: Vec::new(),
+        }
+    }
+
+    /// Debug builds always have the tools; release builds only when the config allows it
+    pub fn available() -> bool {
+        cfg!(debug_assertions) || GameConfig::DEBUG_TOOLS
+    }
+
+    /// Step the free camera mode, starting from or handing back to the follow camera
+    pub fn cycle_free_camera(&mut self, camera_offset: &mut Vec2) {
+        self.free_camera = self.free_camera.next();
+        match self.free_camera {
+            FreeCamera::Frozen => {
+                self.camera = *camera_offset;
+                self.zoom = 1.0;
+                self.reattaching = false;
+            }
+            FreeCamera::Live => {}
+            FreeCamera::Off => {
+                // Start the follow camera from where we were looking and let it catch up
+                *camera_offset = self.camera;
+                self.drag_from = None;
+                self.reattaching = true;
+            }
+        }
+    }
+
+    /// Whether the simulation should stand still this frame
+    pub fn is_frozen(&self) -> bool {
+        self.free_camera == FreeCamera::Frozen
+    }
+
+    /// F7 holds the simulation still for stepping through, or lets it run again
+    pub fn toggle_stepping(&mut self) {
+        self.stepping = !self.stepping;
+        self.contacts.clear();
+    }
+
+    /// How many fixed steps to take this frame while stepping: one when '.' is pressed,
+    /// then `DEBUG_STEP_REPEAT_RATE` a second for as long as it's held
+    pub fn steps_due(&mut self, input: &InputHandler, real_dt: f32) -> u32 {
+        let interval = 1.0 / GameConfig::DEBUG_STEP_REPEAT_RATE;
+        if input.is_key_pressed(KeyCode::Period) {
+            self.step_repeat = interval;
+            return 1;
+        }
+        if !input.is_key_down(KeyCode::Period) {
+            return 0;
+        }
+        self.step_repeat -= real_dt;
+        let mut steps = 0;
+        while self.step_repeat <= 0.0 {
+            self.step_repeat += interval;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Pan with IJKL or a middle-mouse drag and zoom with +/-, in real time
+    pub fn update_free_camera(&mut self, input: &InputHandler, real_dt: f32) {
+        if !self.free_camera.is_active() {
+            return;
+        }
+
+        let mut direction = Vec2::ZERO;
+        if input.is_key_down(KeyCode::J) {
+            direction.x -= 1.0;
+        }
+        if input.is_key_down(KeyCode::L) {
+            direction.x += 1.0;
+        }
+        if input.is_key_down(KeyCode::I) {
+            direction.y -= 1.0;
+        }
+        if input.is_key_down(KeyCode::K) {
+            direction.y += 1.0;
+        }
+        // Pan at a constant on-screen speed whatever the zoom
+        self.camera += direction * GameConfig::FREE_CAMERA_SPEED * real_dt / self.zoom;
+
+        if is_mouse_button_down(MouseButton::Middle) {
+            let mouse = Viewport::mouse_position();
+            if let Some(from) = self.drag_from {
+                self.camera -= (mouse - from) / self.zoom;
+            }
+            self.drag_from = Some(mouse);
+        } else {
+            self.drag_from = None;
+        }
+
+        let zoom_in = input.is_key_down(KeyCode::Equal) || input.is_key_down(KeyCode::KpAdd);
+        let zoom_out = input.is_key_down(KeyCode::Minus) || input.is_key_down(KeyCode::KpSubtract);
+        let mut zoom_step = 0.0;
+        if zoom_in {
+            zoom_step += 1.0;
+        }
+        if zoom_out {
+            zoom_step -= 1.0;
+        }
+        self.zoom = (self.zoom * (1.0 + zoom_step * GameConfig::FREE_CAMERA_ZOOM_RATE * real_dt))
+            .clamp(
+                GameConfig::FREE_CAMERA_MIN_ZOOM,
+                GameConfig::FREE_CAMERA_MAX_ZOOM,
+            );
+    }
+
+    /// Direction the noclip player is being steered in, from the usual movement keys
+    pub fn noclip_direction(input: &InputHandler) -> Vec2 {
+        let mut direction = Vec2::ZERO;
+        if input.is_key_down(KeyCode::A) || input.is_key_down(KeyCode::Left) {
+            direction.x -= 1.0;
+        }
+        if input.is_key_down(KeyCode::D) || input.is_key_down(KeyCode::Right) {
+            direction.x += 1.0;
+        }
+        if input.is_key_down(KeyCode::W) || input.is_key_down(KeyCode::Up) {
+            direction.y -= 1.0;
+        }
+        if input.is_key_down(KeyCode::S) || input.is_key_down(KeyCode::Down) {
+            direction.y += 1.0;
+        }
+        direction.normalize_or_zero()
+    }
+
+    /// Outline a body's boxes in world space: solid green, hurt yellow, damage red
+    pub fn draw_hitboxes(body: &PhysicsBody, cam_x: f32, cam_y: f32) {
+        for (kind, color, thickness) in [
+            (BoxKind::Solid, GREEN, 3.0),
+            (BoxKind::Hurt, YELLOW, 2.0),
+            (BoxKind::Damage, RED, 1.0),
+        ] {
+            let (x1, y1, x2, y2) = body.box_bounds(kind);
+            draw_rectangle_lines(x1 + cam_x, y1 + cam_y, x2 - x1, y2 - y1, thickness, color);
+        }
+    }
+
+    /// Mark where the last step's contacts were, with a tick on the side of the player
+    /// that touched
+    pub fn draw_contacts(&self, cam_x: f32, cam_y: f32) {
+        for contact in &self.contacts {
+            let point = contact.info.contact_point + Vec2::new(cam_x, cam_y);
+            let normal = match contact.info.side {
+                CollisionSide::Top => Vec2::new(0.0, -1.0),
+                CollisionSide::Bottom => Vec2::new(0.0, 1.0),
+                CollisionSide::Left => Vec2::new(-1.0, 0.0),
+                CollisionSide::Right => Vec2::new(1.0, 0.0),
+            };
+            let tip = point + normal * 10.0;
+            draw_line(point.x, point.y, tip.x, tip.y, 2.0, MAGENTA);
+            draw_circle(point.x, point.y, 3.0, MAGENTA);
+        }
+    }
+
+    /// The inputs the player is holding, by what they do
+    fn held_inputs(input: &InputHandler) -> Vec<&'static str> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| input.is_down(*action))
+            .map(|action| action.name())
+            .collect()
+    }
+
+    /// Exactly where the player is after the last step, what it touched and what's held
+    pub fn render_step_info(&self, player: &Player, input: &InputHandler, rumble: &Rumble) {
+        if !self.stepping {
+            return;
+        }
+        let position = player.position();
+        let velocity = player.velocity();
+        let held = Self::held_inputs(input);
+        let mut lines = vec![
+            format!("STEP {}", self.step_count),
+            format!("pos ({:.3}, {:.3})", position.x, position.y),
+            format!("vel ({:.3}, {:.3})", velocity.x, velocity.y),
+            format!(
+                "on ground: {}",
+                if player.is_on_ground() { "yes" } else { "no" }
+            ),
+            format!("state: {}", player.state().name()),
+            format!(
+                "held: {}",
+                if held.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    held.join(" ")
+                }
+            ),
+            format!("rumble: {:.2}", rumble.strength()),
+            format!("contacts: {}", self.contacts.len()),
+        ];
+        lines.extend(self.contacts.iter().map(|contact| {
+            format!(
+                "  {:?} side on {}, {:.3} deep",
+                contact.info.side, contact.what, contact.info.overlap
+            )
+        }));
+
+        let line_height = 20.0;
+        let x = GameConfig::UI_MARGIN;
+        let y = 150.0;
+        let width = 380.0;
+        let height = lines.len() as f32 * line_height + 12.0;
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+        for (index, line) in lines.iter().enumerate() {
+            GraphicsUtils::text(
+                line,
+                x + 8.0,
+                y + 20.0 + index as f32 * line_height,
+                TextStyle::Caption.size(),
+                ORANGE,
+            );
+        }
+    }
+
+    /// The level clock and which beat it's on, with a dot that flashes on each beat
+    pub fn render_clock(level_clock: f32) {
+        let beat = cycle::beat_at(level_clock);
+        let x = GameConfig::UI_MARGIN;
+        let y = GameConfig::VIRTUAL_HEIGHT - 60.0;
+        let flash = 1.0 - beat.fract();
+        draw_circle(x + 6.0, y - 6.0, 6.0, Color::new(1.0, 0.85, 0.2, flash));
+        GraphicsUtils::text(
+            &format!("BEAT {} ({:.1}s)", beat.floor(), level_clock),
+            x + 18.0,
+            y,
+            TextStyle::Hud.size(),
+            ORANGE,
+        );
+    }
+
+    /// Corner labels so it's obvious the game isn't running normally
+    pub fn render_indicators(&self) {
+        let mut lines = Vec::new();
+        match self.free_camera {
+            FreeCamera::Off => {}
+            FreeCamera::Frozen => lines.push(format!(
+                "FREE CAMERA (frozen) {:.2}x - IJKL/middle drag, +/-, F8",
+                self.zoom
+            )),
+            FreeCamera::Live => lines.push(format!(
+                "FREE CAMERA (live) {:.2}x - IJKL/middle drag, +/-, F8",
+                self.zoom
+            )),
+        }
+        if self.noclip {
+            lines.push("NOCLIP - WASD to fly, F4 to land".to_string());
+        }
+        if contact_log::is_enabled() {
+            let mut count = 0;
+            contact_log::for_each(|_| count += 1);
+            lines.push(format!(
+                "CONTACT LOG {}/{} - F1 to stop, Shift+F1 to dump",
+                count,
+                GameConfig::CONTACT_LOG_CAPACITY
+            ));
+        }
+        if self.stepping {
+            lines.push(format!(
+                "STEPPING - '.' for one step, hold for {}/s, F7 to run",
+                GameConfig::DEBUG_STEP_REPEAT_RATE
+            ));
+        }
+        if lines.is_empty() {
+            return;
+        }
+
+        // A frame around the whole view reads as "debug" even at a glance
+        draw_rectangle_lines(
+            0.0,
+            0.0,
+            GameConfig::VIRTUAL_WIDTH,
+            GameConfig::VIRTUAL_HEIGHT,
+            6.0,
+            ORANGE,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            let width = GraphicsUtils::measure_text(line, TextStyle::Hud.size()).width;
+            GraphicsUtils::text(
+                line,
+                GameConfig::VIRTUAL_WIDTH - width - 16.0,
+                30.0 + index as f32 * 24.0,
+                TextStyle::Hud.size(),
+                ORANGE,
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, TextStyle};
+use crate::locale::{tr, tr_with};
+
+/// A conversation with an NPC shown at the bottom of the screen. The run is on hold
+/// while it's open.
+#[derive(Debug, Clone)]
+pub struct Dialogue {
+    pages: Vec<String>,
+    page: usize,
+    /// Characters of the current page shown so far; grows for the typewriter effect
+    revealed: f32,
+}
+
+impl Dialogue {
+    pub fn new(pages: Vec<String>) -> Self {
+        Self {
+            pages,
+            page: 0,
+            revealed: 0.0,
+        }
+    }
+
+    fn current(&self) -> &str {
+        self.pages.get(self.page).map(String::as_str).unwrap_or("")
+    }
+
+    fn page_length(&self) -> usize {
+        self.current().chars().count()
+    }
+
+    fn is_page_revealed(&self) -> bool {
+        self.revealed as usize >= self.page_length()
+    }
+
+    /// Type out more of the page, in real seconds
+    pub fn update(&mut self, real_dt: f32) {
+        if RenderStyle::current().reduced_motion {
+            self.revealed = self.page_length() as f32;
+        } else {
+            self.revealed += GameConfig::DIALOGUE_CHARS_PER_SECOND * real_dt;
+        }
+    }
+
+    /// Finish typing the page, or move on to the next one. Returns false once the last
+    /// page has been dismissed.
+    pub fn advance(&mut self) -> bool {
+        if !self.is_page_revealed() {
+            self.revealed = self.page_length() as f32;
+            return true;
+        }
+        self.page += 1;
+        self.revealed = 0.0;
+        self.page < self.pages.len()
+    }
+
+    pub fn render(&self) {
+        let margin = 40.0;
+        let height = 140.0;
+        let padding = 20.0;
+        let font_size = TextStyle::Body.size();
+        let x = margin;
+        let y = GameConfig::VIRTUAL_HEIGHT - height - margin;
+        let width = GameConfig::VIRTUAL_WIDTH - margin * 2.0;
+        GraphicsUtils::draw_panel(x, y, width, height);
+
+        // Wrap the whole page up front so words don't jump between lines as they appear
+        let mut remaining = self.revealed as usize;
+        let lines = GraphicsUtils::wrap_text(self.current(), font_size, width - padding * 2.0);
+        for (index, line) in lines.iter().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            let shown: String = line.chars().take(remaining).collect();
+            remaining = remaining.saturating_sub(line.chars().count() + 1);
+            GraphicsUtils::text(
+                &shown,
+                x + padding,
+                y + padding + font_size + index as f32 * (font_size + 4.0),
+                font_size,
+                WHITE,
+            );
+        }
+
+        let hint = if !self.is_page_revealed() {
+            String::new()
+        } else if self.page + 1 < self.pages.len() {
+            tr_with(
+                "dialogue.next",
+                &[("page", &(self.page + 1)), ("pages", &self.pages.len())],
+            )
+        } else {
+            tr("dialogue.close")
+        };
+        let hint_width = GraphicsUtils::measure_text(&hint, TextStyle::Caption.size()).width;
+        GraphicsUtils::text(
+            &hint,
+            x + width - padding - hint_width,
+            y + height - 12.0,
+            TextStyle::Caption.size(),
+            LIGHTGRAY,
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use macroquad::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::config::GameConfig;
+use crate::entities::collectible::CollectibleType;
+use crate::graphics::{Anchor, GraphicsUtils, HudLayout, TextStyle};
+use crate::locale::{tr, tr_with};
+
+/// Something worth a line in the event feed
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// A pickup other than a coin, which has its own counter, and the points it gave
+    Pickup(CollectibleType, i32),
+    /// An enemy stomped, and the points it gave if the mode pays for kills
+    EnemyStomped(Option<i32>),
+    PerfectLanding(i32),
+    CheckpointReached,
+    WaveComplete(u32),
+    /// An enemy knocked out by a kicked shell: how many in a row, and the points it gave
+    ShellChain(u32, i32),
+}
+
+impl GameEvent {
+    /// The line the feed shows for it
+    pub fn describe(&self) -> String {
+        match self {
+            GameEvent::Pickup(kind, points) => tr_with(
+                "feed.pickup",
+                &[("item", &tr(pickup_key(*kind))), ("points", points)],
+            ),
+            GameEvent::EnemyStomped(Some(points)) => {
+                tr_with("feed.stomp_points", &[("points", points)])
+            }
+            GameEvent::EnemyStomped(None) => tr("feed.stomp"),
+            GameEvent::PerfectLanding(points) => tr_with("feed.perfect", &[("points", points)]),
+            GameEvent::CheckpointReached => tr("feed.checkpoint"),
+            GameEvent::WaveComplete(wave) => tr_with("feed.wave", &[("wave", wave)]),
+            GameEvent::ShellChain(chain, points) => {
+                tr_with("feed.shell_chain", &[("chain", chain), ("points", points)])
+            }
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            GameEvent::Pickup(..) | GameEvent::PerfectLanding(_) => GOLD,
+            GameEvent::EnemyStomped(_) | GameEvent::ShellChain(..) => ORANGE,
+            GameEvent::CheckpointReached | GameEvent::WaveComplete(_) => GREEN,
+        }
+    }
+}
+
+fn pickup_key(kind: CollectibleType) -> &'static str {
+    match kind {
+        CollectibleType::Coin => "feed.item.coin",
+        CollectibleType::Gem => "feed.item.gem",
+        CollectibleType::PowerUp => "feed.item.powerup",
+        CollectibleType::Lantern => "feed.item.lantern",
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FeedEntry {
+    text: String,
+    color: Color,
+    /// Seconds since it was added
+    age: f32,
+    /// Row it's drawn on, easing towards its place in the stack
+    row: f32,
+}
+
+/// Recent notable events listed in the top-right corner. New lines slide in at the bottom
+/// and push older ones up; each fades out after a few seconds.
+#[derive(Debug, Clone, Default)]
+pub struct EventFeed {
+    entries: VecDeque<FeedEntry>,
+}
+
+impl EventFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn push(&mut self, event: &GameEvent) {
+        if self.entries.len() == GameConfig::FEED_MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(FeedEntry {
+            text: event.describe(),
+            color: event.color(),
+            age: 0.0,
+            row: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let count = self.entries.len();
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.age += delta_time;
+            // Rows count up from the newest entry at the bottom
+            let target = (count - 1 - index) as f32;
+            let step = GameConfig::FEED_SLIDE_RATE * delta_time;
+            entry.row += (target - entry.row).clamp(-step, step);
+        }
+        self.entries
+            .retain(|entry| entry.age < GameConfig::FEED_ENTRY_TIME);
+    }
+
+    /// Draw the stack with its newest line `bottom_line` rows below the top of the screen
+    pub fn render(&self, hud: &HudLayout, bottom_line: usize) {
+        let size = hud.font_size(TextStyle::Caption.size());
+        for entry in &self.entries {
+            let slide = (1.0 - entry.age / GameConfig::FEED_SLIDE_TIME).max(0.0);
+            let fade_start = GameConfig::FEED_ENTRY_TIME - GameConfig::FEED_FADE_TIME;
+            let alpha =
+                1.0 - ((entry.age - fade_start) / GameConfig::FEED_FADE_TIME).clamp(0.0, 1.0);
+            let width = GraphicsUtils::measure_text(&entry.text, size).width;
+            let row = bottom_line as f32 - entry.row;
+            let top_left = hud.place(
+                Anchor::TopRight,
+                Vec2::new(
+                    -slide * GameConfig::FEED_SLIDE_DISTANCE,
+                    row * GameConfig::UI_LINE_HEIGHT,
+                ),
+                Vec2::new(width, size),
+            );
+            let color = entry.color;
+            GraphicsUtils::draw_text_with_shadow(
+                &entry.text,
+                top_left.x,
+                top_left.y + size * 0.75,
+                size,
+                Color::new(color.r, color.g, color.b, alpha),
+                Color::new(0.0, 0.0, 0.0, alpha * 0.6),
+                Vec2::new(1.0, 1.0) * hud.scale,
+            );
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use macroquad::prelude::*;
+
+use std::path::{Path, PathBuf};
+
+use super::menu::Menu;
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, TextStyle, ThumbnailCache};
+use crate::input::InputHandler;
+use crate::level::catalog::{self, LevelEntry};
+use crate::level::Level;
+use crate::locale::{tr, tr_with};
+use crate::records::{LevelRecords, MAX_STARS};
+
+/// What the player did on the level select screen this frame
+#[derive(Debug, Clone)]
+pub enum LevelSelectEvent {
+    Play(PathBuf, Level),
+    /// Open a custom level in the editor
+    Edit(PathBuf, Level),
+    /// Delete a custom level's file, once confirmed
+    Delete(PathBuf),
+    /// Go to the level code import screen
+    Import,
+    Back,
+}
+
+/// Built-in and custom levels to pick from, each with a thumbnail and its best results
+#[derive(Debug, Clone)]
+pub struct LevelSelect {
+    pub entries: Vec<LevelEntry>,
+    pub cursor: usize,
+    /// First entry shown, once there are more than fit
+    scroll: usize,
+    /// Waiting for Y or N before deleting the level under the cursor
+    pub confirm_delete: bool,
+    /// Why the last thing tried didn't work
+    pub error: Option<String>,
+}
+
+impl LevelSelect {
+    /// Read every level there is to offer, with the cursor on `current` if it's among them
+    pub fn new(current: &Path) -> Self {
+        let entries = catalog::scan();
+        let cursor = entries
+            .iter()
+            .position(|entry| entry.path == current)
+            .unwrap_or(0);
+        let mut screen = Self {
+            entries,
+            cursor,
+            scroll: 0,
+            confirm_delete: false,
+            error: None,
+        };
+        screen.scroll_to_cursor();
+        screen
+    }
+
+    fn row_rect(row: usize) -> (f32, f32, f32, f32) {
+        let (_, thumbnail_height) = GameConfig::THUMBNAIL_SIZE;
+        let width = 720.0;
+        let height = thumbnail_height as f32 + 8.0;
+        let x = (GameConfig::VIRTUAL_WIDTH - width) / 2.0;
+        let y = 150.0 + row as f32 * (height + 12.0);
+        (x, y, width, height)
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let rows = GameConfig::LEVEL_SELECT_VISIBLE_ROWS;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + rows {
+            self.scroll = self.cursor + 1 - rows;
+        }
+    }
+
+    fn selected(&self) -> Option<&LevelEntry> {
+        self.entries.get(self.cursor)
+    }
+
+    /// Levels asking for more stars than `records` hold can still be edited, but not played
+    pub fn handle_input(
+        &mut self,
+        input: &InputHandler,
+        records: &LevelRecords,
+    ) -> Option<LevelSelectEvent> {
+        if self.confirm_delete {
+            if input.is_key_pressed(KeyCode::Y) {
+                self.confirm_delete = false;
+                return self
+                    .selected()
+                    .map(|entry| LevelSelectEvent::Delete(entry.path.clone()));
+            } else if input.is_key_pressed(KeyCode::N) || input.is_key_pressed(KeyCode::Escape) {
+                self.confirm_delete = false;
+            }
+            return None;
+        }
+
+        let count = self.entries.len();
+        self.cursor = Menu::navigate(self.cursor, count, input);
+        let (_, wheel) = mouse_wheel();
+        if wheel > 0.0 {
+            self.cursor = self.cursor.saturating_sub(1);
+        } else if wheel < 0.0 && self.cursor + 1 < count {
+            self.cursor += 1;
+        }
+        self.scroll_to_cursor();
+
+        let mut chosen = input.is_action_pressed();
+        let visible = count.min(self.scroll + GameConfig::LEVEL_SELECT_VISIBLE_ROWS);
+        for index in self.scroll..visible {
+            let (x, y, w, h) = Self::row_rect(index - self.scroll);
+            if GraphicsUtils::is_mouse_over_rect(x, y, w, h) {
+                self.cursor = index;
+                chosen |= is_mouse_button_pressed(MouseButton::Left);
+            }
+        }
+
+        if input.is_key_pressed(KeyCode::Escape) {
+            return Some(LevelSelectEvent::Back);
+        }
+        if input.is_key_pressed(KeyCode::I) {
+            return Some(LevelSelectEvent::Import);
+        }
+        let editing = input.is_key_pressed(KeyCode::E);
+        let deleting = input.is_key_pressed(KeyCode::Delete) || input.is_key_pressed(KeyCode::X);
+        if !(chosen || editing || deleting) {
+            return None;
+        }
+        let entry = self.entries.get(self.cursor)?;
+
+        let (event, error) = if (editing || deleting) && !entry.custom {
+            (None, Some(tr("level_select.builtin_locked")))
+        } else if deleting {
+            self.confirm_delete = true;
+            (None, None)
+        } else {
+            match &entry.level {
+                Ok(level) if editing => (
+                    Some(LevelSelectEvent::Edit(entry.path.clone(), level.clone())),
+                    None,
+                ),
+                Ok(level) if level.properties.required_stars > records.total_stars() => (
+                    None,
+                    Some(tr_with(
+                        "level_select.locked",
+                        &[
+                            ("stars", &level.properties.required_stars),
+                            ("have", &records.total_stars()),
+                        ],
+                    )),
+                ),
+                Ok(level) => (
+                    Some(LevelSelectEvent::Play(entry.path.clone(), level.clone())),
+                    None,
+                ),
+                Err(err) => (
+                    None,
+                    Some(tr_with("level_select.open_error", &[("error", err)])),
+                ),
+            }
+        };
+        self.error = error;
+        event
+    }
+
+    /// Drop a level whose file has been deleted
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|entry| entry.path != path);
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+        self.scroll_to_cursor();
+    }
+
+    pub fn render(&self, thumbnails: &ThumbnailCache, records: &LevelRecords) {
+        let center_x = GameConfig::VIRTUAL_WIDTH / 2.0;
+        GraphicsUtils::draw_text_centered(
+            &tr("level_select.title"),
+            center_x,
+            100.0,
+            TextStyle::Title.size(),
+            WHITE,
+        );
+        let palette = RenderStyle::current().palette();
+        let (thumbnail_width, thumbnail_height) = GameConfig::THUMBNAIL_SIZE;
+        let rows = GameConfig::LEVEL_SELECT_VISIBLE_ROWS;
+        let total_stars = records.total_stars();
+
+        let total = tr_with("level_select.total_stars", &[("stars", &total_stars)]);
+        let (x, _, w, _) = Self::row_rect(0);
+        let width = GraphicsUtils::measure_text(&total, TextStyle::Hud.size()).width;
+        GraphicsUtils::text(
+            &total,
+            x + w - width,
+            100.0,
+            TextStyle::Hud.size(),
+            palette.coin,
+        );
+
+        for (row, entry) in self.entries.iter().enumerate().skip(self.scroll).take(rows) {
+            let (x, y, w, h) = Self::row_rect(row - self.scroll);
+            GraphicsUtils::draw_panel(x, y, w, h);
+            if row == self.cursor {
+                draw_rectangle_lines(x, y, w, h, 3.0, YELLOW);
+            }
+
+            let (thumb_x, thumb_y) = (x + 4.0, y + 4.0);
+            match thumbnails.get(&entry.path) {
+                Some(texture) => draw_texture(texture, thumb_x, thumb_y, WHITE),
+                None => draw_rectangle(
+                    thumb_x,
+                    thumb_y,
+                    thumbnail_width as f32,
+                    thumbnail_height as f32,
+                    Color::new(0.0, 0.0, 0.0, 0.4),
+                ),
+            }
+
+            let text_x = thumb_x + thumbnail_width as f32 + 16.0;
+            GraphicsUtils::text(
+                &entry.name(),
+                text_x,
+                y + 32.0,
+                TextStyle::HudLarge.size(),
+                WHITE,
+            );
+            let kind = if entry.custom {
+                tr("level_select.custom")
+            } else {
+                tr("level_select.builtin")
+            };
+            let width = GraphicsUtils::measure_text(&kind, TextStyle::Caption.size()).width;
+            GraphicsUtils::text(
+                &kind,
+                x + w - 16.0 - width,
+                y + 32.0,
+                TextStyle::Caption.size(),
+                LIGHTGRAY,
+            );
+
+            let level = match &entry.level {
+                Ok(level) => level,
+                Err(err) => {
+                    GraphicsUtils::text(
+                        err,
+                        text_x,
+                        y + 60.0,
+                        TextStyle::Caption.size(),
+                        palette.warning,
+                    );
+                    continue;
+                }
+            };
+            let author = if level.author.is_empty() {
+                tr("level_select.unknown_author")
+            } else {
+                tr_with("level_select.author", &[("author", &level.author)])
+            };
+            GraphicsUtils::text(&author, text_x, y + 58.0, TextStyle::Hud.size(), LIGHTGRAY);
+
+            let record = records.get(&entry.key());
+            let stars = record.map_or(0, |record| record.stars);
+            for index in 0..MAX_STARS {
+                let radius = GameConfig::LEVEL_SELECT_STAR_RADIUS;
+                let center = Vec2::new(
+                    x + w - 16.0 - radius - (MAX_STARS - 1 - index) as f32 * radius * 2.4,
+                    y + 78.0,
+                );
+                let color = if index < stars {
+                    palette.coin
+                } else {
+                    Color::new(0.0, 0.0, 0.0, 0.5)
+                };
+                GraphicsUtils::draw_star(center, radius, color);
+            }
+
+            let required = level.properties.required_stars;
+            if required > total_stars {
+                let locked = tr_with("level_select.requires", &[("stars", &required)]);
+                GraphicsUtils::text(
+                    &locked,
+                    text_x,
+                    y + 84.0,
+                    TextStyle::Hud.size(),
+                    palette.warning,
+                );
+                continue;
+            }
+            let best = match record {
+                Some(record) => match record.best_time {
+                    Some(time) => tr_with(
+                        "level_select.cleared",
+                        &[
+                            ("score", &record.best_score),
+                            ("time", &GameConfig::format_time(time)),
+                        ],
+                    ),
+                    None => tr_with("level_select.not_cleared", &[("score", &record.best_score)]),
+                },
+                None => tr("level_select.not_played"),
+            };
+            GraphicsUtils::text(&best, text_x, y + 84.0, TextStyle::Hud.size(), GOLD);
+        }
+
+        if self.scroll > 0 {
+            GraphicsUtils::draw_text_centered(
+                &tr("level_select.more_above"),
+                center_x,
+                140.0,
+                TextStyle::Caption.size(),
+                LIGHTGRAY,
+            );
+        }
+        if self.scroll + rows < self.entries.len() {
+            let (_, y, _, h) = Self::row_rect(rows - 1);
+            GraphicsUtils::draw_text_centered(
+                &tr("level_select.more_below"),
+                center_x,
+                y + h + 20.0,
+                TextStyle::Caption.size(),
+                LIGHTGRAY,
+            );
+        }
+
+        let (footer, color) = if self.confirm_delete {
+            (tr("level_select.confirm_delete"), palette.warning)
+        } else if let Some(error) = &self.error {
+            (error.clone(), palette.warning)
+        } else if self.selected().is_some_and(|entry| entry.custom) {
+            (tr("level_select.prompt_custom"), LIGHTGRAY)
+        } else {
+            (tr("level_select.prompt"), LIGHTGRAY)
+        };
+        GraphicsUtils::draw_text_centered(
+            &footer,
+            center_x,
+            GameConfig::VIRTUAL_HEIGHT - 70.0,
+            TextStyle::Hud.size(),
+            color,
+        );
+    }
+}
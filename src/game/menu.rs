@@ -0,0 +1,543 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, TextStyle, Viewport};
+use crate::input::InputHandler;
+use crate::locale::tr;
+use crate::settings::SettingSlider;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuItem {
+    Continue,
+    Play,
+    Leaderboard,
+    Stats,
+    Skins,
+    Shop,
+    Profiles,
+    Editor,
+    Quit,
+    NormalMode,
+    DailyChallenge,
+    DailyPractice,
+    Survival,
+    LevelSelect,
+    Back,
+    Resume,
+    Restart,
+    Settings,
+    /// Opens the assist options screen
+    Assist,
+    /// Move on to the next level, offered after dying on this one too often
+    SkipLevel,
+    SaveAndQuit,
+    // Settings screen entries, each flipping or cycling one setting
+    AutoPause,
+    FallDamage,
+    ToggleSprint,
+    AutoRepeatJump,
+    ReducedMotion,
+    HighContrast,
+    Palette,
+    Language,
+    Rumble,
+    EventFeed,
+    /// Opens the screen for moving the on-screen touch buttons
+    TouchLayout,
+    /// Which control preset plays the game
+    Controls,
+    /// Opens the screen for binding keys to actions
+    Rebind,
+    // Assist screen entries
+    Invincible,
+    InfiniteJumps,
+    AutoLedge,
+    Slider(SettingSlider),
+}
+
+/// What the player did with a menu this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuEvent {
+    /// Enter, Space or a click on an item
+    Chosen(MenuItem),
+    /// Left (-1) or right (+1) on an item with a value
+    Adjusted(MenuItem, i32),
+    /// A slider dragged with the mouse to this point along its track, 0 to 1
+    Set(MenuItem, f32),
+    /// Escape
+    Cancelled,
+}
+
+/// What an item shows beside its label
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemValue {
+    /// A plain button, labelled in the middle
+    None,
+    /// An on/off switch
+    Toggle(bool),
+    /// One of a list of choices
+    Text(String),
+    /// A filled track with a handle at `fraction` of the way along, and the value as text
+    Slider { fraction: f32, text: String },
+}
+
+impl MenuItem {
+    pub fn label(&self) -> String {
+        tr(self.key())
+    }
+
+    /// Key of the item's label in the language files
+    fn key(&self) -> &'static str {
+        match self {
+            MenuItem::Continue => "menu.continue",
+            MenuItem::Play => "menu.play",
+            MenuItem::Leaderboard => "menu.leaderboard",
+            MenuItem::Stats => "menu.stats",
+            MenuItem::Skins => "menu.skins",
+            MenuItem::Shop => "menu.shop",
+            MenuItem::Profiles => "menu.profiles",
+            MenuItem::Editor => "menu.editor",
+            MenuItem::Quit => "menu.quit",
+            MenuItem::NormalMode => "menu.normal_mode",
+            MenuItem::DailyChallenge => "menu.daily_challenge",
+            MenuItem::DailyPractice => "menu.daily_practice",
+            MenuItem::Survival => "menu.survival",
+            MenuItem::LevelSelect => "menu.level_select",
+            MenuItem::Back => "menu.back",
+            MenuItem::Resume => "menu.resume",
+            MenuItem::Restart => "menu.restart",
+            MenuItem::Settings => "menu.settings",
+            MenuItem::Assist => "menu.assist",
+            MenuItem::SkipLevel => "menu.skip_level",
+            // The run is saved on the way out, so Continue picks it back up
+            MenuItem::SaveAndQuit => "menu.save_and_quit",
+            MenuItem::AutoPause => "menu.auto_pause",
+            MenuItem::FallDamage => "menu.fall_damage",
+            MenuItem::ToggleSprint => "menu.toggle_sprint",
+            MenuItem::AutoRepeatJump => "menu.auto_repeat_jump",
+            MenuItem::ReducedMotion => "menu.reduced_motion",
+            MenuItem::HighContrast => "menu.high_contrast",
+            MenuItem::Palette => "menu.palette",
+            MenuItem::Language => "menu.language",
+            MenuItem::Rumble => "menu.rumble",
+            MenuItem::EventFeed => "menu.event_feed",
+            MenuItem::TouchLayout => "menu.touch_layout",
+            MenuItem::Controls => "menu.controls",
+            MenuItem::Rebind => "menu.rebind",
+            MenuItem::Invincible => "menu.invincible",
+            MenuItem::InfiniteJumps => "menu.infinite_jumps",
+            MenuItem::AutoLedge => "menu.auto_ledge",
+            MenuItem::Slider(slider) => slider.key(),
+        }
+    }
+
+    /// Items holding a value that Left and Right change in place
+    pub fn is_adjustable(&self) -> bool {
+        matches!(
+            self,
+            MenuItem::AutoPause
+                | MenuItem::FallDamage
+                | MenuItem::ToggleSprint
+                | MenuItem::AutoRepeatJump
+                | MenuItem::ReducedMotion
+                | MenuItem::HighContrast
+                | MenuItem::Palette
+                | MenuItem::Language
+                | MenuItem::Controls
+                | MenuItem::Rumble
+                | MenuItem::EventFeed
+                | MenuItem::Invincible
+                | MenuItem::InfiniteJumps
+                | MenuItem::AutoLedge
+                | MenuItem::Slider(_)
+        )
+    }
+}
+
+/// A vertical list of options picked with up/down and confirmed with Enter
+#[derive(Debug, Clone)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,
+    pub selected: usize,
+    /// Slider being dragged with the mouse, which keeps hold of it until the button is let go
+    dragging: Option<usize>,
+}
+
+impl Menu {
+    pub fn new(items: Vec<MenuItem>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            dragging: None,
+        }
+    }
+
+    /// The title menu; Continue is only offered when there's a run to resume
+    pub fn main(can_continue: bool) -> Self {
+        let mut items = vec![
+            MenuItem::Play,
+            MenuItem::Leaderboard,
+            MenuItem::Stats,
+            MenuItem::Skins,
+            MenuItem::Shop,
+            MenuItem::Profiles,
+            MenuItem::Editor,
+            MenuItem::Quit,
+        ];
+        if can_continue {
+            items.insert(0, MenuItem::Continue);
+        }
+        Self::new(items)
+    }
+
+    /// The pause menu; Skip Level is only offered once the level has been died on often
+    pub fn pause(can_skip: bool) -> Self {
+        let mut items = vec![
+            MenuItem::Resume,
+            MenuItem::Restart,
+            MenuItem::Settings,
+            MenuItem::Assist,
+            MenuItem::SaveAndQuit,
+        ];
+        if can_skip {
+            items.insert(2, MenuItem::SkipLevel);
+        }
+        Self::new(items)
+    }
+
+    pub fn settings() -> Self {
+        Self::new(vec![
+            MenuItem::AutoPause,
+            MenuItem::FallDamage,
+            MenuItem::ToggleSprint,
+            MenuItem::AutoRepeatJump,
+            MenuItem::ReducedMotion,
+            MenuItem::HighContrast,
+            MenuItem::Palette,
+            MenuItem::Language,
+            MenuItem::Controls,
+            MenuItem::Rebind,
+            MenuItem::Slider(SettingSlider::MasterVolume),
+            MenuItem::Slider(SettingSlider::SfxVolume),
+            MenuItem::Slider(SettingSlider::MusicVolume),
+            MenuItem::Slider(SettingSlider::UiScale),
+            MenuItem::EventFeed,
+            MenuItem::Slider(SettingSlider::CameraSmoothing),
+            MenuItem::Rumble,
+            MenuItem::Slider(SettingSlider::RumbleIntensity),
+            MenuItem::TouchLayout,
+            MenuItem::Back,
+        ])
+    }
+
+    pub fn assist() -> Self {
+        Self::new(vec![
+            MenuItem::Invincible,
+            MenuItem::InfiniteJumps,
+            MenuItem::Slider(SettingSlider::GameSpeed),
+            MenuItem::AutoLedge,
+            MenuItem::Back,
+        ])
+    }
+
+    pub fn mode_select() -> Self {
+        Self::new(vec![
+            MenuItem::NormalMode,
+            MenuItem::DailyChallenge,
+            MenuItem::DailyPractice,
+            MenuItem::Survival,
+            MenuItem::LevelSelect,
+            MenuItem::Back,
+        ])
+    }
+
+    /// Up/down movement of a list cursor over `count` rows, wrapping at either end. Screens
+    /// that draw their own rows, like the shop, share it with menus.
+    pub fn navigate(selected: usize, count: usize, input: &InputHandler) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let mut selected = selected.min(count - 1);
+        if input.is_key_pressed(KeyCode::Up) || input.is_key_pressed(KeyCode::W) {
+            selected = (selected + count - 1) % count;
+        }
+        if input.is_key_pressed(KeyCode::Down) || input.is_key_pressed(KeyCode::S) {
+            selected = (selected + 1) % count;
+        }
+        selected
+    }
+
+    /// Move the selection and return what the player did this frame, if anything
+    pub fn handle_input(&mut self, input: &InputHandler) -> Option<MenuEvent> {
+        self.selected = Self::navigate(self.selected, self.items.len(), input);
+
+        if let Some(index) = self.dragging {
+            if is_mouse_button_down(MouseButton::Left) {
+                self.selected = index;
+                return Some(MenuEvent::Set(
+                    self.items[index],
+                    self.track_fraction(index),
+                ));
+            }
+            self.dragging = None;
+        }
+
+        for index in 0..self.items.len() {
+            if matches!(self.items[index], MenuItem::Slider(_)) {
+                let (x, y, w, h) = self.track_rect(index);
+                // A little slack above and below makes the thin track easier to grab
+                if GraphicsUtils::is_mouse_over_rect(x, y - h, w, h * 3.0)
+                    && is_mouse_button_pressed(MouseButton::Left)
+                {
+                    self.selected = index;
+                    self.dragging = Some(index);
+                    return Some(MenuEvent::Set(
+                        self.items[index],
+                        self.track_fraction(index),
+                    ));
+                }
+            }
+        }
+
+        for index in 0..self.items.len() {
+            let (x, y, w, h) = self.item_rect(index);
+            if GraphicsUtils::is_mouse_over_rect(x, y, w, h) {
+                self.selected = index;
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    return Some(MenuEvent::Chosen(self.items[index]));
+                }
+            }
+        }
+
+        let item = self.items[self.selected];
+        if item.is_adjustable() {
+            if input.is_key_pressed(KeyCode::Left) || input.is_key_pressed(KeyCode::A) {
+                return Some(MenuEvent::Adjusted(item, -1));
+            }
+            if input.is_key_pressed(KeyCode::Right) || input.is_key_pressed(KeyCode::D) {
+                return Some(MenuEvent::Adjusted(item, 1));
+            }
+        }
+        if input.is_action_pressed() {
+            return Some(MenuEvent::Chosen(item));
+        }
+        if input.is_key_pressed(KeyCode::Escape) {
+            return Some(MenuEvent::Cancelled);
+        }
+        None
+    }
+
+    fn item_rect(&self, index: usize) -> (f32, f32, f32, f32) {
+        // Items with values need room for them; long lists pack tighter
+        let width = if self.items.iter().any(MenuItem::is_adjustable) {
+            480.0
+        } else {
+            320.0
+        };
+        let (height, spacing) = if self.items.len() > 16 {
+            (26.0, 30.0)
+        } else if self.items.len() > 12 {
+            (30.0, 36.0)
+        } else if self.items.len() > 8 {
+            (34.0, 40.0)
+        } else {
+            (44.0, 56.0)
+        };
+        let x = (GameConfig::VIRTUAL_WIDTH - width) / 2.0;
+        // Long lists start higher up so the last item stays on screen
+        let top = (GameConfig::VIRTUAL_HEIGHT * 0.4)
+            .min(GameConfig::VIRTUAL_HEIGHT - 40.0 - self.items.len() as f32 * spacing);
+        let y = top + index as f32 * spacing;
+        (x, y, width, height)
+    }
+
+    /// The slider track on an item's right-hand side
+    fn track_rect(&self, index: usize) -> (f32, f32, f32, f32) {
+        let (x, y, w, h) = self.item_rect(index);
+        let width = 150.0;
+        let height = 10.0;
+        (x + w - 16.0 - width, y + (h - height) / 2.0, width, height)
+    }
+
+    /// How far along the track of item `index` the mouse is, 0 to 1
+    fn track_fraction(&self, index: usize) -> f32 {
+        let (x, _, w, _) = self.track_rect(index);
+        ((Viewport::mouse_position().x - x) / w).clamp(0.0, 1.0)
+    }
+
+    pub fn render(&self, title: &str) {
+        self.render_with(title, |_| ItemValue::None);
+    }
+
+    /// Render with each item's current value from `value`, e.g. the settings it controls
+    pub fn render_with(&self, title: &str, value: impl Fn(MenuItem) -> ItemValue) {
+        let center_x = GameConfig::VIRTUAL_WIDTH / 2.0;
+        // Kept clear of the first item when a long list starts high up
+        let (_, first_y, _, _) = self.item_rect(0);
+        GraphicsUtils::draw_text_with_shadow(
+            title,
+            center_x - GraphicsUtils::measure_text(title, TextStyle::Logo.size()).width / 2.0,
+            (GameConfig::VIRTUAL_HEIGHT * 0.25).min(first_y - 30.0),
+            TextStyle::Logo.size(),
+            WHITE,
+            BLACK,
+            Vec2::new(3.0, 3.0),
+        );
+
+        for (index, item) in self.items.iter().enumerate() {
+            let (x, y, w, h) = self.item_rect(index);
+            let selected = index == self.selected;
+            let fill = if selected {
+                Color::new(1.0, 1.0, 1.0, 0.9)
+            } else {
+                Color::new(0.0, 0.0, 0.0, 0.6)
+            };
+            let text_color = if selected { BLACK } else { WHITE };
+
+            draw_rectangle(x, y, w, h, fill);
+            draw_rectangle_lines(x, y, w, h, 2.0, WHITE);
+
+            let value = value(*item);
+            if value == ItemValue::None {
+                GraphicsUtils::draw_text_centered(
+                    &item.label(),
+                    x + w / 2.0,
+                    y + h / 2.0,
+                    TextStyle::Hud.size(),
+                    text_color,
+                );
+                continue;
+            }
+            let baseline = y + h / 2.0 + GameConfig::UI_FONT_SIZE * 0.3;
+            GraphicsUtils::text(
+                &item.label(),
+                x + 16.0,
+                baseline,
+                TextStyle::Hud.size(),
+                text_color,
+            );
+            let right = x + w - 16.0;
+            match value {
+                ItemValue::None => {}
+                ItemValue::Toggle(on) => Self::draw_toggle(right, y + h / 2.0, on, text_color),
+                ItemValue::Text(text) => {
+                    let width = GraphicsUtils::measure_text(&text, TextStyle::Hud.size()).width;
+                    GraphicsUtils::text(
+                        &text,
+                        right - width,
+                        baseline,
+                        TextStyle::Hud.size(),
+                        text_color,
+                    );
+                }
+                ItemValue::Slider { fraction, text } => {
+                    let (tx, ty, tw, th) = self.track_rect(index);
+                    Self::draw_slider(tx, ty, tw, th, fraction, selected);
+                    let width = GraphicsUtils::measure_text(&text, TextStyle::Hud.size()).width;
+                    GraphicsUtils::text(
+                        &text,
+                        tx - 14.0 - width,
+                        baseline,
+                        TextStyle::Hud.size(),
+                        text_color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// An on/off switch ending at `right`, vertically centered on `center_y`
+    fn draw_toggle(right: f32, center_y: f32, on: bool, color: Color) {
+        let (width, height) = (44.0, 22.0);
+        let x = right - width;
+        let y = center_y - height / 2.0;
+        let track = if on {
+            RenderStyle::current().palette().health_high
+        } else {
+            DARKGRAY
+        };
+        draw_rectangle(x, y, width, height, track);
+        draw_rectangle_lines(x, y, width, height, 2.0, color);
+        let knob_x = if on { x + width - height } else { x };
+        draw_rectangle(knob_x + 3.0, y + 3.0, height - 6.0, height - 6.0, WHITE);
+    }
+
+    /// A progress bar track with a handle at `fraction` of the way along
+    fn draw_slider(x: f32, y: f32, width: f32, height: f32, fraction: f32, selected: bool) {
+        GraphicsUtils::draw_progress_bar(
+            x,
+            y,
+            width,
+            height,
+            fraction,
+            DARKGRAY,
+            SKYBLUE,
+            if selected { BLACK } else { WHITE },
+        );
+        let handle_x = x + width * fraction.clamp(0.0, 1.0);
+        let handle = Vec2::new(8.0, height + 10.0);
+        draw_rectangle(
+            handle_x - handle.x / 2.0,
+            y - 5.0,
+            handle.x,
+            handle.y,
+            WHITE,
+        );
+        draw_rectangle_lines(
+            handle_x - handle.x / 2.0,
+            y - 5.0,
+            handle.x,
+            handle.y,
+            2.0,
+            BLACK,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Where the cursor ends up after one frame with `keys` pressed
+    fn navigate_with(selected: usize, count: usize, keys: &[KeyCode]) -> usize {
+        let mut input = InputHandler::new();
+        input.hold_keys(keys);
+        Menu::navigate(selected, count, &input)
+    }
+
+    #[test]
+    fn up_from_the_top_wraps_to_the_bottom() {
+        assert_eq!(navigate_with(0, 5, &[KeyCode::Up]), 4);
+        assert_eq!(navigate_with(0, 5, &[KeyCode::W]), 4);
+        assert_eq!(navigate_with(3, 5, &[KeyCode::Up]), 2);
+    }
+
+    #[test]
+    fn down_from_the_bottom_wraps_to_the_top() {
+        assert_eq!(navigate_with(4, 5, &[KeyCode::Down]), 0);
+        assert_eq!(navigate_with(4, 5, &[KeyCode::S]), 0);
+        assert_eq!(navigate_with(1, 5, &[KeyCode::Down]), 2);
+    }
+
+    #[test]
+    fn held_keys_move_once() {
+        let mut input = InputHandler::new();
+        input.hold_keys(&[KeyCode::Down]);
+        assert_eq!(Menu::navigate(0, 3, &input), 1);
+        input.hold_keys(&[KeyCode::Down]);
+        assert_eq!(Menu::navigate(1, 3, &input), 1);
+    }
+
+    #[test]
+    fn single_and_empty_lists_stay_put() {
+        assert_eq!(navigate_with(0, 1, &[KeyCode::Down]), 0);
+        assert_eq!(navigate_with(0, 1, &[KeyCode::Up]), 0);
+        assert_eq!(navigate_with(0, 0, &[KeyCode::Down]), 0);
+    }
+
+    #[test]
+    fn a_cursor_past_a_shrunk_list_comes_back_onto_it() {
+        assert_eq!(navigate_with(9, 3, &[]), 2);
+        assert_eq!(navigate_with(9, 3, &[KeyCode::Down]), 0);
+    }
+}
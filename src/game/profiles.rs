@@ -0,0 +1,222 @@
+use macroquad::prelude::*;
+
+use super::menu::Menu;
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, TextStyle};
+use crate::input::InputHandler;
+use crate::locale::{tr, tr_with};
+use crate::profile::{Profile, Profiles, PROFILE_COUNT, PROFILE_NAME_LENGTH};
+use crate::shop::Shop;
+use crate::stats::{format_duration, Stats};
+
+/// What the player did on the profile screen this frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileEvent {
+    /// Play as this profile from now on
+    Switch(Profile),
+    Back,
+}
+
+/// The save slots, where profiles are picked, made and deleted
+#[derive(Debug, Clone)]
+pub struct ProfileScreen {
+    pub profiles: Profiles,
+    pub cursor: usize,
+    /// Name typed so far for a new profile in the slot under the cursor
+    pub naming: Option<String>,
+    /// Waiting for Y or N before deleting the profile under the cursor
+    pub confirm_delete: bool,
+    /// Why the last thing tried didn't work
+    pub error: Option<String>,
+    /// A line about each taken slot's progress, read when the screen opens
+    summaries: [Option<String>; PROFILE_COUNT],
+}
+
+impl ProfileScreen {
+    pub fn new(profiles: Profiles) -> Self {
+        let summaries = std::array::from_fn(|slot| profiles.get(slot).map(|p| summarize(&p)));
+        Self {
+            cursor: profiles.active,
+            profiles,
+            naming: None,
+            confirm_delete: false,
+            error: None,
+            summaries,
+        }
+    }
+
+    fn row_rect(slot: usize) -> (f32, f32, f32, f32) {
+        let width = 560.0;
+        let height = 80.0;
+        let x = (GameConfig::VIRTUAL_WIDTH - width) / 2.0;
+        let y = 170.0 + slot as f32 * (height + 16.0);
+        (x, y, width, height)
+    }
+
+    pub fn handle_input(&mut self, input: &InputHandler) -> Option<ProfileEvent> {
+        if self.naming.is_some() {
+            return self.name_profile(input);
+        }
+        if self.confirm_delete {
+            if input.is_key_pressed(KeyCode::Y) {
+                self.confirm_delete = false;
+                self.delete(self.cursor);
+            } else if input.is_key_pressed(KeyCode::N) || input.is_key_pressed(KeyCode::Escape) {
+                self.confirm_delete = false;
+            }
+            return None;
+        }
+
+        self.cursor = Menu::navigate(self.cursor, PROFILE_COUNT, input);
+        let mut chosen = input.is_action_pressed();
+        for slot in 0..PROFILE_COUNT {
+            let (x, y, w, h) = Self::row_rect(slot);
+            if GraphicsUtils::is_mouse_over_rect(x, y, w, h) {
+                self.cursor = slot;
+                chosen |= is_mouse_button_pressed(MouseButton::Left);
+            }
+        }
+
+        if chosen {
+            self.error = None;
+            match self.profiles.get(self.cursor) {
+                Some(profile) => return Some(ProfileEvent::Switch(profile)),
+                None => self.naming = Some(String::new()),
+            }
+        } else if input.is_key_pressed(KeyCode::Delete) || input.is_key_pressed(KeyCode::X) {
+            self.error = None;
+            if self.cursor == self.profiles.active {
+                self.error = Some(tr("profiles.delete_active"));
+            } else if self.profiles.get(self.cursor).is_some() {
+                self.confirm_delete = true;
+            }
+        } else if input.is_key_pressed(KeyCode::Escape) {
+            return Some(ProfileEvent::Back);
+        }
+        None
+    }
+
+    /// A new profile's name is being typed, so letter keys belong to it
+    pub fn is_typing(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    /// Type a name for a new profile: Enter makes it and switches to it, Escape gives up
+    fn name_profile(&mut self, input: &InputHandler) -> Option<ProfileEvent> {
+        let name = self.naming.as_mut()?;
+        for character in input.typed().chars() {
+            if name.chars().count() < PROFILE_NAME_LENGTH {
+                name.push(character);
+            }
+        }
+        if input.is_key_pressed(KeyCode::Backspace) {
+            name.pop();
+        }
+        if input.is_key_pressed(KeyCode::Escape) {
+            self.naming = None;
+        } else if input.is_key_pressed(KeyCode::Enter) {
+            let name = self.naming.take().unwrap_or_default();
+            let profile = self.profiles.create(self.cursor, &name);
+            self.summaries[self.cursor] = Some(summarize(&profile));
+            return Some(ProfileEvent::Switch(profile));
+        }
+        None
+    }
+
+    fn delete(&mut self, slot: usize) {
+        match self
+            .profiles
+            .delete(slot)
+            .and_then(|()| self.profiles.save())
+        {
+            Ok(()) => self.summaries[slot] = None,
+            Err(err) => {
+                self.error = Some(tr_with("profiles.delete_error", &[("error", &err)]));
+            }
+        }
+    }
+
+    pub fn render(&self) {
+        let center_x = GameConfig::VIRTUAL_WIDTH / 2.0;
+        GraphicsUtils::draw_text_centered(
+            &tr("profiles.title"),
+            center_x,
+            100.0,
+            TextStyle::Title.size(),
+            WHITE,
+        );
+        let palette = RenderStyle::current().palette();
+
+        for slot in 0..PROFILE_COUNT {
+            let (x, y, w, h) = Self::row_rect(slot);
+            GraphicsUtils::draw_panel(x, y, w, h);
+            if slot == self.cursor {
+                draw_rectangle_lines(x, y, w, h, 3.0, YELLOW);
+            }
+
+            let (name, color) = match (&self.naming, &self.profiles.names[slot]) {
+                (Some(typed), _) if slot == self.cursor => (format!("{}_", typed), YELLOW),
+                (_, Some(name)) => (name.clone(), WHITE),
+                (_, None) => (tr("profiles.empty"), GRAY),
+            };
+            GraphicsUtils::text(
+                &format!("{}. {}", slot + 1, name),
+                x + 16.0,
+                y + 34.0,
+                TextStyle::HudLarge.size(),
+                color,
+            );
+            if let Some(summary) = &self.summaries[slot] {
+                GraphicsUtils::text(
+                    summary,
+                    x + 16.0,
+                    y + 62.0,
+                    TextStyle::Caption.size(),
+                    LIGHTGRAY,
+                );
+            }
+            if slot == self.profiles.active {
+                let playing = tr("profiles.playing");
+                let width = GraphicsUtils::measure_text(&playing, TextStyle::Hud.size()).width;
+                GraphicsUtils::text(
+                    &playing,
+                    x + w - 16.0 - width,
+                    y + 34.0,
+                    TextStyle::Hud.size(),
+                    GREEN,
+                );
+            }
+        }
+
+        let (footer, color) = if self.naming.is_some() {
+            (tr("profiles.naming_prompt"), LIGHTGRAY)
+        } else if self.confirm_delete {
+            (tr("profiles.confirm_delete"), palette.warning)
+        } else if let Some(error) = &self.error {
+            (error.clone(), palette.warning)
+        } else {
+            (tr("profiles.prompt"), LIGHTGRAY)
+        };
+        GraphicsUtils::draw_text_centered(
+            &footer,
+            center_x,
+            GameConfig::VIRTUAL_HEIGHT - 70.0,
+            TextStyle::Hud.size(),
+            color,
+        );
+    }
+}
+
+/// Best score, time played and banked coins for a profile's slot row
+fn summarize(profile: &Profile) -> String {
+    let stats = Stats::load(profile);
+    let shop = Shop::load(profile);
+    tr_with(
+        "profiles.summary",
+        &[
+            ("score", &stats.best_normal),
+            ("playtime", &format_duration(stats.playtime)),
+            ("coins", &shop.coins),
+        ],
+    )
+}
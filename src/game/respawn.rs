@@ -0,0 +1,121 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+
+/// Steps of putting the player back into the level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RespawnPhase {
+    /// The view dims where the player was
+    FadeOut,
+    /// The player has been moved and the camera glides over to them
+    Pan,
+    /// The view brightens again; spawn protection starts once it's done
+    FadeIn,
+}
+
+/// Timer-driven respawn sequence that runs inside `GameState::Playing`. The world holds
+/// still and input is ignored until it finishes.
+#[derive(Debug, Clone)]
+pub struct Respawn {
+    pub phase: RespawnPhase,
+    /// Seconds spent in the current phase
+    elapsed: f32,
+    /// Where the player reappears
+    pub target: Vec2,
+    /// Whether the level goes back to how it was at the last checkpoint while the view
+    /// is dark
+    pub to_checkpoint: bool,
+    /// Camera position when the pan started
+    pan_from: Vec2,
+}
+
+/// What the game has to do after advancing the sequence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RespawnStep {
+    Continue,
+    /// The fade-out finished: move the player to `target` and start panning
+    MovePlayer,
+    Finished,
+}
+
+impl Respawn {
+    pub fn new(target: Vec2) -> Self {
+        Self {
+            phase: RespawnPhase::FadeOut,
+            elapsed: 0.0,
+            target,
+            to_checkpoint: false,
+            pan_from: Vec2::ZERO,
+        }
+    }
+
+    /// Back to the last checkpoint at `target`, with the level as it was then
+    pub fn checkpoint(target: Vec2) -> Self {
+        Self {
+            to_checkpoint: true,
+            ..Self::new(target)
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        match self.phase {
+            RespawnPhase::FadeOut | RespawnPhase::FadeIn => GameConfig::RESPAWN_FADE_TIME,
+            RespawnPhase::Pan => GameConfig::RESPAWN_PAN_TIME,
+        }
+    }
+
+    /// How far through the current phase we are, 0 to 1
+    fn progress(&self) -> f32 {
+        (self.elapsed / self.duration()).clamp(0.0, 1.0)
+    }
+
+    /// Advance in real seconds
+    pub fn update(&mut self, real_dt: f32) -> RespawnStep {
+        self.elapsed += real_dt;
+        if self.elapsed < self.duration() {
+            return RespawnStep::Continue;
+        }
+
+        self.elapsed = 0.0;
+        match self.phase {
+            RespawnPhase::FadeOut => {
+                self.phase = RespawnPhase::Pan;
+                RespawnStep::MovePlayer
+            }
+            RespawnPhase::Pan => {
+                self.phase = RespawnPhase::FadeIn;
+                RespawnStep::Continue
+            }
+            RespawnPhase::FadeIn => RespawnStep::Finished,
+        }
+    }
+
+    /// Remember where the camera was when the player got moved
+    pub fn start_pan(&mut self, camera: Vec2) {
+        self.pan_from = camera;
+    }
+
+    /// Camera position once the pan has started, easing from where it was to `destination`;
+    /// `None` while fading out, when the camera stays put
+    pub fn camera(&self, destination: Vec2) -> Option<Vec2> {
+        match self.phase {
+            RespawnPhase::FadeOut => None,
+            RespawnPhase::Pan => {
+                let t = self.progress();
+                let eased = t * t * (3.0 - 2.0 * t);
+                Some(self.pan_from.lerp(destination, eased))
+            }
+            RespawnPhase::FadeIn => Some(destination),
+        }
+    }
+
+    /// Opacity of the black overlay drawn over the world
+    pub fn dim(&self) -> f32 {
+        let full = GameConfig::RESPAWN_DIM;
+        match self.phase {
+            RespawnPhase::FadeOut => full * self.progress(),
+            RespawnPhase::Pan => full,
+            RespawnPhase::FadeIn => full * (1.0 - self.progress()),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::graphics::RenderStyle;
+
+/// A short-lived boost to bonus points, earned with perfect landings. It fades back to
+/// nothing over a few seconds; another perfect landing refreshes it and raises it a little.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScoreMultiplier {
+    /// Share added to bonus points while the timer is full, e.g. 0.25 for +25%
+    bonus: f32,
+    /// Seconds left before the bonus has faded out
+    remaining: f32,
+}
+
+impl ScoreMultiplier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the bonus, or refresh it and raise it a step if it's still running
+    pub fn perfect(&mut self) {
+        self.bonus = if self.is_active() {
+            (self.bonus + GameConfig::PERFECT_MULTIPLIER_STEP)
+                .min(GameConfig::PERFECT_MULTIPLIER_MAX)
+        } else {
+            GameConfig::PERFECT_MULTIPLIER
+        };
+        self.remaining = GameConfig::PERFECT_MULTIPLIER_TIME;
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.remaining = (self.remaining - delta_time).max(0.0);
+        if self.remaining == 0.0 {
+            self.bonus = 0.0;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    /// What points are multiplied by right now, easing down to 1 as the bonus fades
+    pub fn factor(&self) -> f32 {
+        1.0 + self.bonus * self.remaining / GameConfig::PERFECT_MULTIPLIER_TIME
+    }
+
+    /// `points` with the bonus added
+    pub fn apply(&self, points: i32) -> i32 {
+        (points as f32 * self.factor()).round() as i32
+    }
+
+    /// A thin bar under the score that empties as the bonus fades
+    pub fn render_bar(&self, score_area: Rect, scale: f32) {
+        if !self.is_active() {
+            return;
+        }
+        let width = GameConfig::PERFECT_BAR_WIDTH * scale;
+        let height = GameConfig::PERFECT_BAR_HEIGHT * scale;
+        let y = score_area.y + score_area.h;
+        let fill = self.remaining / GameConfig::PERFECT_MULTIPLIER_TIME;
+        draw_rectangle(
+            score_area.x,
+            y,
+            width,
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.5),
+        );
+        draw_rectangle(
+            score_area.x,
+            y,
+            width * fill,
+            height,
+            RenderStyle::current().palette().coin,
+        );
+    }
+}
+
+/// Whether a landing at `center_x` counts as perfect on a platform spanning `left` to
+/// `right`: close enough to either edge that a little further would have missed it. The
+/// window is capped so long stretches of ground don't hand them out.
+pub fn is_edge_landing(center_x: f32, left: f32, right: f32) -> bool {
+    let margin =
+        ((right - left) * GameConfig::PERFECT_EDGE_FRACTION).min(GameConfig::PERFECT_EDGE_MAX);
+    (left..=right).contains(&center_x) && (center_x <= left + margin || center_x >= right - margin)
+}
@@ -1,5 +1,72 @@
+use super::daily::DailyChallenge;
+use crate::level::Level;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameState {
+    /// Asset files loading behind a progress bar, before the title menu
+    Loading,
+    MainMenu,
     Playing,
+    Paused,
+    /// Settings screen opened from the pause menu
+    Settings,
+    /// Dragging the on-screen touch buttons around, opened from the settings screen
+    TouchLayout,
+    /// Binding keys to actions, opened from the settings screen
+    Controls,
+    /// Assist options screen opened from the pause menu
+    Assist,
     GameOver,
+    /// The boss went down; shown after the run is recorded
+    Victory,
+    EnterInitials,
+    Leaderboard,
+    Stats,
+    Skins,
+    Shop,
+    ModeSelect,
+    DailyResults,
+    /// Picking a built-in or custom level to play, edit or delete
+    LevelSelect,
+    /// Typing or pasting a level code to import
+    ImportLevel,
+    /// Picking, making or deleting a save profile
+    Profiles,
+    Editor,
+}
+
+/// What kind of run is being played
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    Normal,
+    /// Today's challenge; practice runs are never recorded
+    Daily {
+        challenge: DailyChallenge,
+        practice: bool,
+    },
+    /// Waves of enemies in a closed arena
+    Survival,
+}
+
+impl RunMode {
+    /// The level as this mode plays it
+    pub fn level_for(&self, level: &Level) -> Level {
+        match self {
+            RunMode::Normal => level.clone(),
+            RunMode::Daily { challenge, .. } => challenge.apply(level),
+            // Survival is always fought in its own arena
+            RunMode::Survival => Level::load_survival(),
+        }
+    }
+
+    /// A daily attempt that will be written to the records when it ends
+    pub fn is_scored_daily(&self) -> bool {
+        matches!(
+            self,
+            RunMode::Daily {
+                practice: false,
+                ..
+            }
+        )
+    }
 }
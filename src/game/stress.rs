@@ -0,0 +1,32 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::Enemy;
+use crate::graphics::ParticleSystem;
+
+/// The stress test crowd, spread out across a screen's width centered on `center_x`
+/// and dropped from `drop_y`
+pub fn crowd(center_x: f32, drop_y: f32) -> Vec<Enemy> {
+    let count = GameConfig::STRESS_ENEMIES;
+    let spacing = GameConfig::VIRTUAL_WIDTH / count as f32;
+    let left = center_x - GameConfig::VIRTUAL_WIDTH / 2.0;
+    (0..count)
+        .map(|index| {
+            // Every other one starts the other way so they don't walk in lockstep
+            let mut enemy = Enemy::new(
+                left + index as f32 * spacing,
+                drop_y,
+                GameConfig::ENEMY_SPEED,
+            );
+            enemy.facing = if index % 2 == 0 { 1.0 } else { -1.0 };
+            enemy
+        })
+        .collect()
+}
+
+/// Keep `STRESS_PARTICLES` dust puffs alive around `origin`
+pub fn top_up_particles(particles: &mut ParticleSystem, origin: Vec2) {
+    particles.set_limit(GameConfig::STRESS_PARTICLES);
+    let missing = GameConfig::STRESS_PARTICLES.saturating_sub(particles.len());
+    particles.emit_dust(origin, missing, 300.0, Color::new(0.9, 0.9, 0.9, 0.8));
+}
@@ -0,0 +1,148 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, TextStyle};
+use crate::locale::{tr, tr_with};
+use crate::rng::SeededRng;
+
+/// Runs survival mode: sends out waves of enemies from the arena's spawn points, with a
+/// breather between waves, and keeps the score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemySpawner {
+    /// The wave being fought, or about to start during a breather; starts at 1
+    pub wave: u32,
+    /// Seconds left of the breather before `wave` starts
+    pub breather: f32,
+    /// Enemies of the current wave not sent out yet
+    pub remaining: u32,
+    /// Walking speed of this wave's enemies
+    pub speed: f32,
+    spawn_timer: f32,
+    pub kills: u32,
+    /// Points from completed waves
+    pub wave_bonus: i32,
+}
+
+impl EnemySpawner {
+    pub fn new() -> Self {
+        Self {
+            wave: 1,
+            breather: GameConfig::WAVE_BREATHER,
+            remaining: 0,
+            speed: GameConfig::ENEMY_SPEED,
+            spawn_timer: 0.0,
+            kills: 0,
+            wave_bonus: 0,
+        }
+    }
+
+    /// Enemies in a wave, before the difficulty multiplier
+    pub fn wave_size(wave: u32) -> u32 {
+        GameConfig::WAVE_BASE_SIZE + (wave - 1) * GameConfig::WAVE_SIZE_STEP
+    }
+
+    /// Walking speed in a wave, before the difficulty multiplier
+    pub fn wave_speed(wave: u32) -> f32 {
+        GameConfig::ENEMY_SPEED * (1.0 + (wave - 1) as f32 * GameConfig::ENEMY_SPEED_STEP)
+    }
+
+    pub fn waves_survived(&self) -> u32 {
+        self.wave - 1
+    }
+
+    pub fn is_resting(&self) -> bool {
+        self.breather > 0.0
+    }
+
+    pub fn score(&self) -> i32 {
+        self.kills as i32 * GameConfig::ENEMY_KILL_SCORE + self.wave_bonus
+    }
+
+    pub fn record_kill(&mut self) {
+        self.kills += 1;
+    }
+
+    /// Advance the wave clock with `alive` enemies in the arena. Returns the spawn point
+    /// index to send the next enemy out of, if one is due, drawn from the run's `rng`.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        alive: usize,
+        spawn_points: usize,
+        difficulty: f32,
+        rng: &mut SeededRng,
+    ) -> Option<usize> {
+        if self.is_resting() {
+            self.breather -= delta_time;
+            if !self.is_resting() {
+                self.start_wave(difficulty);
+            }
+            return None;
+        }
+
+        if self.remaining == 0 {
+            if alive == 0 {
+                // Cleared; rest before the next one
+                self.wave_bonus += self.wave as i32 * GameConfig::WAVE_CLEAR_BONUS;
+                self.wave += 1;
+                self.breather = GameConfig::WAVE_BREATHER;
+            }
+            return None;
+        }
+
+        self.spawn_timer -= delta_time;
+        if self.spawn_timer > 0.0 || alive >= GameConfig::MAX_ENEMIES || spawn_points == 0 {
+            return None;
+        }
+        self.spawn_timer = GameConfig::ENEMY_SPAWN_INTERVAL;
+        self.remaining -= 1;
+        Some(rng.index(spawn_points))
+    }
+
+    fn start_wave(&mut self, difficulty: f32) {
+        self.remaining = (Self::wave_size(self.wave) as f32 * difficulty).round() as u32;
+        self.speed = Self::wave_speed(self.wave) * difficulty;
+        self.spawn_timer = 0.0;
+    }
+
+    /// "Wave N" across the middle of the screen during the breather
+    pub fn render_banner(&self) {
+        if !self.is_resting() {
+            return;
+        }
+        let center = GameConfig::screen_center();
+        GraphicsUtils::draw_text_centered(
+            &tr_with("survival.wave", &[("wave", &self.wave)]),
+            center.x,
+            center.y - 60.0,
+            TextStyle::Logo.size(),
+            WHITE,
+        );
+        let note = if self.wave == 1 {
+            tr("survival.first_wave")
+        } else {
+            tr_with("survival.cleared", &[("wave", &(self.wave - 1))])
+        };
+        GraphicsUtils::draw_text_centered(
+            &note,
+            center.x,
+            center.y - 20.0,
+            TextStyle::Body.size(),
+            LIGHTGRAY,
+        );
+        GraphicsUtils::draw_text_centered(
+            &format!("{:.0}", self.breather.ceil()),
+            center.x,
+            center.y + 20.0,
+            TextStyle::HudLarge.size(),
+            YELLOW,
+        );
+    }
+}
+
+impl Default for EnemySpawner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
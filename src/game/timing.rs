@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+
+/// Splits variable frame times into fixed simulation steps
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    pub step: f32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        Self {
+            step,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Add a frame's worth of time and return how many steps are now due
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time;
+        let steps = (self.accumulator / self.step) as u32;
+        self.accumulator -= steps as f32 * self.step;
+        steps
+    }
+
+    /// The manual path, for stepping through a run one step at a time: `steps` are due
+    /// whatever the time, and the frame is drawn exactly where the last of them left things
+    pub fn advance_manually(&mut self, steps: u32) -> u32 {
+        self.accumulator = self.step;
+        steps
+    }
+
+    /// How far into the next step the leftover time reaches, from 0 to 1; rendering
+    /// blends between the last two steps by this much
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+
+    /// Drop any partial step, e.g. after a pause
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+/// Counts events and reports how many happened over the last second
+#[derive(Debug, Clone, Default)]
+pub struct RateCounter {
+    count: u32,
+    elapsed: f32,
+    rate: f32,
+}
+
+impl RateCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, events: u32) {
+        self.count += events;
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+        if self.elapsed >= 1.0 {
+            self.rate = self.count as f32 / self.elapsed;
+            self.count = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+}
+
+/// Caps the render rate for machines where vsync is off
+pub struct FrameLimiter {
+    last_frame: f64,
+}
+
+impl FrameLimiter {
+    /// Sleeping is imprecise, so the last stretch of each frame is spent spinning
+    #[cfg(not(target_arch = "wasm32"))]
+    const SPIN_MARGIN: f64 = 0.002;
+
+    pub fn new() -> Self {
+        Self {
+            last_frame: get_time(),
+        }
+    }
+
+    /// Wait out whatever is left of the frame budget; a limit of 0 disables waiting
+    pub fn wait(&mut self, fps_limit: u32) {
+        if fps_limit > 0 {
+            let deadline = self.last_frame + 1.0 / fps_limit as f64;
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let remaining = deadline - get_time() - Self::SPIN_MARGIN;
+                if remaining > 0.0 {
+                    std::thread::sleep(std::time::Duration::from_secs_f64(remaining));
+                }
+            }
+
+            while get_time() < deadline {
+                std::hint::spin_loop();
+            }
+        }
+
+        self.last_frame = get_time();
+    }
+}
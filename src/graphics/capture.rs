@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::date::Date;
+
+/// Where screenshots and clips are written, relative to the working directory
+pub const CAPTURE_DIR: &str = "captures";
+
+/// Message for the toast once a capture has been written or has failed
+pub type CaptureResult = Result<String, String>;
+
+/// Screenshots of the virtual frame, plus a rolling buffer of recent frames that can be
+/// saved as a GIF. Files are encoded off the main thread so the game doesn't hitch.
+pub struct Capture {
+    /// Downscaled recent frames, oldest first
+    frames: VecDeque<RgbaImage>,
+    /// Real seconds since the last frame was sampled into the clip buffer
+    since_sample: f32,
+    sender: Sender<CaptureResult>,
+    receiver: Receiver<CaptureResult>,
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            frames: VecDeque::new(),
+            since_sample: 0.0,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Most frames the clip buffer holds
+    fn capacity() -> usize {
+        (GameConfig::CLIP_SECONDS * GameConfig::CLIP_FPS).ceil() as usize
+    }
+
+    /// Save the frame at full virtual resolution
+    pub fn save_screenshot(&self, frame: &Texture2D) {
+        let image = frame.get_texture_data();
+        self.spawn(move || {
+            let path = unique_path("screenshot", "png")?;
+            let upright = flip_and_downscale(&image, 1);
+            upright
+                .save(&path)
+                .map_err(|err| format!("Screenshot failed: {}", err))?;
+            Ok(format!("Saved {}", path.display()))
+        });
+    }
+
+    /// Sample the frame into the clip buffer at the clip frame rate, dropping the oldest
+    pub fn record(&mut self, frame: &Texture2D, delta_time: f32) {
+        self.since_sample += delta_time;
+        let interval = 1.0 / GameConfig::CLIP_FPS;
+        if self.since_sample < interval {
+            return;
+        }
+        // Don't try to catch up after a stall; one sample per frame is plenty
+        self.since_sample = (self.since_sample - interval).min(interval);
+
+        let image = frame.get_texture_data();
+        self.frames
+            .push_back(flip_and_downscale(&image, GameConfig::CLIP_DOWNSCALE));
+        while self.frames.len() > Self::capacity() {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Write the buffered frames out as a looping GIF
+    pub fn save_clip(&self) {
+        if self.frames.is_empty() {
+            let _ = self.sender.send(Err("Clip buffer is empty".to_string()));
+            return;
+        }
+
+        let frames: Vec<RgbaImage> = self.frames.iter().cloned().collect();
+        self.spawn(move || {
+            let path = unique_path("clip", "gif")?;
+            write_gif(&path, frames).map_err(|err| format!("Clip failed: {}", err))?;
+            Ok(format!("Saved {}", path.display()))
+        });
+    }
+
+    /// The outcome of a finished capture, if one has come back since the last call
+    pub fn poll(&self) -> Option<CaptureResult> {
+        self.receiver.try_recv().ok()
+    }
+
+    fn spawn(&self, job: impl FnOnce() -> CaptureResult + Send + 'static) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let sender = self.sender.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(job());
+            });
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            drop(job);
+            let _ = self
+                .sender
+                .send(Err("Captures aren't available on the web".to_string()));
+        }
+    }
+}
+
+/// Render targets come back bottom row first; turn the frame upright, keeping every
+/// `step`th pixel in each direction
+fn flip_and_downscale(image: &Image, step: usize) -> RgbaImage {
+    let source_width = image.width as usize;
+    let source_height = image.height as usize;
+    let width = source_width / step;
+    let height = source_height / step;
+
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row = source_height - 1 - y * step;
+        for x in 0..width {
+            let index = (row * source_width + x * step) * 4;
+            bytes.extend_from_slice(&image.bytes[index..index + 4]);
+        }
+    }
+
+    RgbaImage::from_raw(width as u32, height as u32, bytes)
+        .expect("pixel buffer matches its dimensions")
+}
+
+fn write_gif(path: &Path, frames: Vec<RgbaImage>) -> image::ImageResult<()> {
+    let file = File::create(path)?;
+    // Quantizing is the slow part; trade a little color accuracy for speed
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay =
+        Delay::from_saturating_duration(Duration::from_secs_f32(1.0 / GameConfig::CLIP_FPS));
+    encoder.encode_frames(
+        frames
+            .into_iter()
+            .map(|image| Frame::from_parts(image, 0, 0, delay)),
+    )
+}
+
+/// A fresh timestamped file in the capture directory, e.g. `captures/clip-2024-05-01_14-03-22.gif`
+pub fn unique_path(prefix: &str, extension: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(CAPTURE_DIR)
+        .map_err(|err| format!("Can't create {}: {}", CAPTURE_DIR, err))?;
+
+    let stem = format!("{}-{}", prefix, timestamp());
+    let mut path = Path::new(CAPTURE_DIR).join(format!("{}.{}", stem, extension));
+    let mut copy = 2;
+    while path.exists() {
+        path = Path::new(CAPTURE_DIR).join(format!("{}-{}.{}", stem, copy, extension));
+        copy += 1;
+    }
+    Ok(path)
+}
+
+/// Current UTC time as `YYYY-MM-DD_HH-MM-SS`
+fn timestamp() -> String {
+    let seconds = miniquad::date::now().max(0.0) as u64;
+    let date = Date::from_days_since_epoch((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!(
+        "{}_{:02}-{:02}-{:02}",
+        date,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
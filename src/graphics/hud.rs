@@ -0,0 +1,385 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::locale::tr_with;
+
+use super::{GraphicsUtils, TextStyle, Viewport};
+
+/// The screen edge or corner a HUD element stays attached to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    // Nothing sits at the bottom middle yet, but the set of edges is kept whole
+    #[allow(dead_code)]
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    fn is_bottom(&self) -> bool {
+        matches!(
+            self,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight
+        )
+    }
+}
+
+/// Places HUD elements against screen edges and scales their text, margins and bars by
+/// the UI scale, so they stay readable and apart in any window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HudLayout {
+    pub scale: f32,
+}
+
+impl HudLayout {
+    pub fn new(scale: f32) -> Self {
+        Self {
+            scale: scale.clamp(GameConfig::UI_MIN_SCALE, GameConfig::UI_MAX_SCALE),
+        }
+    }
+
+    /// Scale for the current window: a small window shrinks the whole frame, so the HUD
+    /// grows to make up for it, up to `UI_MAX_AUTO_SCALE`
+    pub fn auto_scale() -> f32 {
+        (1.0 / Viewport::scale()).clamp(1.0, GameConfig::UI_MAX_AUTO_SCALE)
+    }
+
+    pub fn font_size(&self, size: f32) -> f32 {
+        size * self.scale
+    }
+
+    pub fn margin(&self) -> f32 {
+        GameConfig::UI_MARGIN * self.scale
+    }
+
+    pub fn line_height(&self) -> f32 {
+        GameConfig::UI_LINE_HEIGHT * self.scale
+    }
+
+    /// Top-left corner of a box `size` big (already scaled) attached at `anchor`, with
+    /// `offset` (unscaled) pushing it in from that edge: right and down from top-left
+    /// corners, left and up from bottom-right ones
+    pub fn place(&self, anchor: Anchor, offset: Vec2, size: Vec2) -> Vec2 {
+        Self::place_on(
+            Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT),
+            self.margin(),
+            anchor,
+            offset * self.scale,
+            size,
+        )
+    }
+
+    /// The placement math on its own, for a screen `screen` big
+    pub fn place_on(screen: Vec2, margin: f32, anchor: Anchor, offset: Vec2, size: Vec2) -> Vec2 {
+        // Centered elements move right with a positive offset, edge ones move inwards
+        let x = match anchor {
+            Anchor::TopLeft | Anchor::BottomLeft => margin + offset.x,
+            Anchor::TopCenter | Anchor::BottomCenter => (screen.x - size.x) / 2.0 + offset.x,
+            Anchor::TopRight | Anchor::BottomRight => screen.x - size.x - margin - offset.x,
+        };
+        let y = if anchor.is_bottom() {
+            screen.y - size.y - margin - offset.y
+        } else {
+            margin + offset.y
+        };
+        Vec2::new(x, y)
+    }
+
+    /// Draw text on the `line`th row away from the anchored edge, returning the area it
+    /// covers
+    pub fn text(
+        &self,
+        text: &str,
+        anchor: Anchor,
+        line: usize,
+        font_size: f32,
+        color: Color,
+    ) -> Rect {
+        let size = self.font_size(font_size);
+        let dimensions = GraphicsUtils::measure_text(text, size);
+        let row = Vec2::new(0.0, line as f32 * GameConfig::UI_LINE_HEIGHT);
+        let top_left = self.place(anchor, row, Vec2::new(dimensions.width, size));
+        // Text is drawn from its baseline
+        GraphicsUtils::text(text, top_left.x, top_left.y + size * 0.75, size, color);
+        Rect::new(top_left.x, top_left.y, dimensions.width, size)
+    }
+
+    /// Rows of text stacked away from `anchor`, starting at `first_line`
+    pub fn column(&self, anchor: Anchor, first_line: usize) -> HudColumn {
+        HudColumn {
+            layout: *self,
+            anchor,
+            line: first_line,
+        }
+    }
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Hands out successive rows at one anchor, so optional lines never overlap
+#[derive(Debug, Clone, Copy)]
+pub struct HudColumn {
+    layout: HudLayout,
+    anchor: Anchor,
+    line: usize,
+}
+
+impl HudColumn {
+    pub fn text(&mut self, text: &str, font_size: f32, color: Color) -> Rect {
+        let area = self
+            .layout
+            .text(text, self.anchor, self.line, font_size, color);
+        self.line += 1;
+        area
+    }
+}
+
+/// The score as shown on the HUD: it counts up towards the real score instead of jumping,
+/// pops at every milestone and briefly shows what was just gained
+#[derive(Debug, Clone)]
+pub struct ScoreCounter {
+    /// The number on screen, catching up with `target`
+    shown: f32,
+    target: i32,
+    /// Seconds left of the milestone flash
+    flash: f32,
+    /// Points from recent gains, shown as "+N"
+    recent: i32,
+    /// Seconds until `recent` is cleared, restarted by every gain
+    recent_timer: f32,
+}
+
+impl ScoreCounter {
+    pub fn new() -> Self {
+        Self {
+            shown: 0.0,
+            target: 0,
+            flash: 0.0,
+            recent: 0,
+            recent_timer: 0.0,
+        }
+    }
+
+    /// Follow the real score. A drop, such as a new run starting, is shown straight away.
+    pub fn update(&mut self, delta_time: f32, score: i32) {
+        let gain = score - self.target;
+        if gain < 0 {
+            self.snap(score);
+            return;
+        }
+        self.target = score;
+        // The steady trickle from distance and time isn't worth a "+N"
+        if gain >= GameConfig::SCORE_GAIN_MIN {
+            self.recent += gain;
+            self.recent_timer = GameConfig::SCORE_GAIN_TIME;
+        }
+        self.recent_timer = (self.recent_timer - delta_time).max(0.0);
+        if self.recent_timer == 0.0 {
+            self.recent = 0;
+        }
+
+        // Closing a fixed fraction of the gap each second: big gains race, small ones creep
+        let before = self.shown;
+        let gap = self.target as f32 - self.shown;
+        self.shown += gap * (GameConfig::SCORE_COUNT_RATE * delta_time).min(1.0);
+        if (self.target as f32 - self.shown).abs() < 1.0 {
+            self.shown = self.target as f32;
+        }
+
+        let milestone = GameConfig::SCORE_MILESTONE as f32;
+        if (self.shown / milestone).floor() > (before / milestone).floor() {
+            self.flash = GameConfig::SCORE_FLASH_TIME;
+        } else {
+            self.flash = (self.flash - delta_time).max(0.0);
+        }
+    }
+
+    /// Show `score` at once, with no count-up, flash or "+N"
+    pub fn snap(&mut self, score: i32) {
+        *self = Self::new();
+        self.shown = score as f32;
+        self.target = score;
+    }
+
+    pub fn shown(&self) -> i32 {
+        self.shown as i32
+    }
+
+    /// "Score: N" as the next row of `column`, followed by any recent "+N". Returns the area
+    /// the score covers.
+    pub fn render(&self, column: &mut HudColumn, font_size: f32) -> Rect {
+        let flash = self.flash / GameConfig::SCORE_FLASH_TIME;
+        let reduced_motion = super::RenderStyle::current().reduced_motion;
+        let pop = if reduced_motion {
+            1.0
+        } else {
+            1.0 + 0.2 * flash
+        };
+        let color = if flash > 0.0 { YELLOW } else { WHITE };
+        let area = column.text(
+            &tr_with("hud.score", &[("score", &self.shown())]),
+            font_size * pop,
+            color,
+        );
+
+        if self.recent > 0 {
+            let size = column.layout.font_size(font_size * 0.8);
+            let fade = self.recent_timer / GameConfig::SCORE_GAIN_TIME;
+            GraphicsUtils::text(
+                &format!("+{}", self.recent),
+                area.x + area.w + size * 0.5,
+                area.y + area.h * 0.75,
+                size,
+                Color::new(1.0, 0.9, 0.4, fade),
+            );
+        }
+        area
+    }
+}
+
+impl Default for ScoreCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A collected coin on its way to the HUD counter
+#[derive(Debug, Clone, Copy)]
+struct Flight {
+    /// Screen position it was picked up at
+    start: Vec2,
+    elapsed: f32,
+}
+
+/// Coins arcing from where they were picked up into the HUD coin counter, which only
+/// counts each one once it lands
+#[derive(Debug, Clone, Default)]
+pub struct PickupFlights {
+    flights: Vec<Flight>,
+}
+
+impl PickupFlights {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.flights.clear();
+    }
+
+    /// Coins still in the air, not yet added to the counter
+    pub fn in_flight(&self) -> u32 {
+        self.flights.len() as u32
+    }
+
+    /// Send off a coin collected at `start` on screen
+    pub fn launch(&mut self, start: Vec2) {
+        self.flights.push(Flight {
+            start,
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for flight in &mut self.flights {
+            flight.elapsed += delta_time;
+        }
+        self.flights
+            .retain(|flight| flight.elapsed < GameConfig::PICKUP_FLIGHT_TIME);
+    }
+
+    /// Draw each coin along a curve that rises from its start and drops into `target`
+    pub fn render(&self, target: Vec2, radius: f32, color: Color) {
+        for flight in &self.flights {
+            let t = (flight.elapsed / GameConfig::PICKUP_FLIGHT_TIME).clamp(0.0, 1.0);
+            // Ease in, so it lifts off gently and speeds into the counter
+            let eased = t * t;
+            let control = Vec2::new(
+                flight.start.x + (target.x - flight.start.x) * 0.2,
+                flight.start.y.min(target.y) - GameConfig::PICKUP_FLIGHT_ARC,
+            );
+            let position = flight
+                .start
+                .lerp(control, eased)
+                .lerp(control.lerp(target, eased), eased);
+            let scale = 1.0 - 0.6 * eased;
+            let alpha = 1.0 - 0.4 * eased;
+            draw_circle(
+                position.x,
+                position.y,
+                radius * scale,
+                Color::new(color.r, color.g, color.b, color.a * alpha),
+            );
+        }
+    }
+}
+
+/// Text that floats up from somewhere in the world and fades, e.g. "Perfect!" over a landing
+#[derive(Debug, Clone, Default)]
+pub struct ScorePopups {
+    popups: Vec<Popup>,
+}
+
+#[derive(Debug, Clone)]
+struct Popup {
+    text: String,
+    /// World position it rose from
+    start: Vec2,
+    color: Color,
+    elapsed: f32,
+}
+
+impl ScorePopups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.popups.clear();
+    }
+
+    /// Float `text` up from `position` in the world
+    pub fn push(&mut self, text: String, position: Vec2, color: Color) {
+        self.popups.push(Popup {
+            text,
+            start: position,
+            color,
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for popup in &mut self.popups {
+            popup.elapsed += delta_time;
+        }
+        self.popups
+            .retain(|popup| popup.elapsed < GameConfig::POPUP_TIME);
+    }
+
+    pub fn render(&self, camera_x: f32, camera_y: f32) {
+        let size = TextStyle::HudLarge.size();
+        for popup in &self.popups {
+            let t = popup.elapsed / GameConfig::POPUP_TIME;
+            // Eases out, so it pops up quickly and then drifts
+            let rise = GameConfig::POPUP_RISE * (1.0 - (1.0 - t) * (1.0 - t));
+            let alpha = (1.0 - t) * popup.color.a;
+            let width = GraphicsUtils::measure_text(&popup.text, size).width;
+            GraphicsUtils::draw_text_with_shadow(
+                &popup.text,
+                popup.start.x + camera_x - width / 2.0,
+                popup.start.y + camera_y - rise,
+                size,
+                Color::new(popup.color.r, popup.color.g, popup.color.b, alpha),
+                Color::new(0.0, 0.0, 0.0, alpha * 0.6),
+                Vec2::new(2.0, 2.0),
+            );
+        }
+    }
+}
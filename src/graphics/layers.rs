@@ -0,0 +1,54 @@
+use crate::entities::Entity;
+
+/// Depth bands the world is drawn in, back to front
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer {
+    /// Sky, mountains and clouds scrolling slower than the world
+    FarBackground,
+    /// Scenery behind everything solid
+    Background,
+    /// Platforms, terrain and what's built into them
+    Platforms,
+    Entities,
+    Player,
+    /// Drawn over the player, such as water they're swimming in
+    Foreground,
+    /// Particles and score popups, over the whole world
+    Effects,
+}
+
+type DrawCommand<'a> = Box<dyn FnOnce() + 'a>;
+
+/// Draw calls for one frame, gathered in any order and made layer by layer. Within a
+/// layer they go lowest depth first, then in the order they were pushed.
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    commands: Vec<(RenderLayer, f32, DrawCommand<'a>)>,
+}
+
+impl<'a> RenderQueue<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, layer: RenderLayer, depth: f32, draw: impl FnOnce() + 'a) {
+        self.commands.push((layer, depth, Box::new(draw)));
+    }
+
+    /// Queue `draw` in the layer and at the depth `entity` asks for
+    pub fn push_entity<E: Entity>(&mut self, entity: &'a E, draw: impl FnOnce(&'a E) + 'a) {
+        self.push(entity.render_layer(), entity.render_depth(), move || {
+            draw(entity)
+        });
+    }
+
+    /// Make every queued draw call, back to front
+    pub fn flush(mut self) {
+        // A stable sort keeps the push order between equals
+        self.commands
+            .sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        for (_, _, draw) in self.commands {
+            draw();
+        }
+    }
+}
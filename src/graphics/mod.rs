@@ -1,24 +1,33 @@
 use macroquad::prelude::*;
 
+use crate::config::GameConfig;
+
+pub mod capture;
+pub mod hud;
+pub mod layers;
+pub mod palette;
+pub mod particles;
+pub mod style;
+pub mod text;
+pub mod thumbnail;
+pub mod trail;
+pub mod viewport;
+
+pub use capture::Capture;
+pub use hud::{Anchor, HudLayout, PickupFlights, ScoreCounter, ScorePopups};
+pub use layers::{RenderLayer, RenderQueue};
+pub use palette::PaletteKind;
+pub use particles::ParticleSystem;
+pub use style::RenderStyle;
+pub use text::TextStyle;
+pub use thumbnail::ThumbnailCache;
+pub use trail::{TrailColor, TrailConfig, TrailRenderer};
+pub use viewport::Viewport;
+
 pub struct GraphicsUtils;
 
 impl GraphicsUtils {
-    /// Draw a filled rectangle with a border
-    pub fn draw_rectangle_with_border(
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
-        fill_color: Color,
-        border_color: Color,
-        border_width: f32,
-    ) {
-        // Draw filled rectangle
-        draw_rectangle(x, y, width, height, fill_color);
-
-        // Draw border
-        draw_rectangle_lines(x, y, width, height, border_width, border_color);
-    }
+    const HEALTH_BAR_SEGMENTS: usize = 10;
 
     /// Draw a gradient rectangle
     pub fn draw_gradient_rectangle(
@@ -62,6 +71,28 @@ impl GraphicsUtils {
         draw_circle_lines(x, y, radius, border_width, border_color);
     }
 
+    /// Draw text in the UI font with its baseline at `y`
+    pub fn text(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+        text::with_font(|font| {
+            draw_text_ex(
+                text,
+                x,
+                y,
+                TextParams {
+                    font,
+                    font_size: font_size as u16,
+                    color,
+                    ..Default::default()
+                },
+            );
+        });
+    }
+
+    /// Size of text drawn in the UI font, so layouts line up with what `text` draws
+    pub fn measure_text(text: &str, font_size: f32) -> TextDimensions {
+        text::with_font(|font| measure_text(text, font, font_size as u16, 1.0))
+    }
+
     /// Draw text with a shadow/outline effect
     pub fn draw_text_with_shadow(
         text: &str,
@@ -73,7 +104,7 @@ impl GraphicsUtils {
         shadow_offset: Vec2,
     ) {
         // Draw shadow
-        draw_text(
+        Self::text(
             text,
             x + shadow_offset.x,
             y + shadow_offset.y,
@@ -82,19 +113,48 @@ impl GraphicsUtils {
         );
 
         // Draw main text
-        draw_text(text, x, y, font_size, text_color);
+        Self::text(text, x, y, font_size, text_color);
     }
 
     /// Draw text centered at a position
     pub fn draw_text_centered(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
-        let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+        let text_dimensions = Self::measure_text(text, font_size);
         let text_x = x - text_dimensions.width / 2.0;
         let text_y = y + text_dimensions.height / 2.0;
 
-        draw_text(text, text_x, text_y, font_size, color);
+        Self::text(text, text_x, text_y, font_size, color);
+    }
+
+    /// Draw a translucent dark box with a light border for grouping text
+    pub fn draw_panel(x: f32, y: f32, width: f32, height: f32) {
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_rectangle_lines(x, y, width, height, 2.0, LIGHTGRAY);
+    }
+
+    /// Break text into lines no wider than `max_width`, splitting on spaces
+    pub fn wrap_text(text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && Self::measure_text(&candidate, font_size).width > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
     }
 
     /// Draw a progress bar
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_progress_bar(
         x: f32,
         y: f32,
@@ -144,15 +204,21 @@ impl GraphicsUtils {
 
         // Draw button text
         let text_color = if is_pressed { WHITE } else { BLACK };
-        Self::draw_text_centered(text, x + width / 2.0, y + height / 2.0, 20.0, text_color);
+        Self::draw_text_centered(
+            text,
+            x + width / 2.0,
+            y + height / 2.0,
+            TextStyle::Hud.size(),
+            text_color,
+        );
 
         // Return if button was clicked
         if is_mouse_button_pressed(MouseButton::Left) {
-            let mouse_pos = mouse_position();
-            return mouse_pos.0 >= x
-                && mouse_pos.0 <= x + width
-                && mouse_pos.1 >= y
-                && mouse_pos.1 <= y + height;
+            let mouse_pos = Viewport::mouse_position();
+            return mouse_pos.x >= x
+                && mouse_pos.x <= x + width
+                && mouse_pos.y >= y
+                && mouse_pos.y <= y + height;
         }
 
         false
@@ -160,57 +226,11 @@ impl GraphicsUtils {
 
     /// Check if mouse is over a rectangle
     pub fn is_mouse_over_rect(x: f32, y: f32, width: f32, height: f32) -> bool {
-        let mouse_pos = mouse_position();
-        mouse_pos.0 >= x
-            && mouse_pos.0 <= x + width
-            && mouse_pos.1 >= y
-            && mouse_pos.1 <= y + height
-    }
-
-    /// Draw a simple animated sprite (using color cycling)
-    pub fn draw_animated_rectangle(
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
-        base_color: Color,
-        animation_speed: f32,
-    ) {
-        let time = get_time() as f32;
-        let wave = (time * animation_speed).sin();
-
-        // Animate the brightness
-        let brightness = 0.8 + 0.2 * wave;
-        let animated_color = Color::new(
-            base_color.r * brightness,
-            base_color.g * brightness,
-            base_color.b * brightness,
-            base_color.a,
-        );
-
-        draw_rectangle(x, y, width, height, animated_color);
-    }
-
-    /// Draw a grid for debugging
-    pub fn draw_debug_grid(camera_x: f32, camera_y: f32, grid_size: f32, color: Color) {
-        let screen_w = screen_width();
-        let screen_h = screen_height();
-
-        // Vertical lines
-        let start_x = (camera_x / grid_size).floor() * grid_size - camera_x;
-        let mut x = start_x;
-        while x < screen_w {
-            draw_line(x, 0.0, x, screen_h, 1.0, color);
-            x += grid_size;
-        }
-
-        // Horizontal lines
-        let start_y = (camera_y / grid_size).floor() * grid_size - camera_y;
-        let mut y = start_y;
-        while y < screen_h {
-            draw_line(0.0, y, screen_w, y, 1.0, color);
-            y += grid_size;
-        }
+        let mouse_pos = Viewport::mouse_position();
+        mouse_pos.x >= x
+            && mouse_pos.x <= x + width
+            && mouse_pos.y >= y
+            && mouse_pos.y <= y + height
     }
 
     /// Draw a simple particle effect
@@ -232,6 +252,124 @@ impl GraphicsUtils {
         }
     }
 
+    /// Draw a filled ring between two radii as a strip of triangles
+    pub fn draw_annulus(center: Vec2, inner_radius: f32, outer_radius: f32, color: Color) {
+        let segments = 48;
+
+        for i in 0..segments {
+            let a0 = (i as f32 / segments as f32) * 2.0 * std::f32::consts::PI;
+            let a1 = ((i + 1) as f32 / segments as f32) * 2.0 * std::f32::consts::PI;
+            let (d0, d1) = (Vec2::new(a0.cos(), a0.sin()), Vec2::new(a1.cos(), a1.sin()));
+
+            let inner0 = center + d0 * inner_radius;
+            let inner1 = center + d1 * inner_radius;
+            let outer0 = center + d0 * outer_radius;
+            let outer1 = center + d1 * outer_radius;
+
+            draw_triangle(inner0, outer0, outer1, color);
+            draw_triangle(inner0, outer1, inner1, color);
+        }
+    }
+
+    /// Draw evenly spaced dots along a line through `points`
+    pub fn draw_dotted_path(points: &[Vec2], spacing: f32, radius: f32, color: Color) {
+        // Carried over from one piece to the next so the spacing stays even at the joins
+        let mut offset = 0.0;
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let length = a.distance(b);
+            let mut along = offset;
+            while along < length {
+                let dot = a.lerp(b, along / length);
+                draw_circle(dot.x, dot.y, radius, color);
+                along += spacing;
+            }
+            offset = along - length;
+        }
+    }
+
+    /// Cover the screen in darkness except for a soft-edged circle of light
+    pub fn draw_darkness(center: Vec2, radius: f32, softness: f32, alpha: f32) {
+        let bands = 12;
+        let inner_radius = (radius - softness).max(0.0);
+
+        // Fade from fully lit to fully dark across the soft edge
+        for i in 0..bands {
+            let r0 = inner_radius + (radius - inner_radius) * i as f32 / bands as f32;
+            let r1 = inner_radius + (radius - inner_radius) * (i + 1) as f32 / bands as f32;
+            let band_alpha = alpha * (i + 1) as f32 / bands as f32;
+            Self::draw_annulus(center, r0, r1, Color::new(0.0, 0.0, 0.0, band_alpha));
+        }
+
+        // Solid darkness out to the furthest screen corner
+        let far_radius = [
+            Vec2::ZERO,
+            Vec2::new(GameConfig::VIRTUAL_WIDTH, 0.0),
+            Vec2::new(0.0, GameConfig::VIRTUAL_HEIGHT),
+            Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT),
+        ]
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(0.0, f32::max)
+            + 1.0;
+
+        if far_radius > radius {
+            Self::draw_annulus(center, radius, far_radius, Color::new(0.0, 0.0, 0.0, alpha));
+        }
+    }
+
+    /// Draw a soft glow made of stacked translucent circles
+    pub fn draw_glow(center: Vec2, radius: f32, color: Color) {
+        let layers = 6;
+
+        for i in 0..layers {
+            let layer_radius = radius * (layers - i) as f32 / layers as f32;
+            draw_circle(
+                center.x,
+                center.y,
+                layer_radius,
+                Color::new(color.r, color.g, color.b, color.a / layers as f32),
+            );
+        }
+    }
+
+    /// Draw a filled five-pointed star, point up
+    pub fn draw_star(center: Vec2, radius: f32, color: Color) {
+        let inner_radius = radius * 0.45;
+        let corner = |index: usize| {
+            let angle = index as f32 * std::f32::consts::PI / 5.0 - std::f32::consts::FRAC_PI_2;
+            let distance = if index.is_multiple_of(2) {
+                radius
+            } else {
+                inner_radius
+            };
+            center + Vec2::new(angle.cos(), angle.sin()) * distance
+        };
+        for index in 0..10 {
+            draw_triangle(center, corner(index), corner(index + 1), color);
+        }
+    }
+
+    /// Draw the mark for a run played with assist options: a white cross on a blue disc
+    pub fn draw_assist_badge(center: Vec2, radius: f32) {
+        draw_circle(center.x, center.y, radius, SKYBLUE);
+        let (long, short) = (radius * 1.2, radius * 0.4);
+        draw_rectangle(
+            center.x - long / 2.0,
+            center.y - short / 2.0,
+            long,
+            short,
+            WHITE,
+        );
+        draw_rectangle(
+            center.x - short / 2.0,
+            center.y - long / 2.0,
+            short,
+            long,
+            WHITE,
+        );
+    }
+
     /// Draw a simple health bar
     pub fn draw_health_bar(
         x: f32,
@@ -244,13 +382,9 @@ impl GraphicsUtils {
         let health_percentage = (current_health / max_health).clamp(0.0, 1.0);
 
         // Choose color based on health percentage
-        let fill_color = if health_percentage > 0.6 {
-            GREEN
-        } else if health_percentage > 0.3 {
-            YELLOW
-        } else {
-            RED
-        };
+        let fill_color = RenderStyle::current()
+            .palette()
+            .health_color(health_percentage);
 
         Self::draw_progress_bar(
             x,
@@ -262,38 +396,31 @@ impl GraphicsUtils {
             fill_color,
             WHITE,
         );
-    }
-
-    /// Draw a simple minimap
-    pub fn draw_minimap(
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
-        world_width: f32,
-        world_height: f32,
-        player_pos: Vec2,
-        platforms: &[Vec2], // Platform positions
-    ) {
-        // Draw minimap background
-        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
-        draw_rectangle_lines(x, y, width, height, 2.0, WHITE);
 
-        // Scale factors
-        let scale_x = width / world_width;
-        let scale_y = height / world_height;
-
-        // Draw platforms
-        for platform_pos in platforms {
-            let map_x = x + platform_pos.x * scale_x;
-            let map_y = y + platform_pos.y * scale_y;
-            draw_rectangle(map_x, map_y, 4.0, 2.0, GREEN);
+        // Segment dividers so the amount left reads without relying on color
+        let segment_width = width / Self::HEALTH_BAR_SEGMENTS as f32;
+        for i in 1..Self::HEALTH_BAR_SEGMENTS {
+            let divider_x = x + segment_width * i as f32;
+            draw_line(divider_x, y, divider_x, y + height, 2.0, BLACK);
         }
 
-        // Draw player
-        let player_map_x = x + player_pos.x * scale_x;
-        let player_map_y = y + player_pos.y * scale_y;
-        draw_circle(player_map_x, player_map_y, 3.0, BLUE);
+        // Hatch the fill once health is critical
+        if health_percentage <= 0.3 {
+            let fill_width = width * health_percentage;
+            let mut hatch_x = x;
+            while hatch_x < x + fill_width {
+                let end_x = (hatch_x + height).min(x + fill_width);
+                draw_line(
+                    hatch_x,
+                    y + height,
+                    end_x,
+                    y + height - (end_x - hatch_x),
+                    1.0,
+                    BLACK,
+                );
+                hatch_x += 6.0;
+            }
+        }
     }
 }
 
@@ -306,17 +433,6 @@ pub mod colors {
         Color::new(color.r, color.g, color.b, alpha)
     }
 
-    /// Lerp between two colors
-    pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
-        let t = t.clamp(0.0, 1.0);
-        Color::new(
-            a.r + (b.r - a.r) * t,
-            a.g + (b.g - a.g) * t,
-            a.b + (b.b - a.b) * t,
-            a.a + (b.a - a.a) * t,
-        )
-    }
-
     /// Get a rainbow color based on time
     pub fn rainbow_color(time: f32, speed: f32) -> Color {
         let hue = (time * speed) % 1.0;
@@ -345,13 +461,4 @@ pub mod colors {
 
         Color::new(r + m, g + m, b + m, 1.0)
     }
-
-    /// Common game colors
-    pub const PLATFORM_GREEN: Color = Color::new(0.2, 0.8, 0.2, 1.0);
-    pub const PLATFORM_BROWN: Color = Color::new(0.6, 0.4, 0.2, 1.0);
-    pub const PLAYER_BLUE: Color = Color::new(0.2, 0.4, 1.0, 1.0);
-    pub const DANGER_RED: Color = Color::new(1.0, 0.2, 0.2, 1.0);
-    pub const COLLECTIBLE_YELLOW: Color = Color::new(1.0, 1.0, 0.2, 1.0);
-    pub const UI_BACKGROUND: Color = Color::new(0.0, 0.0, 0.0, 0.7);
-    pub const UI_BORDER: Color = Color::new(0.8, 0.8, 0.8, 1.0);
 }
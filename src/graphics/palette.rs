@@ -0,0 +1,201 @@
+use macroquad::prelude::*;
+
+use crate::entities::collectible::CollectibleType;
+use crate::entities::platform::PlatformType;
+use crate::locale::tr;
+
+/// Selectable color schemes, including ones safe for common color vision deficiencies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaletteKind {
+    #[default]
+    Default,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl PaletteKind {
+    pub const ALL: [PaletteKind; 4] = [
+        PaletteKind::Default,
+        PaletteKind::Deuteranopia,
+        PaletteKind::Protanopia,
+        PaletteKind::Tritanopia,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Name shown in menus
+    pub fn label(&self) -> String {
+        tr(&format!("palette.{}", self.name()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaletteKind::Default => "default",
+            PaletteKind::Deuteranopia => "deuteranopia",
+            PaletteKind::Protanopia => "protanopia",
+            PaletteKind::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// The palette after this one, wrapping around
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The palette before this one, wrapping around
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn palette(&self) -> &'static Palette {
+        match self {
+            PaletteKind::Default => &Palette::DEFAULT,
+            PaletteKind::Deuteranopia => &Palette::DEUTERANOPIA,
+            PaletteKind::Protanopia => &Palette::PROTANOPIA,
+            PaletteKind::Tritanopia => &Palette::TRITANOPIA,
+        }
+    }
+}
+
+/// Every color that carries gameplay meaning, chosen per palette
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub player: Color,
+    pub ground: Color,
+    pub platform: Color,
+    pub breakable_platform: Color,
+    pub moving_platform: Color,
+    pub grass: Color,
+    pub coin: Color,
+    pub gem: Color,
+    pub powerup: Color,
+    pub lantern: Color,
+    pub hazard_top: Color,
+    pub hazard_bottom: Color,
+    pub danger: Color,
+    pub warning: Color,
+    pub health_high: Color,
+    pub health_mid: Color,
+    pub health_low: Color,
+}
+
+impl Palette {
+    pub const DEFAULT: Palette = Palette {
+        player: BLUE,
+        ground: BROWN,
+        platform: GREEN,
+        breakable_platform: ORANGE,
+        moving_platform: Color::new(1.0, 0.6, 0.2, 1.0),
+        grass: LIME,
+        coin: YELLOW,
+        gem: PURPLE,
+        powerup: PINK,
+        lantern: GOLD,
+        hazard_top: Color::new(1.0, 0.55, 0.1, 1.0),
+        hazard_bottom: Color::new(0.6, 0.05, 0.0, 1.0),
+        danger: RED,
+        warning: ORANGE,
+        health_high: GREEN,
+        health_mid: YELLOW,
+        health_low: RED,
+    };
+
+    /// Blue/orange contrasts in place of red/green (Okabe-Ito based)
+    pub const DEUTERANOPIA: Palette = Palette {
+        player: Color::new(0.9, 0.6, 0.0, 1.0),
+        ground: Color::new(0.4, 0.33, 0.25, 1.0),
+        platform: Color::new(0.0, 0.45, 0.7, 1.0),
+        breakable_platform: Color::new(0.8, 0.47, 0.65, 1.0),
+        moving_platform: Color::new(0.34, 0.71, 0.91, 1.0),
+        grass: Color::new(0.34, 0.71, 0.91, 1.0),
+        coin: Color::new(0.94, 0.89, 0.26, 1.0),
+        gem: Color::new(0.8, 0.47, 0.65, 1.0),
+        powerup: WHITE,
+        lantern: Color::new(0.94, 0.89, 0.26, 1.0),
+        hazard_top: Color::new(0.84, 0.37, 0.0, 1.0),
+        hazard_bottom: Color::new(0.3, 0.1, 0.0, 1.0),
+        danger: Color::new(0.84, 0.37, 0.0, 1.0),
+        warning: Color::new(0.94, 0.89, 0.26, 1.0),
+        health_high: Color::new(0.0, 0.45, 0.7, 1.0),
+        health_mid: Color::new(0.94, 0.89, 0.26, 1.0),
+        health_low: Color::new(0.84, 0.37, 0.0, 1.0),
+    };
+
+    /// Like deuteranopia, but keeps danger bright since reds look dark
+    pub const PROTANOPIA: Palette = Palette {
+        player: Color::new(0.94, 0.89, 0.26, 1.0),
+        ground: Color::new(0.4, 0.33, 0.25, 1.0),
+        platform: Color::new(0.0, 0.45, 0.7, 1.0),
+        breakable_platform: Color::new(0.8, 0.47, 0.65, 1.0),
+        moving_platform: Color::new(0.34, 0.71, 0.91, 1.0),
+        grass: Color::new(0.34, 0.71, 0.91, 1.0),
+        coin: Color::new(0.9, 0.6, 0.0, 1.0),
+        gem: Color::new(0.8, 0.47, 0.65, 1.0),
+        powerup: WHITE,
+        lantern: Color::new(0.94, 0.89, 0.26, 1.0),
+        hazard_top: Color::new(0.9, 0.6, 0.0, 1.0),
+        hazard_bottom: Color::new(0.35, 0.2, 0.0, 1.0),
+        danger: Color::new(0.9, 0.6, 0.0, 1.0),
+        warning: Color::new(0.94, 0.89, 0.26, 1.0),
+        health_high: Color::new(0.34, 0.71, 0.91, 1.0),
+        health_mid: Color::new(0.94, 0.89, 0.26, 1.0),
+        health_low: Color::new(0.8, 0.47, 0.65, 1.0),
+    };
+
+    /// Red/teal contrasts in place of blue/yellow
+    pub const TRITANOPIA: Palette = Palette {
+        player: Color::new(0.85, 0.1, 0.3, 1.0),
+        ground: Color::new(0.35, 0.3, 0.3, 1.0),
+        platform: Color::new(0.0, 0.6, 0.6, 1.0),
+        breakable_platform: Color::new(1.0, 0.6, 0.75, 1.0),
+        moving_platform: Color::new(0.5, 0.85, 0.85, 1.0),
+        grass: Color::new(0.0, 0.6, 0.6, 1.0),
+        coin: Color::new(1.0, 0.6, 0.75, 1.0),
+        gem: Color::new(0.6, 0.0, 0.2, 1.0),
+        powerup: WHITE,
+        lantern: Color::new(1.0, 0.75, 0.8, 1.0),
+        hazard_top: Color::new(0.9, 0.1, 0.1, 1.0),
+        hazard_bottom: Color::new(0.35, 0.0, 0.05, 1.0),
+        danger: Color::new(0.9, 0.1, 0.1, 1.0),
+        warning: Color::new(1.0, 0.6, 0.75, 1.0),
+        health_high: Color::new(0.0, 0.6, 0.6, 1.0),
+        health_mid: Color::new(1.0, 0.6, 0.75, 1.0),
+        health_low: Color::new(0.9, 0.1, 0.1, 1.0),
+    };
+
+    pub fn platform_color(&self, platform_type: &PlatformType) -> Color {
+        match platform_type {
+            PlatformType::Ground => self.ground,
+            PlatformType::Normal => self.platform,
+            PlatformType::Breakable => self.breakable_platform,
+            PlatformType::Moving => self.moving_platform,
+        }
+    }
+
+    pub fn collectible_color(&self, collectible_type: &CollectibleType) -> Color {
+        match collectible_type {
+            CollectibleType::Coin => self.coin,
+            CollectibleType::Gem => self.gem,
+            CollectibleType::PowerUp => self.powerup,
+            CollectibleType::Lantern => self.lantern,
+        }
+    }
+
+    /// Health bar fill for a 0..1 health fraction
+    pub fn health_color(&self, fraction: f32) -> Color {
+        if fraction > 0.6 {
+            self.health_high
+        } else if fraction > 0.3 {
+            self.health_mid
+        } else {
+            self.health_low
+        }
+    }
+}
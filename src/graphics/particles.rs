@@ -0,0 +1,141 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::rng::SeededRng;
+
+use super::colors;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Seconds left before it disappears
+    pub life: f32,
+    pub max_life: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// Short-lived world-space particles such as landing dust, capped at `MAX_PARTICLES`
+/// unless a stress test raises the limit
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    limit: usize,
+    rng: SeededRng,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            limit: GameConfig::MAX_PARTICLES,
+            rng: SeededRng::new(0x5EED),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Most particles alive at once; going back down drops the oldest extras
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        let excess = self.particles.len().saturating_sub(limit);
+        self.particles.drain(..excess);
+    }
+
+    /// Puffs thrown out sideways and up from a point on the ground
+    pub fn emit_dust(&mut self, origin: Vec2, count: usize, speed: f32, color: Color) {
+        for _ in 0..count {
+            if self.particles.len() >= self.limit {
+                break;
+            }
+            let side = if self.rng.chance(0.5) { -1.0 } else { 1.0 };
+            let velocity = Vec2::new(
+                side * self.rng.range(0.4, 1.0) * speed,
+                -self.rng.range(0.1, 0.4) * speed,
+            );
+            let life = self.rng.range(0.25, 0.45);
+            self.particles.push(Particle {
+                position: origin + Vec2::new(self.rng.range(-6.0, 6.0), 0.0),
+                velocity,
+                life,
+                max_life: life,
+                radius: self.rng.range(2.0, 4.0),
+                color,
+            });
+        }
+    }
+
+    /// Small specks of foam kicked up where something breaks a water surface at `origin`
+    pub fn emit_foam(&mut self, origin: Vec2, count: usize, color: Color) {
+        for _ in 0..count {
+            if self.particles.len() >= self.limit {
+                break;
+            }
+            let life = self.rng.range(0.3, 0.6);
+            self.particles.push(Particle {
+                position: origin + Vec2::new(self.rng.range(-10.0, 10.0), 0.0),
+                velocity: Vec2::new(self.rng.range(-40.0, 40.0), -self.rng.range(10.0, 50.0)),
+                life,
+                max_life: life,
+                radius: self.rng.range(1.0, 2.0),
+                color,
+            });
+        }
+    }
+
+    /// Throw `count` particles out evenly in every direction, as from a blast
+    pub fn emit_ring(&mut self, origin: Vec2, count: usize, speed: f32, color: Color) {
+        for i in 0..count {
+            if self.particles.len() >= self.limit {
+                break;
+            }
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            let life = self.rng.range(0.35, 0.55);
+            self.particles.push(Particle {
+                position: origin,
+                velocity: Vec2::from_angle(angle) * speed * self.rng.range(0.8, 1.0),
+                life,
+                max_life: life,
+                radius: self.rng.range(3.0, 5.0),
+                color,
+            });
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for particle in &mut self.particles {
+            particle.position += particle.velocity * delta_time;
+            // Dust drags to a stop rather than falling
+            particle.velocity *= 1.0 - (GameConfig::PARTICLE_DRAG * delta_time).min(1.0);
+            particle.life -= delta_time;
+        }
+        self.particles.retain(|particle| particle.life > 0.0);
+    }
+
+    pub fn render(&self, camera_x: f32, camera_y: f32) {
+        for particle in &self.particles {
+            let fade = particle.life / particle.max_life;
+            // Puffs grow as they fade out
+            let radius = particle.radius * (2.0 - fade);
+            draw_circle(
+                particle.position.x + camera_x,
+                particle.position.y + camera_y,
+                radius,
+                colors::with_alpha(particle.color, particle.color.a * fade),
+            );
+        }
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
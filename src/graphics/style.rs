@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+use std::cell::Cell;
+
+use super::palette::{Palette, PaletteKind};
+use crate::config::GameConfig;
+
+/// Accessibility options that change how the frame is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStyle {
+    /// Hold decorative animation at rest
+    pub reduced_motion: bool,
+    /// Swap entity colors for bold, outlined high-contrast ones
+    pub high_contrast: bool,
+    pub palette: PaletteKind,
+}
+
+thread_local! {
+    static CURRENT_STYLE: Cell<RenderStyle> = Cell::new(RenderStyle::default());
+}
+
+impl RenderStyle {
+    /// Style used by everything drawn this frame
+    pub fn current() -> Self {
+        CURRENT_STYLE.with(Cell::get)
+    }
+
+    pub fn apply(self) {
+        CURRENT_STYLE.with(|style| style.set(self));
+    }
+
+    pub fn palette(&self) -> &'static Palette {
+        self.palette.palette()
+    }
+
+    /// Clock for decorative animation; stands still under reduced motion
+    pub fn animation_time(&self, time: f32) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            time
+        }
+    }
+
+    /// Pick the high-contrast color when that mode is on
+    pub fn pick(&self, normal: Color, high_contrast: Color) -> Color {
+        if self.high_contrast {
+            high_contrast
+        } else {
+            normal
+        }
+    }
+
+    /// Thick dark outline around a rectangle in high-contrast mode
+    pub fn outline_rectangle(&self, x: f32, y: f32, width: f32, height: f32) {
+        if self.high_contrast {
+            draw_rectangle_lines(
+                x,
+                y,
+                width,
+                height,
+                GameConfig::HIGH_CONTRAST_OUTLINE_WIDTH,
+                GameConfig::HIGH_CONTRAST_OUTLINE,
+            );
+        }
+    }
+
+    /// Thick dark outline around a circle in high-contrast mode
+    pub fn outline_circle(&self, x: f32, y: f32, radius: f32) {
+        if self.high_contrast {
+            draw_circle_lines(
+                x,
+                y,
+                radius,
+                GameConfig::HIGH_CONTRAST_OUTLINE_WIDTH,
+                GameConfig::HIGH_CONTRAST_OUTLINE,
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use macroquad::prelude::*;
+
+use std::cell::RefCell;
+
+use crate::config::GameConfig;
+
+/// Font all UI text is drawn in; without it the built-in font is used, which only covers
+/// ASCII, so languages with accented letters need a UI font that has them
+pub const UI_FONT: &str = "assets/fonts/ui.ttf";
+
+thread_local! {
+    static CURRENT_FONT: RefCell<Option<Font>> = const { RefCell::new(None) };
+}
+
+/// Use `font` for all text from now on, or the built-in font for `None`
+pub fn set_font(font: Option<Font>) {
+    CURRENT_FONT.with(|current| *current.borrow_mut() = font);
+}
+
+/// Call `f` with the font text is drawn in, `None` meaning the built-in one
+pub fn with_font<T>(f: impl FnOnce(Option<&Font>) -> T) -> T {
+    CURRENT_FONT.with(|current| f(current.borrow().as_ref()))
+}
+
+/// Text sizes used around the game, smallest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextStyle {
+    /// Labels on things in the world, e.g. zone names in debug views
+    Label,
+    HudSmall,
+    /// Hints and secondary lines under menu entries
+    Caption,
+    Hud,
+    /// Lines on full-screen panels such as statistics and the leaderboard
+    Body,
+    HudLarge,
+    Heading,
+    Title,
+    /// The game's name on the title and loading screens
+    Logo,
+}
+
+impl TextStyle {
+    pub fn size(&self) -> f32 {
+        match self {
+            TextStyle::Label => GameConfig::UI_LABEL_FONT_SIZE,
+            TextStyle::HudSmall => GameConfig::UI_SMALL_FONT_SIZE,
+            TextStyle::Caption => GameConfig::UI_CAPTION_FONT_SIZE,
+            TextStyle::Hud => GameConfig::UI_FONT_SIZE,
+            TextStyle::Body => GameConfig::UI_BODY_FONT_SIZE,
+            TextStyle::HudLarge => GameConfig::UI_HUD_LARGE_FONT_SIZE,
+            TextStyle::Heading => GameConfig::UI_HEADING_FONT_SIZE,
+            TextStyle::Title => GameConfig::UI_LARGE_FONT_SIZE,
+            TextStyle::Logo => GameConfig::UI_LOGO_FONT_SIZE,
+        }
+    }
+}
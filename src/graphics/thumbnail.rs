@@ -0,0 +1,140 @@
+use macroquad::prelude::*;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{PaletteKind, RenderStyle};
+use crate::config::GameConfig;
+use crate::entities::platform::PlatformType;
+use crate::level::catalog::LevelEntry;
+use crate::level::Level;
+
+/// A small picture of a level's layout: platforms and terrain in their palette colors
+/// over the sky, with the spawn point marked, scaled to fit `width` by `height`
+pub fn layout_image(level: &Level, width: u16, height: u16) -> Image {
+    let palette = RenderStyle::current().palette();
+    let mut image = Image::gen_image_color(width, height, GameConfig::THUMBNAIL_SKY_COLOR);
+
+    let mut rects: Vec<(Rect, Color)> = level
+        .platforms
+        .iter()
+        .map(|def| {
+            let color = match def.platform_type {
+                PlatformType::Ground => palette.ground,
+                PlatformType::Normal => palette.platform,
+                PlatformType::Breakable => palette.breakable_platform,
+                PlatformType::Moving => palette.moving_platform,
+            };
+            (
+                Rect::new(def.position.x, def.position.y, def.size.x, def.size.y),
+                color,
+            )
+        })
+        .collect();
+    rects.extend(level.terrain.iter().map(|def| {
+        (
+            Rect::new(def.position.x, def.position.y, def.size.x, def.size.y),
+            GameConfig::TERRAIN_COLOR,
+        )
+    }));
+
+    let spawn = Rect::new(level.spawn.x, level.spawn.y, 1.0, 1.0);
+    let bounds = rects
+        .iter()
+        .fold(spawn, |bounds, (rect, _)| bounds.combine_with(*rect));
+    let margin = GameConfig::THUMBNAIL_MARGIN;
+    let scale = ((width as f32 - margin * 2.0) / bounds.w.max(1.0))
+        .min((height as f32 - margin * 2.0) / bounds.h.max(1.0));
+    // Centered, and sitting on the bottom edge like the level sits on its floor
+    let offset = Vec2::new(
+        (width as f32 - bounds.w * scale) / 2.0,
+        height as f32 - margin - bounds.h * scale,
+    ) - bounds.point() * scale;
+    let to_image = |point: Vec2| point * scale + offset;
+
+    for (rect, color) in &rects {
+        let top_left = to_image(rect.point());
+        // Even the thinnest ledge gets a pixel
+        let size = (rect.size() * scale).max(Vec2::ONE);
+        fill(&mut image, top_left, size, *color);
+    }
+    let spawn = to_image(level.spawn);
+    fill(
+        &mut image,
+        spawn - Vec2::new(1.0, 3.0),
+        Vec2::new(3.0, 3.0),
+        palette.player,
+    );
+
+    image
+}
+
+fn fill(image: &mut Image, top_left: Vec2, size: Vec2, color: Color) {
+    let x1 = top_left.x.round().max(0.0) as u32;
+    let y1 = top_left.y.round().max(0.0) as u32;
+    let x2 = ((top_left.x + size.x).round() as u32).min(image.width as u32);
+    let y2 = ((top_left.y + size.y).round() as u32).min(image.height as u32);
+    for y in y1..y2.max(y1 + 1).min(image.height as u32) {
+        for x in x1..x2.max(x1 + 1).min(image.width as u32) {
+            image.set_pixel(x, y, color);
+        }
+    }
+}
+
+struct Thumbnail {
+    /// When the level file was written when this was drawn
+    modified: Option<SystemTime>,
+    palette: PaletteKind,
+    texture: Texture2D,
+}
+
+/// Level thumbnails, each drawn once and kept until its file changes or the palette does
+#[derive(Default)]
+pub struct ThumbnailCache {
+    thumbnails: HashMap<PathBuf, Thumbnail>,
+}
+
+impl ThumbnailCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw thumbnails for any of `entries` that don't have an up-to-date one yet
+    pub fn refresh(&mut self, entries: &[LevelEntry]) {
+        let palette = RenderStyle::current().palette;
+        for entry in entries {
+            let Ok(level) = &entry.level else {
+                self.thumbnails.remove(&entry.path);
+                continue;
+            };
+            let current = self.thumbnails.get(&entry.path).is_some_and(|thumbnail| {
+                thumbnail.modified == entry.modified && thumbnail.palette == palette
+            });
+            if current {
+                continue;
+            }
+            let (width, height) = GameConfig::THUMBNAIL_SIZE;
+            let texture = Texture2D::from_image(&layout_image(level, width, height));
+            texture.set_filter(FilterMode::Nearest);
+            self.thumbnails.insert(
+                entry.path.clone(),
+                Thumbnail {
+                    modified: entry.modified,
+                    palette,
+                    texture,
+                },
+            );
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Texture2D> {
+        self.thumbnails
+            .get(path)
+            .map(|thumbnail| &thumbnail.texture)
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        self.thumbnails.remove(path);
+    }
+}
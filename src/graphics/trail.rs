@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use super::{colors, RenderStyle};
+
+/// How a trail is tinted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailColor {
+    Solid(Color),
+    /// Cycles through the hues along its length, this many times a second
+    Rainbow(f32),
+}
+
+/// How often a trail samples, how long it is and how it tapers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailConfig {
+    /// Seconds between samples
+    pub sample_interval: f32,
+    /// Samples kept; the oldest fades out as a new one comes in
+    pub max_points: usize,
+    /// Diameter at the newest sample
+    pub width: f32,
+    /// Fraction of `width` left at the oldest sample
+    pub width_falloff: f32,
+    pub color: TrailColor,
+    /// A sample further than this from the last one is a teleport, which starts the
+    /// trail over rather than streaking across the level
+    pub break_distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrailPoint {
+    position: Vec2,
+    /// Seconds since it was sampled
+    age: f32,
+    /// Trail clock when it was sampled, so rainbow hues stay put on each point
+    stamp: f32,
+}
+
+/// A fading, narrowing line of dots left behind a moving thing in world space
+#[derive(Debug, Clone)]
+pub struct TrailRenderer {
+    pub config: TrailConfig,
+    points: VecDeque<TrailPoint>,
+    since_sample: f32,
+    clock: f32,
+}
+
+impl TrailRenderer {
+    pub fn new(config: TrailConfig) -> Self {
+        Self {
+            config,
+            points: VecDeque::with_capacity(config.max_points),
+            since_sample: 0.0,
+            clock: 0.0,
+        }
+    }
+
+    /// Forget every sample, e.g. after a teleport
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.since_sample = 0.0;
+    }
+
+    /// Age the samples and, while `position` is given, take a new one when due. With no
+    /// position the trail just fades away behind whatever stopped leaving it.
+    pub fn update(&mut self, delta_time: f32, position: Option<Vec2>) {
+        self.clock += delta_time;
+        let lifetime = self.lifetime();
+        for point in &mut self.points {
+            point.age += delta_time;
+        }
+        while self
+            .points
+            .front()
+            .is_some_and(|point| point.age >= lifetime)
+        {
+            self.points.pop_front();
+        }
+
+        let Some(position) = position else {
+            self.since_sample = 0.0;
+            return;
+        };
+        self.since_sample -= delta_time;
+        if self.since_sample > 0.0 {
+            return;
+        }
+        self.since_sample = self.config.sample_interval;
+
+        let jumped = self
+            .points
+            .back()
+            .is_some_and(|last| last.position.distance(position) > self.config.break_distance);
+        if jumped {
+            self.points.clear();
+        }
+        if self.points.len() >= self.config.max_points {
+            self.points.pop_front();
+        }
+        self.points.push_back(TrailPoint {
+            position,
+            age: 0.0,
+            stamp: self.clock,
+        });
+    }
+
+    /// Seconds a sample lasts before it has completely faded
+    fn lifetime(&self) -> f32 {
+        self.config.sample_interval * self.config.max_points as f32
+    }
+
+    pub fn render(&self, camera_x: f32, camera_y: f32) {
+        self.render_with(camera_x, camera_y, self.config.color);
+    }
+
+    /// Render in a color picked at draw time, such as one from the current palette
+    pub fn render_with(&self, camera_x: f32, camera_y: f32, color: TrailColor) {
+        if RenderStyle::current().reduced_motion {
+            return;
+        }
+        let lifetime = self.lifetime();
+        let falloff = self.config.width_falloff;
+        for point in &self.points {
+            // 1 when fresh, 0 when about to disappear
+            let freshness = (1.0 - point.age / lifetime).clamp(0.0, 1.0);
+            let radius = self.config.width / 2.0 * (falloff + (1.0 - falloff) * freshness);
+            let color = match color {
+                TrailColor::Solid(color) => color,
+                TrailColor::Rainbow(speed) => colors::rainbow_color(point.stamp, speed),
+            };
+            draw_circle(
+                point.position.x + camera_x,
+                point.position.y + camera_y,
+                radius,
+                colors::with_alpha(color, color.a * freshness * 0.6),
+            );
+        }
+    }
+}
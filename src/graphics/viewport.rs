@@ -0,0 +1,103 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+
+/// Renders the game at a fixed virtual resolution and scales it into the window,
+/// adding letterbox or pillarbox bars when the aspect ratios differ
+pub struct Viewport {
+    target: RenderTarget,
+    camera: Camera2D,
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        let target = render_target(
+            GameConfig::VIRTUAL_WIDTH as u32,
+            GameConfig::VIRTUAL_HEIGHT as u32,
+        );
+        target.texture.set_filter(FilterMode::Linear);
+
+        let mut camera = Camera2D::from_display_rect(Rect::new(
+            0.0,
+            0.0,
+            GameConfig::VIRTUAL_WIDTH,
+            GameConfig::VIRTUAL_HEIGHT,
+        ));
+        camera.render_target = Some(target.clone());
+
+        Self { target, camera }
+    }
+
+    /// Start drawing in virtual coordinates
+    pub fn begin(&self) {
+        set_camera(&self.camera);
+    }
+
+    /// Start drawing in virtual coordinates magnified around the screen center
+    pub fn begin_zoomed(&self, zoom: f32) {
+        let width = GameConfig::VIRTUAL_WIDTH / zoom;
+        let height = GameConfig::VIRTUAL_HEIGHT / zoom;
+        let mut camera = Camera2D::from_display_rect(Rect::new(
+            (GameConfig::VIRTUAL_WIDTH - width) / 2.0,
+            (GameConfig::VIRTUAL_HEIGHT - height) / 2.0,
+            width,
+            height,
+        ));
+        camera.render_target = Some(self.target.clone());
+        set_camera(&camera);
+    }
+
+    /// The virtual frame as drawn so far, stored bottom row first
+    pub fn texture(&self) -> &Texture2D {
+        &self.target.texture
+    }
+
+    /// Draw the finished virtual frame into the window
+    pub fn present(&self) {
+        set_default_camera();
+        clear_background(BLACK);
+
+        let offset = Self::offset();
+        let scale = Self::scale();
+        draw_texture_ex(
+            &self.target.texture,
+            offset.x,
+            offset.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(Vec2::new(
+                    GameConfig::VIRTUAL_WIDTH * scale,
+                    GameConfig::VIRTUAL_HEIGHT * scale,
+                )),
+                // Render targets come out upside down
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// How many window pixels one virtual pixel covers
+    pub fn scale() -> f32 {
+        (screen_width() / GameConfig::VIRTUAL_WIDTH)
+            .min(screen_height() / GameConfig::VIRTUAL_HEIGHT)
+    }
+
+    /// Window position of the virtual screen's top-left corner
+    pub fn offset() -> Vec2 {
+        let scale = Self::scale();
+        Vec2::new(
+            (screen_width() - GameConfig::VIRTUAL_WIDTH * scale) / 2.0,
+            (screen_height() - GameConfig::VIRTUAL_HEIGHT * scale) / 2.0,
+        )
+    }
+
+    /// Map a window position into virtual coordinates
+    pub fn to_virtual(window_position: Vec2) -> Vec2 {
+        (window_position - Self::offset()) / Self::scale()
+    }
+
+    /// Mouse position in virtual coordinates
+    pub fn mouse_position() -> Vec2 {
+        Self::to_virtual(Vec2::from(mouse_position()))
+    }
+}
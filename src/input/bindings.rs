@@ -0,0 +1,375 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::locale::tr;
+
+/// Something the player does during a run, done with whichever keys are bound to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Left,
+    Right,
+    /// Climbs ropes
+    Up,
+    /// Drops from ledges and ziplines
+    Down,
+    Jump,
+    Sprint,
+    Dash,
+    Attack,
+    /// Starts a conversation with someone nearby
+    Talk,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Jump,
+        Action::Sprint,
+        Action::Dash,
+        Action::Attack,
+        Action::Talk,
+    ];
+
+    /// Name in settings files, after `bind_`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Jump => "jump",
+            Action::Sprint => "sprint",
+            Action::Dash => "dash",
+            Action::Attack => "attack",
+            Action::Talk => "talk",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr(&format!("action.{}", self.name()))
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|action| action == self)
+            .unwrap_or(0)
+    }
+}
+
+/// A ready-made set of key bindings, picked on the settings screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ControlPreset {
+    /// WASD or the arrows, Space to jump
+    #[default]
+    Default,
+    /// Everything on the right side of the keyboard: the arrows to move and the right-hand
+    /// modifier keys for the rest
+    LeftHanded,
+    /// The player runs right by themselves and Space does everything: tap to jump, hold
+    /// to jump higher, tap twice quickly to turn around
+    OneButton,
+    /// The keys picked on the controls screen
+    Custom,
+}
+
+impl ControlPreset {
+    pub const ALL: [ControlPreset; 4] = [
+        ControlPreset::Default,
+        ControlPreset::LeftHanded,
+        ControlPreset::OneButton,
+        ControlPreset::Custom,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlPreset::Default => "default",
+            ControlPreset::LeftHanded => "left_handed",
+            ControlPreset::OneButton => "one_button",
+            ControlPreset::Custom => "custom",
+        }
+    }
+
+    /// Name shown in menus
+    pub fn label(&self) -> String {
+        tr(&format!("controls.{}", self.name()))
+    }
+
+    /// The preset after this one, wrapping around
+    pub fn next(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|preset| preset == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The preset before this one, wrapping around
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|preset| preset == self)
+            .unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the player runs by themselves
+    pub fn auto_runs(&self) -> bool {
+        *self == ControlPreset::OneButton
+    }
+}
+
+/// Keys that can be bound to an action. Escape is left out, as it always pauses and
+/// backs out of screens, and so are P, R and M, which pause, restart and mute during play.
+pub const BINDABLE_KEYS: [KeyCode; 59] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::Q,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::Backslash,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Insert,
+    KeyCode::Home,
+];
+
+/// Name of a key in settings files and on the controls screen
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// A bindable key by its `key_name`
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name))
+}
+
+/// Which keys do each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: [Vec<KeyCode>; Action::ALL.len()],
+}
+
+impl KeyBindings {
+    /// The bindings a preset stands for. Custom starts out as the defaults.
+    pub fn preset(preset: ControlPreset) -> Self {
+        use KeyCode::*;
+        let keys = |action: Action| -> Vec<KeyCode> {
+            match (preset, action) {
+                (ControlPreset::OneButton, Action::Jump) => vec![Space],
+                (ControlPreset::OneButton, _) => Vec::new(),
+                (ControlPreset::LeftHanded, Action::Left) => vec![Left],
+                (ControlPreset::LeftHanded, Action::Right) => vec![Right],
+                (ControlPreset::LeftHanded, Action::Up) => vec![Up],
+                (ControlPreset::LeftHanded, Action::Down) => vec![Down],
+                (ControlPreset::LeftHanded, Action::Jump) => vec![Up, RightControl],
+                (ControlPreset::LeftHanded, Action::Sprint) => vec![RightShift],
+                (ControlPreset::LeftHanded, Action::Dash) => vec![RightAlt],
+                (ControlPreset::LeftHanded, Action::Attack) => vec![Slash],
+                (ControlPreset::LeftHanded, Action::Talk) => vec![Enter],
+                (_, Action::Left) => vec![A, Left],
+                (_, Action::Right) => vec![D, Right],
+                (_, Action::Up) => vec![W, Up],
+                (_, Action::Down) => vec![S, Down],
+                (_, Action::Jump) => vec![Space, W, Up],
+                (_, Action::Sprint) => vec![GameConfig::SPRINT_KEY],
+                (_, Action::Dash) => vec![GameConfig::DASH_KEY],
+                (_, Action::Attack) => vec![GameConfig::ATTACK_KEY],
+                (_, Action::Talk) => vec![Enter],
+            }
+        };
+        Self {
+            keys: Action::ALL.map(keys),
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        &self.keys[action.index()]
+    }
+
+    /// Make `key` the only key for `action`. Another action that had `key` takes one of
+    /// `action`'s old keys in its place, or goes without if it already has that one too.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        let index = action.index();
+        let previous = std::mem::replace(&mut self.keys[index], vec![key]);
+        let swapped = previous.into_iter().find(|old| *old != key);
+        for (other, keys) in self.keys.iter_mut().enumerate() {
+            if other == index {
+                continue;
+            }
+            let Some(position) = keys.iter().position(|bound| *bound == key) else {
+                continue;
+            };
+            match swapped.filter(|old| !keys.contains(old)) {
+                Some(old) => keys[position] = old,
+                None => {
+                    keys.remove(position);
+                }
+            }
+        }
+    }
+
+    pub fn set_keys(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.keys[action.index()] = keys;
+    }
+
+    /// The keys for `action` as a settings value or screen text, e.g. "Space, W, Up"
+    pub fn describe(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .map(|key| key_name(*key))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Read a `describe` list back; names that aren't bindable keys are skipped
+    pub fn parse_keys(value: &str) -> Vec<KeyCode> {
+        value
+            .split(',')
+            .filter_map(|name| key_from_name(name.trim()))
+            .collect()
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::preset(ControlPreset::Default)
+    }
+}
+
+/// The one-button preset's running: which way the player is heading, and when the button
+/// was last tapped, to catch a quick second tap
+#[derive(Debug, Clone, Copy)]
+pub struct AutoRun {
+    /// -1 for left, 1 for right
+    pub direction: f32,
+    last_tap: Option<f64>,
+    /// Whether the last jump came from the button and it's still being held
+    pub holding_jump: bool,
+}
+
+impl AutoRun {
+    pub fn new() -> Self {
+        Self {
+            direction: 1.0,
+            last_tap: None,
+            holding_jump: false,
+        }
+    }
+
+    /// Count a press of the button at `time` seconds. A second press soon after the first
+    /// turns the player around and returns true, in which case it doesn't jump.
+    pub fn tap(&mut self, time: f64) -> bool {
+        let double = self
+            .last_tap
+            .is_some_and(|last| time - last <= GameConfig::ONE_BUTTON_DOUBLE_TAP as f64);
+        if double {
+            self.direction = -self.direction;
+            self.last_tap = None;
+        } else {
+            self.last_tap = Some(time);
+        }
+        double
+    }
+}
+
+impl Default for AutoRun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_keys_cant_be_bound() {
+        for name in ["P", "R", "M", "Escape"] {
+            assert_eq!(key_from_name(name), None, "{name} should stay with play");
+        }
+    }
+
+    #[test]
+    fn binding_a_key_in_use_swaps_it_with_the_other_action() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Left, KeyCode::D);
+        assert_eq!(bindings.keys(Action::Left), [KeyCode::D]);
+        assert_eq!(bindings.keys(Action::Right), [KeyCode::A, KeyCode::Right]);
+    }
+
+    #[test]
+    fn a_key_the_other_action_can_spare_is_just_taken() {
+        let mut bindings = KeyBindings::default();
+        // Jump already has W, Up's old first key, so it only gives up Space
+        bindings.bind(Action::Up, KeyCode::Space);
+        assert_eq!(bindings.keys(Action::Up), [KeyCode::Space]);
+        assert_eq!(bindings.keys(Action::Jump), [KeyCode::W, KeyCode::Up]);
+        for action in Action::ALL
+            .into_iter()
+            .filter(|action| *action != Action::Up)
+        {
+            assert!(!bindings.keys(action).contains(&KeyCode::Space));
+        }
+    }
+}
@@ -1,10 +1,24 @@
 use macroquad::prelude::*;
 
+pub mod bindings;
+pub mod rumble;
+pub mod touch;
+
+pub use bindings::{Action, AutoRun, ControlPreset, KeyBindings};
+pub use rumble::{NoRumble, Rumble};
+pub use touch::{TouchControls, TouchLayout};
+
 pub struct InputHandler {
     // Current frame key states
     current_keys: Vec<KeyCode>,
     // Previous frame key states
     previous_keys: Vec<KeyCode>,
+    // Text typed this frame
+    typed: String,
+    /// On-screen buttons, read as keys alongside the keyboard
+    pub touch: TouchControls,
+    /// Which keys do what during play, from the chosen control preset
+    pub bindings: KeyBindings,
 }
 
 impl InputHandler {
@@ -12,6 +26,9 @@ impl InputHandler {
         Self {
             current_keys: Vec::new(),
             previous_keys: Vec::new(),
+            typed: String::new(),
+            touch: TouchControls::new(),
+            bindings: KeyBindings::default(),
         }
     }
 
@@ -35,15 +52,95 @@ impl InputHandler {
             KeyCode::Down,
             KeyCode::Enter,
             KeyCode::Escape,
+            KeyCode::Tab,
             KeyCode::R,
+            KeyCode::Y,
+            KeyCode::N,
+            KeyCode::I,
+            KeyCode::J,
+            KeyCode::K,
+            KeyCode::L,
+            KeyCode::M,
+            KeyCode::Equal,
+            KeyCode::Minus,
+            KeyCode::KpAdd,
+            KeyCode::KpSubtract,
             KeyCode::P,
+            KeyCode::F1,
+            KeyCode::F2,
+            KeyCode::F3,
+            KeyCode::F4,
+            KeyCode::F5,
+            KeyCode::F6,
+            KeyCode::F7,
+            KeyCode::Period,
+            KeyCode::F8,
+            KeyCode::F9,
+            KeyCode::F10,
+            KeyCode::F11,
+            KeyCode::F12,
+            KeyCode::LeftShift,
+            KeyCode::RightShift,
+            KeyCode::Z,
+            KeyCode::C,
+            KeyCode::V,
+            KeyCode::G,
+            KeyCode::LeftBracket,
+            KeyCode::RightBracket,
+            KeyCode::LeftAlt,
+            KeyCode::RightAlt,
+            KeyCode::Key0,
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+            KeyCode::Delete,
+            KeyCode::Backspace,
+            KeyCode::GraveAccent,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::LeftControl,
+            KeyCode::RightControl,
         ];
 
-        for key in keys_to_check {
-            if is_key_down(key) {
+        // Anything that can be bound is watched too, whatever the preset
+        for key in keys_to_check.into_iter().chain(bindings::BINDABLE_KEYS) {
+            if is_key_down(key) && !self.current_keys.contains(&key) {
                 self.current_keys.push(key);
             }
         }
+
+        self.touch.update();
+        for key in self.touch.keys(&self.bindings) {
+            if !self.current_keys.contains(&key) {
+                self.current_keys.push(key);
+            }
+        }
+
+        // Read every frame, so characters typed while nothing wants text don't pile up
+        self.typed.clear();
+        while let Some(character) = get_char_pressed() {
+            if !character.is_control() {
+                self.typed.push(character);
+            }
+        }
+    }
+
+    /// Move on a frame with exactly `keys` held, in place of reading the keyboard
+    #[cfg(test)]
+    pub fn hold_keys(&mut self, keys: &[KeyCode]) {
+        self.previous_keys = std::mem::take(&mut self.current_keys);
+        self.current_keys = keys.to_vec();
+    }
+
+    /// Printable characters typed since the last update, for text fields
+    pub fn typed(&self) -> &str {
+        &self.typed
     }
 
     /// Check if a key is currently being held down
@@ -56,9 +153,31 @@ impl InputHandler {
         self.current_keys.contains(&key) && !self.previous_keys.contains(&key)
     }
 
-    /// Check if a key was just released this frame
-    pub fn is_key_released(&self, key: KeyCode) -> bool {
-        !self.current_keys.contains(&key) && self.previous_keys.contains(&key)
+    /// Check if any key bound to an action is held down
+    pub fn is_down(&self, action: Action) -> bool {
+        self.bindings
+            .keys(action)
+            .iter()
+            .any(|key| self.is_key_down(*key))
+    }
+
+    /// Check if a key bound to an action was just pressed, and none were held before
+    pub fn is_pressed(&self, action: Action) -> bool {
+        let keys = self.bindings.keys(action);
+        keys.iter().any(|key| self.is_key_pressed(*key))
+            && !keys.iter().any(|key| self.previous_keys.contains(key))
+    }
+
+    /// Movement between two opposing actions (-1, 1, or 0 for neither or both)
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        let mut axis = 0.0;
+        if self.is_down(negative) {
+            axis -= 1.0;
+        }
+        if self.is_down(positive) {
+            axis += 1.0;
+        }
+        axis
     }
 
     /// Get the horizontal movement input (-1 for left, 1 for right, 0 for none)
@@ -91,11 +210,14 @@ impl InputHandler {
         vertical
     }
 
-    /// Check if the jump key was pressed
+    /// Check if a jump key was pressed
     pub fn is_jump_pressed(&self) -> bool {
-        self.is_key_pressed(KeyCode::Space)
-            || self.is_key_pressed(KeyCode::W)
-            || self.is_key_pressed(KeyCode::Up)
+        self.is_pressed(Action::Jump)
+    }
+
+    /// Check if a jump key is being held down
+    pub fn is_jump_held(&self) -> bool {
+        self.is_down(Action::Jump)
     }
 
     /// Check if the action key was pressed (for interacting with objects)
@@ -108,30 +230,10 @@ impl InputHandler {
         self.is_key_pressed(KeyCode::P) || self.is_key_pressed(KeyCode::Escape)
     }
 
-    /// Check if the reset key was pressed
-    pub fn is_reset_pressed(&self) -> bool {
-        self.is_key_pressed(KeyCode::R)
-    }
-
-    /// Get all currently pressed keys (for debugging)
-    pub fn get_pressed_keys(&self) -> &Vec<KeyCode> {
-        &self.current_keys
-    }
-
     /// Check if any key is pressed
     pub fn any_key_pressed(&self) -> bool {
         !self.current_keys.is_empty()
     }
-
-    /// Check if any key was just pressed this frame
-    pub fn any_key_just_pressed(&self) -> bool {
-        for key in &self.current_keys {
-            if !self.previous_keys.contains(key) {
-                return true;
-            }
-        }
-        false
-    }
 }
 
 impl Default for InputHandler {
@@ -139,68 +241,3 @@ impl Default for InputHandler {
         Self::new()
     }
 }
-
-/// Helper functions for common input patterns
-pub mod helpers {
-    use super::*;
-
-    /// Check if movement keys are being held
-    pub fn is_moving(input: &InputHandler) -> bool {
-        input.get_horizontal_input() != 0.0 || input.get_vertical_input() != 0.0
-    }
-
-    /// Get normalized movement vector
-    pub fn get_movement_vector(input: &InputHandler) -> Vec2 {
-        let horizontal = input.get_horizontal_input();
-        let vertical = input.get_vertical_input();
-
-        let movement = Vec2::new(horizontal, vertical);
-
-        // Normalize diagonal movement
-        if movement.length() > 1.0 {
-            movement.normalize()
-        } else {
-            movement
-        }
-    }
-
-    /// Check if player wants to move left
-    pub fn wants_to_move_left(input: &InputHandler) -> bool {
-        input.is_key_down(KeyCode::A) || input.is_key_down(KeyCode::Left)
-    }
-
-    /// Check if player wants to move right
-    pub fn wants_to_move_right(input: &InputHandler) -> bool {
-        input.is_key_down(KeyCode::D) || input.is_key_down(KeyCode::Right)
-    }
-
-    /// Check if player just started moving left
-    pub fn just_started_moving_left(input: &InputHandler) -> bool {
-        (input.is_key_pressed(KeyCode::A) || input.is_key_pressed(KeyCode::Left))
-            && !wants_to_move_right(input)
-    }
-
-    /// Check if player just started moving right
-    pub fn just_started_moving_right(input: &InputHandler) -> bool {
-        (input.is_key_pressed(KeyCode::D) || input.is_key_pressed(KeyCode::Right))
-            && !wants_to_move_left(input)
-    }
-
-    /// Check if player just stopped moving horizontally
-    pub fn just_stopped_moving_horizontal(input: &InputHandler) -> bool {
-        input.get_horizontal_input() == 0.0 && {
-            let prev_horizontal = if input.previous_keys.contains(&KeyCode::A)
-                || input.previous_keys.contains(&KeyCode::Left)
-            {
-                -1.0
-            } else if input.previous_keys.contains(&KeyCode::D)
-                || input.previous_keys.contains(&KeyCode::Right)
-            {
-                1.0
-            } else {
-                0.0
-            };
-            prev_horizontal != 0.0
-        }
-    }
-}
@@ -0,0 +1,102 @@
+use crate::settings::Settings;
+
+/// Whatever actually shakes the controller
+pub trait RumbleMotor {
+    /// Run the motor at `strength`, 0 to stop it and 1 for full power
+    fn drive(&mut self, strength: f32);
+}
+
+/// Feels like nothing; used where there's no controller that can rumble. macroquad doesn't
+/// expose gamepads yet, so for now that's everywhere.
+pub struct NoRumble;
+
+impl RumbleMotor for NoRumble {
+    fn drive(&mut self, _strength: f32) {}
+}
+
+/// A short burst of rumble
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pulse {
+    strength: f32,
+    /// Seconds left
+    remaining: f32,
+}
+
+/// Controller feedback. Gameplay asks for pulses and steady hums without caring whether
+/// anything can rumble; overlapping requests mix by taking the strongest.
+pub struct Rumble {
+    motor: Box<dyn RumbleMotor>,
+    pulses: Vec<Pulse>,
+    /// Steady rumble asked for this step, e.g. while wall sliding
+    hum: f32,
+    /// Strength the motor was last driven at, after the intensity setting
+    strength: f32,
+}
+
+impl Rumble {
+    pub fn new(motor: Box<dyn RumbleMotor>) -> Self {
+        Self {
+            motor,
+            pulses: Vec::new(),
+            hum: 0.0,
+            strength: 0.0,
+        }
+    }
+
+    /// Rumble at `strength` (0 to 1) for `duration` seconds
+    pub fn pulse(&mut self, strength: f32, duration: f32) {
+        if strength > 0.0 && duration > 0.0 {
+            self.pulses.push(Pulse {
+                strength: strength.min(1.0),
+                remaining: duration,
+            });
+        }
+    }
+
+    /// Rumble at `strength` until the next update; ask again every step to keep it going
+    pub fn hum(&mut self, strength: f32) {
+        self.hum = self.hum.max(strength.min(1.0));
+    }
+
+    /// Stop everything at once, e.g. on pausing
+    pub fn stop(&mut self) {
+        self.pulses.clear();
+        self.hum = 0.0;
+        self.drive(0.0);
+    }
+
+    /// Drive the motor at the strongest pulse or hum, scaled by the intensity setting, then
+    /// age the pulses
+    pub fn update(&mut self, delta_time: f32, settings: &Settings) {
+        let mixed = self
+            .pulses
+            .iter()
+            .map(|pulse| pulse.strength)
+            .fold(self.hum, f32::max);
+        let strength = if settings.rumble {
+            mixed * settings.rumble_intensity
+        } else {
+            0.0
+        };
+        self.drive(strength);
+
+        for pulse in &mut self.pulses {
+            pulse.remaining -= delta_time;
+        }
+        self.pulses.retain(|pulse| pulse.remaining > 0.0);
+        self.hum = 0.0;
+    }
+
+    /// What the motor is running at, for the debug overlay
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    fn drive(&mut self, strength: f32) {
+        // Motors are only told about changes
+        if strength != self.strength {
+            self.strength = strength;
+            self.motor.drive(strength);
+        }
+    }
+}
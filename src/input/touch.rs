@@ -0,0 +1,354 @@
+use macroquad::prelude::*;
+
+use std::fmt;
+
+use super::{Action, KeyBindings};
+use crate::config::GameConfig;
+use crate::graphics::{GraphicsUtils, RenderStyle, Viewport};
+
+/// One of the on-screen buttons, each standing in for a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchButton {
+    Left,
+    Right,
+    Jump,
+    Pause,
+}
+
+impl TouchButton {
+    pub const ALL: [TouchButton; 4] = [
+        TouchButton::Left,
+        TouchButton::Right,
+        TouchButton::Jump,
+        TouchButton::Pause,
+    ];
+
+    /// The key the button holds down while it's touched: the first one bound to its
+    /// action, so the buttons follow the control preset
+    pub fn key(&self, bindings: &KeyBindings) -> KeyCode {
+        let (action, fallback) = match self {
+            TouchButton::Left => (Action::Left, KeyCode::Left),
+            TouchButton::Right => (Action::Right, KeyCode::Right),
+            TouchButton::Jump => (Action::Jump, KeyCode::Space),
+            TouchButton::Pause => return KeyCode::Escape,
+        };
+        bindings.keys(action).first().copied().unwrap_or(fallback)
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            TouchButton::Left => 0,
+            TouchButton::Right => 1,
+            TouchButton::Jump => 2,
+            TouchButton::Pause => 3,
+        }
+    }
+
+    fn radius(&self) -> f32 {
+        match self {
+            TouchButton::Pause => GameConfig::TOUCH_PAUSE_RADIUS,
+            _ => GameConfig::TOUCH_BUTTON_RADIUS,
+        }
+    }
+}
+
+/// Where each button sits, as fractions of the screen so the layout holds at any size.
+/// Saved in the settings as `x,y` pairs in `TouchButton::ALL` order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchLayout {
+    positions: [Vec2; 4],
+}
+
+impl TouchLayout {
+    /// Center of `button` on the virtual screen
+    pub fn center(&self, button: TouchButton) -> Vec2 {
+        self.positions[button.index()]
+            * Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT)
+    }
+
+    /// Move `button` to a point on the virtual screen, keeping all of it in view
+    pub fn move_to(&mut self, button: TouchButton, center: Vec2) {
+        let radius = button.radius();
+        let center = Vec2::new(
+            center.x.clamp(radius, GameConfig::VIRTUAL_WIDTH - radius),
+            center.y.clamp(radius, GameConfig::VIRTUAL_HEIGHT - radius),
+        );
+        self.positions[button.index()] =
+            center / Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT);
+    }
+
+    /// The button under `point`; where circles overlap the nearest center wins
+    pub fn button_at(&self, point: Vec2) -> Option<TouchButton> {
+        TouchButton::ALL
+            .into_iter()
+            .map(|button| (button, self.center(button).distance(point)))
+            .filter(|(button, distance)| *distance <= button.radius())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(button, _)| button)
+    }
+
+    /// Read a saved layout, or `None` if it doesn't have a position for every button
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut positions = [Vec2::ZERO; 4];
+        let mut pairs = value.split_whitespace();
+        for position in &mut positions {
+            let (x, y) = pairs.next()?.split_once(',')?;
+            let (x, y) = (x.trim().parse::<f32>().ok()?, y.trim().parse::<f32>().ok()?);
+            *position = Vec2::new(x.clamp(0.0, 1.0), y.clamp(0.0, 1.0));
+        }
+        pairs.next().is_none().then_some(Self { positions })
+    }
+}
+
+impl Default for TouchLayout {
+    fn default() -> Self {
+        Self {
+            positions: [
+                GameConfig::TOUCH_LEFT_POSITION,
+                GameConfig::TOUCH_RIGHT_POSITION,
+                GameConfig::TOUCH_JUMP_POSITION,
+                GameConfig::TOUCH_PAUSE_POSITION,
+            ],
+        }
+    }
+}
+
+impl fmt::Display for TouchLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, position) in self.positions.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:.3},{:.3}", position.x, position.y)?;
+        }
+        Ok(())
+    }
+}
+
+/// On-screen buttons for touch screens. Touches become key presses, so the rest of the
+/// game can't tell them from the keyboard. Nothing is drawn until the first touch, so
+/// keyboard players never see them.
+pub struct TouchControls {
+    pub layout: TouchLayout,
+    /// Only play reads the buttons; menus already take taps as mouse clicks
+    pub enabled: bool,
+    seen_touch: bool,
+    /// Buttons under a finger this frame
+    held: Vec<TouchButton>,
+    /// Button being moved on the layout screen
+    dragging: Option<TouchButton>,
+}
+
+impl TouchControls {
+    pub fn new() -> Self {
+        Self {
+            layout: TouchLayout::default(),
+            enabled: false,
+            seen_touch: false,
+            held: Vec::new(),
+            dragging: None,
+        }
+    }
+
+    /// Read every finger on the screen and note which buttons they're on
+    pub fn update(&mut self) {
+        self.held.clear();
+        let fingers: Vec<Vec2> = touches()
+            .into_iter()
+            .filter(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|touch| Viewport::to_virtual(touch.position))
+            .collect();
+        if !fingers.is_empty() {
+            self.seen_touch = true;
+        }
+        if !self.enabled {
+            return;
+        }
+        for finger in fingers {
+            if let Some(button) = self.layout.button_at(finger) {
+                if !self.held.contains(&button) {
+                    self.held.push(button);
+                }
+            }
+        }
+    }
+
+    /// Keys held down by fingers on the buttons
+    pub fn keys<'a>(&'a self, bindings: &'a KeyBindings) -> impl Iterator<Item = KeyCode> + 'a {
+        self.held.iter().map(|button| button.key(bindings))
+    }
+
+    /// Whether the player has touched the screen at all, and so wants the buttons shown
+    pub fn is_visible(&self) -> bool {
+        self.seen_touch
+    }
+
+    /// Move buttons around on the layout screen by dragging them, with a finger or the
+    /// mouse; taps come through as mouse clicks
+    pub fn drag(&mut self) {
+        let pointer = Viewport::mouse_position();
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.dragging = self.layout.button_at(pointer);
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        if let Some(button) = self.dragging {
+            self.layout.move_to(button, pointer);
+        }
+    }
+
+    /// Draw the buttons over the game, lit while held
+    pub fn render(&self) {
+        for button in TouchButton::ALL {
+            let held = self.held.contains(&button) || self.dragging == Some(button);
+            Self::render_button(self.layout.center(button), button, held);
+        }
+    }
+
+    fn render_button(center: Vec2, button: TouchButton, held: bool) {
+        let style = RenderStyle::current();
+        let alpha = if held {
+            GameConfig::TOUCH_HELD_ALPHA
+        } else {
+            GameConfig::TOUCH_BUTTON_ALPHA
+        };
+        let radius = button.radius();
+        GraphicsUtils::draw_circle_with_border(
+            center.x,
+            center.y,
+            radius,
+            Color::new(0.0, 0.0, 0.0, alpha * 0.5),
+            Color::new(1.0, 1.0, 1.0, alpha),
+            3.0,
+        );
+        style.outline_circle(center.x, center.y, radius);
+
+        let icon = Color::new(1.0, 1.0, 1.0, alpha);
+        let size = radius * 0.45;
+        match button {
+            TouchButton::Left | TouchButton::Right => {
+                let facing = if button == TouchButton::Left {
+                    -1.0
+                } else {
+                    1.0
+                };
+                draw_triangle(
+                    center + Vec2::new(size * facing, 0.0),
+                    center + Vec2::new(-size * 0.6 * facing, -size),
+                    center + Vec2::new(-size * 0.6 * facing, size),
+                    icon,
+                );
+            }
+            TouchButton::Jump => draw_triangle(
+                center + Vec2::new(0.0, -size),
+                center + Vec2::new(-size, size * 0.6),
+                center + Vec2::new(size, size * 0.6),
+                icon,
+            ),
+            TouchButton::Pause => {
+                let bar = Vec2::new(size * 0.35, size * 1.6);
+                for side in [-1.0, 1.0] {
+                    draw_rectangle(
+                        center.x + side * size * 0.45 - bar.x / 2.0,
+                        center.y - bar.y / 2.0,
+                        bar.x,
+                        bar.y,
+                        icon,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Default for TouchControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f32 = GameConfig::TOUCH_BUTTON_RADIUS;
+
+    #[test]
+    fn each_button_is_hit_inside_its_circle_only() {
+        let layout = TouchLayout::default();
+        for button in TouchButton::ALL {
+            let center = layout.center(button);
+            assert_eq!(layout.button_at(center), Some(button));
+            let edge = center + Vec2::new(0.0, button.radius() - 1.0);
+            assert_eq!(layout.button_at(edge), Some(button));
+        }
+        let middle = Vec2::new(GameConfig::VIRTUAL_WIDTH, GameConfig::VIRTUAL_HEIGHT) / 2.0;
+        assert_eq!(layout.button_at(middle), None);
+    }
+
+    #[test]
+    fn where_buttons_overlap_the_nearest_center_wins() {
+        let mut layout = TouchLayout::default();
+        let left = layout.center(TouchButton::Left);
+        // Right's circle covers most of Left's
+        layout.move_to(TouchButton::Right, left + Vec2::new(RADIUS, 0.0));
+
+        let overlap = |x: f32| left + Vec2::new(x, 0.0);
+        assert_eq!(
+            layout.button_at(overlap(RADIUS * 0.4)),
+            Some(TouchButton::Left)
+        );
+        assert_eq!(
+            layout.button_at(overlap(RADIUS * 0.6)),
+            Some(TouchButton::Right)
+        );
+        // Only in one circle, so that one, however near the other center is
+        assert_eq!(
+            layout.button_at(overlap(-RADIUS * 0.9)),
+            Some(TouchButton::Left)
+        );
+        assert_eq!(
+            layout.button_at(overlap(RADIUS * 1.9)),
+            Some(TouchButton::Right)
+        );
+    }
+
+    #[test]
+    fn a_small_button_only_takes_touches_in_its_own_circle() {
+        let mut layout = TouchLayout::default();
+        let jump = layout.center(TouchButton::Jump);
+        let pause_center = jump + Vec2::new(0.0, -100.0);
+        layout.move_to(TouchButton::Pause, pause_center);
+
+        // Past Pause's small circle but nearer its center than Jump's
+        let outside_pause = pause_center + Vec2::new(0.0, GameConfig::TOUCH_PAUSE_RADIUS + 4.0);
+        assert!(outside_pause.distance(pause_center) < outside_pause.distance(jump));
+        assert_eq!(layout.button_at(outside_pause), Some(TouchButton::Jump));
+        assert_eq!(
+            layout.button_at(pause_center + Vec2::new(0.0, 10.0)),
+            Some(TouchButton::Pause)
+        );
+    }
+
+    #[test]
+    fn moved_buttons_stay_on_screen() {
+        let mut layout = TouchLayout::default();
+        layout.move_to(TouchButton::Jump, Vec2::new(-50.0, 10_000.0));
+        assert_eq!(
+            layout.center(TouchButton::Jump),
+            Vec2::new(RADIUS, GameConfig::VIRTUAL_HEIGHT - RADIUS)
+        );
+    }
+
+    #[test]
+    fn a_layout_reads_back_what_it_saves() {
+        let mut layout = TouchLayout::default();
+        layout.move_to(TouchButton::Left, Vec2::new(400.0, 300.0));
+        let saved = TouchLayout::parse(&layout.to_string()).unwrap();
+        for button in TouchButton::ALL {
+            assert!(saved.center(button).distance(layout.center(button)) < 1.0);
+        }
+        assert_eq!(TouchLayout::parse("0.1,0.2 0.3,0.4"), None);
+    }
+}
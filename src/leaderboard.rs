@@ -0,0 +1,184 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::date::Date;
+use crate::profile::Profile;
+
+/// Where the local high score table is persisted, inside each profile's folder
+pub const LEADERBOARD_PATH: &str = "leaderboard.txt";
+
+/// Entries kept on the table
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Letters in a player's initials
+pub const INITIALS_LENGTH: usize = 3;
+
+/// Initials used when none were entered
+pub const DEFAULT_INITIALS: &str = "AAA";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: i32,
+    pub date: Date,
+    /// Played with an assist option on
+    pub assisted: bool,
+}
+
+impl LeaderboardEntry {
+    pub fn new(name: &str, score: i32, date: Date) -> Self {
+        Self {
+            name: sanitize_initials(name),
+            score,
+            date,
+            assisted: false,
+        }
+    }
+
+    pub fn with_assist(mut self, assisted: bool) -> Self {
+        self.assisted = assisted;
+        self
+    }
+}
+
+/// Top scores, best first, persisted as `NAME SCORE DATE` lines, with `assist` on the end
+/// of a run played with assist options
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn load(profile: &Profile) -> Self {
+        Self::load_from(profile.path(LEADERBOARD_PATH))
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse the table, skipping lines that don't hold a valid entry
+    pub fn parse(source: &str) -> Self {
+        let mut leaderboard = Self::default();
+
+        for line in source.lines() {
+            let mut words = line.split_whitespace();
+            let (Some(name), Some(score), Some(date)) = (words.next(), words.next(), words.next())
+            else {
+                continue;
+            };
+            let assisted = words.next() == Some("assist");
+            if let (Ok(score), Ok(date)) = (score.parse(), date.parse()) {
+                leaderboard.insert(LeaderboardEntry::new(name, score, date).with_assist(assisted));
+            }
+        }
+
+        leaderboard
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(LEADERBOARD_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Whether a score is good enough to make the table
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Add an entry, returning its zero-based rank if it made the table.
+    /// Submitting an entry that is already on the table changes nothing.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        if self.entries.contains(&entry) || !self.qualifies(entry.score) {
+            return None;
+        }
+
+        // Ties go below existing entries, so earlier runs keep their place
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+
+        Some(rank)
+    }
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{} {} {}", entry.name, entry.score, entry.date)?;
+            if entry.assisted {
+                write!(f, " assist")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Upper-case letters only, padded with the default initials
+pub fn sanitize_initials(name: &str) -> String {
+    let letters: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .take(INITIALS_LENGTH)
+        .collect();
+    let padding = &DEFAULT_INITIALS[letters.len()..];
+    letters + padding
+}
+
+/// Arcade-style initials selector: left/right cycles the letter, jump confirms the slot
+#[derive(Debug, Clone)]
+pub struct InitialsEntry {
+    pub letters: [u8; INITIALS_LENGTH],
+    pub slot: usize,
+}
+
+impl InitialsEntry {
+    pub fn new() -> Self {
+        Self {
+            letters: [b'A'; INITIALS_LENGTH],
+            slot: 0,
+        }
+    }
+
+    /// Move the current slot's letter forwards or backwards through the alphabet
+    pub fn cycle(&mut self, step: i32) {
+        let Some(letter) = self.letters.get_mut(self.slot) else {
+            return;
+        };
+        let index = (i32::from(*letter - b'A') + step).rem_euclid(26);
+        *letter = b'A' + index as u8;
+    }
+
+    /// Lock in the current letter; returns true once every slot is filled
+    pub fn confirm(&mut self) -> bool {
+        self.slot += 1;
+        self.is_complete()
+    }
+
+    /// Go back to the previous slot
+    pub fn back(&mut self) {
+        self.slot = self.slot.saturating_sub(1);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.slot >= INITIALS_LENGTH
+    }
+
+    pub fn name(&self) -> String {
+        sanitize_initials(&String::from_utf8_lossy(&self.letters))
+    }
+}
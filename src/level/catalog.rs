@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::share::CUSTOM_LEVELS_DIR;
+use super::{Level, DEFAULT_LEVEL_PATH};
+
+/// Levels that come with the game, in the order they're listed
+pub const BUILTIN_LEVELS: [&str; 1] = [DEFAULT_LEVEL_PATH];
+
+/// A level file the level select screen can offer
+#[derive(Debug, Clone)]
+pub struct LevelEntry {
+    pub path: PathBuf,
+    /// Whether it's one of the player's own or imported levels, which can be edited and
+    /// deleted from the level select screen
+    pub custom: bool,
+    /// When the file was last written, to notice it changing
+    pub modified: Option<SystemTime>,
+    /// The level, or why it can't be played
+    pub level: Result<Level, String>,
+}
+
+impl LevelEntry {
+    pub fn load(path: PathBuf, custom: bool) -> Self {
+        let modified = modified_time(&path);
+        let level = match Level::load(&path) {
+            Ok(level) => match level.first_error() {
+                Some(issue) => Err(issue.message),
+                None => Ok(level),
+            },
+            // The built-in level always has the copy inside the game to fall back on
+            Err(_) if !custom && path == Path::new(DEFAULT_LEVEL_PATH) => Ok(Level::load_default()),
+            Err(err) => Err(err.to_string()),
+        };
+        Self {
+            path,
+            custom,
+            modified,
+            level,
+        }
+    }
+
+    /// The level's name, or its file name if it has none or can't be read
+    pub fn name(&self) -> String {
+        match &self.level {
+            Ok(level) if !level.name.is_empty() => level.name.clone(),
+            _ => self
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// How records for this level are looked up
+    pub fn key(&self) -> String {
+        record_key(&self.path)
+    }
+}
+
+/// The name a level file's records are kept under
+pub fn record_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+pub fn is_builtin(path: &Path) -> bool {
+    BUILTIN_LEVELS
+        .iter()
+        .any(|builtin| path == Path::new(builtin))
+}
+
+/// The built-in levels followed by every `.lvl` file in `CUSTOM_LEVELS_DIR`, sorted by
+/// file name
+pub fn scan() -> Vec<LevelEntry> {
+    let mut entries: Vec<LevelEntry> = BUILTIN_LEVELS
+        .iter()
+        .map(|path| LevelEntry::load(PathBuf::from(path), false))
+        .collect();
+
+    let mut custom: Vec<PathBuf> = std::fs::read_dir(CUSTOM_LEVELS_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lvl"))
+        .collect();
+    custom.sort();
+    entries.extend(custom.into_iter().map(|path| LevelEntry::load(path, true)));
+    entries
+}
+
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
@@ -0,0 +1,110 @@
+use macroquad::prelude::*;
+
+use std::fmt;
+
+use super::level_y;
+
+/// When a level's cutscene plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CutsceneKind {
+    /// Before the player gets control, when a run on the level starts from the menus
+    Intro,
+    /// Once the boss has fallen, before the results
+    Victory,
+}
+
+impl CutsceneKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "intro" => Some(CutsceneKind::Intro),
+            "victory" => Some(CutsceneKind::Victory),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CutsceneKind::Intro => "intro",
+            CutsceneKind::Victory => "victory",
+        }
+    }
+}
+
+/// One thing a cutscene does, played one after another
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutsceneStep {
+    /// Glide the camera over `duration` seconds until `target` is in the middle of the
+    /// view, or back to following the player when there's no target; 0 cuts straight there
+    Pan {
+        target: Option<Vec2>,
+        duration: f32,
+    },
+    /// A line in the dialogue box, which stays up until the player moves it on. A
+    /// language file key is shown translated.
+    Say(String),
+    /// Walk an NPC through `path`, positions of its feet, reaching the end after
+    /// `duration` seconds
+    Move {
+        actor: String,
+        duration: f32,
+        path: Vec<Vec2>,
+    },
+    Wait(f32),
+    /// Darken the view to black, or brighten it back
+    Fade {
+        out: bool,
+        duration: f32,
+    },
+}
+
+impl CutsceneStep {
+    /// Seconds the step takes, or `None` for a line that waits on the player
+    pub fn duration(&self) -> Option<f32> {
+        match self {
+            CutsceneStep::Pan { duration, .. }
+            | CutsceneStep::Move { duration, .. }
+            | CutsceneStep::Fade { duration, .. } => Some(*duration),
+            CutsceneStep::Wait(duration) => Some(*duration),
+            CutsceneStep::Say(_) => None,
+        }
+    }
+}
+
+/// Written as it's read: the step's word and its arguments
+impl fmt::Display for CutsceneStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CutsceneStep::Pan {
+                target: Some(target),
+                duration,
+            } => write!(f, "pan {} {} {}", target.x, level_y(target.y), duration),
+            CutsceneStep::Pan {
+                target: None,
+                duration,
+            } => write!(f, "pan player {}", duration),
+            CutsceneStep::Say(text) => write!(f, "say {}", text),
+            CutsceneStep::Move {
+                actor,
+                duration,
+                path,
+            } => {
+                write!(f, "move {} {}", actor, duration)?;
+                for point in path {
+                    write!(f, " {} {}", point.x, level_y(point.y))?;
+                }
+                Ok(())
+            }
+            CutsceneStep::Wait(duration) => write!(f, "wait {}", duration),
+            CutsceneStep::Fade { out, duration } => {
+                write!(f, "fade {} {}", if *out { "out" } else { "in" }, duration)
+            }
+        }
+    }
+}
+
+/// A level's script for one of its cutscenes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cutscene {
+    pub kind: CutsceneKind,
+    pub steps: Vec<CutsceneStep>,
+}
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::config::GameConfig;
+
+/// Polls a level file's modification time so edits can be picked up while the game runs
+#[derive(Debug, Clone)]
+pub struct LevelWatcher {
+    pub path: PathBuf,
+    last_modified: Option<SystemTime>,
+    poll_timer: f32,
+}
+
+impl LevelWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let last_modified = Self::modified_time(&path);
+
+        Self {
+            path,
+            last_modified,
+            poll_timer: 0.0,
+        }
+    }
+
+    /// Check the file at most once per poll interval; returns true when it has changed
+    pub fn poll(&mut self, delta_time: f32) -> bool {
+        self.poll_timer += delta_time;
+        if self.poll_timer < GameConfig::LEVEL_RELOAD_POLL_INTERVAL {
+            return false;
+        }
+        self.poll_timer = 0.0;
+
+        let modified = Self::modified_time(&self.path);
+        if modified.is_some() && modified != self.last_modified {
+            self.last_modified = modified;
+            return true;
+        }
+        false
+    }
+
+    /// Remember the current modification time so a manual reload isn't followed by an automatic one
+    pub fn mark_current(&mut self) {
+        self.last_modified = Self::modified_time(&self.path);
+    }
+
+    fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+}
+
+/// On-screen feedback about the last level reload
+#[derive(Debug, Clone)]
+pub struct ReloadBanner {
+    pub message: String,
+    pub is_error: bool,
+    /// Seconds left on screen; errors stay until the next successful reload
+    pub remaining: f32,
+}
+
+impl ReloadBanner {
+    pub fn success(message: String) -> Self {
+        Self {
+            message,
+            is_error: false,
+            remaining: GameConfig::LEVEL_RELOAD_BANNER_TIME,
+        }
+    }
+
+    pub fn error(message: String) -> Self {
+        Self {
+            message,
+            is_error: true,
+            remaining: f32::INFINITY,
+        }
+    }
+
+    /// An error that clears itself, for problems that aren't about the level file
+    pub fn transient_error(message: String) -> Self {
+        Self {
+            message,
+            is_error: true,
+            remaining: GameConfig::LEVEL_RELOAD_BANNER_TIME,
+        }
+    }
+
+    /// Count down the banner; returns false once it should disappear
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.remaining -= delta_time;
+        self.remaining > 0.0
+    }
+}
@@ -0,0 +1,1733 @@
+use macroquad::prelude::*;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::GameConfig;
+use crate::entities::collectible::CollectibleType;
+use crate::entities::enemy::{EnemyBehavior, EnemyKind};
+use crate::entities::pad::PadKind;
+use crate::entities::path::PathMode;
+use crate::entities::platform::PlatformType;
+use crate::entities::prop::PropKind;
+use crate::entities::trigger::TriggerAction;
+use crate::entities::zone::{self, Weather, ZoneEffect};
+use crate::entities::{
+    Bomb, Boss, BossTuning, Collectible, Cycle, Door, Enemy, Npc, Pad, Platform, PlatformPath,
+    Prop, Rope, RopeBridge, Switch, Terrain, Trigger, TriggerZone, Water, Zipline,
+};
+
+pub mod catalog;
+pub mod cutscene;
+pub mod hot_reload;
+pub mod share;
+pub mod validate;
+
+pub use cutscene::{Cutscene, CutsceneKind, CutsceneStep};
+pub use hot_reload::{LevelWatcher, ReloadBanner};
+pub use validate::LevelIssue;
+
+/// Level shipped with the game, used when the level file can't be found on disk
+pub const DEFAULT_LEVEL_PATH: &str = "levels/level1.lvl";
+pub const DEFAULT_LEVEL_SOURCE: &str = include_str!("../../levels/level1.lvl");
+
+/// Example of every level command, kept for reference and to check the format with
+pub const SCHEMA_LEVEL_PATH: &str = "levels/schema.lvl";
+
+/// Arena played in survival mode
+pub const SURVIVAL_LEVEL_PATH: &str = "levels/survival.lvl";
+pub const SURVIVAL_LEVEL_SOURCE: &str = include_str!("../../levels/survival.lvl");
+
+/// What happens when the player falls below the bottom of the level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutOfBounds {
+    /// The run ends
+    #[default]
+    Death,
+    /// The player is put back on the last solid ground they stood on. There's no health
+    /// yet, so it costs nothing.
+    Respawn,
+    /// The pit throws the player back up towards the last solid ground
+    Bounce,
+}
+
+impl OutOfBounds {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "death" => Some(OutOfBounds::Death),
+            "respawn" => Some(OutOfBounds::Respawn),
+            "bounce" => Some(OutOfBounds::Bounce),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutOfBounds::Death => "death",
+            OutOfBounds::Respawn => "respawn",
+            OutOfBounds::Bounce => "bounce",
+        }
+    }
+}
+
+/// Per-level rules and presentation flags
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelProperties {
+    /// The world is pitch black apart from a light around the player
+    pub dark: bool,
+    /// A lava plane rises from below and must be outrun
+    pub rising_lava: bool,
+    /// Constant sideways push on the player in pixels per second; positive blows right
+    pub wind: f32,
+    pub out_of_bounds: OutOfBounds,
+    /// Set with `camera_bounds`; otherwise the camera keeps between x 0 and the right
+    /// edge of the furthest platform
+    pub camera_bounds: Option<CameraBounds>,
+    /// Seconds to beat the level in for its second star; without one, any finish earns it
+    pub par_time: Option<f32>,
+    /// Stars the player needs across all levels before this one can be played; locked
+    /// levels can still be opened in the editor
+    pub required_stars: u32,
+}
+
+/// The stretch of the world, left to right, the camera is allowed to show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraBounds {
+    pub left: f32,
+    pub right: f32,
+}
+
+impl CameraBounds {
+    /// Left edge for a view `view_width` wide that would like its left edge at `target`.
+    /// It stops dead at either bound, and is centered on them when they're narrower than
+    /// the view.
+    pub fn clamp(&self, target: f32, view_width: f32) -> f32 {
+        let width = self.right - self.left;
+        if width <= view_width {
+            self.left + (width - view_width) / 2.0
+        } else {
+            target.clamp(self.left, self.right - view_width)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlatformDef {
+    pub id: String,
+    pub platform_type: PlatformType,
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Where a moving platform goes from its starting position
+    pub path: Option<PathDef>,
+    /// When a timed platform is there
+    pub cycle: Option<Cycle>,
+}
+
+/// A moving platform's route. The platform starts at its own position, which is the
+/// first waypoint, so only the ones after it are listed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathDef {
+    pub waypoints: Vec<Vec2>,
+    pub speed: f32,
+    pub mode: PathMode,
+    pub smooth: bool,
+}
+
+impl PathDef {
+    pub fn new(waypoint: Vec2) -> Self {
+        Self {
+            waypoints: vec![waypoint],
+            speed: GameConfig::PLATFORM_PATH_SPEED,
+            mode: PathMode::PingPong,
+            smooth: false,
+        }
+    }
+}
+
+/// A block of destructible cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainDef {
+    pub id: String,
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// A pool of water, with the current that flows through it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterDef {
+    pub id: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Push on whatever is under the surface, in pixels per second squared
+    pub current: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectibleDef {
+    pub id: String,
+    pub collectible_type: CollectibleType,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZiplineDef {
+    pub id: String,
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BridgeDef {
+    pub id: String,
+    /// Left anchor
+    pub start: Vec2,
+    /// Right anchor
+    pub end: Vec2,
+    /// Snaps if the player stands on the middle too long
+    pub fragile: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RopeDef {
+    pub id: String,
+    /// Where it hangs from
+    pub anchor: Vec2,
+    pub length: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PadDef {
+    pub id: String,
+    pub pad_kind: PadKind,
+    pub x: f32,
+    pub surface_y: f32,
+    /// When timed spikes are out
+    pub cycle: Option<Cycle>,
+}
+
+/// A bomb sitting on a surface, waiting to be kicked
+#[derive(Debug, Clone, PartialEq)]
+pub struct BombDef {
+    pub id: String,
+    pub x: f32,
+    pub surface_y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchDef {
+    pub id: String,
+    pub x: f32,
+    pub surface_y: f32,
+    /// Id of the door it opens
+    pub door: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoorDef {
+    pub id: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Seconds it stays open after its switch is pressed
+    pub open_time: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerDef {
+    pub id: String,
+    pub action: TriggerAction,
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneDef {
+    pub id: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    pub effect: ZoneEffect,
+    /// Seconds before it can fire again, or None to fire only once
+    pub repeat: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NpcDef {
+    pub id: String,
+    pub x: f32,
+    pub surface_y: f32,
+    /// What they say, one entry per dialogue page
+    pub pages: Vec<String>,
+}
+
+/// Decorative scenery; see `Prop::anchor` for what the position marks
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropDef {
+    pub id: String,
+    pub kind: PropKind,
+    pub position: Vec2,
+}
+
+/// An enemy placed in the level, standing on a surface whose top edge is at `surface_y`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnemyDef {
+    pub id: String,
+    pub kind: EnemyKind,
+    pub x: f32,
+    pub surface_y: f32,
+    pub behavior: EnemyBehavior,
+}
+
+/// Where survival mode sends enemies out from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnPointDef {
+    pub id: String,
+    pub x: f32,
+    pub surface_y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossDef {
+    pub id: String,
+    pub x: f32,
+    pub surface_y: f32,
+    /// World x range the fight is locked to once the player walks in
+    pub arena_left: f32,
+    pub arena_right: f32,
+}
+
+impl PlatformDef {
+    pub fn build(&self) -> Platform {
+        let platform = Platform::new_of_type(
+            self.position.x,
+            self.position.y,
+            self.size.x,
+            self.size.y,
+            self.platform_type.clone(),
+        );
+        let platform = match self.cycle {
+            Some(cycle) => platform.with_cycle(cycle),
+            None => platform,
+        };
+        match &self.path {
+            Some(path) => {
+                let waypoints = std::iter::once(self.position)
+                    .chain(path.waypoints.iter().copied())
+                    .collect();
+                platform.with_path(PlatformPath::new(
+                    waypoints,
+                    path.speed,
+                    path.mode,
+                    path.smooth,
+                ))
+            }
+            None => platform,
+        }
+    }
+}
+
+impl TerrainDef {
+    pub fn build(&self) -> Terrain {
+        Terrain::new(self.position, self.size)
+    }
+}
+
+impl WaterDef {
+    pub fn build(&self) -> Water {
+        Water::new(self.position, self.size, self.current)
+    }
+}
+
+impl CollectibleDef {
+    pub fn build(&self) -> Collectible {
+        Collectible::new(self.position.x, self.position.y, self.collectible_type)
+            .with_id(self.id.clone())
+    }
+}
+
+impl ZiplineDef {
+    pub fn build(&self) -> Zipline {
+        Zipline::new(self.start.x, self.start.y, self.end.x, self.end.y)
+    }
+}
+
+impl BridgeDef {
+    pub fn build(&self) -> RopeBridge {
+        RopeBridge::new(self.start, self.end, self.fragile)
+    }
+}
+
+impl RopeDef {
+    pub fn build(&self) -> Rope {
+        Rope::new(self.anchor, self.length)
+    }
+}
+
+impl PadDef {
+    pub fn build(&self) -> Pad {
+        let pad = Pad::new(self.x, self.surface_y, self.pad_kind.clone());
+        match self.cycle {
+            Some(cycle) => pad.with_cycle(cycle),
+            None => pad,
+        }
+    }
+}
+
+impl BombDef {
+    pub fn build(&self) -> Bomb {
+        Bomb::new(self.x, self.surface_y)
+    }
+}
+
+impl SwitchDef {
+    pub fn build(&self) -> Switch {
+        Switch::new(self.x, self.surface_y, self.door.clone()).with_id(self.id.clone())
+    }
+}
+
+impl DoorDef {
+    pub fn build(&self) -> Door {
+        Door::new(
+            self.position.x,
+            self.position.y,
+            self.size.x,
+            self.size.y,
+            self.open_time,
+        )
+        .with_id(self.id.clone())
+    }
+}
+
+impl TriggerDef {
+    pub fn build(&self) -> Trigger {
+        Trigger::new(
+            self.position.x,
+            self.position.y,
+            self.size.x,
+            self.size.y,
+            self.action,
+        )
+    }
+}
+
+impl ZoneDef {
+    pub fn build(&self) -> TriggerZone {
+        TriggerZone::new(self.position, self.size, self.effect.clone(), self.repeat)
+    }
+}
+
+impl NpcDef {
+    pub fn build(&self) -> Npc {
+        Npc::new(self.x, self.surface_y, self.pages.clone()).with_id(self.id.clone())
+    }
+}
+
+impl PropDef {
+    pub fn build(&self) -> Prop {
+        Prop::new(self.kind, self.position)
+    }
+}
+
+impl EnemyDef {
+    pub fn build(&self) -> Enemy {
+        Enemy::new(self.x, self.surface_y, GameConfig::ENEMY_SPEED)
+            .with_kind(self.kind)
+            .with_behavior(self.behavior.clone())
+    }
+}
+
+impl BossDef {
+    pub fn build(&self, tuning: BossTuning) -> Boss {
+        Boss::new(
+            self.x,
+            self.surface_y,
+            self.arena_left,
+            self.arena_right,
+            tuning,
+        )
+    }
+}
+
+/// Everything needed to build a level, in world coordinates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Level {
+    pub name: String,
+    /// Who made the level, shown on the level select screen; empty if nobody said
+    pub author: String,
+    pub spawn: Vec2,
+    pub properties: LevelProperties,
+    pub platforms: Vec<PlatformDef>,
+    pub terrain: Vec<TerrainDef>,
+    pub water: Vec<WaterDef>,
+    pub collectibles: Vec<CollectibleDef>,
+    pub ziplines: Vec<ZiplineDef>,
+    pub bridges: Vec<BridgeDef>,
+    pub ropes: Vec<RopeDef>,
+    pub pads: Vec<PadDef>,
+    pub bombs: Vec<BombDef>,
+    pub switches: Vec<SwitchDef>,
+    pub doors: Vec<DoorDef>,
+    pub triggers: Vec<TriggerDef>,
+    pub zones: Vec<ZoneDef>,
+    pub npcs: Vec<NpcDef>,
+    pub props: Vec<PropDef>,
+    pub bosses: Vec<BossDef>,
+    pub enemies: Vec<EnemyDef>,
+    pub spawn_points: Vec<SpawnPointDef>,
+    /// Scripts played before the run and after the boss, at most one of each kind
+    pub cutscenes: Vec<Cutscene>,
+}
+
+/// A problem found while reading a level file
+#[derive(Debug, Clone)]
+pub struct LevelError {
+    /// 1-based line number, or 0 for problems with the file as a whole
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl Level {
+    /// Read and parse a level file from disk
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|err| LevelError {
+            line: 0,
+            message: format!("could not read {}: {}", path.display(), err),
+        })?;
+        Self::parse(&source)
+    }
+
+    /// Load the default level from disk, falling back to the copy built into the binary
+    /// if it can't be read or the file's level has errors that stop it being played
+    pub fn load_default() -> Self {
+        Self::load(DEFAULT_LEVEL_PATH)
+            .ok()
+            .filter(|level| level.first_error().is_none())
+            .or_else(|| Self::parse(DEFAULT_LEVEL_SOURCE).ok())
+            .expect("built-in level must be valid")
+    }
+
+    /// Where the camera may look: the level's `camera_bounds`, or from x 0 to the right
+    /// edge of the furthest platform
+    pub fn camera_bounds(&self) -> CameraBounds {
+        self.properties.camera_bounds.unwrap_or_else(|| {
+            let right = self
+                .platforms
+                .iter()
+                .map(|def| def.position.x + def.size.x)
+                .fold(0.0, f32::max);
+            CameraBounds { left: 0.0, right }
+        })
+    }
+
+    /// How many collectibles the level places, all of which the third star asks for
+    pub fn collectible_total(&self) -> usize {
+        self.collectibles.len()
+    }
+
+    /// Load the survival arena from disk, falling back to the copy built into the binary
+    pub fn load_survival() -> Self {
+        Self::load(SURVIVAL_LEVEL_PATH)
+            .or_else(|_| Self::parse(SURVIVAL_LEVEL_SOURCE))
+            .expect("built-in survival arena must be valid")
+    }
+
+    /// Parse the text level format.
+    ///
+    /// Each line is a command followed by whitespace-separated arguments; `#` starts a comment.
+    /// Y coordinates are offsets from the level floor, so negative values are up.
+    pub fn parse(source: &str) -> Result<Self, LevelError> {
+        let (level, has_spawn) = Self::parse_lines(source)?;
+        if !has_spawn {
+            return Err(LevelError {
+                line: 0,
+                message: "no spawn point; add a 'spawn <x> <y>' line".to_string(),
+            });
+        }
+        Ok(level)
+    }
+
+    /// Parse part of a level, such as an editor prefab, which needn't have a spawn point
+    pub fn parse_fragment(source: &str) -> Result<Self, LevelError> {
+        Self::parse_lines(source).map(|(level, _)| level)
+    }
+
+    /// The level in `source`, and whether it set the spawn point
+    fn parse_lines(source: &str) -> Result<(Self, bool), LevelError> {
+        let mut level = Level::default();
+        let mut ids = HashSet::new();
+        // Switches are checked against the doors, and enemies against the switches, once
+        // the whole file has been read
+        let mut switch_lines = Vec::new();
+        let mut enemy_lines = Vec::new();
+        let mut has_spawn = false;
+
+        for (index, raw_line) in source.lines().enumerate() {
+            let line = index + 1;
+            let content = raw_line.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let mut parser = LineParser::new(content, line);
+            let command = parser.word()?;
+
+            match command {
+                "name" => level.name = parser.rest(),
+                "author" => level.author = parser.rest(),
+                "spawn" => {
+                    level.spawn = parser.point()?;
+                    has_spawn = true;
+                }
+                "dark" => level.properties.dark = parser.flag()?,
+                "rising_lava" => level.properties.rising_lava = parser.flag()?,
+                "wind" => level.properties.wind = parser.number()?,
+                "camera_bounds" => {
+                    let left = parser.number()?;
+                    let right = parser.number()?;
+                    if right <= left {
+                        return Err(
+                            parser.error("camera_bounds right must be past left".to_string())
+                        );
+                    }
+                    level.properties.camera_bounds = Some(CameraBounds { left, right });
+                }
+                "par" => {
+                    let seconds = parser.number()?;
+                    if seconds <= 0.0 {
+                        return Err(parser.error("par must be positive".to_string()));
+                    }
+                    level.properties.par_time = Some(seconds);
+                }
+                "requires_stars" => {
+                    let stars = parser.number()?;
+                    if stars < 0.0 || stars.fract() != 0.0 {
+                        return Err(parser
+                            .error("requires_stars must be a whole number of stars".to_string()));
+                    }
+                    level.properties.required_stars = stars as u32;
+                }
+                "out_of_bounds" => {
+                    let name = parser.word()?;
+                    level.properties.out_of_bounds =
+                        OutOfBounds::from_name(name).ok_or_else(|| {
+                            parser.error(format!(
+                                "out_of_bounds must be death, respawn or bounce, got '{}'",
+                                name
+                            ))
+                        })?;
+                }
+                "platform" => {
+                    let type_name = parser.word()?;
+                    let platform_type = PlatformType::from_name(type_name).ok_or_else(|| {
+                        parser.error(format!("unknown platform type '{}'", type_name))
+                    })?;
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("platform size must be positive".to_string()));
+                    }
+                    let cycle = parser.cycle()?;
+                    let path = if parser.has_more() {
+                        if platform_type != PlatformType::Moving {
+                            return Err(
+                                parser.error("only moving platforms can follow a path".to_string())
+                            );
+                        }
+                        Some(parser.path()?)
+                    } else {
+                        None
+                    };
+                    level.platforms.push(PlatformDef {
+                        id,
+                        platform_type,
+                        position,
+                        size,
+                        path,
+                        cycle,
+                    });
+                }
+                "terrain" => {
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("terrain size must be positive".to_string()));
+                    }
+                    level.terrain.push(TerrainDef { id, position, size });
+                }
+                "water" => {
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("water size must be positive".to_string()));
+                    }
+                    let current = if parser.has_more() {
+                        let setting = parser.word()?;
+                        if setting != "current" {
+                            return Err(
+                                parser.error(format!("unknown water setting '{}'", setting))
+                            );
+                        }
+                        Vec2::new(parser.number()?, parser.number()?)
+                    } else {
+                        Vec2::ZERO
+                    };
+                    level.water.push(WaterDef {
+                        id,
+                        position,
+                        size,
+                        current,
+                    });
+                }
+                "collectible" => {
+                    let type_name = parser.word()?;
+                    let collectible_type =
+                        CollectibleType::from_name(type_name).ok_or_else(|| {
+                            parser.error(format!("unknown collectible type '{}'", type_name))
+                        })?;
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    level.collectibles.push(CollectibleDef {
+                        id,
+                        collectible_type,
+                        position,
+                    });
+                }
+                "zipline" => {
+                    let id = parser.id(&mut ids)?;
+                    let start = parser.point()?;
+                    let end = parser.point()?;
+                    level.ziplines.push(ZiplineDef { id, start, end });
+                }
+                "bridge" => {
+                    let id = parser.id(&mut ids)?;
+                    let start = parser.point()?;
+                    let end = parser.point()?;
+                    if end.x <= start.x {
+                        return Err(parser
+                            .error("bridge must end to the right of where it starts".to_string()));
+                    }
+                    let fragile = match parser.word()? {
+                        "sturdy" => false,
+                        "fragile" => true,
+                        other => {
+                            return Err(parser.error(format!(
+                                "bridge must be sturdy or fragile, got '{}'",
+                                other
+                            )))
+                        }
+                    };
+                    level.bridges.push(BridgeDef {
+                        id,
+                        start,
+                        end,
+                        fragile,
+                    });
+                }
+                "rope" => {
+                    let id = parser.id(&mut ids)?;
+                    let anchor = parser.point()?;
+                    let length = parser.number()?;
+                    if length <= 0.0 {
+                        return Err(parser.error("rope length must be positive".to_string()));
+                    }
+                    level.ropes.push(RopeDef { id, anchor, length });
+                }
+                "pad" => {
+                    let kind_name = parser.word()?;
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    let pad_kind = match kind_name {
+                        "spring" => PadKind::Spring,
+                        "spikes" => PadKind::Spikes,
+                        "boost" => {
+                            let direction = match parser.word()? {
+                                "left" => -1.0,
+                                "right" => 1.0,
+                                other => {
+                                    return Err(parser.error(format!(
+                                        "boost direction must be left or right, got '{}'",
+                                        other
+                                    )))
+                                }
+                            };
+                            PadKind::Boost { direction }
+                        }
+                        other => return Err(parser.error(format!("unknown pad type '{}'", other))),
+                    };
+                    let cycle = parser.cycle()?;
+                    if cycle.is_some() && pad_kind != PadKind::Spikes {
+                        return Err(parser.error("only spikes can be timed".to_string()));
+                    }
+                    level.pads.push(PadDef {
+                        id,
+                        pad_kind,
+                        x,
+                        surface_y,
+                        cycle,
+                    });
+                }
+                "bomb" => {
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    level.bombs.push(BombDef { id, x, surface_y });
+                }
+                "switch" => {
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    let door = parser.word()?.to_string();
+                    switch_lines.push(line);
+                    level.switches.push(SwitchDef {
+                        id,
+                        x,
+                        surface_y,
+                        door,
+                    });
+                }
+                "door" => {
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("door size must be positive".to_string()));
+                    }
+                    let open_time = parser.number()?;
+                    if open_time <= 0.0 {
+                        return Err(parser.error("door open time must be positive".to_string()));
+                    }
+                    level.doors.push(DoorDef {
+                        id,
+                        position,
+                        size,
+                        open_time,
+                    });
+                }
+                "trigger" => {
+                    let action_name = parser.word()?;
+                    let action = TriggerAction::from_name(action_name).ok_or_else(|| {
+                        parser.error(format!("unknown trigger action '{}'", action_name))
+                    })?;
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("trigger size must be positive".to_string()));
+                    }
+                    level.triggers.push(TriggerDef {
+                        id,
+                        action,
+                        position,
+                        size,
+                    });
+                }
+                "zone" => {
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    let size = Vec2::new(parser.number()?, parser.number()?);
+                    if size.x <= 0.0 || size.y <= 0.0 {
+                        return Err(parser.error("zone size must be positive".to_string()));
+                    }
+                    let repeat = match parser.word()? {
+                        "once" => None,
+                        "every" => {
+                            let cooldown = parser.number()?;
+                            if cooldown < 0.0 {
+                                return Err(
+                                    parser.error("zone cooldown can't be negative".to_string())
+                                );
+                            }
+                            Some(cooldown)
+                        }
+                        other => {
+                            return Err(parser.error(format!(
+                                "zone must fire once or every N seconds, got '{}'",
+                                other
+                            )))
+                        }
+                    };
+                    let effect = parser.zone_effect()?;
+                    level.zones.push(ZoneDef {
+                        id,
+                        position,
+                        size,
+                        effect,
+                        repeat,
+                    });
+                }
+                "npc" => {
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    let pages: Vec<String> = parser
+                        .rest()
+                        .split('|')
+                        .map(|page| page.trim().to_string())
+                        .filter(|page| !page.is_empty())
+                        .collect();
+                    if pages.is_empty() {
+                        return Err(parser.error(format!("npc '{}' has nothing to say", id)));
+                    }
+                    level.npcs.push(NpcDef {
+                        id,
+                        x,
+                        surface_y,
+                        pages,
+                    });
+                }
+                "prop" => {
+                    let kind_name = parser.word()?;
+                    let kind = PropKind::from_name(kind_name).ok_or_else(|| {
+                        parser.error(format!("unknown prop kind '{}'", kind_name))
+                    })?;
+                    let id = parser.id(&mut ids)?;
+                    let position = parser.point()?;
+                    level.props.push(PropDef { id, kind, position });
+                }
+                "boss" => {
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    let arena_left = parser.number()?;
+                    let arena_right = parser.number()?;
+                    if arena_right - arena_left < GameConfig::BOSS_SIZE.0 {
+                        return Err(parser.error(format!("boss '{}' arena is too narrow", id)));
+                    }
+                    if x < arena_left || x + GameConfig::BOSS_SIZE.0 > arena_right {
+                        return Err(parser.error(format!("boss '{}' starts outside its arena", id)));
+                    }
+                    level.bosses.push(BossDef {
+                        id,
+                        x,
+                        surface_y,
+                        arena_left,
+                        arena_right,
+                    });
+                }
+                "enemy" => {
+                    let kind_name = parser.word()?;
+                    let kind = EnemyKind::from_name(kind_name).ok_or_else(|| {
+                        parser.error(format!("unknown enemy type '{}'", kind_name))
+                    })?;
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    let behavior = parser.enemy_behavior()?;
+                    if behavior.switch.is_some() {
+                        enemy_lines.push((level.enemies.len(), line));
+                    }
+                    level.enemies.push(EnemyDef {
+                        id,
+                        kind,
+                        x,
+                        surface_y,
+                        behavior,
+                    });
+                }
+                "enemy_spawn" => {
+                    let id = parser.id(&mut ids)?;
+                    let x = parser.number()?;
+                    let surface_y = parser.y()?;
+                    level.spawn_points.push(SpawnPointDef { id, x, surface_y });
+                }
+                "cutscene" => {
+                    let name = parser.word()?;
+                    let kind = CutsceneKind::from_name(name).ok_or_else(|| {
+                        parser.error(format!("cutscene must be intro or victory, got '{}'", name))
+                    })?;
+                    let step = parser.cutscene_step()?;
+                    match level.cutscenes.iter_mut().find(|scene| scene.kind == kind) {
+                        Some(scene) => scene.steps.push(step),
+                        None => level.cutscenes.push(Cutscene {
+                            kind,
+                            steps: vec![step],
+                        }),
+                    }
+                }
+                other => return Err(parser.error(format!("unknown command '{}'", other))),
+            }
+
+            parser.finish()?;
+        }
+
+        for (def, line) in level.switches.iter().zip(switch_lines) {
+            if !level.doors.iter().any(|door| door.id == def.door) {
+                return Err(LevelError {
+                    line,
+                    message: format!("switch '{}' opens unknown door '{}'", def.id, def.door),
+                });
+            }
+        }
+
+        for (index, line) in enemy_lines {
+            let def = &level.enemies[index];
+            let switch = def.behavior.switch.as_deref().unwrap_or_default();
+            if !level.switches.iter().any(|other| other.id == switch) {
+                return Err(LevelError {
+                    line,
+                    message: format!("enemy '{}' waits for unknown switch '{}'", def.id, switch),
+                });
+            }
+        }
+
+        Ok((level, has_spawn))
+    }
+
+    /// The level's cutscene of `kind`, if it has one
+    pub fn cutscene(&self, kind: CutsceneKind) -> Option<&Cutscene> {
+        self.cutscenes.iter().find(|scene| scene.kind == kind)
+    }
+}
+
+impl Level {
+    /// Write the level to disk in the text level format
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Write the level out and read it back in, to check the format keeps everything
+    /// in it. Returns how many lines were written, or the first one that didn't come back
+    /// the same. Lines that come back the same can still hide a setting the writer leaves
+    /// out, so the level read back must also equal this one.
+    pub fn check_round_trip(&self) -> Result<usize, String> {
+        let written = self.to_string();
+        let level = Self::parse(&written)
+            .map_err(|err| format!("written level doesn't read back: {}", err))?;
+        let reread = level.to_string();
+        if let Some((before, after)) = written
+            .lines()
+            .zip(reread.lines())
+            .find(|(before, after)| before != after)
+        {
+            return Err(format!("'{}' came back as '{}'", before, after));
+        }
+        if written.lines().count() != reread.lines().count() {
+            return Err("lines went missing on the way back".to_string());
+        }
+        if level != *self {
+            return Err("the level read back differs from the one written".to_string());
+        }
+        Ok(written.lines().count())
+    }
+
+    /// Every id currently used by an entity in the level
+    pub fn ids(&self) -> HashSet<&str> {
+        self.platforms
+            .iter()
+            .map(|def| def.id.as_str())
+            .chain(self.terrain.iter().map(|def| def.id.as_str()))
+            .chain(self.water.iter().map(|def| def.id.as_str()))
+            .chain(self.collectibles.iter().map(|def| def.id.as_str()))
+            .chain(self.ziplines.iter().map(|def| def.id.as_str()))
+            .chain(self.bridges.iter().map(|def| def.id.as_str()))
+            .chain(self.ropes.iter().map(|def| def.id.as_str()))
+            .chain(self.pads.iter().map(|def| def.id.as_str()))
+            .chain(self.bombs.iter().map(|def| def.id.as_str()))
+            .chain(self.switches.iter().map(|def| def.id.as_str()))
+            .chain(self.doors.iter().map(|def| def.id.as_str()))
+            .chain(self.triggers.iter().map(|def| def.id.as_str()))
+            .chain(self.zones.iter().map(|def| def.id.as_str()))
+            .chain(self.npcs.iter().map(|def| def.id.as_str()))
+            .chain(self.props.iter().map(|def| def.id.as_str()))
+            .chain(self.bosses.iter().map(|def| def.id.as_str()))
+            .chain(self.enemies.iter().map(|def| def.id.as_str()))
+            .chain(self.spawn_points.iter().map(|def| def.id.as_str()))
+            .collect()
+    }
+
+    /// Generate an id that isn't used yet, e.g. "coin7"
+    pub fn fresh_id(&self, prefix: &str) -> String {
+        let ids = self.ids();
+        (1..)
+            .map(|n| format!("{}{}", prefix, n))
+            .find(|id| !ids.contains(id.as_str()))
+            .expect("ran out of ids")
+    }
+}
+
+/// Writes the level back out in the text level format
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let y = level_y;
+
+        writeln!(f, "name {}", self.name)?;
+        if !self.author.is_empty() {
+            writeln!(f, "author {}", self.author)?;
+        }
+        writeln!(f, "spawn {} {}", self.spawn.x, y(self.spawn.y))?;
+        writeln!(f, "dark {}", self.properties.dark)?;
+        writeln!(f, "rising_lava {}", self.properties.rising_lava)?;
+        if self.properties.wind != 0.0 {
+            writeln!(f, "wind {}", self.properties.wind)?;
+        }
+        if self.properties.out_of_bounds != OutOfBounds::Death {
+            writeln!(f, "out_of_bounds {}", self.properties.out_of_bounds.name())?;
+        }
+        if let Some(bounds) = self.properties.camera_bounds {
+            writeln!(f, "camera_bounds {} {}", bounds.left, bounds.right)?;
+        }
+        if let Some(par) = self.properties.par_time {
+            writeln!(f, "par {}", par)?;
+        }
+        if self.properties.required_stars > 0 {
+            writeln!(f, "requires_stars {}", self.properties.required_stars)?;
+        }
+        writeln!(f)?;
+
+        write!(f, "{}", self.entities())?;
+        for scene in &self.cutscenes {
+            for step in &scene.steps {
+                writeln!(f, "cutscene {} {}", scene.kind.name(), step)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Just the entity lines of a level, without its name, spawn point or properties
+pub struct LevelEntities<'a>(&'a Level);
+
+impl Level {
+    /// The entity lines alone, e.g. for an editor prefab
+    pub fn entities(&self) -> LevelEntities<'_> {
+        LevelEntities(self)
+    }
+}
+
+impl fmt::Display for LevelEntities<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.0;
+        let y = level_y;
+
+        for def in &level.platforms {
+            write!(
+                f,
+                "platform {} {} {} {} {} {}",
+                def.platform_type.name(),
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y
+            )?;
+            if let Some(cycle) = def.cycle {
+                write!(f, " timed {} {}", cycle.period, cycle.phase)?;
+            }
+            if let Some(path) = &def.path {
+                write!(
+                    f,
+                    " path {} {} {}",
+                    path.mode.name(),
+                    if path.smooth { "smooth" } else { "straight" },
+                    path.speed
+                )?;
+                for waypoint in &path.waypoints {
+                    write!(f, " {} {}", waypoint.x, y(waypoint.y))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        for def in &level.terrain {
+            writeln!(
+                f,
+                "terrain {} {} {} {} {}",
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y
+            )?;
+        }
+        for def in &level.water {
+            write!(
+                f,
+                "water {} {} {} {} {}",
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y
+            )?;
+            if def.current != Vec2::ZERO {
+                write!(f, " current {} {}", def.current.x, def.current.y)?;
+            }
+            writeln!(f)?;
+        }
+        for def in &level.collectibles {
+            writeln!(
+                f,
+                "collectible {} {} {} {}",
+                def.collectible_type.name(),
+                def.id,
+                def.position.x,
+                y(def.position.y)
+            )?;
+        }
+        for def in &level.ziplines {
+            writeln!(
+                f,
+                "zipline {} {} {} {} {}",
+                def.id,
+                def.start.x,
+                y(def.start.y),
+                def.end.x,
+                y(def.end.y)
+            )?;
+        }
+        for def in &level.bridges {
+            writeln!(
+                f,
+                "bridge {} {} {} {} {} {}",
+                def.id,
+                def.start.x,
+                y(def.start.y),
+                def.end.x,
+                y(def.end.y),
+                if def.fragile { "fragile" } else { "sturdy" }
+            )?;
+        }
+        for def in &level.ropes {
+            writeln!(
+                f,
+                "rope {} {} {} {}",
+                def.id,
+                def.anchor.x,
+                y(def.anchor.y),
+                def.length
+            )?;
+        }
+        for def in &level.pads {
+            match def.pad_kind {
+                PadKind::Spring => {
+                    writeln!(f, "pad spring {} {} {}", def.id, def.x, y(def.surface_y))?
+                }
+                PadKind::Spikes => {
+                    write!(f, "pad spikes {} {} {}", def.id, def.x, y(def.surface_y))?;
+                    if let Some(cycle) = def.cycle {
+                        write!(f, " timed {} {}", cycle.period, cycle.phase)?;
+                    }
+                    writeln!(f)?
+                }
+                PadKind::Boost { direction } => writeln!(
+                    f,
+                    "pad boost {} {} {} {}",
+                    def.id,
+                    def.x,
+                    y(def.surface_y),
+                    if direction < 0.0 { "left" } else { "right" }
+                )?,
+            }
+        }
+        for def in &level.bombs {
+            writeln!(f, "bomb {} {} {}", def.id, def.x, y(def.surface_y))?;
+        }
+        for def in &level.doors {
+            writeln!(
+                f,
+                "door {} {} {} {} {} {}",
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y,
+                def.open_time
+            )?;
+        }
+        for def in &level.switches {
+            writeln!(
+                f,
+                "switch {} {} {} {}",
+                def.id,
+                def.x,
+                y(def.surface_y),
+                def.door
+            )?;
+        }
+        for def in &level.triggers {
+            writeln!(
+                f,
+                "trigger {} {} {} {} {} {}",
+                def.action.name(),
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y
+            )?;
+        }
+        for def in &level.zones {
+            write!(
+                f,
+                "zone {} {} {} {} {} ",
+                def.id,
+                def.position.x,
+                y(def.position.y),
+                def.size.x,
+                def.size.y
+            )?;
+            match def.repeat {
+                Some(cooldown) => write!(f, "every {} ", cooldown)?,
+                None => write!(f, "once ")?,
+            }
+            write!(f, "{}", def.effect.name())?;
+            match &def.effect {
+                ZoneEffect::ShowTutorial(text) => write!(f, " {}", text)?,
+                ZoneEffect::StartBoulder | ZoneEffect::SetCheckpoint => {}
+                ZoneEffect::ChangeMusic(track) => write!(f, " {}", zone::music_name(*track))?,
+                ZoneEffect::SetWeather(weather) => {
+                    write!(f, " {}", weather.name())?;
+                    if let Weather::Wind(strength) = weather {
+                        write!(f, " {}", strength)?;
+                    }
+                }
+                ZoneEffect::CameraLock { position, size } => {
+                    write!(f, " {} {} {} {}", position.x, y(position.y), size.x, size.y)?
+                }
+                ZoneEffect::GiveScore(points) => write!(f, " {}", points)?,
+                ZoneEffect::Teleport(target) => write!(f, " {} {}", target.x, y(target.y))?,
+                ZoneEffect::AutoScroll { speed, end_x } => write!(f, " {} {}", speed, end_x)?,
+            }
+            writeln!(f)?;
+        }
+        for def in &level.npcs {
+            writeln!(
+                f,
+                "npc {} {} {} {}",
+                def.id,
+                def.x,
+                y(def.surface_y),
+                def.pages.join(" | ")
+            )?;
+        }
+        for def in &level.props {
+            writeln!(
+                f,
+                "prop {} {} {} {}",
+                def.kind.name(),
+                def.id,
+                def.position.x,
+                y(def.position.y)
+            )?;
+        }
+        for def in &level.bosses {
+            writeln!(
+                f,
+                "boss {} {} {} {} {}",
+                def.id,
+                def.x,
+                y(def.surface_y),
+                def.arena_left,
+                def.arena_right
+            )?;
+        }
+        for def in &level.enemies {
+            write!(
+                f,
+                "enemy {} {} {} {}",
+                def.kind.name(),
+                def.id,
+                def.x,
+                y(def.surface_y)
+            )?;
+            let behavior = &def.behavior;
+            if let Some((left, right)) = behavior.patrol {
+                write!(f, " patrol {} {}", left, right)?;
+            }
+            if let Some(radius) = behavior.detect_radius {
+                write!(f, " detect {}", radius)?;
+            }
+            if let Some(interval) = behavior.shoot_interval {
+                write!(f, " shoot {}", interval)?;
+            }
+            if let Some(switch) = &behavior.switch {
+                write!(f, " switch {}", switch)?;
+            }
+            writeln!(f)?;
+        }
+        for def in &level.spawn_points {
+            writeln!(f, "enemy_spawn {} {} {}", def.id, def.x, y(def.surface_y))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a floor-relative level y coordinate into a world y coordinate
+fn world_y(level_y: f32) -> f32 {
+    GameConfig::LEVEL_FLOOR_Y + level_y
+}
+
+/// Convert a world y coordinate into a floor-relative level y coordinate
+fn level_y(world_y: f32) -> f32 {
+    world_y - GameConfig::LEVEL_FLOOR_Y
+}
+
+/// Tokenizer for a single level file line
+struct LineParser<'a> {
+    tokens: std::iter::Peekable<std::str::SplitWhitespace<'a>>,
+    content: &'a str,
+    line: usize,
+}
+
+impl<'a> LineParser<'a> {
+    fn new(content: &'a str, line: usize) -> Self {
+        Self {
+            tokens: content.split_whitespace().peekable(),
+            content,
+            line,
+        }
+    }
+
+    fn error(&self, message: String) -> LevelError {
+        LevelError {
+            line: self.line,
+            message,
+        }
+    }
+
+    fn word(&mut self) -> Result<&'a str, LevelError> {
+        self.tokens
+            .next()
+            .ok_or_else(|| self.error(format!("missing argument in '{}'", self.content)))
+    }
+
+    fn number(&mut self) -> Result<f32, LevelError> {
+        let word = self.word()?;
+        word.parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| self.error(format!("expected a number, got '{}'", word)))
+    }
+
+    fn y(&mut self) -> Result<f32, LevelError> {
+        Ok(world_y(self.number()?))
+    }
+
+    fn point(&mut self) -> Result<Vec2, LevelError> {
+        let x = self.number()?;
+        let y = self.y()?;
+        Ok(Vec2::new(x, y))
+    }
+
+    fn flag(&mut self) -> Result<bool, LevelError> {
+        match self.word()? {
+            "true" | "on" | "yes" => Ok(true),
+            "false" | "off" | "no" => Ok(false),
+            other => Err(self.error(format!("expected true or false, got '{}'", other))),
+        }
+    }
+
+    /// Read an entity id, rejecting ids already used elsewhere in the level
+    fn id(&mut self, ids: &mut HashSet<String>) -> Result<String, LevelError> {
+        let id = self.word()?.to_string();
+        if !ids.insert(id.clone()) {
+            return Err(self.error(format!("duplicate id '{}'", id)));
+        }
+        Ok(id)
+    }
+
+    /// A zone's effect name and its settings
+    fn zone_effect(&mut self) -> Result<ZoneEffect, LevelError> {
+        let effect = match self.word()? {
+            "tutorial" => {
+                let text = self.rest();
+                if text.is_empty() {
+                    return Err(self.error("tutorial zone needs some text".to_string()));
+                }
+                ZoneEffect::ShowTutorial(text)
+            }
+            "boulder" => ZoneEffect::StartBoulder,
+            "checkpoint" => ZoneEffect::SetCheckpoint,
+            "music" => {
+                let name = self.word()?;
+                let track = zone::music_from_name(name).ok_or_else(|| {
+                    self.error(format!("music must be main or off, got '{}'", name))
+                })?;
+                ZoneEffect::ChangeMusic(track)
+            }
+            "weather" => ZoneEffect::SetWeather(match self.word()? {
+                "clear" => Weather::Clear,
+                "dark" => Weather::Dark,
+                "wind" => Weather::Wind(self.number()?),
+                other => {
+                    return Err(self.error(format!(
+                        "weather must be clear, dark or wind, got '{}'",
+                        other
+                    )))
+                }
+            }),
+            "camera" => {
+                let position = self.point()?;
+                let size = Vec2::new(self.number()?, self.number()?);
+                if size.x <= 0.0 || size.y <= 0.0 {
+                    return Err(self.error("camera lock size must be positive".to_string()));
+                }
+                ZoneEffect::CameraLock { position, size }
+            }
+            "score" => ZoneEffect::GiveScore(self.number()? as i32),
+            "teleport" => ZoneEffect::Teleport(self.point()?),
+            "scroll" => {
+                let speed = self.number()?;
+                if speed <= 0.0 {
+                    return Err(self.error("scroll speed must be positive".to_string()));
+                }
+                ZoneEffect::AutoScroll {
+                    speed,
+                    end_x: self.number()?,
+                }
+            }
+            other => return Err(self.error(format!("unknown zone effect '{}'", other))),
+        };
+        Ok(effect)
+    }
+
+    /// One step of a cutscene: `pan <x> <y> <seconds>`, `pan player <seconds>`, `say
+    /// <text>`, `move <npc id> <seconds> <x> <y> ...`, `wait <seconds>` or `fade <out|in>
+    /// <seconds>`
+    fn cutscene_step(&mut self) -> Result<CutsceneStep, LevelError> {
+        let seconds = |parser: &mut Self| -> Result<f32, LevelError> {
+            let seconds = parser.number()?;
+            if seconds < 0.0 {
+                return Err(parser.error("cutscene times can't be negative".to_string()));
+            }
+            Ok(seconds)
+        };
+        let step = match self.word()? {
+            "pan" => {
+                let target = if self.tokens.peek() == Some(&"player") {
+                    self.tokens.next();
+                    None
+                } else {
+                    Some(self.point()?)
+                };
+                CutsceneStep::Pan {
+                    target,
+                    duration: seconds(self)?,
+                }
+            }
+            "say" => {
+                let text = self.rest();
+                if text.is_empty() {
+                    return Err(self.error("cutscene line needs some text".to_string()));
+                }
+                CutsceneStep::Say(text)
+            }
+            "move" => {
+                let actor = self.word()?.to_string();
+                let duration = seconds(self)?;
+                let mut path = Vec::new();
+                while self.has_more() {
+                    path.push(self.point()?);
+                }
+                if path.is_empty() {
+                    return Err(self.error("a cutscene move needs somewhere to go".to_string()));
+                }
+                CutsceneStep::Move {
+                    actor,
+                    duration,
+                    path,
+                }
+            }
+            "wait" => CutsceneStep::Wait(seconds(self)?),
+            "fade" => {
+                let out = match self.word()? {
+                    "out" => true,
+                    "in" => false,
+                    other => {
+                        return Err(self.error(format!("fade must be out or in, got '{}'", other)))
+                    }
+                };
+                CutsceneStep::Fade {
+                    out,
+                    duration: seconds(self)?,
+                }
+            }
+            other => return Err(self.error(format!("unknown cutscene step '{}'", other))),
+        };
+        Ok(step)
+    }
+
+    /// A moving platform's path: the word `path`, its mode, straight or smooth, its speed
+    /// and then the waypoints after the start, to the end of the line
+    fn path(&mut self) -> Result<PathDef, LevelError> {
+        let word = self.word()?;
+        if word != "path" {
+            return Err(self.error(format!("expected 'path', got '{}'", word)));
+        }
+        let name = self.word()?;
+        let mode = PathMode::from_name(name).ok_or_else(|| {
+            self.error(format!(
+                "path mode must be loop or pingpong, got '{}'",
+                name
+            ))
+        })?;
+        let smooth = match self.word()? {
+            "straight" => false,
+            "smooth" => true,
+            other => {
+                return Err(self.error(format!("path must be straight or smooth, got '{}'", other)))
+            }
+        };
+        let speed = self.number()?;
+        if speed <= 0.0 {
+            return Err(self.error("path speed must be positive".to_string()));
+        }
+        let mut waypoints = Vec::new();
+        while self.has_more() {
+            waypoints.push(self.point()?);
+        }
+        if waypoints.is_empty() {
+            return Err(self.error("a path needs a waypoint after the start".to_string()));
+        }
+        Ok(PathDef {
+            waypoints,
+            speed,
+            mode,
+            smooth,
+        })
+    }
+
+    /// An enemy's optional settings, in any order to the end of the line: `patrol <left x>
+    /// <right x>`, `detect <radius>`, `shoot <seconds>` and `switch <id>`
+    fn enemy_behavior(&mut self) -> Result<EnemyBehavior, LevelError> {
+        let mut behavior = EnemyBehavior::default();
+        while self.has_more() {
+            match self.word()? {
+                "patrol" => {
+                    let left = self.number()?;
+                    let right = self.number()?;
+                    if right <= left {
+                        return Err(self.error("patrol right must be past left".to_string()));
+                    }
+                    behavior.patrol = Some((left, right));
+                }
+                "detect" => {
+                    let radius = self.number()?;
+                    if radius <= 0.0 {
+                        return Err(self.error("detect radius must be positive".to_string()));
+                    }
+                    behavior.detect_radius = Some(radius);
+                }
+                "shoot" => {
+                    let interval = self.number()?;
+                    if interval <= 0.0 {
+                        return Err(self.error("shoot interval must be positive".to_string()));
+                    }
+                    behavior.shoot_interval = Some(interval);
+                }
+                "switch" => behavior.switch = Some(self.word()?.to_string()),
+                other => {
+                    return Err(self.error(format!(
+                        "expected patrol, detect, shoot or switch, got '{}'",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(behavior)
+    }
+
+    /// `timed <period> <phase>`, in beats of the level clock, if that's what comes next
+    fn cycle(&mut self) -> Result<Option<Cycle>, LevelError> {
+        if self.tokens.peek() != Some(&"timed") {
+            return Ok(None);
+        }
+        self.tokens.next();
+        let period = self.number()?;
+        if period <= 0.0 {
+            return Err(self.error("timed period must be positive".to_string()));
+        }
+        let phase = self.number()?;
+        Ok(Some(Cycle::new(period, phase)))
+    }
+
+    /// Whether anything is left on the line
+    fn has_more(&mut self) -> bool {
+        self.tokens.peek().is_some()
+    }
+
+    /// Everything left on the line, used for free-text values
+    fn rest(&mut self) -> String {
+        self.tokens.by_ref().collect::<Vec<_>>().join(" ")
+    }
+
+    fn finish(&mut self) -> Result<(), LevelError> {
+        match self.tokens.next() {
+            Some(extra) => Err(self.error(format!("unexpected extra argument '{}'", extra))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEW: f32 = 800.0;
+
+    fn bounds(left: f32, right: f32) -> CameraBounds {
+        CameraBounds { left, right }
+    }
+
+    #[test]
+    fn camera_follows_freely_between_the_bounds() {
+        assert_eq!(bounds(0.0, 3000.0).clamp(1200.0, VIEW), 1200.0);
+    }
+
+    #[test]
+    fn camera_stops_dead_at_the_left_edge() {
+        assert_eq!(bounds(0.0, 3000.0).clamp(-250.0, VIEW), 0.0);
+        assert_eq!(bounds(500.0, 3000.0).clamp(480.0, VIEW), 500.0);
+    }
+
+    #[test]
+    fn camera_stops_dead_at_the_right_edge() {
+        // The view's left edge stops a view's width short of the right bound
+        assert_eq!(bounds(0.0, 3000.0).clamp(2900.0, VIEW), 2200.0);
+        assert_eq!(bounds(0.0, 3000.0).clamp(2200.0, VIEW), 2200.0);
+    }
+
+    #[test]
+    fn narrow_levels_are_centered() {
+        // 400 wide in an 800 wide view leaves 200 either side, wherever the player is
+        let narrow = bounds(100.0, 500.0);
+        assert_eq!(narrow.clamp(0.0, VIEW), -100.0);
+        assert_eq!(narrow.clamp(450.0, VIEW), -100.0);
+        // Exactly as wide as the view just shows it all
+        assert_eq!(bounds(0.0, VIEW).clamp(300.0, VIEW), 0.0);
+    }
+
+    #[test]
+    fn the_schema_level_survives_a_round_trip() {
+        let source = include_str!("../../levels/schema.lvl");
+        let level = Level::parse(source).unwrap();
+        let written = level.to_string();
+        let reread = Level::parse(&written).unwrap();
+        assert_eq!(reread, level);
+        assert_eq!(reread.to_string(), written);
+    }
+
+    #[test]
+    fn shipped_levels_survive_a_round_trip() {
+        for source in [DEFAULT_LEVEL_SOURCE, SURVIVAL_LEVEL_SOURCE] {
+            let level = Level::parse(source).unwrap();
+            assert!(level.check_round_trip().is_ok_and(|lines| lines > 0));
+        }
+    }
+}
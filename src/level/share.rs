@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{Level, LevelError};
+
+/// Folder imported and player-made levels are saved in
+pub const CUSTOM_LEVELS_DIR: &str = "levels/custom";
+
+/// Every level code starts with this, so other text pasted by mistake is easy to spot and
+/// a later format can be told apart
+pub const SHARE_CODE_PREFIX: &str = "PLV1:";
+
+/// Codes longer than this are awkward to paste into chat, so exporting one warns about it
+pub const SHARE_CODE_WARN_LENGTH: usize = 8 * 1024;
+
+/// Longest code that will be decoded at all
+pub const MAX_SHARE_CODE_LENGTH: usize = 256 * 1024;
+
+/// Largest level text a code may unpack to, checked before anything is unpacked
+const MAX_LEVEL_SOURCE_LENGTH: usize = 1024 * 1024;
+
+/// Back-references reach this many bytes behind and copy up to `MAX_MATCH` of them
+const WINDOW_SIZE: usize = 4096;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = MIN_MATCH + 15;
+/// Earlier places with the same three bytes tried for each back-reference
+const MAX_CANDIDATES: usize = 64;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The level as a single line of text: its level file, compressed and base64-encoded
+/// behind `SHARE_CODE_PREFIX`
+pub fn export_code(level: &Level) -> String {
+    let source = level.to_string();
+    let bytes = source.as_bytes();
+    let mut packed = Vec::with_capacity(bytes.len() / 2 + 8);
+    packed.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    packed.extend_from_slice(&checksum(bytes).to_le_bytes());
+    packed.extend(compress(bytes));
+    format!("{}{}", SHARE_CODE_PREFIX, base64_encode(&packed))
+}
+
+/// The level in a code made by `export_code`. Whitespace in the code is ignored, so one
+/// wrapped across lines still reads. Codes that are damaged, too big, or hold a level
+/// with errors are turned away.
+pub fn import_code(code: &str) -> Result<Level, LevelError> {
+    let code: String = code.split_whitespace().collect();
+    if code.is_empty() {
+        return Err(share_error("no level code to import"));
+    }
+    if code.len() > MAX_SHARE_CODE_LENGTH {
+        return Err(share_error(format!(
+            "level code is too long ({} characters, at most {})",
+            code.len(),
+            MAX_SHARE_CODE_LENGTH
+        )));
+    }
+    let Some(body) = code.strip_prefix(SHARE_CODE_PREFIX) else {
+        return Err(share_error(format!(
+            "not a level code; they start with {}",
+            SHARE_CODE_PREFIX
+        )));
+    };
+
+    let corrupt = |detail: &str| share_error(format!("level code is damaged ({})", detail));
+    let packed = base64_decode(body).ok_or_else(|| corrupt("not valid base64"))?;
+    if packed.len() < 8 {
+        return Err(corrupt("cut short"));
+    }
+    let length = u32::from_le_bytes([packed[0], packed[1], packed[2], packed[3]]) as usize;
+    let expected = u32::from_le_bytes([packed[4], packed[5], packed[6], packed[7]]);
+    if length > MAX_LEVEL_SOURCE_LENGTH {
+        return Err(share_error(format!(
+            "level code unpacks to {} KB, more than the {} KB allowed",
+            length / 1024,
+            MAX_LEVEL_SOURCE_LENGTH / 1024
+        )));
+    }
+    let bytes = decompress(&packed[8..], length).ok_or_else(|| corrupt("cut short"))?;
+    if checksum(&bytes) != expected {
+        return Err(corrupt("checksum doesn't match"));
+    }
+    let source = String::from_utf8(bytes).map_err(|_| corrupt("not text"))?;
+
+    let level = Level::parse(&source)?;
+    if let Some(issue) = level.first_error() {
+        return Err(share_error(format!(
+            "the level can't be played: {}",
+            issue.message
+        )));
+    }
+    Ok(level)
+}
+
+/// Save a level as a new file in `CUSTOM_LEVELS_DIR`, named after the level and never
+/// replacing one that's already there
+pub fn save_custom(level: &Level) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(CUSTOM_LEVELS_DIR)?;
+    let stem = file_stem(&level.name);
+    let path = (1..)
+        .map(|n| match n {
+            1 => format!("{}.lvl", stem),
+            n => format!("{}-{}.lvl", stem, n),
+        })
+        .map(|file| Path::new(CUSTOM_LEVELS_DIR).join(file))
+        .find(|path| !path.exists())
+        .expect("some numbered file name is free");
+    level.save(&path)?;
+    Ok(path)
+}
+
+fn share_error(message: impl Into<String>) -> LevelError {
+    LevelError {
+        line: 0,
+        message: message.into(),
+    }
+}
+
+/// Lower-case letters, digits and dashes from a level name
+fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() {
+        "level".to_string()
+    } else {
+        stem.chars().take(40).collect()
+    }
+}
+
+/// FNV-1a, to notice a code that was changed in a way that still unpacks
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// LZSS: a flag byte says which of the next eight items are literal bytes (bit set) and
+/// which are two-byte back-references, packing the distance back less one into 12 bits
+/// and the length less `MIN_MATCH` into 4
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2);
+    // Where each three-byte sequence has been seen, most recent last
+    let mut seen: HashMap<[u8; 3], Vec<usize>> = HashMap::new();
+    let mut flag_index = 0;
+    let mut items = 8;
+    let mut position = 0;
+
+    while position < input.len() {
+        if items == 8 {
+            flag_index = output.len();
+            output.push(0);
+            items = 0;
+        }
+
+        let mut best = (0, 0);
+        if let Some(key) = triple(input, position) {
+            for &start in seen
+                .get(&key)
+                .into_iter()
+                .flatten()
+                .rev()
+                .take(MAX_CANDIDATES)
+            {
+                let distance = position - start;
+                if distance > WINDOW_SIZE {
+                    break;
+                }
+                let length = input[position..]
+                    .iter()
+                    .zip(&input[start..])
+                    .take(MAX_MATCH)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best.1 {
+                    best = (distance, length);
+                }
+            }
+        }
+
+        let (distance, length) = best;
+        let advance = if length >= MIN_MATCH {
+            let packed = ((distance - 1) << 4) | (length - MIN_MATCH);
+            output.push((packed >> 8) as u8);
+            output.push(packed as u8);
+            length
+        } else {
+            output[flag_index] |= 1 << items;
+            output.push(input[position]);
+            1
+        };
+        for skipped in position..position + advance {
+            if let Some(key) = triple(input, skipped) {
+                seen.entry(key).or_default().push(skipped);
+            }
+        }
+        position += advance;
+        items += 1;
+    }
+    output
+}
+
+fn triple(input: &[u8], position: usize) -> Option<[u8; 3]> {
+    input.get(position..position + 3)?.try_into().ok()
+}
+
+/// Undo `compress`, giving up on a stream that runs out or points back before its start
+/// instead of producing `length` bytes
+fn decompress(input: &[u8], length: usize) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(length);
+    let mut bytes = input.iter().copied();
+
+    while output.len() < length {
+        let flags = bytes.next()?;
+        for item in 0..8 {
+            if output.len() >= length {
+                break;
+            }
+            if flags & (1 << item) != 0 {
+                output.push(bytes.next()?);
+                continue;
+            }
+            let packed = (usize::from(bytes.next()?) << 8) | usize::from(bytes.next()?);
+            let distance = (packed >> 4) + 1;
+            let count = (packed & 0xF) + MIN_MATCH;
+            let start = output.len().checked_sub(distance)?;
+            for offset in 0..count {
+                output.push(output[start + offset]);
+            }
+        }
+    }
+
+    (output.len() == length && bytes.next().is_none()).then_some(output)
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let group = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3F;
+                text.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut group = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        group = (group << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((group >> bits) as u8);
+        }
+    }
+    // A lone leftover character can't have come from whole bytes
+    (bits < 6).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_SOURCE: &str = include_str!("../../levels/schema.lvl");
+
+    fn round_trip(source: &str) {
+        let level = Level::parse(source).expect("level parses");
+        let code = export_code(&level);
+        let imported = import_code(&code).expect("code imports");
+        assert_eq!(imported.to_string(), level.to_string());
+    }
+
+    /// The code's packed bytes, to damage on purpose
+    fn packed(code: &str) -> Vec<u8> {
+        base64_decode(code.strip_prefix(SHARE_CODE_PREFIX).unwrap()).unwrap()
+    }
+
+    fn repack(bytes: &[u8]) -> String {
+        format!("{}{}", SHARE_CODE_PREFIX, base64_encode(bytes))
+    }
+
+    #[test]
+    fn default_level_round_trips() {
+        round_trip(super::super::DEFAULT_LEVEL_SOURCE);
+    }
+
+    #[test]
+    fn schema_level_round_trips() {
+        round_trip(SCHEMA_SOURCE);
+    }
+
+    #[test]
+    fn compression_round_trips() {
+        for input in [
+            &b""[..],
+            b"a",
+            b"abcabcabcabcabcabc",
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        ] {
+            let packed = compress(input);
+            assert_eq!(decompress(&packed, input.len()).as_deref(), Some(input));
+        }
+    }
+
+    #[test]
+    fn base64_round_trips_every_padding() {
+        for bytes in [&b""[..], b"f", b"fo", b"foo", b"foob"] {
+            assert_eq!(base64_decode(&base64_encode(bytes)).as_deref(), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn flipped_byte_fails_the_checksum() {
+        let level = Level::load_default();
+        let mut bytes = packed(&export_code(&level));
+        // The first item after the first flag byte is always a literal, so changing it
+        // still unpacks
+        bytes[9] ^= 0x01;
+        let err = import_code(&repack(&bytes)).unwrap_err();
+        assert!(err.message.contains("checksum"), "{}", err.message);
+    }
+
+    #[test]
+    fn truncated_code_is_rejected() {
+        let code = export_code(&Level::load_default());
+        let cut = &code[..code.len() / 2];
+        assert!(import_code(cut).is_err());
+        let err = import_code(&code[..SHARE_CODE_PREFIX.len() + 4]).unwrap_err();
+        assert!(err.message.contains("cut short"), "{}", err.message);
+    }
+
+    #[test]
+    fn bad_prefix_is_rejected() {
+        let code = export_code(&Level::load_default());
+        let body = code.strip_prefix(SHARE_CODE_PREFIX).unwrap();
+        let err = import_code(&format!("PLV9:{}", body)).unwrap_err();
+        assert!(err.message.contains("not a level code"), "{}", err.message);
+    }
+
+    #[test]
+    fn long_codes_still_import() {
+        // Collectibles scattered around enough to defeat the compression
+        let mut source = super::super::DEFAULT_LEVEL_SOURCE.to_string();
+        let mut rng = crate::rng::SeededRng::new(7);
+        for i in 0..2000 {
+            source.push_str(&format!(
+                "collectible coin extra{} {} {}\n",
+                i,
+                rng.range(0.0, 3000.0) as i32,
+                -rng.range(60.0, 600.0) as i32,
+            ));
+        }
+        let level = Level::parse(&source).expect("level parses");
+        let code = export_code(&level);
+        assert!(code.len() > SHARE_CODE_WARN_LENGTH);
+        assert_eq!(import_code(&code).unwrap().to_string(), level.to_string());
+    }
+
+    #[test]
+    fn oversized_code_is_rejected_before_decoding() {
+        let code = format!("{}{}", SHARE_CODE_PREFIX, "A".repeat(MAX_SHARE_CODE_LENGTH));
+        let err = import_code(&code).unwrap_err();
+        assert!(err.message.contains("too long"), "{}", err.message);
+    }
+}
@@ -0,0 +1,467 @@
+use std::fmt;
+
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::pad::PadKind;
+use crate::entities::zone::ZoneEffect;
+
+use super::{CutsceneStep, Level, PlatformDef};
+
+/// Platforms sharing more than this fraction of the smaller one's area are reported
+const OVERLAP_WARNING_FRACTION: f32 = 0.25;
+/// Points tested along a zipline for where it can be grabbed and let go of
+const ZIPLINE_SAMPLE_SPACING: f32 = 32.0;
+
+/// How bad a problem is: warnings are shown but the level can still be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found by `Level::validate`, at a world position the editor can show
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelIssue {
+    pub severity: Severity,
+    pub message: String,
+    pub position: Vec2,
+}
+
+impl LevelIssue {
+    fn warning(message: String, position: Vec2) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+            position,
+        }
+    }
+
+    fn error(message: String, position: Vec2) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+            position,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {:.0}, {:.0}",
+            self.message, self.position.x, self.position.y
+        )
+    }
+}
+
+/// Somewhere the player can set off from: standing on a platform, falling from the spawn
+/// or letting go of a zipline. Heights are world y, so smaller is higher.
+#[derive(Debug, Clone, Copy)]
+struct Launch {
+    left: f32,
+    right: f32,
+    /// Where the player's feet are
+    y: f32,
+    /// Upward speed of the first jump, e.g. more from a spring
+    rise_speed: f32,
+    /// Jumps available, the first at `rise_speed` and the rest at the normal jump force
+    jumps: u32,
+    run_speed: f32,
+}
+
+impl Launch {
+    /// Standing on the top of `def`
+    fn standing(def: &PlatformDef) -> Self {
+        Self::on_foot(def.position.x, def.position.x + def.size.x, def.position.y)
+    }
+
+    fn on_foot(left: f32, right: f32, y: f32) -> Self {
+        Self {
+            left,
+            right,
+            y,
+            rise_speed: -GameConfig::PLAYER_JUMP_FORCE,
+            jumps: GameConfig::PLAYER_MAX_JUMPS,
+            run_speed: GameConfig::PLAYER_MOVE_SPEED * GameConfig::SPRINT_MULTIPLIER,
+        }
+    }
+
+    /// In the air at `point` with only the air jumps left
+    fn airborne(point: Vec2) -> Self {
+        Self {
+            left: point.x,
+            right: point.x + GameConfig::PLAYER_SIZE.0,
+            y: point.y,
+            rise_speed: -GameConfig::PLAYER_JUMP_FORCE,
+            jumps: GameConfig::PLAYER_MAX_JUMPS.saturating_sub(1),
+            run_speed: GameConfig::PLAYER_MOVE_SPEED * GameConfig::SPRINT_MULTIPLIER,
+        }
+    }
+
+    /// Highest the player's feet can get above `y`, with every jump used at its peak
+    fn max_height(&self) -> f32 {
+        if self.jumps == 0 {
+            return 0.0;
+        }
+        let gravity = GameConfig::GRAVITY;
+        let jump = -GameConfig::PLAYER_JUMP_FORCE;
+        let first = self.rise_speed * self.rise_speed / (2.0 * gravity);
+        first + (self.jumps - 1) as f32 * jump * jump / (2.0 * gravity)
+    }
+
+    /// How far sideways the player can travel to land `rise` above where they set off
+    /// (negative for lower), or None if it's too high to reach
+    fn reach(&self, rise: f32) -> Option<f32> {
+        let height = self.max_height();
+        if rise > height {
+            return None;
+        }
+        let gravity = GameConfig::GRAVITY;
+        let rise_time = if self.jumps == 0 {
+            0.0
+        } else {
+            (self.rise_speed + (self.jumps - 1) as f32 * -GameConfig::PLAYER_JUMP_FORCE) / gravity
+        };
+        let fall_gravity = gravity * GameConfig::FALL_GRAVITY_SCALE;
+        let fall_time = (2.0 * (height - rise) / fall_gravity).sqrt();
+        Some(self.run_speed * (rise_time + fall_time))
+    }
+
+    /// Whether the player's feet can get to height `y` anywhere between `left` and `right`
+    fn can_reach(&self, left: f32, right: f32, y: f32) -> bool {
+        let gap = (left - self.right).max(self.left - right).max(0.0);
+        self.reach(self.y - y).is_some_and(|reach| gap <= reach)
+    }
+}
+
+/// Overlapping area of two rectangles given as position and size
+fn overlap_area(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> f32 {
+    let width = (a.0.x + a.1.x).min(b.0.x + b.1.x) - a.0.x.max(b.0.x);
+    let height = (a.0.y + a.1.y).min(b.0.y + b.1.y) - a.0.y.max(b.0.y);
+    width.max(0.0) * height.max(0.0)
+}
+
+impl Level {
+    /// Look for mistakes that make the level broken or hard to finish. Errors mean it
+    /// can't be played; warnings are worth a look but don't stop anything.
+    pub fn validate(&self) -> Vec<LevelIssue> {
+        let mut issues = Vec::new();
+        let player = Vec2::from(GameConfig::PLAYER_SIZE);
+        let collectible = Vec2::from(GameConfig::COLLECTIBLE_SIZE);
+
+        // The spawn point
+        if let Some(def) = self
+            .platforms
+            .iter()
+            .find(|def| overlap_area((self.spawn, player), (def.position, def.size)) > 0.0)
+        {
+            issues.push(LevelIssue::error(
+                format!("The spawn point is inside platform '{}'", def.id),
+                self.spawn,
+            ));
+        }
+        let start = Launch {
+            jumps: 0,
+            ..Launch::airborne(self.spawn + Vec2::new(0.0, player.y))
+        };
+        let has_ground = self.platforms.iter().any(|def| {
+            def.position.y >= start.y
+                && def.position.x < start.right
+                && def.position.x + def.size.x > start.left
+        });
+        if !has_ground {
+            issues.push(LevelIssue::error(
+                "Nothing below the spawn point to land on".to_string(),
+                self.spawn,
+            ));
+        }
+
+        // Things in the wrong place
+        for def in &self.collectibles {
+            let center = def.position + collectible / 2.0;
+            if let Some(platform) = self.platforms.iter().find(|platform| {
+                Rect::new(
+                    platform.position.x,
+                    platform.position.y,
+                    platform.size.x,
+                    platform.size.y,
+                )
+                .contains(center)
+            }) {
+                issues.push(LevelIssue::warning(
+                    format!("'{}' is inside platform '{}'", def.id, platform.id),
+                    def.position,
+                ));
+            }
+        }
+        for def in &self.zones {
+            let ZoneEffect::Teleport(target) = def.effect else {
+                continue;
+            };
+            if let Some(platform) = self.platforms.iter().find(|platform| {
+                overlap_area((target, player), (platform.position, platform.size)) > 0.0
+            }) {
+                issues.push(LevelIssue::error(
+                    format!(
+                        "Zone '{}' teleports into platform '{}'",
+                        def.id, platform.id
+                    ),
+                    target,
+                ));
+            }
+        }
+        for scene in &self.cutscenes {
+            for step in &scene.steps {
+                let CutsceneStep::Move { actor, path, .. } = step else {
+                    continue;
+                };
+                if !self.npcs.iter().any(|npc| npc.id == *actor) {
+                    issues.push(LevelIssue::warning(
+                        format!(
+                            "The {} cutscene moves '{}', which isn't an npc",
+                            scene.kind.name(),
+                            actor
+                        ),
+                        path[0],
+                    ));
+                }
+            }
+        }
+        for (index, a) in self.platforms.iter().enumerate() {
+            for b in &self.platforms[index + 1..] {
+                let shared = overlap_area((a.position, a.size), (b.position, b.size));
+                let smaller = (a.size.x * a.size.y).min(b.size.x * b.size.y);
+                if smaller > 0.0 && shared / smaller > OVERLAP_WARNING_FRACTION {
+                    issues.push(LevelIssue::warning(
+                        format!("Platforms '{}' and '{}' overlap", a.id, b.id),
+                        a.position.max(b.position),
+                    ));
+                }
+            }
+        }
+
+        // What can be reached from the spawn
+        let reachable = self.reachable_platforms(start);
+        let mut launches: Vec<Launch> = vec![start];
+        launches.extend(
+            reachable
+                .iter()
+                .enumerate()
+                .filter(|(_, &reached)| reached)
+                .map(|(index, _)| self.launch_from(index)),
+        );
+        launches.extend(self.zipline_drops(&launches));
+
+        match self.bosses.first() {
+            None => issues.push(LevelIssue::warning(
+                "No boss, so the level can't be won".to_string(),
+                self.spawn,
+            )),
+            Some(boss) => {
+                let reached = launches.iter().any(|launch| {
+                    launch.can_reach(boss.x - player.x, boss.x + player.x, boss.surface_y)
+                });
+                if !reached {
+                    issues.push(self.gap_issue(&reachable, boss.x));
+                }
+            }
+        }
+
+        for def in &self.collectibles {
+            // Touching it with the top of the player's head is enough
+            let feet = def.position.y + collectible.y + player.y;
+            let reached = launches.iter().any(|launch| {
+                launch.can_reach(
+                    def.position.x - player.x,
+                    def.position.x + collectible.x,
+                    feet,
+                )
+            });
+            if !reached {
+                issues.push(LevelIssue::warning(
+                    format!("'{}' can't be reached from the spawn", def.id),
+                    def.position,
+                ));
+            }
+        }
+
+        issues
+    }
+
+    /// The first error, which stops the level being played
+    pub fn first_error(&self) -> Option<LevelIssue> {
+        self.validate().into_iter().find(LevelIssue::is_error)
+    }
+
+    /// Top-left corner for the player standing on the first platform straight below
+    /// `point`, centered on it and clear of every platform, for play-tests that start
+    /// partway through the level
+    pub fn safe_spot_below(&self, point: Vec2) -> Option<Vec2> {
+        let player = Vec2::from(GameConfig::PLAYER_SIZE);
+        let left = point.x - player.x / 2.0;
+        let mut tops: Vec<f32> = self
+            .platforms
+            .iter()
+            .filter(|def| {
+                def.position.y >= point.y
+                    && def.position.x < left + player.x
+                    && def.position.x + def.size.x > left
+            })
+            .map(|def| def.position.y)
+            .collect();
+        tops.sort_by(f32::total_cmp);
+        // A surface with something else sitting on it is skipped for the next one down
+        tops.into_iter()
+            .map(|top| Vec2::new(left, top - player.y))
+            .find(|&spot| {
+                self.platforms
+                    .iter()
+                    .all(|def| overlap_area((spot, player), (def.position, def.size)) == 0.0)
+            })
+    }
+
+    /// Standing on platform `index`, taking any spring or boost pad on it into account
+    fn launch_from(&self, index: usize) -> Launch {
+        let def = &self.platforms[index];
+        let mut launch = Launch::standing(def);
+        for pad in &self.pads {
+            let on_top = (pad.surface_y - def.position.y).abs() < 1.0
+                && pad.x < launch.right
+                && pad.x > launch.left - GameConfig::SPRING_SIZE.0;
+            if !on_top {
+                continue;
+            }
+            match pad.pad_kind {
+                PadKind::Spring => launch.rise_speed = -GameConfig::SPRING_LAUNCH_VELOCITY,
+                PadKind::Boost { .. } => launch.run_speed = GameConfig::BOOST_MAX_SPEED,
+                PadKind::Spikes => {}
+            }
+        }
+        launch
+    }
+
+    /// Platforms the player can get onto from `start`, hopping from one to the next and
+    /// riding ziplines
+    fn reachable_platforms(&self, start: Launch) -> Vec<bool> {
+        let mut reached = vec![false; self.platforms.len()];
+        let mut launches = vec![start];
+        let ziplines = self.zipline_samples();
+        let mut used_ziplines = vec![false; ziplines.len()];
+
+        while let Some(launch) = launches.pop() {
+            for (index, def) in self.platforms.iter().enumerate() {
+                if !reached[index]
+                    && launch.can_reach(def.position.x, def.position.x + def.size.x, def.position.y)
+                {
+                    reached[index] = true;
+                    launches.push(self.launch_from(index));
+                }
+            }
+            for (index, samples) in ziplines.iter().enumerate() {
+                if used_ziplines[index] || !self.can_grab(&launch, samples) {
+                    continue;
+                }
+                used_ziplines[index] = true;
+                launches.extend(self.ride_from(samples));
+            }
+        }
+        reached
+    }
+
+    /// Points along each zipline, from its top end to its bottom end
+    fn zipline_samples(&self) -> Vec<Vec<Vec2>> {
+        self.ziplines
+            .iter()
+            .map(|def| {
+                let (top, bottom) = if def.start.y <= def.end.y {
+                    (def.start, def.end)
+                } else {
+                    (def.end, def.start)
+                };
+                let steps = (top.distance(bottom) / ZIPLINE_SAMPLE_SPACING)
+                    .ceil()
+                    .max(1.0) as usize;
+                (0..=steps)
+                    .map(|step| top.lerp(bottom, step as f32 / steps as f32))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Whether the player's hands can get to any point along a zipline
+    fn can_grab(&self, launch: &Launch, samples: &[Vec2]) -> bool {
+        let player = Vec2::from(GameConfig::PLAYER_SIZE);
+        samples
+            .iter()
+            .any(|point| launch.can_reach(point.x - player.x, point.x, point.y + player.y))
+    }
+
+    /// Everywhere the player can let go once riding a zipline, which only slides downhill
+    fn ride_from(&self, samples: &[Vec2]) -> Vec<Launch> {
+        let player = Vec2::from(GameConfig::PLAYER_SIZE);
+        samples
+            .iter()
+            .map(|point| Launch::airborne(*point + Vec2::new(-player.x / 2.0, player.y)))
+            .collect()
+    }
+
+    /// Drop points of every zipline grabbable from `launches`
+    fn zipline_drops(&self, launches: &[Launch]) -> Vec<Launch> {
+        self.zipline_samples()
+            .iter()
+            .filter(|samples| launches.iter().any(|launch| self.can_grab(launch, samples)))
+            .flat_map(|samples| self.ride_from(samples))
+            .collect()
+    }
+
+    /// Where the way to `goal_x` runs out: the gap after the furthest platform reached
+    fn gap_issue(&self, reachable: &[bool], goal_x: f32) -> LevelIssue {
+        let longest = Launch::on_foot(0.0, 0.0, 0.0).reach(0.0).unwrap_or(0.0);
+
+        let furthest = self
+            .platforms
+            .iter()
+            .zip(reachable)
+            .filter(|(def, &reached)| reached && def.position.x < goal_x)
+            .map(|(def, _)| def)
+            .max_by(|a, b| (a.position.x + a.size.x).total_cmp(&(b.position.x + b.size.x)));
+        let Some(furthest) = furthest else {
+            return LevelIssue::warning(
+                "The boss can't be reached from the spawn".to_string(),
+                self.spawn,
+            );
+        };
+        let edge = furthest.position.x + furthest.size.x;
+        let next = self
+            .platforms
+            .iter()
+            .zip(reachable)
+            .filter(|(def, &reached)| !reached && def.position.x >= edge)
+            .map(|(def, _)| def.position.x)
+            .fold(f32::INFINITY, f32::min);
+
+        let position = Vec2::new(edge, furthest.position.y);
+        if next.is_finite() {
+            LevelIssue::warning(
+                format!(
+                    "The {:.0} px gap after '{}' is wider than the longest jump ({:.0} px), so the boss can't be reached",
+                    next - edge,
+                    furthest.id,
+                    longest
+                ),
+                position,
+            )
+        } else {
+            LevelIssue::warning(
+                format!("Nothing leads on from '{}' to the boss", furthest.id),
+                position,
+            )
+        }
+    }
+}
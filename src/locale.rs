@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Folder other languages are loaded from, as `<code>.lang`, relative to the working directory
+pub const LANGUAGE_DIR: &str = "lang";
+
+/// Language built into the game, used for any key another language leaves out
+pub const DEFAULT_LANGUAGE: &str = "en";
+const DEFAULT_SOURCE: &str = include_str!("../lang/en.lang");
+
+/// The UI text for one language, looked up by key
+pub struct Locale {
+    pub code: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+    /// Show keys instead of text, so anything still hard-coded stands out
+    pub show_keys: bool,
+}
+
+thread_local! {
+    static CURRENT_LOCALE: RefCell<Locale> = RefCell::new(Locale::load(DEFAULT_LANGUAGE));
+}
+
+impl Locale {
+    /// The language with this code, or just English if its file is missing
+    pub fn load(code: &str) -> Self {
+        let strings = if code == DEFAULT_LANGUAGE {
+            HashMap::new()
+        } else {
+            std::fs::read_to_string(Path::new(LANGUAGE_DIR).join(format!("{}.lang", code)))
+                .map(|source| Self::parse(&source))
+                .unwrap_or_default()
+        };
+        Self {
+            code: code.to_string(),
+            strings,
+            fallback: Self::parse(DEFAULT_SOURCE),
+            show_keys: false,
+        }
+    }
+
+    /// Read `key = value` lines; `#` starts a comment line and `\n` in a value is a line break
+    pub fn parse(source: &str) -> HashMap<String, String> {
+        source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().replace("\\n", "\n")))
+            .collect()
+    }
+
+    /// Text for `key` in this language, then English, then the key itself
+    pub fn get(&self, key: &str) -> String {
+        if self.show_keys {
+            return format!("[{}]", key);
+        }
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Whether `key` is a known key, rather than text to show as it is
+    pub fn has(&self, key: &str) -> bool {
+        self.strings.contains_key(key) || self.fallback.contains_key(key)
+    }
+
+    /// Make this the language everything is drawn in
+    pub fn apply(self) {
+        CURRENT_LOCALE.with(|locale| *locale.borrow_mut() = self);
+    }
+
+    /// Codes of every language there's a file for, English first
+    pub fn available() -> Vec<String> {
+        let mut codes: Vec<String> = std::fs::read_dir(LANGUAGE_DIR)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lang"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .filter(|code| code != DEFAULT_LANGUAGE)
+            .collect();
+        codes.sort();
+        codes.insert(0, DEFAULT_LANGUAGE.to_string());
+        codes
+    }
+}
+
+/// Text for `key` in the current language
+pub fn tr(key: &str) -> String {
+    CURRENT_LOCALE.with(|locale| locale.borrow().get(key))
+}
+
+/// Text for `key` with each `{name}` replaced by its value
+pub fn tr_with(key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = tr(key);
+    if show_keys() {
+        return text;
+    }
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// Text from a level that may be a key, such as a tutorial hint; anything that isn't a
+/// key is shown as written
+pub fn tr_content(text: &str) -> String {
+    CURRENT_LOCALE.with(|locale| {
+        let locale = locale.borrow();
+        if locale.has(text) {
+            locale.get(text)
+        } else {
+            text.to_string()
+        }
+    })
+}
+
+/// Code of the language in use
+pub fn current_language() -> String {
+    CURRENT_LOCALE.with(|locale| locale.borrow().code.clone())
+}
+
+pub fn show_keys() -> bool {
+    CURRENT_LOCALE.with(|locale| locale.borrow().show_keys)
+}
+
+pub fn set_show_keys(show: bool) {
+    CURRENT_LOCALE.with(|locale| locale.borrow_mut().show_keys = show);
+}
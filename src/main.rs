@@ -1,33 +1,99 @@
 use macroquad::prelude::*;
 
+mod animation;
+mod assets;
+mod audio;
 mod config;
+mod date;
+mod editor;
 mod entities;
 mod game;
 mod graphics;
 mod input;
+mod leaderboard;
+mod level;
+mod locale;
 mod physics;
+mod profile;
+mod records;
+mod rng;
+mod save;
+mod settings;
+mod shop;
+mod stats;
 
+use config::GameConfig;
+use game::timing::FrameLimiter;
 use game::Game;
+use graphics::{Anchor, TextStyle, Viewport};
+use profile::Profiles;
+use settings::Settings;
 
-#[macroquad::main("Platformer")]
+fn window_conf() -> Conf {
+    Settings::load(&Profiles::load().active()).window_conf()
+}
+
+#[macroquad::main(window_conf)]
 async fn main() {
     let mut game = Game::new();
+    let viewport = Viewport::new();
+    let mut frame_limiter = FrameLimiter::new();
 
     loop {
+        // A few files a frame, so the loading screen keeps drawing
+        if game.is_loading() {
+            game.assets.load_for(GameConfig::ASSET_LOAD_BUDGET).await;
+            if !game.assets.is_loading() {
+                game.finish_loading();
+            }
+        }
+        if game.assets.take_reload_request() {
+            let reloaded = game.assets.reload_changed().await;
+            game.assets_reloaded(&reloaded);
+        }
+
         // Handle input
         game.handle_input();
 
         // Update game state
         game.update();
 
-        // Clear screen
+        // Draw into the fixed virtual resolution
+        viewport.begin();
         clear_background(SKYBLUE);
 
         // Render game
-        game.render();
+        game.render(&viewport);
+
+        // Show rendered frames, simulated ticks per second and how much of the level is
+        // being simulated
+        game.settings.hud_layout().text(
+            &format!(
+                "FPS: {}  Ticks: {:.0}  Sim: {} full, {} reduced, {} asleep",
+                get_fps(),
+                game.tick_rate(),
+                game.sim_counts.full,
+                game.sim_counts.reduced,
+                game.sim_counts.asleep
+            ),
+            Anchor::TopLeft,
+            0,
+            TextStyle::Hud.size(),
+            WHITE,
+        );
+
+        // Scale the frame into the window with letterboxing
+        viewport.present();
+
+        // Presenting flushed the virtual frame, so captures see all of it
+        game.capture_frame(viewport.texture());
+
+        // Cap the render rate when vsync isn't doing it for us
+        frame_limiter.wait(game.settings.frame_limit);
 
-        // Show FPS
-        draw_text(&format!("FPS: {}", get_fps()), 10.0, 30.0, 20.0, WHITE);
+        if game.quit_requested {
+            break;
+        }
 
         next_frame().await
     }
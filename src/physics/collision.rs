@@ -1,8 +1,10 @@
 use macroquad::prelude::*;
 
-use crate::entities::{PhysicsBody, Platform};
+use super::contact_log::{self, LoggedContact};
+use crate::config::GameConfig;
+use crate::entities::{BoxKind, PhysicsBody, Platform};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CollisionSide {
     Top,
     Bottom,
@@ -10,6 +12,17 @@ pub enum CollisionSide {
     Right,
 }
 
+impl CollisionSide {
+    /// Which way (-1 left, 1 right) a wall touching this side is, for the two sides
+    pub fn wall_direction(self) -> Option<f32> {
+        match self {
+            CollisionSide::Left => Some(-1.0),
+            CollisionSide::Right => Some(1.0),
+            CollisionSide::Top | CollisionSide::Bottom => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CollisionInfo {
     pub side: CollisionSide,
@@ -32,101 +45,72 @@ impl CollisionDetector {
         x1 < ox2 && x2 > ox1 && y1 < oy2 && y2 > oy1
     }
 
-    /// Get detailed collision information between two physics bodies
-    pub fn get_collision_info(body1: &PhysicsBody, body2: &PhysicsBody) -> Option<CollisionInfo> {
-        let bounds1 = body1.get_bounds();
-        let bounds2 = body2.get_bounds();
+    /// Smallest move that takes a circle out of a rectangle, or `None` if they don't touch.
+    /// A center inside the rectangle is pushed out through the nearest edge.
+    pub fn circle_aabb_push(center: Vec2, radius: f32, rect: (f32, f32, f32, f32)) -> Option<Vec2> {
+        let (x1, y1, x2, y2) = rect;
+        let closest = Vec2::new(center.x.clamp(x1, x2), center.y.clamp(y1, y2));
+        let offset = center - closest;
+        let distance = offset.length();
 
-        if !Self::aabb_overlap(bounds1, bounds2) {
-            return None;
+        if distance > 0.0 {
+            return (distance < radius).then(|| offset / distance * (radius - distance));
         }
 
-        let (x1, y1, x2, y2) = bounds1;
-        let (ox1, oy1, ox2, oy2) = bounds2;
-
-        // Calculate overlap distances
-        let overlap_x = (x2 - ox1).min(ox2 - x1);
-        let overlap_y = (y2 - oy1).min(oy2 - y1);
-
-        // Determine collision side based on minimum overlap
-        let (side, overlap) = if overlap_x < overlap_y {
-            // Horizontal collision
-            if x1 < ox1 {
-                (CollisionSide::Right, overlap_x)
-            } else {
-                (CollisionSide::Left, overlap_x)
-            }
-        } else {
-            // Vertical collision
-            if y1 < oy1 {
-                (CollisionSide::Bottom, overlap_y)
-            } else {
-                (CollisionSide::Top, overlap_y)
-            }
-        };
-
-        let contact_point = Vec2::new((x1 + x2) / 2.0, (y1 + y2) / 2.0);
-
-        Some(CollisionInfo {
-            side,
-            overlap,
-            contact_point,
-        })
+        // The center is inside: leave through whichever edge is closest
+        let exits = [
+            (center.x - x1, Vec2::NEG_X),
+            (x2 - center.x, Vec2::X),
+            (center.y - y1, Vec2::NEG_Y),
+            (y2 - center.y, Vec2::Y),
+        ];
+        let (depth, direction) = exits
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap_or((0.0, Vec2::NEG_Y));
+        Some(direction * (depth + radius))
     }
 
-    /// Check if a point is inside a rectangle
-    pub fn point_in_rect(point: Vec2, rect: (f32, f32, f32, f32)) -> bool {
+    /// Check if a line segment crosses an axis-aligned bounding box (slab test)
+    pub fn segment_intersects_aabb(start: Vec2, end: Vec2, rect: (f32, f32, f32, f32)) -> bool {
         let (x1, y1, x2, y2) = rect;
-        point.x >= x1 && point.x <= x2 && point.y >= y1 && point.y <= y2
-    }
-
-    /// Check if a moving entity will collide with a static platform
-    pub fn predict_collision(
-        entity: &PhysicsBody,
-        platform: &Platform,
-        velocity: Vec2,
-        delta_time: f32,
-    ) -> Option<CollisionInfo> {
-        let future_position = entity.position + velocity * delta_time;
-        let future_body = PhysicsBody {
-            position: future_position,
-            ..*entity
-        };
-
-        Self::get_collision_info(&future_body, &platform.body)
-    }
+        let delta = end - start;
 
-    /// Sweep test for continuous collision detection
-    pub fn sweep_test(
-        entity: &PhysicsBody,
-        platform: &Platform,
-        velocity: Vec2,
-        delta_time: f32,
-    ) -> Option<(f32, CollisionInfo)> {
-        // Simple sweep test using multiple samples
-        let samples = 10;
-        let step = delta_time / samples as f32;
-
-        for i in 1..=samples {
-            let t = step * i as f32;
-            let test_position = entity.position + velocity * t;
-            let test_body = PhysicsBody {
-                position: test_position,
-                ..*entity
-            };
+        let mut t_min: f32 = 0.0;
+        let mut t_max: f32 = 1.0;
 
-            if let Some(collision) = Self::get_collision_info(&test_body, &platform.body) {
-                return Some((t, collision));
+        for (origin, dir, min, max) in [(start.x, delta.x, x1, x2), (start.y, delta.y, y1, y2)] {
+            if dir.abs() < f32::EPSILON {
+                // Segment is parallel to this slab, so it must start inside it
+                if origin < min || origin > max {
+                    return false;
+                }
+            } else {
+                let t1 = (min - origin) / dir;
+                let t2 = (max - origin) / dir;
+                t_min = t_min.max(t1.min(t2));
+                t_max = t_max.min(t1.max(t2));
+                if t_min > t_max {
+                    return false;
+                }
             }
         }
 
-        None
+        true
     }
 
     /// Check if an entity is standing on a platform
     pub fn is_on_platform(entity: &PhysicsBody, platform: &Platform, tolerance: f32) -> bool {
+        Self::is_on_bounds(entity, platform.get_bounds(), tolerance)
+    }
+
+    /// Check if an entity is standing on top of a rectangle
+    pub fn is_on_bounds(
+        entity: &PhysicsBody,
+        platform_bounds: (f32, f32, f32, f32),
+        tolerance: f32,
+    ) -> bool {
         let entity_bounds = entity.get_bounds();
-        let platform_bounds = platform.get_bounds();
 
         let (ex1, _ey1, ex2, ey2) = entity_bounds;
         let (px1, py1, px2, _py2) = platform_bounds;
@@ -140,6 +124,73 @@ impl CollisionDetector {
         horizontal_overlap && vertical_alignment
     }
 
+    /// Horizontal distance from the body's center to where the ground under it runs out,
+    /// walking in `direction` (negative for left). Platforms flush with `platform` continue
+    /// the surface, so straddling two neighbours isn't a ledge. Negative once the center
+    /// is past the edge.
+    pub fn distance_to_edge<'a>(
+        body: &PhysicsBody,
+        platform: &Platform,
+        platforms: impl IntoIterator<Item = &'a Platform>,
+        direction: f32,
+    ) -> f32 {
+        let tolerance = GameConfig::LEDGE_JOIN_TOLERANCE;
+        let (left, top, right, _) = platform.get_bounds();
+        let mut neighbours: Vec<(f32, f32)> = platforms
+            .into_iter()
+            .map(|other| other.get_bounds())
+            .filter(|(_, other_top, _, _)| (other_top - top).abs() <= tolerance)
+            .map(|(other_left, _, other_right, _)| (other_left, other_right))
+            .collect();
+
+        // Keep extending the surface until nothing else joins onto its far end
+        let mut edge = if direction < 0.0 { left } else { right };
+        loop {
+            let joined = neighbours.iter().position(|&(other_left, other_right)| {
+                if direction < 0.0 {
+                    other_left < edge && other_right >= edge - tolerance
+                } else {
+                    other_right > edge && other_left <= edge + tolerance
+                }
+            });
+            let Some(index) = joined else {
+                break;
+            };
+            let (other_left, other_right) = neighbours.swap_remove(index);
+            edge = if direction < 0.0 {
+                other_left
+            } else {
+                other_right
+            };
+        }
+
+        let center = body.position.x + body.size.x / 2.0;
+        if direction < 0.0 {
+            center - edge
+        } else {
+            edge - center
+        }
+    }
+
+    /// The side (-1 or 1) of a ledge the body is hanging more than half over, if any
+    pub fn ledge_side<'a>(
+        body: &PhysicsBody,
+        platform: &Platform,
+        platforms: impl IntoIterator<Item = &'a Platform>,
+    ) -> Option<f32> {
+        let platforms: Vec<&Platform> = platforms.into_iter().collect();
+        [-1.0, 1.0].into_iter().find(|&direction| {
+            Self::distance_to_edge(body, platform, platforms.iter().copied(), direction) < 0.0
+        })
+    }
+
+    /// Whether any part of a rectangle is within `radius` of a point
+    pub fn aabb_within_radius(rect: (f32, f32, f32, f32), center: Vec2, radius: f32) -> bool {
+        let (x1, y1, x2, y2) = rect;
+        let closest = Vec2::new(center.x.clamp(x1, x2), center.y.clamp(y1, y2));
+        closest.distance(center) <= radius
+    }
+
     /// Get the distance between two physics bodies
     pub fn distance_between(body1: &PhysicsBody, body2: &PhysicsBody) -> f32 {
         let center1 = body1.position + body1.size / 2.0;
@@ -158,50 +209,42 @@ impl CollisionDetector {
 pub struct CollisionResolver;
 
 impl CollisionResolver {
-    /// Resolve collision by separating two bodies
-    pub fn separate_bodies(
-        body1: &mut PhysicsBody,
-        body2: &PhysicsBody,
-        collision: &CollisionInfo,
-    ) {
-        match collision.side {
-            CollisionSide::Top => {
-                body1.position.y = body2.position.y - body1.size.y;
-                body1.velocity.y = body1.velocity.y.min(0.0);
-                body1.on_ground = true;
-            }
-            CollisionSide::Bottom => {
-                body1.position.y = body2.position.y + body2.size.y;
-                body1.velocity.y = body1.velocity.y.max(0.0);
-            }
-            CollisionSide::Left => {
-                body1.position.x = body2.position.x - body1.size.x;
-                body1.velocity.x = body1.velocity.x.min(0.0);
-            }
-            CollisionSide::Right => {
-                body1.position.x = body2.position.x + body2.size.x;
-                body1.velocity.x = body1.velocity.x.max(0.0);
-            }
-        }
-    }
-
-    /// Apply bounce effect to a body
-    pub fn apply_bounce(body: &mut PhysicsBody, collision: &CollisionInfo, restitution: f32) {
-        match collision.side {
-            CollisionSide::Top | CollisionSide::Bottom => {
-                body.velocity.y = -body.velocity.y * restitution;
-            }
-            CollisionSide::Left | CollisionSide::Right => {
-                body.velocity.x = -body.velocity.x * restitution;
-            }
+    /// Move a body's solid box out of a rectangle that appeared on top of it, to whichever
+    /// side is closer. Only sideways, so it can't end up wedged above or below the solid.
+    /// Returns false if they weren't overlapping.
+    pub fn push_out_sideways(body: &mut PhysicsBody, bounds: (f32, f32, f32, f32)) -> bool {
+        let (bx1, by1, bx2, by2) = body.box_bounds(BoxKind::Solid);
+        if !CollisionDetector::aabb_overlap((bx1, by1, bx2, by2), bounds) {
+            return false;
         }
-    }
 
-    /// Apply friction to a body
-    pub fn apply_friction(body: &mut PhysicsBody, friction_coefficient: f32) {
-        if body.on_ground {
-            body.velocity.x *= 1.0 - friction_coefficient;
-        }
+        let (x1, y1, x2, y2) = bounds;
+        let to_left = bx2 - x1;
+        let to_right = x2 - bx1;
+        let velocity_before = body.velocity;
+        let (side, overlap) = if to_left <= to_right {
+            body.position.x -= to_left;
+            body.velocity.x = body.velocity.x.min(0.0);
+            (CollisionSide::Right, to_left)
+        } else {
+            body.position.x += to_right;
+            body.velocity.x = body.velocity.x.max(0.0);
+            (CollisionSide::Left, to_right)
+        };
+        contact_log::record(|| LoggedContact {
+            step: 0,
+            body: "body",
+            other: bounds,
+            side,
+            overlap,
+            point: Vec2::new(
+                (bx1.max(x1) + bx2.min(x2)) / 2.0,
+                (by1.max(y1) + by2.min(y2)) / 2.0,
+            ),
+            velocity_before,
+            velocity_after: body.velocity,
+        });
+        true
     }
 }
 
@@ -210,3 +253,66 @@ impl Default for CollisionDetector {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A body 20 wide standing on y = 0 with its center at `center_x`
+    fn body_at(center_x: f32) -> PhysicsBody {
+        PhysicsBody::new(center_x - 10.0, -30.0, 20.0, 30.0)
+    }
+
+    fn platform(left: f32, right: f32, top: f32) -> Platform {
+        Platform::new(left, top, right - left, 20.0)
+    }
+
+    #[test]
+    fn straddling_flush_platforms_is_not_a_ledge() {
+        let platforms = [platform(0.0, 100.0, 0.0), platform(100.0, 200.0, 0.0)];
+        let body = body_at(100.0);
+        for standing_on in &platforms {
+            let right = CollisionDetector::distance_to_edge(&body, standing_on, &platforms, 1.0);
+            let left = CollisionDetector::distance_to_edge(&body, standing_on, &platforms, -1.0);
+            assert_eq!(right, 100.0);
+            assert_eq!(left, 100.0);
+            assert_eq!(
+                CollisionDetector::ledge_side(&body, standing_on, &platforms),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn surface_joins_within_the_tolerance() {
+        let gap = GameConfig::LEDGE_JOIN_TOLERANCE / 2.0;
+        let platforms = [
+            platform(0.0, 100.0, 0.0),
+            platform(100.0 + gap, 200.0, gap),
+            platform(200.0, 300.0, 0.0),
+        ];
+        let body = body_at(50.0);
+        let right = CollisionDetector::distance_to_edge(&body, &platforms[0], &platforms, 1.0);
+        assert_eq!(right, 250.0);
+    }
+
+    #[test]
+    fn straddling_a_gap_is_a_ledge() {
+        let platforms = [platform(0.0, 100.0, 0.0), platform(110.0, 200.0, 0.0)];
+        let body = body_at(105.0);
+        let right = CollisionDetector::distance_to_edge(&body, &platforms[0], &platforms, 1.0);
+        assert_eq!(right, -5.0);
+        assert_eq!(
+            CollisionDetector::ledge_side(&body, &platforms[0], &platforms),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn a_step_up_is_not_part_of_the_surface() {
+        let platforms = [platform(0.0, 100.0, 0.0), platform(100.0, 200.0, -20.0)];
+        let body = body_at(95.0);
+        let right = CollisionDetector::distance_to_edge(&body, &platforms[0], &platforms, 1.0);
+        assert_eq!(right, 5.0);
+    }
+}
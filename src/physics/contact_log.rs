@@ -0,0 +1,194 @@
+//! A record of the last few hundred contacts the collision resolver handled, for working
+//! out afterwards why the player snagged on a corner or went through a wall.
+//!
+//! Only built with the `contact-log` feature. Without it every function here is an empty
+//! stub, so the hooks in the resolver cost nothing.
+
+use macroquad::prelude::*;
+
+#[cfg(feature = "contact-log")]
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::Path;
+
+use super::collision::CollisionSide;
+use crate::config::GameConfig;
+
+/// One push out of a solid, as the resolver saw it
+#[derive(Debug, Clone)]
+pub struct LoggedContact {
+    /// Simulation step it happened in
+    pub step: u64,
+    /// What was pushed, such as "player"
+    pub body: &'static str,
+    /// The solid rectangle it was pushed out of
+    pub other: (f32, f32, f32, f32),
+    /// Side of the pushed body that touched
+    pub side: CollisionSide,
+    /// How deep the body had sunk in
+    pub overlap: f32,
+    /// Middle of the overlap, in world space
+    pub point: Vec2,
+    pub velocity_before: Vec2,
+    pub velocity_after: Vec2,
+}
+
+impl LoggedContact {
+    /// Direction the body was pushed
+    pub fn normal(&self) -> Vec2 {
+        match self.side {
+            CollisionSide::Top => Vec2::new(0.0, 1.0),
+            CollisionSide::Bottom => Vec2::new(0.0, -1.0),
+            CollisionSide::Left => Vec2::new(1.0, 0.0),
+            CollisionSide::Right => Vec2::new(-1.0, 0.0),
+        }
+    }
+}
+
+impl fmt::Display for LoggedContact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x1, y1, x2, y2) = self.other;
+        let normal = self.normal();
+        write!(
+            f,
+            "step {} {} {:?} side, normal ({}, {}), {:.3} deep at ({:.3}, {:.3}) \
+             against ({:.1}, {:.1})-({:.1}, {:.1}), velocity ({:.3}, {:.3}) -> ({:.3}, {:.3})",
+            self.step,
+            self.body,
+            self.side,
+            normal.x,
+            normal.y,
+            self.overlap,
+            self.point.x,
+            self.point.y,
+            x1,
+            y1,
+            x2,
+            y2,
+            self.velocity_before.x,
+            self.velocity_before.y,
+            self.velocity_after.x,
+            self.velocity_after.y
+        )
+    }
+}
+
+/// The most recent contacts, oldest first, while logging is switched on
+#[cfg(feature = "contact-log")]
+#[derive(Debug, Default)]
+struct ContactLog {
+    enabled: bool,
+    step: u64,
+    contacts: VecDeque<LoggedContact>,
+}
+
+#[cfg(feature = "contact-log")]
+thread_local! {
+    static LOG: std::cell::RefCell<ContactLog> = std::cell::RefCell::default();
+}
+
+/// Whether this build can log contacts at all
+pub const AVAILABLE: bool = cfg!(feature = "contact-log");
+
+/// Log a contact, if logging is on. `contact` is only called then, so building the entry
+/// costs nothing otherwise; its step number is filled in here.
+#[inline(always)]
+pub fn record(contact: impl FnOnce() -> LoggedContact) {
+    #[cfg(feature = "contact-log")]
+    LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if !log.enabled {
+            return;
+        }
+        let mut contact = contact();
+        contact.step = log.step;
+        if log.contacts.len() == GameConfig::CONTACT_LOG_CAPACITY {
+            log.contacts.pop_front();
+        }
+        log.contacts.push_back(contact);
+    });
+    #[cfg(not(feature = "contact-log"))]
+    let _ = contact;
+}
+
+/// Tell the log which simulation step contacts now belong to
+#[inline(always)]
+pub fn set_step(step: u64) {
+    #[cfg(feature = "contact-log")]
+    LOG.with(|log| log.borrow_mut().step = step);
+    #[cfg(not(feature = "contact-log"))]
+    let _ = step;
+}
+
+pub fn is_enabled() -> bool {
+    #[cfg(feature = "contact-log")]
+    return LOG.with(|log| log.borrow().enabled);
+    #[cfg(not(feature = "contact-log"))]
+    false
+}
+
+/// Start or stop logging, returning whether it's now on. Stopping keeps what was logged
+/// so it can still be looked at and dumped.
+pub fn toggle() -> bool {
+    #[cfg(feature = "contact-log")]
+    return LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        log.enabled = !log.enabled;
+        log.enabled
+    });
+    #[cfg(not(feature = "contact-log"))]
+    false
+}
+
+/// Forget every logged contact, e.g. when a new run starts
+pub fn clear() {
+    #[cfg(feature = "contact-log")]
+    LOG.with(|log| log.borrow_mut().contacts.clear());
+}
+
+/// Visit the logged contacts, oldest first
+pub fn for_each(mut visit: impl FnMut(&LoggedContact)) {
+    #[cfg(feature = "contact-log")]
+    LOG.with(|log| log.borrow().contacts.iter().for_each(&mut visit));
+    #[cfg(not(feature = "contact-log"))]
+    let _ = &mut visit;
+}
+
+/// Write every logged contact to `path`, one line each, returning how many there were
+pub fn dump(path: &Path) -> std::io::Result<usize> {
+    let mut text = String::new();
+    let mut count = 0;
+    for_each(|contact| {
+        text.push_str(&contact.to_string());
+        text.push('\n');
+        count += 1;
+    });
+    std::fs::write(path, text)?;
+    Ok(count)
+}
+
+/// Mark each logged contact where it happened with an arrow along the push, colored by
+/// the side that touched and fading with age
+pub fn render(cam_x: f32, cam_y: f32) {
+    let mut newest = 0;
+    for_each(|contact| newest = newest.max(contact.step));
+    let fade_steps = GameConfig::CONTACT_LOG_CAPACITY as f32;
+    for_each(|contact| {
+        let age = (newest - contact.step) as f32;
+        let alpha = (1.0 - age / fade_steps).max(0.2);
+        let color = match contact.side {
+            CollisionSide::Bottom => GREEN,
+            CollisionSide::Top => RED,
+            CollisionSide::Left | CollisionSide::Right => SKYBLUE,
+        };
+        let color = Color::new(color.r, color.g, color.b, alpha);
+        let point = contact.point + Vec2::new(cam_x, cam_y);
+        let tip = point + contact.normal() * GameConfig::CONTACT_LOG_ARROW_LENGTH;
+        draw_circle(point.x, point.y, 2.5, color);
+        draw_line(point.x, point.y, tip.x, tip.y, 1.5, color);
+        // Arrowhead
+        let back = -contact.normal() * 4.0;
+        let across = contact.normal().perp() * 3.0;
+        draw_triangle(tip, tip + back + across, tip + back - across, color);
+    });
+}
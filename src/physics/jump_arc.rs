@@ -0,0 +1,206 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::graphics::GraphicsUtils;
+
+/// What a jump arc is worked out from. Heights are world y, so up is negative.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpArcParams {
+    /// Where the feet leave the ground
+    pub origin: Vec2,
+    /// -1 left, 1 right
+    pub direction: f32,
+    pub run_speed: f32,
+    /// Upward speed each jump starts with
+    pub jump_speed: f32,
+    pub gravity: f32,
+    /// Gravity multiplier on the way down
+    pub fall_gravity_scale: f32,
+    pub terminal_velocity: f32,
+    /// Jumps taken one after another, each at the top of the last
+    pub jumps: u32,
+    /// How far below the origin the fall is followed
+    pub drop: f32,
+}
+
+impl JumpArcParams {
+    /// The player's furthest jump from `origin`: sprinting, with `jumps` jumps
+    pub fn player(origin: Vec2, direction: f32, jumps: u32) -> Self {
+        Self {
+            origin,
+            direction,
+            run_speed: GameConfig::PLAYER_MOVE_SPEED * GameConfig::SPRINT_MULTIPLIER,
+            jump_speed: -GameConfig::PLAYER_JUMP_FORCE,
+            gravity: GameConfig::GRAVITY,
+            fall_gravity_scale: GameConfig::FALL_GRAVITY_SCALE,
+            terminal_velocity: GameConfig::TERMINAL_VELOCITY,
+            jumps,
+            drop: GameConfig::JUMP_ARC_DROP,
+        }
+    }
+
+    /// Seconds each jump rises for
+    fn rise_time(&self) -> f32 {
+        self.jump_speed / self.gravity
+    }
+
+    /// Depth fallen `t` seconds after the top, speeding up until terminal velocity
+    fn fall_depth(&self, t: f32) -> f32 {
+        let fall_gravity = self.gravity * self.fall_gravity_scale;
+        let terminal_time = self.terminal_velocity / fall_gravity;
+        if t <= terminal_time {
+            fall_gravity * t * t / 2.0
+        } else {
+            fall_gravity * terminal_time * terminal_time / 2.0
+                + self.terminal_velocity * (t - terminal_time)
+        }
+    }
+
+    /// Seconds after the top until the feet have fallen `depth`
+    fn fall_time(&self, depth: f32) -> f32 {
+        let fall_gravity = self.gravity * self.fall_gravity_scale;
+        let terminal_time = self.terminal_velocity / fall_gravity;
+        let terminal_depth = fall_gravity * terminal_time * terminal_time / 2.0;
+        if depth <= terminal_depth {
+            (2.0 * depth.max(0.0) / fall_gravity).sqrt()
+        } else {
+            terminal_time + (depth - terminal_depth) / self.terminal_velocity
+        }
+    }
+}
+
+/// Points along the path of the feet through a jump: each rise under gravity, the next
+/// jump taken at the top of the last, then the fall under fall gravity to `drop` below
+/// the origin. Like the level checker, it leaves out the lighter gravity at the apex,
+/// so the real jump goes a little further.
+pub fn jump_arc(params: &JumpArcParams) -> Vec<Vec2> {
+    let step = GameConfig::JUMP_ARC_SAMPLE_TIME;
+    let mut points = vec![params.origin];
+    if params.gravity <= 0.0 {
+        return points;
+    }
+    let speed = params.direction * params.run_speed;
+
+    let rise_time = params.rise_time();
+    let rise_samples = (rise_time / step).ceil().max(1.0) as usize;
+    for _ in 0..params.jumps {
+        let start = points[points.len() - 1];
+        for i in 1..=rise_samples {
+            let t = rise_time * i as f32 / rise_samples as f32;
+            let rise = params.jump_speed * t - params.gravity * t * t / 2.0;
+            points.push(start + Vec2::new(speed * t, -rise));
+        }
+    }
+
+    let top = points[points.len() - 1];
+    let fall_time = params.fall_time(params.origin.y + params.drop - top.y);
+    let fall_samples = (fall_time / step).ceil().max(1.0) as usize;
+    for i in 1..=fall_samples {
+        let t = fall_time * i as f32 / fall_samples as f32;
+        points.push(top + Vec2::new(speed * t, params.fall_depth(t)));
+    }
+    points
+}
+
+/// Draw the arc of a single jump and, with more jumps, the extended one from chaining
+/// them, as dotted curves from `origin`
+pub fn render(origin: Vec2, direction: f32, jumps: u32, cam_x: f32, cam_y: f32) {
+    let camera = Vec2::new(cam_x, cam_y);
+    let draw = |jumps: u32, color: Color| {
+        let params = JumpArcParams::player(origin, direction, jumps);
+        let points: Vec<Vec2> = jump_arc(&params)
+            .into_iter()
+            .map(|point| point + camera)
+            .collect();
+        GraphicsUtils::draw_dotted_path(&points, GameConfig::JUMP_ARC_DOT_SPACING, 2.0, color);
+    };
+    if jumps > 1 {
+        draw(jumps, GameConfig::JUMP_ARC_EXTENDED_COLOR);
+    }
+    draw(1, GameConfig::JUMP_ARC_COLOR);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Vec2 = Vec2::new(100.0, 500.0);
+
+    fn params(jumps: u32, drop: f32) -> JumpArcParams {
+        JumpArcParams {
+            drop,
+            ..JumpArcParams::player(ORIGIN, 1.0, jumps)
+        }
+    }
+
+    fn highest(points: &[Vec2]) -> f32 {
+        points.iter().map(|point| point.y).fold(f32::MAX, f32::min)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn a_jump_tops_out_at_the_analytic_height() {
+        for jumps in 1..=3 {
+            let params = params(jumps, 0.0);
+            // v² / 2g for each jump, taken one on top of the last
+            let apex = jumps as f32 * params.jump_speed.powi(2) / (2.0 * params.gravity);
+            let points = jump_arc(&params);
+            assert!(close(ORIGIN.y - highest(&points), apex), "{jumps} jumps");
+        }
+    }
+
+    #[test]
+    fn a_jump_lands_at_the_analytic_range() {
+        let params = params(1, 0.0);
+        let apex = params.jump_speed.powi(2) / (2.0 * params.gravity);
+        let fall_gravity = params.gravity * params.fall_gravity_scale;
+        let fall_time = (2.0 * apex / fall_gravity).sqrt();
+        // Short enough that the fall never reaches terminal velocity
+        assert!(fall_gravity * fall_time < params.terminal_velocity);
+        let flight = params.jump_speed / params.gravity + fall_time;
+
+        let end = *jump_arc(&params).last().unwrap();
+        assert!(close(end.y, ORIGIN.y));
+        assert!(close(end.x - ORIGIN.x, params.run_speed * flight));
+    }
+
+    #[test]
+    fn a_long_drop_falls_at_terminal_velocity() {
+        for jumps in 1..=2 {
+            let params = params(jumps, GameConfig::JUMP_ARC_DROP);
+            let apex = jumps as f32 * params.jump_speed.powi(2) / (2.0 * params.gravity);
+            let fall_gravity = params.gravity * params.fall_gravity_scale;
+            let terminal_time = params.terminal_velocity / fall_gravity;
+            let terminal_depth = fall_gravity * terminal_time.powi(2) / 2.0;
+            let depth = apex + params.drop;
+            assert!(depth > terminal_depth);
+            let flight = jumps as f32 * params.jump_speed / params.gravity
+                + terminal_time
+                + (depth - terminal_depth) / params.terminal_velocity;
+
+            let end = *jump_arc(&params).last().unwrap();
+            assert!(close(end.y, ORIGIN.y + params.drop));
+            assert!(
+                close(end.x - ORIGIN.x, params.run_speed * flight),
+                "{jumps} jumps"
+            );
+        }
+    }
+
+    #[test]
+    fn facing_left_mirrors_the_arc() {
+        let right = jump_arc(&params(2, GameConfig::JUMP_ARC_DROP));
+        let left = jump_arc(&JumpArcParams {
+            direction: -1.0,
+            ..params(2, GameConfig::JUMP_ARC_DROP)
+        });
+        assert_eq!(right.len(), left.len());
+        for (right, left) in right.iter().zip(&left) {
+            assert!(close(right.x - ORIGIN.x, ORIGIN.x - left.x));
+            assert!(close(right.y, left.y));
+        }
+    }
+}
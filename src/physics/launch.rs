@@ -0,0 +1,148 @@
+use macroquad::prelude::*;
+
+use crate::config::GameConfig;
+use crate::entities::PhysicsBody;
+
+/// What happens to a body's velocity when something launches it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MomentumRule {
+    /// Leave with exactly the impulse, whatever the body was doing
+    Overwrite,
+    /// Take the impulse on the axes it pushes along and keep the speed on the others,
+    /// a little more of it (`LAUNCH_MOMENTUM_KEEP`) so chained bounces build up speed
+    Preserve,
+    /// Add the impulse to the velocity the body already had
+    Additive,
+    /// Bounce off a surface facing the way the impulse points: the speed going into it
+    /// comes back out, and at least the impulse's strength, with the rest kept as it was
+    Reflect,
+}
+
+/// Change `body`'s velocity for a launch, such as off a spring or out of an explosion.
+/// Every launch goes through here so they all treat incoming momentum the same way.
+pub fn launch(body: &mut PhysicsBody, impulse: Vec2, rule: MomentumRule) {
+    let velocity = body.velocity;
+    body.velocity = match rule {
+        MomentumRule::Overwrite => impulse,
+        MomentumRule::Preserve => {
+            let keep = |pushed: f32, current: f32| {
+                if pushed != 0.0 {
+                    pushed
+                } else {
+                    current * GameConfig::LAUNCH_MOMENTUM_KEEP
+                }
+            };
+            Vec2::new(keep(impulse.x, velocity.x), keep(impulse.y, velocity.y))
+        }
+        MomentumRule::Additive => velocity + impulse,
+        MomentumRule::Reflect => {
+            let normal = impulse.normalize_or_zero();
+            let into = velocity.dot(normal);
+            let reflected = if into < 0.0 {
+                velocity - 2.0 * into * normal
+            } else {
+                velocity
+            };
+            let away = reflected.dot(normal);
+            reflected + normal * (impulse.length() - away).max(0.0)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Velocity after launching a body moving at `velocity`
+    fn launched(velocity: Vec2, impulse: Vec2, rule: MomentumRule) -> Vec2 {
+        let mut body = PhysicsBody::new(0.0, 0.0, 32.0, 32.0);
+        body.velocity = velocity;
+        launch(&mut body, impulse, rule);
+        body.velocity
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-3
+    }
+
+    #[test]
+    fn overwrite_ignores_what_the_body_was_doing() {
+        let impulse = Vec2::new(300.0, -500.0);
+        assert_eq!(
+            launched(Vec2::new(-250.0, 400.0), impulse, MomentumRule::Overwrite),
+            impulse
+        );
+    }
+
+    #[test]
+    fn preserve_keeps_and_builds_speed_across_the_push() {
+        let velocity = launched(
+            Vec2::new(200.0, 300.0),
+            Vec2::new(0.0, -600.0),
+            MomentumRule::Preserve,
+        );
+        let kept = 200.0 * GameConfig::LAUNCH_MOMENTUM_KEEP;
+        assert!(close(velocity, Vec2::new(kept, -600.0)));
+    }
+
+    #[test]
+    fn additive_composes_with_incoming_velocity() {
+        let velocity = launched(
+            Vec2::new(200.0, 300.0),
+            Vec2::new(-100.0, -600.0),
+            MomentumRule::Additive,
+        );
+        assert!(close(velocity, Vec2::new(100.0, -300.0)));
+    }
+
+    #[test]
+    fn reflect_bounces_speed_going_into_the_surface() {
+        // Falling fast onto an upward-facing pad: the fall comes back up, and that's
+        // already more than the pad's own push
+        let velocity = launched(
+            Vec2::new(150.0, 800.0),
+            Vec2::new(0.0, -500.0),
+            MomentumRule::Reflect,
+        );
+        assert!(close(velocity, Vec2::new(150.0, -800.0)));
+
+        // A slow fall is topped up to the pad's strength
+        let velocity = launched(
+            Vec2::new(150.0, 100.0),
+            Vec2::new(0.0, -500.0),
+            MomentumRule::Reflect,
+        );
+        assert!(close(velocity, Vec2::new(150.0, -500.0)));
+    }
+
+    #[test]
+    fn reflect_leaves_speed_already_going_away_from_the_surface() {
+        // Already rising faster than the push: nothing changes
+        let velocity = launched(
+            Vec2::new(150.0, -700.0),
+            Vec2::new(0.0, -500.0),
+            MomentumRule::Reflect,
+        );
+        assert!(close(velocity, Vec2::new(150.0, -700.0)));
+
+        // Rising slower than the push is topped up along the normal only
+        let velocity = launched(
+            Vec2::new(150.0, -200.0),
+            Vec2::new(0.0, -500.0),
+            MomentumRule::Reflect,
+        );
+        assert!(close(velocity, Vec2::new(150.0, -500.0)));
+    }
+
+    #[test]
+    fn reflect_works_off_slanted_surfaces() {
+        // A 45° slope facing up and right: running left into it sends the body straight up
+        let normal = Vec2::new(1.0, -1.0).normalize();
+        let velocity = launched(
+            Vec2::new(-300.0, 0.0),
+            normal * 100.0,
+            MomentumRule::Reflect,
+        );
+        assert!(close(velocity, Vec2::new(0.0, -300.0)));
+    }
+}
@@ -1,12 +1,26 @@
 use macroquad::prelude::*;
 
-use crate::entities::{Entity, Platform, Player};
+use crate::config::GameConfig;
+use crate::entities::{BoxKind, Entity, PhysicsBody, Player, Rope, Water};
+use collision::{CollisionInfo, CollisionSide};
+use contact_log::LoggedContact;
 
 pub mod collision;
+pub mod contact_log;
+pub mod jump_arc;
+pub mod launch;
+pub mod spatial;
 
 pub struct Physics {
     pub gravity: f32,
     pub terminal_velocity: f32,
+    /// Gravity multiplier near the top of a jump
+    pub apex_hang_gravity_scale: f32,
+    /// Gravity multiplier while moving down
+    pub fall_gravity_scale: f32,
+    /// How far short of a ledge's top the player's feet can bump into its side and still be
+    /// lifted onto it, for the auto-ledge assist; 0 turns it off
+    pub ledge_assist: f32,
 }
 
 impl Physics {
@@ -14,28 +28,47 @@ impl Physics {
         Self {
             gravity: 980.0, // pixels per second squared
             terminal_velocity: 500.0,
+            apex_hang_gravity_scale: GameConfig::APEX_HANG_GRAVITY_SCALE,
+            fall_gravity_scale: GameConfig::FALL_GRAVITY_SCALE,
+            ledge_assist: 0.0,
         }
     }
 
-    pub fn apply_gravity(&self, player: &mut Player) {
+    /// Gravity for the player's current motion: lighter around a jump's apex, heavier on the way down
+    pub fn gravity_for(&self, player: &Player) -> f32 {
+        let vertical_speed = player.velocity().y;
+        if player.is_at_apex() {
+            self.gravity * self.apex_hang_gravity_scale
+        } else if vertical_speed > 0.0 {
+            self.gravity * self.fall_gravity_scale
+        } else {
+            self.gravity
+        }
+    }
+
+    pub fn apply_gravity(&self, player: &mut Player, delta_time: f32) {
         if !player.is_on_ground() {
-            let delta_time = get_frame_time();
+            let state = player.state();
             let mut velocity = player.velocity();
 
-            // Apply gravity
-            velocity.y += self.gravity * delta_time;
+            // Apply gravity, as far as the player's state lets it
+            velocity.y += self.gravity_for(player) * state.gravity_scale() * delta_time;
 
-            // Limit terminal velocity
-            if velocity.y > self.terminal_velocity {
-                velocity.y = self.terminal_velocity;
+            // Limit terminal velocity, or the slower fall of a slide, glide or swim
+            let max_fall = state
+                .max_fall_speed()
+                .map_or(self.terminal_velocity, |speed| {
+                    speed.min(self.terminal_velocity)
+                });
+            if velocity.y > max_fall {
+                velocity.y = max_fall;
             }
 
             player.set_velocity(velocity);
         }
     }
 
-    pub fn update_position(&self, player: &mut Player) {
-        let delta_time = get_frame_time();
+    pub fn update_position(&self, player: &mut Player, delta_time: f32) {
         let velocity = player.velocity();
         let mut position = player.position();
 
@@ -46,22 +79,77 @@ impl Physics {
         player.set_position(position);
 
         // Update player state
-        player.update();
+        player.update(delta_time);
     }
 
-    pub fn check_collision(&self, player: &mut Player, platform: &Platform) {
-        let player_bounds = (
-            player.position().x,
-            player.position().y,
-            player.position().x + player.size().x,
-            player.position().y + player.size().y,
-        );
+    /// Swing a rope as a pendulum for one step, as if its weight hung `length` below the
+    /// anchor. `push` is extra angular acceleration, such as from the player pumping, and
+    /// `damping` is the share of the swing lost per second.
+    pub fn swing_rope(
+        &self,
+        rope: &mut Rope,
+        length: f32,
+        push: f32,
+        damping: f32,
+        delta_time: f32,
+    ) {
+        let acceleration = -(self.gravity / length.max(1.0)) * rope.angle.sin()
+            - damping * rope.angular_velocity
+            + push;
+        rope.angular_velocity += acceleration * delta_time;
+        rope.angle += rope.angular_velocity * delta_time;
+
+        // Stop dead at the limit rather than looping over the anchor
+        let limit = GameConfig::ROPE_MAX_ANGLE;
+        if rope.angle.abs() > limit {
+            rope.angle = rope.angle.clamp(-limit, limit);
+            rope.angular_velocity = 0.0;
+        }
+    }
 
-        let platform_bounds = platform.get_bounds();
+    /// Upward acceleration from water on a body with `submerged` of it under the surface.
+    /// `buoyancy` is how many times over it would float if wholly under, so at rest it
+    /// floats with `1 / buoyancy` of its height under, and anything below 1 sinks.
+    pub fn buoyancy(&self, submerged: f32, buoyancy: f32) -> f32 {
+        self.gravity * submerged * buoyancy
+    }
+
+    /// Push a body that's in `water` along with the current and up by its `buoyancy` (0
+    /// for something that neither floats nor sinks), and slow it with the water's drag.
+    /// Returns how much of it is under.
+    pub fn immerse(
+        &self,
+        body: &mut PhysicsBody,
+        water: &Water,
+        buoyancy: f32,
+        delta_time: f32,
+    ) -> f32 {
+        let submerged = water.submerged(body);
+        if submerged <= 0.0 {
+            return 0.0;
+        }
+        body.velocity += water.current * submerged * delta_time;
+        body.velocity.y -= self.buoyancy(submerged, buoyancy) * delta_time;
+        body.velocity *= (1.0 - GameConfig::WATER_DRAG * submerged * delta_time).max(0.0);
+        submerged
+    }
+
+    /// Push the player out of a solid rectangle, such as a platform or a closed door.
+    /// Reports the contact whenever the player had to be pushed out: which side of the
+    /// player touched, how deep it had sunk in and where, along with the downward speed
+    /// they hit it with if it was a landing.
+    pub fn touch_solid(
+        &self,
+        player: &mut Player,
+        bounds: (f32, f32, f32, f32),
+    ) -> Option<(CollisionInfo, Option<f32>)> {
+        let player_bounds = player.body.box_bounds(BoxKind::Solid);
 
         // Check if there's an overlap
-        if self.rectangles_overlap(player_bounds, platform_bounds) {
-            self.resolve_collision(player, platform);
+        if self.rectangles_overlap(player_bounds, bounds) {
+            Some(self.resolve_collision(player, bounds))
+        } else {
+            None
         }
     }
 
@@ -72,15 +160,12 @@ impl Physics {
         x1 < ox2 && x2 > ox1 && y1 < oy2 && y2 > oy1
     }
 
-    fn resolve_collision(&self, player: &mut Player, platform: &Platform) {
-        let player_bounds = (
-            player.position().x,
-            player.position().y,
-            player.position().x + player.size().x,
-            player.position().y + player.size().y,
-        );
-
-        let platform_bounds = platform.get_bounds();
+    fn resolve_collision(
+        &self,
+        player: &mut Player,
+        platform_bounds: (f32, f32, f32, f32),
+    ) -> (CollisionInfo, Option<f32>) {
+        let player_bounds = player.body.box_bounds(BoxKind::Solid);
 
         let (px1, py1, px2, py2) = player_bounds;
         let (plx1, ply1, plx2, ply2) = platform_bounds;
@@ -88,58 +173,239 @@ impl Physics {
         // Calculate overlap distances
         let overlap_x = (px2 - plx1).min(plx2 - px1);
         let overlap_y = (py2 - ply1).min(ply2 - py1);
+        // Middle of the overlapping area, before the player is pushed out of it
+        let contact_point = Vec2::new(
+            (px1.max(plx1) + px2.min(plx2)) / 2.0,
+            (py1.max(ply1) + py2.min(ply2)) / 2.0,
+        );
 
+        // The solid box may be inset from the body, so move the body by however far the box has to go
         let mut position = player.position();
         let mut velocity = player.velocity();
+        let velocity_before = velocity;
+        let mut impact = None;
 
         // Resolve collision based on smallest overlap
-        if overlap_x < overlap_y {
+        let (side, overlap) = if overlap_x < overlap_y && py2 - ply1 < self.ledge_assist {
+            // Only just short of the top, so step up onto it rather than stop at its side
+            position.y += ply1 - py2;
+            impact = Some(velocity.y.max(0.0));
+            velocity.y = 0.0;
+            player.set_on_ground(true);
+            (CollisionSide::Bottom, overlap_y)
+        } else if overlap_x < overlap_y {
             // Horizontal collision
+            velocity.x = 0.0;
             if px1 < plx1 {
                 // Player is to the left of platform
-                position.x = plx1 - player.size().x;
+                position.x += plx1 - px2;
+                (CollisionSide::Right, overlap_x)
             } else {
                 // Player is to the right of platform
-                position.x = plx2;
+                position.x += plx2 - px1;
+                (CollisionSide::Left, overlap_x)
             }
-            velocity.x = 0.0;
         } else {
             // Vertical collision
             if py1 < ply1 {
                 // Player is above platform (landing)
-                position.y = ply1 - player.size().y;
+                position.y += ply1 - py2;
+                impact = Some(velocity.y.max(0.0));
                 velocity.y = 0.0;
                 player.set_on_ground(true);
+                (CollisionSide::Bottom, overlap_y)
             } else {
                 // Player is below platform (hitting head)
-                position.y = ply2;
+                position.y += ply2 - py1;
                 velocity.y = 0.0;
+                (CollisionSide::Top, overlap_y)
             }
-        }
+        };
 
         player.set_position(position);
         player.set_velocity(velocity);
+        contact_log::record(|| LoggedContact {
+            step: 0,
+            body: "player",
+            other: platform_bounds,
+            side,
+            overlap,
+            point: contact_point,
+            velocity_before,
+            velocity_after: velocity,
+        });
+        let contact = CollisionInfo {
+            side,
+            overlap,
+            contact_point,
+        };
+        (contact, impact)
     }
+}
 
-    pub fn check_bounds(&self, player: &mut Player) {
-        let mut position = player.position();
-        let mut velocity = player.velocity();
+impl Default for Physics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Keep player within screen bounds horizontally (optional)
-        if position.x < 0.0 {
-            position.x = 0.0;
-            velocity.x = 0.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 1.0 / 10_000.0;
+
+    /// Jump from standing and fall back to the take-off height, returning the seconds to
+    /// the top and the seconds in the air
+    fn simulate_jump(physics: &Physics) -> (f32, f32) {
+        let mut player = Player::new(0.0, 0.0);
+        player.set_on_ground(true);
+        assert!(player.jump());
+        let start_y = player.position().y;
+        let mut time = 0.0;
+        let mut peak = None;
+        while time < 5.0 {
+            physics.apply_gravity(&mut player, STEP);
+            physics.update_position(&mut player, STEP);
+            time += STEP;
+            if peak.is_none() && player.velocity().y >= 0.0 {
+                peak = Some(time);
+            }
+            if player.position().y >= start_y {
+                break;
+            }
         }
+        (peak.expect("the jump tops out"), time)
+    }
 
-        // Don't restrict right boundary to allow scrolling
+    #[test]
+    fn gravity_changes_with_the_phase_of_the_jump() {
+        let physics = Physics::new();
+        let mut player = Player::new(0.0, 0.0);
+        player.set_on_ground(true);
+        player.jump();
+        assert_eq!(physics.gravity_for(&player), physics.gravity);
 
-        player.set_position(position);
-        player.set_velocity(velocity);
+        player.set_velocity(Vec2::new(0.0, GameConfig::APEX_HANG_VELOCITY / 2.0));
+        assert_eq!(
+            physics.gravity_for(&player),
+            physics.gravity * GameConfig::APEX_HANG_GRAVITY_SCALE
+        );
+
+        player.set_velocity(Vec2::new(0.0, GameConfig::APEX_HANG_VELOCITY * 2.0));
+        assert_eq!(
+            physics.gravity_for(&player),
+            physics.gravity * GameConfig::FALL_GRAVITY_SCALE
+        );
     }
-}
 
-impl Default for Physics {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn landing_reports_the_speed_it_hit_at() {
+        let physics = Physics::new();
+        let mut player = Player::new(0.0, 0.0);
+        player.set_velocity(Vec2::new(0.0, 600.0));
+        // Sunk a few pixels into a floor whose top is at the player's feet
+        let (_, impact) = physics
+            .touch_solid(&mut player, (-50.0, 28.0, 50.0, 60.0))
+            .expect("the player overlaps the floor");
+        assert_eq!(impact, Some(600.0));
+        assert!(player.is_on_ground());
+        assert_eq!(player.velocity().y, 0.0);
+
+        // Bumping a head isn't a landing
+        let mut player = Player::new(0.0, 20.0);
+        player.set_velocity(Vec2::new(0.0, -300.0));
+        let (_, impact) = physics
+            .touch_solid(&mut player, (-50.0, -10.0, 50.0, 24.0))
+            .expect("the player overlaps the ceiling");
+        assert_eq!(impact, None);
+    }
+
+    #[test]
+    fn jump_timing_matches_the_analytic_values() {
+        let physics = Physics::new();
+        let g = physics.gravity;
+        let hang = g * physics.apex_hang_gravity_scale;
+        let fall = g * physics.fall_gravity_scale;
+        let launch = 400.0;
+        let apex = GameConfig::APEX_HANG_VELOCITY;
+
+        // Full gravity until the apex band, then the lighter hang gravity to the top
+        let rise_time = (launch - apex) / g;
+        let hang_time = apex / hang;
+        let peak_time = rise_time + hang_time;
+        let height = (launch * launch - apex * apex) / (2.0 * g) + apex * apex / (2.0 * hang);
+        // Out of the band again on the way down, then falling at the heavier gravity for
+        // the rest of the drop: s = apex t + fall t^2 / 2
+        let rest = height - apex * apex / (2.0 * hang);
+        let fall_time = (-apex + (apex * apex + 2.0 * fall * rest).sqrt()) / fall;
+        let air_time = peak_time + hang_time + fall_time;
+
+        let (simulated_peak, simulated_air) = simulate_jump(&physics);
+        assert!(
+            (simulated_peak - peak_time).abs() < 0.005,
+            "peak at {simulated_peak}, expected {peak_time}"
+        );
+        assert!(
+            (simulated_air - air_time).abs() < 0.005,
+            "landed at {simulated_air}, expected {air_time}"
+        );
+    }
+
+    #[test]
+    fn the_fall_is_quicker_than_the_rise() {
+        let mut physics = Physics::new();
+        let (peak, landed) = simulate_jump(&physics);
+        assert!(landed - peak < peak);
+
+        physics.fall_gravity_scale = 1.0;
+        physics.apex_hang_gravity_scale = 1.0;
+        let (peak, landed) = simulate_jump(&physics);
+        // Without the multipliers it's the plain parabola, as long going down as up
+        assert!((peak - 400.0 / physics.gravity).abs() < 0.005);
+        assert!((landed - 800.0 / physics.gravity).abs() < 0.005);
+    }
+
+    /// Drop a 32 pixel box into still water with `buoyancy` and let it settle for
+    /// `seconds`, returning how much of it ends up under and how fast it's still moving
+    fn settle(buoyancy: f32, seconds: f32) -> (f32, f32) {
+        let physics = Physics::new();
+        // Centered where the swell crosses the still level, so the surface is flat there
+        let water = Water::new(
+            Vec2::new(-500.0, 500.0),
+            Vec2::new(1000.0, 2000.0),
+            Vec2::ZERO,
+        );
+        let mut body = PhysicsBody::new(-16.0, 400.0, 32.0, 32.0);
+        let step = 1.0 / 120.0;
+        let mut submerged = 0.0;
+        for _ in 0..(seconds / step) as usize {
+            body.velocity.y += physics.gravity * step;
+            submerged = physics.immerse(&mut body, &water, buoyancy, step);
+            body.position += body.velocity * step;
+        }
+        (submerged, body.velocity.y)
+    }
+
+    #[test]
+    fn floating_bodies_settle_at_the_equilibrium_depth() {
+        let physics = Physics::new();
+        for buoyancy in [1.25, GameConfig::BOMB_BUOYANCY, 2.0, 4.0] {
+            let (submerged, speed) = settle(buoyancy, 30.0);
+            assert!(
+                (submerged - 1.0 / buoyancy).abs() < 0.005,
+                "{buoyancy}: {submerged} under"
+            );
+            assert!(speed.abs() < 0.5);
+            // Where the lift just holds it up
+            assert!((physics.buoyancy(submerged, buoyancy) - physics.gravity).abs() < 5.0);
+        }
+    }
+
+    #[test]
+    fn bodies_below_1_sink() {
+        let (submerged, speed) = settle(GameConfig::ENEMY_BUOYANCY, 2.0);
+        assert_eq!(submerged, 1.0);
+        assert!(speed > 0.0);
     }
 }
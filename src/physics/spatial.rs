@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+/// Uniform grid broad phase: items are filed under every cell their bounds touch, so a
+/// query only has to look at what's near it instead of everything in the level
+// Only the collision bench uses it so far, to measure it against the plain loop
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<T>>,
+}
+
+#[allow(dead_code)]
+impl<T: Copy + Ord> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Number of non-empty cells
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn insert(&mut self, item: T, bounds: (f32, f32, f32, f32)) {
+        let (x1, y1, x2, y2) = self.cell_range(bounds);
+        for cx in x1..=x2 {
+            for cy in y1..=y2 {
+                self.cells.entry((cx, cy)).or_default().push(item);
+            }
+        }
+    }
+
+    /// Items whose cells overlap `bounds`, written into `found` sorted and without
+    /// repeats so callers see them in the same order as a plain loop would. These are
+    /// only candidates; the caller still does the exact overlap test.
+    pub fn query(&self, bounds: (f32, f32, f32, f32), found: &mut Vec<T>) {
+        found.clear();
+        let (x1, y1, x2, y2) = self.cell_range(bounds);
+        for cx in x1..=x2 {
+            for cy in y1..=y2 {
+                if let Some(items) = self.cells.get(&(cx, cy)) {
+                    found.extend_from_slice(items);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+    }
+
+    fn cell_range(&self, bounds: (f32, f32, f32, f32)) -> (i32, i32, i32, i32) {
+        let (x1, y1, x2, y2) = bounds;
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        (cell(x1), cell(y1), cell(x2), cell(y2))
+    }
+}
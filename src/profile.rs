@@ -0,0 +1,215 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::game::daily::DAILY_RECORDS_PATH;
+use crate::leaderboard::LEADERBOARD_PATH;
+use crate::save::SAVE_PATH;
+use crate::settings::SETTINGS_PATH;
+use crate::shop::SHOP_PATH;
+use crate::stats::STATS_PATH;
+
+/// Which slots hold a profile and which one is playing, relative to the working directory
+pub const PROFILES_PATH: &str = "profiles.txt";
+
+/// Each profile keeps its files in its own folder under here
+pub const PROFILES_DIR: &str = "profiles";
+
+pub const PROFILE_COUNT: usize = 3;
+
+/// Characters allowed in a profile name
+pub const PROFILE_NAME_LENGTH: usize = 12;
+
+/// Files that used to sit in the working directory before there were profiles. Settings
+/// aren't moved: the shared file stays behind as the starting point for every profile.
+const LEGACY_FILES: [&str; 5] = [
+    STATS_PATH,
+    LEADERBOARD_PATH,
+    SHOP_PATH,
+    DAILY_RECORDS_PATH,
+    SAVE_PATH,
+];
+
+/// One save slot's player: their settings, stats, shop, scores and saved run all live in
+/// the slot's folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Zero-based; shown to the player counting from 1
+    pub slot: usize,
+    pub name: String,
+}
+
+impl Profile {
+    pub fn new(slot: usize, name: &str) -> Self {
+        Self {
+            slot,
+            name: sanitize_name(name),
+        }
+    }
+
+    /// The profile made on a first run, or to hold data from before profiles
+    pub fn first() -> Self {
+        Self::new(0, "Player 1")
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        slot_dir(self.slot)
+    }
+
+    /// Where this profile keeps the file that was once at `file` in the working directory
+    pub fn path(&self, file: &str) -> PathBuf {
+        self.dir().join(file)
+    }
+}
+
+fn slot_dir(slot: usize) -> PathBuf {
+    Path::new(PROFILES_DIR).join(format!("slot{}", slot + 1))
+}
+
+/// Printable characters only, trimmed and cut to length
+pub fn sanitize_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(PROFILE_NAME_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The save slots, persisted as an `active SLOT` line and a `slot SLOT NAME` line for each
+/// one that's taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profiles {
+    pub names: [Option<String>; PROFILE_COUNT],
+    /// Slot of the profile being played; always one that's taken
+    pub active: usize,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        let first = Profile::first();
+        let mut names: [Option<String>; PROFILE_COUNT] = Default::default();
+        names[first.slot] = Some(first.name);
+        Self {
+            names,
+            active: first.slot,
+        }
+    }
+}
+
+impl Profiles {
+    /// The slots from the default location. On a first run, or the first run since
+    /// profiles were added, this sets up slot 1 and moves any older files into it.
+    pub fn load() -> Self {
+        match std::fs::read_to_string(PROFILES_PATH) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => {
+                let profiles = Self::default();
+                let _ = profiles.migrate_legacy_files();
+                let _ = profiles.save();
+                profiles
+            }
+        }
+    }
+
+    /// Parse the slots, skipping lines that don't make sense. If the active slot isn't
+    /// taken, the first one that is takes over, or slot 1 is set up if none are.
+    pub fn parse(source: &str) -> Self {
+        let mut names: [Option<String>; PROFILE_COUNT] = Default::default();
+        let mut active = None;
+
+        for line in source.lines() {
+            let mut words = line.trim().splitn(3, ' ');
+            match (words.next(), words.next().map(str::parse::<usize>)) {
+                (Some("active"), Some(Ok(slot))) if (1..=PROFILE_COUNT).contains(&slot) => {
+                    active = Some(slot - 1);
+                }
+                (Some("slot"), Some(Ok(slot))) if (1..=PROFILE_COUNT).contains(&slot) => {
+                    let name = sanitize_name(words.next().unwrap_or(""));
+                    if !name.is_empty() {
+                        names[slot - 1] = Some(name);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let active = active
+            .filter(|slot| names[*slot].is_some())
+            .or_else(|| names.iter().position(Option::is_some));
+        match active {
+            Some(active) => Self { names, active },
+            None => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        for slot in 0..PROFILE_COUNT {
+            if self.names[slot].is_some() {
+                std::fs::create_dir_all(slot_dir(slot))?;
+            }
+        }
+        std::fs::write(PROFILES_PATH, self.to_string())
+    }
+
+    pub fn get(&self, slot: usize) -> Option<Profile> {
+        let name = self.names.get(slot)?.as_deref()?;
+        Some(Profile::new(slot, name))
+    }
+
+    pub fn active(&self) -> Profile {
+        self.get(self.active).unwrap_or_else(Profile::first)
+    }
+
+    /// Take an empty slot for a new profile; an empty name gets a numbered default
+    pub fn create(&mut self, slot: usize, name: &str) -> Profile {
+        let mut name = sanitize_name(name);
+        if name.is_empty() {
+            name = format!("Player {}", slot + 1);
+        }
+        self.names[slot] = Some(name.clone());
+        Profile { slot, name }
+    }
+
+    /// Empty a slot and throw away everything in its folder. The profile being played
+    /// can't be deleted.
+    pub fn delete(&mut self, slot: usize) -> io::Result<()> {
+        if slot == self.active {
+            return Err(io::Error::other("Switch to another profile first"));
+        }
+        match std::fs::remove_dir_all(slot_dir(slot)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        self.names[slot] = None;
+        Ok(())
+    }
+
+    /// Move files from before profiles into slot 1, and copy the shared settings there too
+    fn migrate_legacy_files(&self) -> io::Result<()> {
+        let first = Profile::first();
+        std::fs::create_dir_all(first.dir())?;
+        for file in LEGACY_FILES {
+            if Path::new(file).exists() {
+                std::fs::rename(file, first.path(file))?;
+            }
+        }
+        if Path::new(SETTINGS_PATH).exists() {
+            std::fs::copy(SETTINGS_PATH, first.path(SETTINGS_PATH))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Profiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "active {}", self.active + 1)?;
+        for (slot, name) in self.names.iter().enumerate() {
+            if let Some(name) = name {
+                writeln!(f, "slot {} {}", slot + 1, name)?;
+            }
+        }
+        Ok(())
+    }
+}
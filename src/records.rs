@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::profile::Profile;
+
+/// Where each level's best results are persisted, inside each profile's folder
+pub const LEVEL_RECORDS_PATH: &str = "records.txt";
+
+/// Best results on one level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelRecord {
+    pub best_score: i32,
+    /// Fewest seconds taken to beat the level, if it's been beaten
+    pub best_time: Option<f32>,
+    /// Most stars earned in one run, 0 to `MAX_STARS`
+    pub stars: u8,
+}
+
+/// Stars a level can award: one for finishing, one for beating par and one for beating
+/// par with every collectible picked up
+pub const MAX_STARS: u8 = 3;
+
+/// Stars earned by beating a level in `cleared_in` seconds with `found` of its `total`
+/// collectibles. A level without a par time gives the second star for any finish.
+pub fn star_rating(cleared_in: f32, par: Option<f32>, found: usize, total: usize) -> u8 {
+    let under_par = par.is_none_or(|par| cleared_in <= par);
+    match (under_par, found >= total) {
+        (false, _) => 1,
+        (true, false) => 2,
+        (true, true) => MAX_STARS,
+    }
+}
+
+/// Best results per level file, persisted as `SCORE TIME STARS LEVEL` lines with `-` for
+/// a level that hasn't been beaten. Older `SCORE TIME LEVEL` lines read as no stars.
+#[derive(Debug, Clone, Default)]
+pub struct LevelRecords {
+    pub levels: BTreeMap<String, LevelRecord>,
+}
+
+impl LevelRecords {
+    pub fn load(profile: &Profile) -> Self {
+        Self::load_from(profile.path(LEVEL_RECORDS_PATH))
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse the records, skipping malformed lines
+    pub fn parse(source: &str) -> Self {
+        let mut records = Self::default();
+        for line in source.lines() {
+            let mut words = line.trim().splitn(3, ' ');
+            let (Some(score), Some(time), Some(mut level)) =
+                (words.next(), words.next(), words.next())
+            else {
+                continue;
+            };
+            let mut stars = 0;
+            if let Some((count, rest)) = level.split_once(' ') {
+                if let Ok(count) = count.parse::<u8>() {
+                    stars = count.min(MAX_STARS);
+                    level = rest;
+                }
+            }
+            let best_time = match time {
+                "-" => None,
+                time => match time.parse() {
+                    Ok(time) => Some(time),
+                    Err(_) => continue,
+                },
+            };
+            if let Ok(best_score) = score.parse() {
+                records.levels.insert(
+                    level.to_string(),
+                    LevelRecord {
+                        best_score,
+                        best_time,
+                        stars,
+                    },
+                );
+            }
+        }
+        records
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(LEVEL_RECORDS_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, level: &str) -> Option<LevelRecord> {
+        self.levels.get(level).copied()
+    }
+
+    /// Keep a finished run's score, and its time and stars if it beat the level, when
+    /// they're better than the level's bests
+    pub fn record(&mut self, level: &str, score: i32, cleared_in: Option<(f32, u8)>) {
+        let record = self.levels.entry(level.to_string()).or_default();
+        record.best_score = record.best_score.max(score);
+        if let Some((time, stars)) = cleared_in {
+            record.best_time = Some(record.best_time.map_or(time, |best| best.min(time)));
+            record.stars = record.stars.max(stars);
+        }
+    }
+
+    /// Stars earned across every level, which later levels can ask for
+    pub fn total_stars(&self) -> u32 {
+        self.levels.values().map(|record| record.stars as u32).sum()
+    }
+
+    /// Forget a level, e.g. once its file is deleted
+    pub fn remove(&mut self, level: &str) {
+        self.levels.remove(level);
+    }
+}
+
+impl fmt::Display for LevelRecords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (level, record) in &self.levels {
+            match record.best_time {
+                Some(time) => writeln!(
+                    f,
+                    "{} {:.2} {} {}",
+                    record.best_score, time, record.stars, level
+                )?,
+                None => writeln!(f, "{} - {} {}", record.best_score, record.stars, level)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Small deterministic random number generator (SplitMix64), so a seed always
+/// produces the same sequence on every machine. Anything that changes how a run plays
+/// out draws from the game's own instance so runs can be reproduced from their seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A seed that differs between launches, for when nothing asked for a particular one.
+    /// The browser's clock only ticks in milliseconds, so a counter keeps two seeds taken
+    /// in the same instant apart.
+    pub fn clock_seed() -> u64 {
+        static CALLS: AtomicU64 = AtomicU64::new(0);
+        let micros = (macroquad::miniquad::date::now().max(0.0) * 1_000_000.0) as u64;
+        let call = CALLS.fetch_add(1, Ordering::Relaxed);
+        Self::new(micros ^ call.wrapping_mul(0x9E37_79B9_7F4A_7C15)).next_u64()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in 0.0..1.0
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform value in min..max
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// True with the given probability
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Uniform index in 0..len; `len` must not be zero
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
@@ -0,0 +1,121 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::entities::{EntityManager, Lava, Player};
+use crate::game::survival::EnemySpawner;
+use crate::profile::Profile;
+use crate::rng::SeededRng;
+
+/// Where a run in progress is kept between sessions, inside each profile's folder
+pub const SAVE_PATH: &str = "save.json";
+
+/// Bumped whenever `SaveGame` changes shape; older saves are ignored rather than misread
+pub const SAVE_VERSION: u32 = 1;
+
+/// Everything needed to pick a run back up exactly where it was left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    /// The daily challenge's date and whether it's a practice run; `None` for a normal run.
+    /// The date is the seed the challenge is rebuilt from.
+    pub daily: Option<(Date, bool)>,
+    /// The custom level file the run is on; `None` for the built-in level
+    #[serde(default)]
+    pub level: Option<PathBuf>,
+    pub player: Player,
+    pub entities: EntityManager,
+    pub camera_offset: Vec2,
+    pub score: i32,
+    /// Points from pickups, which the recomputed part of the score doesn't cover
+    #[serde(default)]
+    pub bonus_score: i32,
+    pub time_survived: f32,
+    /// Where the level clock had got to, so timed platforms pick up in step
+    #[serde(default)]
+    pub level_clock: f32,
+    pub bullet_time: f32,
+    pub lantern_timer: f32,
+    pub lava: Option<Lava>,
+    pub lava_bonus: f32,
+    pub wind: f32,
+    /// Coins picked up this run, not yet banked in the shop wallet
+    #[serde(default)]
+    pub coins: u32,
+    /// Level pickups taken this run, towards its star rating
+    #[serde(default)]
+    pub collectibles: usize,
+    /// An assist option was on at some point in the run
+    #[serde(default)]
+    pub assisted: bool,
+    /// Extra hearts left this run
+    #[serde(default)]
+    pub hearts: u32,
+    /// Wave progress; only set for a survival run
+    #[serde(default)]
+    pub survival: Option<EnemySpawner>,
+    /// Where the run's random sequence had got to, so it carries on unchanged
+    #[serde(default)]
+    pub rng: Option<SeededRng>,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+impl SaveGame {
+    pub fn load(profile: &Profile) -> Option<Self> {
+        Self::load_from(profile.path(SAVE_PATH))
+    }
+
+    /// The save at `path`, if there is one this version of the game can read
+    pub fn load_from(path: impl AsRef<Path>) -> Option<Self> {
+        let source = std::fs::read_to_string(path).ok()?;
+        let header: SaveHeader = serde_json::from_str(&source).ok()?;
+        if header.version != SAVE_VERSION {
+            return None;
+        }
+        serde_json::from_str(&source).ok()
+    }
+
+    /// Whether the main menu should offer to continue
+    pub fn exists(profile: &Profile) -> bool {
+        Self::load(profile).is_some()
+    }
+
+    pub fn save(&self, profile: &Profile) -> io::Result<()> {
+        self.save_to(profile.path(SAVE_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Remove the profile's saved run; a missing file counts as success
+    pub fn delete(profile: &Profile) -> io::Result<()> {
+        match std::fs::remove_file(profile.path(SAVE_PATH)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `#[serde(with = "crate::save::color_serde")]` for macroquad colors, stored as `[r, g, b, a]`
+pub mod color_serde {
+    use macroquad::prelude::Color;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        [color.r, color.g, color.b, color.a].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(deserializer)?;
+        Ok(Color::new(r, g, b, a))
+    }
+}
@@ -0,0 +1,551 @@
+use macroquad::prelude::*;
+
+use std::fmt;
+use std::path::Path;
+
+use crate::config::GameConfig;
+use crate::entities::PlayerSkin;
+use crate::graphics::{HudLayout, PaletteKind, RenderStyle};
+use crate::input::{Action, ControlPreset, KeyBindings, TouchLayout};
+use crate::locale::{tr, tr_with, DEFAULT_LANGUAGE};
+use crate::profile::Profile;
+
+/// Settings shared by every profile, relative to the working directory. Each profile
+/// keeps its own copy once it changes anything.
+pub const SETTINGS_PATH: &str = "settings.cfg";
+
+/// Render rate caps offered to the player; 0 means no limit
+pub const FRAME_LIMIT_OPTIONS: [u32; 5] = [0, 30, 60, 120, 144];
+
+/// Player-adjustable settings, persisted as `key = value` lines
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub window_width: i32,
+    pub window_height: i32,
+    pub fullscreen: bool,
+    pub high_dpi: bool,
+    /// Pause when the game stalls, e.g. after alt-tabbing away
+    pub auto_pause: bool,
+    /// Render rate cap from `FRAME_LIMIT_OPTIONS`, 0 for off
+    pub frame_limit: u32,
+    /// Player appearance, picked from the skins menu
+    pub skin: &'static PlayerSkin,
+    /// Very long drops end the run
+    pub fall_damage: bool,
+    /// HUD text, margin and bar size multiplier; `None` picks one for the window size
+    pub ui_scale: Option<f32>,
+    /// Share of the gap to its target the camera closes each step where it eases, 1 to snap
+    pub camera_smoothing: f32,
+    /// List pickups, kills and checkpoints in the top-right corner during play
+    pub event_feed: bool,
+    /// Code of the language the UI is shown in; anything but English is read from `lang/`
+    pub language: String,
+
+    // Audio, each 0 to 1
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    /// Silences everything; M switches it
+    pub muted: bool,
+    pub sfx_muted: bool,
+    pub music_muted: bool,
+    /// Menu sounds
+    pub ui_muted: bool,
+
+    // Controller
+    /// Shake the controller on landings, hits and explosions, where it can
+    pub rumble: bool,
+    /// Rumble strength multiplier, 0 to 1
+    pub rumble_intensity: f32,
+    /// Where the on-screen buttons sit, moved on the touch layout screen
+    pub touch_layout: TouchLayout,
+
+    // Controls
+    /// Which set of keys plays the game
+    pub controls: ControlPreset,
+    /// The keys for the custom preset, picked on the controls screen
+    pub custom_bindings: KeyBindings,
+
+    // Accessibility
+    /// Sprint key switches sprinting on and off instead of being held
+    pub toggle_sprint: bool,
+    /// Holding jump jumps again as soon as the player lands
+    pub auto_repeat_jump: bool,
+    /// Hold decorative animation (clouds, grass, floating pickups) at rest
+    pub reduced_motion: bool,
+    /// Bold outlined colors for the player, platforms, hazards and collectibles
+    pub high_contrast: bool,
+    /// Color scheme, including colorblind-safe alternatives
+    pub palette: PaletteKind,
+
+    // Assist; runs with any of these on are marked on the high score table
+    /// Nothing hurts the player, and falling out of the level puts them back on safe ground
+    pub invincible: bool,
+    /// Jump again in the air as often as wanted
+    pub infinite_jumps: bool,
+    /// World speed multiplier, from `ASSIST_SPEED_MIN` to `ASSIST_SPEED_MAX`
+    pub game_speed: f32,
+    /// Lift the player onto a ledge whose top they only just failed to clear
+    pub auto_ledge: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_width: 1280,
+            window_height: 720,
+            fullscreen: false,
+            high_dpi: false,
+            auto_pause: true,
+            frame_limit: 0,
+            skin: PlayerSkin::classic(),
+            fall_damage: false,
+            ui_scale: None,
+            camera_smoothing: GameConfig::CAMERA_SMOOTHING,
+            event_feed: true,
+            language: DEFAULT_LANGUAGE.to_string(),
+            master_volume: GameConfig::MASTER_VOLUME,
+            sfx_volume: GameConfig::SFX_VOLUME,
+            music_volume: GameConfig::MUSIC_VOLUME,
+            muted: false,
+            sfx_muted: false,
+            music_muted: false,
+            ui_muted: false,
+            rumble: true,
+            rumble_intensity: 1.0,
+            touch_layout: TouchLayout::default(),
+            controls: ControlPreset::Default,
+            custom_bindings: KeyBindings::default(),
+            toggle_sprint: false,
+            auto_repeat_jump: false,
+            reduced_motion: false,
+            high_contrast: false,
+            palette: PaletteKind::Default,
+            invincible: false,
+            infinite_jumps: false,
+            game_speed: 1.0,
+            auto_ledge: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Load a profile's settings, or the shared ones if it hasn't changed any yet, falling
+    /// back to defaults for anything missing
+    pub fn load(profile: &Profile) -> Self {
+        let path = profile.path(SETTINGS_PATH);
+        if path.exists() {
+            Self::load_from(path)
+        } else {
+            Self::load_from(SETTINGS_PATH)
+        }
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse settings text; unknown keys and malformed values keep their defaults
+    pub fn parse(source: &str) -> Self {
+        let mut settings = Self::default();
+
+        for line in source.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            settings.apply(key.trim(), value.trim());
+        }
+
+        settings
+    }
+
+    fn apply(&mut self, key: &str, value: &str) {
+        match key {
+            "window_width" => set_parsed(&mut self.window_width, value),
+            "window_height" => set_parsed(&mut self.window_height, value),
+            "fullscreen" => set_parsed(&mut self.fullscreen, value),
+            "high_dpi" => set_parsed(&mut self.high_dpi, value),
+            "auto_pause" => set_parsed(&mut self.auto_pause, value),
+            "fall_damage" => set_parsed(&mut self.fall_damage, value),
+            "toggle_sprint" => set_parsed(&mut self.toggle_sprint, value),
+            "auto_repeat_jump" => set_parsed(&mut self.auto_repeat_jump, value),
+            "reduced_motion" => set_parsed(&mut self.reduced_motion, value),
+            "high_contrast" => set_parsed(&mut self.high_contrast, value),
+            "event_feed" => set_parsed(&mut self.event_feed, value),
+            "invincible" => set_parsed(&mut self.invincible, value),
+            "infinite_jumps" => set_parsed(&mut self.infinite_jumps, value),
+            "auto_ledge" => set_parsed(&mut self.auto_ledge, value),
+            "game_speed" => set_slider(self, SettingSlider::GameSpeed, value),
+            "language" if !value.is_empty() => self.language = value.to_string(),
+            "skin" => {
+                if let Some(skin) = PlayerSkin::from_name(value) {
+                    self.skin = skin;
+                }
+            }
+            "palette" => {
+                if let Some(palette) = PaletteKind::from_name(value) {
+                    self.palette = palette;
+                }
+            }
+            "camera_smoothing" => set_slider(self, SettingSlider::CameraSmoothing, value),
+            "master_volume" => set_slider(self, SettingSlider::MasterVolume, value),
+            "sfx_volume" => set_slider(self, SettingSlider::SfxVolume, value),
+            "music_volume" => set_slider(self, SettingSlider::MusicVolume, value),
+            "muted" => set_parsed(&mut self.muted, value),
+            "sfx_muted" => set_parsed(&mut self.sfx_muted, value),
+            "music_muted" => set_parsed(&mut self.music_muted, value),
+            "ui_muted" => set_parsed(&mut self.ui_muted, value),
+            "rumble" => set_parsed(&mut self.rumble, value),
+            "rumble_intensity" => set_slider(self, SettingSlider::RumbleIntensity, value),
+            "touch_layout" => {
+                if let Some(layout) = TouchLayout::parse(value) {
+                    self.touch_layout = layout;
+                }
+            }
+            "controls" => {
+                if let Some(controls) = ControlPreset::from_name(value) {
+                    self.controls = controls;
+                }
+            }
+            _ if key.starts_with("bind_") => {
+                let name = &key["bind_".len()..];
+                if let Some(action) = Action::ALL.into_iter().find(|a| a.name() == name) {
+                    self.custom_bindings
+                        .set_keys(action, KeyBindings::parse_keys(value));
+                }
+            }
+            "ui_scale" => {
+                if value.eq_ignore_ascii_case("auto") {
+                    self.ui_scale = None;
+                } else if let Ok(scale) = value.parse::<f32>() {
+                    self.ui_scale =
+                        Some(scale.clamp(GameConfig::UI_MIN_SCALE, GameConfig::UI_MAX_SCALE));
+                }
+            }
+            "frame_limit" => {
+                if value.eq_ignore_ascii_case("off") {
+                    self.frame_limit = 0;
+                } else if let Some(limit) = value
+                    .parse()
+                    .ok()
+                    .filter(|limit| FRAME_LIMIT_OPTIONS.contains(limit))
+                {
+                    self.frame_limit = limit;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(SETTINGS_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// HUD placement and sizing for the current window
+    pub fn hud_layout(&self) -> HudLayout {
+        HudLayout::new(self.ui_scale.unwrap_or_else(HudLayout::auto_scale))
+    }
+
+    /// How the accessibility options affect drawing
+    pub fn render_style(&self) -> RenderStyle {
+        RenderStyle {
+            reduced_motion: self.reduced_motion,
+            high_contrast: self.high_contrast,
+            palette: self.palette,
+        }
+    }
+
+    /// The keys that play the game under the chosen preset
+    pub fn key_bindings(&self) -> KeyBindings {
+        match self.controls {
+            ControlPreset::Custom => self.custom_bindings.clone(),
+            preset => KeyBindings::preset(preset),
+        }
+    }
+
+    /// Whether any assist option is changing how the game plays
+    pub fn assists_active(&self) -> bool {
+        self.invincible || self.infinite_jumps || self.auto_ledge || self.game_speed != 1.0
+    }
+
+    /// Window setup for macroquad, built from these settings
+    pub fn window_conf(&self) -> Conf {
+        Conf {
+            window_title: format!("Platformer v{}", env!("CARGO_PKG_VERSION")),
+            window_width: self.window_width.max(320),
+            window_height: self.window_height.max(240),
+            fullscreen: self.fullscreen,
+            high_dpi: self.high_dpi,
+            window_resizable: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "window_width = {}", self.window_width)?;
+        writeln!(f, "window_height = {}", self.window_height)?;
+        writeln!(f, "fullscreen = {}", self.fullscreen)?;
+        writeln!(f, "high_dpi = {}", self.high_dpi)?;
+        writeln!(f, "auto_pause = {}", self.auto_pause)?;
+        match self.frame_limit {
+            0 => writeln!(f, "frame_limit = off")?,
+            limit => writeln!(f, "frame_limit = {}", limit)?,
+        }
+        writeln!(f, "skin = {}", self.skin.name)?;
+        writeln!(f, "fall_damage = {}", self.fall_damage)?;
+        match self.ui_scale {
+            None => writeln!(f, "ui_scale = auto")?,
+            Some(scale) => writeln!(f, "ui_scale = {}", scale)?,
+        }
+        writeln!(f, "camera_smoothing = {}", self.camera_smoothing)?;
+        writeln!(f, "event_feed = {}", self.event_feed)?;
+        writeln!(f, "language = {}", self.language)?;
+
+        writeln!(f, "\n# Audio")?;
+        writeln!(f, "master_volume = {}", self.master_volume)?;
+        writeln!(f, "sfx_volume = {}", self.sfx_volume)?;
+        writeln!(f, "music_volume = {}", self.music_volume)?;
+        writeln!(f, "muted = {}", self.muted)?;
+        writeln!(f, "sfx_muted = {}", self.sfx_muted)?;
+        writeln!(f, "music_muted = {}", self.music_muted)?;
+        writeln!(f, "ui_muted = {}", self.ui_muted)?;
+
+        writeln!(f, "\n# Controller")?;
+        writeln!(f, "rumble = {}", self.rumble)?;
+        writeln!(f, "rumble_intensity = {}", self.rumble_intensity)?;
+        writeln!(f, "touch_layout = {}", self.touch_layout)?;
+
+        writeln!(f, "\n# Controls")?;
+        writeln!(f, "controls = {}", self.controls.name())?;
+        for action in Action::ALL {
+            let keys = self.custom_bindings.describe(action);
+            writeln!(f, "bind_{} = {}", action.name(), keys)?;
+        }
+
+        writeln!(f, "\n# Accessibility")?;
+        writeln!(f, "toggle_sprint = {}", self.toggle_sprint)?;
+        writeln!(f, "auto_repeat_jump = {}", self.auto_repeat_jump)?;
+        writeln!(f, "reduced_motion = {}", self.reduced_motion)?;
+        writeln!(f, "high_contrast = {}", self.high_contrast)?;
+        writeln!(f, "palette = {}", self.palette.name())?;
+
+        writeln!(f, "\n# Assist")?;
+        writeln!(f, "invincible = {}", self.invincible)?;
+        writeln!(f, "infinite_jumps = {}", self.infinite_jumps)?;
+        writeln!(f, "game_speed = {}", self.game_speed)?;
+        writeln!(f, "auto_ledge = {}", self.auto_ledge)
+    }
+}
+
+/// Settings with a continuous value, changed with a slider on the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSlider {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    UiScale,
+    CameraSmoothing,
+    RumbleIntensity,
+    GameSpeed,
+}
+
+impl SettingSlider {
+    /// Key of the slider's label in the language files
+    pub fn key(&self) -> &'static str {
+        match self {
+            SettingSlider::MasterVolume => "slider.master_volume",
+            SettingSlider::SfxVolume => "slider.sfx_volume",
+            SettingSlider::MusicVolume => "slider.music_volume",
+            SettingSlider::UiScale => "slider.ui_scale",
+            SettingSlider::CameraSmoothing => "slider.camera_smoothing",
+            SettingSlider::RumbleIntensity => "slider.rumble_intensity",
+            SettingSlider::GameSpeed => "slider.game_speed",
+        }
+    }
+
+    /// Lowest and highest values the setting accepts
+    pub fn range(&self) -> (f32, f32) {
+        match self {
+            SettingSlider::MasterVolume
+            | SettingSlider::SfxVolume
+            | SettingSlider::MusicVolume
+            | SettingSlider::RumbleIntensity => (0.0, 1.0),
+            SettingSlider::UiScale => (GameConfig::UI_MIN_SCALE, GameConfig::UI_MAX_SCALE),
+            SettingSlider::CameraSmoothing => (
+                GameConfig::CAMERA_SMOOTHING_MIN,
+                GameConfig::CAMERA_SMOOTHING_MAX,
+            ),
+            SettingSlider::GameSpeed => {
+                (GameConfig::ASSIST_SPEED_MIN, GameConfig::ASSIST_SPEED_MAX)
+            }
+        }
+    }
+
+    pub fn get(&self, settings: &Settings) -> f32 {
+        match self {
+            SettingSlider::MasterVolume => settings.master_volume,
+            SettingSlider::SfxVolume => settings.sfx_volume,
+            SettingSlider::MusicVolume => settings.music_volume,
+            SettingSlider::UiScale => settings.hud_layout().scale,
+            SettingSlider::CameraSmoothing => settings.camera_smoothing,
+            SettingSlider::RumbleIntensity => settings.rumble_intensity,
+            SettingSlider::GameSpeed => settings.game_speed,
+        }
+    }
+
+    /// Store `value`, clamped into range
+    pub fn set(&self, settings: &mut Settings, value: f32) {
+        let (min, max) = self.range();
+        let value = value.clamp(min, max);
+        match self {
+            SettingSlider::MasterVolume => settings.master_volume = value,
+            SettingSlider::SfxVolume => settings.sfx_volume = value,
+            SettingSlider::MusicVolume => settings.music_volume = value,
+            // Touching the slider takes the HUD size off auto
+            SettingSlider::UiScale => settings.ui_scale = Some(value),
+            SettingSlider::CameraSmoothing => settings.camera_smoothing = value,
+            SettingSlider::RumbleIntensity => settings.rumble_intensity = value,
+            SettingSlider::GameSpeed => settings.game_speed = value,
+        }
+    }
+
+    /// Where the value sits in its range, 0 to 1
+    pub fn fraction(&self, settings: &Settings) -> f32 {
+        let (min, max) = self.range();
+        ((self.get(settings) - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    pub fn set_fraction(&self, settings: &mut Settings, fraction: f32) {
+        let (min, max) = self.range();
+        self.set(settings, min + (max - min) * fraction.clamp(0.0, 1.0));
+    }
+
+    /// Share of the range one Left/Right press moves the value
+    fn step_fraction(&self) -> f32 {
+        match self {
+            // Whole tenths of normal speed rather than a share of the range
+            SettingSlider::GameSpeed => {
+                let (min, max) = self.range();
+                GameConfig::ASSIST_SPEED_STEP / (max - min)
+            }
+            _ => GameConfig::SLIDER_STEP,
+        }
+    }
+
+    /// Move the value `steps` slider steps along its range
+    pub fn step(&self, settings: &mut Settings, steps: i32) {
+        let step = self.step_fraction();
+        let fraction = self.fraction(settings) + steps as f32 * step;
+        // Snap to the step grid so repeated presses land on round values
+        let snapped = (fraction / step).round() * step;
+        self.set_fraction(settings, snapped);
+    }
+
+    /// The value as shown next to the slider
+    pub fn display(&self, settings: &Settings) -> String {
+        let value = self.get(settings);
+        match self {
+            SettingSlider::MasterVolume
+            | SettingSlider::SfxVolume
+            | SettingSlider::MusicVolume
+            | SettingSlider::RumbleIntensity
+            | SettingSlider::GameSpeed => format!("{:.0}%", value * 100.0),
+            SettingSlider::UiScale if settings.ui_scale.is_none() => {
+                tr_with("slider.auto_scale", &[("scale", &format!("{:.2}", value))])
+            }
+            SettingSlider::UiScale => format!("{:.2}x", value),
+            SettingSlider::CameraSmoothing if value >= GameConfig::CAMERA_SMOOTHING_MAX => {
+                tr("slider.off")
+            }
+            SettingSlider::CameraSmoothing => format!("{:.2}", value),
+        }
+    }
+}
+
+/// Set a slider setting from text, clamping it into range
+fn set_slider(settings: &mut Settings, slider: SettingSlider, value: &str) {
+    if let Ok(value) = value.parse::<f32>() {
+        slider.set(settings, value);
+    }
+}
+
+/// Overwrite a setting only when the new value parses
+pub(crate) fn set_parsed<T: std::str::FromStr>(field: &mut T, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-4, "{value} != {expected}");
+    }
+
+    #[test]
+    fn slider_steps_move_a_share_of_the_range() {
+        let mut settings = Settings::default();
+        let slider = SettingSlider::MasterVolume;
+        slider.set(&mut settings, 0.5);
+        slider.step(&mut settings, 1);
+        assert_near(settings.master_volume, 0.5 + GameConfig::SLIDER_STEP);
+        slider.step(&mut settings, -3);
+        assert_near(settings.master_volume, 0.5 - 2.0 * GameConfig::SLIDER_STEP);
+    }
+
+    #[test]
+    fn slider_steps_snap_to_the_grid() {
+        let mut settings = Settings::default();
+        let slider = SettingSlider::MusicVolume;
+        // Dragged somewhere between two steps
+        slider.set(&mut settings, 0.33);
+        slider.step(&mut settings, 1);
+        assert_near(settings.music_volume, 0.4);
+    }
+
+    #[test]
+    fn sliders_stop_at_their_ends() {
+        let mut settings = Settings::default();
+        let slider = SettingSlider::SfxVolume;
+        slider.set(&mut settings, 0.95);
+        slider.step(&mut settings, 5);
+        assert_near(settings.sfx_volume, 1.0);
+        slider.step(&mut settings, -100);
+        assert_near(settings.sfx_volume, 0.0);
+    }
+
+    #[test]
+    fn game_speed_moves_in_tenths() {
+        let mut settings = Settings::default();
+        let slider = SettingSlider::GameSpeed;
+        slider.set(&mut settings, 1.0);
+        slider.step(&mut settings, -2);
+        assert_near(settings.game_speed, 0.8);
+        slider.step(&mut settings, 7);
+        assert_near(settings.game_speed, 1.5);
+    }
+
+    #[test]
+    fn palettes_cycle_both_ways() {
+        let start = PaletteKind::Default;
+        let mut palette = start;
+        for _ in 0..PaletteKind::ALL.len() {
+            palette = palette.next();
+        }
+        assert_eq!(palette, start);
+        assert_eq!(start.previous(), *PaletteKind::ALL.last().unwrap());
+        assert_eq!(start.previous().next(), start);
+    }
+}
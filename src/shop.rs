@@ -0,0 +1,211 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::config::GameConfig;
+use crate::entities::Player;
+use crate::locale::{tr, tr_with};
+use crate::profile::Profile;
+use crate::settings::set_parsed;
+
+/// Where the coin wallet and bought upgrades are kept, inside each profile's folder
+pub const SHOP_PATH: &str = "shop.txt";
+
+/// Something coins can buy, applied to every normal run from then on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Upgrade {
+    /// One more hit survived per run
+    ExtraHeart,
+    /// One more jump in the air
+    ExtraJump,
+    /// Faster running
+    Speed,
+    /// A few seconds of spawn protection at the start of each run
+    Shield,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 4] = [
+        Upgrade::ExtraHeart,
+        Upgrade::ExtraJump,
+        Upgrade::Speed,
+        Upgrade::Shield,
+    ];
+
+    /// Name used in `shop.txt`, and for its text in the language files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Upgrade::ExtraHeart => "hearts",
+            Upgrade::ExtraJump => "extra_jump",
+            Upgrade::Speed => "speed",
+            Upgrade::Shield => "shield",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr(&format!("upgrade.{}", self.key()))
+    }
+
+    pub fn description(&self) -> String {
+        tr(&format!("upgrade.{}.description", self.key()))
+    }
+
+    /// How many times it can be bought; one-time upgrades have 1
+    pub fn max_level(&self) -> u32 {
+        match self {
+            Upgrade::ExtraHeart => 3,
+            Upgrade::ExtraJump => 1,
+            Upgrade::Speed => 3,
+            Upgrade::Shield => 1,
+        }
+    }
+
+    /// Coins for the next level when `owned` levels have already been bought
+    pub fn cost(&self, owned: u32) -> u32 {
+        let base = match self {
+            Upgrade::ExtraHeart => 30,
+            Upgrade::ExtraJump => 60,
+            Upgrade::Speed => 20,
+            Upgrade::Shield => 25,
+        };
+        // Each level of a stackable upgrade costs double the last
+        base << owned.min(8)
+    }
+}
+
+/// Why a purchase didn't go through
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PurchaseError {
+    /// Already bought as many as there are
+    SoldOut,
+    /// The wallet is short by this many coins
+    TooExpensive(u32),
+}
+
+impl fmt::Display for PurchaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PurchaseError::SoldOut => write!(f, "{}", tr("shop.sold_out")),
+            PurchaseError::TooExpensive(short) => {
+                write!(f, "{}", tr_with("shop.too_expensive", &[("coins", short)]))
+            }
+        }
+    }
+}
+
+/// Coins banked from past runs and the upgrades they paid for, stored as `key = value`
+/// lines like the stats file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Shop {
+    /// Spendable coins; separate from score, which is never spent
+    pub coins: u32,
+    pub hearts: u32,
+    pub extra_jump: u32,
+    pub speed: u32,
+    pub shield: u32,
+}
+
+impl Shop {
+    pub fn load(profile: &Profile) -> Self {
+        Self::load_from(profile.path(SHOP_PATH))
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse the file, leaving unknown keys and bad values at zero
+    pub fn parse(source: &str) -> Self {
+        let mut shop = Self::default();
+
+        for line in source.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "coins" {
+                set_parsed(&mut shop.coins, value);
+            } else if let Some(upgrade) = Upgrade::ALL.iter().find(|upgrade| upgrade.key() == key) {
+                let level = shop.level_mut(*upgrade);
+                set_parsed(level, value);
+                *level = (*level).min(upgrade.max_level());
+            }
+        }
+
+        shop
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(SHOP_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        match upgrade {
+            Upgrade::ExtraHeart => self.hearts,
+            Upgrade::ExtraJump => self.extra_jump,
+            Upgrade::Speed => self.speed,
+            Upgrade::Shield => self.shield,
+        }
+    }
+
+    fn level_mut(&mut self, upgrade: Upgrade) -> &mut u32 {
+        match upgrade {
+            Upgrade::ExtraHeart => &mut self.hearts,
+            Upgrade::ExtraJump => &mut self.extra_jump,
+            Upgrade::Speed => &mut self.speed,
+            Upgrade::Shield => &mut self.shield,
+        }
+    }
+
+    pub fn is_sold_out(&self, upgrade: Upgrade) -> bool {
+        self.level(upgrade) >= upgrade.max_level()
+    }
+
+    /// Cost of the next level, or `None` once it's sold out
+    pub fn price(&self, upgrade: Upgrade) -> Option<u32> {
+        (!self.is_sold_out(upgrade)).then(|| upgrade.cost(self.level(upgrade)))
+    }
+
+    /// Spend coins on the next level of an upgrade
+    pub fn buy(&mut self, upgrade: Upgrade) -> Result<(), PurchaseError> {
+        let price = self.price(upgrade).ok_or(PurchaseError::SoldOut)?;
+        if self.coins < price {
+            return Err(PurchaseError::TooExpensive(price - self.coins));
+        }
+        self.coins -= price;
+        *self.level_mut(upgrade) += 1;
+        Ok(())
+    }
+
+    /// Fit a freshly made player with the upgrades bought so far
+    pub fn apply(&self, player: &mut Player) {
+        player.max_jump_count += self.extra_jump;
+        player.move_speed *= 1.0 + GameConfig::SPEED_UPGRADE_STEP * self.speed as f32;
+    }
+
+    /// Seconds of hazard protection a run starts with
+    pub fn head_start(&self) -> f32 {
+        if self.shield > 0 {
+            GameConfig::HEAD_START_SHIELD_TIME
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for Shop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "coins = {}", self.coins)?;
+        for upgrade in Upgrade::ALL {
+            writeln!(f, "{} = {}", upgrade.key(), self.level(upgrade))?;
+        }
+        Ok(())
+    }
+}
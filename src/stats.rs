@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::entities::collectible::CollectibleType;
+use crate::entities::enemy::EnemyKind;
+use crate::profile::Profile;
+use crate::settings::set_parsed;
+
+/// Where lifetime statistics are persisted, inside each profile's folder
+pub const STATS_PATH: &str = "stats.txt";
+
+/// What ended a run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeathCause {
+    FellOffWorld,
+    Spikes,
+    Enemy {
+        kind: EnemyKind,
+    },
+    Boss,
+    /// Pushed into something solid, e.g. by a forced scroll
+    Crushed,
+    Lava,
+    Drowned,
+    Boulder,
+    /// Caught in a bomb's blast
+    Explosion,
+    /// A drop long enough to be fatal with fall damage on
+    HardLanding,
+}
+
+impl DeathCause {
+    /// The hazards the "every way" skin asks the player to have died to
+    pub const HAZARDS: [DeathCause; 6] = [
+        DeathCause::FellOffWorld,
+        DeathCause::Spikes,
+        DeathCause::Crushed,
+        DeathCause::Lava,
+        DeathCause::Boulder,
+        DeathCause::Explosion,
+    ];
+
+    /// Name its deaths are counted under, as `deaths_<name>` in the statistics file. Every
+    /// kind of enemy counts as `enemy`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::FellOffWorld => "fell",
+            DeathCause::Spikes => "spikes",
+            DeathCause::Enemy { .. } => "enemy",
+            DeathCause::Boss => "boss",
+            DeathCause::Crushed => "crushed",
+            DeathCause::Lava => "lava",
+            DeathCause::Drowned => "drowned",
+            DeathCause::Boulder => "boulder",
+            DeathCause::Explosion => "explosion",
+            DeathCause::HardLanding => "landing",
+        }
+    }
+
+    /// The cause called `name`, with an enemy kind's name standing for that enemy
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(kind) = EnemyKind::from_name(name) {
+            return Some(DeathCause::Enemy { kind });
+        }
+        Some(match name {
+            "fell" => DeathCause::FellOffWorld,
+            "spikes" => DeathCause::Spikes,
+            "enemy" => DeathCause::Enemy {
+                kind: EnemyKind::Walker,
+            },
+            "boss" => DeathCause::Boss,
+            "crushed" => DeathCause::Crushed,
+            "lava" => DeathCause::Lava,
+            "drowned" => DeathCause::Drowned,
+            "boulder" => DeathCause::Boulder,
+            "explosion" => DeathCause::Explosion,
+            "landing" => DeathCause::HardLanding,
+            _ => return None,
+        })
+    }
+}
+
+/// Which best score a finished run counts towards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunKind {
+    Normal,
+    Daily,
+    Survival,
+}
+
+/// Totals across every session, stored as `key = value` lines like the settings file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// Seconds spent in runs
+    pub playtime: f32,
+    pub runs: u32,
+    pub jumps: u32,
+    pub coins: u32,
+    pub gems: u32,
+    pub powerups: u32,
+    pub lanterns: u32,
+    /// Deaths by the name of their cause. Files from before causes were told apart can
+    /// also hold `hazard`.
+    pub deaths: BTreeMap<String, u32>,
+    pub best_normal: i32,
+    pub best_daily: i32,
+    pub best_survival: i32,
+    /// Most survival waves cleared in one run
+    pub most_waves: u32,
+    /// Seconds survived in the longest run
+    pub longest_run: f32,
+    /// Furthest distance right of the spawn point, in pixels
+    pub furthest_distance: f32,
+}
+
+impl Stats {
+    pub fn load(profile: &Profile) -> Self {
+        Self::load_from(profile.path(STATS_PATH))
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse the file, leaving unknown keys and bad values at zero
+    pub fn parse(source: &str) -> Self {
+        let mut stats = Self::default();
+
+        for line in source.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            stats.apply(key.trim(), value.trim());
+        }
+
+        stats
+    }
+
+    fn apply(&mut self, key: &str, value: &str) {
+        match key {
+            "playtime" => set_parsed(&mut self.playtime, value),
+            "runs" => set_parsed(&mut self.runs, value),
+            "jumps" => set_parsed(&mut self.jumps, value),
+            "coins" => set_parsed(&mut self.coins, value),
+            "gems" => set_parsed(&mut self.gems, value),
+            "powerups" => set_parsed(&mut self.powerups, value),
+            "lanterns" => set_parsed(&mut self.lanterns, value),
+            _ if key.starts_with("deaths_") => {
+                if let Ok(count) = value.parse() {
+                    self.deaths
+                        .insert(key["deaths_".len()..].to_string(), count);
+                }
+            }
+            "best_normal" => set_parsed(&mut self.best_normal, value),
+            "best_daily" => set_parsed(&mut self.best_daily, value),
+            "best_survival" => set_parsed(&mut self.best_survival, value),
+            "most_waves" => set_parsed(&mut self.most_waves, value),
+            "longest_run" => set_parsed(&mut self.longest_run, value),
+            "furthest_distance" => set_parsed(&mut self.furthest_distance, value),
+            _ => {}
+        }
+    }
+
+    pub fn save(&self, profile: &Profile) -> std::io::Result<()> {
+        self.save_to(profile.path(STATS_PATH))
+    }
+
+    pub fn save_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn record_pickup(&mut self, collectible_type: CollectibleType) {
+        let counter = match collectible_type {
+            CollectibleType::Coin => &mut self.coins,
+            CollectibleType::Gem => &mut self.gems,
+            CollectibleType::PowerUp => &mut self.powerups,
+            CollectibleType::Lantern => &mut self.lanterns,
+        };
+        *counter += 1;
+    }
+
+    pub fn record_death(&mut self, cause: DeathCause) {
+        *self.deaths.entry(cause.name().to_string()).or_default() += 1;
+    }
+
+    pub fn deaths_by(&self, cause: DeathCause) -> u32 {
+        self.deaths.get(cause.name()).copied().unwrap_or(0)
+    }
+
+    /// Fold a finished run into the records
+    pub fn record_run(&mut self, score: i32, kind: RunKind, duration: f32, distance: f32) {
+        self.runs += 1;
+        let best = match kind {
+            RunKind::Normal => &mut self.best_normal,
+            RunKind::Daily => &mut self.best_daily,
+            RunKind::Survival => &mut self.best_survival,
+        };
+        *best = (*best).max(score);
+        self.longest_run = self.longest_run.max(duration);
+        self.furthest_distance = self.furthest_distance.max(distance);
+    }
+
+    pub fn record_waves(&mut self, waves: u32) {
+        self.most_waves = self.most_waves.max(waves);
+    }
+
+    pub fn total_deaths(&self) -> u32 {
+        self.deaths.values().sum()
+    }
+
+    /// The `count` causes died to most, by name, most common first
+    pub fn top_deaths(&self, count: usize) -> Vec<(&str, u32)> {
+        let mut causes: Vec<(&str, u32)> = self
+            .deaths
+            .iter()
+            .filter(|(_, &deaths)| deaths > 0)
+            .map(|(name, &deaths)| (name.as_str(), deaths))
+            .collect();
+        // Stable, so ties stay in name order
+        causes.sort_by_key(|&(_, deaths)| std::cmp::Reverse(deaths));
+        causes.truncate(count);
+        causes
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "playtime = {}", self.playtime)?;
+        writeln!(f, "runs = {}", self.runs)?;
+        writeln!(f, "jumps = {}", self.jumps)?;
+        writeln!(f, "coins = {}", self.coins)?;
+        writeln!(f, "gems = {}", self.gems)?;
+        writeln!(f, "powerups = {}", self.powerups)?;
+        writeln!(f, "lanterns = {}", self.lanterns)?;
+        for (cause, count) in &self.deaths {
+            writeln!(f, "deaths_{} = {}", cause, count)?;
+        }
+        writeln!(f, "best_normal = {}", self.best_normal)?;
+        writeln!(f, "best_daily = {}", self.best_daily)?;
+        writeln!(f, "best_survival = {}", self.best_survival)?;
+        writeln!(f, "most_waves = {}", self.most_waves)?;
+        writeln!(f, "longest_run = {}", self.longest_run)?;
+        writeln!(f, "furthest_distance = {}", self.furthest_distance)
+    }
+}
+
+/// Seconds as `1h 02m 03s`, dropping leading zero units
+pub fn format_duration(seconds: f32) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, seconds) = (total / 3600, total % 3600 / 60, total % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}